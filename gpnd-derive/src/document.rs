@@ -0,0 +1,48 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput};
+
+pub(crate) fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let ident = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            ident,
+            "`Document` can only be derived for structs",
+        ));
+    };
+    let mut members = Vec::new();
+    for (field, member) in data.fields.iter().zip(data.fields.members()) {
+        let mut is_node = false;
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("document"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("node") {
+                    is_node = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `node`"))
+                }
+            })?;
+        }
+        if is_node {
+            members.push(member);
+        }
+    }
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::gpnd::Document for #ident #type_generics #where_clause {
+            fn nodes(&self) -> ::gpnd::__private::Vec<&dyn ::gpnd::Node> {
+                #[allow(unused_mut)]
+                let mut nodes = ::gpnd::__private::Vec::new();
+                #(::gpnd::NodeList::extend_nodes(&self.#members, &mut nodes);)*
+                nodes
+            }
+        }
+    })
+}
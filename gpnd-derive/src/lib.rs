@@ -0,0 +1,114 @@
+#![warn(clippy::pedantic)]
+#![warn(unused_crate_dependencies)]
+#![deny(unsafe_code)]
+
+mod document;
+mod node;
+mod value_enum;
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+/// Implement `gpnd::Node` for a struct.
+///
+/// Each field becomes an argument, a parameter, or a child node:
+///
+/// - `#[node(arg)]` makes the field the next argument.
+/// - `#[node(param)]` or `#[node(param = "key")]` makes the field a parameter,
+///   named after the field unless a key is given.
+/// - `#[node(child)]` makes the field, which must implement `Node`, a child.
+/// - `#[node(children)]` makes every item of the field, such as a `Vec` of
+///   nodes, a child.
+/// - `#[node(skip)]` leaves the field out.
+///
+/// Fields without an attribute are parameters in a struct with named fields,
+/// and arguments in a tuple struct. Field values are converted with
+/// `gpnd::ToValue`.
+///
+/// Parameters can also be given these, as in
+/// `#[node(param = "port", default = 8080, range = "1..=65535")]`:
+///
+/// - `default = ...` gives the value of an `Option` field when it is `None`.
+/// - `required` expects an `Option` field in the schema, so that it must be
+///   `Some` to be valid.
+/// - `range = "..."` restricts an integer field to a range, such as `1..=10`,
+///   `..10`, or `-5..`, in the schema.
+///
+/// The node is named after the struct in kebab-case, unless a name is given
+/// with `#[node(name = "...")]` on the struct. A namespace can be given with
+/// `#[node(namespace = "...")]`.
+///
+/// With `#[node(schema)]` on the struct, `gpnd::schema::NodeReflect` is
+/// implemented too, which requires the types of argument and parameter fields
+/// to implement `gpnd::schema::SchemaType`. Its `validate` method checks the
+/// node against the schema. `required` and `range` need it.
+#[proc_macro_derive(Node, attributes(node))]
+pub fn derive_node(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    node::expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implement `gpnd::Document` for a struct.
+///
+/// Fields marked with `#[document(node)]` are the document's nodes, in the
+/// order they are declared. A field can be a single node, or a collection of
+/// nodes such as a `Vec` or an `Option`, which is flattened into the node
+/// list (see `gpnd::NodeList`). Other fields are left out.
+#[proc_macro_derive(Document, attributes(document))]
+pub fn derive_document(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    document::expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implement `gpnd::ValueEnum` for an enum without fields, so that it is
+/// written as the name of a variant.
+///
+/// Variants are named in kebab-case, unless another case is given with
+/// `#[value(rename_all = "...")]` on the enum, one of `snake_case`,
+/// `lowercase`, `UPPERCASE`, `SCREAMING-KEBAB-CASE`, `SCREAMING_SNAKE_CASE`,
+/// `camelCase`, or `PascalCase`. A variant can be given its own name with
+/// `#[value(rename = "...")]`.
+///
+/// `gpnd::ToValue`, `gpnd::schema::SchemaType`, `From<T> for gpnd::Value`,
+/// and `TryFrom` from a `gpnd::Value` and a reference to one are implemented
+/// too, so that the enum can be the field of a derived node.
+#[proc_macro_derive(ValueEnum, attributes(value))]
+pub fn derive_value_enum(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    value_enum::expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Split an identifier in `PascalCase` into lowercase words, before each
+/// uppercase letter that starts a word and at each underscore. A run of
+/// uppercase letters is one word, so `HTTPServer` is `http` and `server`.
+fn words(ident: &str) -> Vec<String> {
+    let chars: Vec<char> = ident.chars().collect();
+    let mut words = Vec::new();
+    let mut word = String::new();
+    for (index, &ch) in chars.iter().enumerate() {
+        let after_lower = index > 0 && !chars[index - 1].is_uppercase();
+        let before_lower = chars.get(index + 1).is_some_and(|next| next.is_lowercase());
+        let starts_word = ch == '_' || ch.is_uppercase() && (after_lower || before_lower);
+        if starts_word && !word.is_empty() {
+            words.push(core::mem::take(&mut word));
+        }
+        if ch != '_' {
+            word.extend(ch.to_lowercase());
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+/// Convert an identifier in `PascalCase` to `kebab-case`.
+fn kebab_case(ident: &str) -> String {
+    words(ident).join("-")
+}
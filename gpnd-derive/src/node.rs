@@ -0,0 +1,383 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Expr, ExprLit, Field, Fields, Lit, LitStr, Member, Type};
+
+use crate::kebab_case;
+
+pub(crate) enum FieldKind {
+    Arg,
+    Param(String),
+    Child,
+    Children,
+    Skip,
+}
+
+pub(crate) struct NodeField {
+    pub(crate) member: Member,
+    pub(crate) ty: Type,
+    pub(crate) kind: FieldKind,
+    /// Whether an optional parameter is required by the schema.
+    pub(crate) required: bool,
+    /// The value of an optional parameter when it is left out.
+    pub(crate) default: Option<Expr>,
+    /// The inclusive bounds of an integer parameter.
+    pub(crate) range: Option<(Option<i128>, Option<i128>)>,
+}
+
+impl NodeField {
+    /// Parse a field, which is a parameter if it has no kind and `named` is
+    /// true, or an argument otherwise.
+    fn parse(field: &Field, member: Member, named: bool) -> syn::Result<Self> {
+        let mut kind = None;
+        let mut required = false;
+        let mut default = None;
+        let mut range = None;
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("node"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("required") {
+                    required = true;
+                    return Ok(());
+                } else if meta.path.is_ident("default") {
+                    default = Some(meta.value()?.parse::<Expr>()?);
+                    return Ok(());
+                } else if meta.path.is_ident("range") {
+                    range = Some(parse_range(&meta.value()?.parse::<LitStr>()?)?);
+                    return Ok(());
+                }
+                if kind.is_some() {
+                    return Err(meta.error("a field can only have one kind"));
+                }
+                kind = Some(if meta.path.is_ident("arg") {
+                    FieldKind::Arg
+                } else if meta.path.is_ident("param") {
+                    if meta.input.peek(syn::Token![=]) {
+                        FieldKind::Param(meta.value()?.parse::<LitStr>()?.value())
+                    } else if let Some(ident) = &field.ident {
+                        FieldKind::Param(ident.to_string())
+                    } else {
+                        return Err(meta.error("a tuple field parameter needs a key"));
+                    }
+                } else if meta.path.is_ident("child") {
+                    FieldKind::Child
+                } else if meta.path.is_ident("children") {
+                    FieldKind::Children
+                } else if meta.path.is_ident("skip") {
+                    FieldKind::Skip
+                } else {
+                    return Err(meta.error(
+                        "expected one of `arg`, `param`, `child`, `children`, `skip`, `required`, \
+                         `default`, or `range`",
+                    ));
+                });
+                Ok(())
+            })?;
+        }
+        let kind = match (kind, &field.ident) {
+            (Some(kind), _) => kind,
+            (None, Some(ident)) if named => FieldKind::Param(ident.to_string()),
+            (None, _) => FieldKind::Arg,
+        };
+        let is_param = matches!(kind, FieldKind::Param(_));
+        if !is_param && (required || default.is_some() || range.is_some()) {
+            return Err(syn::Error::new_spanned(
+                field,
+                "only parameters can be `required`, or have a `default` or a `range`",
+            ));
+        }
+        if required && default.is_some() {
+            return Err(syn::Error::new_spanned(
+                field,
+                "a parameter with a `default` cannot be `required`",
+            ));
+        }
+        Ok(NodeField {
+            member,
+            ty: field.ty.clone(),
+            kind,
+            required,
+            default,
+            range,
+        })
+    }
+
+    fn value(&self) -> TokenStream {
+        let member = &self.member;
+        match &self.default {
+            // String literals are converted, so that they can be given for
+            // any string type.
+            Some(Expr::Lit(ExprLit {
+                lit: Lit::Str(default),
+                ..
+            })) => quote! {
+                ::gpnd::schema::FieldDefault::value_or(&self.#member, || {
+                    ::core::convert::Into::into(#default)
+                })
+            },
+            Some(default) => quote! {
+                ::gpnd::schema::FieldDefault::value_or(&self.#member, || #default)
+            },
+            None => quote! { ::gpnd::ToValue::to_value(&self.#member) },
+        }
+    }
+
+    fn range(&self, key: &str) -> Option<TokenStream> {
+        let (min, max) = self.range?;
+        let [min, max] = [min, max].map(|bound| {
+            bound.map_or_else(
+                || quote! { ::core::option::Option::None },
+                |bound| quote! { ::core::option::Option::Some(#bound) },
+            )
+        });
+        Some(quote! { (#key, ::gpnd::schema::IntegerRange::new(#min, #max)) })
+    }
+
+    fn field_type(&self) -> TokenStream {
+        let ty = &self.ty;
+        let field_type = quote! { <#ty as ::gpnd::schema::SchemaType>::FIELD_TYPE };
+        if self.required {
+            quote! { ::gpnd::schema::FieldType { required: true, ..#field_type } }
+        } else {
+            field_type
+        }
+    }
+}
+
+/// Parse a range such as `1..=65535`, `..10`, or `-5..`, as inclusive bounds.
+fn parse_range(lit: &LitStr) -> syn::Result<(Option<i128>, Option<i128>)> {
+    let error = || syn::Error::new_spanned(lit, "expected an integer range such as `1..=65535`");
+    let value = lit.value();
+    let (start, end) = value.split_once("..").ok_or_else(error)?;
+    let bound = |text: &str| -> syn::Result<Option<i128>> {
+        match text.trim() {
+            "" => Ok(None),
+            text => text.parse().map(Some).map_err(|_| error()),
+        }
+    };
+    let min = bound(start)?;
+    let max = match end.strip_prefix('=') {
+        Some(end) => bound(end)?,
+        None => bound(end)?
+            .map(|end: i128| end.checked_sub(1).ok_or_else(error))
+            .transpose()?,
+    };
+    if let (Some(min), Some(max)) = (min, max) {
+        if min > max {
+            return Err(syn::Error::new_spanned(lit, "the range is empty"));
+        }
+    }
+    Ok((min, max))
+}
+
+pub(crate) struct NodeStruct {
+    pub(crate) namespace: Option<String>,
+    pub(crate) name: String,
+    pub(crate) fields: Vec<NodeField>,
+    /// Whether `NodeReflect` is implemented, for `#[node(schema)]`.
+    pub(crate) schema: bool,
+}
+
+impl NodeStruct {
+    pub(crate) fn parse(input: &DeriveInput) -> syn::Result<Self> {
+        let mut name = kebab_case(&input.ident.to_string());
+        let mut namespace = None;
+        let mut schema = false;
+        for attr in input
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("node"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("name") {
+                    name = meta.value()?.parse::<LitStr>()?.value();
+                    Ok(())
+                } else if meta.path.is_ident("namespace") {
+                    namespace = Some(meta.value()?.parse::<LitStr>()?.value());
+                    Ok(())
+                } else if meta.path.is_ident("schema") {
+                    schema = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `name = \"...\"`, `namespace = \"...\"`, or `schema`"))
+                }
+            })?;
+        }
+
+        let Data::Struct(data) = &input.data else {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "`Node` can only be derived for structs",
+            ));
+        };
+        let named = matches!(data.fields, Fields::Named(_));
+        let fields = data
+            .fields
+            .iter()
+            .zip(data.fields.members())
+            .map(|(field, member)| NodeField::parse(field, member, named))
+            .collect::<syn::Result<Vec<_>>>()?;
+        if let Some(field) = fields
+            .iter()
+            .find(|field| !schema && (field.required || field.range.is_some()))
+        {
+            return Err(syn::Error::new_spanned(
+                &field.ty,
+                "`required` and `range` need `#[node(schema)]` on the struct",
+            ));
+        }
+
+        Ok(Self {
+            namespace,
+            name,
+            fields,
+            schema,
+        })
+    }
+
+    fn namespace_fn(&self) -> Option<TokenStream> {
+        let namespace = self.namespace.as_ref()?;
+        Some(quote! {
+            fn namespace(&self) -> ::core::option::Option<&str> {
+                ::core::option::Option::Some(#namespace)
+            }
+        })
+    }
+
+    /// The `children` method, which collects every child field.
+    fn children_fn(&self) -> TokenStream {
+        let children = self.fields.iter().filter_map(|field| {
+            let member = &field.member;
+            match field.kind {
+                FieldKind::Child => Some(quote! {
+                    children.push(&self.#member);
+                }),
+                FieldKind::Children => Some(quote! {
+                    children.extend(
+                        ::core::iter::IntoIterator::into_iter(&self.#member)
+                            .map(|child| -> &dyn ::gpnd::Node { child }),
+                    );
+                }),
+                _ => None,
+            }
+        });
+        quote! {
+            fn children(&self) -> ::gpnd::__private::Vec<&dyn ::gpnd::Node> {
+                #[allow(unused_mut)]
+                let mut children: ::gpnd::__private::Vec<&dyn ::gpnd::Node> = ::gpnd::__private::Vec::new();
+                #(#children)*
+                children
+            }
+        }
+    }
+}
+
+pub(crate) fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let node = NodeStruct::parse(input)?;
+    let ident = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    let name = &node.name;
+    let namespace = node.namespace_fn();
+    let children = node.children_fn();
+
+    let args = node
+        .fields
+        .iter()
+        .filter(|field| matches!(field.kind, FieldKind::Arg))
+        .map(|field| &field.member)
+        .collect::<Vec<_>>();
+    let indices = 0..args.len();
+    let arg_count = args.len();
+    let arg_types = node
+        .fields
+        .iter()
+        .filter(|field| matches!(field.kind, FieldKind::Arg))
+        .map(|field| &field.ty);
+
+    let (keys, params): (Vec<_>, Vec<_>) = node
+        .fields
+        .iter()
+        .filter_map(|field| match &field.kind {
+            FieldKind::Param(key) => Some((key, field)),
+            _ => None,
+        })
+        .unzip();
+    let param_values = params.iter().map(|field| field.value()).collect::<Vec<_>>();
+    let param_types = params.iter().map(|field| field.field_type());
+    let param_ranges = keys
+        .iter()
+        .zip(&params)
+        .filter_map(|(key, field)| field.range(key));
+    let has_children = node
+        .fields
+        .iter()
+        .any(|field| matches!(field.kind, FieldKind::Child | FieldKind::Children));
+
+    let reflect = node.schema.then(|| {
+        quote! {
+            #[automatically_derived]
+            impl #impl_generics ::gpnd::schema::NodeReflect for #ident #type_generics #where_clause {
+                const NAME: &'static str = #name;
+
+                const ARG_TYPES: &'static [::gpnd::schema::FieldType] = &[
+                    #(<#arg_types as ::gpnd::schema::SchemaType>::FIELD_TYPE),*
+                ];
+
+                const PARAM_NAMES: &'static [&'static str] = &[#(#keys),*];
+
+                const PARAM_TYPES: &'static [::gpnd::schema::FieldType] = &[#(#param_types),*];
+
+                const PARAM_RANGES: &'static [(&'static str, ::gpnd::schema::IntegerRange)] = &[
+                    #(#param_ranges),*
+                ];
+
+                const HAS_CHILDREN: bool = #has_children;
+            }
+        }
+    });
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::gpnd::Node for #ident #type_generics #where_clause {
+            fn name(&self) -> &str {
+                #name
+            }
+
+            #namespace
+
+            fn args(&self) -> ::gpnd::__private::Vec<::gpnd::Value<'_>> {
+                ::gpnd::__private::vec![#(::gpnd::ToValue::to_value(&self.#args)),*]
+            }
+
+            fn get_arg(&self, index: usize) -> ::core::option::Option<::gpnd::Value<'_>> {
+                match index {
+                    #(#indices => ::core::option::Option::Some(
+                        ::gpnd::ToValue::to_value(&self.#args)
+                    ),)*
+                    _ => ::core::option::Option::None,
+                }
+            }
+
+            fn arg_count(&self) -> usize {
+                #arg_count
+            }
+
+            fn params(&self) -> ::gpnd::__private::Vec<(&str, ::gpnd::Value<'_>)> {
+                ::gpnd::__private::vec![#((#keys, #param_values)),*]
+            }
+
+            fn get_param(&self, key: &str) -> ::core::option::Option<::gpnd::Value<'_>> {
+                match key {
+                    #(#keys => ::core::option::Option::Some(#param_values),)*
+                    _ => ::core::option::Option::None,
+                }
+            }
+
+            #children
+        }
+
+        #reflect
+    })
+}
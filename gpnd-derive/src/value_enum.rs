@@ -0,0 +1,185 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, LitStr};
+
+use crate::words;
+
+/// The ways of joining the words of a variant name, for `rename_all`.
+const CASES: &[&str] = &[
+    "kebab-case",
+    "snake_case",
+    "lowercase",
+    "UPPERCASE",
+    "SCREAMING-KEBAB-CASE",
+    "SCREAMING_SNAKE_CASE",
+    "camelCase",
+    "PascalCase",
+];
+
+/// Convert a variant name in `PascalCase` to `case`, one of [`CASES`].
+fn convert_case(ident: &str, case: &str) -> String {
+    let words = words(ident);
+    let capitalize = |word: &String| {
+        let mut chars = word.chars();
+        chars
+            .next()
+            .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+            .unwrap_or_default()
+    };
+    match case {
+        "snake_case" => words.join("_"),
+        "lowercase" => words.concat(),
+        "UPPERCASE" => words.concat().to_uppercase(),
+        "SCREAMING-KEBAB-CASE" => words.join("-").to_uppercase(),
+        "SCREAMING_SNAKE_CASE" => words.join("_").to_uppercase(),
+        "camelCase" => words
+            .iter()
+            .enumerate()
+            .map(|(index, word)| match index {
+                0 => word.clone(),
+                _ => capitalize(word),
+            })
+            .collect(),
+        "PascalCase" => words.iter().map(capitalize).collect(),
+        _ => words.join("-"),
+    }
+}
+
+/// Parse the case of `#[value(rename_all = "...")]` on the enum.
+fn rename_all(input: &DeriveInput) -> syn::Result<String> {
+    let mut case = String::from("kebab-case");
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("value"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename_all") {
+                let lit = meta.value()?.parse::<LitStr>()?;
+                case = lit.value();
+                if CASES.contains(&case.as_str()) {
+                    Ok(())
+                } else {
+                    let cases = CASES.join("`, `");
+                    Err(syn::Error::new_spanned(
+                        lit,
+                        format!("expected one of `{cases}`"),
+                    ))
+                }
+            } else {
+                Err(meta.error("expected `rename_all = \"...\"`"))
+            }
+        })?;
+    }
+    Ok(case)
+}
+
+pub(crate) fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let ident = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    let case = rename_all(input)?;
+    let Data::Enum(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            ident,
+            "`ValueEnum` can only be derived for enums",
+        ));
+    };
+    let mut variants = Vec::new();
+    let mut names = Vec::new();
+    for variant in &data.variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(syn::Error::new_spanned(
+                variant,
+                "`ValueEnum` variants cannot have fields",
+            ));
+        }
+        let mut name = convert_case(&variant.ident.to_string(), &case);
+        for attr in variant
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("value"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    name = meta.value()?.parse::<LitStr>()?.value();
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `rename = \"...\"`"))
+                }
+            })?;
+        }
+        if names.contains(&name) {
+            return Err(syn::Error::new_spanned(
+                variant,
+                format!("another variant is also named `{name}`"),
+            ));
+        }
+        variants.push(&variant.ident);
+        names.push(name);
+    }
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::gpnd::ValueEnum for #ident #type_generics #where_clause {
+            const NAMES: &'static [&'static str] = &[#(#names),*];
+
+            fn from_name(name: &str) -> ::core::option::Option<Self> {
+                match name {
+                    #(#names => ::core::option::Option::Some(Self::#variants),)*
+                    _ => ::core::option::Option::None,
+                }
+            }
+
+            fn name(&self) -> &'static str {
+                match self {
+                    #(Self::#variants => #names,)*
+                }
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_generics ::gpnd::ToValue for #ident #type_generics #where_clause {
+            fn to_value(&self) -> ::gpnd::Value<'_> {
+                ::gpnd::Value::from(::gpnd::ValueEnum::name(self))
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_generics ::core::convert::From<#ident #type_generics>
+            for ::gpnd::Value<'_> #where_clause
+        {
+            fn from(value: #ident #type_generics) -> Self {
+                ::gpnd::Value::from(::gpnd::ValueEnum::name(&value))
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_generics ::core::convert::TryFrom<::gpnd::Value<'_>>
+            for #ident #type_generics #where_clause
+        {
+            type Error = ::gpnd::EnumValueError;
+
+            fn try_from(value: ::gpnd::Value<'_>) -> ::core::result::Result<Self, Self::Error> {
+                <Self as ::gpnd::ValueEnum>::from_value(&value)
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_generics ::core::convert::TryFrom<&::gpnd::Value<'_>>
+            for #ident #type_generics #where_clause
+        {
+            type Error = ::gpnd::EnumValueError;
+
+            fn try_from(value: &::gpnd::Value<'_>) -> ::core::result::Result<Self, Self::Error> {
+                <Self as ::gpnd::ValueEnum>::from_value(value)
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_generics ::gpnd::schema::SchemaType for #ident #type_generics #where_clause {
+            const FIELD_TYPE: ::gpnd::schema::FieldType =
+                ::gpnd::schema::FieldType::required(::gpnd::schema::ValueType::String);
+        }
+    })
+}
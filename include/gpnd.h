@@ -0,0 +1,64 @@
+/* C declarations for the `ffi` feature of the `gpnd` crate, see the
+ * documentation of `gpnd::ffi` for ownership and safety. */
+
+#ifndef GPND_H
+#define GPND_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct DocumentBuf gpnd_document;
+typedef struct NodeBuf gpnd_node;
+typedef struct Value gpnd_value;
+typedef struct NodeList gpnd_node_list;
+
+typedef enum {
+    GPND_KIND_NULL,
+    GPND_KIND_BOOL,
+    GPND_KIND_INTEGER,
+    GPND_KIND_FLOAT,
+    GPND_KIND_STRING,
+    GPND_KIND_LIST,
+    GPND_KIND_MAP,
+} gpnd_kind;
+
+gpnd_document *gpnd_parse(const uint8_t *source, size_t len, char **error);
+void gpnd_document_free(gpnd_document *document);
+const uint8_t *gpnd_document_version(const gpnd_document *document, size_t *len);
+size_t gpnd_document_len(const gpnd_document *document);
+const gpnd_node *gpnd_document_node(const gpnd_document *document, size_t index);
+
+gpnd_node_list *gpnd_query(const gpnd_document *document, const uint8_t *query, size_t len,
+                           char **error);
+size_t gpnd_node_list_len(const gpnd_node_list *list);
+const gpnd_node *gpnd_node_list_get(const gpnd_node_list *list, size_t index);
+void gpnd_node_list_free(gpnd_node_list *list);
+
+const uint8_t *gpnd_node_name(const gpnd_node *node, size_t *len);
+size_t gpnd_node_arg_count(const gpnd_node *node);
+const gpnd_value *gpnd_node_arg(const gpnd_node *node, size_t index);
+const gpnd_value *gpnd_node_param(const gpnd_node *node, const uint8_t *key, size_t len);
+size_t gpnd_node_child_count(const gpnd_node *node);
+const gpnd_node *gpnd_node_child(const gpnd_node *node, size_t index);
+
+gpnd_kind gpnd_value_kind(const gpnd_value *value);
+bool gpnd_value_bool(const gpnd_value *value, bool *out);
+bool gpnd_value_i64(const gpnd_value *value, int64_t *out);
+bool gpnd_value_f64(const gpnd_value *value, double *out);
+const uint8_t *gpnd_value_str(const gpnd_value *value, size_t *len);
+size_t gpnd_value_len(const gpnd_value *value);
+const gpnd_value *gpnd_value_item(const gpnd_value *value, size_t index);
+const gpnd_value *gpnd_value_get(const gpnd_value *value, const uint8_t *key, size_t len);
+
+void gpnd_string_free(char *string);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,137 @@
+//! Implementations of [`Arbitrary`] for values, nodes, and documents, for
+//! fuzzing and property-based tests.
+//!
+//! Lists, maps, and children are nested at most [`MAX_DEPTH`] levels deep,
+//! and hold at most [`MAX_LEN`] items each, so that generated documents stay
+//! small enough to check quickly. Namespaces are only generated for nodes
+//! whose namespace and name can both be written without quotes, since a
+//! qualified name cannot be quoted. Borrowed slices are never generated.
+
+use std::borrow::Cow;
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use crate::emit::is_bare_ident;
+use crate::{DocumentBuf, NodeBuf, Value};
+
+/// How deeply lists, maps, and children are nested.
+const MAX_DEPTH: usize = 3;
+/// How many items a list, map, or node has at most, of each kind.
+const MAX_LEN: usize = 4;
+
+fn len(u: &mut Unstructured<'_>) -> Result<usize> {
+    u.int_in_range(0..=MAX_LEN)
+}
+
+fn value(u: &mut Unstructured<'_>, depth: usize) -> Result<Value<'static>> {
+    let variants = if depth == 0 { 15 } else { 17 };
+    Ok(match u.choose_index(variants)? {
+        0 => Value::U8(u.arbitrary()?),
+        1 => Value::U16(u.arbitrary()?),
+        2 => Value::U32(u.arbitrary()?),
+        3 => Value::U64(u.arbitrary()?),
+        4 => Value::Uint(u.arbitrary()?),
+        5 => Value::I8(u.arbitrary()?),
+        6 => Value::I16(u.arbitrary()?),
+        7 => Value::I32(u.arbitrary()?),
+        8 => Value::I64(u.arbitrary()?),
+        9 => Value::Int(u.arbitrary()?),
+        10 => Value::F32(u.arbitrary()?),
+        11 => Value::F64(u.arbitrary()?),
+        12 => Value::Bool(u.arbitrary()?),
+        13 => Value::String(Cow::Owned(u.arbitrary()?)),
+        14 => Value::Null,
+        15 => Value::List(Box::new(
+            (0..len(u)?)
+                .map(|_| value(u, depth - 1))
+                .collect::<Result<_>>()?,
+        )),
+        _ => Value::Map(Box::new(
+            (0..len(u)?)
+                .map(|_| Ok((Cow::Owned(u.arbitrary()?), value(u, depth - 1)?)))
+                .collect::<Result<_>>()?,
+        )),
+    })
+}
+
+/// Whether `namespace` can be written before `name`.
+fn is_qualified(namespace: &str, name: &str) -> bool {
+    namespace.starts_with(|ch: char| ch.is_ascii_alphabetic())
+        && namespace
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '_' | '-'))
+        && is_bare_ident(name)
+        && !name.contains(':')
+}
+
+fn node(u: &mut Unstructured<'_>, depth: usize) -> Result<NodeBuf> {
+    let name: String = u.arbitrary()?;
+    let namespace: Option<String> = u.arbitrary()?;
+    let namespace = namespace.filter(|namespace| is_qualified(namespace, &name));
+    let mut node = NodeBuf::new(name);
+    node.set_namespace(namespace);
+    for _ in 0..len(u)? {
+        node.args_mut().push(value(u, MAX_DEPTH)?);
+    }
+    for _ in 0..len(u)? {
+        node.params_mut()
+            .push(u.arbitrary::<String>()?, value(u, MAX_DEPTH)?);
+    }
+    if depth > 0 {
+        for _ in 0..len(u)? {
+            node.children_mut().push(self::node(u, depth - 1)?);
+        }
+    }
+    Ok(node)
+}
+
+impl<'a> Arbitrary<'a> for Value<'static> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        value(u, MAX_DEPTH)
+    }
+}
+
+impl<'a> Arbitrary<'a> for NodeBuf {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        node(u, MAX_DEPTH)
+    }
+}
+
+impl<'a> Arbitrary<'a> for DocumentBuf {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut document = DocumentBuf::new();
+        document.set_version(u.arbitrary::<Option<String>>()?);
+        for _ in 0..len(u)? {
+            document.nodes_mut().push(node(u, MAX_DEPTH)?);
+        }
+        Ok(document)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emit::to_string;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_arbitrary_round_trip() {
+        // A xorshift generator, so that failures can be reproduced.
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let mut bytes = vec![0; 4096];
+        for _ in 0..200 {
+            for byte in &mut bytes {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                *byte = state.to_le_bytes()[0];
+            }
+            let document = DocumentBuf::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+            // The parser reads every integer as an `I64` or `U64`, and every
+            // float as an `F64`, so it is the text that must round-trip.
+            let source = to_string(&document);
+            let parsed = parse(&source).unwrap_or_else(|error| panic!("{error}:\n{source}"));
+            assert_eq!(to_string(&parsed), source);
+        }
+    }
+}
@@ -0,0 +1,507 @@
+//! A compact binary encoding of documents, that keeps the variant of every
+//! [`Value`], for caching parsed documents or passing them between
+//! processes.
+//!
+//! The encoding starts with the bytes `GPND` and a format version, currently
+//! `1`, followed by the document. Lengths and counts are unsigned LEB128
+//! integers, and strings are a length followed by UTF-8. A document is its
+//! optional version, then its count of nodes and the nodes. A node is its
+//! optional namespace, its name, then each of its arguments, parameters, and
+//! children, as a count followed by the items.
+//!
+//! A value is a tag byte, the index of its [`ValueKind`], followed by the
+//! inner value, except that `Size` and `Duration` come after `Null`, so that
+//! bytes encoded before they were added decode the same. Numbers are
+//! little-endian, with `Uint` and `Int` widened to 64 bits. A `Slice` is
+//! encoded as a `List`. Spans and sources are not
+//! encoded.
+//!
+//! Children, lists, and maps may be nested at most [`MAX_DEPTH`] levels
+//! deep, so that decoding untrusted bytes cannot overflow the stack.
+
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt;
+
+use crate::{Document, DocumentBuf, Node, NodeBuf, Value, ValueKind};
+
+const MAGIC: &[u8; 4] = b"GPND";
+const FORMAT_VERSION: u8 = 1;
+
+/// How deeply children, lists, and maps may be nested in decoded bytes, as
+/// for [`ParserConfig::max_depth`](crate::parser::ParserConfig::max_depth).
+pub const MAX_DEPTH: usize = 128;
+
+/// The tags of the variants of [`Value`], in order.
+const TAGS: [ValueKind; 19] = [
+    ValueKind::U8,
+    ValueKind::U16,
+    ValueKind::U32,
+    ValueKind::U64,
+    ValueKind::Uint,
+    ValueKind::I8,
+    ValueKind::I16,
+    ValueKind::I32,
+    ValueKind::I64,
+    ValueKind::Int,
+    ValueKind::F32,
+    ValueKind::F64,
+    ValueKind::Bool,
+    ValueKind::String,
+    ValueKind::List,
+    ValueKind::Map,
+    ValueKind::Null,
+    ValueKind::Size,
+    ValueKind::Duration,
+];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The bytes do not start with the magic bytes.
+    InvalidMagic,
+    /// The format version is not supported.
+    UnsupportedVersion(u8),
+    /// The bytes end in the middle of the document.
+    UnexpectedEnd,
+    /// There are bytes after the end of the document.
+    TrailingBytes,
+    InvalidTag(u8),
+    InvalidUtf8,
+    /// A length, or a `Uint` or `Int`, does not fit on this platform.
+    OutOfRange,
+    /// Children, lists, or maps are nested deeper than [`MAX_DEPTH`].
+    TooDeep,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidMagic => f.write_str("not an encoded document"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported format version {version}")
+            }
+            Self::UnexpectedEnd => f.write_str("unexpected end of input"),
+            Self::TrailingBytes => f.write_str("trailing bytes after the document"),
+            Self::InvalidTag(tag) => write!(f, "invalid value tag {tag}"),
+            Self::InvalidUtf8 => f.write_str("invalid UTF-8 in a string"),
+            Self::OutOfRange => f.write_str("integer out of range for this platform"),
+            Self::TooDeep => write!(f, "nesting deeper than {MAX_DEPTH} levels"),
+        }
+    }
+}
+
+impl Error for DecodeError {}
+
+struct Encoder {
+    bytes: Vec<u8>,
+}
+
+impl Encoder {
+    fn length(&mut self, mut length: usize) {
+        loop {
+            #[allow(clippy::cast_possible_truncation)]
+            let byte = (length & 0x7f) as u8;
+            length >>= 7;
+            if length == 0 {
+                self.bytes.push(byte);
+                return;
+            }
+            self.bytes.push(byte | 0x80);
+        }
+    }
+
+    fn string(&mut self, string: &str) {
+        self.length(string.len());
+        self.bytes.extend_from_slice(string.as_bytes());
+    }
+
+    fn optional(&mut self, string: Option<&str>) {
+        match string {
+            Some(string) => {
+                self.bytes.push(1);
+                self.string(string);
+            }
+            None => self.bytes.push(0),
+        }
+    }
+
+    fn value(&mut self, value: &Value<'_>) {
+        let kind = match value {
+            Value::Slice(_) => ValueKind::List,
+            value => value.kind(),
+        };
+        let tag = TAGS
+            .iter()
+            .position(|other| *other == kind)
+            .unwrap_or_default();
+        #[allow(clippy::cast_possible_truncation)]
+        self.bytes.push(tag as u8);
+        match value {
+            Value::U8(inner) => self.bytes.push(*inner),
+            Value::U16(inner) => self.bytes.extend(inner.to_le_bytes()),
+            Value::U32(inner) => self.bytes.extend(inner.to_le_bytes()),
+            Value::U64(inner) | Value::Size(inner) | Value::Duration(inner) => {
+                self.bytes.extend(inner.to_le_bytes());
+            }
+            Value::Uint(inner) => self.bytes.extend((*inner as u64).to_le_bytes()),
+            Value::I8(inner) => self.bytes.extend(inner.to_le_bytes()),
+            Value::I16(inner) => self.bytes.extend(inner.to_le_bytes()),
+            Value::I32(inner) => self.bytes.extend(inner.to_le_bytes()),
+            Value::I64(inner) => self.bytes.extend(inner.to_le_bytes()),
+            Value::Int(inner) => self.bytes.extend((*inner as i64).to_le_bytes()),
+            Value::F32(inner) => self.bytes.extend(inner.to_bits().to_le_bytes()),
+            Value::F64(inner) => self.bytes.extend(inner.to_bits().to_le_bytes()),
+            Value::Bool(inner) => self.bytes.push(u8::from(*inner)),
+            Value::String(inner) => self.string(inner),
+            Value::List(_) | Value::Slice(_) => {
+                let items = value.items().unwrap_or_default();
+                self.length(items.len());
+                for item in items {
+                    self.value(item);
+                }
+            }
+            Value::Map(inner) => {
+                self.length(inner.len());
+                for (key, item) in inner.iter() {
+                    self.string(key);
+                    self.value(item);
+                }
+            }
+            Value::Null => {}
+        }
+    }
+
+    fn node(&mut self, node: &dyn Node) {
+        self.optional(node.namespace());
+        self.string(node.name());
+        let args: Vec<_> = node.args_iter().collect();
+        self.length(args.len());
+        for arg in &args {
+            self.value(arg);
+        }
+        let params = node.params_all();
+        self.length(params.len());
+        for (key, value) in &params {
+            self.string(key);
+            self.value(value);
+        }
+        let children = node.children();
+        self.length(children.len());
+        for child in children {
+            self.node(child);
+        }
+    }
+}
+
+/// Encode a document, see the [module](self) documentation.
+///
+/// ```
+/// # use gpnd::bridge::binary::{from_bytes, to_bytes};
+/// let document = gpnd::parser::parse("server port=8080 { tls }").unwrap();
+/// assert_eq!(from_bytes(&to_bytes(&document)).unwrap(), document);
+/// ```
+#[must_use]
+pub fn to_bytes<D>(document: &D) -> Vec<u8>
+where
+    D: Document + ?Sized,
+{
+    let mut encoder = Encoder {
+        bytes: MAGIC.to_vec(),
+    };
+    encoder.bytes.push(FORMAT_VERSION);
+    encoder.optional(document.version());
+    let nodes = document.nodes();
+    encoder.length(nodes.len());
+    for node in nodes {
+        encoder.node(node);
+    }
+    encoder.bytes
+}
+
+struct Decoder<'bytes> {
+    bytes: &'bytes [u8],
+    depth: usize,
+}
+
+impl<'bytes> Decoder<'bytes> {
+    fn take(&mut self, count: usize) -> Result<&'bytes [u8], DecodeError> {
+        if self.bytes.len() < count {
+            return Err(DecodeError::UnexpectedEnd);
+        }
+        let (taken, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn byte(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn length(&mut self) -> Result<usize, DecodeError> {
+        let mut length = 0_usize;
+        let mut shift = 0;
+        loop {
+            let byte = self.byte()?;
+            let bits = usize::from(byte & 0x7f);
+            if shift >= usize::BITS || (bits << shift) >> shift != bits {
+                return Err(DecodeError::OutOfRange);
+            }
+            length |= bits << shift;
+            if byte & 0x80 == 0 {
+                return Ok(length);
+            }
+            shift += 7;
+        }
+    }
+
+    fn string(&mut self) -> Result<String, DecodeError> {
+        let length = self.length()?;
+        let bytes = self.take(length)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| DecodeError::InvalidUtf8)
+    }
+
+    fn optional(&mut self) -> Result<Option<String>, DecodeError> {
+        match self.byte()? {
+            0 => Ok(None),
+            _ => self.string().map(Some),
+        }
+    }
+
+    /// Decode with `decode` one level deeper, failing if that is deeper than
+    /// [`MAX_DEPTH`].
+    fn nested<T, F>(&mut self, decode: F) -> Result<T, DecodeError>
+    where
+        F: FnOnce(&mut Self) -> Result<T, DecodeError>,
+    {
+        if self.depth >= MAX_DEPTH {
+            return Err(DecodeError::TooDeep);
+        }
+        self.depth += 1;
+        let result = decode(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn value(&mut self) -> Result<Value<'static>, DecodeError> {
+        let tag = self.byte()?;
+        let kind = TAGS
+            .get(usize::from(tag))
+            .ok_or(DecodeError::InvalidTag(tag))?;
+        Ok(match kind {
+            ValueKind::U8 => Value::U8(self.byte()?),
+            ValueKind::U16 => Value::U16(u16::from_le_bytes(self.array()?)),
+            ValueKind::U32 => Value::U32(u32::from_le_bytes(self.array()?)),
+            ValueKind::U64 => Value::U64(u64::from_le_bytes(self.array()?)),
+            ValueKind::Size => Value::Size(u64::from_le_bytes(self.array()?)),
+            ValueKind::Duration => Value::Duration(u64::from_le_bytes(self.array()?)),
+            ValueKind::Uint => Value::Uint(
+                usize::try_from(u64::from_le_bytes(self.array()?))
+                    .map_err(|_| DecodeError::OutOfRange)?,
+            ),
+            ValueKind::I8 => Value::I8(i8::from_le_bytes(self.array()?)),
+            ValueKind::I16 => Value::I16(i16::from_le_bytes(self.array()?)),
+            ValueKind::I32 => Value::I32(i32::from_le_bytes(self.array()?)),
+            ValueKind::I64 => Value::I64(i64::from_le_bytes(self.array()?)),
+            ValueKind::Int => Value::Int(
+                isize::try_from(i64::from_le_bytes(self.array()?))
+                    .map_err(|_| DecodeError::OutOfRange)?,
+            ),
+            ValueKind::F32 => Value::F32(f32::from_bits(u32::from_le_bytes(self.array()?))),
+            ValueKind::F64 => Value::F64(f64::from_bits(u64::from_le_bytes(self.array()?))),
+            ValueKind::Bool => Value::Bool(self.byte()? != 0),
+            ValueKind::String => Value::String(Cow::Owned(self.string()?)),
+            ValueKind::List => {
+                let length = self.length()?;
+                self.nested(|decoder| {
+                    (0..length)
+                        .map(|_| decoder.value())
+                        .collect::<Result<Vec<_>, _>>()
+                })?
+                .into()
+            }
+            ValueKind::Map => {
+                let length = self.length()?;
+                Value::Map(Box::new(self.nested(|decoder| {
+                    (0..length)
+                        .map(|_| Ok((Cow::Owned(decoder.string()?), decoder.value()?)))
+                        .collect::<Result<_, _>>()
+                })?))
+            }
+            ValueKind::Slice | ValueKind::Null => Value::Null,
+        })
+    }
+
+    fn node(&mut self) -> Result<NodeBuf, DecodeError> {
+        let namespace = self.optional()?;
+        let mut node = NodeBuf::new(self.string()?);
+        node.set_namespace(namespace);
+        for _ in 0..self.length()? {
+            let value = self.value()?;
+            node.args_mut().push(value);
+        }
+        for _ in 0..self.length()? {
+            let key = self.string()?;
+            let value = self.value()?;
+            node.params_mut().push(key, value);
+        }
+        let length = self.length()?;
+        if length > 0 {
+            let children = self.nested(|decoder| {
+                (0..length)
+                    .map(|_| decoder.node())
+                    .collect::<Result<Vec<_>, _>>()
+            })?;
+            *node.children_mut() = children;
+        }
+        Ok(node)
+    }
+}
+
+/// Decode a document encoded by [`to_bytes`].
+///
+/// # Errors
+/// Fails if the bytes are not an encoded document, or are truncated.
+pub fn from_bytes(bytes: &[u8]) -> Result<DocumentBuf, DecodeError> {
+    let mut decoder = Decoder { bytes, depth: 0 };
+    if decoder.take(MAGIC.len()).ok() != Some(MAGIC.as_slice()) {
+        return Err(DecodeError::InvalidMagic);
+    }
+    match decoder.byte()? {
+        FORMAT_VERSION => {}
+        version => return Err(DecodeError::UnsupportedVersion(version)),
+    }
+    let mut document = DocumentBuf::new();
+    document.set_version(decoder.optional()?);
+    for _ in 0..decoder.length()? {
+        let node = decoder.node()?;
+        document.nodes_mut().push(node);
+    }
+    if !decoder.bytes.is_empty() {
+        return Err(DecodeError::TrailingBytes);
+    }
+    Ok(document)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn test_binary_roundtrip() {
+        let mut node = NodeBuf::new("all");
+        node.set_namespace(Some("test"));
+        node.args_mut().extend([
+            Value::U8(1),
+            Value::U16(2),
+            Value::U32(3),
+            Value::U64(u64::MAX),
+            Value::Uint(5),
+            Value::I8(-1),
+            Value::I16(-2),
+            Value::I32(-3),
+            Value::I64(i64::MIN),
+            Value::Int(-5),
+            Value::F32(1.5),
+            Value::F64(f64::NEG_INFINITY),
+            Value::Bool(true),
+            Value::Size(4096),
+            Value::Duration(250_000_000),
+            Value::from("text"),
+            vec![Value::Null, Value::from("a")].into(),
+            Value::Null,
+        ]);
+        node.params_mut().push("key", 1_u8);
+        node.params_mut().push("key", "again");
+        let mut map = alloc::collections::BTreeMap::new();
+        map.insert(Cow::Borrowed("k"), Value::I16(7));
+        node.params_mut().push("map", Value::Map(Box::new(map)));
+        node.children_mut().push(NodeBuf::new("child"));
+        let mut document = DocumentBuf::from(vec![node, NodeBuf::new("x".repeat(200))]);
+        document.set_version(Some("1"));
+
+        let bytes = to_bytes(&document);
+        let decoded = from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, document);
+        let (lhs, rhs) = (decoded.nodes()[0], document.nodes()[0]);
+        assert!(lhs
+            .args_iter()
+            .zip(rhs.args_iter())
+            .all(|(lhs, rhs)| lhs.kind() == rhs.kind()));
+    }
+
+    #[test]
+    fn test_binary_errors() {
+        let bytes = to_bytes(&crate::parser::parse("a 1").unwrap());
+        assert_eq!(from_bytes(b"nope"), Err(DecodeError::InvalidMagic));
+        assert_eq!(
+            from_bytes(&bytes[..bytes.len() - 1]),
+            Err(DecodeError::UnexpectedEnd)
+        );
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert_eq!(from_bytes(&trailing), Err(DecodeError::TrailingBytes));
+        let mut version = bytes;
+        version[4] = 2;
+        assert_eq!(
+            from_bytes(&version),
+            Err(DecodeError::UnsupportedVersion(2))
+        );
+    }
+
+    #[test]
+    fn test_binary_max_depth() {
+        let nested = |depth: usize| {
+            let mut node = NodeBuf::new("leaf");
+            let mut value = Value::Null;
+            for _ in 0..depth {
+                let mut parent = NodeBuf::new("node");
+                parent.children_mut().push(node);
+                node = parent;
+                value = vec![value].into();
+            }
+            node.args_mut().push(value);
+            DocumentBuf::from(vec![node])
+        };
+        let document = nested(MAX_DEPTH);
+        assert_eq!(from_bytes(&to_bytes(&document)).unwrap(), document);
+        assert_eq!(
+            from_bytes(&to_bytes(&nested(MAX_DEPTH + 1))),
+            Err(DecodeError::TooDeep)
+        );
+
+        // An argument that is a list or a map nested 100000 times, which
+        // would otherwise overflow the stack.
+        let deep = |item: &[u8]| {
+            // The node `a`, without its counts of parameters and children,
+            // and with one argument.
+            let mut bytes = to_bytes(&crate::parser::parse("a").unwrap());
+            bytes.truncate(bytes.len() - 3);
+            bytes.push(1);
+            for _ in 0..100_000 {
+                bytes.extend_from_slice(item);
+            }
+            bytes
+        };
+        assert_eq!(from_bytes(&deep(&[14, 1])), Err(DecodeError::TooDeep));
+        assert_eq!(
+            from_bytes(&deep(&[15, 1, 1, b'k'])),
+            Err(DecodeError::TooDeep)
+        );
+        assert_eq!(
+            DecodeError::TooDeep.to_string(),
+            "nesting deeper than 128 levels"
+        );
+    }
+}
@@ -0,0 +1,252 @@
+//! [`clap`] value parsers for values, nodes, and overrides of parameters, so
+//! that command-line arguments are read with the same rules as documents.
+//!
+//! A value is written as it would be as the argument of a node, so a string
+//! needs quotes. An override is a path and a value, separated by `=`, such as
+//! `server/port=8080`, and sets a parameter as [`DocumentBuf::set`] does.
+//!
+//! With the `derive` feature of clap, the types can be used as the types of
+//! fields, since they implement [`ValueParserFactory`].
+//!
+//! ```
+//! # use clap::{Arg, ArgAction, Command};
+//! # use gpnd::bridge::clap::{Override, OverrideParser};
+//! # use gpnd::Document;
+//! let command = Command::new("app").arg(
+//!     Arg::new("set")
+//!         .long("set")
+//!         .action(ArgAction::Append)
+//!         .value_parser(OverrideParser),
+//! );
+//! let matches = command.get_matches_from(["app", "--set", "server/port=8080"]);
+//! let mut document = gpnd::parser::parse("server port=80").unwrap();
+//! for setting in matches.get_many::<Override>("set").unwrap() {
+//!     setting.apply(&mut document).unwrap();
+//! }
+//! assert_eq!(document.get_path_value("server/port").unwrap(), 8080);
+//! ```
+
+use std::ffi::OsStr;
+use std::fmt;
+
+use clap::builder::{TypedValueParser, ValueParserFactory};
+use clap::error::ErrorKind;
+use clap::{Arg, Command, Error};
+
+use crate::parser::parse;
+use crate::{DocumentBuf, Node, NodeBuf, PathError, Value};
+
+fn invalid<D>(cmd: &Command, arg: Option<&Arg>, value: &str, reason: D) -> Error
+where
+    D: fmt::Display,
+{
+    let message = match arg {
+        Some(arg) => format!("invalid value '{value}' for '{arg}': {reason}\n"),
+        None => format!("invalid value '{value}': {reason}\n"),
+    };
+    Error::raw(ErrorKind::ValueValidation, message).with_cmd(cmd)
+}
+
+fn to_str<'a>(cmd: &Command, arg: Option<&Arg>, value: &'a OsStr) -> Result<&'a str, Error> {
+    value
+        .to_str()
+        .ok_or_else(|| invalid(cmd, arg, &value.to_string_lossy(), "not valid UTF-8"))
+}
+
+/// Parse `text` as the only node of a document.
+fn parse_node(text: &str) -> Result<NodeBuf, String> {
+    let mut document = parse(text).map_err(|error| error.kind().to_string())?;
+    match document.nodes_ref().len() {
+        1 => Ok(document.nodes_mut().remove(0)),
+        0 => Err("expected a node".to_owned()),
+        _ => Err("expected a single node".to_owned()),
+    }
+}
+
+/// Parse `text` as the only argument of a node.
+fn parse_value(text: &str) -> Result<Value<'static>, String> {
+    let mut node = parse_node(&format!("_ {text}"))?;
+    if node.args_ref().len() != 1 || node.has_params() || node.has_children() {
+        return Err("expected a single value".to_owned());
+    }
+    Ok(node.args_mut().remove(0))
+}
+
+/// Parses a [`Value`], such as `8080`, `"localhost"`, or `[1 2]`.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct ValueParser;
+
+impl TypedValueParser for ValueParser {
+    type Value = Value<'static>;
+
+    fn parse_ref(
+        &self,
+        cmd: &Command,
+        arg: Option<&Arg>,
+        value: &OsStr,
+    ) -> Result<Self::Value, Error> {
+        let value = to_str(cmd, arg, value)?;
+        parse_value(value).map_err(|reason| invalid(cmd, arg, value, reason))
+    }
+}
+
+impl ValueParserFactory for Value<'static> {
+    type Parser = ValueParser;
+
+    fn value_parser() -> Self::Parser {
+        ValueParser
+    }
+}
+
+/// Parses a single node, such as `server port=8080 { tls }`.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct NodeParser;
+
+impl TypedValueParser for NodeParser {
+    type Value = NodeBuf;
+
+    fn parse_ref(
+        &self,
+        cmd: &Command,
+        arg: Option<&Arg>,
+        value: &OsStr,
+    ) -> Result<Self::Value, Error> {
+        let value = to_str(cmd, arg, value)?;
+        parse_node(value).map_err(|reason| invalid(cmd, arg, value, reason))
+    }
+}
+
+impl ValueParserFactory for NodeBuf {
+    type Parser = NodeParser;
+
+    fn value_parser() -> Self::Parser {
+        NodeParser
+    }
+}
+
+/// A parameter to set by path, such as `server/port=8080`, see the
+/// [module](self) documentation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Override {
+    pub path: String,
+    pub value: Value<'static>,
+}
+
+impl Override {
+    /// Set the parameter in `document`, returning its previous value.
+    ///
+    /// # Errors
+    /// Fails if there is no node at the path, see [`DocumentBuf::set`].
+    pub fn apply(&self, document: &mut DocumentBuf) -> Result<Option<Value<'static>>, PathError> {
+        document.set(&self.path, self.value.clone())
+    }
+}
+
+/// Parses an [`Override`].
+#[derive(Copy, Clone, Debug, Default)]
+pub struct OverrideParser;
+
+impl TypedValueParser for OverrideParser {
+    type Value = Override;
+
+    fn parse_ref(
+        &self,
+        cmd: &Command,
+        arg: Option<&Arg>,
+        value: &OsStr,
+    ) -> Result<Self::Value, Error> {
+        let text = to_str(cmd, arg, value)?;
+        let (path, value) = text
+            .split_once('=')
+            .filter(|(path, _)| !path.is_empty())
+            .ok_or_else(|| invalid(cmd, arg, text, "expected `path=value`"))?;
+        Ok(Override {
+            path: path.to_owned(),
+            value: parse_value(value).map_err(|reason| invalid(cmd, arg, text, reason))?,
+        })
+    }
+}
+
+impl ValueParserFactory for Override {
+    type Parser = OverrideParser;
+
+    fn value_parser() -> Self::Parser {
+        OverrideParser
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::{value_parser, ArgAction};
+
+    use super::*;
+
+    #[test]
+    fn test_clap_parsers() {
+        let command = Command::new("app")
+            .arg(
+                Arg::new("set")
+                    .long("set")
+                    .action(ArgAction::Append)
+                    .value_parser(value_parser!(Override)),
+            )
+            .arg(
+                Arg::new("value")
+                    .long("value")
+                    .value_parser(value_parser!(Value<'static>)),
+            )
+            .arg(
+                Arg::new("node")
+                    .long("node")
+                    .value_parser(value_parser!(NodeBuf)),
+            );
+        let matches = command
+            .clone()
+            .try_get_matches_from([
+                "app",
+                "--set",
+                "server/port=8080",
+                "--set",
+                "server/tls/hosts=[\"a\" \"b\"]",
+                "--value",
+                "#inf",
+                "--node",
+                "client url=\"a=b\" { retry 3 }",
+            ])
+            .unwrap();
+        let settings: Vec<_> = matches.get_many::<Override>("set").unwrap().collect();
+        assert_eq!(settings[0].path, "server/port");
+        assert_eq!(settings[0].value, Value::I64(8080));
+        let mut document = parse("server port=80 { tls }").unwrap();
+        for setting in settings {
+            setting.apply(&mut document).unwrap();
+        }
+        assert_eq!(
+            document,
+            parse("server port=8080 { tls hosts=[\"a\" \"b\"] }").unwrap()
+        );
+        assert_eq!(
+            matches.get_one::<Value<'static>>("value"),
+            Some(&Value::F64(f64::INFINITY))
+        );
+        let node = matches.get_one::<NodeBuf>("node").unwrap();
+        assert_eq!(node.get_param("url"), Some(Value::from("a=b")));
+        assert_eq!(node.children_ref()[0].name(), "retry");
+
+        for (flag, value, message) in [
+            ("--value", "localhost", "expected value, found `localhost`"),
+            ("--value", "1 2", "expected a single value"),
+            ("--value", "\"a", "unterminated string"),
+            ("--set", "port", "expected `path=value`"),
+            ("--set", "=1", "expected `path=value`"),
+            ("--node", "a; b", "expected a single node"),
+        ] {
+            let error = command
+                .clone()
+                .try_get_matches_from(["app", flag, value])
+                .unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::ValueValidation);
+            assert!(error.to_string().contains(message), "{error}");
+        }
+    }
+}
@@ -0,0 +1,313 @@
+//! A [`figment`] provider for documents, so that a document can be a layer of
+//! configuration with the other formats that figment supports.
+//!
+//! A document becomes a dictionary with a key for each node name, with the
+//! same mapping as the [TOML bridge](super::toml): nodes with parameters or
+//! children become dictionaries, nodes that share a name become arrays,
+//! nodes with only arguments become their argument or an array of them, and
+//! the arguments of other nodes are kept under [`ARGS_KEY`]. `null` becomes
+//! an empty value, which is extracted as `None`.
+//!
+//! ```
+//! # use figment::Figment;
+//! # use gpnd::bridge::figment::GpndFile;
+//! # let directory = std::env::temp_dir().join("gpnd-figment-doc");
+//! # std::fs::create_dir_all(&directory).unwrap();
+//! # let path = directory.join("app.gpnd");
+//! # std::fs::write(&path, "server port=8080 { tls cert=\"a.pem\" }").unwrap();
+//! let figment = Figment::new().merge(GpndFile::new(&path));
+//! assert_eq!(figment.extract_inner::<u16>("server.port").unwrap(), 8080);
+//! assert_eq!(
+//!     figment.extract_inner::<String>("server.tls.cert").unwrap(),
+//!     "a.pem"
+//! );
+//! ```
+
+use std::path::{Path, PathBuf};
+use std::{error, fmt, fs, io};
+
+use figment::value::{Dict, Empty, Map, Num, Tag, Value as FigmentValue};
+use figment::{Error, Metadata, Profile, Provider};
+
+use crate::{Document, DocumentBuf, Node, Value};
+
+/// The key that holds the arguments of a node that is converted to a
+/// dictionary.
+pub const ARGS_KEY: &str = "args";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ToDictError {
+    /// Two entries of the same dictionary would have the same key, such as a
+    /// parameter and a child with the same name.
+    DuplicateKey(String),
+}
+
+impl fmt::Display for ToDictError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DuplicateKey(key) => write!(f, "duplicate key `{key}`"),
+        }
+    }
+}
+
+impl error::Error for ToDictError {}
+
+impl From<ToDictError> for Error {
+    fn from(error: ToDictError) -> Self {
+        Self::from(error.to_string())
+    }
+}
+
+impl From<Value<'_>> for FigmentValue {
+    fn from(other: Value<'_>) -> Self {
+        let num = |num| Self::Num(Tag::Default, num);
+        match other {
+            Value::U8(inner) => num(Num::U8(inner)),
+            Value::U16(inner) => num(Num::U16(inner)),
+            Value::U32(inner) => num(Num::U32(inner)),
+            Value::U64(inner) | Value::Size(inner) | Value::Duration(inner) => num(Num::U64(inner)),
+            Value::Uint(inner) => num(Num::USize(inner)),
+            Value::I8(inner) => num(Num::I8(inner)),
+            Value::I16(inner) => num(Num::I16(inner)),
+            Value::I32(inner) => num(Num::I32(inner)),
+            Value::I64(inner) => num(Num::I64(inner)),
+            Value::Int(inner) => num(Num::ISize(inner)),
+            Value::F32(inner) => num(Num::F32(inner)),
+            Value::F64(inner) => num(Num::F64(inner)),
+            Value::Bool(inner) => Self::Bool(Tag::Default, inner),
+            Value::String(inner) => Self::String(Tag::Default, inner.into_owned()),
+            Value::List(inner) => {
+                Self::Array(Tag::Default, inner.into_iter().map(Self::from).collect())
+            }
+            Value::Slice(inner) => Self::Array(
+                Tag::Default,
+                inner.iter().cloned().map(Self::from).collect(),
+            ),
+            Value::Map(inner) => Self::Dict(
+                Tag::Default,
+                inner
+                    .into_iter()
+                    .map(|(key, value)| (key.into_owned(), value.into()))
+                    .collect(),
+            ),
+            Value::Null => Self::Empty(Tag::Default, Empty::None),
+        }
+    }
+}
+
+/// The key of a node, including its namespace.
+fn node_key(node: &dyn Node) -> String {
+    match node.namespace() {
+        Some(namespace) => format!("{namespace}:{}", node.name()),
+        None => node.name().to_owned(),
+    }
+}
+
+fn insert(dict: &mut Dict, key: String, value: FigmentValue) -> Result<(), ToDictError> {
+    if dict.contains_key(&key) {
+        return Err(ToDictError::DuplicateKey(key));
+    }
+    dict.insert(key, value);
+    Ok(())
+}
+
+/// Add `nodes` to `dict`, grouping the nodes that share a name.
+fn insert_nodes(dict: &mut Dict, nodes: Vec<&dyn Node>) -> Result<(), ToDictError> {
+    let mut groups: Vec<(String, Vec<&dyn Node>)> = Vec::new();
+    for node in nodes {
+        let key = node_key(node);
+        match groups.iter_mut().find(|(other, _)| *other == key) {
+            Some((_, group)) => group.push(node),
+            None => groups.push((key, vec![node])),
+        }
+    }
+    for (key, group) in groups {
+        let value = match group.as_slice() {
+            [node] => node_to_value(*node)?,
+            _ => FigmentValue::Array(
+                Tag::Default,
+                group
+                    .into_iter()
+                    .map(node_to_value)
+                    .collect::<Result<_, _>>()?,
+            ),
+        };
+        insert(dict, key, value)?;
+    }
+    Ok(())
+}
+
+fn node_to_value(node: &dyn Node) -> Result<FigmentValue, ToDictError> {
+    let mut args: Vec<FigmentValue> = node.args_iter().map(Into::into).collect();
+    let (params, children) = (node.params(), node.children());
+    if params.is_empty() && children.is_empty() {
+        return Ok(match args.len() {
+            0 => FigmentValue::Dict(Tag::Default, Dict::new()),
+            1 => args.remove(0),
+            _ => FigmentValue::Array(Tag::Default, args),
+        });
+    }
+
+    let mut dict = Dict::new();
+    if !args.is_empty() {
+        dict.insert(ARGS_KEY.to_owned(), FigmentValue::Array(Tag::Default, args));
+    }
+    for (key, value) in params {
+        insert(&mut dict, key.to_owned(), value.into())?;
+    }
+    insert_nodes(&mut dict, children)?;
+    Ok(FigmentValue::Dict(Tag::Default, dict))
+}
+
+/// Convert a document to a figment dictionary, see the [module](self)
+/// documentation.
+///
+/// # Errors
+/// Fails if a parameter and a child of a node have the same name.
+pub fn to_dict<D>(document: &D) -> Result<Dict, ToDictError>
+where
+    D: Document + ?Sized,
+{
+    let mut dict = Dict::new();
+    insert_nodes(&mut dict, document.nodes())?;
+    Ok(dict)
+}
+
+/// A provider that reads a document from a file, see the [module](self)
+/// documentation.
+///
+/// As with the formats of figment, a file that does not exist provides no
+/// data, so that it can be an optional layer.
+#[derive(Clone, Debug)]
+pub struct GpndFile {
+    path: PathBuf,
+    profile: Option<Profile>,
+    nested: bool,
+}
+
+impl GpndFile {
+    #[must_use]
+    pub fn new<P>(path: P) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        Self {
+            path: path.as_ref().to_owned(),
+            profile: None,
+            nested: false,
+        }
+    }
+
+    /// Provide the data at `profile`, instead of the default profile.
+    #[must_use]
+    pub fn profile<P>(mut self, profile: P) -> Self
+    where
+        P: Into<Profile>,
+    {
+        self.profile = Some(profile.into());
+        self
+    }
+
+    /// Treat each top-level node as a profile, with the nodes in it as its
+    /// data, so that `debug { port 80 }` sets `port` for the `debug` profile.
+    /// The nodes at the top level must then have parameters or children.
+    #[must_use]
+    pub fn nested(mut self) -> Self {
+        self.nested = true;
+        self
+    }
+}
+
+impl Provider for GpndFile {
+    fn metadata(&self) -> Metadata {
+        Metadata::from("GPND file", self.path.as_path())
+    }
+
+    fn data(&self) -> Result<Map<Profile, Dict>, Error> {
+        let source = match fs::read_to_string(&self.path) {
+            Ok(source) => source,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Map::new()),
+            Err(error) => return Err(Error::from(error.to_string())),
+        };
+        let document =
+            crate::parser::parse(&source).map_err(|error| Error::from(error.to_string()))?;
+        let dict = to_dict(&document)?;
+        if !self.nested {
+            let profile = self.profile.clone().unwrap_or_default();
+            return Ok(Map::from([(profile, dict)]));
+        }
+        let mut data = Map::new();
+        for (key, value) in dict {
+            let FigmentValue::Dict(_, dict) = value else {
+                return Err(Error::from(format!("profile `{key}` is not a dictionary")));
+            };
+            data.insert(Profile::from(key), dict);
+        }
+        Ok(data)
+    }
+}
+
+impl Provider for DocumentBuf {
+    fn metadata(&self) -> Metadata {
+        Metadata::named("GPND document")
+    }
+
+    fn data(&self) -> Result<Map<Profile, Dict>, Error> {
+        Ok(Map::from([(Profile::Default, to_dict(self)?)]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use figment::Figment;
+
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_figment_provider() {
+        let document = parse(concat!(
+            "name \"app\"\n",
+            "server port=80 address=null { tls; route \"/\" \"/api\" }\n",
+            "worker id=1\n",
+            "worker id=2\n",
+            "log 1 level=\"debug\"\n",
+        ))
+        .unwrap();
+        let figment = Figment::from(&document);
+        assert_eq!(figment.extract_inner::<String>("name").unwrap(), "app");
+        assert_eq!(figment.extract_inner::<u16>("server.port").unwrap(), 80);
+        assert_eq!(
+            figment
+                .extract_inner::<Option<String>>("server.address")
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            figment
+                .extract_inner::<Vec<String>>("server.route")
+                .unwrap(),
+            ["/", "/api"]
+        );
+        let workers = figment.extract_inner::<Vec<Map<String, u8>>>("worker");
+        assert_eq!(workers.unwrap()[1]["id"], 2);
+        assert_eq!(figment.extract_inner::<Vec<u8>>("log.args").unwrap(), [1]);
+
+        let error = Figment::from(parse("a k=1 { k }").unwrap())
+            .extract::<Dict>()
+            .unwrap_err();
+        assert!(error.to_string().contains("duplicate key `k`"));
+
+        let directory = std::env::temp_dir().join("gpnd-figment-test");
+        fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("app.gpnd");
+        fs::write(&path, "default { port 80 }\ndebug { port 8080 }").unwrap();
+        let figment = Figment::from(&document)
+            .merge(GpndFile::new(&path).nested())
+            .merge(GpndFile::new(directory.join("missing.gpnd")));
+        assert_eq!(figment.extract_inner::<u16>("port").unwrap(), 80);
+        let figment = figment.select("debug");
+        assert_eq!(figment.extract_inner::<u16>("port").unwrap(), 8080);
+        assert_eq!(figment.extract_inner::<String>("name").unwrap(), "app");
+    }
+}
@@ -0,0 +1,133 @@
+//! Conversions between [`Value`] and [`serde_json::Value`].
+//!
+//! JSON numbers become `I64` when they fit, otherwise `U64` or `F64`.
+//! Objects become `Map` and arrays become `List`. The reverse conversion is
+//! fallible, because JSON cannot represent non-finite floats.
+
+use std::borrow::Cow;
+use std::error::Error;
+
+use serde_json::{Map, Number};
+
+use crate::{Value, ValueKind};
+
+impl From<serde_json::Value> for Value<'static> {
+    fn from(other: serde_json::Value) -> Self {
+        match other {
+            serde_json::Value::Null => Self::Null,
+            serde_json::Value::Bool(inner) => Self::Bool(inner),
+            serde_json::Value::Number(inner) => inner.into(),
+            serde_json::Value::String(inner) => Self::String(Cow::Owned(inner)),
+            serde_json::Value::Array(inner) => inner.into_iter().collect(),
+            serde_json::Value::Object(inner) => Self::Map(Box::new(
+                inner
+                    .into_iter()
+                    .map(|(key, value)| (Cow::Owned(key), value.into()))
+                    .collect(),
+            )),
+        }
+    }
+}
+
+impl From<Number> for Value<'static> {
+    fn from(other: Number) -> Self {
+        if let Some(inner) = other.as_i64() {
+            Self::I64(inner)
+        } else if let Some(inner) = other.as_u64() {
+            Self::U64(inner)
+        } else {
+            other.as_f64().map_or(Self::Null, Self::F64)
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct ToJsonError {
+    variant: ValueKind,
+    value: f64,
+}
+
+impl std::fmt::Display for ToJsonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "cannot convert non-finite `{}` value `{}` to JSON",
+            self.variant, self.value
+        )
+    }
+}
+
+impl Error for ToJsonError {}
+
+impl TryFrom<Value<'_>> for serde_json::Value {
+    type Error = ToJsonError;
+
+    fn try_from(other: Value<'_>) -> Result<Self, Self::Error> {
+        let float = |variant, value: f64| {
+            Number::from_f64(value)
+                .map(Self::Number)
+                .ok_or(ToJsonError { variant, value })
+        };
+        Ok(match other {
+            Value::U8(inner) => inner.into(),
+            Value::U16(inner) => inner.into(),
+            Value::U32(inner) => inner.into(),
+            Value::U64(inner) | Value::Size(inner) | Value::Duration(inner) => inner.into(),
+            Value::Uint(inner) => inner.into(),
+            Value::I8(inner) => inner.into(),
+            Value::I16(inner) => inner.into(),
+            Value::I32(inner) => inner.into(),
+            Value::I64(inner) => inner.into(),
+            Value::Int(inner) => inner.into(),
+            Value::F32(inner) => float(ValueKind::F32, f64::from(inner))?,
+            Value::F64(inner) => float(ValueKind::F64, inner)?,
+            Value::Bool(inner) => inner.into(),
+            Value::String(inner) => inner.into_owned().into(),
+            Value::List(inner) => Self::Array(
+                inner
+                    .into_iter()
+                    .map(TryInto::try_into)
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::Slice(inner) => Self::Array(
+                inner
+                    .iter()
+                    .cloned()
+                    .map(TryInto::try_into)
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::Map(inner) => Self::Object(
+                inner
+                    .into_iter()
+                    .map(|(key, value)| Ok((key.into_owned(), value.try_into()?)))
+                    .collect::<Result<Map<_, _>, _>>()?,
+            ),
+            Value::Null => Self::Null,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::Value;
+
+    #[test]
+    fn test_json_roundtrip() {
+        let json = json!({
+            "name": "server",
+            "port": 8080,
+            "ratio": 0.5,
+            "tags": ["a", "b", null],
+            "tls": { "enabled": true },
+        });
+        let value = Value::from(json.clone());
+        assert_eq!(serde_json::Value::try_from(value).unwrap(), json);
+    }
+
+    #[test]
+    fn test_json_non_finite() {
+        assert!(serde_json::Value::try_from(Value::F64(f64::NAN)).is_err());
+    }
+}
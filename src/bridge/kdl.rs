@@ -0,0 +1,235 @@
+//! Conversions between documents and [`kdl::KdlDocument`], and between
+//! [`Value`] and [`kdl::KdlValue`].
+//!
+//! The two formats have the same shape, so nodes keep their arguments,
+//! properties, and children. A node with a namespace is named `namespace:name`
+//! in KDL, and a KDL name with one colon is split into a namespace and a name
+//! again, and the type annotation of a node is kept. Lists and maps have no
+//! KDL equivalent, and the version of a document, the type annotations of
+//! values, and formatting are not converted.
+//!
+//! ```
+//! # use gpnd::DocumentBuf;
+//! # use kdl::KdlDocument;
+//! let document = gpnd::parser::parse("server port=8080 { tls }").unwrap();
+//! let kdl = KdlDocument::try_from(&document).unwrap();
+//! assert_eq!(
+//!     kdl.nodes()[0]
+//!         .get("port")
+//!         .and_then(|port| port.as_integer()),
+//!     Some(8080)
+//! );
+//! assert_eq!(DocumentBuf::try_from(&kdl).unwrap(), document);
+//! ```
+
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+
+use kdl::{KdlDocument, KdlEntry, KdlIdentifier, KdlNode, KdlValue};
+
+use crate::{Document, DocumentBuf, Node, NodeBuf, Value, ValueKind};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ToKdlError {
+    variant: ValueKind,
+}
+
+impl fmt::Display for ToKdlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cannot convert `{}` value to KDL", self.variant)
+    }
+}
+
+impl Error for ToKdlError {}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FromKdlError {
+    value: i128,
+}
+
+impl fmt::Display for FromKdlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "integer `{}` is out of range", self.value)
+    }
+}
+
+impl Error for FromKdlError {}
+
+impl TryFrom<Value<'_>> for KdlValue {
+    type Error = ToKdlError;
+
+    fn try_from(other: Value<'_>) -> Result<Self, Self::Error> {
+        Ok(match other {
+            Value::U8(inner) => Self::Integer(inner.into()),
+            Value::U16(inner) => Self::Integer(inner.into()),
+            Value::U32(inner) => Self::Integer(inner.into()),
+            Value::U64(inner) | Value::Size(inner) | Value::Duration(inner) => {
+                Self::Integer(inner.into())
+            }
+            Value::Uint(inner) => Self::Integer(inner as i128),
+            Value::I8(inner) => Self::Integer(inner.into()),
+            Value::I16(inner) => Self::Integer(inner.into()),
+            Value::I32(inner) => Self::Integer(inner.into()),
+            Value::I64(inner) => Self::Integer(inner.into()),
+            Value::Int(inner) => Self::Integer(inner as i128),
+            Value::F32(inner) => Self::Float(inner.into()),
+            Value::F64(inner) => Self::Float(inner),
+            Value::Bool(inner) => Self::Bool(inner),
+            Value::String(inner) => Self::String(inner.into_owned()),
+            Value::Null => Self::Null,
+            Value::List(_) | Value::Slice(_) => {
+                return Err(ToKdlError {
+                    variant: ValueKind::List,
+                })
+            }
+            Value::Map(_) => {
+                return Err(ToKdlError {
+                    variant: ValueKind::Map,
+                })
+            }
+        })
+    }
+}
+
+impl TryFrom<&KdlValue> for Value<'static> {
+    type Error = FromKdlError;
+
+    fn try_from(other: &KdlValue) -> Result<Self, Self::Error> {
+        Ok(match *other {
+            KdlValue::String(ref inner) => Self::String(Cow::Owned(inner.clone())),
+            KdlValue::Integer(inner) => match (i64::try_from(inner), u64::try_from(inner)) {
+                (Ok(inner), _) => Self::I64(inner),
+                (_, Ok(inner)) => Self::U64(inner),
+                _ => return Err(FromKdlError { value: inner }),
+            },
+            KdlValue::Float(inner) => Self::F64(inner),
+            KdlValue::Bool(inner) => Self::Bool(inner),
+            KdlValue::Null => Self::Null,
+        })
+    }
+}
+
+fn node_to_kdl(node: &dyn Node) -> Result<KdlNode, ToKdlError> {
+    let mut kdl = match node.namespace() {
+        Some(namespace) => KdlNode::new(format!("{namespace}:{}", node.name())),
+        None => KdlNode::new(node.name()),
+    };
+    if let Some(type_annotation) = node.type_annotation() {
+        kdl.set_ty(type_annotation);
+    }
+    for arg in node.args_iter() {
+        kdl.entries_mut()
+            .push(KdlEntry::new(KdlValue::try_from(arg)?));
+    }
+    for (key, value) in node.params_all() {
+        kdl.entries_mut()
+            .push(KdlEntry::new_prop(key, KdlValue::try_from(value)?));
+    }
+    let children = node.children();
+    if !children.is_empty() {
+        kdl.set_children(nodes_to_kdl(children)?);
+    }
+    Ok(kdl)
+}
+
+fn nodes_to_kdl(nodes: Vec<&dyn Node>) -> Result<KdlDocument, ToKdlError> {
+    let mut document = KdlDocument::new();
+    for node in nodes {
+        document.nodes_mut().push(node_to_kdl(node)?);
+    }
+    Ok(document)
+}
+
+impl TryFrom<&DocumentBuf> for KdlDocument {
+    type Error = ToKdlError;
+
+    fn try_from(other: &DocumentBuf) -> Result<Self, Self::Error> {
+        nodes_to_kdl(other.nodes())
+    }
+}
+
+fn node_from_kdl(kdl: &KdlNode) -> Result<NodeBuf, FromKdlError> {
+    let name = kdl.name().value();
+    let mut node = match name.split_once(':') {
+        Some((namespace, name))
+            if !namespace.is_empty() && !name.is_empty() && !name.contains(':') =>
+        {
+            let mut node = NodeBuf::new(name);
+            node.set_namespace(Some(namespace));
+            node
+        }
+        _ => NodeBuf::new(name),
+    };
+    node.set_type_annotation(kdl.ty().map(KdlIdentifier::value));
+    for entry in kdl.entries() {
+        let value = Value::try_from(entry.value())?;
+        match entry.name() {
+            Some(key) => node.params_mut().push(key.value(), value),
+            None => node.args_mut().push(value),
+        }
+    }
+    if let Some(children) = kdl.children() {
+        for child in children.nodes() {
+            node.children_mut().push(node_from_kdl(child)?);
+        }
+    }
+    Ok(node)
+}
+
+impl TryFrom<&KdlDocument> for DocumentBuf {
+    type Error = FromKdlError;
+
+    fn try_from(other: &KdlDocument) -> Result<Self, Self::Error> {
+        let mut document = DocumentBuf::new();
+        for node in other.nodes() {
+            document.nodes_mut().push(node_from_kdl(node)?);
+        }
+        Ok(document)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_kdl_roundtrip() {
+        let kdl: KdlDocument = concat!(
+            "package name=gpnd version=\"0.1.0\" {\n",
+            "    (exe)app:bin main 1 2.5 #true #null big=18446744073709551615\n",
+            "}\n",
+            "\"with space\" -3\n",
+        )
+        .parse()
+        .unwrap();
+        let document = DocumentBuf::try_from(&kdl).unwrap();
+        assert_eq!(
+            document,
+            parse(concat!(
+                "package name=\"gpnd\" version=\"0.1.0\" {\n",
+                "    (exe)app:bin \"main\" 1 2.5 true null big=18446744073709551615\n",
+                "}\n",
+                "\"with space\" -3\n",
+            ))
+            .unwrap()
+        );
+        let back = KdlDocument::try_from(&document).unwrap();
+        assert_eq!(DocumentBuf::try_from(&back).unwrap(), document);
+        assert_eq!(
+            back.nodes()[0].children().unwrap().nodes()[0]
+                .name()
+                .value(),
+            "app:bin"
+        );
+
+        let error = KdlDocument::try_from(&parse("a [1]").unwrap()).unwrap_err();
+        assert_eq!(error.to_string(), "cannot convert `List` value to KDL");
+        let error = Value::try_from(&KdlValue::Integer(i128::MIN)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "integer `-170141183460469231731687303715884105728` is out of range"
+        );
+    }
+}
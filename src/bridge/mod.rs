@@ -0,0 +1,27 @@
+//! Conversions between this crate's types and those of other data formats.
+
+pub mod binary;
+
+#[cfg(feature = "json")]
+pub mod json;
+
+#[cfg(feature = "serde")]
+pub mod serde;
+
+#[cfg(feature = "yaml")]
+pub mod yaml;
+
+#[cfg(feature = "toml")]
+pub mod toml;
+
+#[cfg(feature = "xml")]
+pub mod xml;
+
+#[cfg(feature = "figment")]
+pub mod figment;
+
+#[cfg(feature = "kdl")]
+pub mod kdl;
+
+#[cfg(feature = "clap")]
+pub mod clap;
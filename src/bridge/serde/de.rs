@@ -0,0 +1,574 @@
+use std::borrow::Cow;
+
+use serde::de::value::{BorrowedStrDeserializer, MapDeserializer, SeqDeserializer};
+use serde::de::{DeserializeSeed, Deserializer, Error as _, IntoDeserializer, MapAccess, Visitor};
+use serde::{forward_to_deserialize_any, Deserialize};
+
+use super::DeserializeError;
+use crate::{Document, Node, Value};
+
+/// Deserialize a `T` from the nodes of `document`.
+///
+/// # Errors
+/// Returns an error if the document does not have the shape of a `T`.
+pub fn from_document<'de, T, D>(document: &'de D) -> Result<T, DeserializeError>
+where
+    T: Deserialize<'de>,
+    D: Document + ?Sized,
+{
+    T::deserialize(NodesDeserializer::new(document.nodes()))
+}
+
+/// Deserialize a `T` from a single node.
+///
+/// # Errors
+/// Returns an error if the node does not have the shape of a `T`.
+pub fn from_node<'de, T, N>(node: &'de N) -> Result<T, DeserializeError>
+where
+    T: Deserialize<'de>,
+    N: Node,
+{
+    T::deserialize(NodeDeserializer::new(node))
+}
+
+/// Deserializes a list of nodes, such as a document or the children of a node,
+/// as a map from names to groups of nodes.
+#[derive(Clone)]
+pub struct NodesDeserializer<'de> {
+    nodes: Vec<&'de dyn Node>,
+}
+
+impl<'de> NodesDeserializer<'de> {
+    #[must_use]
+    pub fn new(nodes: Vec<&'de dyn Node>) -> Self {
+        Self { nodes }
+    }
+}
+
+impl std::fmt::Debug for NodesDeserializer<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NodesDeserializer")
+            .field("nodes", &self.nodes.len())
+            .finish()
+    }
+}
+
+impl<'de> Deserializer<'de> for NodesDeserializer<'de> {
+    type Error = DeserializeError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(EntriesAccess::new(Vec::new(), self.nodes))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// Deserializes a single node, see the [module documentation](super).
+#[derive(Clone, Copy)]
+pub struct NodeDeserializer<'de> {
+    node: &'de dyn Node,
+}
+
+impl<'de> NodeDeserializer<'de> {
+    #[must_use]
+    pub fn new(node: &'de dyn Node) -> Self {
+        Self { node }
+    }
+
+    fn only_arg(self) -> Result<ValueDeserializer<'de>, DeserializeError> {
+        let mut args = self.node.args();
+        if args.len() == 1 && !self.has_entries() {
+            Ok(ValueDeserializer::new(args.remove(0)))
+        } else {
+            Err(DeserializeError::custom(format_args!(
+                "expected node `{}` to have a single argument",
+                self.node.name()
+            )))
+        }
+    }
+
+    fn has_entries(self) -> bool {
+        self.node.has_params() || self.node.has_children()
+    }
+
+    fn entries(self) -> EntriesAccess<'de> {
+        EntriesAccess::new(self.node.params(), self.node.children())
+    }
+}
+
+impl std::fmt::Debug for NodeDeserializer<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NodeDeserializer")
+            .field("name", &self.node.name())
+            .finish()
+    }
+}
+
+macro_rules! forward_to_arg {
+    ($($method:ident),+) => {
+        $(fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: Visitor<'de>,
+        {
+            self.only_arg()?.$method(visitor)
+        })+
+    };
+}
+
+impl<'de> Deserializer<'de> for NodeDeserializer<'de> {
+    type Error = DeserializeError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if self.has_entries() {
+            return visitor.visit_map(self.entries());
+        }
+        let mut args = self.node.args();
+        match args.len() {
+            0 => visitor.visit_unit(),
+            1 => ValueDeserializer::new(args.remove(0)).deserialize_any(visitor),
+            _ => visitor.visit_seq(SeqDeserializer::new(args.into_iter())),
+        }
+    }
+
+    forward_to_arg!(
+        deserialize_bool,
+        deserialize_i8,
+        deserialize_i16,
+        deserialize_i32,
+        deserialize_i64,
+        deserialize_i128,
+        deserialize_u8,
+        deserialize_u16,
+        deserialize_u32,
+        deserialize_u64,
+        deserialize_u128,
+        deserialize_f32,
+        deserialize_f64,
+        deserialize_char,
+        deserialize_str,
+        deserialize_string,
+        deserialize_bytes,
+        deserialize_byte_buf
+    );
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if !self.node.has_args() && self.has_entries() {
+            visitor.visit_seq(SeqDeserializer::new(std::iter::once(self)))
+        } else {
+            visitor.visit_seq(SeqDeserializer::new(self.node.args().into_iter()))
+        }
+    }
+
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(self.entries())
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(self.entries())
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.only_arg()?.deserialize_enum(name, variants, visitor)
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        unit unit_struct identifier
+    }
+}
+
+impl<'de> IntoDeserializer<'de, DeserializeError> for NodeDeserializer<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+/// A group of sibling nodes with the same name. A group of one is the same as
+/// the node, and a larger group is a sequence of the nodes.
+struct GroupDeserializer<'de> {
+    nodes: Vec<&'de dyn Node>,
+}
+
+impl<'de> Deserializer<'de> for GroupDeserializer<'de> {
+    type Error = DeserializeError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(SeqDeserializer::new(
+            self.nodes.into_iter().map(NodeDeserializer::new),
+        ))
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct enum
+        identifier ignored_any
+    }
+}
+
+enum Entry<'de> {
+    Param(Value<'de>),
+    Group(Vec<&'de dyn Node>),
+}
+
+/// The parameters and grouped children of a node, as a map.
+struct EntriesAccess<'de> {
+    entries: std::vec::IntoIter<(&'de str, Entry<'de>)>,
+    value: Option<Entry<'de>>,
+}
+
+impl<'de> EntriesAccess<'de> {
+    fn new(params: Vec<(&'de str, Value<'de>)>, nodes: Vec<&'de dyn Node>) -> Self {
+        let mut entries: Vec<_> = params
+            .into_iter()
+            .map(|(key, value)| (key, Entry::Param(value)))
+            .collect();
+        let mut groups: Vec<(&str, Vec<&dyn Node>)> = Vec::new();
+        for node in nodes {
+            match groups.iter_mut().find(|(name, _)| *name == node.name()) {
+                Some((_, group)) => group.push(node),
+                None => groups.push((node.name(), vec![node])),
+            }
+        }
+        entries.extend(
+            groups
+                .into_iter()
+                .map(|(name, group)| (name, Entry::Group(group))),
+        );
+        Self {
+            entries: entries.into_iter(),
+            value: None,
+        }
+    }
+}
+
+impl<'de> MapAccess<'de> for EntriesAccess<'de> {
+    type Error = DeserializeError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        let Some((key, value)) = self.entries.next() else {
+            return Ok(None);
+        };
+        self.value = Some(value);
+        seed.deserialize(BorrowedStrDeserializer::new(key))
+            .map(Some)
+    }
+
+    fn next_value_seed<T>(&mut self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        match self.value.take() {
+            Some(Entry::Param(value)) => seed.deserialize(ValueDeserializer::new(value)),
+            Some(Entry::Group(mut nodes)) if nodes.len() == 1 => {
+                seed.deserialize(NodeDeserializer::new(nodes.remove(0)))
+            }
+            Some(Entry::Group(nodes)) => seed.deserialize(GroupDeserializer { nodes }),
+            None => Err(DeserializeError::custom("value is missing")),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+/// Deserializes a [`Value`], borrowing strings from it where possible.
+#[derive(Clone, Debug)]
+pub struct ValueDeserializer<'de> {
+    value: Value<'de>,
+}
+
+impl<'de> ValueDeserializer<'de> {
+    #[must_use]
+    pub fn new(value: Value<'de>) -> Self {
+        Self { value }
+    }
+}
+
+impl<'de> Deserializer<'de> for ValueDeserializer<'de> {
+    type Error = DeserializeError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Value::U8(inner) => visitor.visit_u8(inner),
+            Value::U16(inner) => visitor.visit_u16(inner),
+            Value::U32(inner) => visitor.visit_u32(inner),
+            Value::U64(inner) | Value::Size(inner) | Value::Duration(inner) => {
+                visitor.visit_u64(inner)
+            }
+            Value::Uint(inner) => visitor.visit_u64(inner as u64),
+            Value::I8(inner) => visitor.visit_i8(inner),
+            Value::I16(inner) => visitor.visit_i16(inner),
+            Value::I32(inner) => visitor.visit_i32(inner),
+            Value::I64(inner) => visitor.visit_i64(inner),
+            Value::Int(inner) => visitor.visit_i64(inner as i64),
+            Value::F32(inner) => visitor.visit_f32(inner),
+            Value::F64(inner) => visitor.visit_f64(inner),
+            Value::Bool(inner) => visitor.visit_bool(inner),
+            Value::String(Cow::Borrowed(inner)) => visitor.visit_borrowed_str(inner),
+            Value::String(Cow::Owned(inner)) => visitor.visit_string(inner),
+            Value::List(inner) => visitor.visit_seq(SeqDeserializer::new(inner.into_iter())),
+            Value::Slice(inner) => {
+                visitor.visit_seq(SeqDeserializer::new(inner.iter().map(Value::borrowed)))
+            }
+            Value::Map(inner) => visitor.visit_map(MapDeserializer::new(inner.into_iter())),
+            Value::Null => visitor.visit_unit(),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if self.value.is_null() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Value::String(Cow::Borrowed(inner)) => visitor.visit_enum(inner.into_deserializer()),
+            Value::String(Cow::Owned(inner)) => visitor.visit_enum(inner.into_deserializer()),
+            other => Err(DeserializeError::custom(format_args!(
+                "expected a string for an enum variant, found `{}`",
+                other.kind()
+            ))),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, DeserializeError> for Value<'de> {
+    type Deserializer = ValueDeserializer<'de>;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        ValueDeserializer::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::{from_document, from_node};
+    use crate::{DocumentBuf, NodeBuf, Value};
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename_all = "lowercase")]
+    enum Level {
+        Debug,
+        Info,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Listen {
+        port: u16,
+        host: Option<String>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Server<'a> {
+        name: &'a str,
+        listen: Vec<Listen>,
+        aliases: Vec<String>,
+        tls: Option<Tls>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Tls(String, bool);
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Config<'a> {
+        #[serde(borrow)]
+        server: Server<'a>,
+        level: Level,
+        workers: u8,
+    }
+
+    fn node<const N: usize>(name: &str, args: [Value<'static>; N]) -> NodeBuf {
+        let mut node = NodeBuf::new(name);
+        node.args_mut().extend(args);
+        node
+    }
+
+    #[test]
+    fn test_from_document() {
+        let mut server = NodeBuf::new("server");
+        server.params_mut().insert("name", "main");
+        for port in [80_u16, 443] {
+            let mut listen = NodeBuf::new("listen");
+            listen.params_mut().insert("port", port);
+            server.children_mut().push(listen);
+        }
+        server
+            .children_mut()
+            .push(node("aliases", ["www".into(), "web".into()]));
+        let document = DocumentBuf::from(vec![
+            server,
+            node("level", ["info".into()]),
+            node("workers", [4_u8.into()]),
+        ]);
+
+        let config: Config<'_> = from_document(&document).unwrap();
+        assert_eq!(config.server.name, "main");
+        assert_eq!(config.server.listen.len(), 2);
+        assert_eq!(config.server.listen[1].port, 443);
+        assert_eq!(config.server.listen[1].host, None);
+        assert_eq!(config.server.aliases, ["www", "web"]);
+        assert_eq!(config.server.tls, None);
+        assert_eq!(config.level, Level::Info);
+        assert_eq!(config.workers, 4);
+    }
+
+    #[test]
+    fn test_from_node() {
+        let tls = node("tls", ["cert.pem".into(), true.into()]);
+        assert_eq!(
+            from_node::<Tls, _>(&tls).unwrap(),
+            Tls("cert.pem".to_owned(), true)
+        );
+
+        let mut listen = NodeBuf::new("listen");
+        listen.params_mut().insert("port", 80_u16);
+        let single: Vec<Listen> = from_node(&listen).unwrap();
+        assert_eq!(single.len(), 1);
+
+        let level = node("level", ["verbose".into()]);
+        assert!(from_node::<Level, _>(&level).is_err());
+        assert!(from_node::<Level, _>(&node("level", [])).is_err());
+        assert_eq!(
+            from_node::<Level, _>(&node("level", ["debug".into()])).unwrap(),
+            Level::Debug
+        );
+    }
+}
@@ -0,0 +1,76 @@
+//! Mapping between documents and types that implement `serde` traits.
+//!
+//! A document is deserialized like a map from node names to nodes. Nodes that
+//! share a name are grouped, and a group of several nodes is a sequence.
+//!
+//! A node is deserialized depending on the type that is asked for:
+//!
+//! - Structs and maps take their fields from the parameters and children of the
+//!   node, with children grouped by name like the nodes of a document.
+//! - Sequences and tuples take the arguments of the node. A node without
+//!   arguments is a sequence of just itself, so that a single node can fill a
+//!   `Vec` field.
+//! - Anything else, such as a number or a string, takes the only argument of
+//!   the node.
+//!
+//! Serializing follows the same rules in reverse, see [`SerializeConfig`] for
+//! the options.
+
+use std::error::Error;
+use std::fmt;
+
+pub mod de;
+pub mod ser;
+pub mod transcode;
+
+pub use de::*;
+pub use ser::*;
+pub use transcode::*;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeserializeError {
+    message: String,
+}
+
+impl fmt::Display for DeserializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl Error for DeserializeError {}
+
+impl serde::de::Error for DeserializeError {
+    fn custom<T>(message: T) -> Self
+    where
+        T: fmt::Display,
+    {
+        Self {
+            message: message.to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SerializeError {
+    message: String,
+}
+
+impl fmt::Display for SerializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl Error for SerializeError {}
+
+impl serde::ser::Error for SerializeError {
+    fn custom<T>(message: T) -> Self
+    where
+        T: fmt::Display,
+    {
+        Self {
+            message: message.to_string(),
+        }
+    }
+}
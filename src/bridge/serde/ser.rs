@@ -0,0 +1,716 @@
+use std::borrow::Cow;
+
+use serde::ser::{
+    Error as _, Impossible, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant,
+    SerializeTuple, SerializeTupleStruct, SerializeTupleVariant,
+};
+use serde::{Serialize, Serializer};
+
+use super::SerializeError;
+use crate::{DocumentBuf, NodeBuf, Value};
+
+/// Where the scalar fields of a struct are put on its node.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum FieldPlacement {
+    /// As parameters of the node.
+    #[default]
+    Params,
+    /// As child nodes, each with the value as its only argument.
+    Children,
+}
+
+/// Options for [`to_document_with`].
+///
+/// Whatever the placement of scalar fields, nested structs and maps become
+/// child nodes, as do sequences, which become a child for every item unless
+/// every item is a scalar, in which case they are the arguments of a single
+/// child.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SerializeConfig {
+    pub scalar_fields: FieldPlacement,
+    /// The names of fields that become arguments of their node instead, in the
+    /// order they are serialized. A sequence of scalars adds every item.
+    pub arg_fields: Vec<String>,
+}
+
+/// Serialize `value`, which must be a struct or a map, to a document with a
+/// node for every field, using the default [`SerializeConfig`].
+///
+/// # Errors
+/// Returns an error if `value` is not a struct or a map, or if it fails to
+/// serialize.
+pub fn to_document<T>(value: &T) -> Result<DocumentBuf, SerializeError>
+where
+    T: Serialize + ?Sized,
+{
+    to_document_with(value, &SerializeConfig::default())
+}
+
+/// Serialize `value` to a document, see [`to_document`].
+///
+/// # Errors
+/// Returns an error if `value` is not a struct or a map, or if it fails to
+/// serialize.
+pub fn to_document_with<T>(
+    value: &T,
+    config: &SerializeConfig,
+) -> Result<DocumentBuf, SerializeError>
+where
+    T: Serialize + ?Sized,
+{
+    match value.serialize(ContentSerializer)? {
+        Content::Map(entries) => Ok(entries
+            .into_iter()
+            .filter(|(_, content)| !content.is_null())
+            .flat_map(|(name, content)| config.nodes(&name, content))
+            .collect()),
+        _ => Err(SerializeError::custom(
+            "only structs and maps can be serialized to a document",
+        )),
+    }
+}
+
+/// Serialize `value` to a single node called `name`, using the default
+/// [`SerializeConfig`].
+///
+/// # Errors
+/// Returns an error if `value` is a sequence of structs or maps, which would
+/// be several nodes, or if it fails to serialize.
+pub fn to_node<T>(name: &str, value: &T) -> Result<NodeBuf, SerializeError>
+where
+    T: Serialize + ?Sized,
+{
+    let mut nodes = SerializeConfig::default().nodes(name, value.serialize(ContentSerializer)?);
+    match nodes.len() {
+        1 => Ok(nodes.remove(0)),
+        _ => Err(SerializeError::custom(format_args!(
+            "value of node `{name}` is not a single node"
+        ))),
+    }
+}
+
+/// A serialized value, before it is arranged into nodes.
+pub(super) enum Content {
+    Value(Value<'static>),
+    Seq(Vec<Content>),
+    Map(Vec<(String, Content)>),
+}
+
+impl Content {
+    fn is_null(&self) -> bool {
+        matches!(self, Self::Value(Value::Null))
+    }
+
+    fn scalars(items: &[Content]) -> bool {
+        items.iter().all(|item| matches!(item, Self::Value(_)))
+    }
+
+    /// The values of the scalar items, see [`Content::scalars`].
+    fn values(items: Vec<Content>) -> impl Iterator<Item = Value<'static>> {
+        items.into_iter().filter_map(|item| match item {
+            Self::Value(value) => Some(value),
+            _ => None,
+        })
+    }
+
+    fn variant(variant: &'static str, content: Content) -> Self {
+        Self::Map(vec![(variant.to_owned(), content)])
+    }
+
+    /// The content as a single value, with sequences as lists and maps as
+    /// maps.
+    pub(super) fn into_value(self) -> Value<'static> {
+        match self {
+            Self::Value(value) => value,
+            Self::Seq(items) => {
+                Value::List(Box::new(items.into_iter().map(Self::into_value).collect()))
+            }
+            Self::Map(entries) => Value::Map(Box::new(
+                entries
+                    .into_iter()
+                    .map(|(key, content)| (Cow::Owned(key), content.into_value()))
+                    .collect(),
+            )),
+        }
+    }
+}
+
+impl SerializeConfig {
+    fn nodes(&self, name: &str, content: Content) -> Vec<NodeBuf> {
+        let mut node = NodeBuf::new(name);
+        match content {
+            Content::Value(Value::Null) => {}
+            Content::Value(value) => node.args_mut().push(value),
+            Content::Seq(items) if Content::scalars(&items) => {
+                node.args_mut()
+                    .extend(items.into_iter().filter_map(|item| match item {
+                        Content::Value(value) => Some(value),
+                        _ => None,
+                    }));
+            }
+            Content::Seq(items) => {
+                return items
+                    .into_iter()
+                    .flat_map(|item| self.nodes(name, item))
+                    .collect();
+            }
+            Content::Map(entries) => {
+                for (key, content) in entries {
+                    self.entry(&mut node, key, content);
+                }
+            }
+        }
+        vec![node]
+    }
+
+    fn entry(&self, node: &mut NodeBuf, key: String, content: Content) {
+        let is_arg = self.arg_fields.contains(&key);
+        match content {
+            Content::Value(Value::Null) => {}
+            Content::Value(value) if is_arg => node.args_mut().push(value),
+            Content::Seq(items) if is_arg && Content::scalars(&items) => {
+                node.args_mut().extend(Content::values(items));
+            }
+            Content::Value(value) if self.scalar_fields == FieldPlacement::Params => {
+                node.params_mut().push(key, value);
+            }
+            content => node.children_mut().extend(self.nodes(&key, content)),
+        }
+    }
+}
+
+pub(super) struct ContentSerializer;
+
+macro_rules! serialize_value {
+    ($($method:ident: $ty:ty),+) => {
+        $(fn $method(self, value: $ty) -> Result<Self::Ok, Self::Error> {
+            Ok(Content::Value(Value::from(value)))
+        })+
+    };
+}
+
+impl Serializer for ContentSerializer {
+    type Ok = Content;
+    type Error = SerializeError;
+    type SerializeSeq = SeqBuilder;
+    type SerializeTuple = SeqBuilder;
+    type SerializeTupleStruct = SeqBuilder;
+    type SerializeTupleVariant = SeqBuilder;
+    type SerializeMap = MapBuilder;
+    type SerializeStruct = MapBuilder;
+    type SerializeStructVariant = MapBuilder;
+
+    serialize_value!(
+        serialize_bool: bool,
+        serialize_i8: i8,
+        serialize_i16: i16,
+        serialize_i32: i32,
+        serialize_i64: i64,
+        serialize_u8: u8,
+        serialize_u16: u16,
+        serialize_u32: u32,
+        serialize_u64: u64,
+        serialize_f32: f32,
+        serialize_f64: f64,
+        serialize_char: char
+    );
+
+    fn serialize_i128(self, value: i128) -> Result<Self::Ok, Self::Error> {
+        i64::try_from(value)
+            .map(|value| Content::Value(Value::I64(value)))
+            .map_err(SerializeError::custom)
+    }
+
+    fn serialize_u128(self, value: u128) -> Result<Self::Ok, Self::Error> {
+        u64::try_from(value)
+            .map(|value| Content::Value(Value::U64(value)))
+            .map_err(SerializeError::custom)
+    }
+
+    fn serialize_str(self, value: &str) -> Result<Self::Ok, Self::Error> {
+        Ok(Content::Value(Value::String(Cow::Owned(value.to_owned()))))
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<Self::Ok, Self::Error> {
+        Ok(Content::Value(Value::from(value)))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Ok(Content::Value(Value::Null))
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Ok(Content::Value(Value::Null))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        Ok(Content::variant(variant, value.serialize(self)?))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(SeqBuilder::new(None, len))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Ok(SeqBuilder::new(None, Some(len)))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Ok(SeqBuilder::new(None, Some(len)))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Ok(SeqBuilder::new(Some(variant), Some(len)))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(MapBuilder::new(None, len))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Ok(MapBuilder::new(None, Some(len)))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Ok(MapBuilder::new(Some(variant), Some(len)))
+    }
+}
+
+pub(super) struct SeqBuilder {
+    variant: Option<&'static str>,
+    items: Vec<Content>,
+}
+
+impl SeqBuilder {
+    fn new(variant: Option<&'static str>, len: Option<usize>) -> Self {
+        Self {
+            variant,
+            items: Vec::with_capacity(len.unwrap_or_default()),
+        }
+    }
+
+    fn push<T>(&mut self, value: &T) -> Result<(), SerializeError>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.items.push(value.serialize(ContentSerializer)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Content {
+        let content = Content::Seq(self.items);
+        match self.variant {
+            Some(variant) => Content::variant(variant, content),
+            None => content,
+        }
+    }
+}
+
+macro_rules! impl_seq_builder {
+    ($($trait:ident::$method:ident),+) => {
+        $(impl $trait for SeqBuilder {
+            type Ok = Content;
+            type Error = SerializeError;
+
+            fn $method<T>(&mut self, value: &T) -> Result<(), Self::Error>
+            where
+                T: Serialize + ?Sized,
+            {
+                self.push(value)
+            }
+
+            fn end(self) -> Result<Self::Ok, Self::Error> {
+                Ok(self.finish())
+            }
+        })+
+    };
+}
+
+impl_seq_builder!(
+    SerializeSeq::serialize_element,
+    SerializeTuple::serialize_element,
+    SerializeTupleStruct::serialize_field,
+    SerializeTupleVariant::serialize_field
+);
+
+pub(super) struct MapBuilder {
+    variant: Option<&'static str>,
+    entries: Vec<(String, Content)>,
+    key: Option<String>,
+}
+
+impl MapBuilder {
+    fn new(variant: Option<&'static str>, len: Option<usize>) -> Self {
+        Self {
+            variant,
+            entries: Vec::with_capacity(len.unwrap_or_default()),
+            key: None,
+        }
+    }
+
+    fn push<T>(&mut self, key: String, value: &T) -> Result<(), SerializeError>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.entries
+            .push((key, value.serialize(ContentSerializer)?));
+        Ok(())
+    }
+
+    fn finish(self) -> Content {
+        let content = Content::Map(self.entries);
+        match self.variant {
+            Some(variant) => Content::variant(variant, content),
+            None => content,
+        }
+    }
+}
+
+impl SerializeMap for MapBuilder {
+    type Ok = Content;
+    type Error = SerializeError;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.key = Some(key.serialize(KeySerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| SerializeError::custom("map value without a key"))?;
+        self.push(key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.finish())
+    }
+}
+
+macro_rules! impl_map_builder {
+    ($($trait:ident),+) => {
+        $(impl $trait for MapBuilder {
+            type Ok = Content;
+            type Error = SerializeError;
+
+            fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
+            where
+                T: Serialize + ?Sized,
+            {
+                self.push(key.to_owned(), value)
+            }
+
+            fn end(self) -> Result<Self::Ok, Self::Error> {
+                Ok(self.finish())
+            }
+        })+
+    };
+}
+
+impl_map_builder!(SerializeStruct, SerializeStructVariant);
+
+/// Serializes map keys, which become names of parameters and nodes.
+pub(super) struct KeySerializer;
+
+macro_rules! serialize_key {
+    ($($method:ident: $ty:ty),+) => {
+        $(fn $method(self, value: $ty) -> Result<Self::Ok, Self::Error> {
+            Ok(value.to_string())
+        })+
+    };
+}
+
+impl Serializer for KeySerializer {
+    type Ok = String;
+    type Error = SerializeError;
+    type SerializeSeq = Impossible<String, SerializeError>;
+    type SerializeTuple = Impossible<String, SerializeError>;
+    type SerializeTupleStruct = Impossible<String, SerializeError>;
+    type SerializeTupleVariant = Impossible<String, SerializeError>;
+    type SerializeMap = Impossible<String, SerializeError>;
+    type SerializeStruct = Impossible<String, SerializeError>;
+    type SerializeStructVariant = Impossible<String, SerializeError>;
+
+    serialize_key!(
+        serialize_bool: bool,
+        serialize_i8: i8,
+        serialize_i16: i16,
+        serialize_i32: i32,
+        serialize_i64: i64,
+        serialize_i128: i128,
+        serialize_u8: u8,
+        serialize_u16: u16,
+        serialize_u32: u32,
+        serialize_u64: u64,
+        serialize_u128: u128,
+        serialize_f32: f32,
+        serialize_f64: f64,
+        serialize_char: char,
+        serialize_str: &str
+    );
+
+    fn serialize_bytes(self, _value: &[u8]) -> Result<Self::Ok, Self::Error> {
+        Err(key_error())
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Err(key_error())
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Err(key_error())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+        Err(key_error())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        Ok(variant.to_owned())
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        Err(key_error())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Err(key_error())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Err(key_error())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Err(key_error())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(key_error())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Err(key_error())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Err(key_error())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(key_error())
+    }
+}
+
+fn key_error() -> SerializeError {
+    SerializeError::custom("map keys must be strings, numbers, or unit variants")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::{to_document, to_document_with, to_node, FieldPlacement, SerializeConfig};
+    use crate::bridge::serde::from_document;
+    use crate::{Node, Value};
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct Listen {
+        port: u16,
+        host: Option<String>,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct Server {
+        name: String,
+        listen: Vec<Listen>,
+        aliases: Vec<String>,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct Config {
+        server: Server,
+        workers: u8,
+    }
+
+    fn config() -> Config {
+        Config {
+            server: Server {
+                name: "main".to_owned(),
+                listen: vec![
+                    Listen {
+                        port: 80,
+                        host: None,
+                    },
+                    Listen {
+                        port: 443,
+                        host: Some("localhost".to_owned()),
+                    },
+                ],
+                aliases: vec!["www".to_owned(), "web".to_owned()],
+            },
+            workers: 4,
+        }
+    }
+
+    #[test]
+    fn test_to_document() {
+        let document = to_document(&config()).unwrap();
+        let server = &document.nodes_ref()[0];
+        assert_eq!(server.get_param("name"), Some(Value::from("main")));
+        assert_eq!(server.children().len(), 3);
+        assert_eq!(
+            server.children()[1].get_param("host"),
+            Some(Value::from("localhost"))
+        );
+        assert!(!server.children()[0].has_args());
+        assert_eq!(server.children()[2].args().len(), 2);
+        assert_eq!(document.nodes_ref()[1].get_arg(0), Some(Value::U8(4)));
+
+        let roundtrip: Config = from_document(&document).unwrap();
+        assert_eq!(roundtrip, config());
+    }
+
+    #[test]
+    fn test_to_document_config() {
+        let config = SerializeConfig {
+            scalar_fields: FieldPlacement::Children,
+            arg_fields: vec!["name".to_owned()],
+        };
+        let document = to_document_with(&self::config(), &config).unwrap();
+        let server = &document.nodes_ref()[0];
+        assert_eq!(server.get_arg(0), Some(Value::from("main")));
+        assert!(!server.has_params());
+        assert_eq!(
+            server.children()[0]
+                .first_child_named("port")
+                .unwrap()
+                .args(),
+            [Value::U16(80)]
+        );
+
+        assert!(to_document(&5_u8).is_err());
+        let node = to_node("listen", &self::config().server.listen[0]).unwrap();
+        assert_eq!(node.name(), "listen");
+        assert!(to_node("listen", &self::config().server.listen).is_err());
+    }
+}
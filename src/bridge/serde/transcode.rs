@@ -0,0 +1,888 @@
+//! A [`Deserializer`] over a stream of [`Event`]s and a [`Serializer`] into
+//! one, so that a document can be transcoded to and from other `serde`
+//! formats, such as with `serde_transcode`, without building the whole tree.
+//!
+//! Unlike the rest of the bridge, the events are not mapped onto the fields
+//! of a type but described as they are, so that any document can be
+//! converted and converted back:
+//!
+//! - A document is a map with an optional `version` string and a `nodes`
+//!   sequence.
+//! - A node is a map with a `name`, and a `namespace` if it has one, an `args`
+//!   sequence and a `params` map if they are not empty, and a `children`
+//!   sequence of nodes if it has any.
+//!
+//! The serializer writes a node as soon as its `children` start, if its name
+//! is known by then, so the entries of a node must come before its children
+//! for it to stream. Otherwise the children are kept until the end of the
+//! node. A `version` must come before the `nodes`.
+//!
+//! ```
+//! # use gpnd::bridge::serde::{EventDeserializer, EventSerializer};
+//! # use gpnd::emit::Emitter;
+//! # use gpnd::parser::Parser;
+//! # use serde::{Deserialize, Serialize};
+//! #[derive(Deserialize, Serialize)]
+//! struct Document {
+//!     nodes: Vec<Node>,
+//! }
+//!
+//! #[derive(Deserialize, Serialize)]
+//! struct Node {
+//!     name: String,
+//!     #[serde(default)]
+//!     children: Vec<Node>,
+//! }
+//!
+//! let events = Parser::new("server port=80 { tls }").events();
+//! let document = Document::deserialize(&mut EventDeserializer::new(events)).unwrap();
+//! assert_eq!(document.nodes[0].children[0].name, "tls");
+//!
+//! let mut emitter = Emitter::new(String::new());
+//! document
+//!     .serialize(EventSerializer::new(&mut emitter))
+//!     .unwrap();
+//! assert_eq!(emitter.into_inner(), "server {\n    tls\n}\n");
+//! ```
+
+use std::iter::Peekable;
+use std::{fmt, io, mem};
+
+use serde::de::value::{MapDeserializer, StringDeserializer};
+use serde::de::{DeserializeSeed, Deserializer, Error as _, MapAccess, SeqAccess, Visitor};
+use serde::ser::{Error as _, Impossible, SerializeMap, SerializeSeq, SerializeStruct};
+use serde::{forward_to_deserialize_any, Serialize, Serializer};
+
+use super::ser::{Content, ContentSerializer, KeySerializer};
+use super::{DeserializeError, SerializeError, ValueDeserializer};
+use crate::emit::{Emitter, IoEmitter};
+use crate::parser::{Event, ParseError};
+use crate::{Node, NodeBuf, Value};
+
+/// Deserializes a document from a stream of events, see the
+/// [module](self) documentation.
+///
+/// The stream is read as the document is visited, and a parse error in it
+/// is returned as a [`DeserializeError`].
+#[derive(Debug)]
+pub struct EventDeserializer<I>
+where
+    I: Iterator<Item = Result<Event, ParseError>>,
+{
+    events: Peekable<I>,
+}
+
+impl<I> EventDeserializer<I>
+where
+    I: Iterator<Item = Result<Event, ParseError>>,
+{
+    pub fn new(events: I) -> Self {
+        Self {
+            events: events.peekable(),
+        }
+    }
+
+    fn peek(&mut self) -> Result<Option<&Event>, DeserializeError> {
+        match self.events.peek() {
+            Some(Ok(event)) => Ok(Some(event)),
+            Some(Err(error)) => Err(DeserializeError::custom(error)),
+            None => Ok(None),
+        }
+    }
+
+    fn next(&mut self) -> Result<Option<Event>, DeserializeError> {
+        self.events
+            .next()
+            .transpose()
+            .map_err(DeserializeError::custom)
+    }
+}
+
+impl<'de, I> Deserializer<'de> for &mut EventDeserializer<I>
+where
+    I: Iterator<Item = Result<Event, ParseError>>,
+{
+    type Error = DeserializeError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(DocumentAccess {
+            de: self,
+            stage: 0,
+            version: None,
+        })
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// The entries of a document.
+struct DocumentAccess<'a, I>
+where
+    I: Iterator<Item = Result<Event, ParseError>>,
+{
+    de: &'a mut EventDeserializer<I>,
+    stage: u8,
+    version: Option<String>,
+}
+
+impl<'de, I> MapAccess<'de> for DocumentAccess<'_, I>
+where
+    I: Iterator<Item = Result<Event, ParseError>>,
+{
+    type Error = DeserializeError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        if self.stage == 0 {
+            if let Some(Event::Version(_)) = self.de.peek()? {
+                if let Some(Event::Version(version)) = self.de.next()? {
+                    self.version = Some(version);
+                }
+            }
+        }
+        let key = match self.stage {
+            0 if self.version.is_some() => "version",
+            0 | 1 => "nodes",
+            _ => return Ok(None),
+        };
+        self.stage = if key == "version" { 1 } else { 2 };
+        seed.deserialize(StringDeserializer::new(key.to_owned()))
+            .map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        match self.version.take() {
+            Some(version) => seed.deserialize(ValueDeserializer::new(Value::from(version))),
+            None => seed.deserialize(NodeSeq {
+                de: &mut *self.de,
+                nested: false,
+            }),
+        }
+    }
+}
+
+/// The nodes of a document, or the children of a node if `nested`, which
+/// end at the end of their parent.
+struct NodeSeq<'a, I>
+where
+    I: Iterator<Item = Result<Event, ParseError>>,
+{
+    de: &'a mut EventDeserializer<I>,
+    nested: bool,
+}
+
+impl<'de, I> Deserializer<'de> for NodeSeq<'_, I>
+where
+    I: Iterator<Item = Result<Event, ParseError>>,
+{
+    type Error = DeserializeError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(self)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+impl<'de, I> SeqAccess<'de> for NodeSeq<'_, I>
+where
+    I: Iterator<Item = Result<Event, ParseError>>,
+{
+    type Error = DeserializeError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        match self.de.peek()? {
+            Some(Event::Start(_)) => {}
+            Some(Event::End) if self.nested => return Ok(None),
+            None if !self.nested => return Ok(None),
+            Some(Event::End) => return Err(DeserializeError::custom("unexpected end of a node")),
+            Some(Event::Version(_)) => {
+                return Err(DeserializeError::custom("unexpected version after nodes"))
+            }
+            None => return Err(DeserializeError::custom("unexpected end of events")),
+        }
+        let Some(Event::Start(node)) = self.de.next()? else {
+            unreachable!("the next event is the start of a node");
+        };
+        seed.deserialize(NodeAccess {
+            de: &mut *self.de,
+            node: *node,
+            stage: 0,
+        })
+        .map(Some)
+    }
+}
+
+/// The entries of a node, with its children read from the stream.
+struct NodeAccess<'a, I>
+where
+    I: Iterator<Item = Result<Event, ParseError>>,
+{
+    de: &'a mut EventDeserializer<I>,
+    node: NodeBuf,
+    stage: u8,
+}
+
+impl<'de, I> Deserializer<'de> for NodeAccess<'_, I>
+where
+    I: Iterator<Item = Result<Event, ParseError>>,
+{
+    type Error = DeserializeError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(self)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+impl<'de, I> MapAccess<'de> for NodeAccess<'_, I>
+where
+    I: Iterator<Item = Result<Event, ParseError>>,
+{
+    type Error = DeserializeError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        let key = loop {
+            self.stage += 1;
+            match self.stage {
+                1 => break "name",
+                2 if self.node.namespace().is_some() => break "namespace",
+                3 if !self.node.args_ref().is_empty() => break "args",
+                4 if !self.node.params_ref().is_empty() => break "params",
+                5 if !matches!(self.de.peek()?, Some(Event::End)) => break "children",
+                2..=5 => {}
+                _ => {
+                    return match self.de.next()? {
+                        Some(Event::End) => Ok(None),
+                        _ => Err(DeserializeError::custom("expected the end of a node")),
+                    }
+                }
+            }
+        };
+        seed.deserialize(StringDeserializer::new(key.to_owned()))
+            .map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        match self.stage {
+            1 => seed.deserialize(ValueDeserializer::new(Value::from(
+                self.node.name().to_owned(),
+            ))),
+            2 => {
+                let namespace = self.node.namespace().unwrap_or_default().to_owned();
+                seed.deserialize(ValueDeserializer::new(Value::from(namespace)))
+            }
+            3 => {
+                let args = mem::take(self.node.args_mut()).into_vec();
+                seed.deserialize(ValueDeserializer::new(Value::List(Box::new(args))))
+            }
+            4 => {
+                let params = self
+                    .node
+                    .params_ref()
+                    .iter()
+                    .map(|(key, value)| (key.to_owned(), value.clone()))
+                    .collect();
+                seed.deserialize(ParamsDeserializer { params })
+            }
+            _ => seed.deserialize(NodeSeq {
+                de: &mut *self.de,
+                nested: true,
+            }),
+        }
+    }
+}
+
+/// The parameters of a node, in order and with any duplicates.
+struct ParamsDeserializer {
+    params: Vec<(String, Value<'static>)>,
+}
+
+impl<'de> Deserializer<'de> for ParamsDeserializer {
+    type Error = DeserializeError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let mut entries = MapDeserializer::new(self.params.into_iter());
+        let value = visitor.visit_map(&mut entries)?;
+        entries.end()?;
+        Ok(value)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// Where an [`EventSerializer`] sends its events.
+pub trait EventSink {
+    /// # Errors
+    /// Fails if the event cannot be written.
+    fn emit(&mut self, event: Event) -> Result<(), SerializeError>;
+}
+
+impl EventSink for Vec<Event> {
+    fn emit(&mut self, event: Event) -> Result<(), SerializeError> {
+        self.push(event);
+        Ok(())
+    }
+}
+
+impl<W> EventSink for Emitter<W>
+where
+    W: fmt::Write,
+{
+    fn emit(&mut self, event: Event) -> Result<(), SerializeError> {
+        self.emit_event(&event).map_err(SerializeError::custom)
+    }
+}
+
+impl<W> EventSink for IoEmitter<W>
+where
+    W: io::Write,
+{
+    fn emit(&mut self, event: Event) -> Result<(), SerializeError> {
+        self.emit_event(&event).map_err(SerializeError::custom)
+    }
+}
+
+impl<S> EventSink for &mut S
+where
+    S: EventSink + ?Sized,
+{
+    fn emit(&mut self, event: Event) -> Result<(), SerializeError> {
+        (**self).emit(event)
+    }
+}
+
+/// What an [`EventSerializer`] accepts.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Shape {
+    Document,
+    Nodes,
+    Node,
+}
+
+impl Shape {
+    fn expected(self) -> SerializeError {
+        SerializeError::custom(match self {
+            Self::Document => "expected a map for a document",
+            Self::Nodes => "expected a sequence of nodes",
+            Self::Node => "expected a map for a node",
+        })
+    }
+}
+
+/// Serializes a document into events, see the [module](self) documentation.
+#[derive(Debug)]
+pub struct EventSerializer<'a, S>
+where
+    S: EventSink + ?Sized,
+{
+    sink: &'a mut S,
+    shape: Shape,
+}
+
+impl<'a, S> EventSerializer<'a, S>
+where
+    S: EventSink + ?Sized,
+{
+    pub fn new(sink: &'a mut S) -> Self {
+        Self {
+            sink,
+            shape: Shape::Document,
+        }
+    }
+}
+
+macro_rules! reject {
+    ($($method:ident($($ty:ty),*) -> $ret:ty),+ $(,)?) => {
+        $(fn $method(self, $(_: $ty),*) -> Result<$ret, Self::Error> {
+            Err(self.shape.expected())
+        })+
+    };
+}
+
+impl<'a, S> Serializer for EventSerializer<'a, S>
+where
+    S: EventSink + ?Sized,
+{
+    type Ok = ();
+    type Error = SerializeError;
+    type SerializeSeq = NodeSeqSerializer<'a, S>;
+    type SerializeTuple = Impossible<(), SerializeError>;
+    type SerializeTupleStruct = Impossible<(), SerializeError>;
+    type SerializeTupleVariant = Impossible<(), SerializeError>;
+    type SerializeMap = EntrySerializer<'a, S>;
+    type SerializeStruct = EntrySerializer<'a, S>;
+    type SerializeStructVariant = Impossible<(), SerializeError>;
+
+    reject!(
+        serialize_bool(bool) -> (),
+        serialize_i8(i8) -> (),
+        serialize_i16(i16) -> (),
+        serialize_i32(i32) -> (),
+        serialize_i64(i64) -> (),
+        serialize_u8(u8) -> (),
+        serialize_u16(u16) -> (),
+        serialize_u32(u32) -> (),
+        serialize_u64(u64) -> (),
+        serialize_f32(f32) -> (),
+        serialize_f64(f64) -> (),
+        serialize_char(char) -> (),
+        serialize_str(&str) -> (),
+        serialize_bytes(&[u8]) -> (),
+        serialize_unit_struct(&'static str) -> (),
+        serialize_unit_variant(&'static str, u32, &'static str) -> (),
+        serialize_tuple(usize) -> Self::SerializeTuple,
+        serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct,
+        serialize_tuple_variant(&'static str, u32, &'static str, usize)
+            -> Self::SerializeTupleVariant,
+        serialize_struct_variant(&'static str, u32, &'static str, usize)
+            -> Self::SerializeStructVariant,
+    );
+
+    /// A missing sequence of nodes has no nodes.
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        self.serialize_unit()
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        match self.shape {
+            Shape::Nodes => Ok(()),
+            shape => Err(shape.expected()),
+        }
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        Err(self.shape.expected())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        match self.shape {
+            Shape::Nodes => Ok(NodeSeqSerializer { sink: self.sink }),
+            shape => Err(shape.expected()),
+        }
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        let node = match self.shape {
+            Shape::Document => None,
+            Shape::Node => Some(NodeEntries {
+                node: NodeBuf::new(""),
+                named: false,
+                children: Vec::new(),
+            }),
+            Shape::Nodes => return Err(self.shape.expected()),
+        };
+        Ok(EntrySerializer {
+            sink: self.sink,
+            key: String::new(),
+            node,
+            started: false,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        self.serialize_map(Some(len))
+    }
+}
+
+/// Serializes each node of a sequence.
+#[derive(Debug)]
+pub struct NodeSeqSerializer<'a, S>
+where
+    S: EventSink + ?Sized,
+{
+    sink: &'a mut S,
+}
+
+impl<S> SerializeSeq for NodeSeqSerializer<'_, S>
+where
+    S: EventSink + ?Sized,
+{
+    type Ok = ();
+    type Error = SerializeError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(EventSerializer {
+            sink: &mut *self.sink,
+            shape: Shape::Node,
+        })
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+}
+
+/// A node whose entries are being serialized.
+#[derive(Debug)]
+struct NodeEntries {
+    node: NodeBuf,
+    named: bool,
+    /// The events of children that came before the name of the node.
+    children: Vec<Event>,
+}
+
+/// Serializes the entries of a document, or of a node.
+#[derive(Debug)]
+pub struct EntrySerializer<'a, S>
+where
+    S: EventSink + ?Sized,
+{
+    sink: &'a mut S,
+    key: String,
+    /// The node, or `None` for the document.
+    node: Option<NodeEntries>,
+    /// Whether the nodes of the document, or the node itself, have been
+    /// written.
+    started: bool,
+}
+
+impl<S> EntrySerializer<'_, S>
+where
+    S: EventSink + ?Sized,
+{
+    fn document_entry<T>(&mut self, value: &T) -> Result<(), SerializeError>
+    where
+        T: Serialize + ?Sized,
+    {
+        match self.key.as_str() {
+            "version" if self.started => Err(SerializeError::custom("version after nodes")),
+            "version" => match value.serialize(ContentSerializer)? {
+                Content::Value(Value::Null) => Ok(()),
+                Content::Value(Value::String(version)) => {
+                    self.sink.emit(Event::Version(version.into_owned()))
+                }
+                _ => Err(SerializeError::custom("expected a string for the version")),
+            },
+            "nodes" => {
+                self.started = true;
+                value.serialize(EventSerializer {
+                    sink: &mut *self.sink,
+                    shape: Shape::Nodes,
+                })
+            }
+            key => Err(SerializeError::custom(format_args!(
+                "unknown document entry `{key}`"
+            ))),
+        }
+    }
+
+    fn node_entry<T>(&mut self, value: &T) -> Result<(), SerializeError>
+    where
+        T: Serialize + ?Sized,
+    {
+        let Some(entries) = &mut self.node else {
+            return self.document_entry(value);
+        };
+        if self.key != "children" && self.started {
+            return Err(SerializeError::custom(format_args!(
+                "node entry `{}` after its children",
+                self.key
+            )));
+        }
+        if self.key == "children" {
+            if !entries.named {
+                return value.serialize(EventSerializer {
+                    sink: &mut entries.children,
+                    shape: Shape::Nodes,
+                });
+            }
+            if !mem::replace(&mut self.started, true) {
+                let node = mem::replace(&mut entries.node, NodeBuf::new(""));
+                self.sink.emit(Event::Start(Box::new(node)))?;
+                for event in mem::take(&mut entries.children) {
+                    self.sink.emit(event)?;
+                }
+            }
+            return value.serialize(EventSerializer {
+                sink: &mut *self.sink,
+                shape: Shape::Nodes,
+            });
+        }
+        let node = &mut entries.node;
+        match (self.key.as_str(), value.serialize(ContentSerializer)?) {
+            ("name", Content::Value(Value::String(name))) => {
+                node.set_name(name);
+                entries.named = true;
+            }
+            ("namespace", Content::Value(Value::String(namespace))) => {
+                node.set_namespace(Some(namespace));
+            }
+            ("args", Content::Seq(items)) => {
+                node.args_mut()
+                    .extend(items.into_iter().map(Content::into_value));
+            }
+            ("params", Content::Map(params)) => {
+                for (key, content) in params {
+                    node.params_mut().push(key, content.into_value());
+                }
+            }
+            ("namespace" | "args" | "params", Content::Value(Value::Null)) => {}
+            (key @ ("name" | "namespace" | "args" | "params"), _) => {
+                return Err(SerializeError::custom(format_args!(
+                    "unexpected value for node entry `{key}`"
+                )))
+            }
+            (key, _) => {
+                return Err(SerializeError::custom(format_args!(
+                    "unknown node entry `{key}`"
+                )))
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<S> SerializeMap for EntrySerializer<'_, S>
+where
+    S: EventSink + ?Sized,
+{
+    type Ok = ();
+    type Error = SerializeError;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.key = key.serialize(KeySerializer)?;
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.node_entry(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        let Some(entries) = self.node else {
+            return Ok(());
+        };
+        if !entries.named {
+            return Err(SerializeError::custom("node without a name"));
+        }
+        if !self.started {
+            self.sink.emit(Event::Start(Box::new(entries.node)))?;
+        }
+        for event in entries.children {
+            self.sink.emit(event)?;
+        }
+        self.sink.emit(Event::End)
+    }
+}
+
+impl<S> SerializeStruct for EntrySerializer<'_, S>
+where
+    S: EventSink + ?Sized,
+{
+    type Ok = ();
+    type Error = SerializeError;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        key.clone_into(&mut self.key);
+        self.node_entry(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        SerializeMap::end(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::parser::{parse, Parser};
+    use crate::Node as _;
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct Document {
+        version: Option<String>,
+        nodes: Vec<Node>,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct Node {
+        name: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        namespace: Option<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        args: Vec<u16>,
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        params: BTreeMap<String, bool>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        children: Vec<Node>,
+    }
+
+    #[derive(Serialize)]
+    #[serde(untagged)]
+    enum Entry {
+        Str(&'static str),
+        Nodes(Vec<BTreeMap<&'static str, Entry>>),
+    }
+
+    #[test]
+    fn test_transcode() {
+        let source =
+            "@version \"1\"\napp:server 80 443 tls=true {\n    route { index }\n}\nclient\n";
+        let events = Parser::new(source).events();
+        let document = Document::deserialize(&mut EventDeserializer::new(events)).unwrap();
+        assert_eq!(document.version.as_deref(), Some("1"));
+        let server = &document.nodes[0];
+        assert_eq!(server.namespace.as_deref(), Some("app"));
+        assert_eq!(server.args, [80, 443]);
+        assert_eq!(server.children[0].children[0].name, "index");
+        assert_eq!(document.nodes[1].name, "client");
+
+        let mut emitter = Emitter::new(String::new());
+        document
+            .serialize(EventSerializer::new(&mut emitter))
+            .unwrap();
+        assert_eq!(
+            parse(&emitter.into_inner()).unwrap(),
+            parse(source).unwrap()
+        );
+
+        // Children that come before the name of their node are kept until
+        // the end of it.
+        let node = |name, children| {
+            BTreeMap::from([
+                ("children", Entry::Nodes(children)),
+                ("name", Entry::Str(name)),
+            ])
+        };
+        let document = BTreeMap::from([(
+            "nodes",
+            Entry::Nodes(vec![node("a", vec![node("b", Vec::new())])]),
+        )]);
+        let mut events = Vec::new();
+        document
+            .serialize(EventSerializer::new(&mut events))
+            .unwrap();
+        let names: Vec<_> = events
+            .iter()
+            .map(|event| match event {
+                Event::Start(node) => node.name(),
+                _ => "",
+            })
+            .collect();
+        assert_eq!(names, ["a", "b", "", ""]);
+        let document = BTreeMap::from([
+            ("nodes", Entry::Nodes(Vec::new())),
+            ("version", Entry::Str("1")),
+        ]);
+        let error = document
+            .serialize(EventSerializer::new(&mut Vec::new()))
+            .unwrap_err();
+        assert_eq!(error.to_string(), "version after nodes");
+
+        let error = Document::deserialize(&mut EventDeserializer::new(Parser::new("a {").events()))
+            .unwrap_err();
+        assert!(error.to_string().contains("expected"));
+    }
+}
@@ -0,0 +1,339 @@
+//! Conversions between documents and [`toml::Table`], and between [`Value`]
+//! and [`toml::Value`].
+//!
+//! A document becomes a table with a key for each node name, in order. Nodes
+//! are converted as follows:
+//!
+//! - A node with only parameters and children becomes a table, with a key for
+//!   each parameter, and a key for each name among its children.
+//! - A node that shares its name with its siblings becomes an element of an
+//!   array of tables.
+//! - A node with only arguments becomes its argument, or an array of them.
+//! - A node with arguments and also parameters or children keeps the arguments
+//!   under the [`ARGS_KEY`] of its table.
+//!
+//! Converting a table back makes a node of each key, where tables are nodes
+//! with children and arrays of tables are repeated nodes. Within a table,
+//! other values are parameters, and at the top level, other values are the
+//! arguments of a node. TOML dates and times become strings.
+
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+
+use toml::{Table, Value as TomlValue};
+
+use crate::{Document, DocumentBuf, Node, NodeBuf, Value};
+
+/// The key that holds the arguments of a node that is converted to a table.
+pub const ARGS_KEY: &str = "args";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ToTomlError {
+    /// TOML has no null value.
+    Null,
+    /// TOML integers are 64-bit signed integers.
+    OutOfRange(u64),
+    /// Two entries of the same table would have the same key, such as a
+    /// parameter and a child with the same name.
+    DuplicateKey(String),
+}
+
+impl fmt::Display for ToTomlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Null => f.write_str("cannot convert `null` to TOML"),
+            Self::OutOfRange(value) => write!(f, "integer `{value}` is out of range for TOML"),
+            Self::DuplicateKey(key) => write!(f, "duplicate TOML key `{key}`"),
+        }
+    }
+}
+
+impl Error for ToTomlError {}
+
+impl From<TomlValue> for Value<'static> {
+    fn from(other: TomlValue) -> Self {
+        match other {
+            TomlValue::String(inner) => Self::String(Cow::Owned(inner)),
+            TomlValue::Integer(inner) => Self::I64(inner),
+            TomlValue::Float(inner) => Self::F64(inner),
+            TomlValue::Boolean(inner) => Self::Bool(inner),
+            TomlValue::Datetime(inner) => Self::String(Cow::Owned(inner.to_string())),
+            TomlValue::Array(inner) => inner.into_iter().collect(),
+            TomlValue::Table(inner) => Self::Map(Box::new(
+                inner
+                    .into_iter()
+                    .map(|(key, value)| (Cow::Owned(key), value.into()))
+                    .collect(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<Value<'_>> for TomlValue {
+    type Error = ToTomlError;
+
+    fn try_from(other: Value<'_>) -> Result<Self, Self::Error> {
+        let unsigned = |value: u64| {
+            i64::try_from(value)
+                .map(Self::Integer)
+                .map_err(|_| ToTomlError::OutOfRange(value))
+        };
+        Ok(match other {
+            Value::U8(inner) => Self::Integer(inner.into()),
+            Value::U16(inner) => Self::Integer(inner.into()),
+            Value::U32(inner) => Self::Integer(inner.into()),
+            Value::U64(inner) | Value::Size(inner) | Value::Duration(inner) => unsigned(inner)?,
+            Value::Uint(inner) => unsigned(inner as u64)?,
+            Value::I8(inner) => Self::Integer(inner.into()),
+            Value::I16(inner) => Self::Integer(inner.into()),
+            Value::I32(inner) => Self::Integer(inner.into()),
+            Value::I64(inner) => Self::Integer(inner),
+            Value::Int(inner) => Self::Integer(inner as i64),
+            Value::F32(inner) => Self::Float(inner.into()),
+            Value::F64(inner) => Self::Float(inner),
+            Value::Bool(inner) => Self::Boolean(inner),
+            Value::String(inner) => Self::String(inner.into_owned()),
+            Value::List(inner) => Self::Array(
+                inner
+                    .into_iter()
+                    .map(TryInto::try_into)
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::Slice(inner) => Self::Array(
+                inner
+                    .iter()
+                    .cloned()
+                    .map(TryInto::try_into)
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::Map(inner) => Self::Table(
+                inner
+                    .into_iter()
+                    .map(|(key, value)| Ok((key.into_owned(), value.try_into()?)))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::Null => return Err(ToTomlError::Null),
+        })
+    }
+}
+
+/// The key of a node, including its namespace.
+fn node_key(node: &dyn Node) -> String {
+    match node.namespace() {
+        Some(namespace) => format!("{namespace}:{}", node.name()),
+        None => node.name().to_owned(),
+    }
+}
+
+fn insert(table: &mut Table, key: String, value: TomlValue) -> Result<(), ToTomlError> {
+    if table.contains_key(&key) {
+        return Err(ToTomlError::DuplicateKey(key));
+    }
+    table.insert(key, value);
+    Ok(())
+}
+
+/// Add `nodes` to `table`, grouping the nodes that share a name.
+fn insert_nodes(table: &mut Table, nodes: Vec<&dyn Node>) -> Result<(), ToTomlError> {
+    let mut groups: Vec<(String, Vec<&dyn Node>)> = Vec::new();
+    for node in nodes {
+        let key = node_key(node);
+        match groups.iter_mut().find(|(other, _)| *other == key) {
+            Some((_, group)) => group.push(node),
+            None => groups.push((key, vec![node])),
+        }
+    }
+    for (key, group) in groups {
+        let value = match group.as_slice() {
+            [node] => node_to_toml(*node)?,
+            _ => TomlValue::Array(
+                group
+                    .into_iter()
+                    .map(node_to_toml)
+                    .collect::<Result<_, _>>()?,
+            ),
+        };
+        insert(table, key, value)?;
+    }
+    Ok(())
+}
+
+fn node_to_toml(node: &dyn Node) -> Result<TomlValue, ToTomlError> {
+    let mut args = node
+        .args_iter()
+        .map(TryInto::try_into)
+        .collect::<Result<Vec<TomlValue>, _>>()?;
+    let (params, children) = (node.params(), node.children());
+    if params.is_empty() && children.is_empty() {
+        return Ok(match args.len() {
+            0 => TomlValue::Table(Table::new()),
+            1 => args.remove(0),
+            _ => TomlValue::Array(args),
+        });
+    }
+
+    let mut table = Table::new();
+    if !args.is_empty() {
+        table.insert(ARGS_KEY.to_owned(), TomlValue::Array(args));
+    }
+    for (key, value) in params {
+        insert(&mut table, key.to_owned(), value.try_into()?)?;
+    }
+    insert_nodes(&mut table, children)?;
+    Ok(TomlValue::Table(table))
+}
+
+/// Convert a document to a TOML table, see the [module](self) documentation.
+///
+/// ```
+/// # use gpnd::bridge::toml::to_toml;
+/// let document = gpnd::parser::parse(
+///     "package name=\"gpnd\" edition=\"2021\"\nbin name=\"a\"\nbin name=\"b\"",
+/// )
+/// .unwrap();
+/// let table = to_toml(&document).unwrap();
+/// assert_eq!(
+///     table.to_string(),
+///     "[package]\nname = \"gpnd\"\nedition = \"2021\"\n\n[[bin]]\nname = \"a\"\n\n[[bin]]\nname \
+///      = \"b\"\n"
+/// );
+/// ```
+///
+/// # Errors
+/// Fails if a value cannot be represented in TOML, or if a parameter and a
+/// child of a node have the same name.
+pub fn to_toml<D>(document: &D) -> Result<Table, ToTomlError>
+where
+    D: Document + ?Sized,
+{
+    let mut table = Table::new();
+    insert_nodes(&mut table, document.nodes())?;
+    Ok(table)
+}
+
+/// Make a node called `key`, splitting off a namespace.
+fn new_node(key: &str) -> NodeBuf {
+    match key.split_once(':') {
+        Some((namespace, name)) => {
+            let mut node = NodeBuf::new(name);
+            node.set_namespace(Some(namespace));
+            node
+        }
+        None => NodeBuf::new(key),
+    }
+}
+
+/// Whether a value is a non-empty array of tables, which are repeated nodes.
+fn is_table_array(value: &TomlValue) -> bool {
+    matches!(value, TomlValue::Array(items) if !items.is_empty() && items.iter().all(TomlValue::is_table))
+}
+
+fn table_to_node(key: &str, table: Table) -> NodeBuf {
+    let mut node = new_node(key);
+    for (key, value) in table {
+        match value {
+            TomlValue::Array(items) if key == ARGS_KEY => {
+                node.args_mut().extend(items.into_iter().map(Value::from));
+            }
+            TomlValue::Table(table) => node.children_mut().push(table_to_node(&key, table)),
+            value if is_table_array(&value) => {
+                node.children_mut().extend(toml_to_nodes(&key, value));
+            }
+            value => node.params_mut().push(key, Value::from(value)),
+        }
+    }
+    node
+}
+
+/// The nodes for a top-level `key`.
+fn toml_to_nodes(key: &str, value: TomlValue) -> Vec<NodeBuf> {
+    match value {
+        TomlValue::Table(table) => vec![table_to_node(key, table)],
+        TomlValue::Array(items) if items.iter().all(TomlValue::is_table) && !items.is_empty() => {
+            items
+                .into_iter()
+                .flat_map(|item| toml_to_nodes(key, item))
+                .collect()
+        }
+        TomlValue::Array(items) => {
+            let mut node = new_node(key);
+            node.args_mut().extend(items.into_iter().map(Value::from));
+            vec![node]
+        }
+        value => {
+            let mut node = new_node(key);
+            node.args_mut().push(value.into());
+            vec![node]
+        }
+    }
+}
+
+/// Convert a TOML table to a document, see the [module](self)
+/// documentation.
+///
+/// ```
+/// # use gpnd::bridge::toml::from_toml;
+/// # use gpnd::{Document, Node};
+/// let table: toml::Table = "[package]\nname = \"gpnd\"\n[[bin]]\nname = \"a\"\n[[bin]]\nname = \
+///                           \"b\""
+///     .parse()
+///     .unwrap();
+/// let document = from_toml(table);
+/// assert_eq!(document.nodes_named("bin").count(), 2);
+/// assert_eq!(document.nodes()[0].get_param("name").unwrap(), "gpnd");
+/// ```
+#[must_use]
+pub fn from_toml(table: Table) -> DocumentBuf {
+    let mut document = DocumentBuf::new();
+    for (key, value) in table {
+        document.nodes_mut().extend(toml_to_nodes(&key, value));
+    }
+    document
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_toml_roundtrip() {
+        let document = parse(
+            r#"
+            server host="localhost" port=8080 {
+                tls enabled=true
+                route path="/"
+                route path="/api"
+            }
+            "#,
+        )
+        .unwrap();
+        let table = to_toml(&document).unwrap();
+        assert_eq!(
+            table.to_string(),
+            concat!(
+                "[server]\nhost = \"localhost\"\nport = 8080\n\n",
+                "[server.tls]\nenabled = true\n\n",
+                "[[server.route]]\npath = \"/\"\n\n",
+                "[[server.route]]\npath = \"/api\"\n",
+            )
+        );
+        assert_eq!(from_toml(table), document);
+    }
+
+    #[test]
+    fn test_toml_args() {
+        let document = parse("name \"gpnd\"\nlist 1 2\nmixed 1 key=2 { child }").unwrap();
+        let table = to_toml(&document).unwrap();
+        assert_eq!(table["name"].as_str(), Some("gpnd"));
+        assert_eq!(table["mixed"][ARGS_KEY].as_array().unwrap().len(), 1);
+        assert_eq!(from_toml(table), document);
+
+        assert_eq!(to_toml(&parse("a null").unwrap()), Err(ToTomlError::Null));
+        assert_eq!(
+            to_toml(&parse("a key=1 { key }").unwrap()),
+            Err(ToTomlError::DuplicateKey("key".to_owned()))
+        );
+    }
+}
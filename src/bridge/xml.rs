@@ -0,0 +1,478 @@
+//! Conversions between documents and XML.
+//!
+//! A document becomes a [`ROOT_ELEMENT`] with an element for each node, and
+//! nodes are converted as follows:
+//!
+//! - The name of the node is the name of the element, with the namespace as its
+//!   prefix, such as `ns:name`.
+//! - Parameters are attributes. Strings are written as they are, and other
+//!   values as they would appear in a document. Only the value that lookups
+//!   would find is kept for repeated parameters.
+//! - A single string argument of a node without children is the text of the
+//!   element. Otherwise, arguments are [`ARG_ELEMENT`]s, before the children,
+//!   with the variant of the value in their `type` attribute, so that they can
+//!   be read back exactly.
+//! - Children are child elements.
+//!
+//! Reading XML reverses this, except that attributes are always read as
+//! strings, and text is read as a string argument. Declarations, comments,
+//! and processing instructions are skipped, and the root element can have any
+//! name.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::{self, Write};
+use std::str::FromStr;
+
+use quick_xml::escape::escape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+use crate::{Document, DocumentBuf, Node, NodeBuf, Value};
+
+/// The name of the element that holds the nodes of a document.
+pub const ROOT_ELEMENT: &str = "document";
+
+/// The name of the elements that hold arguments, and the items of lists and
+/// maps.
+pub const ARG_ELEMENT: &str = "arg";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ToXmlError {
+    name: String,
+}
+
+impl fmt::Display for ToXmlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` is not a valid XML name", self.name)
+    }
+}
+
+impl Error for ToXmlError {}
+
+#[derive(Debug)]
+pub enum FromXmlError {
+    Xml(quick_xml::Error),
+    /// There was no root element.
+    MissingRoot,
+    /// The `type` attribute of an argument is not the name of a variant.
+    UnknownType(String),
+    /// The text of an argument cannot be read as its `type`.
+    InvalidValue {
+        kind: String,
+        text: String,
+    },
+}
+
+impl fmt::Display for FromXmlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Xml(error) => write!(f, "invalid XML: {error}"),
+            Self::MissingRoot => f.write_str("missing root element"),
+            Self::UnknownType(kind) => write!(f, "unknown argument type `{kind}`"),
+            Self::InvalidValue { kind, text } => {
+                write!(f, "invalid `{kind}` argument `{text}`")
+            }
+        }
+    }
+}
+
+impl Error for FromXmlError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Xml(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<quick_xml::Error> for FromXmlError {
+    fn from(other: quick_xml::Error) -> Self {
+        Self::Xml(other)
+    }
+}
+
+impl From<quick_xml::events::attributes::AttrError> for FromXmlError {
+    fn from(other: quick_xml::events::attributes::AttrError) -> Self {
+        Self::Xml(other.into())
+    }
+}
+
+/// Whether `name` is a valid XML name.
+fn is_xml_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|ch| ch.is_alphabetic() || matches!(ch, '_' | ':'))
+        && chars.all(|ch| ch.is_alphanumeric() || matches!(ch, '_' | ':' | '-' | '.'))
+}
+
+fn xml_name(name: String) -> Result<String, ToXmlError> {
+    if is_xml_name(&name) {
+        Ok(name)
+    } else {
+        Err(ToXmlError { name })
+    }
+}
+
+fn indent(out: &mut String, depth: usize) {
+    out.extend(std::iter::repeat_n("  ", depth));
+}
+
+/// The text of a scalar value, or of a parameter.
+fn value_text(value: &Value<'_>) -> String {
+    match value {
+        Value::String(inner) => inner.to_string(),
+        Value::F32(inner) => inner.to_string(),
+        Value::F64(inner) => inner.to_string(),
+        Value::Size(inner) | Value::Duration(inner) => inner.to_string(),
+        Value::Null => String::new(),
+        value => value.to_string(),
+    }
+}
+
+fn write_arg(out: &mut String, value: &Value<'_>, key: Option<&str>, depth: usize) {
+    let kind = match value {
+        Value::Slice(_) => "List",
+        value => value.kind().as_str(),
+    };
+    indent(out, depth);
+    let _ = write!(out, "<{ARG_ELEMENT} type=\"{kind}\"");
+    if let Some(key) = key {
+        let _ = write!(out, " key=\"{}\"", escape(key));
+    }
+    match value {
+        Value::Null => out.push_str("/>\n"),
+        Value::List(_) | Value::Slice(_) | Value::Map(_) => {
+            out.push_str(">\n");
+            if let Value::Map(items) = value {
+                for (key, item) in items.iter() {
+                    write_arg(out, item, Some(key), depth + 1);
+                }
+            } else {
+                for item in value.items().into_iter().flatten() {
+                    write_arg(out, item, None, depth + 1);
+                }
+            }
+            indent(out, depth);
+            let _ = writeln!(out, "</{ARG_ELEMENT}>");
+        }
+        value => {
+            let _ = writeln!(out, ">{}</{ARG_ELEMENT}>", escape(value_text(value)));
+        }
+    }
+}
+
+fn write_node(out: &mut String, node: &dyn Node, depth: usize) -> Result<(), ToXmlError> {
+    let name = xml_name(match node.namespace() {
+        Some(namespace) => format!("{namespace}:{}", node.name()),
+        None => node.name().to_owned(),
+    })?;
+    indent(out, depth);
+    out.push('<');
+    out.push_str(&name);
+    for (key, value) in node.params() {
+        let key = xml_name(key.to_owned())?;
+        let _ = write!(out, " {key}=\"{}\"", escape(value_text(&value)));
+    }
+
+    let args: Vec<_> = node.args_iter().collect();
+    let children = node.children();
+    match args.as_slice() {
+        [Value::String(text)] if children.is_empty() => {
+            let _ = writeln!(out, ">{}</{name}>", escape(text.as_ref()));
+            return Ok(());
+        }
+        [] if children.is_empty() => {
+            out.push_str("/>\n");
+            return Ok(());
+        }
+        _ => out.push_str(">\n"),
+    }
+    for arg in &args {
+        write_arg(out, arg, None, depth + 1);
+    }
+    for child in children {
+        write_node(out, child, depth + 1)?;
+    }
+    indent(out, depth);
+    let _ = writeln!(out, "</{name}>");
+    Ok(())
+}
+
+/// Convert a document to XML, see the [module](self) documentation.
+///
+/// ```
+/// # use gpnd::bridge::xml::to_xml;
+/// let document = gpnd::parser::parse("server port=80 { name \"a & b\"; weight 1.5 }").unwrap();
+/// assert_eq!(
+///     to_xml(&document).unwrap(),
+///     concat!(
+///         "<document>\n",
+///         "  <server port=\"80\">\n",
+///         "    <name>a &amp; b</name>\n",
+///         "    <weight>\n",
+///         "      <arg type=\"F64\">1.5</arg>\n",
+///         "    </weight>\n",
+///         "  </server>\n",
+///         "</document>\n",
+///     )
+/// );
+/// ```
+///
+/// # Errors
+/// Fails if the name of a node or parameter is not a valid XML name.
+pub fn to_xml<D>(document: &D) -> Result<String, ToXmlError>
+where
+    D: Document + ?Sized,
+{
+    let mut out = String::new();
+    out.push('<');
+    out.push_str(ROOT_ELEMENT);
+    if let Some(version) = document.version() {
+        let _ = write!(out, " version=\"{}\"", escape(version));
+    }
+    out.push_str(">\n");
+    for node in document.nodes() {
+        write_node(&mut out, node, 1)?;
+    }
+    let _ = writeln!(out, "</{ROOT_ELEMENT}>");
+    Ok(out)
+}
+
+/// An element that is being read.
+enum Frame {
+    Root(DocumentBuf),
+    Node(Box<NodeBuf>),
+    Arg {
+        kind: String,
+        key: Option<String>,
+        text: String,
+        items: Vec<(Option<String>, Value<'static>)>,
+    },
+}
+
+fn parse_text<T>(kind: &str, text: &str) -> Result<T, FromXmlError>
+where
+    T: FromStr,
+{
+    text.parse().map_err(|_| FromXmlError::InvalidValue {
+        kind: kind.to_owned(),
+        text: text.to_owned(),
+    })
+}
+
+fn arg_value(
+    kind: &str,
+    text: String,
+    items: Vec<(Option<String>, Value<'static>)>,
+) -> Result<Value<'static>, FromXmlError> {
+    Ok(match kind {
+        "U8" => Value::U8(parse_text(kind, &text)?),
+        "U16" => Value::U16(parse_text(kind, &text)?),
+        "U32" => Value::U32(parse_text(kind, &text)?),
+        "U64" => Value::U64(parse_text(kind, &text)?),
+        "Uint" => Value::Uint(parse_text(kind, &text)?),
+        "I8" => Value::I8(parse_text(kind, &text)?),
+        "I16" => Value::I16(parse_text(kind, &text)?),
+        "I32" => Value::I32(parse_text(kind, &text)?),
+        "I64" => Value::I64(parse_text(kind, &text)?),
+        "Int" => Value::Int(parse_text(kind, &text)?),
+        "F32" => Value::F32(parse_text(kind, &text)?),
+        "F64" => Value::F64(parse_text(kind, &text)?),
+        "Bool" => Value::Bool(parse_text(kind, &text)?),
+        "Size" => Value::Size(parse_text(kind, &text)?),
+        "Duration" => Value::Duration(parse_text(kind, &text)?),
+        "String" => Value::String(Cow::Owned(text)),
+        "List" => items.into_iter().map(|(_, item)| item).collect(),
+        "Map" => Value::Map(Box::new(
+            items
+                .into_iter()
+                .map(|(key, item)| (Cow::Owned(key.unwrap_or_default()), item))
+                .collect::<BTreeMap<_, _>>(),
+        )),
+        "Null" => Value::Null,
+        kind => return Err(FromXmlError::UnknownType(kind.to_owned())),
+    })
+}
+
+/// The frame for an element that starts under `parent`.
+fn open(parent: Option<&Frame>, element: &BytesStart<'_>) -> Result<Frame, FromXmlError> {
+    let name = String::from_utf8_lossy(element.name().as_ref()).into_owned();
+    let mut attributes = Vec::new();
+    for attribute in element.attributes() {
+        let attribute = attribute?;
+        let key = String::from_utf8_lossy(attribute.key.as_ref()).into_owned();
+        attributes.push((key, attribute.unescape_value()?.into_owned()));
+    }
+
+    Ok(match parent {
+        None => {
+            let mut document = DocumentBuf::new();
+            let version = attributes.into_iter().find(|(key, _)| key == "version");
+            document.set_version(version.map(|(_, version)| version));
+            Frame::Root(document)
+        }
+        Some(Frame::Node(_) | Frame::Arg { .. }) if name == ARG_ELEMENT => {
+            let mut kind = "String".to_owned();
+            let mut key = None;
+            for (name, value) in attributes {
+                match name.as_str() {
+                    "type" => kind = value,
+                    "key" => key = Some(value),
+                    _ => {}
+                }
+            }
+            Frame::Arg {
+                kind,
+                key,
+                text: String::new(),
+                items: Vec::new(),
+            }
+        }
+        Some(_) => {
+            let mut node = match name.split_once(':') {
+                Some((namespace, name)) => {
+                    let mut node = NodeBuf::new(name);
+                    node.set_namespace(Some(namespace));
+                    node
+                }
+                None => NodeBuf::new(name),
+            };
+            for (key, value) in attributes {
+                node.params_mut().push(key, value);
+            }
+            Frame::Node(Box::new(node))
+        }
+    })
+}
+
+/// Finish the top element, adding it to its parent, or returning the
+/// document if it is the root.
+fn close(stack: &mut Vec<Frame>) -> Result<Option<DocumentBuf>, FromXmlError> {
+    let Some(frame) = stack.pop() else {
+        return Ok(None);
+    };
+    match (frame, stack.last_mut()) {
+        (Frame::Root(document), _) => return Ok(Some(document)),
+        (Frame::Node(node), Some(Frame::Root(document))) => document.nodes_mut().push(*node),
+        (Frame::Node(node), Some(Frame::Node(parent))) => parent.children_mut().push(*node),
+        (
+            Frame::Arg {
+                kind,
+                key,
+                text,
+                items,
+            },
+            Some(parent),
+        ) => {
+            let value = arg_value(&kind, text, items)?;
+            match parent {
+                Frame::Node(node) => node.args_mut().push(value),
+                Frame::Arg { items, .. } => items.push((key, value)),
+                Frame::Root(_) => {}
+            }
+        }
+        _ => {}
+    }
+    Ok(None)
+}
+
+/// Read a document from XML, see the [module](self) documentation.
+///
+/// ```
+/// # use gpnd::bridge::xml::from_xml;
+/// # use gpnd::{Document, Node, Value};
+/// let document = from_xml(r#"<config><server port="80">main</server></config>"#).unwrap();
+/// let server = document.first_node_named("server").unwrap();
+/// assert_eq!(server.get_param("port").unwrap(), "80");
+/// assert_eq!(server.get_arg(0).unwrap(), "main");
+/// ```
+///
+/// # Errors
+/// Fails if the XML is not well-formed, has no root element, or has an
+/// argument that cannot be read as its type.
+pub fn from_xml(source: &str) -> Result<DocumentBuf, FromXmlError> {
+    let mut reader = Reader::from_str(source);
+    reader.config_mut().trim_text(true);
+    let mut stack: Vec<Frame> = Vec::new();
+    loop {
+        match reader.read_event()? {
+            Event::Start(element) => {
+                let frame = open(stack.last(), &element)?;
+                stack.push(frame);
+            }
+            Event::Empty(element) => {
+                let frame = open(stack.last(), &element)?;
+                stack.push(frame);
+                if let Some(document) = close(&mut stack)? {
+                    return Ok(document);
+                }
+            }
+            Event::End(_) => {
+                if let Some(document) = close(&mut stack)? {
+                    return Ok(document);
+                }
+            }
+            Event::Text(text) => {
+                let text = text.unescape()?;
+                match stack.last_mut() {
+                    Some(Frame::Node(node)) => node.args_mut().push(text.into_owned().into()),
+                    Some(Frame::Arg { text: buffer, .. }) => buffer.push_str(&text),
+                    _ => {}
+                }
+            }
+            Event::CData(text) => {
+                let text = String::from_utf8_lossy(&text).into_owned();
+                match stack.last_mut() {
+                    Some(Frame::Node(node)) => node.args_mut().push(text.into()),
+                    Some(Frame::Arg { text: buffer, .. }) => buffer.push_str(&text),
+                    _ => {}
+                }
+            }
+            Event::Eof => return Err(FromXmlError::MissingRoot),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_xml_roundtrip() {
+        let document = parse(
+            r#"
+            @version "1"
+            server "a < b" {
+                http:route 1 2.5 #inf true null ["x" 1] #{ k=-1 }
+                empty
+            }
+            "#,
+        )
+        .unwrap();
+        let xml = to_xml(&document).unwrap();
+        assert!(xml.starts_with(
+            "<document version=\"1\">\n  <server>\n    <arg type=\"String\">a &lt; b</arg>\n"
+        ));
+        assert_eq!(from_xml(&xml).unwrap(), document);
+    }
+
+    #[test]
+    fn test_xml_errors() {
+        assert!(to_xml(&parse("\"not a name\"").unwrap()).is_err());
+        assert!(to_xml(&parse("a \"not a key\"=1").unwrap()).is_err());
+        assert!(matches!(from_xml(""), Err(FromXmlError::MissingRoot)));
+        assert!(matches!(
+            from_xml("<d><a><arg type=\"U8\">300</arg></a></d>"),
+            Err(FromXmlError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            from_xml("<d><a></b></d>"),
+            Err(FromXmlError::Xml(_))
+        ));
+    }
+}
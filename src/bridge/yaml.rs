@@ -0,0 +1,145 @@
+//! Conversions between [`Value`] and [`serde_yaml::Value`].
+//!
+//! The mapping is the same as for JSON: numbers become `I64` when they fit,
+//! otherwise `U64` or `F64`, mappings become `Map`, and sequences become
+//! `List`. Tags are dropped, keeping the tagged value.
+//!
+//! Converting from YAML is fallible, because mapping keys can be any value,
+//! and only scalars are kept, as strings. Converting to YAML always succeeds,
+//! since YAML can represent non-finite floats.
+
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+
+use serde_yaml::{Mapping, Number};
+
+use crate::Value;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FromYamlError {
+    key: serde_yaml::Value,
+}
+
+impl fmt::Display for FromYamlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "cannot convert YAML mapping key `{:?}` to a string",
+            self.key
+        )
+    }
+}
+
+impl Error for FromYamlError {}
+
+/// The string for a scalar mapping key.
+fn key_to_string(key: serde_yaml::Value) -> Result<String, FromYamlError> {
+    match key {
+        serde_yaml::Value::String(inner) => Ok(inner),
+        serde_yaml::Value::Bool(inner) => Ok(inner.to_string()),
+        serde_yaml::Value::Number(inner) => Ok(inner.to_string()),
+        serde_yaml::Value::Null => Ok("null".to_owned()),
+        serde_yaml::Value::Tagged(inner) => key_to_string(inner.value),
+        key => Err(FromYamlError { key }),
+    }
+}
+
+impl TryFrom<serde_yaml::Value> for Value<'static> {
+    type Error = FromYamlError;
+
+    fn try_from(other: serde_yaml::Value) -> Result<Self, Self::Error> {
+        Ok(match other {
+            serde_yaml::Value::Null => Self::Null,
+            serde_yaml::Value::Bool(inner) => Self::Bool(inner),
+            serde_yaml::Value::Number(inner) => inner.into(),
+            serde_yaml::Value::String(inner) => Self::String(Cow::Owned(inner)),
+            serde_yaml::Value::Sequence(inner) => Self::List(Box::new(
+                inner
+                    .into_iter()
+                    .map(Self::try_from)
+                    .collect::<Result<_, _>>()?,
+            )),
+            serde_yaml::Value::Mapping(inner) => Self::Map(Box::new(
+                inner
+                    .into_iter()
+                    .map(|(key, value)| Ok((Cow::Owned(key_to_string(key)?), value.try_into()?)))
+                    .collect::<Result<_, _>>()?,
+            )),
+            serde_yaml::Value::Tagged(inner) => inner.value.try_into()?,
+        })
+    }
+}
+
+impl From<Number> for Value<'static> {
+    fn from(other: Number) -> Self {
+        if let Some(inner) = other.as_i64() {
+            Self::I64(inner)
+        } else if let Some(inner) = other.as_u64() {
+            Self::U64(inner)
+        } else {
+            other.as_f64().map_or(Self::Null, Self::F64)
+        }
+    }
+}
+
+impl From<Value<'_>> for serde_yaml::Value {
+    fn from(other: Value<'_>) -> Self {
+        match other {
+            Value::U8(inner) => inner.into(),
+            Value::U16(inner) => inner.into(),
+            Value::U32(inner) => inner.into(),
+            Value::U64(inner) | Value::Size(inner) | Value::Duration(inner) => inner.into(),
+            Value::Uint(inner) => inner.into(),
+            Value::I8(inner) => inner.into(),
+            Value::I16(inner) => inner.into(),
+            Value::I32(inner) => inner.into(),
+            Value::I64(inner) => inner.into(),
+            Value::Int(inner) => inner.into(),
+            Value::F32(inner) => inner.into(),
+            Value::F64(inner) => inner.into(),
+            Value::Bool(inner) => inner.into(),
+            Value::String(inner) => inner.into_owned().into(),
+            Value::List(inner) => Self::Sequence(inner.into_iter().map(Into::into).collect()),
+            Value::Slice(inner) => Self::Sequence(inner.iter().cloned().map(Into::into).collect()),
+            Value::Map(inner) => Self::Mapping(
+                inner
+                    .into_iter()
+                    .map(|(key, value)| (key.into_owned().into(), value.into()))
+                    .collect::<Mapping>(),
+            ),
+            Value::Null => Self::Null,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Value;
+
+    #[test]
+    fn test_yaml_roundtrip() {
+        let yaml: serde_yaml::Value = serde_yaml::from_str(
+            "name: server\nport: 8080\nratio: .inf\ntags: [a, b, ~]\ntls: { enabled: true }\n",
+        )
+        .unwrap();
+        let value = Value::try_from(yaml.clone()).unwrap();
+        let Value::Map(map) = &value else {
+            panic!("expected a map");
+        };
+        assert_eq!(map["ratio"], Value::F64(f64::INFINITY));
+        assert_eq!(serde_yaml::Value::from(value), yaml);
+    }
+
+    #[test]
+    fn test_yaml_keys() {
+        let yaml: serde_yaml::Value = serde_yaml::from_str("1: a\ntrue: b").unwrap();
+        let Value::Map(map) = Value::try_from(yaml).unwrap() else {
+            panic!("expected a map");
+        };
+        assert_eq!(map["1"], "a");
+        assert_eq!(map["true"], "b");
+        let yaml: serde_yaml::Value = serde_yaml::from_str("[a]: b").unwrap();
+        assert!(Value::try_from(yaml).is_err());
+    }
+}
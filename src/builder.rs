@@ -0,0 +1,209 @@
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use crate::{DocumentBuf, NodeBuf, Value};
+
+/// Build a [`NodeBuf`] with chained calls.
+///
+/// ```
+/// # use gpnd::builder::NodeBuilder;
+/// # use gpnd::Node;
+/// let node = NodeBuilder::new("server")
+///     .arg("localhost")
+///     .param("port", 8080)
+///     .child(NodeBuilder::new("tls").param("enabled", true))
+///     .build();
+///
+/// assert_eq!(node.get_param("port"), Some(8080.into()));
+/// assert!(node.has_children());
+/// ```
+#[derive(Clone, Debug, Default)]
+#[must_use]
+pub struct NodeBuilder {
+    node: NodeBuf,
+}
+
+impl NodeBuilder {
+    pub fn new<S>(name: S) -> Self
+    where
+        S: Into<Arc<str>>,
+    {
+        Self {
+            node: NodeBuf::new(name),
+        }
+    }
+
+    pub fn namespace<S>(mut self, namespace: S) -> Self
+    where
+        S: Into<Arc<str>>,
+    {
+        self.node.set_namespace(Some(namespace));
+        self
+    }
+
+    pub fn arg<V>(mut self, value: V) -> Self
+    where
+        V: Into<Value<'static>>,
+    {
+        self.node.args_mut().push(value.into());
+        self
+    }
+
+    pub fn args<I>(mut self, values: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<Value<'static>>,
+    {
+        self.node
+            .args_mut()
+            .extend(values.into_iter().map(Into::into));
+        self
+    }
+
+    /// Set a parameter, replacing any previous value of `key`.
+    pub fn param<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<Arc<str>>,
+        V: Into<Value<'static>>,
+    {
+        self.node.params_mut().insert(key, value);
+        self
+    }
+
+    pub fn params<I, K, V>(mut self, params: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<Arc<str>>,
+        V: Into<Value<'static>>,
+    {
+        self.node.params_mut().extend(params);
+        self
+    }
+
+    pub fn child<N>(mut self, child: N) -> Self
+    where
+        N: Into<NodeBuf>,
+    {
+        self.node.children_mut().push(child.into());
+        self
+    }
+
+    pub fn children<I>(mut self, children: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<NodeBuf>,
+    {
+        self.node
+            .children_mut()
+            .extend(children.into_iter().map(Into::into));
+        self
+    }
+
+    /// Add a line of comment before the node.
+    pub fn comment<S>(mut self, comment: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.node.leading_comments_mut().push(comment.into());
+        self
+    }
+
+    /// Set the comment at the end of the line of the node.
+    pub fn trailing_comment<S>(mut self, comment: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.node.set_trailing_comment(Some(comment));
+        self
+    }
+
+    #[must_use]
+    pub fn build(self) -> NodeBuf {
+        self.node
+    }
+}
+
+impl From<NodeBuilder> for NodeBuf {
+    fn from(builder: NodeBuilder) -> Self {
+        builder.build()
+    }
+}
+
+/// Build a [`DocumentBuf`] with chained calls.
+#[derive(Clone, Debug, Default)]
+#[must_use]
+pub struct DocumentBuilder {
+    version: Option<String>,
+    nodes: Vec<NodeBuf>,
+}
+
+impl DocumentBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn version<S>(mut self, version: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.version = Some(version.into());
+        self
+    }
+
+    pub fn node<N>(mut self, node: N) -> Self
+    where
+        N: Into<NodeBuf>,
+    {
+        self.nodes.push(node.into());
+        self
+    }
+
+    pub fn nodes<I>(mut self, nodes: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<NodeBuf>,
+    {
+        self.nodes.extend(nodes.into_iter().map(Into::into));
+        self
+    }
+
+    #[must_use]
+    pub fn build(self) -> DocumentBuf {
+        let mut document = DocumentBuf::from(self.nodes);
+        document.set_version(self.version);
+        document
+    }
+}
+
+impl From<DocumentBuilder> for DocumentBuf {
+    fn from(builder: DocumentBuilder) -> Self {
+        builder.build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DocumentBuilder, NodeBuilder};
+    use crate::{Document, Node, NodeBuf, Value};
+
+    #[test]
+    fn test_builder() {
+        let document = DocumentBuilder::new()
+            .node(
+                NodeBuilder::new("server")
+                    .args(["a", "b"])
+                    .params([("port", 80), ("port", 8080)])
+                    .children([NodeBuilder::new("tls"), NodeBuilder::new("log")]),
+            )
+            .node(NodeBuf::new("empty"))
+            .build();
+
+        let server = document.get_node(0).unwrap();
+        assert_eq!(server.args(), [Value::from("a"), Value::from("b")]);
+        assert_eq!(server.get_param("port"), Some(Value::I32(8080)));
+        assert_eq!(server.params().len(), 1);
+        assert_eq!(server.get_child(1).map(Node::name), Some("log"));
+        assert_eq!(document.get_node(1).map(Node::name), Some("empty"));
+    }
+}
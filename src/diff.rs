@@ -0,0 +1,391 @@
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+
+use crate::{Document, Node, Value};
+
+/// A difference between two documents, found by [`diff`].
+///
+/// Every change has the path of the node it applies to. Paths are names
+/// separated by `/`, like those taken by [`Document::get_path`], except that a
+/// node that is not the first of its name among its siblings is suffixed with
+/// its occurrence, such as `server[1]`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change<'doc> {
+    NodeAdded {
+        path: String,
+    },
+    NodeRemoved {
+        path: String,
+    },
+    ArgAdded {
+        path: String,
+        index: usize,
+        value: Value<'doc>,
+    },
+    ArgRemoved {
+        path: String,
+        index: usize,
+        value: Value<'doc>,
+    },
+    ArgChanged {
+        path: String,
+        index: usize,
+        old: Value<'doc>,
+        new: Value<'doc>,
+    },
+    ParamAdded {
+        path: String,
+        key: &'doc str,
+        value: Value<'doc>,
+    },
+    ParamRemoved {
+        path: String,
+        key: &'doc str,
+        value: Value<'doc>,
+    },
+    ParamChanged {
+        path: String,
+        key: &'doc str,
+        old: Value<'doc>,
+        new: Value<'doc>,
+    },
+}
+
+impl Change<'_> {
+    /// The path of the node the change applies to.
+    #[must_use]
+    pub fn path(&self) -> &str {
+        match self {
+            Self::NodeAdded { path }
+            | Self::NodeRemoved { path }
+            | Self::ArgAdded { path, .. }
+            | Self::ArgRemoved { path, .. }
+            | Self::ArgChanged { path, .. }
+            | Self::ParamAdded { path, .. }
+            | Self::ParamRemoved { path, .. }
+            | Self::ParamChanged { path, .. } => path,
+        }
+    }
+}
+
+/// Describes the change on one line, for logs.
+///
+/// ```
+/// # use gpnd::diff::diff;
+/// let old = gpnd::parser::parse("server port=80").unwrap();
+/// let new = gpnd::parser::parse("server port=8080").unwrap();
+/// assert_eq!(
+///     diff(&old, &new)[0].to_string(),
+///     "server: changed parameter `port` from 80 to 8080"
+/// );
+/// ```
+impl fmt::Display for Change<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NodeAdded { path } => write!(f, "{path}: added node"),
+            Self::NodeRemoved { path } => write!(f, "{path}: removed node"),
+            Self::ArgAdded { path, index, value } => {
+                write!(f, "{path}: added argument {index}: {value}")
+            }
+            Self::ArgRemoved { path, index, value } => {
+                write!(f, "{path}: removed argument {index}: {value}")
+            }
+            Self::ArgChanged {
+                path,
+                index,
+                old,
+                new,
+            } => write!(f, "{path}: changed argument {index} from {old} to {new}"),
+            Self::ParamAdded { path, key, value } => {
+                write!(f, "{path}: added parameter `{key}`: {value}")
+            }
+            Self::ParamRemoved { path, key, value } => {
+                write!(f, "{path}: removed parameter `{key}`: {value}")
+            }
+            Self::ParamChanged {
+                path,
+                key,
+                old,
+                new,
+            } => write!(f, "{path}: changed parameter `{key}` from {old} to {new}"),
+        }
+    }
+}
+
+/// Write changes like a unified diff, for showing to people.
+///
+/// Added and removed nodes are written as `+++ path` and `--- path`. Changes
+/// to the arguments and parameters of a node are grouped under an
+/// `@@ path @@` line, with a `-` line for each old value and a `+` line for
+/// each new one.
+///
+/// ```
+/// # use gpnd::diff::{diff, write_diff};
+/// let old = gpnd::parser::parse("server \"a\" port=80\nlog").unwrap();
+/// let new = gpnd::parser::parse("server \"b\" port=80 { tls }").unwrap();
+/// let mut output = String::new();
+/// write_diff(&mut output, &diff(&old, &new)).unwrap();
+/// assert_eq!(
+///     output,
+///     "@@ server @@\n-0: \"a\"\n+0: \"b\"\n+++ server/tls\n--- log\n"
+/// );
+/// ```
+pub fn write_diff<W>(f: &mut W, changes: &[Change<'_>]) -> fmt::Result
+where
+    W: Write,
+{
+    let mut hunk = None;
+    for change in changes {
+        let path = change.path();
+        let (old, new) = match change {
+            Change::NodeAdded { .. } => {
+                hunk = None;
+                writeln!(f, "+++ {path}")?;
+                continue;
+            }
+            Change::NodeRemoved { .. } => {
+                hunk = None;
+                writeln!(f, "--- {path}")?;
+                continue;
+            }
+            Change::ArgAdded { index, value, .. } => (None, Some(format!("{index}: {value}"))),
+            Change::ArgRemoved { index, value, .. } => (Some(format!("{index}: {value}")), None),
+            Change::ArgChanged {
+                index, old, new, ..
+            } => (
+                Some(format!("{index}: {old}")),
+                Some(format!("{index}: {new}")),
+            ),
+            Change::ParamAdded { key, value, .. } => (None, Some(format!("{key}={value}"))),
+            Change::ParamRemoved { key, value, .. } => (Some(format!("{key}={value}")), None),
+            Change::ParamChanged { key, old, new, .. } => {
+                (Some(format!("{key}={old}")), Some(format!("{key}={new}")))
+            }
+        };
+        if hunk != Some(path) {
+            hunk = Some(path);
+            writeln!(f, "@@ {path} @@")?;
+        }
+        if let Some(old) = old {
+            writeln!(f, "-{old}")?;
+        }
+        if let Some(new) = new {
+            writeln!(f, "+{new}")?;
+        }
+    }
+    Ok(())
+}
+
+/// Write changes like a unified diff, see [`write_diff`].
+#[allow(clippy::missing_panics_doc)]
+#[must_use]
+pub fn render_diff(changes: &[Change<'_>]) -> String {
+    let mut string = String::new();
+    write_diff(&mut string, changes).expect("writing to a `String` does not fail");
+    string
+}
+
+/// Find the changes that turn document `old` into `new`.
+///
+/// Nodes are matched by name and by their occurrence among siblings of the
+/// same name, so the second `server` node in `old` is compared with the second
+/// one in `new`. Added and removed nodes are reported once, without changes
+/// for their contents.
+#[must_use]
+pub fn diff<'doc>(old: &'doc dyn Document, new: &'doc dyn Document) -> Vec<Change<'doc>> {
+    let mut changes = Vec::new();
+    diff_nodes("", &old.nodes(), &new.nodes(), &mut changes);
+    changes
+}
+
+pub(crate) fn occurrences<'doc>(nodes: &[&'doc dyn Node]) -> Vec<(&'doc str, usize)> {
+    let mut keys: Vec<(&str, usize)> = Vec::with_capacity(nodes.len());
+    for node in nodes {
+        let occurrence = keys.iter().filter(|(name, _)| *name == node.name()).count();
+        keys.push((node.name(), occurrence));
+    }
+    keys
+}
+
+pub(crate) fn join(parent: &str, (name, occurrence): (&str, usize)) -> String {
+    let separator = if parent.is_empty() { "" } else { "/" };
+    if occurrence == 0 {
+        format!("{parent}{separator}{name}")
+    } else {
+        format!("{parent}{separator}{name}[{occurrence}]")
+    }
+}
+
+fn diff_nodes<'doc>(
+    parent: &str,
+    old: &[&'doc dyn Node],
+    new: &[&'doc dyn Node],
+    changes: &mut Vec<Change<'doc>>,
+) {
+    let old_keys = occurrences(old);
+    let new_keys = occurrences(new);
+    for (old_node, key) in old.iter().zip(&old_keys) {
+        let path = join(parent, *key);
+        match new_keys.iter().position(|other| other == key) {
+            Some(index) => diff_node(&path, *old_node, new[index], changes),
+            None => changes.push(Change::NodeRemoved { path }),
+        }
+    }
+    for key in new_keys.iter().filter(|key| !old_keys.contains(key)) {
+        changes.push(Change::NodeAdded {
+            path: join(parent, *key),
+        });
+    }
+}
+
+fn diff_node<'doc>(
+    path: &str,
+    old: &'doc dyn Node,
+    new: &'doc dyn Node,
+    changes: &mut Vec<Change<'doc>>,
+) {
+    let old_args = old.args();
+    let new_args = new.args();
+    for index in 0..old_args.len().max(new_args.len()) {
+        let path = path.to_owned();
+        match (old_args.get(index), new_args.get(index)) {
+            (Some(old), Some(new)) if old != new => changes.push(Change::ArgChanged {
+                path,
+                index,
+                old: old.clone(),
+                new: new.clone(),
+            }),
+            (Some(value), None) => changes.push(Change::ArgRemoved {
+                path,
+                index,
+                value: value.clone(),
+            }),
+            (None, Some(value)) => changes.push(Change::ArgAdded {
+                path,
+                index,
+                value: value.clone(),
+            }),
+            _ => {}
+        }
+    }
+
+    let new_params = new.params();
+    for (key, value) in old.params() {
+        let path = path.to_owned();
+        match new.get_param(key) {
+            Some(new) if new != value => changes.push(Change::ParamChanged {
+                path,
+                key,
+                old: value,
+                new,
+            }),
+            Some(_) => {}
+            None => changes.push(Change::ParamRemoved { path, key, value }),
+        }
+    }
+    for (key, value) in new_params {
+        if old.get_param(key).is_none() {
+            changes.push(Change::ParamAdded {
+                path: path.to_owned(),
+                key,
+                value,
+            });
+        }
+    }
+
+    diff_nodes(path, &old.children(), &new.children(), changes);
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::borrow::ToOwned;
+    use alloc::string::ToString;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use super::{diff, render_diff, Change};
+    use crate::{DocumentBuf, NodeBuf, Value};
+
+    #[test]
+    fn test_diff() {
+        let mut server = NodeBuf::new("server");
+        server.args_mut().push(Value::from("a"));
+        server.params_mut().insert("port", 80_u16);
+        server.params_mut().insert("host", "localhost");
+        let old = DocumentBuf::from(vec![server.clone(), NodeBuf::new("log")]);
+
+        server.args_mut()[0] = Value::from("b");
+        server.args_mut().push(Value::Bool(true));
+        server.params_mut().insert("port", 8080_u16);
+        server.params_mut().remove("host");
+        server.children_mut().push(NodeBuf::new("tls"));
+        let new = DocumentBuf::from(vec![server.clone(), server]);
+
+        assert_eq!(
+            diff(&old, &new),
+            [
+                Change::ArgChanged {
+                    path: "server".to_owned(),
+                    index: 0,
+                    old: Value::from("a"),
+                    new: Value::from("b"),
+                },
+                Change::ArgAdded {
+                    path: "server".to_owned(),
+                    index: 1,
+                    value: Value::Bool(true),
+                },
+                Change::ParamChanged {
+                    path: "server".to_owned(),
+                    key: "port",
+                    old: Value::U16(80),
+                    new: Value::U16(8080),
+                },
+                Change::ParamRemoved {
+                    path: "server".to_owned(),
+                    key: "host",
+                    value: Value::from("localhost"),
+                },
+                Change::NodeAdded {
+                    path: "server/tls".to_owned(),
+                },
+                Change::NodeRemoved {
+                    path: "log".to_owned(),
+                },
+                Change::NodeAdded {
+                    path: "server[1]".to_owned(),
+                },
+            ]
+        );
+        assert!(diff(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn test_render_diff() {
+        let old = crate::parser::parse("a 1 2 key=1 gone=true\nb x=1 { c }").unwrap();
+        let new = crate::parser::parse("a 1 3 key=2 new=\"n\"\nb x=1 { d }").unwrap();
+        let changes = diff(&old, &new);
+        assert_eq!(
+            render_diff(&changes),
+            concat!(
+                "@@ a @@\n-1: 2\n+1: 3\n-key=1\n+key=2\n-gone=true\n+new=\"n\"\n",
+                "--- b/c\n+++ b/d\n",
+            )
+        );
+        assert_eq!(
+            changes.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            [
+                "a: changed argument 1 from 2 to 3",
+                "a: changed parameter `key` from 1 to 2",
+                "a: removed parameter `gone`: true",
+                "a: added parameter `new`: \"n\"",
+                "b/c: removed node",
+                "b/d: added node",
+            ]
+        );
+        assert_eq!(render_diff(&[]), "");
+    }
+}
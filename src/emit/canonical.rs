@@ -0,0 +1,131 @@
+use alloc::string::String;
+
+use super::document::write_document_with;
+use super::options::{Align, FormatOptions, Indent, LineBreaks, Newline};
+use super::value::{FloatFormat, NonAscii, QuoteStyle};
+use crate::{Document, DocumentBuf, NodeBuf, Value};
+
+/// The options for the canonical form, which must never change.
+const CANONICAL: FormatOptions = FormatOptions {
+    indent: Indent::Spaces(4),
+    quotes: QuoteStyle::Escaped,
+    bare_identifiers: true,
+    non_ascii: NonAscii::Verbatim,
+    floats: FloatFormat::Shortest,
+    line_breaks: LineBreaks::Never,
+    max_width: usize::MAX,
+    newline: Newline::Lf,
+    compact: false,
+    comments: false,
+    sort_nodes: false,
+    sort_params: false,
+    align: Align::Never,
+};
+
+/// Replace negative zeros, which are equal to positive zeros but written
+/// differently.
+fn normalize_value(value: &mut Value<'static>) {
+    match value {
+        Value::F32(float) if *float == 0.0 => *float = 0.0,
+        Value::F64(float) if *float == 0.0 => *float = 0.0,
+        Value::List(items) => items.iter_mut().for_each(normalize_value),
+        Value::Map(items) => items.values_mut().for_each(normalize_value),
+        _ => {}
+    }
+}
+
+fn normalize_node(node: &mut NodeBuf) {
+    node.args_mut().iter_mut().for_each(normalize_value);
+    node.params_mut().sort();
+    for (_, value) in node.params_mut().iter_mut() {
+        normalize_value(value);
+    }
+    node.children_mut().iter_mut().for_each(normalize_node);
+}
+
+/// Write a document in its canonical form, which is the same for documents
+/// that are equal, regardless of how they were written, so that it can be
+/// hashed, signed, or compared as text.
+///
+/// The canonical form is:
+///
+/// - the `@version` directive, if any, on the first line;
+/// - one node per line, with children indented by four spaces, and every line
+///   ending in `\n`;
+/// - names and keys bare where possible, and quoted with escapes otherwise;
+/// - parameters sorted by key, with repeated keys in the order they were
+///   written, and map entries sorted by key;
+/// - integers in decimal, and floats with the fewest digits that read back as
+///   the same float, with at least one fractional digit and no negative zero;
+/// - strings quoted, escaping only quotes, backslashes, and control characters.
+///
+/// ```
+/// # use gpnd::emit::to_canonical_string;
+/// let lhs = gpnd::parser::parse("a   b=0x10 a=1.50 {\n  c \"\\u{41}\"\n}").unwrap();
+/// let rhs = gpnd::parser::parse("a a=1.5 b=16 { c \"A\" }").unwrap();
+/// assert_eq!(
+///     to_canonical_string(&lhs),
+///     "a a=1.5 b=16 {\n    c \"A\"\n}\n"
+/// );
+/// assert_eq!(to_canonical_string(&lhs), to_canonical_string(&rhs));
+/// ```
+#[allow(clippy::missing_panics_doc)]
+#[must_use]
+pub fn to_canonical_string<D>(document: &D) -> String
+where
+    D: Document + ?Sized,
+{
+    let mut canonical = DocumentBuf::new();
+    canonical.set_version(document.version());
+    for node in document.nodes() {
+        let mut node = node.to_node_buf();
+        normalize_node(&mut node);
+        canonical.nodes_mut().push(node);
+    }
+    let mut string = String::new();
+    write_document_with(&mut string, &canonical, &CANONICAL)
+        .expect("writing to a `String` does not fail");
+    string
+}
+
+impl DocumentBuf {
+    /// Write this document in its canonical form, see
+    /// [`to_canonical_string`].
+    #[must_use]
+    pub fn to_canonical_string(&self) -> String {
+        to_canonical_string(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_canonical() {
+        let lhs = parse("@version \"1\"\nn -0.0 [1e0 -0.0] z=1 a=2 z=3 m=#{ b=1 a=2 }").unwrap();
+        let mut rhs = DocumentBuf::new();
+        rhs.set_version(Some("1"));
+        let mut node = NodeBuf::new("n");
+        node.args_mut().push(Value::F32(0.0));
+        node.args_mut()
+            .push(vec![Value::F64(1.0), Value::F64(0.0)].into());
+        node.params_mut().push("a", 2_u8);
+        node.params_mut()
+            .push("m", lhs.nodes()[0].get_param("m").unwrap().into_owned());
+        node.params_mut().push("z", 1_i16);
+        node.params_mut().push("z", 3_u64);
+        rhs.nodes_mut().push(node);
+
+        let canonical = lhs.to_canonical_string();
+        assert_eq!(
+            canonical,
+            "@version \"1\"\nn 0.0 [1.0 0.0] a=2 m=#{a=2 b=1} z=1 z=3\n"
+        );
+        assert_eq!(rhs.to_canonical_string(), canonical);
+        assert_eq!(parse(&canonical).unwrap().to_canonical_string(), canonical);
+    }
+}
@@ -0,0 +1,688 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+
+use super::options::{Align, FormatOptions, Indent, LineBreaks};
+use super::value::{write_ident, write_string_with, write_value_with};
+use crate::{validate_node_name, Document, DocumentBuf, Node, NodeBuf, Value};
+
+/// Whether `text` can be written without quotes as a node name or parameter
+/// key, and read back as the same text.
+pub(crate) fn is_bare_ident(text: &str) -> bool {
+    let mut chars = text.chars();
+    let Some(first) = chars.next() else {
+        return false;
+    };
+    let second = chars.next();
+    let number = first.is_ascii_digit()
+        || (matches!(first, '+' | '-') && second.is_some_and(|ch| ch.is_ascii_digit()));
+    !number
+        && !matches!(first, '#' | '@')
+        && text != "---"
+        && !text.contains("//")
+        && !text.contains("/*")
+        && !text
+            .chars()
+            .any(|ch| ch.is_whitespace() || ";={}[]()\",\\".contains(ch))
+}
+
+/// The widths of the columns that sibling nodes are aligned to, see
+/// [`Align`].
+struct Columns {
+    name: usize,
+    /// The width of the widest key at each position.
+    keys: Vec<usize>,
+    /// The width of the widest aligned entry at each position.
+    entries: Vec<usize>,
+}
+
+/// Writes nodes with a set of [`FormatOptions`].
+pub(super) struct Printer<'out, W> {
+    pub(super) out: &'out mut W,
+    pub(super) options: &'out FormatOptions,
+}
+
+impl<W> Printer<'_, W>
+where
+    W: Write,
+{
+    pub(super) fn indent(&mut self, depth: usize) -> fmt::Result {
+        for _ in 0..depth {
+            match self.options.indent {
+                Indent::Spaces(width) => write!(self.out, "{:width$}", "")?,
+                Indent::Tabs => self.out.write_char('\t')?,
+            }
+        }
+        Ok(())
+    }
+
+    pub(super) fn newline(&mut self) -> fmt::Result {
+        self.out.write_str(self.options.newline.as_str())
+    }
+
+    fn ident(&self, f: &mut String, text: &str) -> fmt::Result {
+        write_ident(f, text, self.options)
+    }
+
+    fn value(&self, f: &mut String, value: &Value<'_>) -> fmt::Result {
+        write_value_with(f, value, self.options)
+    }
+
+    /// Render the name of a node, after its type annotation.
+    pub(super) fn name(&self, node: &dyn Node) -> Result<String, fmt::Error> {
+        let mut name = String::new();
+        if let Some(type_annotation) = node.type_annotation() {
+            name.push('(');
+            self.ident(&mut name, type_annotation)?;
+            name.push(')');
+        }
+        match node.namespace() {
+            Some(namespace)
+                if validate_node_name(namespace).is_ok()
+                    && validate_node_name(node.name()).is_ok() =>
+            {
+                write!(name, "{namespace}:{}", node.name())?;
+            }
+            // A namespace can only be written with identifiers, so that the
+            // name is quoted as a whole, and read back without a namespace,
+            // rather than written so that it cannot be read back.
+            Some(namespace) => {
+                write_string_with(
+                    &mut name,
+                    &format!("{namespace}:{}", node.name()),
+                    self.options,
+                )?;
+            }
+            // A bare name with a colon would be read back with a namespace.
+            None if node.name().contains(':') => {
+                write_string_with(&mut name, node.name(), self.options)?;
+            }
+            None => self.ident(&mut name, node.name())?,
+        }
+        Ok(name)
+    }
+
+    /// Render the arguments and parameters of a node, with the key of each
+    /// parameter apart from its value.
+    fn entry_parts(&self, node: &dyn Node) -> Result<Vec<(Option<String>, String)>, fmt::Error> {
+        let mut entries = Vec::new();
+        for arg in node.args_iter() {
+            let mut entry = String::new();
+            self.value(&mut entry, &arg)?;
+            entries.push((None, entry));
+        }
+        let mut params = node.params_all();
+        if self.options.sort_params {
+            params.sort_by_key(|&(key, _)| key);
+        }
+        for (key, value) in params {
+            let (mut key_text, mut entry) = (String::new(), String::new());
+            self.ident(&mut key_text, key)?;
+            self.value(&mut entry, &value)?;
+            entries.push((Some(key_text), entry));
+        }
+        Ok(entries)
+    }
+
+    /// Render the arguments and parameters of a node.
+    pub(super) fn entries(&self, node: &dyn Node) -> Result<Vec<String>, fmt::Error> {
+        Ok(self
+            .entry_parts(node)?
+            .into_iter()
+            .map(|(key, value)| match key {
+                Some(key) => format!("{key}={value}"),
+                None => value,
+            })
+            .collect())
+    }
+
+    /// Render an entry padded to the key width of its column.
+    fn aligned_entry(&self, (key, value): (Option<String>, String), key_width: usize) -> String {
+        match (key, self.options.align) {
+            (Some(key), Align::Equals) => format!("{key:<key_width$} = {value}"),
+            (Some(key), Align::Values) => {
+                format!("{:<width$}{value}", key + "=", width = key_width + 1)
+            }
+            (Some(key), Align::Never) => format!("{key}={value}"),
+            (None, _) => value,
+        }
+    }
+
+    /// Find the columns to align `nodes` to, if they are aligned.
+    fn columns(&self, nodes: &[&dyn Node]) -> Result<Option<Columns>, fmt::Error> {
+        if self.options.align == Align::Never {
+            return Ok(None);
+        }
+        let mut columns = Columns {
+            name: 0,
+            keys: Vec::new(),
+            entries: Vec::new(),
+        };
+        let mut all_parts = Vec::with_capacity(nodes.len());
+        for node in nodes {
+            columns.name = columns.name.max(self.name(*node)?.chars().count());
+            let parts = self.entry_parts(*node)?;
+            for (index, (key, _)) in parts.iter().enumerate() {
+                if index == columns.keys.len() {
+                    columns.keys.push(0);
+                }
+                let width = key.as_ref().map_or(0, |key| key.chars().count());
+                columns.keys[index] = columns.keys[index].max(width);
+            }
+            all_parts.push(parts);
+        }
+        for parts in all_parts {
+            for (index, part) in parts.into_iter().enumerate() {
+                if index == columns.entries.len() {
+                    columns.entries.push(0);
+                }
+                let width = self
+                    .aligned_entry(part, columns.keys[index])
+                    .chars()
+                    .count();
+                columns.entries[index] = columns.entries[index].max(width);
+            }
+        }
+        Ok(Some(columns))
+    }
+
+    /// Write a node with no optional whitespace, leaving the separator to
+    /// the caller.
+    fn compact_node(&mut self, node: &dyn Node) -> fmt::Result {
+        self.out.write_str(&self.name(node)?)?;
+        for entry in self.entries(node)? {
+            self.out.write_char(' ')?;
+            self.out.write_str(&entry)?;
+        }
+        let children = node.children();
+        if !children.is_empty() {
+            self.out.write_char('{')?;
+            self.compact_nodes(children)?;
+            self.out.write_char('}')?;
+        }
+        Ok(())
+    }
+
+    fn compact_nodes<'node, I>(&mut self, nodes: I) -> fmt::Result
+    where
+        I: IntoIterator<Item = &'node dyn Node>,
+    {
+        for (index, node) in nodes.into_iter().enumerate() {
+            if index > 0 {
+                self.out.write_char(';')?;
+            }
+            self.compact_node(node)?;
+        }
+        Ok(())
+    }
+
+    pub(super) fn node(&mut self, node: &dyn Node, depth: usize) -> fmt::Result {
+        if self.options.compact {
+            return self.compact_node(node);
+        }
+        let columns = self.columns(&[node])?;
+        self.aligned_node(node, depth, columns.as_ref())
+    }
+
+    /// Write the nodes at `depth`, aligned to each other.
+    fn nodes(&mut self, nodes: &[&dyn Node], depth: usize) -> fmt::Result {
+        let columns = self.columns(nodes)?;
+        for node in nodes {
+            self.aligned_node(*node, depth, columns.as_ref())?;
+        }
+        Ok(())
+    }
+
+    fn aligned_node(
+        &mut self,
+        node: &dyn Node,
+        depth: usize,
+        columns: Option<&Columns>,
+    ) -> fmt::Result {
+        let mut name = self.name(node)?;
+        let entries = match columns {
+            Some(columns) => {
+                let parts = self.entry_parts(node)?;
+                let last = parts.len().saturating_sub(1);
+                if !parts.is_empty() {
+                    name = format!("{name:<width$}", width = columns.name);
+                }
+                parts
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, part)| {
+                        let entry = self.aligned_entry(part, columns.keys[index]);
+                        if index == last {
+                            entry
+                        } else {
+                            format!("{entry:<width$}", width = columns.entries[index])
+                        }
+                    })
+                    .collect()
+            }
+            None => self.entries(node)?,
+        };
+        let children = node.children();
+        let break_lines =
+            columns.is_none() && self.break_lines(depth, &name, &entries, !children.is_empty());
+        self.header(node, depth, &name, &entries, break_lines)?;
+        if !children.is_empty() {
+            self.out.write_str(" {")?;
+            self.newline()?;
+            self.nodes(&children, depth + 1)?;
+            self.indent(depth)?;
+            self.out.write_char('}')?;
+        }
+        if let Some(comment) = node.trailing_comment().filter(|_| self.options.comments) {
+            self.out.write_char(' ')?;
+            self.comment(comment)?;
+        }
+        self.newline()
+    }
+
+    /// Whether the entries of a node are written on lines of their own.
+    fn break_lines(&self, depth: usize, name: &str, entries: &[String], braced: bool) -> bool {
+        let width = depth * self.options.indent.width()
+            + name.chars().count()
+            + entries
+                .iter()
+                .map(|entry| 1 + entry.chars().count())
+                .sum::<usize>()
+            + if braced { 2 } else { 0 };
+        match self.options.line_breaks {
+            LineBreaks::Never => false,
+            LineBreaks::Overflow => width > self.options.max_width && !entries.is_empty(),
+            LineBreaks::Always => entries.len() > 1,
+        }
+    }
+
+    /// Write the leading comments, name, and entries of a node, up to its
+    /// block of children.
+    fn header(
+        &mut self,
+        node: &dyn Node,
+        depth: usize,
+        name: &str,
+        entries: &[String],
+        break_lines: bool,
+    ) -> fmt::Result {
+        if self.options.comments {
+            for line in node.leading_comments() {
+                self.indent(depth)?;
+                self.comment(line)?;
+                self.newline()?;
+            }
+        }
+        self.indent(depth)?;
+        self.out.write_str(name)?;
+        for entry in entries {
+            if break_lines {
+                self.out.write_str(" \\")?;
+                self.newline()?;
+                self.indent(depth + 1)?;
+            } else {
+                self.out.write_char(' ')?;
+            }
+            self.out.write_str(entry)?;
+        }
+        Ok(())
+    }
+
+    /// Write the header of a node whose children are not known yet, see
+    /// [`Printer::header`]. The block and the end of the line are left to
+    /// the caller.
+    pub(super) fn open_node(&mut self, node: &dyn Node, depth: usize) -> fmt::Result {
+        let name = self.name(node)?;
+        let entries = self.entries(node)?;
+        if self.options.compact {
+            self.out.write_str(&name)?;
+            return entries.iter().try_for_each(|entry| {
+                self.out.write_char(' ')?;
+                self.out.write_str(entry)
+            });
+        }
+        let break_lines = self.break_lines(depth, &name, &entries, false);
+        self.header(node, depth, &name, &entries, break_lines)
+    }
+
+    /// Write a line comment, joining the lines of `text` with spaces.
+    pub(super) fn comment(&mut self, text: &str) -> fmt::Result {
+        self.out.write_str("//")?;
+        for line in text.lines() {
+            self.out.write_char(' ')?;
+            self.out.write_str(line)?;
+        }
+        Ok(())
+    }
+
+    /// Write a `@version` directive, with its terminator.
+    pub(super) fn version(&mut self, version: &str) -> fmt::Result {
+        self.out.write_str("@version ")?;
+        write_string_with(self.out, version, self.options)?;
+        if self.options.compact {
+            self.out.write_char(';')
+        } else {
+            self.newline()
+        }
+    }
+
+    fn document<D>(&mut self, document: &D) -> fmt::Result
+    where
+        D: Document + ?Sized,
+    {
+        if let Some(version) = document.version() {
+            self.version(version)?;
+        }
+        let mut nodes = document.nodes();
+        if self.options.sort_nodes {
+            nodes.sort_by(|lhs, rhs| {
+                (lhs.namespace(), lhs.name()).cmp(&(rhs.namespace(), rhs.name()))
+            });
+        }
+        if self.options.compact {
+            return self.compact_nodes(nodes);
+        }
+        self.nodes(&nodes, 0)
+    }
+}
+
+/// Write a node and its children, indented by `depth` levels, with the
+/// default [`FormatOptions`].
+pub fn write_node<W>(f: &mut W, node: &dyn Node, depth: usize) -> fmt::Result
+where
+    W: Write,
+{
+    write_node_with(f, node, depth, &FormatOptions::default())
+}
+
+/// Write a node and its children, indented by `depth` levels.
+pub fn write_node_with<W>(
+    f: &mut W,
+    node: &dyn Node,
+    depth: usize,
+    options: &FormatOptions,
+) -> fmt::Result
+where
+    W: Write,
+{
+    Printer { out: f, options }.node(node, depth)
+}
+
+/// Write a document as source text, which can be parsed back into an equal
+/// document, with the default [`FormatOptions`].
+pub fn write_document<W, D>(f: &mut W, document: &D) -> fmt::Result
+where
+    W: Write,
+    D: Document + ?Sized,
+{
+    write_document_with(f, document, &FormatOptions::default())
+}
+
+/// Write a document as source text, which can be parsed back into an equal
+/// document.
+pub fn write_document_with<W, D>(f: &mut W, document: &D, options: &FormatOptions) -> fmt::Result
+where
+    W: Write,
+    D: Document + ?Sized,
+{
+    Printer { out: f, options }.document(document)
+}
+
+/// Write a document as source text, see [`write_document`].
+///
+/// ```
+/// # use gpnd::{NodeBuf, DocumentBuf};
+/// let mut server = NodeBuf::new("server");
+/// server.params_mut().insert("host", "local host");
+/// server.children_mut().push(NodeBuf::new("tls"));
+/// let document = DocumentBuf::from(vec![server]);
+/// assert_eq!(
+///     gpnd::emit::to_string(&document),
+///     "server host=\"local host\" {\n    tls\n}\n"
+/// );
+/// ```
+#[allow(clippy::missing_panics_doc)]
+#[must_use]
+pub fn to_string<D>(document: &D) -> String
+where
+    D: Document + ?Sized,
+{
+    to_string_with(document, &FormatOptions::default())
+}
+
+/// Write a document as source text, see [`write_document_with`].
+#[allow(clippy::missing_panics_doc)]
+#[must_use]
+pub fn to_string_with<D>(document: &D, options: &FormatOptions) -> String
+where
+    D: Document + ?Sized,
+{
+    let mut string = String::new();
+    write_document_with(&mut string, document, options)
+        .expect("writing to a `String` does not fail");
+    string
+}
+
+impl DocumentBuf {
+    /// Write this document as source text, see [`write_document`].
+    #[must_use]
+    pub fn to_document_string(&self) -> String {
+        to_string(self)
+    }
+}
+
+/// The options for `{}`, or for `{:#}` if `alternate` is set.
+fn display_options(alternate: bool) -> FormatOptions {
+    if alternate {
+        FormatOptions::default()
+    } else {
+        FormatOptions::compact()
+    }
+}
+
+/// Writes the document as source text on one line, see
+/// [`FormatOptions::compact`]. The alternate form, `{:#}`, writes it with
+/// the default [`FormatOptions`], with each node on its own line.
+///
+/// ```
+/// # use gpnd::{NodeBuf, DocumentBuf};
+/// let mut server = NodeBuf::new("server");
+/// server.children_mut().push(NodeBuf::new("tls"));
+/// let document = DocumentBuf::from(vec![server, NodeBuf::new("log")]);
+/// assert_eq!(format!("{document}"), "server{tls};log");
+/// assert_eq!(format!("{document:#}"), "server {\n    tls\n}\nlog\n");
+/// ```
+impl fmt::Display for DocumentBuf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_document_with(f, self, &display_options(f.alternate()))
+    }
+}
+
+/// Writes the node and its children as source text, like the
+/// [`Display`](fmt::Display) of [`DocumentBuf`].
+///
+/// ```
+/// # use gpnd::NodeBuf;
+/// let mut server = NodeBuf::new("server");
+/// server.params_mut().insert("port", 80);
+/// server.children_mut().push(NodeBuf::new("tls"));
+/// assert_eq!(format!("{server}"), "server port=80{tls}");
+/// assert_eq!(format!("{server:#}"), "server port=80 {\n    tls\n}\n");
+/// ```
+impl fmt::Display for NodeBuf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_node_with(f, self, 0, &display_options(f.alternate()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::borrow::ToOwned;
+    use alloc::vec;
+
+    use super::*;
+    use crate::emit::{Newline, QuoteStyle};
+    use crate::parser::parse;
+    use crate::{NodeBuf, Value};
+
+    #[test]
+    fn test_is_bare_ident() {
+        assert!(is_bare_ident("server"));
+        assert!(is_bare_ident("max-conns"));
+        assert!(is_bare_ident("-flag"));
+        assert!(!is_bare_ident(""));
+        assert!(!is_bare_ident("-1"));
+        assert!(!is_bare_ident("2fa"));
+        assert!(!is_bare_ident("a b"));
+        assert!(!is_bare_ident("#inf"));
+        assert!(!is_bare_ident("---"));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let source = r#"
+            @version "2"
+            server "localhost" 8080 1.5 #-inf null port=80 port=81 {
+                http:route "/" methods=["GET" "HEAD"] limits=#{ "a b"=1 }
+                "quoted name" "a:b"=true
+                "with:colon"
+                (widget)button; ("my type")"ok"
+            }
+            empty
+        "#;
+        let document = parse(source).unwrap();
+        let emitted = document.to_document_string();
+        assert_eq!(parse(&emitted).unwrap(), document);
+        assert!(emitted.starts_with("@version \"2\"\nserver \"localhost\""));
+
+        let mut node = NodeBuf::new("escapes");
+        node.args_mut().push(Value::from("tab\tquote\" \u{7}"));
+        let document = DocumentBuf::from(vec![node]);
+        assert_eq!(parse(&to_string(&document)).unwrap(), document);
+
+        // A namespace with a name that is not an identifier is still written
+        // so that it can be read back.
+        let mut node = NodeBuf::new("my route");
+        node.set_namespace(Some("http"));
+        let emitted = to_string(&DocumentBuf::from(vec![node]));
+        assert_eq!(emitted, "\"http:my route\"\n");
+        assert_eq!(
+            parse(&emitted).unwrap().nodes_ref()[0].name(),
+            "http:my route"
+        );
+    }
+
+    #[test]
+    fn test_format_options() {
+        let document = parse(r#"a { b "C:\\dir" key=1 flag=true }"#).unwrap();
+        let options = FormatOptions {
+            indent: Indent::Tabs,
+            quotes: QuoteStyle::Raw,
+            line_breaks: LineBreaks::Always,
+            newline: Newline::CrLf,
+            ..FormatOptions::default()
+        };
+        let emitted = to_string_with(&document, &options);
+        assert_eq!(
+            emitted,
+            "a {\r\n\tb \\\r\n\t\tr\"C:\\dir\" \\\r\n\t\tkey=1 \\\r\n\t\tflag=true\r\n}\r\n"
+        );
+        assert_eq!(parse(&emitted).unwrap(), document);
+
+        let options = FormatOptions {
+            max_width: 16,
+            ..FormatOptions::default()
+        };
+        assert_eq!(
+            to_string_with(&document, &options),
+            "a {\n    b \\\n        \"C:\\\\dir\" \\\n        key=1 \\\n        flag=true\n}\n"
+        );
+        assert_eq!(
+            to_string_with(&parse("short key=1").unwrap(), &options),
+            "short key=1\n"
+        );
+
+        let document = parse("c 1\nb z=1 a=2 z=3 { y b=1 a=2 }\nx:a\nc 2\na").unwrap();
+        let options = FormatOptions {
+            sort_nodes: true,
+            sort_params: true,
+            ..FormatOptions::default()
+        };
+        assert_eq!(
+            to_string_with(&document, &options),
+            "a\nb a=2 z=1 z=3 {\n    y a=2 b=1\n}\nc 1\nc 2\nx:a\n"
+        );
+
+        let document =
+            parse("db { host \"localhost\" port=5432; user_name \"admin\"; pool }").unwrap();
+        let options = FormatOptions {
+            align: Align::Equals,
+            ..FormatOptions::default()
+        };
+        let emitted = to_string_with(&document, &options);
+        assert_eq!(
+            emitted,
+            concat!(
+                "db {\n",
+                "    host      \"localhost\" port = 5432\n",
+                "    user_name \"admin\"\n",
+                "    pool\n",
+                "}\n",
+            )
+        );
+        assert_eq!(parse(&emitted).unwrap(), document);
+    }
+
+    #[test]
+    fn test_compact() {
+        let source = r#"
+            @version "2"
+            server "localhost" 8080 1.5 1000000.0 0.000001 {
+                route "/" methods=["GET" "HEAD"] limits=#{ a=1 b=2 }
+                empty
+            }
+            other
+        "#;
+        let document = parse(source).unwrap();
+        let emitted = to_string_with(&document, &FormatOptions::compact());
+        assert_eq!(
+            emitted,
+            concat!(
+                r#"@version "2";server "localhost" 8080 1.5 1e6 1e-6{"#,
+                r#"route "/" methods=["GET" "HEAD"] limits=#{a=1 b=2};empty};other"#,
+            )
+        );
+        assert_eq!(parse(&emitted).unwrap(), document);
+        assert_eq!(
+            to_string_with(&DocumentBuf::new(), &FormatOptions::compact()),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_comments() {
+        let mut node = NodeBuf::new("server");
+        node.leading_comments_mut()
+            .push("The main server.".to_owned());
+        node.leading_comments_mut().push(String::new());
+        node.set_trailing_comment(Some("port\n80"));
+        let mut child = NodeBuf::new("tls");
+        child.leading_comments_mut().push("Encryption".to_owned());
+        node.children_mut().push(child);
+        let mut document = DocumentBuf::new();
+        document.nodes_mut().push(node);
+
+        let emitted = to_string(&document);
+        assert_eq!(
+            emitted,
+            "// The main server.\n//\nserver {\n    // Encryption\n    tls\n} // port 80\n"
+        );
+        let parsed = parse(&emitted).unwrap();
+        assert_eq!(to_string(&parsed), emitted);
+        assert_eq!(
+            to_string_with(&document, &FormatOptions::compact()),
+            "server{tls}"
+        );
+    }
+}
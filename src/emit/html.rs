@@ -0,0 +1,195 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use super::document::to_string_with;
+use super::options::FormatOptions;
+use crate::parser::lexer::Lexer;
+use crate::parser::token::{Token, TokenKind};
+use crate::parser::ParseError;
+use crate::Document;
+
+/// The class of the `<pre>` element that [`to_html`] wraps its output in.
+pub const HTML_CLASS: &str = "gpnd";
+
+/// The class of the `<span>` for a token, or [`None`] for newlines.
+fn class(token: &Token<'_>, name: bool, key: bool) -> Option<&'static str> {
+    Some(match token.kind {
+        TokenKind::Ident | TokenKind::String if key => "key",
+        TokenKind::Ident | TokenKind::String if name => "name",
+        TokenKind::Ident | TokenKind::Keyword => "keyword",
+        TokenKind::String => "string",
+        TokenKind::Number => "number",
+        TokenKind::Directive => "directive",
+        TokenKind::Annotation => "type",
+        TokenKind::Newline | TokenKind::End => return None,
+        TokenKind::Equals
+        | TokenKind::Comma
+        | TokenKind::Semicolon
+        | TokenKind::LeftBrace
+        | TokenKind::RightBrace
+        | TokenKind::LeftBracket
+        | TokenKind::RightBracket
+        | TokenKind::MapOpen
+        | TokenKind::Separator => "punctuation",
+    })
+}
+
+/// Write `text` with the characters that are special in HTML escaped.
+fn escape(out: &mut String, text: &str) {
+    for ch in text.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(ch),
+        }
+    }
+}
+
+fn span(out: &mut String, class: &str, text: &str) {
+    write!(out, "<span class=\"{class}\">").expect("writing to a `String` does not fail");
+    escape(out, text);
+    out.push_str("</span>");
+}
+
+/// Highlight source text as HTML, with each token in a `<span>` whose class
+/// is its kind: `name`, `type`, `key`, `string`, `number`, `keyword`,
+/// `directive`, `punctuation`, or `comment`. Whitespace is kept as it is, so
+/// the output belongs in a `<pre>` element.
+///
+/// Only the lexer is used, so the source does not have to be a valid
+/// document, as long as its tokens are.
+///
+/// ```
+/// # use gpnd::emit::highlight_html;
+/// assert_eq!(
+///     highlight_html("a key=1 // one").unwrap(),
+///     concat!(
+///         r#"<span class="name">a</span> <span class="key">key</span>"#,
+///         r#"<span class="punctuation">=</span><span class="number">1</span> "#,
+///         r#"<span class="comment">// one</span>"#,
+///     )
+/// );
+/// ```
+///
+/// # Errors
+/// Fails if the source has a token that cannot be read, such as an
+/// unterminated string.
+#[allow(clippy::missing_panics_doc)]
+pub fn highlight_html(source: &str) -> Result<String, ParseError> {
+    let mut lexer = Lexer::new(source);
+    let mut tokens = Vec::new();
+    let mut comments = Vec::new();
+    loop {
+        let token = lexer.next_token()?;
+        comments.extend(lexer.take_comments());
+        tokens.push(token);
+        if token.kind == TokenKind::End {
+            break;
+        }
+    }
+
+    let mut out = String::new();
+    let mut comments = comments.into_iter().peekable();
+    let mut end = 0;
+    // Whether each enclosing bracket holds nodes, rather than values.
+    let mut blocks = Vec::new();
+    let mut line_start = true;
+    for (index, token) in tokens.iter().enumerate() {
+        let start = token.span.start.offset;
+        while let Some(comment) = comments.next_if(|comment| comment.span.start.offset < start) {
+            escape(&mut out, &source[end..comment.span.start.offset]);
+            span(&mut out, "comment", comment.text);
+            end = comment.span.end.offset;
+        }
+        escape(&mut out, &source[end..start]);
+        end = token.span.end.offset;
+
+        let in_block = blocks.last().copied().unwrap_or(true);
+        let key = tokens
+            .get(index + 1)
+            .is_some_and(|next| next.kind == TokenKind::Equals);
+        match class(token, line_start && in_block, key) {
+            Some(class) => span(&mut out, class, token.text),
+            None => escape(&mut out, token.text),
+        }
+
+        match token.kind {
+            TokenKind::LeftBrace => blocks.push(true),
+            TokenKind::LeftBracket | TokenKind::MapOpen => blocks.push(false),
+            TokenKind::RightBrace | TokenKind::RightBracket => {
+                blocks.pop();
+            }
+            _ => {}
+        }
+        // The name of a node follows its type annotation.
+        line_start = matches!(
+            token.kind,
+            TokenKind::Newline
+                | TokenKind::Annotation
+                | TokenKind::Semicolon
+                | TokenKind::LeftBrace
+                | TokenKind::RightBrace
+                | TokenKind::Separator
+        );
+    }
+    Ok(out)
+}
+
+/// Write a document as highlighted HTML, in a `<pre>` element with the
+/// [`HTML_CLASS`], see [`highlight_html`].
+///
+/// ```
+/// # use gpnd::emit::{to_html, FormatOptions};
+/// let document = gpnd::parser::parse("a \"b\"").unwrap();
+/// assert_eq!(
+///     to_html(&document, &FormatOptions::default()),
+///     "<pre class=\"gpnd\"><span class=\"name\">a</span> <span \
+///      class=\"string\">&quot;b&quot;</span>\n</pre>"
+/// );
+/// ```
+#[allow(clippy::missing_panics_doc)]
+#[must_use]
+pub fn to_html<D>(document: &D, options: &FormatOptions) -> String
+where
+    D: Document + ?Sized,
+{
+    let source = to_string_with(document, options);
+    let highlighted = highlight_html(&source).expect("emitted documents can be read back");
+    format!("<pre class=\"{HTML_CLASS}\">{highlighted}</pre>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlight_html() {
+        let source = "@version \"1\"\n/* <b> */ a:b 1.5 #inf [1 \"y\"] {\n    c m=#{ k=true } \
+                      \\\n        \"d\"=null; e\n}\n";
+        assert_eq!(
+            highlight_html(source).unwrap(),
+            concat!(
+                "<span class=\"directive\">@version</span> <span \
+                 class=\"string\">&quot;1&quot;</span>\n",
+                "<span class=\"comment\">/* &lt;b&gt; */</span> <span class=\"name\">a:b</span> ",
+                "<span class=\"number\">1.5</span> <span class=\"keyword\">#inf</span> ",
+                "<span class=\"punctuation\">[</span><span class=\"number\">1</span> ",
+                "<span class=\"string\">&quot;y&quot;</span><span class=\"punctuation\">]</span> ",
+                "<span class=\"punctuation\">{</span>\n    <span class=\"name\">c</span> ",
+                "<span class=\"key\">m</span><span class=\"punctuation\">=</span>",
+                "<span class=\"punctuation\">#{</span> <span class=\"key\">k</span>",
+                "<span class=\"punctuation\">=</span><span class=\"keyword\">true</span> ",
+                "<span class=\"punctuation\">}</span> \\\n        <span \
+                 class=\"key\">&quot;d&quot;</span>",
+                "<span class=\"punctuation\">=</span><span class=\"keyword\">null</span>",
+                "<span class=\"punctuation\">;</span> <span class=\"name\">e</span>\n",
+                "<span class=\"punctuation\">}</span>\n",
+            )
+        );
+        assert!(highlight_html("a \"b").is_err());
+    }
+}
@@ -0,0 +1,19 @@
+pub mod canonical;
+pub mod document;
+pub mod html;
+pub mod options;
+pub mod preserve;
+pub mod snapshot;
+pub mod stream;
+pub mod value;
+pub mod writer;
+
+pub use canonical::*;
+pub use document::*;
+pub use html::*;
+pub use options::*;
+pub use preserve::*;
+pub use snapshot::*;
+pub use stream::*;
+pub use value::*;
+pub use writer::*;
@@ -0,0 +1,201 @@
+use super::value::{FloatFormat, NonAscii, QuoteStyle};
+
+/// Options for how the emitter lays out a document.
+///
+/// The defaults indent with four spaces, keep every node on one line unless
+/// it is longer than 100 columns, and end lines with `\n`.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FormatOptions {
+    pub indent: Indent,
+    pub quotes: QuoteStyle,
+    /// Write names and keys that are identifiers without quotes. Otherwise
+    /// every name and key is quoted, except for node names with a namespace,
+    /// which cannot be quoted.
+    pub bare_identifiers: bool,
+    pub non_ascii: NonAscii,
+    pub floats: FloatFormat,
+    pub line_breaks: LineBreaks,
+    /// The width that [`LineBreaks::Overflow`] keeps lines within, in
+    /// characters.
+    pub max_width: usize,
+    pub newline: Newline,
+    /// Write the smallest output that reads back as the same document, with
+    /// nodes separated by semicolons and no optional whitespace. Indentation,
+    /// line breaks, and line endings are ignored.
+    pub compact: bool,
+    /// Write the comments attached to nodes, as `//` comments. Compact
+    /// output has no comments.
+    pub comments: bool,
+    /// Write the top-level nodes of a document sorted by namespace and name,
+    /// keeping nodes with the same name in order. The [`Emitter`] writes
+    /// nodes as they are given, so it ignores this.
+    ///
+    /// [`Emitter`]: super::Emitter
+    pub sort_nodes: bool,
+    /// Write the parameters of each node sorted by key, keeping parameters
+    /// with the same key in order.
+    pub sort_params: bool,
+    pub align: Align,
+}
+
+impl FormatOptions {
+    /// Options for consumers that are stricter than the parser, with every
+    /// name and key quoted, no raw strings, and only ASCII characters.
+    ///
+    /// ```
+    /// # use gpnd::emit::{to_string_with, FormatOptions};
+    /// let document = gpnd::parser::parse("café name=r\"C:\\\" { sub }").unwrap();
+    /// assert_eq!(
+    ///     to_string_with(&document, &FormatOptions::conservative()),
+    ///     "\"caf\\u{e9}\" \"name\"=\"C:\\\\\" {\n    \"sub\"\n}\n"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn conservative() -> Self {
+        Self {
+            quotes: QuoteStyle::Escaped,
+            bare_identifiers: false,
+            non_ascii: NonAscii::Escaped,
+            ..Self::default()
+        }
+    }
+
+    /// Options for the smallest output, see [`FormatOptions::compact`].
+    ///
+    /// ```
+    /// # use gpnd::emit::{to_string_with, FormatOptions};
+    /// let document = gpnd::parser::parse("a 1e20 {\n    b\n    c key=1\n}\nd").unwrap();
+    /// assert_eq!(
+    ///     to_string_with(&document, &FormatOptions::compact()),
+    ///     "a 1e20{b;c key=1};d"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn compact() -> Self {
+        Self {
+            floats: FloatFormat::Compact,
+            line_breaks: LineBreaks::Never,
+            compact: true,
+            ..Self::default()
+        }
+    }
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            indent: Indent::default(),
+            quotes: QuoteStyle::default(),
+            bare_identifiers: true,
+            non_ascii: NonAscii::default(),
+            floats: FloatFormat::default(),
+            line_breaks: LineBreaks::default(),
+            max_width: 100,
+            newline: Newline::default(),
+            compact: false,
+            comments: true,
+            sort_nodes: false,
+            sort_params: false,
+            align: Align::default(),
+        }
+    }
+}
+
+/// How child nodes and continuation lines are indented.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Indent {
+    Spaces(usize),
+    /// One tab per level, which counts as four columns toward the
+    /// [`FormatOptions::max_width`].
+    Tabs,
+}
+
+impl Indent {
+    /// The width of one level of indentation, in columns.
+    #[must_use]
+    pub fn width(self) -> usize {
+        match self {
+            Self::Spaces(width) => width,
+            Self::Tabs => 4,
+        }
+    }
+}
+
+impl Default for Indent {
+    fn default() -> Self {
+        Self::Spaces(4)
+    }
+}
+
+/// When the arguments and parameters of a node are moved onto continuation
+/// lines, each ending in `\`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum LineBreaks {
+    /// Every node is written on one line.
+    Never,
+    /// Nodes that would be longer than [`FormatOptions::max_width`] have one
+    /// argument or parameter per line.
+    #[default]
+    Overflow,
+    /// Nodes with more than one argument or parameter have one per line.
+    Always,
+}
+
+/// How the arguments and parameters of sibling nodes are lined up in
+/// columns, for nodes that are written on one line.
+///
+/// The names of the siblings are padded to the same width, and then each of
+/// their entries is padded to the widest entry at the same position. Nodes
+/// are not broken into continuation lines while aligning.
+///
+/// ```
+/// # use gpnd::emit::{to_string_with, Align, FormatOptions};
+/// let document = gpnd::parser::parse("set name=\"a\"\nset_all timeout=30 retries=3").unwrap();
+/// let options = FormatOptions {
+///     align: Align::Equals,
+///     ..FormatOptions::default()
+/// };
+/// assert_eq!(
+///     to_string_with(&document, &options),
+///     "set     name    = \"a\"\nset_all timeout = 30  retries = 3\n"
+/// );
+/// let options = FormatOptions {
+///     align: Align::Values,
+///     ..FormatOptions::default()
+/// };
+/// assert_eq!(
+///     to_string_with(&document, &options),
+///     "set     name=   \"a\"\nset_all timeout=30  retries=3\n"
+/// );
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Align {
+    /// Entries are separated by one space.
+    #[default]
+    Never,
+    /// Keys are padded so that the `=` of parameters line up, with a space
+    /// on either side, as in `key   = value`.
+    Equals,
+    /// Parameters are padded after the `=` so that their values line up, as
+    /// in `key=   value`.
+    Values,
+}
+
+/// The line ending that is written.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Newline {
+    #[default]
+    Lf,
+    CrLf,
+}
+
+impl Newline {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Lf => "\n",
+            Self::CrLf => "\r\n",
+        }
+    }
+}
@@ -0,0 +1,552 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+
+use super::document::Printer;
+use super::options::FormatOptions;
+use super::value::write_string_with;
+use crate::parser::lexer::Lexer;
+use crate::parser::token::TokenKind;
+use crate::parser::{ParseError, Parser, ParserConfig};
+use crate::{Document, DocumentBuf, Node};
+
+/// A document parsed together with its source text, so that it can be
+/// edited and written back without reformatting the parts that were not
+/// changed.
+///
+/// Nodes that are unchanged are copied from the source byte for byte,
+/// keeping their whitespace, comments, and quoting. A changed node keeps the
+/// source text of its unchanged children. Nodes that are new, or that were
+/// moved to a different parent, are written with the [`FormatOptions`].
+///
+/// Nodes are matched to the source by their spans, so the spans of parsed
+/// nodes should be left alone.
+///
+/// ```
+/// # use gpnd::emit::LosslessDocument;
+/// # use gpnd::NodeBuf;
+/// let source = "// Ports\nserver  port=80 {\n  tls   r\"on\"\n}\n";
+/// let mut document = LosslessDocument::parse(source).unwrap();
+/// document.document_mut().nodes_mut()[0]
+///     .params_mut()
+///     .insert("port", 8080);
+/// document
+///     .document_mut()
+///     .nodes_mut()
+///     .push(NodeBuf::new("client"));
+/// assert_eq!(
+///     document.to_source_string(),
+///     "// Ports\nserver port=8080 {\n  tls   r\"on\"\n}\nclient\n"
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct LosslessDocument {
+    source: String,
+    original: DocumentBuf,
+    document: DocumentBuf,
+}
+
+impl LosslessDocument {
+    /// Parse `source` as a single document.
+    ///
+    /// # Errors
+    /// Fails if the source is not valid, see [`Parser`].
+    pub fn parse<S>(source: S) -> Result<Self, ParseError>
+    where
+        S: Into<String>,
+    {
+        Self::parse_with(source, ParserConfig::default())
+    }
+
+    /// Parse `source` as a single document, with the given options.
+    ///
+    /// # Errors
+    /// Fails if the source is not valid, see [`Parser`].
+    pub fn parse_with<S>(source: S, config: ParserConfig) -> Result<Self, ParseError>
+    where
+        S: Into<String>,
+    {
+        let source = source.into();
+        let original = Parser::new(&source).with_config(config).parse()?;
+        Ok(Self {
+            document: original.clone(),
+            original,
+            source,
+        })
+    }
+
+    /// The source text that the document was parsed from.
+    #[must_use]
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    #[must_use]
+    pub fn document(&self) -> &DocumentBuf {
+        &self.document
+    }
+
+    pub fn document_mut(&mut self) -> &mut DocumentBuf {
+        &mut self.document
+    }
+
+    #[must_use]
+    pub fn into_document(self) -> DocumentBuf {
+        self.document
+    }
+
+    /// Write the edited document, with the default [`FormatOptions`] for
+    /// new nodes.
+    ///
+    /// # Errors
+    /// Fails if `f` fails.
+    pub fn write<W>(&self, f: &mut W) -> fmt::Result
+    where
+        W: Write,
+    {
+        self.write_with(f, &FormatOptions::default())
+    }
+
+    /// Write the edited document, with `options` for new nodes.
+    ///
+    /// # Errors
+    /// Fails if `f` fails.
+    pub fn write_with<W>(&self, f: &mut W, options: &FormatOptions) -> fmt::Result
+    where
+        W: Write,
+    {
+        let mut writer = Preserver {
+            source: &self.source,
+            out: String::new(),
+            options,
+        };
+        writer.document(&self.original, &self.document)?;
+        f.write_str(&writer.out)
+    }
+
+    /// Write the edited document, see [`LosslessDocument::write`].
+    #[allow(clippy::missing_panics_doc)]
+    #[must_use]
+    pub fn to_source_string(&self) -> String {
+        let mut string = String::new();
+        self.write(&mut string)
+            .expect("writing to a `String` does not fail");
+        string
+    }
+}
+
+/// Whether two nodes have the same name, arguments, and parameters in the
+/// same order, regardless of their children.
+fn header_eq(lhs: &dyn Node, rhs: &dyn Node) -> bool {
+    lhs.namespace() == rhs.namespace()
+        && lhs.name() == rhs.name()
+        && lhs.type_annotation() == rhs.type_annotation()
+        && lhs.args_iter().eq(rhs.args_iter())
+        && lhs.params_all() == rhs.params_all()
+}
+
+/// Whether a node would be written the same as it was parsed.
+fn unchanged(lhs: &dyn Node, rhs: &dyn Node) -> bool {
+    let (lhs_children, rhs_children) = (lhs.children(), rhs.children());
+    header_eq(lhs, rhs)
+        && lhs_children.len() == rhs_children.len()
+        && lhs_children
+            .into_iter()
+            .zip(rhs_children)
+            .all(|(lhs, rhs)| unchanged(lhs, rhs))
+}
+
+/// The offset of the end of the spaces and tabs at `start` in the source,
+/// up to `end`.
+fn blank_end(source: &str, start: usize, end: usize) -> usize {
+    end - source[start..end].trim_start_matches([' ', '\t']).len()
+}
+
+/// The byte offsets of the source text of a parsed node and the text
+/// around it.
+struct Piece<'node> {
+    original: &'node dyn Node,
+    /// The lines before the node, after the separator of the previous one.
+    leading: (usize, usize),
+    /// The rest of the line after the node, before its separator.
+    trailing: (usize, usize),
+    /// The newline or `;` after the node, unless it is the last one.
+    separator: Option<(usize, usize)>,
+}
+
+/// Writes an edited document, copying what it can from the source.
+struct Preserver<'src> {
+    source: &'src str,
+    out: String,
+    options: &'src FormatOptions,
+}
+
+impl Preserver<'_> {
+    fn printer(&mut self) -> Printer<'_, String> {
+        Printer {
+            out: &mut self.out,
+            options: self.options,
+        }
+    }
+
+    /// The byte offsets of the first directive and of the end of the last
+    /// one, which is where the nodes start.
+    fn directives(&self) -> (usize, usize) {
+        let mut lexer = Lexer::new(self.source);
+        let mut range = None;
+        while let Ok(token) = lexer.next_token() {
+            match token.kind {
+                TokenKind::Newline | TokenKind::Semicolon => {}
+                TokenKind::Directive => {
+                    let Ok(value) = lexer.next_token() else {
+                        break;
+                    };
+                    let start = range.map_or(token.span.start.offset, |(start, _)| start);
+                    range = Some((start, value.span.end.offset));
+                }
+                _ => break,
+            }
+        }
+        range.unwrap_or((0, 0))
+    }
+
+    /// The byte offsets of the braces around the children of a node parsed
+    /// from `start..end`, if it has a block.
+    fn block(&self, start: usize, end: usize) -> Option<(usize, usize)> {
+        let mut lexer = Lexer::new(&self.source[start..end]);
+        let mut open = None;
+        let mut close = None;
+        loop {
+            let token = lexer.next_token().ok()?;
+            match token.kind {
+                TokenKind::End => break,
+                TokenKind::LeftBrace if open.is_none() => open = Some(token.span.start.offset),
+                TokenKind::RightBrace => close = Some(token.span.start.offset),
+                _ => {}
+            }
+        }
+        Some((start + open?, start + close?))
+    }
+
+    fn document(&mut self, original: &DocumentBuf, document: &DocumentBuf) -> fmt::Result {
+        let (start, end) = self.directives();
+        if document.version() == original.version() {
+            self.out.push_str(&self.source[..end]);
+        } else {
+            self.out.push_str(&self.source[..start]);
+            if let Some(version) = document.version() {
+                self.out.push_str("@version ");
+                write_string_with(&mut self.out, version, self.options)?;
+                if end == 0 {
+                    self.printer().newline()?;
+                }
+            }
+        }
+        self.nodes(
+            original.nodes(),
+            document.nodes(),
+            (end, self.source.len()),
+            0,
+        )
+    }
+
+    /// The byte offsets of the first newline, or `;` and the spaces after
+    /// it, between `start` and `end`, in the text between two nodes.
+    fn separator(&self, start: usize, end: usize) -> Option<(usize, usize)> {
+        let mut lexer = Lexer::new(&self.source[start..end]);
+        loop {
+            let token = lexer.next_token().ok()?;
+            let (separator_start, separator_end) = (
+                start + token.span.start.offset,
+                start + token.span.end.offset,
+            );
+            match token.kind {
+                TokenKind::End => return None,
+                TokenKind::Newline => return Some((separator_start, separator_end)),
+                TokenKind::Semicolon => {
+                    return Some((separator_start, blank_end(self.source, separator_end, end)));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Split the `region` of the source that `originals` were parsed from
+    /// into the text of each node with its comments, and the text before
+    /// the first node and after the last that belongs to neither.
+    fn pieces<'node>(
+        &self,
+        originals: Vec<&'node dyn Node>,
+        region: (usize, usize),
+    ) -> (Vec<Piece<'node>>, usize, usize) {
+        let mut pieces: Vec<Piece<'node>> = Vec::new();
+        let mut head = region.0;
+        let mut end = region.0;
+        for original in originals {
+            let Some(span) = original.span() else {
+                continue;
+            };
+            let (start, separator) = (span.start.offset, self.separator(end, span.start.offset));
+            let leading = match (pieces.last_mut(), separator) {
+                (Some(previous), Some((separator_start, separator_end))) => {
+                    previous.trailing.1 = separator_start;
+                    previous.separator = Some((separator_start, separator_end));
+                    separator_end
+                }
+                // The rest of the line of the opening brace or of the last
+                // directive is not moved with the first node.
+                (None, Some((_, separator_end))) if region.0 > 0 => {
+                    head = separator_end;
+                    separator_end
+                }
+                (None, None) if region.0 > 0 => {
+                    head = blank_end(self.source, end, start);
+                    head
+                }
+                _ => end,
+            };
+            end = span.end.offset;
+            pieces.push(Piece {
+                original,
+                leading: (leading, start),
+                trailing: (end, end),
+                separator: None,
+            });
+        }
+        let tail = match pieces.last_mut() {
+            Some(last) => {
+                last.trailing.1 = match self.separator(end, region.1) {
+                    Some((separator_start, _)) => separator_start,
+                    None => end + self.source[end..region.1].trim_end().len(),
+                };
+                last.trailing.1
+            }
+            None => region.0,
+        };
+        (pieces, head, tail)
+    }
+
+    /// Write `nodes`, which replace `originals`, the nodes parsed from the
+    /// `region` of the source.
+    ///
+    /// The comments on the lines before a parsed node, and after it on the
+    /// same line, are written with it wherever it is moved.
+    fn nodes(
+        &mut self,
+        originals: Vec<&dyn Node>,
+        nodes: Vec<&dyn Node>,
+        region: (usize, usize),
+        depth: usize,
+    ) -> fmt::Result {
+        let (pieces, head, tail) = self.pieces(originals, region);
+        let mut inline = !self.source[tail..region.1].contains('\n');
+        let mut end = region.0;
+        for piece in &pieces {
+            inline &= !self.source[end..piece.leading.1].contains('\n');
+            end = piece.trailing.0;
+        }
+
+        self.out.push_str(&self.source[region.0..head]);
+        // The separator that followed the last node written in the source.
+        let mut pending = None;
+        for (index, node) in nodes.into_iter().enumerate() {
+            let piece = node.span().and_then(|span| {
+                pieces
+                    .iter()
+                    .find(|piece| piece.leading.1 == span.start.offset)
+            });
+            if let Some(piece) = piece {
+                if index > 0 {
+                    match pending.take() {
+                        Some((start, end)) => self.out.push_str(&self.source[start..end]),
+                        None if inline => self.out.push_str("; "),
+                        None => self.printer().newline()?,
+                    }
+                }
+                self.out
+                    .push_str(&self.source[piece.leading.0..piece.leading.1]);
+                self.node(piece.original, node, depth)?;
+                self.out
+                    .push_str(&self.source[piece.trailing.0..piece.trailing.1]);
+                pending = piece.separator;
+            } else if inline {
+                let separator = match pending.take() {
+                    _ if index == 0 => " ",
+                    Some((start, end)) => &self.source[start..end],
+                    None => "; ",
+                };
+                self.out.push_str(separator);
+                self.fresh(node, depth)?;
+            } else {
+                pending = None;
+                if !self.out.is_empty() && !self.out.ends_with('\n') {
+                    self.printer().newline()?;
+                }
+                self.printer().indent(depth)?;
+                self.fresh(node, depth)?;
+            }
+        }
+        self.out.push_str(&self.source[tail..region.1]);
+        Ok(())
+    }
+
+    /// Write `node`, which replaces `original`.
+    fn node(&mut self, original: &dyn Node, node: &dyn Node, depth: usize) -> fmt::Result {
+        let span = original.span().expect("only nodes with spans are matched");
+        let (start, end) = (span.start.offset, span.end.offset);
+        if unchanged(original, node) {
+            self.out.push_str(&self.source[start..end]);
+            return Ok(());
+        }
+
+        let children = node.children();
+        let block = self.block(start, end);
+        match block {
+            Some((open, _)) if header_eq(original, node) && children.is_empty() => {
+                self.out.push_str(self.source[start..open].trim_end());
+            }
+            Some((open, close)) if !children.is_empty() => {
+                if header_eq(original, node) {
+                    self.out.push_str(&self.source[start..=open]);
+                } else {
+                    self.header(node)?;
+                    self.out.push_str(" {");
+                }
+                self.nodes(original.children(), children, (open + 1, close), depth + 1)?;
+                self.out.push('}');
+            }
+            // The comments of the node are still in the source before it.
+            _ => {
+                let options = FormatOptions {
+                    comments: false,
+                    ..*self.options
+                };
+                self.fresh_with(node, depth, &options)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the name, arguments, and parameters of a node on one line.
+    fn header(&mut self, node: &dyn Node) -> fmt::Result {
+        let printer = self.printer();
+        let mut header = printer.name(node)?;
+        for entry in printer.entries(node)? {
+            header.push(' ');
+            header.push_str(&entry);
+        }
+        self.out.push_str(&header);
+        Ok(())
+    }
+
+    /// Write a node that was not parsed from the source, without indenting
+    /// its first line or ending its last.
+    fn fresh(&mut self, node: &dyn Node, depth: usize) -> fmt::Result {
+        self.fresh_with(node, depth, self.options)
+    }
+
+    fn fresh_with(
+        &mut self,
+        node: &dyn Node,
+        depth: usize,
+        options: &FormatOptions,
+    ) -> fmt::Result {
+        let mut text = String::new();
+        Printer {
+            out: &mut text,
+            options,
+        }
+        .node(node, depth)?;
+        let text = text
+            .trim_start_matches([' ', '\t'])
+            .strip_suffix(options.newline.as_str())
+            .unwrap_or(&text);
+        self.out.push_str(text);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NodeBuf;
+
+    #[test]
+    fn test_lossless_unchanged() {
+        let source = "@version  r\"1\" // v\n\n/* a */ a  1 ;b{c;d}\n";
+        let document = LosslessDocument::parse(source).unwrap();
+        assert_eq!(document.to_source_string(), source);
+    }
+
+    #[test]
+    fn test_lossless_edits() {
+        let source =
+            "server {\n  // Routes\n  route  \"/\"\n\n  route \"/old\"\n  tls   on=true\n}\n";
+        let mut document = LosslessDocument::parse(source).unwrap();
+        let server = &mut document.document_mut().nodes_mut()[0];
+        server.children_mut().remove(1);
+        server.children_mut()[1].params_mut().insert("on", false);
+        server.children_mut().insert(0, NodeBuf::new("first"));
+        assert_eq!(
+            document.to_source_string(),
+            "server {\n    first\n  // Routes\n  route  \"/\"\n  tls on=false\n}\n"
+        );
+
+        let mut document = LosslessDocument::parse("a { b; c }").unwrap();
+        let a = &mut document.document_mut().nodes_mut()[0];
+        a.children_mut().swap(0, 1);
+        a.children_mut().push(NodeBuf::new("d"));
+        assert_eq!(document.to_source_string(), "a { c; b; d }");
+        assert_eq!(
+            crate::parser::parse(&document.to_source_string()).unwrap(),
+            *document.document()
+        );
+
+        let mut document = LosslessDocument::parse("@version \"1\"\na\n").unwrap();
+        document.document_mut().set_version(Some("2"));
+        document.document_mut().nodes_mut()[0]
+            .children_mut()
+            .push(NodeBuf::new("b"));
+        assert_eq!(
+            document.to_source_string(),
+            "@version \"2\"\na {\n    b\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_lossless_comments() {
+        let source = "// head\na 1 // trailing a\n\n/* b */ b 2; c 3\nd {\n  // e\n  e // \
+                      trailing e\n  f\n}\n";
+
+        let mut document = LosslessDocument::parse(source).unwrap();
+        document.document_mut().nodes_mut().remove(0);
+        assert_eq!(
+            document.to_source_string(),
+            "\n/* b */ b 2; c 3\nd {\n  // e\n  e // trailing e\n  f\n}\n"
+        );
+
+        let mut document = LosslessDocument::parse(source).unwrap();
+        document.document_mut().nodes_mut().remove(1);
+        document.document_mut().nodes_mut()[2]
+            .children_mut()
+            .remove(0);
+        assert_eq!(
+            document.to_source_string(),
+            "// head\na 1 // trailing a\nc 3\nd {\n  f\n}\n"
+        );
+
+        let mut document = LosslessDocument::parse(source).unwrap();
+        let nodes = document.document_mut().nodes_mut();
+        nodes.swap(0, 2);
+        nodes[3].children_mut().swap(0, 1);
+        assert_eq!(
+            document.to_source_string(),
+            "c 3\n\n/* b */ b 2; // head\na 1 // trailing a\nd {\n  f\n  // e\n  e // trailing \
+             e\n}\n"
+        );
+        assert_eq!(
+            crate::parser::parse(&document.to_source_string()).unwrap(),
+            *document.document()
+        );
+    }
+}
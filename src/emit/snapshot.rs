@@ -0,0 +1,197 @@
+use alloc::format;
+use alloc::string::String;
+use core::fmt::{self, Write};
+
+use crate::{Document, DocumentBuf, Node, Value};
+
+struct Snapshot {
+    out: String,
+    depth: usize,
+}
+
+impl Snapshot {
+    fn line(&mut self, args: fmt::Arguments<'_>) -> fmt::Result {
+        for _ in 0..self.depth {
+            self.out.push_str("    ");
+        }
+        self.out.write_fmt(args)?;
+        self.out.push('\n');
+        Ok(())
+    }
+
+    fn nested<F>(&mut self, write: F) -> fmt::Result
+    where
+        F: FnOnce(&mut Self) -> fmt::Result,
+    {
+        self.depth += 1;
+        let result = write(self);
+        self.depth -= 1;
+        result
+    }
+
+    /// Write a value after `prefix`, with the items of lists and maps on the
+    /// lines that follow.
+    fn value(&mut self, prefix: fmt::Arguments<'_>, value: &Value<'_>) -> fmt::Result {
+        let kind = value.kind();
+        match value {
+            Value::U8(inner) => self.line(format_args!("{prefix}{kind} {inner}")),
+            Value::U16(inner) => self.line(format_args!("{prefix}{kind} {inner}")),
+            Value::U32(inner) => self.line(format_args!("{prefix}{kind} {inner}")),
+            Value::U64(inner) | Value::Size(inner) | Value::Duration(inner) => {
+                self.line(format_args!("{prefix}{kind} {inner}"))
+            }
+            Value::Uint(inner) => self.line(format_args!("{prefix}{kind} {inner}")),
+            Value::I8(inner) => self.line(format_args!("{prefix}{kind} {inner}")),
+            Value::I16(inner) => self.line(format_args!("{prefix}{kind} {inner}")),
+            Value::I32(inner) => self.line(format_args!("{prefix}{kind} {inner}")),
+            Value::I64(inner) => self.line(format_args!("{prefix}{kind} {inner}")),
+            Value::Int(inner) => self.line(format_args!("{prefix}{kind} {inner}")),
+            Value::F32(inner) => self.line(format_args!("{prefix}{kind} {inner:?}")),
+            Value::F64(inner) => self.line(format_args!("{prefix}{kind} {inner:?}")),
+            Value::Bool(inner) => self.line(format_args!("{prefix}{kind} {inner}")),
+            Value::String(inner) => self.line(format_args!("{prefix}{kind} {inner:?}")),
+            Value::Null => self.line(format_args!("{prefix}{kind}")),
+            // Lists are written the same whether they are owned or
+            // borrowed.
+            Value::List(items) => self.items(prefix, items),
+            Value::Slice(items) => self.items(prefix, items),
+            Value::Map(entries) => {
+                self.line(format_args!("{prefix}{kind}"))?;
+                self.nested(|this| {
+                    entries
+                        .iter()
+                        .try_for_each(|(key, value)| this.value(format_args!("{key:?} = "), value))
+                })
+            }
+        }
+    }
+
+    fn items(&mut self, prefix: fmt::Arguments<'_>, items: &[Value<'_>]) -> fmt::Result {
+        self.line(format_args!("{prefix}List"))?;
+        self.nested(|this| {
+            items
+                .iter()
+                .try_for_each(|item| this.value(format_args!(""), item))
+        })
+    }
+
+    fn node(&mut self, node: &dyn Node) -> fmt::Result {
+        let mut header = format!("node {:?}", node.name());
+        if let Some(namespace) = node.namespace() {
+            write!(header, " namespace={namespace:?}")?;
+        }
+        if let Some(type_annotation) = node.type_annotation() {
+            write!(header, " type={type_annotation:?}")?;
+        }
+        self.line(format_args!("{header}"))?;
+        self.nested(|this| {
+            for arg in node.args_iter() {
+                this.value(format_args!("arg "), &arg)?;
+            }
+            let mut params = node.params_all();
+            // A stable sort, so that repeated keys stay in the order they
+            // were written.
+            params.sort_by_key(|(key, _)| *key);
+            for (key, value) in &params {
+                this.value(format_args!("param {key:?} = "), value)?;
+            }
+            node.children()
+                .into_iter()
+                .try_for_each(|child| this.node(child))
+        })
+    }
+}
+
+/// Write a document for snapshot tests, such as with `insta`, in a form that
+/// is meant to be compared, not read back or shown to users.
+///
+/// Unlike the other writers, whose output may change as the format evolves,
+/// this one is kept stable. Every node, argument, parameter, list item, and
+/// map entry is on its own line, so that diffs point at what changed, and
+/// every value is written with its [`ValueKind`](crate::ValueKind), except
+/// that slices are written as lists:
+///
+/// - the version of the document, if any, comes first, as `version "1.0"`;
+/// - nodes are written as `node "name"`, with `namespace="ns"` and
+///   `type="type"` if they have them, followed by their arguments, then their
+///   parameters sorted by key, then their children, indented by four spaces;
+/// - arguments are written as `arg I64 1`, parameters as `param "key" = String
+///   "value"`, and the items of lists and the entries of maps on the lines
+///   after them, indented;
+/// - strings, names, and keys are quoted and escaped as by `{:?}`, and floats
+///   are written as by `{:?}`, so that `-0.0` and `NaN` are visible.
+///
+/// Comments, anchors, spans, and sources are left out.
+///
+/// ```
+/// # use gpnd::emit::to_test_string;
+/// let document = gpnd::parser::parse("server port=8080 hosts=[\"a\"] \"main\"").unwrap();
+/// assert_eq!(
+///     to_test_string(&document),
+///     "node \"server\"\n    arg String \"main\"\n    param \"hosts\" = List\n        String \
+///      \"a\"\n    param \"port\" = I64 8080\n"
+/// );
+/// ```
+#[allow(clippy::missing_panics_doc)]
+#[must_use]
+pub fn to_test_string<D>(document: &D) -> String
+where
+    D: Document + ?Sized,
+{
+    let mut snapshot = Snapshot {
+        out: String::new(),
+        depth: 0,
+    };
+    let mut write = || -> fmt::Result {
+        if let Some(version) = document.version() {
+            snapshot.line(format_args!("version {version:?}"))?;
+        }
+        document
+            .nodes()
+            .into_iter()
+            .try_for_each(|node| snapshot.node(node))
+    };
+    write().expect("writing to a `String` does not fail");
+    snapshot.out
+}
+
+impl DocumentBuf {
+    /// Write this document for snapshot tests, see [`to_test_string`].
+    #[must_use]
+    pub fn to_test_string(&self) -> String {
+        to_test_string(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::parse;
+
+    #[test]
+    fn test_to_test_string() {
+        let document = parse(concat!(
+            "@version \"1\"\n",
+            "http:route \"/\" -0.0 z=1 a=#{ b=null a=[true 1.5] } z=\"\\n\" {\n",
+            "    child\n",
+            "}\n",
+        ))
+        .unwrap();
+        assert_eq!(
+            document.to_test_string(),
+            concat!(
+                "version \"1\"\n",
+                "node \"route\" namespace=\"http\"\n",
+                "    arg String \"/\"\n",
+                "    arg F64 -0.0\n",
+                "    param \"a\" = Map\n",
+                "        \"a\" = List\n",
+                "            Bool true\n",
+                "            F64 1.5\n",
+                "        \"b\" = Null\n",
+                "    param \"z\" = I64 1\n",
+                "    param \"z\" = String \"\\n\"\n",
+                "    node \"child\"\n",
+            )
+        );
+    }
+}
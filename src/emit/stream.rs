@@ -0,0 +1,397 @@
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+#[cfg(feature = "std")]
+use std::io;
+
+use super::document::Printer;
+use super::options::FormatOptions;
+use crate::parser::Event;
+use crate::Node;
+
+/// Writes a document one top-level node at a time, so that the output never
+/// has to be held in memory at once.
+///
+/// ```
+/// # use gpnd::emit::Emitter;
+/// # use gpnd::NodeBuf;
+/// let mut emitter = Emitter::new(String::new());
+/// emitter.version("1").unwrap();
+/// for index in 0..3 {
+///     let mut node = NodeBuf::new("item");
+///     node.args_mut().push(index.into());
+///     emitter.emit_node(&node).unwrap();
+/// }
+/// assert_eq!(
+///     emitter.into_inner(),
+///     "@version \"1\"\nitem 0\nitem 1\nitem 2\n"
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct Emitter<W> {
+    out: W,
+    options: FormatOptions,
+    nodes: usize,
+    /// The nodes started by events and not ended yet.
+    open: Vec<OpenNode>,
+}
+
+/// A node started by [`Emitter::emit_event`].
+#[derive(Clone, Debug)]
+struct OpenNode {
+    /// Whether the block of children has been opened.
+    braced: bool,
+    trailing_comment: Option<String>,
+}
+
+impl<W> Emitter<W>
+where
+    W: Write,
+{
+    /// Write to `out` with the default [`FormatOptions`].
+    pub fn new(out: W) -> Self {
+        Self::with_options(out, FormatOptions::default())
+    }
+
+    pub fn with_options(out: W, options: FormatOptions) -> Self {
+        Self {
+            out,
+            options,
+            nodes: 0,
+            open: Vec::new(),
+        }
+    }
+
+    /// Write a `@version` directive.
+    ///
+    /// # Errors
+    /// Fails if the writer fails.
+    ///
+    /// # Panics
+    /// If a node has already been written, since directives must come first.
+    pub fn version(&mut self, version: &str) -> fmt::Result {
+        assert_eq!(self.nodes, 0, "the version must be written before any node");
+        self.printer().version(version)
+    }
+
+    /// Write a top-level node and its children.
+    ///
+    /// # Errors
+    /// Fails if the writer fails.
+    pub fn emit_node(&mut self, node: &dyn Node) -> fmt::Result {
+        if self.options.compact && self.nodes > 0 {
+            self.out.write_char(';')?;
+        }
+        self.nodes += 1;
+        self.printer().node(node, 0)
+    }
+
+    /// Write each of `nodes`, see [`Emitter::emit_node`].
+    ///
+    /// # Errors
+    /// Fails if the writer fails.
+    pub fn emit_nodes<'node, I>(&mut self, nodes: I) -> fmt::Result
+    where
+        I: IntoIterator<Item = &'node dyn Node>,
+    {
+        nodes.into_iter().try_for_each(|node| self.emit_node(node))
+    }
+
+    /// Write an event from a stream, such as from
+    /// [`Parser::events`](crate::parser::Parser::events), so that a node can
+    /// be written before its children are known.
+    ///
+    /// Nodes written by events are not aligned to their siblings.
+    ///
+    /// ```
+    /// # use gpnd::emit::Emitter;
+    /// # use gpnd::parser::Parser;
+    /// let mut emitter = Emitter::new(String::new());
+    /// for event in Parser::new("a { b; c }").events() {
+    ///     emitter.emit_event(&event.unwrap()).unwrap();
+    /// }
+    /// assert_eq!(emitter.into_inner(), "a {\n    b\n    c\n}\n");
+    /// ```
+    ///
+    /// # Errors
+    /// Fails if the writer fails.
+    ///
+    /// # Panics
+    /// If there are more [`Event::End`]s than [`Event::Start`]s, or an
+    /// [`Event::Version`] comes after a node.
+    pub fn emit_event(&mut self, event: &Event) -> fmt::Result {
+        let compact = self.options.compact;
+        match event {
+            Event::Version(version) => self.version(version),
+            Event::Start(node) => {
+                match self.open.last_mut() {
+                    None => {
+                        if compact && self.nodes > 0 {
+                            self.out.write_char(';')?;
+                        }
+                        self.nodes += 1;
+                    }
+                    Some(parent) if compact => {
+                        self.out.write_char(if parent.braced { ';' } else { '{' })?;
+                        parent.braced = true;
+                    }
+                    Some(parent) if !parent.braced => {
+                        parent.braced = true;
+                        self.out.write_str(" {")?;
+                        self.printer().newline()?;
+                    }
+                    Some(_) => {}
+                }
+                let depth = self.open.len();
+                self.open.push(OpenNode {
+                    braced: false,
+                    trailing_comment: node.trailing_comment().map(ToOwned::to_owned),
+                });
+                self.printer().open_node(&**node, depth)
+            }
+            Event::End => {
+                let node = self.open.pop().expect("an end event without a start");
+                if compact {
+                    return if node.braced {
+                        self.out.write_char('}')
+                    } else {
+                        Ok(())
+                    };
+                }
+                let depth = self.open.len();
+                let mut printer = self.printer();
+                if node.braced {
+                    printer.indent(depth)?;
+                    printer.out.write_char('}')?;
+                }
+                if let Some(comment) = node.trailing_comment.filter(|_| printer.options.comments) {
+                    printer.out.write_char(' ')?;
+                    printer.comment(&comment)?;
+                }
+                printer.newline()
+            }
+        }
+    }
+
+    /// The number of top-level nodes written so far.
+    #[must_use]
+    pub fn node_count(&self) -> usize {
+        self.nodes
+    }
+
+    #[must_use]
+    pub fn get_ref(&self) -> &W {
+        &self.out
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.out
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+
+    fn printer(&mut self) -> Printer<'_, W> {
+        Printer {
+            out: &mut self.out,
+            options: &self.options,
+        }
+    }
+}
+
+/// Adapts an [`io::Write`] to a [`fmt::Write`], keeping the I/O error that
+/// a [`fmt::Error`] stands for.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub(super) struct IoAdapter<W> {
+    pub(super) inner: W,
+    pub(super) error: Option<io::Error>,
+}
+
+#[cfg(feature = "std")]
+impl<W> IoAdapter<W> {
+    pub(super) fn new(inner: W) -> Self {
+        Self { inner, error: None }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W> Write for IoAdapter<W>
+where
+    W: io::Write,
+{
+    fn write_str(&mut self, string: &str) -> fmt::Result {
+        self.inner.write_all(string.as_bytes()).map_err(|error| {
+            self.error = Some(error);
+            fmt::Error
+        })
+    }
+}
+
+/// An [`Emitter`] that writes to an [`io::Write`], such as a file or a
+/// socket.
+///
+/// Nothing is buffered here, so wrap unbuffered writers in an
+/// [`io::BufWriter`].
+///
+/// ```
+/// # use gpnd::emit::IoEmitter;
+/// # use gpnd::NodeBuf;
+/// let mut emitter = IoEmitter::new(Vec::new());
+/// emitter.emit_node(&NodeBuf::new("item")).unwrap();
+/// assert_eq!(emitter.into_inner(), b"item\n");
+/// ```
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct IoEmitter<W> {
+    emitter: Emitter<IoAdapter<W>>,
+}
+
+#[cfg(feature = "std")]
+impl<W> IoEmitter<W>
+where
+    W: io::Write,
+{
+    /// Write to `out` with the default [`FormatOptions`].
+    pub fn new(out: W) -> Self {
+        Self::with_options(out, FormatOptions::default())
+    }
+
+    pub fn with_options(out: W, options: FormatOptions) -> Self {
+        Self {
+            emitter: Emitter::with_options(IoAdapter::new(out), options),
+        }
+    }
+
+    /// Write a `@version` directive, see [`Emitter::version`].
+    ///
+    /// # Errors
+    /// Fails if the writer fails.
+    ///
+    /// # Panics
+    /// If a node has already been written, since directives must come first.
+    pub fn version(&mut self, version: &str) -> io::Result<()> {
+        let result = self.emitter.version(version);
+        self.check(result)
+    }
+
+    /// Write a top-level node and its children.
+    ///
+    /// # Errors
+    /// Fails if the writer fails.
+    pub fn emit_node(&mut self, node: &dyn Node) -> io::Result<()> {
+        let result = self.emitter.emit_node(node);
+        self.check(result)
+    }
+
+    /// Write each of `nodes`, see [`IoEmitter::emit_node`].
+    ///
+    /// # Errors
+    /// Fails if the writer fails.
+    pub fn emit_nodes<'node, I>(&mut self, nodes: I) -> io::Result<()>
+    where
+        I: IntoIterator<Item = &'node dyn Node>,
+    {
+        nodes.into_iter().try_for_each(|node| self.emit_node(node))
+    }
+
+    /// Write an event from a stream, see [`Emitter::emit_event`].
+    ///
+    /// # Errors
+    /// Fails if the writer fails.
+    ///
+    /// # Panics
+    /// If there are more [`Event::End`]s than [`Event::Start`]s, or an
+    /// [`Event::Version`] comes after a node.
+    pub fn emit_event(&mut self, event: &Event) -> io::Result<()> {
+        let result = self.emitter.emit_event(event);
+        self.check(result)
+    }
+
+    /// Flush the writer.
+    ///
+    /// # Errors
+    /// Fails if the writer fails.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.emitter.get_mut().inner.flush()
+    }
+
+    /// The number of top-level nodes written so far.
+    #[must_use]
+    pub fn node_count(&self) -> usize {
+        self.emitter.node_count()
+    }
+
+    #[must_use]
+    pub fn get_ref(&self) -> &W {
+        &self.emitter.get_ref().inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.emitter.get_mut().inner
+    }
+
+    pub fn into_inner(self) -> W {
+        self.emitter.into_inner().inner
+    }
+
+    fn check(&mut self, result: fmt::Result) -> io::Result<()> {
+        result.map_err(|fmt::Error| {
+            self.emitter
+                .get_mut()
+                .error
+                .take()
+                .unwrap_or_else(|| io::Error::other("formatting failed"))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+    use crate::Document;
+
+    #[test]
+    fn test_emitter() {
+        let document = parse("@version \"2\"\na 1 { b }\nc key=true").unwrap();
+        let mut emitter = Emitter::with_options(String::new(), FormatOptions::compact());
+        emitter.version("2").unwrap();
+        emitter.emit_nodes(document.nodes()).unwrap();
+        assert_eq!(emitter.node_count(), 2);
+        assert_eq!(emitter.into_inner(), "@version \"2\";a 1{b};c key=true");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_io_emitter() {
+        let document = parse("@version \"2\"\na 1 { b }\nc key=true").unwrap();
+        let mut emitter = IoEmitter::new(Vec::new());
+        emitter.version("2").unwrap();
+        emitter.emit_nodes(document.nodes()).unwrap();
+        let output = String::from_utf8(emitter.into_inner()).unwrap();
+        assert_eq!(output, crate::emit::to_string(&document));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_emitter_io_error() {
+        struct Full;
+
+        impl io::Write for Full {
+            fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+                Err(io::Error::new(io::ErrorKind::StorageFull, "full"))
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut emitter = IoEmitter::new(Full);
+        let error = emitter.emit_node(&crate::NodeBuf::new("a")).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::StorageFull);
+    }
+}
@@ -0,0 +1,354 @@
+use alloc::format;
+use alloc::string::String;
+use core::fmt::{self, Write};
+
+use super::document::is_bare_ident;
+use super::options::FormatOptions;
+use crate::parser::literal::{write_duration, write_size};
+use crate::Value;
+
+/// How floats are written.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum FloatFormat {
+    /// The fewest digits that read back as the same float, with at least one
+    /// fractional digit so that the value is not read back as an integer.
+    #[default]
+    Shortest,
+    /// A fixed number of fractional digits, rounding if necessary.
+    ///
+    /// With zero digits the value is written without a decimal point, and
+    /// will be read back as an integer.
+    Fixed(usize),
+    /// Scientific notation, with the fewest mantissa digits that read back as
+    /// the same float.
+    Scientific,
+    /// Whichever of `Shortest` and `Scientific` has fewer characters.
+    Compact,
+}
+
+/// How strings are quoted.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum QuoteStyle {
+    /// Quoted strings, with escapes for quotes, backslashes, and control
+    /// characters.
+    #[default]
+    Escaped,
+    /// Raw strings, such as `r#"say "hi""#`, for strings that contain quotes
+    /// or backslashes, so that they can be written without escapes. Other
+    /// strings are quoted as with `Escaped`.
+    Raw,
+}
+
+/// How characters outside of ASCII are written in strings.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum NonAscii {
+    /// As they are.
+    #[default]
+    Verbatim,
+    /// As `\u{...}` escapes, for consumers that only accept ASCII. Strings
+    /// that need escapes are never written as raw strings.
+    Escaped,
+}
+
+/// Write a float, or the keyword for a non-finite float.
+fn write_float<W, F>(f: &mut W, float: F, format: FloatFormat) -> fmt::Result
+where
+    W: Write,
+    F: Into<f64> + fmt::Display + fmt::LowerExp + Copy,
+{
+    let wide: f64 = float.into();
+    if wide.is_nan() {
+        return f.write_str("#nan");
+    } else if wide.is_infinite() {
+        return f.write_str(if wide > 0.0 { "#inf" } else { "#-inf" });
+    }
+    let shortest = || {
+        let mut buf = String::new();
+        write!(buf, "{float}")?;
+        if !buf.contains(['.', 'e']) {
+            buf.push_str(".0");
+        }
+        Ok(buf)
+    };
+    match format {
+        FloatFormat::Shortest => f.write_str(&shortest()?),
+        FloatFormat::Fixed(precision) => write!(f, "{float:.precision$}"),
+        FloatFormat::Scientific => write!(f, "{float:e}"),
+        FloatFormat::Compact => {
+            let shortest = shortest()?;
+            let scientific = format!("{float:e}");
+            f.write_str(if scientific.len() < shortest.len() {
+                &scientific
+            } else {
+                &shortest
+            })
+        }
+    }
+}
+
+/// Write a string as a quoted literal, escaping as needed.
+pub fn write_quoted<W>(f: &mut W, string: &str) -> fmt::Result
+where
+    W: Write,
+{
+    write_escaped(f, string, NonAscii::Verbatim)
+}
+
+fn write_escaped<W>(f: &mut W, string: &str, non_ascii: NonAscii) -> fmt::Result
+where
+    W: Write,
+{
+    f.write_char('"')?;
+    for ch in string.chars() {
+        match ch {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            ch if ch.is_control() || (non_ascii == NonAscii::Escaped && !ch.is_ascii()) => {
+                write!(f, "\\u{{{:x}}}", u32::from(ch))?;
+            }
+            ch => f.write_char(ch)?,
+        }
+    }
+    f.write_char('"')
+}
+
+/// Write a string as a raw literal, with as few `#` as are needed.
+fn write_raw<W>(f: &mut W, string: &str) -> fmt::Result
+where
+    W: Write,
+{
+    let hashes = (0..=string.len())
+        .find(|&count| !string.contains(&format!("\"{}", "#".repeat(count))))
+        .unwrap_or_default();
+    let hashes = "#".repeat(hashes);
+    write!(f, "r{hashes}\"{string}\"{hashes}")
+}
+
+/// Write a string with the given [`QuoteStyle`].
+pub fn write_string<W>(f: &mut W, string: &str, quotes: QuoteStyle) -> fmt::Result
+where
+    W: Write,
+{
+    let options = FormatOptions {
+        quotes,
+        ..FormatOptions::default()
+    };
+    write_string_with(f, string, &options)
+}
+
+/// Write a string with the [`QuoteStyle`] and [`NonAscii`] escapes of
+/// `options`.
+pub fn write_string_with<W>(f: &mut W, string: &str, options: &FormatOptions) -> fmt::Result
+where
+    W: Write,
+{
+    let escapes = options.non_ascii == NonAscii::Escaped && !string.is_ascii();
+    match options.quotes {
+        QuoteStyle::Raw if string.contains(['"', '\\']) && !escapes => write_raw(f, string),
+        _ => write_escaped(f, string, options.non_ascii),
+    }
+}
+
+/// Write a node name, parameter key, or map key, which is bare if it is an
+/// identifier and [`FormatOptions::bare_identifiers`] is set, and otherwise
+/// quoted.
+pub fn write_ident<W>(f: &mut W, text: &str, options: &FormatOptions) -> fmt::Result
+where
+    W: Write,
+{
+    if options.bare_identifiers && is_bare_ident(text) {
+        f.write_str(text)
+    } else {
+        write_string_with(f, text, options)
+    }
+}
+
+/// Write a value as it would appear in a document.
+pub fn write_value<W>(f: &mut W, value: &Value<'_>, floats: FloatFormat) -> fmt::Result
+where
+    W: Write,
+{
+    write_value_styled(f, value, floats, QuoteStyle::Escaped)
+}
+
+/// Write a value as it would appear in a document, quoting strings with
+/// `quotes`.
+pub fn write_value_styled<W>(
+    f: &mut W,
+    value: &Value<'_>,
+    floats: FloatFormat,
+    quotes: QuoteStyle,
+) -> fmt::Result
+where
+    W: Write,
+{
+    let options = FormatOptions {
+        quotes,
+        floats,
+        bare_identifiers: false,
+        ..FormatOptions::default()
+    };
+    write_value_with(f, value, &options)
+}
+
+/// Write a value as it would appear in a document, with the float format
+/// and quoting of `options`.
+pub fn write_value_with<W>(f: &mut W, value: &Value<'_>, options: &FormatOptions) -> fmt::Result
+where
+    W: Write,
+{
+    match value {
+        Value::U8(inner) => write!(f, "{inner}"),
+        Value::U16(inner) => write!(f, "{inner}"),
+        Value::U32(inner) => write!(f, "{inner}"),
+        Value::U64(inner) => write!(f, "{inner}"),
+        Value::Uint(inner) => write!(f, "{inner}"),
+        Value::I8(inner) => write!(f, "{inner}"),
+        Value::I16(inner) => write!(f, "{inner}"),
+        Value::I32(inner) => write!(f, "{inner}"),
+        Value::I64(inner) => write!(f, "{inner}"),
+        Value::Int(inner) => write!(f, "{inner}"),
+        Value::F32(inner) => write_float(f, *inner, options.floats),
+        Value::F64(inner) => write_float(f, *inner, options.floats),
+        Value::Bool(inner) => write!(f, "{inner}"),
+        Value::Size(inner) => write_size(f, *inner),
+        Value::Duration(inner) => write_duration(f, *inner),
+        Value::String(inner) => write_string_with(f, inner, options),
+        Value::List(_) | Value::Slice(_) => {
+            f.write_char('[')?;
+            for (index, item) in value.items().into_iter().flatten().enumerate() {
+                if index > 0 {
+                    f.write_char(' ')?;
+                }
+                write_value_with(f, item, options)?;
+            }
+            f.write_char(']')
+        }
+        Value::Map(inner) => {
+            f.write_str("#{")?;
+            for (index, (key, item)) in inner.iter().enumerate() {
+                if index > 0 {
+                    f.write_char(' ')?;
+                }
+                write_ident(f, key, options)?;
+                f.write_char('=')?;
+                write_value_with(f, item, options)?;
+            }
+            f.write_char('}')
+        }
+        Value::Null => f.write_str("null"),
+    }
+}
+
+/// Displays a [`Value`] with a given [`FloatFormat`], see [`Value::display`].
+#[derive(Clone, Debug)]
+pub struct ValueDisplay<'value, 'borrow> {
+    value: &'value Value<'borrow>,
+    floats: FloatFormat,
+}
+
+impl fmt::Display for ValueDisplay<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_value(f, self.value, self.floats)
+    }
+}
+
+impl<'borrow> Value<'borrow> {
+    /// Display the value with a given [`FloatFormat`].
+    ///
+    /// ```
+    /// # use gpnd::emit::FloatFormat;
+    /// # use gpnd::Value;
+    /// let value = Value::from(1234.5_f64);
+    /// assert_eq!(value.to_string(), "1234.5");
+    /// assert_eq!(value.display(FloatFormat::Fixed(2)).to_string(), "1234.50");
+    /// assert_eq!(
+    ///     value.display(FloatFormat::Scientific).to_string(),
+    ///     "1.2345e3"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn display(&self, floats: FloatFormat) -> ValueDisplay<'_, 'borrow> {
+        ValueDisplay {
+            value: self,
+            floats,
+        }
+    }
+}
+
+/// Writes the value as it would appear in a document.
+///
+/// Floats are written with [`FloatFormat::Shortest`], unless a precision is
+/// given (as in `{:.3}`), which selects [`FloatFormat::Fixed`]. Use
+/// `{:e}` for [`FloatFormat::Scientific`].
+impl fmt::Display for Value<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let floats = f
+            .precision()
+            .map_or(FloatFormat::Shortest, FloatFormat::Fixed);
+        write_value(f, self, floats)
+    }
+}
+
+impl fmt::LowerExp for Value<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_value(f, self, FloatFormat::Scientific)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    #[cfg(feature = "std")]
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn test_display_float() {
+        assert_eq!(Value::F64(1.0).to_string(), "1.0");
+        assert_eq!(Value::F32(0.1).to_string(), "0.1");
+        assert_eq!(format!("{:.2}", Value::F64(0.125)), "0.12");
+        assert_eq!(format!("{:e}", Value::F64(1500.0)), "1.5e3");
+        assert_eq!(Value::F64(f64::NEG_INFINITY).to_string(), "#-inf");
+        assert_eq!(Value::F64(f64::NAN).to_string(), "#nan");
+    }
+
+    #[test]
+    fn test_display_value() {
+        assert_eq!(Value::I32(-5).to_string(), "-5");
+        assert_eq!(Value::from("a \"b\"\n").to_string(), r#""a \"b\"\n""#);
+        assert_eq!(
+            Value::from_iter([Value::Bool(true), Value::Null, Value::F64(0.5)]).to_string(),
+            "[true null 0.5]"
+        );
+        #[cfg(feature = "std")]
+        {
+            let map = HashMap::from([("a", 1_u8)]);
+            assert_eq!(Value::from(map).to_string(), r#"#{"a"=1}"#);
+        }
+    }
+
+    #[test]
+    fn test_write_raw() {
+        let mut raw = String::new();
+        write_string(&mut raw, r"C:\dir", QuoteStyle::Raw).unwrap();
+        raw.push(' ');
+        write_string(&mut raw, r#"say "hi""#, QuoteStyle::Raw).unwrap();
+        raw.push(' ');
+        write_string(&mut raw, "plain", QuoteStyle::Raw).unwrap();
+        assert_eq!(raw, "r\"C:\\dir\" r#\"say \"hi\"\"# \"plain\"");
+
+        let options = FormatOptions {
+            quotes: QuoteStyle::Raw,
+            non_ascii: NonAscii::Escaped,
+            ..FormatOptions::default()
+        };
+        let mut escaped = String::new();
+        write_string_with(&mut escaped, "é\\", &options).unwrap();
+        assert_eq!(escaped, "\"\\u{e9}\\\\\"");
+    }
+}
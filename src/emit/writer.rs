@@ -0,0 +1,450 @@
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use core::error::Error;
+use core::fmt::{self, Write};
+#[cfg(feature = "std")]
+use std::io;
+
+use super::options::FormatOptions;
+use super::stream::Emitter;
+#[cfg(feature = "std")]
+use super::stream::IoAdapter;
+use crate::parser::Event;
+use crate::{NodeBuf, Value};
+
+/// Writes a document node by node from calls such as
+/// [`DocumentWriter::start_node`] and [`DocumentWriter::param`], without
+/// building the tree, for exporters that write more nodes than fit in memory.
+///
+/// Only the arguments and parameters of the node being started are held,
+/// until its children are started or it is ended. The calls are checked to
+/// nest properly, and a call out of place is an error that writes nothing.
+///
+/// ```
+/// # use gpnd::emit::DocumentWriter;
+/// let mut writer = DocumentWriter::new(String::new());
+/// writer.start_node("server")?;
+/// writer.arg("main")?;
+/// writer.param("port", 80)?;
+/// writer.start_children()?;
+/// for path in ["/a", "/b"] {
+///     writer.start_node("route")?;
+///     writer.arg(path)?;
+///     writer.end_node()?;
+/// }
+/// writer.end_node()?;
+/// assert_eq!(
+///     writer.finish()?,
+///     "server \"main\" port=80 {\n    route \"/a\"\n    route \"/b\"\n}\n"
+/// );
+/// # Ok::<(), gpnd::emit::WriterError>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct DocumentWriter<W> {
+    emitter: Emitter<W>,
+    /// The node that was started, and whose children were not.
+    header: Option<NodeBuf>,
+    /// The number of nodes whose children were started, and that were not
+    /// ended.
+    open: usize,
+}
+
+/// The error returned by a [`DocumentWriter`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum WriterError {
+    /// The writer failed.
+    Write,
+    #[cfg(feature = "std")]
+    Io(io::Error),
+    /// An argument, a parameter, or the children of a node, outside of the
+    /// node that was last started.
+    NotInHeader(&'static str),
+    /// A node started within a node before its children were started.
+    ChildrenNotStarted,
+    /// A node ended when there is none to end.
+    NoOpenNode,
+    VersionAfterNode,
+    /// The number of nodes that were not ended when the document was
+    /// finished.
+    Unclosed(usize),
+}
+
+impl fmt::Display for WriterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Write => f.write_str("writing the document failed"),
+            #[cfg(feature = "std")]
+            Self::Io(error) => error.fmt(f),
+            Self::NotInHeader(call) => write!(
+                f,
+                "`{call}` must come after `start_node`, before `start_children`"
+            ),
+            Self::ChildrenNotStarted => {
+                f.write_str("`start_node` within a node must come after `start_children`")
+            }
+            Self::NoOpenNode => f.write_str("`end_node` without a node to end"),
+            Self::VersionAfterNode => f.write_str("the version must be written before any node"),
+            Self::Unclosed(count) => write!(f, "{count} nodes were not ended"),
+        }
+    }
+}
+
+impl Error for WriterError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            #[cfg(feature = "std")]
+            Self::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<fmt::Error> for WriterError {
+    fn from(_: fmt::Error) -> Self {
+        Self::Write
+    }
+}
+
+impl<W> DocumentWriter<W>
+where
+    W: Write,
+{
+    /// Write to `out` with the default [`FormatOptions`].
+    pub fn new(out: W) -> Self {
+        Self::with_options(out, FormatOptions::default())
+    }
+
+    /// Write to `out` with `options`. Nodes are not aligned to their
+    /// siblings, which are not known when a node is written.
+    pub fn with_options(out: W, options: FormatOptions) -> Self {
+        Self {
+            emitter: Emitter::with_options(out, options),
+            header: None,
+            open: 0,
+        }
+    }
+
+    /// Write a `@version` directive.
+    ///
+    /// # Errors
+    /// Fails if a node has been started, or if the writer fails.
+    pub fn version(&mut self, version: &str) -> Result<(), WriterError> {
+        if self.depth() > 0 || self.emitter.node_count() > 0 {
+            return Err(WriterError::VersionAfterNode);
+        }
+        Ok(self.emitter.version(version)?)
+    }
+
+    /// Start a node, which is a child of the node whose children were last
+    /// started, or a top-level node if there is none.
+    ///
+    /// # Errors
+    /// Fails if the last node that was started has not been ended, and its
+    /// children were not started.
+    pub fn start_node<S>(&mut self, name: S) -> Result<(), WriterError>
+    where
+        S: Into<Arc<str>>,
+    {
+        if self.header.is_some() {
+            return Err(WriterError::ChildrenNotStarted);
+        }
+        self.header = Some(NodeBuf::new(name));
+        Ok(())
+    }
+
+    /// Add an argument to the node that was last started.
+    ///
+    /// # Errors
+    /// Fails if there is no such node, or its children were started.
+    pub fn arg<V>(&mut self, value: V) -> Result<(), WriterError>
+    where
+        V: Into<Value<'static>>,
+    {
+        self.header_mut("arg")?.args_mut().push(value.into());
+        Ok(())
+    }
+
+    /// Add a parameter to the node that was last started.
+    ///
+    /// # Errors
+    /// Fails if there is no such node, or its children were started.
+    pub fn param<K, V>(&mut self, key: K, value: V) -> Result<(), WriterError>
+    where
+        K: Into<Arc<str>>,
+        V: Into<Value<'static>>,
+    {
+        self.header_mut("param")?.params_mut().push(key, value);
+        Ok(())
+    }
+
+    /// Write the node that was last started, so that the next nodes started
+    /// are its children, until it is ended.
+    ///
+    /// # Errors
+    /// Fails if there is no such node, or its children were already started,
+    /// or if the writer fails.
+    pub fn start_children(&mut self) -> Result<(), WriterError> {
+        let header = self
+            .header
+            .take()
+            .ok_or(WriterError::NotInHeader("start_children"))?;
+        self.emitter.emit_event(&Event::Start(Box::new(header)))?;
+        self.open += 1;
+        Ok(())
+    }
+
+    /// End the node that was last started, and write it if its children
+    /// were not started.
+    ///
+    /// # Errors
+    /// Fails if every node has been ended, or if the writer fails.
+    pub fn end_node(&mut self) -> Result<(), WriterError> {
+        if let Some(header) = self.header.take() {
+            self.emitter.emit_event(&Event::Start(Box::new(header)))?;
+        } else if self.open > 0 {
+            self.open -= 1;
+        } else {
+            return Err(WriterError::NoOpenNode);
+        }
+        Ok(self.emitter.emit_event(&Event::End)?)
+    }
+
+    /// The number of nodes that were started and not ended.
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        self.open + usize::from(self.header.is_some())
+    }
+
+    /// The number of top-level nodes written so far.
+    #[must_use]
+    pub fn node_count(&self) -> usize {
+        self.emitter.node_count()
+    }
+
+    #[must_use]
+    pub fn get_ref(&self) -> &W {
+        self.emitter.get_ref()
+    }
+
+    /// Check that every node was ended, and return the writer.
+    ///
+    /// # Errors
+    /// Fails if a node was not ended.
+    pub fn finish(self) -> Result<W, WriterError> {
+        match self.depth() {
+            0 => Ok(self.emitter.into_inner()),
+            depth => Err(WriterError::Unclosed(depth)),
+        }
+    }
+
+    fn header_mut(&mut self, call: &'static str) -> Result<&mut NodeBuf, WriterError> {
+        self.header.as_mut().ok_or(WriterError::NotInHeader(call))
+    }
+}
+
+/// A [`DocumentWriter`] that writes to an [`io::Write`], such as a file or a
+/// socket.
+///
+/// Nothing is buffered here, so wrap unbuffered writers in an
+/// [`io::BufWriter`].
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct IoDocumentWriter<W> {
+    writer: DocumentWriter<IoAdapter<W>>,
+}
+
+#[cfg(feature = "std")]
+impl<W> IoDocumentWriter<W>
+where
+    W: io::Write,
+{
+    /// Write to `out` with the default [`FormatOptions`].
+    pub fn new(out: W) -> Self {
+        Self::with_options(out, FormatOptions::default())
+    }
+
+    pub fn with_options(out: W, options: FormatOptions) -> Self {
+        Self {
+            writer: DocumentWriter::with_options(IoAdapter::new(out), options),
+        }
+    }
+
+    /// Write a `@version` directive, see [`DocumentWriter::version`].
+    ///
+    /// # Errors
+    /// Fails if a node has been started, or if the writer fails.
+    pub fn version(&mut self, version: &str) -> Result<(), WriterError> {
+        let result = self.writer.version(version);
+        self.check(result)
+    }
+
+    /// Start a node, see [`DocumentWriter::start_node`].
+    ///
+    /// # Errors
+    /// Fails if the last node that was started has not been ended, and its
+    /// children were not started.
+    pub fn start_node<S>(&mut self, name: S) -> Result<(), WriterError>
+    where
+        S: Into<Arc<str>>,
+    {
+        self.writer.start_node(name)
+    }
+
+    /// Add an argument to the node that was last started.
+    ///
+    /// # Errors
+    /// Fails if there is no such node, or its children were started.
+    pub fn arg<V>(&mut self, value: V) -> Result<(), WriterError>
+    where
+        V: Into<Value<'static>>,
+    {
+        self.writer.arg(value)
+    }
+
+    /// Add a parameter to the node that was last started.
+    ///
+    /// # Errors
+    /// Fails if there is no such node, or its children were started.
+    pub fn param<K, V>(&mut self, key: K, value: V) -> Result<(), WriterError>
+    where
+        K: Into<Arc<str>>,
+        V: Into<Value<'static>>,
+    {
+        self.writer.param(key, value)
+    }
+
+    /// Write the node that was last started, see
+    /// [`DocumentWriter::start_children`].
+    ///
+    /// # Errors
+    /// Fails if there is no such node, or its children were already started,
+    /// or if the writer fails.
+    pub fn start_children(&mut self) -> Result<(), WriterError> {
+        let result = self.writer.start_children();
+        self.check(result)
+    }
+
+    /// End the node that was last started, see [`DocumentWriter::end_node`].
+    ///
+    /// # Errors
+    /// Fails if every node has been ended, or if the writer fails.
+    pub fn end_node(&mut self) -> Result<(), WriterError> {
+        let result = self.writer.end_node();
+        self.check(result)
+    }
+
+    /// The number of nodes that were started and not ended.
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        self.writer.depth()
+    }
+
+    /// The number of top-level nodes written so far.
+    #[must_use]
+    pub fn node_count(&self) -> usize {
+        self.writer.node_count()
+    }
+
+    /// Check that every node was ended, flush the writer, and return it.
+    ///
+    /// # Errors
+    /// Fails if a node was not ended, or if the writer fails.
+    pub fn finish(self) -> Result<W, WriterError> {
+        let mut out = self.writer.finish()?.inner;
+        out.flush().map_err(WriterError::Io)?;
+        Ok(out)
+    }
+
+    fn check(&mut self, result: Result<(), WriterError>) -> Result<(), WriterError> {
+        match result {
+            Err(WriterError::Write) => Err(self
+                .writer
+                .emitter
+                .get_mut()
+                .error
+                .take()
+                .map_or(WriterError::Write, WriterError::Io)),
+            result => result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::{String, ToString};
+
+    use super::{DocumentWriter, WriterError};
+    use crate::emit::FormatOptions;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_document_writer() {
+        let mut writer = DocumentWriter::with_options(String::new(), FormatOptions::compact());
+        writer.version("1").unwrap();
+        writer.start_node("a").unwrap();
+        writer.param("k", true).unwrap();
+        writer.start_children().unwrap();
+        writer.start_node("b").unwrap();
+        writer.end_node().unwrap();
+        writer.start_node("c").unwrap();
+        writer.start_children().unwrap();
+        assert_eq!(writer.depth(), 2);
+        writer.end_node().unwrap();
+        writer.end_node().unwrap();
+        writer.start_node("d").unwrap();
+        writer.arg(1).unwrap();
+        writer.end_node().unwrap();
+        assert_eq!(writer.node_count(), 2);
+        let output = writer.finish().unwrap();
+        assert_eq!(output, "@version \"1\";a k=true{b;c};d 1");
+        assert_eq!(
+            parse(&output).unwrap(),
+            parse("@version \"1\"\na k=true { b; c }\nd 1").unwrap()
+        );
+
+        let mut writer = DocumentWriter::new(String::new());
+        assert!(matches!(
+            writer.arg(1),
+            Err(WriterError::NotInHeader("arg"))
+        ));
+        assert!(matches!(writer.end_node(), Err(WriterError::NoOpenNode)));
+        writer.start_node("a").unwrap();
+        assert!(matches!(
+            writer.start_node("b"),
+            Err(WriterError::ChildrenNotStarted)
+        ));
+        assert!(matches!(
+            writer.version("1"),
+            Err(WriterError::VersionAfterNode)
+        ));
+        writer.start_children().unwrap();
+        let error = writer.param("k", 1).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "`param` must come after `start_node`, before `start_children`"
+        );
+        assert!(matches!(
+            writer.start_children(),
+            Err(WriterError::NotInHeader(_))
+        ));
+        writer.start_node("b").unwrap();
+        assert!(matches!(
+            writer.clone().finish(),
+            Err(WriterError::Unclosed(2))
+        ));
+        writer.end_node().unwrap();
+        writer.end_node().unwrap();
+        assert_eq!(writer.finish().unwrap(), "a {\n    b\n}\n");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_io_document_writer() {
+        let mut writer = super::IoDocumentWriter::new(Vec::new());
+        writer.start_node("a").unwrap();
+        writer.arg("x").unwrap();
+        writer.end_node().unwrap();
+        assert_eq!(writer.finish().unwrap(), b"a \"x\"\n");
+    }
+}
@@ -0,0 +1,510 @@
+//! A single error type for the whole crate, for applications that do not
+//! need to tell the errors of each module apart by type.
+//!
+//! Every error of the crate converts into an [`Error`], which keeps it as it
+//! was. Each [`Error`] has an [`ErrorCode`] that is stable across releases,
+//! and an [`ErrorCategory`], so that applications can branch on the kind of
+//! an error, or show a link to its documentation, without matching on
+//! messages.
+//!
+//! ```
+//! # use gpnd::{Error, ErrorCategory, ErrorCode};
+//! let error = Error::from(gpnd::parser::parse("a \"b").unwrap_err());
+//! assert_eq!(error.code(), ErrorCode::UnterminatedString);
+//! assert_eq!(error.code().to_string(), "GPND0102");
+//! assert_eq!(error.category(), ErrorCategory::Lexing);
+//! ```
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::Utf8Error;
+#[cfg(feature = "std")]
+use std::io;
+
+use crate::bridge::binary::DecodeError;
+use crate::emit::WriterError;
+use crate::parser::literal::LiteralError;
+#[cfg(feature = "encoding")]
+use crate::parser::EncodingError;
+#[cfg(feature = "mmap")]
+use crate::parser::MmapError;
+#[cfg(feature = "notify")]
+use crate::parser::WatchError;
+#[cfg(feature = "std")]
+use crate::parser::{IncludeError, InterpolationError, LoadError, LoadErrorKind};
+use crate::parser::{ParseError, ParseErrorKind};
+use crate::patch::PatchError;
+#[cfg(feature = "std")]
+use crate::pipeline::PipelineError;
+use crate::query::QueryError;
+use crate::schema::{SchemaError, Violation};
+use crate::{
+    EnumValueError, IntoInnerError, InvalidNameError, NodeValueError, PathError, PathErrorKind,
+    ValueError,
+};
+
+/// The broad class of an [`Error`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ErrorCategory {
+    /// Text that could not be read as tokens.
+    Lexing,
+    /// Tokens that do not make a valid document, query, or schema.
+    Parsing,
+    /// A value or document that could not be converted to another type or
+    /// format.
+    Conversion,
+    /// A document that does not have the shape that was asked for.
+    Validation,
+    Io,
+}
+
+/// A stable code for a kind of [`Error`], written as `GPND` and its number,
+/// such as `GPND0102`.
+///
+/// The hundreds of the number are its [`ErrorCategory`]. Codes are never
+/// reused or renumbered, but new ones may be added.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+#[repr(u16)]
+pub enum ErrorCode {
+    UnexpectedChar = 101,
+    UnterminatedString = 102,
+    UnterminatedComment = 103,
+    UnknownKeyword = 104,
+    InvalidNumber = 105,
+    UnexpectedSuffix = 106,
+    UnknownUnit = 107,
+    NumberOutOfRange = 108,
+    InvalidEscape = 109,
+    InvalidUtf8 = 110,
+    /// A source that is not valid in its encoding.
+    MalformedEncoding = 111,
+    UnknownEncoding = 112,
+    /// An identifier that is not in Unicode Normalization Form C.
+    Unnormalized = 113,
+
+    /// A token that is valid, but not where it appeared.
+    UnexpectedToken = 201,
+    InvalidName = 202,
+    UnknownDirective = 203,
+    DuplicateDirective = 204,
+    MisplacedDirective = 205,
+    UnknownAnchor = 206,
+    DuplicateAnchor = 207,
+    InvalidQuery = 208,
+    InvalidInclude = 209,
+    IncludeCycle = 210,
+    UnterminatedPlaceholder = 211,
+    InvalidPatch = 212,
+    InvalidSchema = 213,
+    DuplicateParam = 214,
+    DuplicateNode = 215,
+    /// Blocks, lists, or maps nested deeper than the parser allows.
+    TooDeep = 216,
+    /// References that copy more nodes and values than the parser allows.
+    TooLarge = 217,
+    /// A parser option that the parsing method cannot follow.
+    Unsupported = 218,
+
+    MissingValue = 301,
+    /// A value that is not of the type that was asked for.
+    WrongType = 302,
+    InvalidBinary = 303,
+    /// A value or document that another format cannot represent, or the
+    /// reverse.
+    Unrepresentable = 304,
+
+    /// A path that is empty or has an invalid segment.
+    InvalidPath = 401,
+    NodeNotFound = 402,
+    ParamNotFound = 403,
+    SchemaViolation = 404,
+    UnresolvedVariable = 405,
+    /// Nodes written out of order, such as an argument after the children of
+    /// a node were started.
+    InvalidNesting = 406,
+
+    Io = 501,
+}
+
+impl ErrorCode {
+    #[must_use]
+    pub fn number(self) -> u16 {
+        self as u16
+    }
+
+    #[must_use]
+    pub fn category(self) -> ErrorCategory {
+        match self.number() / 100 {
+            1 => ErrorCategory::Lexing,
+            2 => ErrorCategory::Parsing,
+            3 => ErrorCategory::Conversion,
+            4 => ErrorCategory::Validation,
+            _ => ErrorCategory::Io,
+        }
+    }
+
+    /// A link to the documentation of the code.
+    #[must_use]
+    pub fn docs_url(self) -> String {
+        format!("https://docs.rs/gpnd/latest/gpnd/error/enum.ErrorCode.html#variant.{self:?}")
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "GPND{:04}", self.number())
+    }
+}
+
+/// Any error of the crate, see the [module](self) documentation.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    Parse(ParseError),
+    Query(QueryError),
+    Value(ValueError),
+    NodeValue(NodeValueError),
+    EnumValue(EnumValueError),
+    Name(InvalidNameError),
+    Path(PathError),
+    Patch(PatchError),
+    Schema(SchemaError),
+    /// The violations of a schema by a document, which are not empty.
+    Validation(Vec<Violation>),
+    Decode(DecodeError),
+    Utf8(Utf8Error),
+    #[cfg(feature = "std")]
+    Include(IncludeError),
+    #[cfg(feature = "std")]
+    Interpolation(InterpolationError),
+    #[cfg(feature = "std")]
+    Load(LoadError),
+    #[cfg(feature = "notify")]
+    Watch(WatchError),
+    #[cfg(feature = "encoding")]
+    Encoding(EncodingError),
+    #[cfg(feature = "std")]
+    Io(io::Error),
+    Writer(WriterError),
+    /// An error from converting to or from another format.
+    Convert(Box<dyn core::error::Error + Send + Sync>),
+}
+
+fn parse_code(error: &ParseError) -> ErrorCode {
+    match error.kind() {
+        ParseErrorKind::UnexpectedChar(_) => ErrorCode::UnexpectedChar,
+        ParseErrorKind::Unexpected { .. } => ErrorCode::UnexpectedToken,
+        ParseErrorKind::UnterminatedString => ErrorCode::UnterminatedString,
+        ParseErrorKind::UnterminatedComment => ErrorCode::UnterminatedComment,
+        ParseErrorKind::UnknownKeyword(_) => ErrorCode::UnknownKeyword,
+        ParseErrorKind::Literal(LiteralError::Invalid(_)) => ErrorCode::InvalidNumber,
+        ParseErrorKind::Literal(LiteralError::UnexpectedSuffix(_)) => ErrorCode::UnexpectedSuffix,
+        ParseErrorKind::Literal(LiteralError::UnknownUnit(_)) => ErrorCode::UnknownUnit,
+        ParseErrorKind::Literal(LiteralError::OutOfRange(_)) => ErrorCode::NumberOutOfRange,
+        ParseErrorKind::Literal(LiteralError::InvalidEscape(_)) => ErrorCode::InvalidEscape,
+        ParseErrorKind::InvalidName(_) => ErrorCode::InvalidName,
+        ParseErrorKind::UnknownDirective(_) => ErrorCode::UnknownDirective,
+        ParseErrorKind::DuplicateDirective(_) => ErrorCode::DuplicateDirective,
+        ParseErrorKind::MisplacedDirective(_) => ErrorCode::MisplacedDirective,
+        ParseErrorKind::UnknownAnchor(_) => ErrorCode::UnknownAnchor,
+        ParseErrorKind::DuplicateAnchor(_) => ErrorCode::DuplicateAnchor,
+        ParseErrorKind::DuplicateParam(_) => ErrorCode::DuplicateParam,
+        ParseErrorKind::DuplicateNode(_) => ErrorCode::DuplicateNode,
+        #[cfg(feature = "unicode")]
+        ParseErrorKind::Unnormalized(_) => ErrorCode::Unnormalized,
+        ParseErrorKind::TooDeep(_) => ErrorCode::TooDeep,
+        ParseErrorKind::TooLarge(_) => ErrorCode::TooLarge,
+        ParseErrorKind::Unsupported(_) => ErrorCode::Unsupported,
+    }
+}
+
+fn path_code(error: &PathError) -> ErrorCode {
+    match error.kind() {
+        PathErrorKind::Empty | PathErrorKind::InvalidSegment => ErrorCode::InvalidPath,
+        PathErrorKind::NodeNotFound => ErrorCode::NodeNotFound,
+        PathErrorKind::ParamNotFound => ErrorCode::ParamNotFound,
+    }
+}
+
+#[cfg(feature = "encoding")]
+fn encoding_code(error: &EncodingError) -> ErrorCode {
+    match error {
+        EncodingError::UnknownLabel(_) => ErrorCode::UnknownEncoding,
+        EncodingError::Malformed(_) => ErrorCode::MalformedEncoding,
+        EncodingError::Parse(error) => parse_code(error),
+    }
+}
+
+#[cfg(feature = "std")]
+fn load_code(error: &LoadError) -> ErrorCode {
+    match error.kind() {
+        LoadErrorKind::Io(_) => ErrorCode::Io,
+        LoadErrorKind::Utf8(_) => ErrorCode::InvalidUtf8,
+        #[cfg(feature = "encoding")]
+        LoadErrorKind::Encoding(error) => encoding_code(error),
+        LoadErrorKind::Parse(error) => parse_code(error),
+    }
+}
+
+impl Error {
+    #[must_use]
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::Parse(error) => parse_code(error),
+            Self::Query(_) => ErrorCode::InvalidQuery,
+            Self::Value(ValueError::Missing) | Self::NodeValue(NodeValueError::Missing { .. }) => {
+                ErrorCode::MissingValue
+            }
+            Self::Value(ValueError::Convert(_))
+            | Self::NodeValue(NodeValueError::Convert { .. })
+            | Self::EnumValue(_) => ErrorCode::WrongType,
+            Self::Name(_) => ErrorCode::InvalidName,
+            Self::Path(error) | Self::Patch(PatchError::Apply { error, .. }) => path_code(error),
+            Self::Patch(PatchError::Invalid { .. }) => ErrorCode::InvalidPatch,
+            Self::Schema(_) => ErrorCode::InvalidSchema,
+            Self::Validation(_) => ErrorCode::SchemaViolation,
+            Self::Decode(_) => ErrorCode::InvalidBinary,
+            Self::Utf8(_) => ErrorCode::InvalidUtf8,
+            #[cfg(feature = "std")]
+            Self::Include(error) => match error {
+                IncludeError::Parse { error, .. } => parse_code(error),
+                IncludeError::Resolve { .. } => ErrorCode::Io,
+                IncludeError::Invalid { .. } => ErrorCode::InvalidInclude,
+                IncludeError::Cycle(_) => ErrorCode::IncludeCycle,
+            },
+            #[cfg(feature = "std")]
+            Self::Interpolation(error) => match error {
+                InterpolationError::Unresolved(_) => ErrorCode::UnresolvedVariable,
+                InterpolationError::Unterminated(_) => ErrorCode::UnterminatedPlaceholder,
+            },
+            #[cfg(feature = "std")]
+            Self::Load(error) => load_code(error),
+            #[cfg(feature = "notify")]
+            Self::Watch(WatchError::Load(error)) => load_code(error),
+            #[cfg(feature = "notify")]
+            Self::Watch(WatchError::Notify(_)) => ErrorCode::Io,
+            #[cfg(feature = "encoding")]
+            Self::Encoding(error) => encoding_code(error),
+            #[cfg(feature = "std")]
+            Self::Io(_) => ErrorCode::Io,
+            Self::Writer(
+                WriterError::NotInHeader(_)
+                | WriterError::ChildrenNotStarted
+                | WriterError::NoOpenNode
+                | WriterError::VersionAfterNode
+                | WriterError::Unclosed(_),
+            ) => ErrorCode::InvalidNesting,
+            Self::Writer(_) => ErrorCode::Io,
+            Self::Convert(_) => ErrorCode::Unrepresentable,
+        }
+    }
+
+    #[must_use]
+    pub fn category(&self) -> ErrorCategory {
+        self.code().category()
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(error) => error.fmt(f),
+            Self::Query(error) => error.fmt(f),
+            Self::Value(error) => error.fmt(f),
+            Self::NodeValue(error) => error.fmt(f),
+            Self::EnumValue(error) => error.fmt(f),
+            Self::Name(error) => error.fmt(f),
+            Self::Path(error) => error.fmt(f),
+            Self::Patch(error) => error.fmt(f),
+            Self::Schema(error) => error.fmt(f),
+            Self::Validation(violations) => match violations.as_slice() {
+                [] => f.write_str("document does not match the schema"),
+                [violation] => violation.fmt(f),
+                [violation, rest @ ..] => write!(f, "{violation} (and {} more)", rest.len()),
+            },
+            Self::Decode(error) => error.fmt(f),
+            Self::Utf8(error) => error.fmt(f),
+            #[cfg(feature = "std")]
+            Self::Include(error) => error.fmt(f),
+            #[cfg(feature = "std")]
+            Self::Interpolation(error) => error.fmt(f),
+            #[cfg(feature = "std")]
+            Self::Load(error) => error.fmt(f),
+            #[cfg(feature = "notify")]
+            Self::Watch(error) => error.fmt(f),
+            #[cfg(feature = "encoding")]
+            Self::Encoding(error) => error.fmt(f),
+            #[cfg(feature = "std")]
+            Self::Io(error) => error.fmt(f),
+            Self::Writer(error) => error.fmt(f),
+            Self::Convert(error) => error.fmt(f),
+        }
+    }
+}
+
+impl core::error::Error for Error {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        Some(match self {
+            Self::Parse(error) => error,
+            Self::Query(error) => error,
+            Self::Value(error) => error,
+            Self::NodeValue(error) => error,
+            Self::EnumValue(error) => error,
+            Self::Name(error) => error,
+            Self::Path(error) => error,
+            Self::Patch(error) => error,
+            Self::Schema(error) => error,
+            Self::Validation(_) => return None,
+            Self::Decode(error) => error,
+            Self::Utf8(error) => error,
+            #[cfg(feature = "std")]
+            Self::Include(error) => error,
+            #[cfg(feature = "std")]
+            Self::Interpolation(error) => error,
+            #[cfg(feature = "std")]
+            Self::Load(error) => error,
+            #[cfg(feature = "notify")]
+            Self::Watch(error) => error,
+            #[cfg(feature = "encoding")]
+            Self::Encoding(error) => error,
+            #[cfg(feature = "std")]
+            Self::Io(error) => error,
+            Self::Writer(error) => error,
+            Self::Convert(error) => &**error,
+        })
+    }
+}
+
+macro_rules! impl_from {
+    ($($(#[$attr:meta])* $variant:ident($error:ty)),+ $(,)?) => {
+        $($(#[$attr])*
+        impl From<$error> for Error {
+            fn from(error: $error) -> Self {
+                Self::$variant(error)
+            }
+        })+
+    };
+}
+
+impl_from!(
+    Parse(ParseError),
+    Query(QueryError),
+    Value(ValueError),
+    NodeValue(NodeValueError),
+    EnumValue(EnumValueError),
+    Name(InvalidNameError),
+    Path(PathError),
+    Patch(PatchError),
+    Schema(SchemaError),
+    Validation(Vec<Violation>),
+    Decode(DecodeError),
+    Utf8(Utf8Error),
+    #[cfg(feature = "std")]
+    Include(IncludeError),
+    #[cfg(feature = "std")]
+    Interpolation(InterpolationError),
+    #[cfg(feature = "std")]
+    Load(LoadError),
+    #[cfg(feature = "notify")]
+    Watch(WatchError),
+    #[cfg(feature = "encoding")]
+    Encoding(EncodingError),
+    #[cfg(feature = "std")]
+    Io(io::Error),
+    Writer(WriterError),
+);
+
+impl From<IntoInnerError> for Error {
+    fn from(error: IntoInnerError) -> Self {
+        Self::Value(ValueError::Convert(error))
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<PipelineError> for Error {
+    fn from(error: PipelineError) -> Self {
+        match error {
+            PipelineError::Parse(error) => Self::Parse(error),
+            PipelineError::Io(error) => Self::Io(error),
+        }
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl From<MmapError> for Error {
+    fn from(error: MmapError) -> Self {
+        match error {
+            MmapError::Io(error) => Self::Io(error),
+            MmapError::Utf8(error) => Self::Utf8(error),
+            MmapError::Parse(error) => Self::Parse(error),
+        }
+    }
+}
+
+macro_rules! impl_from_convert {
+    ($($feature:literal => $($error:ty),+;)+) => {
+        $($(#[cfg(feature = $feature)]
+        impl From<$error> for Error {
+            fn from(error: $error) -> Self {
+                Self::Convert(Box::new(error))
+            }
+        })+)+
+    };
+}
+
+impl_from_convert! {
+    "figment" => crate::bridge::figment::ToDictError;
+    "json" => crate::bridge::json::ToJsonError;
+    "kdl" => crate::bridge::kdl::ToKdlError, crate::bridge::kdl::FromKdlError;
+    "serde" => crate::bridge::serde::DeserializeError, crate::bridge::serde::SerializeError;
+    "toml" => crate::bridge::toml::ToTomlError;
+    "xml" => crate::bridge::xml::ToXmlError, crate::bridge::xml::FromXmlError;
+    "yaml" => crate::bridge::yaml::FromYamlError;
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+    use crate::parser::parse;
+    use crate::{DocumentBuf, IntoInner};
+
+    #[test]
+    fn test_error_codes() {
+        let code = |source| Error::from(parse(source).unwrap_err()).code();
+        assert_eq!(code("a ("), ErrorCode::UnexpectedChar);
+        assert_eq!(code("a b"), ErrorCode::UnexpectedToken);
+        assert_eq!(code("a \"\\q\""), ErrorCode::InvalidEscape);
+        assert_eq!(code("a\n@version \"1\""), ErrorCode::MisplacedDirective);
+
+        let mut document = DocumentBuf::new();
+        let error = Error::from(document.set("server/port", 1).unwrap_err());
+        assert_eq!(error.code(), ErrorCode::NodeNotFound);
+        assert_eq!(error.category(), ErrorCategory::Validation);
+        assert_eq!(
+            error.to_string(),
+            "no node `server` at segment 1 of path `server/port`"
+        );
+        assert!(core::error::Error::source(&error).is_some());
+
+        let value = crate::Value::from("a");
+        let error = Error::from(IntoInner::<u8>::into_inner(value).unwrap_err());
+        assert_eq!(error.code(), ErrorCode::WrongType);
+        assert_eq!(error.code().to_string(), "GPND0302");
+        assert_eq!(error.category(), ErrorCategory::Conversion);
+        assert_eq!(
+            ErrorCode::WrongType.docs_url(),
+            "https://docs.rs/gpnd/latest/gpnd/error/enum.ErrorCode.html#variant.WrongType"
+        );
+        assert_eq!(ErrorCode::Io.category(), ErrorCategory::Io);
+
+        let error = Error::from(WriterError::NoOpenNode);
+        assert_eq!(error.code(), ErrorCode::InvalidNesting);
+        assert_eq!(error.category(), ErrorCategory::Validation);
+    }
+}
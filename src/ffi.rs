@@ -0,0 +1,516 @@
+//! Functions with the C ABI, so that applications in other languages can
+//! embed the parser. The declarations for C are in `include/gpnd.h`, and the
+//! library can be built with
+//! `cargo rustc --features ffi --crate-type staticlib`.
+//!
+//! Documents, nodes, and values are opaque handles. A document from
+//! [`gpnd_parse`] is owned by the caller and freed with
+//! [`gpnd_document_free`]. The nodes and values read from it are borrowed,
+//! and stay valid until the document is freed. Lists of nodes from
+//! [`gpnd_query`] and error messages are also owned by the caller, and freed
+//! with [`gpnd_node_list_free`] and [`gpnd_string_free`].
+//!
+//! Text is passed in both directions as a pointer to UTF-8 bytes and a
+//! length, without a terminating nul. Error messages are the exception, and
+//! are nul-terminated. Functions that read a handle return a null pointer,
+//! zero, or `false` when the handle is null or what they read is missing.
+//!
+//! # Safety
+//! Every handle must be null or have come from this module and not been
+//! freed, text pointers must point to at least the given number of bytes,
+//! and output pointers must be null or valid for writes.
+
+#![allow(unsafe_code)]
+
+use std::collections::HashSet;
+use std::ffi::{c_char, CString};
+use std::ptr;
+
+use crate::query::Query;
+use crate::{DocumentBuf, Node, NodeBuf, Value};
+
+/// The kind of a value, with every width of integer as one kind, and sizes
+/// and durations as integers of bytes and nanoseconds.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GpndKind {
+    Null,
+    Bool,
+    Integer,
+    Float,
+    String,
+    List,
+    Map,
+}
+
+/// Nodes selected by [`gpnd_query`], borrowed from their document.
+#[derive(Debug)]
+pub struct NodeList {
+    nodes: Vec<*const NodeBuf>,
+}
+
+/// Read `len` bytes at `text` as UTF-8, or `None` if they are not.
+unsafe fn text<'a>(text: *const u8, len: usize) -> Option<&'a str> {
+    if text.is_null() {
+        return None;
+    }
+    std::str::from_utf8(std::slice::from_raw_parts(text, len)).ok()
+}
+
+/// Give `text` to the caller as a pointer, writing its length to `len`.
+unsafe fn borrow_text(text: &str, len: *mut usize) -> *const u8 {
+    if !len.is_null() {
+        *len = text.len();
+    }
+    text.as_ptr()
+}
+
+/// Write `message` to `error` for the caller to free.
+unsafe fn set_error(error: *mut *mut c_char, message: &str) {
+    if !error.is_null() {
+        let message =
+            CString::new(message.replace('\0', "\u{fffd}")).expect("nul bytes were replaced");
+        *error = message.into_raw();
+    }
+}
+
+/// Parse `len` bytes of UTF-8 at `source` as a single document.
+///
+/// Returns null if the source is not valid, and writes a message to `error`
+/// if it is not null.
+///
+/// # Safety
+/// See the [module](self) documentation.
+#[no_mangle]
+pub unsafe extern "C" fn gpnd_parse(
+    source: *const u8,
+    len: usize,
+    error: *mut *mut c_char,
+) -> *mut DocumentBuf {
+    let Some(source) = text(source, len) else {
+        set_error(error, "source is not valid UTF-8");
+        return ptr::null_mut();
+    };
+    match crate::parser::parse(source) {
+        Ok(document) => Box::into_raw(Box::new(document)),
+        Err(parse_error) => {
+            set_error(error, &parse_error.to_string());
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Free a document from [`gpnd_parse`], which invalidates its nodes and
+/// values.
+///
+/// # Safety
+/// See the [module](self) documentation.
+#[no_mangle]
+pub unsafe extern "C" fn gpnd_document_free(document: *mut DocumentBuf) {
+    if !document.is_null() {
+        drop(Box::from_raw(document));
+    }
+}
+
+/// The version from the `@version` directive, or null if there is none.
+///
+/// # Safety
+/// See the [module](self) documentation.
+#[no_mangle]
+pub unsafe extern "C" fn gpnd_document_version(
+    document: *const DocumentBuf,
+    len: *mut usize,
+) -> *const u8 {
+    use crate::Document;
+
+    match document.as_ref().and_then(Document::version) {
+        Some(version) => borrow_text(version, len),
+        None => ptr::null(),
+    }
+}
+
+/// The number of nodes at the top level.
+///
+/// # Safety
+/// See the [module](self) documentation.
+#[no_mangle]
+pub unsafe extern "C" fn gpnd_document_len(document: *const DocumentBuf) -> usize {
+    document
+        .as_ref()
+        .map_or(0, |document| document.nodes_ref().len())
+}
+
+/// The node at the top level at `index`.
+///
+/// # Safety
+/// See the [module](self) documentation.
+#[no_mangle]
+pub unsafe extern "C" fn gpnd_document_node(
+    document: *const DocumentBuf,
+    index: usize,
+) -> *const NodeBuf {
+    document
+        .as_ref()
+        .and_then(|document| document.nodes_ref().get(index))
+        .map_or(ptr::null(), ptr::from_ref)
+}
+
+/// Select the nodes that match a query, see the [`query`](crate::query)
+/// module for the syntax.
+///
+/// Returns null if the query is not valid, and writes a message to `error`
+/// if it is not null.
+///
+/// # Safety
+/// See the [module](self) documentation.
+#[no_mangle]
+pub unsafe extern "C" fn gpnd_query(
+    document: *const DocumentBuf,
+    query: *const u8,
+    len: usize,
+    error: *mut *mut c_char,
+) -> *mut NodeList {
+    let Some(document) = document.as_ref() else {
+        set_error(error, "document is null");
+        return ptr::null_mut();
+    };
+    let Some(query) = text(query, len) else {
+        set_error(error, "query is not valid UTF-8");
+        return ptr::null_mut();
+    };
+    let query = match Query::parse(query) {
+        Ok(query) => query,
+        Err(query_error) => {
+            set_error(error, &query_error.to_string());
+            return ptr::null_mut();
+        }
+    };
+    let selected: HashSet<_> = query
+        .select(document)
+        .into_iter()
+        .map(|node| ptr::from_ref(node).cast::<()>())
+        .collect();
+    let mut nodes = Vec::new();
+    collect(document.nodes_ref(), &selected, &mut nodes);
+    Box::into_raw(Box::new(NodeList { nodes }))
+}
+
+/// Push the nodes in `nodes` and their descendants that are `selected`, in
+/// depth-first order.
+fn collect(nodes: &[NodeBuf], selected: &HashSet<*const ()>, out: &mut Vec<*const NodeBuf>) {
+    for node in nodes {
+        if selected.contains(&ptr::from_ref(node).cast::<()>()) {
+            out.push(node);
+        }
+        collect(node.children_ref(), selected, out);
+    }
+}
+
+/// The number of nodes in a list from [`gpnd_query`].
+///
+/// # Safety
+/// See the [module](self) documentation.
+#[no_mangle]
+pub unsafe extern "C" fn gpnd_node_list_len(list: *const NodeList) -> usize {
+    list.as_ref().map_or(0, |list| list.nodes.len())
+}
+
+/// The node in a list from [`gpnd_query`] at `index`.
+///
+/// # Safety
+/// See the [module](self) documentation.
+#[no_mangle]
+pub unsafe extern "C" fn gpnd_node_list_get(list: *const NodeList, index: usize) -> *const NodeBuf {
+    list.as_ref()
+        .and_then(|list| list.nodes.get(index).copied())
+        .unwrap_or(ptr::null())
+}
+
+/// Free a list from [`gpnd_query`], which does not free its nodes.
+///
+/// # Safety
+/// See the [module](self) documentation.
+#[no_mangle]
+pub unsafe extern "C" fn gpnd_node_list_free(list: *mut NodeList) {
+    if !list.is_null() {
+        drop(Box::from_raw(list));
+    }
+}
+
+/// The name of a node, without its namespace.
+///
+/// # Safety
+/// See the [module](self) documentation.
+#[no_mangle]
+pub unsafe extern "C" fn gpnd_node_name(node: *const NodeBuf, len: *mut usize) -> *const u8 {
+    node.as_ref()
+        .map_or(ptr::null(), |node| borrow_text(node.name(), len))
+}
+
+/// The number of arguments of a node.
+///
+/// # Safety
+/// See the [module](self) documentation.
+#[no_mangle]
+pub unsafe extern "C" fn gpnd_node_arg_count(node: *const NodeBuf) -> usize {
+    node.as_ref().map_or(0, |node| node.args_ref().len())
+}
+
+/// The argument of a node at `index`.
+///
+/// # Safety
+/// See the [module](self) documentation.
+#[no_mangle]
+pub unsafe extern "C" fn gpnd_node_arg(
+    node: *const NodeBuf,
+    index: usize,
+) -> *const Value<'static> {
+    node.as_ref()
+        .and_then(|node| node.args_ref().get(index))
+        .map_or(ptr::null(), ptr::from_ref)
+}
+
+/// The parameter of a node with the key of `len` bytes at `key`.
+///
+/// # Safety
+/// See the [module](self) documentation.
+#[no_mangle]
+pub unsafe extern "C" fn gpnd_node_param(
+    node: *const NodeBuf,
+    key: *const u8,
+    len: usize,
+) -> *const Value<'static> {
+    let key = text(key, len);
+    node.as_ref()
+        .zip(key)
+        .and_then(|(node, key)| node.params_ref().get(key))
+        .map_or(ptr::null(), ptr::from_ref)
+}
+
+/// The number of children of a node.
+///
+/// # Safety
+/// See the [module](self) documentation.
+#[no_mangle]
+pub unsafe extern "C" fn gpnd_node_child_count(node: *const NodeBuf) -> usize {
+    node.as_ref().map_or(0, |node| node.children_ref().len())
+}
+
+/// The child of a node at `index`.
+///
+/// # Safety
+/// See the [module](self) documentation.
+#[no_mangle]
+pub unsafe extern "C" fn gpnd_node_child(node: *const NodeBuf, index: usize) -> *const NodeBuf {
+    node.as_ref()
+        .and_then(|node| node.children_ref().get(index))
+        .map_or(ptr::null(), ptr::from_ref)
+}
+
+/// The kind of a value, which is `Null` for a null handle.
+///
+/// # Safety
+/// See the [module](self) documentation.
+#[no_mangle]
+pub unsafe extern "C" fn gpnd_value_kind(value: *const Value<'static>) -> GpndKind {
+    match value.as_ref() {
+        None | Some(Value::Null) => GpndKind::Null,
+        Some(Value::Bool(_)) => GpndKind::Bool,
+        Some(Value::F32(_) | Value::F64(_)) => GpndKind::Float,
+        Some(Value::String(_)) => GpndKind::String,
+        Some(Value::List(_) | Value::Slice(_)) => GpndKind::List,
+        Some(Value::Map(_)) => GpndKind::Map,
+        Some(_) => GpndKind::Integer,
+    }
+}
+
+/// Write a `Bool` value to `out`, returning whether it was one.
+///
+/// # Safety
+/// See the [module](self) documentation.
+#[no_mangle]
+pub unsafe extern "C" fn gpnd_value_bool(value: *const Value<'static>, out: *mut bool) -> bool {
+    match value.as_ref() {
+        Some(Value::Bool(inner)) if !out.is_null() => {
+            *out = *inner;
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Write an integer value to `out`, returning whether it was one that fits
+/// in an `int64_t`. Sizes and durations are written as their number of bytes
+/// and nanoseconds.
+///
+/// # Safety
+/// See the [module](self) documentation.
+#[no_mangle]
+pub unsafe extern "C" fn gpnd_value_i64(value: *const Value<'static>, out: *mut i64) -> bool {
+    let inner = value
+        .as_ref()
+        .and_then(|value| match *value {
+            Value::Size(inner) | Value::Duration(inner) => Some(i128::from(inner)),
+            ref value => value.as_integer(),
+        })
+        .and_then(|inner| i64::try_from(inner).ok());
+    match inner {
+        Some(inner) if !out.is_null() => {
+            *out = inner;
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Write a number value to `out` as a `double`, returning whether it was a
+/// number.
+///
+/// # Safety
+/// See the [module](self) documentation.
+#[no_mangle]
+pub unsafe extern "C" fn gpnd_value_f64(value: *const Value<'static>, out: *mut f64) -> bool {
+    match value.as_ref().and_then(Value::as_float) {
+        Some(inner) if !out.is_null() => {
+            *out = inner;
+            true
+        }
+        _ => false,
+    }
+}
+
+/// The text of a `String` value, or null if it is not one.
+///
+/// # Safety
+/// See the [module](self) documentation.
+#[no_mangle]
+pub unsafe extern "C" fn gpnd_value_str(
+    value: *const Value<'static>,
+    len: *mut usize,
+) -> *const u8 {
+    match value.as_ref() {
+        Some(Value::String(inner)) => borrow_text(inner, len),
+        _ => ptr::null(),
+    }
+}
+
+/// The number of items of a list, or of entries of a map.
+///
+/// # Safety
+/// See the [module](self) documentation.
+#[no_mangle]
+pub unsafe extern "C" fn gpnd_value_len(value: *const Value<'static>) -> usize {
+    match value.as_ref() {
+        Some(Value::Map(entries)) => entries.len(),
+        Some(value) => value.items().map_or(0, <[_]>::len),
+        None => 0,
+    }
+}
+
+/// The item of a list at `index`.
+///
+/// # Safety
+/// See the [module](self) documentation.
+#[no_mangle]
+pub unsafe extern "C" fn gpnd_value_item(
+    value: *const Value<'static>,
+    index: usize,
+) -> *const Value<'static> {
+    value
+        .as_ref()
+        .and_then(Value::items)
+        .and_then(|items| items.get(index))
+        .map_or(ptr::null(), ptr::from_ref)
+}
+
+/// The entry of a map with the key of `len` bytes at `key`.
+///
+/// # Safety
+/// See the [module](self) documentation.
+#[no_mangle]
+pub unsafe extern "C" fn gpnd_value_get(
+    value: *const Value<'static>,
+    key: *const u8,
+    len: usize,
+) -> *const Value<'static> {
+    let key = text(key, len);
+    match (value.as_ref(), key) {
+        (Some(Value::Map(entries)), Some(key)) => {
+            entries.get(key).map_or(ptr::null(), ptr::from_ref)
+        }
+        _ => ptr::null(),
+    }
+}
+
+/// Free an error message.
+///
+/// # Safety
+/// See the [module](self) documentation.
+#[no_mangle]
+pub unsafe extern "C" fn gpnd_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CStr;
+
+    use super::*;
+
+    unsafe fn read(text: *const u8, len: usize) -> &'static str {
+        std::str::from_utf8(std::slice::from_raw_parts(text, len)).unwrap()
+    }
+
+    #[test]
+    fn test_ffi() {
+        let source = "server port=80 tags=[\"a\" \"b\"] { tls; log level=\"info\" }\nclient";
+        unsafe {
+            let mut error = ptr::null_mut();
+            let document = gpnd_parse(source.as_ptr(), source.len(), &raw mut error);
+            assert!(!document.is_null() && error.is_null());
+            assert_eq!(gpnd_document_len(document), 2);
+            assert!(gpnd_document_version(document, ptr::null_mut()).is_null());
+
+            let server = gpnd_document_node(document, 0);
+            let mut len = 0;
+            assert_eq!(read(gpnd_node_name(server, &raw mut len), len), "server");
+            assert_eq!(gpnd_node_child_count(server), 2);
+            let port = gpnd_node_param(server, "port".as_ptr(), 4);
+            assert_eq!(gpnd_value_kind(port), GpndKind::Integer);
+            let mut port_value = 0;
+            assert!(gpnd_value_i64(port, &raw mut port_value));
+            assert_eq!(port_value, 80);
+            let mut flag = false;
+            assert!(!gpnd_value_bool(port, &raw mut flag));
+            let tags = gpnd_node_param(server, "tags".as_ptr(), 4);
+            assert_eq!(gpnd_value_len(tags), 2);
+            let tag = gpnd_value_item(tags, 1);
+            assert_eq!(read(gpnd_value_str(tag, &raw mut len), len), "b");
+            assert!(gpnd_node_param(server, "host".as_ptr(), 4).is_null());
+
+            let query = "log[level]";
+            let list = gpnd_query(document, query.as_ptr(), query.len(), &raw mut error);
+            assert_eq!(gpnd_node_list_len(list), 1);
+            assert_eq!(gpnd_node_list_get(list, 0), gpnd_node_child(server, 1));
+            gpnd_node_list_free(list);
+
+            let list = gpnd_query(document, "[".as_ptr(), 1, &raw mut error);
+            assert!(list.is_null() && !error.is_null());
+            assert!(!CStr::from_ptr(error).to_bytes().is_empty());
+            gpnd_string_free(error);
+            gpnd_document_free(document);
+
+            let mut error = ptr::null_mut();
+            let document = gpnd_parse("a {".as_ptr(), 3, &raw mut error);
+            assert!(document.is_null());
+            assert_eq!(
+                CStr::from_ptr(error).to_str().unwrap(),
+                "1:4: expected `}`, found end of input"
+            );
+            gpnd_string_free(error);
+            assert_eq!(gpnd_document_len(ptr::null()), 0);
+        }
+    }
+}
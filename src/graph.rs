@@ -0,0 +1,192 @@
+//! Exporting the structure of a document as a graph, to visualize or analyze
+//! it with graph tools.
+//!
+//! A [`Graph`] is a list of labeled nodes and an edge list, which
+//! [`write_dot`] writes in the DOT language of Graphviz.
+//!
+//! ```
+//! # use gpnd::graph::{render_dot, Graph};
+//! let document = gpnd::parser::parse("server \"main\" { tls }").unwrap();
+//! let graph = Graph::new(&document);
+//! assert_eq!(graph.edges, [(0, 1)]);
+//! assert_eq!(
+//!     render_dot(&graph),
+//!     "digraph {\n    n0 [label=\"server \\\"main\\\"\"];\n    n1 [label=\"tls\"];\n    n0 -> \
+//!      n1;\n}\n"
+//! );
+//! ```
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+
+use crate::diff::occurrences;
+use crate::{Document, Node, NodePath, Span};
+
+/// The nodes of a document, in depth-first order, with an edge from each
+/// node to each of its children.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Graph {
+    pub nodes: Vec<GraphNode>,
+    /// The edges from parents to children, as indices into `nodes`.
+    pub edges: Vec<(usize, usize)>,
+}
+
+/// A node of a [`Graph`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GraphNode {
+    /// The name of the node, followed by its arguments and parameters as
+    /// they are written.
+    pub label: String,
+    pub path: NodePath,
+    pub span: Option<Span>,
+}
+
+impl Graph {
+    /// Build the graph of `document`.
+    #[must_use]
+    pub fn new<D>(document: &D) -> Self
+    where
+        D: Document + ?Sized,
+    {
+        let mut graph = Self::default();
+        graph.add_nodes(None, &NodePath::new(), &document.nodes());
+        graph
+    }
+
+    /// The indices of the top-level nodes, which have no parent.
+    pub fn roots(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.nodes.len()).filter(|index| !self.edges.iter().any(|(_, child)| child == index))
+    }
+
+    /// The indices of the children of the node at `index`.
+    pub fn children(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+        self.edges
+            .iter()
+            .filter(move |(parent, _)| *parent == index)
+            .map(|(_, child)| *child)
+    }
+
+    fn add_nodes(&mut self, parent: Option<usize>, path: &NodePath, nodes: &[&dyn Node]) {
+        for (node, (name, occurrence)) in nodes.iter().zip(occurrences(nodes)) {
+            let index = self.nodes.len();
+            let path = path.child(name, occurrence);
+            self.nodes.push(GraphNode {
+                label: label(*node),
+                path: path.clone(),
+                span: node.span(),
+            });
+            if let Some(parent) = parent {
+                self.edges.push((parent, index));
+            }
+            self.add_nodes(Some(index), &path, &node.children());
+        }
+    }
+}
+
+/// Write the name of `node`, with its arguments and parameters.
+fn label(node: &dyn Node) -> String {
+    let mut label = String::new();
+    if let Some(namespace) = node.namespace() {
+        label.push_str(namespace);
+        label.push(':');
+    }
+    label.push_str(node.name());
+    for value in node.args_iter() {
+        write!(label, " {value}").expect("writing to a `String` does not fail");
+    }
+    for (key, value) in node.params_all() {
+        write!(label, " {key}={value}").expect("writing to a `String` does not fail");
+    }
+    label
+}
+
+/// Write `graph` as a directed graph in the DOT language of Graphviz, with
+/// the nodes named `n0`, `n1`, and so on.
+///
+/// # Errors
+/// Fails if writing to `f` fails.
+pub fn write_dot<W>(f: &mut W, graph: &Graph) -> fmt::Result
+where
+    W: Write,
+{
+    f.write_str("digraph {\n")?;
+    for (index, node) in graph.nodes.iter().enumerate() {
+        write!(f, "    n{index} [label=\"")?;
+        for ch in node.label.chars() {
+            match ch {
+                '"' => f.write_str("\\\"")?,
+                '\\' => f.write_str("\\\\")?,
+                '\n' => f.write_str("\\n")?,
+                _ => f.write_char(ch)?,
+            }
+        }
+        f.write_str("\"];\n")?;
+    }
+    for (parent, child) in &graph.edges {
+        writeln!(f, "    n{parent} -> n{child};")?;
+    }
+    f.write_str("}\n")
+}
+
+/// Write `graph` in the DOT language, see [`write_dot`].
+#[allow(clippy::missing_panics_doc)]
+#[must_use]
+pub fn render_dot(graph: &Graph) -> String {
+    let mut string = String::new();
+    write_dot(&mut string, graph).expect("writing to a `String` does not fail");
+    string
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use alloc::vec::Vec;
+
+    use super::{render_dot, Graph};
+    use crate::parser::parse;
+    use crate::DocumentBuf;
+
+    #[test]
+    fn test_graph() {
+        assert_eq!(
+            render_dot(&Graph::new(&DocumentBuf::new())),
+            "digraph {\n}\n"
+        );
+
+        let document =
+            parse("http:server 1 port=80 { route \"a\\\\b\"; route { log } }\nclient").unwrap();
+        let graph = Graph::new(&document);
+        let labels: Vec<_> = graph.nodes.iter().map(|node| node.label.as_str()).collect();
+        assert_eq!(
+            labels,
+            [
+                "http:server 1 port=80",
+                "route \"a\\\\b\"",
+                "route",
+                "log",
+                "client"
+            ]
+        );
+        assert_eq!(graph.edges, [(0, 1), (0, 2), (2, 3)]);
+        assert_eq!(graph.roots().collect::<Vec<_>>(), [0, 4]);
+        assert_eq!(graph.children(0).collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(graph.nodes[3].path.to_string(), "server > route[1] > log");
+        assert_eq!(graph.nodes[3].span.unwrap().to_string(), "1:47");
+        assert_eq!(
+            render_dot(&graph),
+            concat!(
+                "digraph {\n",
+                "    n0 [label=\"http:server 1 port=80\"];\n",
+                "    n1 [label=\"route \\\"a\\\\\\\\b\\\"\"];\n",
+                "    n2 [label=\"route\"];\n",
+                "    n3 [label=\"log\"];\n",
+                "    n4 [label=\"client\"];\n",
+                "    n0 -> n1;\n",
+                "    n0 -> n2;\n",
+                "    n2 -> n3;\n",
+                "}\n",
+            )
+        );
+    }
+}
@@ -1,3 +1,4 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 #![warn(clippy::pedantic)]
 #![warn(unused_crate_dependencies)]
 // FIXME change before release
@@ -21,20 +22,72 @@
 #![warn(missing_debug_implementations)]
 // Unreliable or unintended behavior
 #![warn(unreachable_pub)]
-#![warn(unused_tuple_struct_fields)]
 #![warn(variant_size_differences)]
 #![deny(let_underscore_drop)]
-#![deny(pointer_structural_match)]
 #![deny(unsafe_code)]
 
+extern crate alloc;
+// Tests run with `std` even when the crate is built without it.
+#[cfg(all(test, not(feature = "std")))]
+extern crate std;
+
+// Allows the derive macros to refer to `::gpnd` from within this crate.
+extern crate self as gpnd;
+
+#[cfg(feature = "arbitrary")]
+mod arbitrary;
+pub mod bridge;
+pub mod builder;
+pub mod diff;
+pub mod emit;
+pub mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod graph;
+pub mod lint;
+mod macros;
+pub mod node_path;
+pub mod owned;
 pub mod parser;
+pub mod patch;
+#[cfg(feature = "std")]
+pub mod pipeline;
+pub mod query;
+pub mod redact;
+pub mod schema;
+pub mod span;
+pub mod stats;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod tooling;
 pub mod traits;
 pub mod value;
+pub mod value_enum;
+pub mod warning;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+// Allows the derive macros, and the `value!` macro, to name `alloc` items in
+// crates without `std`.
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::collections::BTreeMap;
+    pub use alloc::vec;
+    pub use alloc::vec::Vec;
+}
 
 pub(crate) use private::Sealed;
 pub(crate) mod private {
     pub trait Sealed {}
 }
 
+pub use error::*;
+#[cfg(feature = "derive")]
+pub use gpnd_derive::{Document, Node, ValueEnum};
+pub use node_path::*;
+pub use owned::*;
+pub use span::*;
 pub use traits::*;
 pub use value::*;
+pub use value_enum::*;
+pub use warning::*;
@@ -0,0 +1,681 @@
+//! Checking documents for likely mistakes that are still valid syntax.
+//!
+//! A [`Linter`] runs a list of [`Rule`]s over a document, each at a
+//! [`Severity`], and collects the [`Diagnostic`]s they report. Diagnostics
+//! have the same paths and spans as schema [`Violation`]s, and violations,
+//! [`ParseError`]s, and [`Warning`]s all convert into diagnostics, so that
+//! every problem with a document can be reported the same way.
+//!
+//! The built-in rules are [`DuplicateNodes`], [`UnknownParams`],
+//! [`NumericNarrowing`], and [`NamingCase`].
+//!
+//! At most [`DEFAULT_MAX_DIAGNOSTICS`] diagnostics are collected by default,
+//! see [`Linter::max_diagnostics`].
+//!
+//! ```
+//! # use gpnd::lint::{Linter, Severity};
+//! let document = gpnd::parser::parse("server port=80\nserver port=80").unwrap();
+//! let diagnostics = Linter::with_defaults().lint(&document);
+//! assert_eq!(
+//!     diagnostics[0].to_string(),
+//!     "server[1] (at 2:1): warning: duplicate of `server` [duplicate-nodes]"
+//! );
+//! ```
+
+use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+
+use crate::diff::occurrences;
+use crate::parser::ParseError;
+use crate::schema::{validate, Schema, Violation, ViolationKind};
+use crate::{node_eq, Document, Node, NodePath, Span, Value, Warning, WarningKind};
+
+/// How serious a [`Diagnostic`] is.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Hint,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Hint => "hint",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        })
+    }
+}
+
+/// A problem reported by a [`Rule`], with the path and span of the node it
+/// was found on, as in a [`Violation`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The name of the rule, see [`Rule::name`].
+    pub rule: &'static str,
+    pub severity: Severity,
+    pub path: NodePath,
+    pub span: Option<Span>,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.path, self.span) {
+            (path, Some(span)) if path.is_empty() => write!(f, "{span}: ")?,
+            (path, None) if path.is_empty() => {}
+            (path, Some(span)) => write!(f, "{path} (at {span}): ")?,
+            (path, None) => write!(f, "{path}: ")?,
+        }
+        write!(f, "{}: {} [{}]", self.severity, self.message, self.rule)
+    }
+}
+
+/// An error reported by the `parse` rule.
+impl From<ParseError> for Diagnostic {
+    fn from(error: ParseError) -> Self {
+        Self {
+            rule: "parse",
+            severity: Severity::Error,
+            path: NodePath::new(),
+            span: Some(error.span()),
+            message: error.kind().to_string(),
+        }
+    }
+}
+
+/// An error reported by the `schema` rule, or by the `max-diagnostics` rule
+/// for [`ViolationKind::TooManyViolations`].
+impl From<Violation> for Diagnostic {
+    fn from(violation: Violation) -> Self {
+        Self {
+            rule: match violation.kind {
+                ViolationKind::TooManyViolations => "max-diagnostics",
+                _ => "schema",
+            },
+            severity: Severity::Error,
+            path: violation.path,
+            span: violation.span,
+            message: violation.kind.to_string(),
+        }
+    }
+}
+
+/// A warning from the parser or the schema validator, reported by a rule
+/// named after its kind.
+impl From<Warning> for Diagnostic {
+    fn from(warning: Warning) -> Self {
+        Self {
+            rule: match warning.kind {
+                WarningKind::DuplicateParam(_) => "duplicate-params",
+                WarningKind::DuplicateMapKey(_) => "duplicate-map-keys",
+                WarningKind::RoundedNumber(_) => "rounded-numbers",
+                WarningKind::DeprecatedNode(_) => "deprecated-nodes",
+                WarningKind::TooManyWarnings => "max-diagnostics",
+            },
+            severity: Severity::Warning,
+            path: warning.path,
+            span: warning.span,
+            message: warning.kind.to_string(),
+        }
+    }
+}
+
+/// A node that a [`Rule`] checks, with its path.
+#[derive(Clone)]
+pub struct Sibling<'doc> {
+    pub node: &'doc dyn Node,
+    pub path: NodePath,
+}
+
+impl fmt::Debug for Sibling<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sibling")
+            .field("node", &self.node.name())
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+/// How many diagnostics a [`Linter`] collects by default, see
+/// [`Linter::max_diagnostics`], and how many warnings and violations the
+/// parser and the schema validator do.
+pub const DEFAULT_MAX_DIAGNOSTICS: usize = 100;
+
+/// Push the result of `item` onto `list`, unless it already has `limit`
+/// items. The first time it is full, the result of `marker` is pushed
+/// instead, to say that the rest were dropped.
+pub(crate) fn push_limited<T, I, M>(list: &mut Vec<T>, limit: usize, item: I, marker: M)
+where
+    I: FnOnce() -> T,
+    M: FnOnce() -> T,
+{
+    match list.len().cmp(&limit) {
+        Ordering::Less => list.push(item()),
+        Ordering::Equal => list.push(marker()),
+        Ordering::Greater => {}
+    }
+}
+
+/// Collects the diagnostics of one [`Rule`], at the severity it runs at.
+#[derive(Debug)]
+pub struct Reporter<'lint> {
+    rule: &'static str,
+    severity: Severity,
+    diagnostics: &'lint mut Vec<Diagnostic>,
+    limit: usize,
+}
+
+impl<'lint> Reporter<'lint> {
+    fn new(
+        rule: &dyn Rule,
+        severity: Severity,
+        diagnostics: &'lint mut Vec<Diagnostic>,
+        limit: usize,
+    ) -> Self {
+        Self {
+            rule: rule.name(),
+            severity,
+            diagnostics,
+            limit,
+        }
+    }
+
+    /// Whether the linter has collected as many diagnostics as it may, so
+    /// that further reports are dropped.
+    #[must_use]
+    pub fn is_full(&self) -> bool {
+        self.diagnostics.len() >= self.limit
+    }
+
+    pub fn report<S>(&mut self, path: &NodePath, span: Option<Span>, message: S)
+    where
+        S: Into<String>,
+    {
+        push_limited(
+            self.diagnostics,
+            self.limit,
+            || Diagnostic {
+                rule: self.rule,
+                severity: self.severity,
+                path: path.clone(),
+                span,
+                message: message.into(),
+            },
+            || Diagnostic {
+                rule: "max-diagnostics",
+                severity: Severity::Error,
+                path: NodePath::new(),
+                span: None,
+                message: "too many diagnostics, stopping".to_owned(),
+            },
+        );
+    }
+
+    /// Report a problem with `sibling`, at the span of the node.
+    pub fn report_node<S>(&mut self, sibling: &Sibling<'_>, message: S)
+    where
+        S: Into<String>,
+    {
+        self.report(&sibling.path, sibling.node.span(), message);
+    }
+}
+
+/// A check run by a [`Linter`].
+///
+/// A rule can check every list of siblings, which are the top-level nodes
+/// and the children of each node, or the whole document at once.
+pub trait Rule {
+    /// The name of the rule in diagnostics, in kebab case.
+    fn name(&self) -> &'static str;
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_siblings(&self, _siblings: &[Sibling<'_>], _reporter: &mut Reporter<'_>) {}
+
+    fn check_document(&self, _document: &dyn Document, _reporter: &mut Reporter<'_>) {}
+}
+
+/// Runs [`Rule`]s over documents, see the [module](self) documentation.
+pub struct Linter {
+    rules: Vec<(Box<dyn Rule>, Severity)>,
+    max_diagnostics: usize,
+}
+
+impl Default for Linter {
+    fn default() -> Self {
+        Self {
+            rules: Vec::new(),
+            max_diagnostics: DEFAULT_MAX_DIAGNOSTICS,
+        }
+    }
+}
+
+impl Linter {
+    /// A linter without any rules.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A linter with the built-in rules that need no configuration, which
+    /// are [`DuplicateNodes`], [`NumericNarrowing`], and [`NamingCase`].
+    #[must_use]
+    pub fn with_defaults() -> Self {
+        Self::new()
+            .rule(DuplicateNodes)
+            .rule(NumericNarrowing)
+            .rule(NamingCase::default())
+    }
+
+    /// Add a rule, at its default severity.
+    #[must_use]
+    pub fn rule<R>(self, rule: R) -> Self
+    where
+        R: Rule + 'static,
+    {
+        let severity = rule.default_severity();
+        self.rule_with_severity(rule, severity)
+    }
+
+    #[must_use]
+    pub fn rule_with_severity<R>(mut self, rule: R, severity: Severity) -> Self
+    where
+        R: Rule + 'static,
+    {
+        self.rules.push((Box::new(rule), severity));
+        self
+    }
+
+    /// Stop after collecting `max` diagnostics, so that a pathological
+    /// document cannot grow the list without bound. Once the limit is
+    /// reached, a last `max-diagnostics` error says that later diagnostics
+    /// were dropped.
+    #[must_use]
+    pub fn max_diagnostics(mut self, max: usize) -> Self {
+        self.max_diagnostics = max;
+        self
+    }
+
+    /// Check `document` with every rule.
+    ///
+    /// The diagnostics of each list of siblings come before those of their
+    /// children, and those of rules that check the whole document come last.
+    #[must_use]
+    pub fn lint(&self, document: &dyn Document) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        self.lint_siblings(&NodePath::new(), &document.nodes(), &mut diagnostics);
+        for (rule, severity) in &self.rules {
+            let mut reporter =
+                Reporter::new(&**rule, *severity, &mut diagnostics, self.max_diagnostics);
+            if reporter.is_full() {
+                break;
+            }
+            rule.check_document(document, &mut reporter);
+        }
+        diagnostics
+    }
+
+    fn lint_siblings(
+        &self,
+        parent: &NodePath,
+        nodes: &[&dyn Node],
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        if diagnostics.len() >= self.max_diagnostics {
+            return;
+        }
+        let siblings: Vec<_> = nodes
+            .iter()
+            .zip(occurrences(nodes))
+            .map(|(node, key)| Sibling {
+                node: *node,
+                path: parent.child(key.0, key.1),
+            })
+            .collect();
+        for (rule, severity) in &self.rules {
+            rule.check_siblings(
+                &siblings,
+                &mut Reporter::new(&**rule, *severity, diagnostics, self.max_diagnostics),
+            );
+        }
+        for sibling in &siblings {
+            self.lint_siblings(&sibling.path, &sibling.node.children(), diagnostics);
+        }
+    }
+}
+
+impl fmt::Debug for Linter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(
+                self.rules
+                    .iter()
+                    .map(|(rule, severity)| (rule.name(), severity)),
+            )
+            .finish()
+    }
+}
+
+/// Reports siblings that are equal to an earlier sibling, by [`node_eq`].
+#[derive(Copy, Clone, Debug, Default)]
+pub struct DuplicateNodes;
+
+impl Rule for DuplicateNodes {
+    fn name(&self) -> &'static str {
+        "duplicate-nodes"
+    }
+
+    fn check_siblings(&self, siblings: &[Sibling<'_>], reporter: &mut Reporter<'_>) {
+        for (index, sibling) in siblings.iter().enumerate() {
+            let original = siblings[..index]
+                .iter()
+                .find(|other| node_eq(other.node, sibling.node));
+            if let Some(original) = original {
+                reporter.report_node(sibling, format!("duplicate of `{}`", original.path));
+            }
+        }
+    }
+}
+
+/// Reports parameters that a schema does not declare.
+///
+/// Other violations of the schema are not reported, so that a document can
+/// be linted against a schema that is not complete.
+#[derive(Clone, Debug)]
+pub struct UnknownParams {
+    pub schema: Schema,
+}
+
+impl Rule for UnknownParams {
+    fn name(&self) -> &'static str {
+        "unknown-params"
+    }
+
+    fn check_document(&self, document: &dyn Document, reporter: &mut Reporter<'_>) {
+        let Err(violations) = validate(document, &self.schema) else {
+            return;
+        };
+        for violation in violations {
+            if let ViolationKind::UnknownParam { .. } = violation.kind {
+                reporter.report(&violation.path, violation.span, violation.kind.to_string());
+            }
+        }
+    }
+}
+
+/// The largest integer below which every integer is exactly a float.
+const MAX_EXACT_FLOAT: i128 = 1 << f64::MANTISSA_DIGITS;
+
+/// Reports integers that cannot be represented exactly as floats, which
+/// applications and formats that read every number as a float, such as
+/// JSON in JavaScript, would silently round.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct NumericNarrowing;
+
+impl NumericNarrowing {
+    fn is_inexact(value: &Value<'_>) -> bool {
+        match value {
+            Value::List(_) | Value::Slice(_) => {
+                value.items().into_iter().flatten().any(Self::is_inexact)
+            }
+            Value::Map(entries) => entries.values().any(Self::is_inexact),
+            _ => value
+                .as_integer()
+                .is_some_and(|integer| integer.abs() > MAX_EXACT_FLOAT),
+        }
+    }
+}
+
+impl Rule for NumericNarrowing {
+    fn name(&self) -> &'static str {
+        "numeric-narrowing"
+    }
+
+    fn check_siblings(&self, siblings: &[Sibling<'_>], reporter: &mut Reporter<'_>) {
+        for sibling in siblings {
+            let node = sibling.node;
+            for (index, value) in node.args().iter().enumerate() {
+                if Self::is_inexact(value) {
+                    let message =
+                        format!("argument {index} cannot be represented exactly as a float");
+                    reporter.report(&sibling.path, node.arg_span(index).or(node.span()), message);
+                }
+            }
+            for (key, value) in node.params() {
+                if Self::is_inexact(&value) {
+                    let message =
+                        format!("parameter `{key}` cannot be represented exactly as a float");
+                    reporter.report(&sibling.path, node.param_span(key).or(node.span()), message);
+                }
+            }
+        }
+    }
+}
+
+/// A style of writing names made of several words.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Case {
+    /// `snake_case`
+    Snake,
+    /// `SCREAMING_SNAKE_CASE`
+    ScreamingSnake,
+    /// `kebab-case`
+    Kebab,
+    /// `camelCase`
+    Camel,
+    /// `PascalCase`
+    Pascal,
+}
+
+impl Case {
+    const ALL: [Self; 5] = [
+        Self::Snake,
+        Self::ScreamingSnake,
+        Self::Kebab,
+        Self::Camel,
+        Self::Pascal,
+    ];
+
+    /// The case of `name`, or [`None`] if it is a single lowercase word,
+    /// which fits several cases, or mixes cases.
+    #[must_use]
+    pub fn of(name: &str) -> Option<Self> {
+        let lower = name.chars().any(char::is_lowercase);
+        let upper = name.chars().any(char::is_uppercase);
+        let alphanumeric = |separator| {
+            name.chars()
+                .all(|ch| ch.is_alphanumeric() || ch == separator)
+        };
+        if name.contains('_') && alphanumeric('_') {
+            match (lower, upper) {
+                (true, false) => Some(Self::Snake),
+                (false, true) => Some(Self::ScreamingSnake),
+                _ => None,
+            }
+        } else if name.contains('-') && alphanumeric('-') {
+            (!upper).then_some(Self::Kebab)
+        } else if alphanumeric('_') && lower && upper {
+            if name.starts_with(char::is_uppercase) {
+                Some(Self::Pascal)
+            } else {
+                Some(Self::Camel)
+            }
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for Case {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Snake => "snake_case",
+            Self::ScreamingSnake => "SCREAMING_SNAKE_CASE",
+            Self::Kebab => "kebab-case",
+            Self::Camel => "camelCase",
+            Self::Pascal => "PascalCase",
+        })
+    }
+}
+
+/// Reports node names and parameter keys that are written in a different
+/// [`Case`] from the rest.
+///
+/// The expected case is `case`, or if it is [`None`], the case of most of
+/// the names in the document.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct NamingCase {
+    pub case: Option<Case>,
+}
+
+impl Rule for NamingCase {
+    fn name(&self) -> &'static str {
+        "naming-case"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Hint
+    }
+
+    fn check_document(&self, document: &dyn Document, reporter: &mut Reporter<'_>) {
+        let mut names = Vec::new();
+        collect_names(&NodePath::new(), &document.nodes(), &mut names);
+        let expected = self.case.or_else(|| {
+            Case::ALL
+                .into_iter()
+                .map(|case| {
+                    (
+                        names.iter().filter(|name| name.case == Some(case)).count(),
+                        case,
+                    )
+                })
+                .filter(|(count, _)| *count > 0)
+                .max_by_key(|(count, _)| *count)
+                .map(|(_, case)| case)
+        });
+        let Some(expected) = expected else {
+            return;
+        };
+        for name in names {
+            match name.case {
+                Some(case) if case != expected => reporter.report(
+                    &name.path,
+                    name.span,
+                    format!("`{}` is {case}, expected {expected}", name.text),
+                ),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// A name checked by [`NamingCase`].
+struct Name<'doc> {
+    text: &'doc str,
+    case: Option<Case>,
+    path: NodePath,
+    span: Option<Span>,
+}
+
+fn collect_names<'doc>(parent: &NodePath, nodes: &[&'doc dyn Node], names: &mut Vec<Name<'doc>>) {
+    for (node, key) in nodes.iter().zip(occurrences(nodes)) {
+        let path = parent.child(key.0, key.1);
+        names.push(Name {
+            text: node.name(),
+            case: Case::of(node.name()),
+            path: path.clone(),
+            span: node.span(),
+        });
+        for (key, _) in node.params() {
+            names.push(Name {
+                text: key,
+                case: Case::of(key),
+                path: path.clone(),
+                span: node.span(),
+            });
+        }
+        collect_names(&path, &node.children(), names);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+    use crate::schema::{NodeSchema, ValueType};
+
+    #[test]
+    fn test_case_of() {
+        assert_eq!(Case::of("max_size"), Some(Case::Snake));
+        assert_eq!(Case::of("MAX_SIZE"), Some(Case::ScreamingSnake));
+        assert_eq!(Case::of("max-size"), Some(Case::Kebab));
+        assert_eq!(Case::of("maxSize"), Some(Case::Camel));
+        assert_eq!(Case::of("MaxSize"), Some(Case::Pascal));
+        assert_eq!(Case::of("size"), None);
+        assert_eq!(Case::of("Max_size"), None);
+    }
+
+    #[test]
+    fn test_lint() {
+        let document = parse(concat!(
+            "server port=80 max_size=1 { route \"/\"; route \"/\" }\n",
+            "server port=80 maxSize=9007199254740993\n",
+            "client retry_count=1 limits=[1 -9007199254740993]\n",
+        ))
+        .unwrap();
+        let schema = Schema::new().allow_unknown_nodes().node(
+            NodeSchema::new("client").param("limits", ValueType::List),
+            crate::schema::Cardinality::ANY,
+        );
+        let linter =
+            Linter::with_defaults().rule_with_severity(UnknownParams { schema }, Severity::Error);
+        let diagnostics: Vec<_> = linter
+            .lint(&document)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            diagnostics,
+            [
+                "server[1] (at 2:24): warning: parameter `maxSize` cannot be represented exactly \
+                 as a float [numeric-narrowing]",
+                "client (at 3:29): warning: parameter `limits` cannot be represented exactly as a \
+                 float [numeric-narrowing]",
+                "server > route[1] (at 1:40): warning: duplicate of `server > route` \
+                 [duplicate-nodes]",
+                "server[1] (at 2:1): hint: `maxSize` is camelCase, expected snake_case \
+                 [naming-case]",
+                "client (at 3:1): error: unknown parameter `retry_count` [unknown-params]",
+            ]
+        );
+
+        let diagnostic = Diagnostic::from(parse("a {").unwrap_err());
+        assert_eq!(
+            diagnostic.to_string(),
+            "1:4: error: expected `}`, found end of input [parse]"
+        );
+
+        let document = parse(&"a; ".repeat(200)).unwrap();
+        let diagnostics = Linter::with_defaults().max_diagnostics(3).lint(&document);
+        let diagnostics: Vec<_> = diagnostics.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            diagnostics,
+            [
+                "a[1] (at 1:4): warning: duplicate of `a` [duplicate-nodes]",
+                "a[2] (at 1:7): warning: duplicate of `a` [duplicate-nodes]",
+                "a[3] (at 1:10): warning: duplicate of `a` [duplicate-nodes]",
+                "error: too many diagnostics, stopping [max-diagnostics]",
+            ]
+        );
+        assert_eq!(Linter::with_defaults().lint(&document).len(), 101);
+    }
+}
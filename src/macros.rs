@@ -0,0 +1,185 @@
+/// Build a [`NodeBuf`](crate::NodeBuf) inline.
+///
+/// The name comes first, followed by arguments and `key = value` parameters in
+/// any order, and optionally a block of child nodes separated by commas. Keys
+/// can be identifiers or string literals, and values can be literals,
+/// identifiers, or any expression in parentheses.
+///
+/// ```
+/// use gpnd::{node, Node};
+///
+/// let port = 8080;
+/// let server = node!("server" "localhost" port tls=true "max-conn"=(16 * 4) {
+///     node!("cert" "/etc/cert.pem"),
+///     node!("log" level="debug"),
+/// });
+/// assert_eq!(server.get_arg(1), Some(port.into()));
+/// assert_eq!(server.children().len(), 2);
+/// ```
+#[macro_export]
+macro_rules! node {
+    (@munch $builder:expr;) => {
+        $builder.build()
+    };
+    (@munch $builder:expr; { $($child:expr),* $(,)? }) => {
+        $builder$(.child($child))*.build()
+    };
+    (@munch $builder:expr; $key:ident = - $value:tt $($rest:tt)*) => {
+        $crate::node!(@munch $builder.param(stringify!($key), -$value); $($rest)*)
+    };
+    (@munch $builder:expr; $key:ident = $value:tt $($rest:tt)*) => {
+        $crate::node!(@munch $builder.param(stringify!($key), $value); $($rest)*)
+    };
+    (@munch $builder:expr; $key:literal = - $value:tt $($rest:tt)*) => {
+        $crate::node!(@munch $builder.param($key, -$value); $($rest)*)
+    };
+    (@munch $builder:expr; $key:literal = $value:tt $($rest:tt)*) => {
+        $crate::node!(@munch $builder.param($key, $value); $($rest)*)
+    };
+    (@munch $builder:expr; - $arg:tt $($rest:tt)*) => {
+        $crate::node!(@munch $builder.arg(-$arg); $($rest)*)
+    };
+    (@munch $builder:expr; $arg:tt $($rest:tt)*) => {
+        $crate::node!(@munch $builder.arg($arg); $($rest)*)
+    };
+    ($name:tt $($rest:tt)*) => {
+        $crate::node!(@munch $crate::builder::NodeBuilder::new($name); $($rest)*)
+    };
+}
+
+/// Build a [`DocumentBuf`](crate::DocumentBuf) inline, from nodes separated by
+/// commas, usually built with [`node!`].
+///
+/// ```
+/// use gpnd::{document, node, Document};
+///
+/// let document = document![node!("server" port=80), node!("client")];
+/// assert_eq!(document.nodes().len(), 2);
+/// ```
+#[macro_export]
+macro_rules! document {
+    ($($node:expr),* $(,)?) => {
+        $crate::builder::DocumentBuilder::new()$(.node($node))*.build()
+    };
+}
+
+/// Build a [`Value`](crate::Value) inline, with lists in brackets and maps
+/// in braces, whose items and `key = value` entries are separated by commas.
+///
+/// Keys can be identifiers or string literals. Values can be `null`, nested
+/// lists and maps, literals, identifiers, or any expression in parentheses,
+/// as in [`node!`].
+///
+/// ```
+/// use gpnd::{value, Value};
+///
+/// let port = 8080;
+/// let value = value!({ hosts = ["a", "b"], port = port, limits = { burst = -1, rate = null } });
+/// assert_eq!(value.to_string(), r#"#{"hosts"=["a" "b"] "limits"=#{"burst"=-1 "rate"=null} "port"=8080}"#);
+/// assert_eq!(value!([1, (2 + 3), "six"]), Value::from([Value::I32(1), Value::I32(5), Value::from("six")]));
+/// ```
+#[macro_export]
+macro_rules! value {
+    (@list [$($item:expr),*] $(,)?) => {
+        $crate::Value::from($crate::__private::vec![$($item),*])
+    };
+    (@list [$($item:expr),*] - $value:tt $(, $($rest:tt)*)?) => {
+        $crate::value!(@list [$($item,)* $crate::value!(-$value)] $($($rest)*)?)
+    };
+    (@list [$($item:expr),*] $value:tt $(, $($rest:tt)*)?) => {
+        $crate::value!(@list [$($item,)* $crate::value!($value)] $($($rest)*)?)
+    };
+    (@map [$(($key:expr, $value:expr)),*] $(,)?) => {
+        $crate::Value::from(
+            <$crate::__private::BTreeMap<_, _> as ::core::iter::FromIterator<_>>::from_iter([
+                $((::core::convert::Into::into($key), $value)),*
+            ]),
+        )
+    };
+    (@map [$($entry:tt),*] $key:ident = - $value:tt $(, $($rest:tt)*)?) => {
+        $crate::value!(@map [$($entry,)* (stringify!($key), $crate::value!(-$value))] $($($rest)*)?)
+    };
+    (@map [$($entry:tt),*] $key:ident = $value:tt $(, $($rest:tt)*)?) => {
+        $crate::value!(@map [$($entry,)* (stringify!($key), $crate::value!($value))] $($($rest)*)?)
+    };
+    (@map [$($entry:tt),*] $key:literal = - $value:tt $(, $($rest:tt)*)?) => {
+        $crate::value!(@map [$($entry,)* ($key, $crate::value!(-$value))] $($($rest)*)?)
+    };
+    (@map [$($entry:tt),*] $key:literal = $value:tt $(, $($rest:tt)*)?) => {
+        $crate::value!(@map [$($entry,)* ($key, $crate::value!($value))] $($($rest)*)?)
+    };
+    (null) => {
+        $crate::Value::Null
+    };
+    ([$($items:tt)*]) => {
+        $crate::value!(@list [] $($items)*)
+    };
+    ({$($entries:tt)*}) => {
+        $crate::value!(@map [] $($entries)*)
+    };
+    (- $value:tt) => {
+        $crate::Value::from(-$value)
+    };
+    ($value:expr) => {
+        $crate::Value::from($value)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::borrow::ToOwned;
+    use alloc::boxed::Box;
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    use crate::{Node, NodeBuf, Value};
+
+    #[test]
+    fn test_node_macro() {
+        let name = "main";
+        let node = node!("server" name -1 offset=-2 tls=true "log-level"=("de".to_owned() + "bug") {
+            node!("cert" "path"),
+            NodeBuf::new("empty"),
+        });
+        assert_eq!(node.name(), "server");
+        assert_eq!(node.args(), [Value::from("main"), Value::I32(-1)]);
+        assert_eq!(node.get_param("offset"), Some(Value::I32(-2)));
+        assert_eq!(node.get_param("tls"), Some(Value::Bool(true)));
+        assert_eq!(node.get_param("log-level"), Some(Value::from("debug")));
+        assert_eq!(node.children().len(), 2);
+        assert_eq!(node!("empty"), NodeBuf::new("empty"));
+    }
+
+    #[test]
+    fn test_value_macro() {
+        let name = "main";
+        assert_eq!(value!(null), Value::Null);
+        assert_eq!(value!(-1), Value::I32(-1));
+        assert_eq!(value!([]), Value::List(Box::default()));
+        assert_eq!(value!({}), Value::Map(Box::default()));
+        assert_eq!(
+            value!([1, -2.5, name, ("a".to_owned() + "b"), null, [true], {},]),
+            Value::List(Box::new(vec![
+                Value::I32(1),
+                Value::F64(-2.5),
+                Value::from("main"),
+                Value::from("ab"),
+                Value::Null,
+                Value::from([true]),
+                Value::Map(Box::default()),
+            ]))
+        );
+        let value = value!({ "log-level" = "debug", retry = { count = -3 }, name = name });
+        assert_eq!(
+            value.to_string(),
+            r#"#{"log-level"="debug" "name"="main" "retry"=#{"count"=-3}}"#
+        );
+    }
+
+    #[test]
+    fn test_document_macro() {
+        let document = document![node!("a"), node!("b" 1),];
+        assert_eq!(document.nodes_ref().len(), 2);
+        assert_eq!(document!(), crate::DocumentBuf::new());
+    }
+}
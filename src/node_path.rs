@@ -0,0 +1,192 @@
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::diff::occurrences;
+use crate::{Document, Node};
+
+/// The names of a node and its ancestors, such as `server > tls > cert[1]`,
+/// which tells which nested node a diagnostic refers to even when it has no
+/// span.
+///
+/// Each segment is a name and the number of earlier siblings with the same
+/// name, which is written in brackets if it is not zero. A path is found for a
+/// node from traversal or a query with [`Document::node_path`].
+///
+/// ```
+/// # use gpnd::Document;
+/// let document = gpnd::parser::parse("server { tls { cert; cert } }").unwrap();
+/// let cert = document.query("cert").unwrap()[1];
+/// let path = document.node_path(cert).unwrap();
+/// assert_eq!(path.to_string(), "server > tls > cert[1]");
+/// assert!(core::ptr::addr_eq(path.resolve(&document).unwrap(), cert));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct NodePath {
+    segments: Vec<PathSegment>,
+}
+
+/// A segment of a [`NodePath`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PathSegment {
+    pub name: String,
+    /// The number of earlier siblings with the same name.
+    pub occurrence: usize,
+}
+
+impl NodePath {
+    /// Create the empty path, of the top level of a document.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.segments.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    pub fn push<S>(&mut self, name: S, occurrence: usize)
+    where
+        S: Into<String>,
+    {
+        self.segments.push(PathSegment {
+            name: name.into(),
+            occurrence,
+        });
+    }
+
+    pub fn pop(&mut self) -> Option<PathSegment> {
+        self.segments.pop()
+    }
+
+    /// Get the path of a child of the node at this path.
+    #[must_use]
+    pub fn child<S>(&self, name: S, occurrence: usize) -> Self
+    where
+        S: Into<String>,
+    {
+        let mut path = self.clone();
+        path.push(name, occurrence);
+        path
+    }
+
+    /// Get the path of the parent of the node at this path, or `None` if the
+    /// path is empty.
+    #[must_use]
+    pub fn parent(&self) -> Option<Self> {
+        let (_, segments) = self.segments.split_last()?;
+        Some(Self {
+            segments: segments.to_owned(),
+        })
+    }
+
+    /// Get the node at this path in `document`, or `None` if the path is
+    /// empty or there is no such node.
+    #[must_use]
+    pub fn resolve<'doc, D>(&self, document: &'doc D) -> Option<&'doc dyn Node>
+    where
+        D: Document + ?Sized,
+    {
+        let (first, rest) = self.segments.split_first()?;
+        let node = first.find(document.nodes())?;
+        rest.iter()
+            .try_fold(node, |node, segment| segment.find(node.children()))
+    }
+
+    /// Find `target` among `nodes` and their descendants, by its address,
+    /// appending its path to this one.
+    pub(crate) fn find(&mut self, nodes: &[&dyn Node], target: &dyn Node) -> bool {
+        for (node, (name, occurrence)) in nodes.iter().zip(occurrences(nodes)) {
+            self.push(name, occurrence);
+            if core::ptr::addr_eq(*node, target) || self.find(&node.children(), target) {
+                return true;
+            }
+            self.pop();
+        }
+        false
+    }
+}
+
+impl PathSegment {
+    fn find<'doc>(&self, nodes: Vec<&'doc dyn Node>) -> Option<&'doc dyn Node> {
+        nodes
+            .into_iter()
+            .filter(|node| node.name() == self.name)
+            .nth(self.occurrence)
+    }
+}
+
+impl fmt::Display for NodePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, segment) in self.segments.iter().enumerate() {
+            if index > 0 {
+                f.write_str(" > ")?;
+            }
+            segment.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for PathSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.occurrence {
+            0 => f.write_str(&self.name),
+            occurrence => write!(f, "{}[{occurrence}]", self.name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use alloc::vec::Vec;
+
+    use super::NodePath;
+    use crate::parser::parse;
+    use crate::Document;
+
+    #[test]
+    fn test_node_path() {
+        let document = parse("a { b; b { c } }\nb { c; c }").unwrap();
+        let paths: Vec<_> = document
+            .iter_dfs()
+            .map(|(node, _)| document.node_path(node).unwrap().to_string())
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "a",
+                "a > b",
+                "a > b[1]",
+                "a > b[1] > c",
+                "b",
+                "b > c",
+                "b > c[1]"
+            ]
+        );
+
+        let path = NodePath::new().child("b", 0).child("c", 1);
+        assert_eq!(path.len(), 2);
+        assert_eq!(path.resolve(&document).unwrap().name(), "c");
+        assert_eq!(path.parent().unwrap().to_string(), "b");
+        assert_eq!(NodePath::new().to_string(), "");
+        assert!(NodePath::new().resolve(&document).is_none());
+        assert!(NodePath::new().child("c", 0).resolve(&document).is_none());
+
+        let other = parse("a").unwrap();
+        assert!(document.node_path(other.get_node(0).unwrap()).is_none());
+    }
+}
@@ -0,0 +1,216 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use super::{DocumentBuf, NodeBuf};
+
+/// A cursor over the nodes of a [`DocumentBuf`], which can move between them
+/// and edit the document around the node it is focused on.
+///
+/// Movements return whether they succeeded, and leave the focus where it was
+/// if they did not. See [`DocumentBuf::cursor`].
+#[derive(Debug)]
+pub struct DocumentCursor<'doc> {
+    document: &'doc mut DocumentBuf,
+    /// The index of the focused node among its siblings, and of each of its
+    /// ancestors, from the top level down. Empty only if the document is.
+    path: Vec<usize>,
+}
+
+impl<'doc> DocumentCursor<'doc> {
+    fn new(document: &'doc mut DocumentBuf) -> Self {
+        let path = if document.nodes_ref().is_empty() {
+            Vec::new()
+        } else {
+            vec![0]
+        };
+        Self { document, path }
+    }
+
+    /// The indices of the focused node and its ancestors, from the top level
+    /// down.
+    #[must_use]
+    pub fn path(&self) -> &[usize] {
+        &self.path
+    }
+
+    /// The depth of the focused node, where top-level nodes have a depth of
+    /// zero.
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        self.path.len().saturating_sub(1)
+    }
+
+    fn siblings(&self) -> &[NodeBuf] {
+        self.path[..self.depth()]
+            .iter()
+            .fold(self.document.nodes_ref(), |nodes, index| {
+                nodes[*index].children_ref()
+            })
+    }
+
+    fn siblings_mut(&mut self) -> &mut Vec<NodeBuf> {
+        let depth = self.depth();
+        self.path[..depth]
+            .iter()
+            .fold(self.document.nodes_mut(), |nodes, index| {
+                nodes[*index].children_mut()
+            })
+    }
+
+    /// The focused node, unless the document is empty.
+    #[must_use]
+    pub fn node(&self) -> Option<&NodeBuf> {
+        let index = *self.path.last()?;
+        self.siblings().get(index)
+    }
+
+    pub fn node_mut(&mut self) -> Option<&mut NodeBuf> {
+        let index = *self.path.last()?;
+        self.siblings_mut().get_mut(index)
+    }
+
+    /// Move to the first child of the focused node.
+    pub fn down(&mut self) -> bool {
+        let has_children = self
+            .node()
+            .is_some_and(|node| !node.children_ref().is_empty());
+        if has_children {
+            self.path.push(0);
+        }
+        has_children
+    }
+
+    /// Move to the parent of the focused node.
+    pub fn up(&mut self) -> bool {
+        if self.path.len() > 1 {
+            self.path.pop();
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn next_sibling(&mut self) -> bool {
+        let len = self.siblings().len();
+        match self.path.last_mut() {
+            Some(index) if *index + 1 < len => {
+                *index += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn prev_sibling(&mut self) -> bool {
+        match self.path.last_mut() {
+            Some(index) if *index > 0 => {
+                *index -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Insert `node` before the focused node, keeping the focus on the same
+    /// node. In an empty document, the node is inserted and focused.
+    pub fn insert_before(&mut self, node: NodeBuf) {
+        let Some(index) = self.path.last().copied() else {
+            self.document.nodes_mut().push(node);
+            self.path.push(0);
+            return;
+        };
+        self.siblings_mut().insert(index, node);
+        self.next_sibling();
+    }
+
+    /// Insert `node` after the focused node, keeping the focus on the same
+    /// node. In an empty document, the node is inserted and focused.
+    pub fn insert_after(&mut self, node: NodeBuf) {
+        let Some(index) = self.path.last().copied() else {
+            return self.insert_before(node);
+        };
+        self.siblings_mut().insert(index + 1, node);
+    }
+
+    /// Replace the focused node with `node`, returning the old one.
+    pub fn replace(&mut self, node: NodeBuf) -> Option<NodeBuf> {
+        self.node_mut().map(|focus| core::mem::replace(focus, node))
+    }
+
+    /// Remove the focused node, moving the focus to its next sibling, or if
+    /// there is none, its previous sibling or its parent.
+    pub fn remove(&mut self) -> Option<NodeBuf> {
+        let index = *self.path.last()?;
+        let node = self.siblings_mut().remove(index);
+        // Otherwise the next sibling has moved into the focused position.
+        if index == self.siblings().len() && !self.prev_sibling() {
+            self.path.pop();
+        }
+        Some(node)
+    }
+}
+
+impl DocumentBuf {
+    /// Get a cursor focused on the first top-level node.
+    pub fn cursor(&mut self) -> DocumentCursor<'_> {
+        DocumentCursor::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use crate::{node, DocumentBuf, Node, NodeBuf};
+
+    fn names(document: &DocumentBuf) -> Vec<&str> {
+        document.nodes_ref().iter().map(Node::name).collect()
+    }
+
+    #[test]
+    fn test_cursor_navigation() {
+        let mut document = DocumentBuf::from(vec![
+            node!("server" { node!("tls"), node!("log") }),
+            node!("client"),
+        ]);
+        let mut cursor = document.cursor();
+        assert_eq!(cursor.node().map(Node::name), Some("server"));
+        assert!(!cursor.prev_sibling());
+        assert!(cursor.down());
+        assert!(cursor.next_sibling());
+        assert_eq!(cursor.node().map(Node::name), Some("log"));
+        assert_eq!(cursor.path(), [0, 1]);
+        assert!(!cursor.next_sibling());
+        assert!(!cursor.down());
+        assert!(cursor.up());
+        assert!(!cursor.up());
+        assert!(cursor.next_sibling());
+        assert_eq!(cursor.node().map(Node::name), Some("client"));
+    }
+
+    #[test]
+    fn test_cursor_editing() {
+        let mut document = DocumentBuf::from(vec![node!("server" { node!("tls") })]);
+        let mut cursor = document.cursor();
+        cursor.insert_before(node!("first"));
+        cursor.insert_after(node!("last"));
+        assert_eq!(cursor.node().map(Node::name), Some("server"));
+
+        assert!(cursor.down());
+        cursor.node_mut().unwrap().set_name("ssl");
+        assert_eq!(cursor.remove(), Some(NodeBuf::new("ssl")));
+        assert_eq!(cursor.depth(), 0);
+        assert_eq!(
+            cursor.replace(NodeBuf::new("proxy")),
+            Some(NodeBuf::new("server"))
+        );
+        assert_eq!(names(&document), ["first", "proxy", "last"]);
+
+        let mut document = DocumentBuf::new();
+        let mut cursor = document.cursor();
+        assert!(cursor.remove().is_none());
+        cursor.insert_after(node!("only"));
+        assert_eq!(cursor.node().map(Node::name), Some("only"));
+    }
+}
@@ -0,0 +1,275 @@
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::ops::{Add, AddAssign};
+
+use super::NodeBuf;
+use crate::{Document, DocumentMut, Node, NodeMut};
+
+/// An owned document, which is a list of top-level nodes.
+///
+/// A document can also carry a version, from a leading `@version` directive,
+/// and the name of the source it was parsed from. The source does not affect
+/// equality.
+#[derive(Clone, Debug, Default)]
+pub struct DocumentBuf {
+    version: Option<String>,
+    nodes: Vec<NodeBuf>,
+    source: Option<Arc<str>>,
+}
+
+impl DocumentBuf {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn nodes_ref(&self) -> &[NodeBuf] {
+        &self.nodes
+    }
+
+    pub fn nodes_mut(&mut self) -> &mut Vec<NodeBuf> {
+        &mut self.nodes
+    }
+
+    pub fn iter(&self) -> core::slice::Iter<'_, NodeBuf> {
+        self.nodes.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> core::slice::IterMut<'_, NodeBuf> {
+        self.nodes.iter_mut()
+    }
+
+    pub fn set_version<S>(&mut self, version: Option<S>)
+    where
+        S: Into<String>,
+    {
+        self.version = version.map(Into::into);
+    }
+
+    /// Append the top-level nodes of `other`, in order.
+    ///
+    /// The version and source of this document are kept, those of `other` are
+    /// dropped.
+    pub fn extend_from(&mut self, other: DocumentBuf) {
+        self.nodes.extend(other.nodes);
+    }
+
+    /// Set the name of the source this document was parsed from, such as a
+    /// file path.
+    pub fn set_source(&mut self, source: Option<Arc<str>>) {
+        self.source = source;
+    }
+}
+
+impl PartialEq for DocumentBuf {
+    fn eq(&self, other: &Self) -> bool {
+        self.version == other.version && self.nodes == other.nodes
+    }
+}
+
+impl Document for DocumentBuf {
+    fn nodes(&self) -> Vec<&dyn Node> {
+        self.nodes_iter().collect()
+    }
+
+    fn nodes_iter(&self) -> Box<dyn Iterator<Item = &dyn Node> + '_> {
+        Box::new(self.nodes.iter().map(|node| -> &dyn Node { node }))
+    }
+
+    fn get_node(&self, index: usize) -> Option<&dyn Node> {
+        self.nodes.get(index).map(|node| -> &dyn Node { node })
+    }
+
+    fn has_nodes(&self) -> bool {
+        !self.nodes.is_empty()
+    }
+
+    fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    fn source(&self) -> Option<&str> {
+        self.source.as_deref()
+    }
+}
+
+impl DocumentMut for DocumentBuf {
+    fn push_node(&mut self, node: NodeBuf) {
+        self.nodes.push(node);
+    }
+
+    fn insert_node(&mut self, index: usize, node: NodeBuf) {
+        self.nodes.insert(index, node);
+    }
+
+    fn remove_node(&mut self, index: usize) -> Option<NodeBuf> {
+        (index < self.nodes.len()).then(|| self.nodes.remove(index))
+    }
+
+    fn get_node_mut(&mut self, index: usize) -> Option<&mut dyn NodeMut> {
+        self.nodes
+            .get_mut(index)
+            .map(|node| -> &mut dyn NodeMut { node })
+    }
+}
+
+impl From<Vec<NodeBuf>> for DocumentBuf {
+    fn from(nodes: Vec<NodeBuf>) -> Self {
+        Self {
+            nodes,
+            ..Self::default()
+        }
+    }
+}
+
+impl FromIterator<NodeBuf> for DocumentBuf {
+    fn from_iter<I: IntoIterator<Item = NodeBuf>>(iter: I) -> Self {
+        Self {
+            nodes: iter.into_iter().collect(),
+            ..Self::default()
+        }
+    }
+}
+
+impl Extend<NodeBuf> for DocumentBuf {
+    fn extend<I: IntoIterator<Item = NodeBuf>>(&mut self, iter: I) {
+        self.nodes.extend(iter);
+    }
+}
+
+/// Appends the nodes of the right document, see [`DocumentBuf::extend_from`].
+impl Add for DocumentBuf {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        self.extend_from(other);
+        self
+    }
+}
+
+impl AddAssign for DocumentBuf {
+    fn add_assign(&mut self, other: Self) {
+        self.extend_from(other);
+    }
+}
+
+impl IntoIterator for DocumentBuf {
+    type Item = NodeBuf;
+    type IntoIter = alloc::vec::IntoIter<NodeBuf>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.nodes.into_iter()
+    }
+}
+
+impl<'document> IntoIterator for &'document DocumentBuf {
+    type Item = &'document NodeBuf;
+    type IntoIter = core::slice::Iter<'document, NodeBuf>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'document> IntoIterator for &'document mut DocumentBuf {
+    type Item = &'document mut NodeBuf;
+    type IntoIter = core::slice::IterMut<'document, NodeBuf>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use alloc::vec::Vec;
+
+    use super::DocumentBuf;
+    use crate::emit::to_string;
+    use crate::parser::{parse, Parser};
+    use crate::{Document, Node, NodeBuf};
+
+    #[test]
+    fn test_document_buf() {
+        let document = DocumentBuf::from_iter([NodeBuf::new("one"), NodeBuf::new("two")]);
+        assert!(document.has_nodes());
+        assert_eq!(
+            document
+                .nodes()
+                .into_iter()
+                .map(Node::name)
+                .collect::<Vec<_>>(),
+            ["one", "two"]
+        );
+        assert_eq!(document.get_node(1).map(Node::name), Some("two"));
+        assert!(DocumentBuf::new().get_node(0).is_none());
+        assert_eq!(document.len(), 2);
+        assert!(DocumentBuf::new().is_empty());
+        assert_eq!(document.iter().len(), 2);
+        assert_eq!(document.iter().next_back().map(Node::name), Some("two"));
+        assert_eq!(
+            document.nodes_named("one").next_back().map(Node::name),
+            Some("one")
+        );
+    }
+
+    #[test]
+    fn test_document_buf_metadata() {
+        let mut document = DocumentBuf::from_iter([NodeBuf::new("one")]);
+        assert_eq!(document.version(), None);
+        document.set_version(Some("1.0"));
+        document.set_source(Some("config.gpnd".into()));
+        assert_eq!(document.version(), Some("1.0"));
+        assert_eq!(document.source(), Some("config.gpnd"));
+
+        let mut other = DocumentBuf::from_iter([NodeBuf::new("one")]);
+        assert_ne!(document, other);
+        other.set_version(Some("1.0"));
+        assert_eq!(document, other);
+
+        let parsed = Parser::new("// Header.\n@version \"1.0\"\n\none")
+            .with_source_name("config.gpnd")
+            .parse()
+            .unwrap();
+        assert_eq!(parsed, document);
+        assert_eq!(parsed.source(), Some("config.gpnd"));
+        assert_eq!(parse(&to_string(&parsed)).unwrap(), document);
+        assert_eq!(parse("one").unwrap().version(), None);
+
+        let error = parse("@version \"1\"\n@version \"2\"").unwrap_err();
+        assert_eq!(error.to_string(), "2:1: duplicate directive `@version`");
+        let error = parse("@version 1").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "1:10: expected a version string, found `1`"
+        );
+    }
+
+    #[test]
+    fn test_document_buf_concat() {
+        let mut document = DocumentBuf::from_iter([NodeBuf::new("one")]);
+        document.set_version(Some("1.0"));
+        let mut fragment = DocumentBuf::from_iter([NodeBuf::new("three")]);
+        fragment.set_version(Some("2.0"));
+
+        document += DocumentBuf::from_iter([NodeBuf::new("two")]);
+        let document = document + fragment;
+        assert_eq!(document.version(), Some("1.0"));
+        assert_eq!(
+            document
+                .nodes()
+                .into_iter()
+                .map(Node::name)
+                .collect::<Vec<_>>(),
+            ["one", "two", "three"]
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use core::ops::{Index, IndexMut};
+
+use super::{DocumentBuf, NodeBuf};
+use crate::{Node, Value};
+
+/// Get the top-level node at `index`.
+impl Index<usize> for DocumentBuf {
+    type Output = NodeBuf;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        self.nodes_ref()
+            .get(index)
+            .unwrap_or_else(|| panic!("document has no node at index {index}"))
+    }
+}
+
+impl IndexMut<usize> for DocumentBuf {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        self.nodes_mut()
+            .get_mut(index)
+            .unwrap_or_else(|| panic!("document has no node at index {index}"))
+    }
+}
+
+/// Get the first top-level node called `name`.
+impl Index<&str> for DocumentBuf {
+    type Output = NodeBuf;
+
+    fn index(&self, name: &str) -> &Self::Output {
+        self.nodes_ref()
+            .iter()
+            .find(|node| node.name() == name)
+            .unwrap_or_else(|| panic!("document has no node named `{name}`"))
+    }
+}
+
+impl IndexMut<&str> for DocumentBuf {
+    fn index_mut(&mut self, name: &str) -> &mut Self::Output {
+        self.nodes_mut()
+            .iter_mut()
+            .find(|node| node.name() == name)
+            .unwrap_or_else(|| panic!("document has no node named `{name}`"))
+    }
+}
+
+/// Get the argument at `index`.
+impl Index<usize> for NodeBuf {
+    type Output = Value<'static>;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        self.args_ref()
+            .get(index)
+            .unwrap_or_else(|| panic!("node `{}` has no argument {index}", self.name()))
+    }
+}
+
+impl IndexMut<usize> for NodeBuf {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        assert!(
+            index < self.args_ref().len(),
+            "node `{}` has no argument {index}",
+            self.name()
+        );
+        &mut self.args_mut()[index]
+    }
+}
+
+/// Get the parameter `key`.
+impl Index<&str> for NodeBuf {
+    type Output = Value<'static>;
+
+    fn index(&self, key: &str) -> &Self::Output {
+        self.params_ref()
+            .get(key)
+            .unwrap_or_else(|| panic!("node `{}` has no parameter `{key}`", self.name()))
+    }
+}
+
+impl IndexMut<&str> for NodeBuf {
+    fn index_mut(&mut self, key: &str) -> &mut Self::Output {
+        assert!(
+            self.params_ref().contains_key(key),
+            "node `{}` has no parameter `{key}`",
+            self.name()
+        );
+        self.params_mut()
+            .get_mut(key)
+            .expect("the parameter is present")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use crate::{DocumentBuf, NodeBuf, Value};
+
+    fn document() -> DocumentBuf {
+        let mut server = NodeBuf::new("server");
+        server.args_mut().push(Value::from("main"));
+        server.params_mut().insert("port", 80_u16);
+        DocumentBuf::from(vec![NodeBuf::new("client"), server])
+    }
+
+    #[test]
+    fn test_index() {
+        let mut document = document();
+        assert_eq!(document["server"]["port"], Value::U16(80));
+        assert_eq!(document[1][0], Value::from("main"));
+        document["server"]["port"] = Value::U16(8080);
+        document[1][0] = Value::from("backup");
+        assert_eq!(document["server"]["port"], 8080_u16);
+        assert_eq!(document["server"][0], "backup");
+    }
+
+    #[test]
+    #[should_panic = "node `server` has no parameter `host`"]
+    fn test_index_missing() {
+        let _ = &document()["server"]["host"];
+    }
+
+    #[test]
+    #[should_panic = "node `server` has no argument 1"]
+    fn test_index_mut_missing() {
+        document()["server"][1] = Value::Null;
+    }
+}
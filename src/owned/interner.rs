@@ -0,0 +1,66 @@
+use alloc::sync::Arc;
+
+/// The strings of an [`Interner`], hashed when `std` is available.
+#[cfg(feature = "std")]
+type Strings = std::collections::HashSet<Arc<str>>;
+#[cfg(not(feature = "std"))]
+type Strings = alloc::collections::BTreeSet<Arc<str>>;
+
+/// A set of shared strings, so that the names and parameter keys that a
+/// document repeats are stored once.
+///
+/// A [`Parser`](crate::parser::Parser) interns into its own interner by
+/// default. Give it one with
+/// [`Parser::parse_interned`](crate::parser::Parser::parse_interned) to share
+/// strings across documents.
+///
+/// ```
+/// # use std::sync::Arc;
+/// # use gpnd::Interner;
+/// let mut interner = Interner::new();
+/// let first = interner.intern("server");
+/// assert!(Arc::ptr_eq(&first, &interner.intern("server")));
+/// assert_eq!(interner.len(), 1);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Interner {
+    strings: Strings,
+}
+
+impl Interner {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the shared copy of `text`, adding it if it is new.
+    pub fn intern(&mut self, text: &str) -> Arc<str> {
+        if let Some(shared) = self.strings.get(text) {
+            return Arc::clone(shared);
+        }
+        let shared: Arc<str> = Arc::from(text);
+        self.strings.insert(Arc::clone(&shared));
+        shared
+    }
+
+    /// Whether `text` has been interned.
+    #[must_use]
+    pub fn contains(&self, text: &str) -> bool {
+        self.strings.contains(text)
+    }
+
+    /// The number of distinct strings.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.strings.clear();
+    }
+}
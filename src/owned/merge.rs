@@ -0,0 +1,103 @@
+use alloc::borrow::ToOwned;
+
+use super::{DocumentBuf, NodeBuf};
+use crate::Node;
+
+/// How [`DocumentBuf::merge`] combines a top-level node with one of the same
+/// name that is already in the document.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// The existing node is replaced by the new one.
+    Replace,
+    /// Both nodes are kept, the new one is appended.
+    Append,
+    /// The new node is merged into the existing one, see [`NodeBuf::merge`].
+    #[default]
+    Deep,
+}
+
+impl DocumentBuf {
+    /// Merge the nodes of `other` into this document, such as when layering
+    /// overrides on top of defaults.
+    ///
+    /// Every node of `other` is matched with the first node of the same name
+    /// in this document, and combined with it according to `strategy`. Nodes
+    /// that don't match any are appended.
+    pub fn merge(&mut self, other: &DocumentBuf, strategy: MergeStrategy) {
+        for node in other.nodes_ref() {
+            let existing = match strategy {
+                MergeStrategy::Append => None,
+                MergeStrategy::Replace | MergeStrategy::Deep => self
+                    .nodes_mut()
+                    .iter_mut()
+                    .find(|existing| existing.name() == node.name()),
+            };
+            match (existing, strategy) {
+                (Some(existing), MergeStrategy::Deep) => existing.merge(node),
+                (Some(existing), _) => node.clone_into(existing),
+                (None, _) => self.nodes_mut().push(node.clone()),
+            }
+        }
+    }
+}
+
+impl NodeBuf {
+    /// Merge `other` into this node.
+    ///
+    /// The arguments of `other` replace these if it has any, its parameters
+    /// override these, and its children are appended.
+    pub fn merge(&mut self, other: &NodeBuf) {
+        if !other.args_ref().is_empty() {
+            let args = self.args_mut();
+            args.clear();
+            args.extend(other.args_ref().iter().cloned());
+        }
+        for (key, value) in other.params_ref().iter_unique() {
+            self.params_mut().insert(key, value.clone());
+        }
+        self.children_mut()
+            .extend(other.children_ref().iter().cloned());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::MergeStrategy;
+    use crate::{DocumentBuf, Node, NodeBuf, Value};
+
+    fn server(port: u16, child: &str) -> NodeBuf {
+        let mut node = NodeBuf::new("server");
+        node.params_mut().insert("port", port);
+        node.children_mut().push(NodeBuf::new(child));
+        node
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut defaults = server(80, "log");
+        defaults.params_mut().insert("host", "localhost");
+        let defaults = DocumentBuf::from(vec![defaults]);
+        let overrides = DocumentBuf::from(vec![server(8080, "tls"), NodeBuf::new("client")]);
+
+        let mut document = defaults.clone();
+        document.merge(&overrides, MergeStrategy::Append);
+        assert_eq!(document.nodes_ref().len(), 3);
+
+        let mut document = defaults.clone();
+        document.merge(&overrides, MergeStrategy::Replace);
+        assert_eq!(document, overrides);
+
+        let mut document = defaults;
+        document.merge(&overrides, MergeStrategy::Deep);
+        let merged = &document.nodes_ref()[0];
+        assert_eq!(merged.params_ref().get("port"), Some(&Value::U16(8080)));
+        assert_eq!(
+            merged.params_ref().get("host"),
+            Some(&Value::from("localhost"))
+        );
+        assert_eq!(merged.children_ref().len(), 2);
+        assert_eq!(document.nodes_ref()[1].name(), "client");
+    }
+}
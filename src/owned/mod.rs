@@ -0,0 +1,20 @@
+mod cursor;
+mod document;
+mod index;
+mod interner;
+mod merge;
+mod name;
+mod node;
+mod params;
+mod path;
+mod store;
+
+pub use cursor::*;
+pub use document::*;
+pub use interner::*;
+pub use merge::*;
+pub use name::*;
+pub use node::*;
+pub use params::*;
+pub use path::*;
+pub use store::*;
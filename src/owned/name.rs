@@ -0,0 +1,87 @@
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use core::error::Error;
+use core::fmt;
+
+use crate::emit::is_bare_ident;
+
+/// The error returned by [`validate_node_name`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InvalidNameError {
+    Empty,
+    /// The name has a colon, which would separate a namespace from it.
+    Colon(String),
+    /// The name is not an identifier, such as one with whitespace or brackets,
+    /// or that would be read as a number or a comment.
+    NotIdentifier(String),
+}
+
+impl fmt::Display for InvalidNameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => f.write_str("node name is empty"),
+            Self::Colon(name) => write!(f, "node name `{name}` has a colon"),
+            Self::NotIdentifier(name) => write!(f, "node name `{name}` is not an identifier"),
+        }
+    }
+}
+
+impl Error for InvalidNameError {}
+
+/// Check that `name` is an identifier, which can be written without quotes as
+/// the name or the namespace of a node.
+///
+/// Any name can be written on its own, because the writer quotes it if it is
+/// not an identifier, but the namespace and the name of a node with a
+/// namespace must both be identifiers.
+///
+/// ```
+/// # use gpnd::{validate_node_name, InvalidNameError};
+/// assert_eq!(validate_node_name("route"), Ok(()));
+/// assert!(matches!(
+///     validate_node_name("my route"),
+///     Err(InvalidNameError::NotIdentifier(_))
+/// ));
+/// ```
+pub fn validate_node_name(name: &str) -> Result<(), InvalidNameError> {
+    if name.is_empty() {
+        Err(InvalidNameError::Empty)
+    } else if name.contains(':') {
+        Err(InvalidNameError::Colon(name.to_owned()))
+    } else if !is_bare_ident(name) {
+        Err(InvalidNameError::NotIdentifier(name.to_owned()))
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{validate_node_name, InvalidNameError};
+    use crate::{Node, NodeBuf};
+
+    #[test]
+    fn test_validate_node_name() {
+        assert_eq!(validate_node_name("http-route_2"), Ok(()));
+        assert_eq!(validate_node_name(""), Err(InvalidNameError::Empty));
+        assert_eq!(
+            validate_node_name("a:b"),
+            Err(InvalidNameError::Colon("a:b".into()))
+        );
+        for name in ["1st", "-1", "a b", "a{", "//", "#tag", "@version"] {
+            assert_eq!(
+                validate_node_name(name),
+                Err(InvalidNameError::NotIdentifier(name.into())),
+            );
+        }
+
+        let mut node = NodeBuf::new("my route");
+        node.try_set_name("route").unwrap();
+        node.try_set_namespace(Some("http")).unwrap();
+        assert!(node.try_set_name("my route").is_err());
+        assert!(node.try_set_namespace(Some("a b")).is_err());
+        assert!(node.is_named(Some("http"), "route"));
+        node.try_set_namespace(None::<&str>).unwrap();
+        node.try_set_name("my route").unwrap();
+    }
+}
@@ -0,0 +1,572 @@
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::ops::{Deref, DerefMut};
+use core::slice;
+
+use super::{validate_node_name, InvalidNameError, ParamEntry, Params};
+use crate::{Node, NodeMut, Span, Value};
+
+/// The arguments of a [`NodeBuf`], which derefs to a slice of them.
+///
+/// With the `smallvec` feature, up to three arguments are kept inline in the
+/// node, rather than in an allocation of their own. The methods are the same
+/// either way.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ArgVec(Args);
+
+#[cfg(not(feature = "smallvec"))]
+type Args = Vec<Value<'static>>;
+#[cfg(feature = "smallvec")]
+type Args = smallvec::SmallVec<[Value<'static>; 3]>;
+
+impl ArgVec {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, value: Value<'static>) {
+        self.0.push(value);
+    }
+
+    /// Insert `value` at `index`, shifting the arguments after it.
+    ///
+    /// # Panics
+    /// Panics if `index` is greater than the number of arguments.
+    pub fn insert(&mut self, index: usize, value: Value<'static>) {
+        self.0.insert(index, value);
+    }
+
+    /// Remove the argument at `index`, shifting the arguments after it.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> Value<'static> {
+        self.0.remove(index)
+    }
+
+    pub fn pop(&mut self) -> Option<Value<'static>> {
+        self.0.pop()
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        self.0.truncate(len);
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    #[must_use]
+    pub fn into_vec(self) -> Vec<Value<'static>> {
+        #[cfg(feature = "smallvec")]
+        return self.0.into_vec();
+        #[cfg(not(feature = "smallvec"))]
+        self.0
+    }
+}
+
+impl Deref for ArgVec {
+    type Target = [Value<'static>];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for ArgVec {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl From<Vec<Value<'static>>> for ArgVec {
+    fn from(values: Vec<Value<'static>>) -> Self {
+        #[cfg(feature = "smallvec")]
+        return Self(Args::from_vec(values));
+        #[cfg(not(feature = "smallvec"))]
+        Self(values)
+    }
+}
+
+impl From<ArgVec> for Vec<Value<'static>> {
+    fn from(args: ArgVec) -> Self {
+        args.into_vec()
+    }
+}
+
+impl FromIterator<Value<'static>> for ArgVec {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = Value<'static>>,
+    {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl Extend<Value<'static>> for ArgVec {
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = Value<'static>>,
+    {
+        self.0.extend(iter);
+    }
+}
+
+impl<'a> IntoIterator for &'a ArgVec {
+    type Item = &'a Value<'static>;
+    type IntoIter = slice::Iter<'a, Value<'static>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut ArgVec {
+    type Item = &'a mut Value<'static>;
+    type IntoIter = slice::IterMut<'a, Value<'static>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// An owned node, with a name, arguments, parameters, and child nodes.
+///
+/// A node can also remember where it was parsed from, and have comments, but
+/// neither affects equality.
+#[derive(Clone, Debug, Default)]
+pub struct NodeBuf {
+    namespace: Option<Arc<str>>,
+    name: Arc<str>,
+    type_annotation: Option<Arc<str>>,
+    args: ArgVec,
+    params: Params,
+    children: Vec<NodeBuf>,
+    span: Option<Span>,
+    arg_spans: Vec<Span>,
+    param_spans: Vec<(Arc<str>, Span)>,
+    source: Option<Arc<str>>,
+    leading_comments: Vec<String>,
+    trailing_comment: Option<String>,
+    anchor: Option<String>,
+    reference: Option<String>,
+}
+
+impl NodeBuf {
+    #[must_use]
+    pub fn new<S>(name: S) -> Self
+    where
+        S: Into<Arc<str>>,
+    {
+        Self {
+            name: name.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn set_name<S>(&mut self, name: S)
+    where
+        S: Into<Arc<str>>,
+    {
+        self.name = name.into();
+    }
+
+    pub fn set_namespace<S>(&mut self, namespace: Option<S>)
+    where
+        S: Into<Arc<str>>,
+    {
+        self.namespace = namespace.map(Into::into);
+    }
+
+    pub fn set_type_annotation<S>(&mut self, type_annotation: Option<S>)
+    where
+        S: Into<Arc<str>>,
+    {
+        self.type_annotation = type_annotation.map(Into::into);
+    }
+
+    /// Set the name, unless the node has a namespace and the name is not an
+    /// identifier, which could not be written, see [`validate_node_name`].
+    pub fn try_set_name<S>(&mut self, name: S) -> Result<(), InvalidNameError>
+    where
+        S: Into<Arc<str>>,
+    {
+        let name = name.into();
+        if self.namespace.is_some() {
+            validate_node_name(&name)?;
+        }
+        self.name = name;
+        Ok(())
+    }
+
+    /// Set the namespace, unless it or the name is not an identifier, which
+    /// could not be written, see [`validate_node_name`].
+    pub fn try_set_namespace<S>(&mut self, namespace: Option<S>) -> Result<(), InvalidNameError>
+    where
+        S: Into<Arc<str>>,
+    {
+        let namespace = namespace.map(Into::into);
+        if let Some(namespace) = &namespace {
+            validate_node_name(namespace)?;
+            validate_node_name(&self.name)?;
+        }
+        self.namespace = namespace;
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn args_ref(&self) -> &[Value<'static>] {
+        &self.args
+    }
+
+    pub fn args_mut(&mut self) -> &mut ArgVec {
+        &mut self.args
+    }
+
+    #[must_use]
+    pub fn params_ref(&self) -> &Params {
+        &self.params
+    }
+
+    pub fn params_mut(&mut self) -> &mut Params {
+        &mut self.params
+    }
+
+    /// Get the entry for the parameter `key`, see [`Params::entry`].
+    pub fn param_entry<K>(&mut self, key: K) -> ParamEntry<'_>
+    where
+        K: Into<Arc<str>>,
+    {
+        self.params.entry(key)
+    }
+
+    #[must_use]
+    pub fn children_ref(&self) -> &[NodeBuf] {
+        &self.children
+    }
+
+    pub fn children_mut(&mut self) -> &mut Vec<NodeBuf> {
+        &mut self.children
+    }
+
+    pub fn set_span(&mut self, span: Option<Span>) {
+        self.span = span;
+    }
+
+    /// The spans of the arguments, by index, see [`Node::arg_span`].
+    pub fn arg_spans_mut(&mut self) -> &mut Vec<Span> {
+        &mut self.arg_spans
+    }
+
+    /// The spans of the parameter values, by key, see [`Node::param_span`].
+    /// Like parameters, later spans for a key override earlier ones.
+    pub fn param_spans_mut(&mut self) -> &mut Vec<(Arc<str>, Span)> {
+        &mut self.param_spans
+    }
+
+    /// Set the name of the source this node was parsed from. The name is
+    /// reference counted, so that every node from a source can share it.
+    pub fn set_source(&mut self, source: Option<Arc<str>>) {
+        self.source = source;
+    }
+
+    /// The lines of the comments before this node, see
+    /// [`Node::leading_comments`].
+    pub fn leading_comments_mut(&mut self) -> &mut Vec<String> {
+        &mut self.leading_comments
+    }
+
+    pub fn set_trailing_comment<S>(&mut self, comment: Option<S>)
+    where
+        S: Into<String>,
+    {
+        self.trailing_comment = comment.map(Into::into);
+    }
+
+    /// The name of the `&name` anchor on this node, if it was parsed with
+    /// one.
+    #[must_use]
+    pub fn anchor(&self) -> Option<&str> {
+        self.anchor.as_deref()
+    }
+
+    pub fn set_anchor<S>(&mut self, anchor: Option<S>)
+    where
+        S: Into<String>,
+    {
+        self.anchor = anchor.map(Into::into);
+    }
+
+    /// The name of the anchor that this node is a copy of, if it was parsed
+    /// from a `*name` reference.
+    #[must_use]
+    pub fn reference(&self) -> Option<&str> {
+        self.reference.as_deref()
+    }
+
+    pub fn set_reference<S>(&mut self, reference: Option<S>)
+    where
+        S: Into<String>,
+    {
+        self.reference = reference.map(Into::into);
+    }
+}
+
+impl PartialEq for NodeBuf {
+    fn eq(&self, other: &Self) -> bool {
+        self.namespace == other.namespace
+            && self.name == other.name
+            && self.type_annotation == other.type_annotation
+            && self.args == other.args
+            && self.params == other.params
+            && self.children == other.children
+    }
+}
+
+impl Node for NodeBuf {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn namespace(&self) -> Option<&str> {
+        self.namespace.as_deref()
+    }
+
+    fn type_annotation(&self) -> Option<&str> {
+        self.type_annotation.as_deref()
+    }
+
+    fn args(&self) -> Vec<Value<'_>> {
+        self.args.iter().map(Value::borrowed).collect()
+    }
+
+    fn params(&self) -> Vec<(&str, Value<'_>)> {
+        self.params_iter().collect()
+    }
+
+    fn params_all(&self) -> Vec<(&str, Value<'_>)> {
+        self.params
+            .iter()
+            .map(|(key, value)| (key, value.borrowed()))
+            .collect()
+    }
+
+    fn args_iter(&self) -> Box<dyn Iterator<Item = Value<'_>> + '_> {
+        Box::new(self.args.iter().map(Value::borrowed))
+    }
+
+    fn params_all_iter(&self) -> Box<dyn Iterator<Item = (&str, Value<'_>)> + '_> {
+        Box::new(
+            self.params
+                .iter()
+                .map(|(key, value)| (key, value.borrowed())),
+        )
+    }
+
+    fn params_iter(&self) -> Box<dyn Iterator<Item = (&str, Value<'_>)> + '_> {
+        Box::new(
+            self.params
+                .iter_unique()
+                .map(|(key, value)| (key, value.borrowed())),
+        )
+    }
+
+    fn span(&self) -> Option<Span> {
+        self.span
+    }
+
+    fn arg_span(&self, index: usize) -> Option<Span> {
+        self.arg_spans.get(index).copied()
+    }
+
+    fn param_span(&self, key: &str) -> Option<Span> {
+        self.param_spans
+            .iter()
+            .rfind(|(other, _)| **other == *key)
+            .map(|(_, span)| *span)
+    }
+
+    fn source(&self) -> Option<&str> {
+        self.source.as_deref()
+    }
+
+    fn leading_comments(&self) -> Vec<&str> {
+        self.leading_comments.iter().map(String::as_str).collect()
+    }
+
+    fn trailing_comment(&self) -> Option<&str> {
+        self.trailing_comment.as_deref()
+    }
+
+    fn get_param_all(&self, key: &str) -> Vec<Value<'_>> {
+        self.params.get_all(key).map(Value::borrowed).collect()
+    }
+
+    fn get_arg(&self, index: usize) -> Option<Value<'_>> {
+        self.args.get(index).map(Value::borrowed)
+    }
+
+    fn arg_count(&self) -> usize {
+        self.args.len()
+    }
+
+    fn get_param(&self, key: &str) -> Option<Value<'_>> {
+        self.params.get(key).map(Value::borrowed)
+    }
+
+    fn has_args(&self) -> bool {
+        !self.args.is_empty()
+    }
+
+    fn has_params(&self) -> bool {
+        !self.params.is_empty()
+    }
+
+    fn children(&self) -> Vec<&dyn Node> {
+        self.children_iter().collect()
+    }
+
+    fn children_iter(&self) -> Box<dyn Iterator<Item = &dyn Node> + '_> {
+        Box::new(self.children.iter().map(|child| -> &dyn Node { child }))
+    }
+
+    fn get_child(&self, index: usize) -> Option<&dyn Node> {
+        self.children.get(index).map(|child| -> &dyn Node { child })
+    }
+
+    fn has_children(&self) -> bool {
+        !self.children.is_empty()
+    }
+}
+
+impl NodeMut for NodeBuf {
+    fn set_name(&mut self, name: &str) {
+        self.name = name.into();
+    }
+
+    fn push_arg(&mut self, value: Value<'static>) {
+        self.args.push(value);
+    }
+
+    fn insert_arg(&mut self, index: usize, value: Value<'static>) {
+        self.args.insert(index, value);
+    }
+
+    fn set_arg(&mut self, index: usize, value: Value<'static>) -> Value<'static> {
+        core::mem::replace(&mut self.args[index], value)
+    }
+
+    fn remove_arg(&mut self, index: usize) -> Option<Value<'static>> {
+        (index < self.args.len()).then(|| self.args.remove(index))
+    }
+
+    fn insert_param(&mut self, key: &str, value: Value<'static>) -> Option<Value<'static>> {
+        self.params.insert(key, value)
+    }
+
+    fn remove_param(&mut self, key: &str) -> Option<Value<'static>> {
+        self.params.remove(key)
+    }
+
+    fn push_child(&mut self, child: NodeBuf) {
+        self.children.push(child);
+    }
+
+    fn insert_child(&mut self, index: usize, child: NodeBuf) {
+        self.children.insert(index, child);
+    }
+
+    fn remove_child(&mut self, index: usize) -> Option<NodeBuf> {
+        (index < self.children.len()).then(|| self.children.remove(index))
+    }
+
+    fn get_child_mut(&mut self, index: usize) -> Option<&mut dyn NodeMut> {
+        self.children
+            .get_mut(index)
+            .map(|child| -> &mut dyn NodeMut { child })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::borrow::ToOwned;
+    use alloc::string::ToString;
+
+    use super::NodeBuf;
+    use crate::{Node, Position, Span, Value};
+
+    #[test]
+    fn test_node_buf() {
+        let mut node = NodeBuf::new("server");
+        node.args_mut().push(Value::from("localhost".to_owned()));
+        node.params_mut().insert("port", 8080_u16);
+        node.params_mut().insert("tls", true);
+        node.children_mut().push(NodeBuf::new("cert"));
+
+        assert_eq!(node.name(), "server");
+        assert_eq!(node.get_arg(0), Some(Value::from("localhost")));
+        assert_eq!(node.get_param("port"), Some(Value::U16(8080)));
+        assert_eq!(node.params().len(), 2);
+        assert_eq!(node.get_child(0).map(Node::name), Some("cert"));
+        assert!(!node.get_child(0).unwrap().has_args());
+
+        node.params_mut().push("port", 8443_u16);
+        assert_eq!(node.params().len(), 2);
+        assert_eq!(node.params_all().len(), 3);
+        assert_eq!(node.get_param("port"), Some(Value::U16(8443)));
+        assert_eq!(
+            node.get_param_all("port"),
+            [Value::U16(8080), Value::U16(8443)]
+        );
+    }
+
+    #[test]
+    fn test_node_buf_namespace() {
+        let mut node = NodeBuf::new("route");
+        assert!(node.is_named(None, "route"));
+        node.set_namespace(Some("http"));
+        assert_eq!(node.namespace(), Some("http"));
+        assert!(node.is_named(Some("http"), "route"));
+        assert!(!node.is_named(None, "route"));
+        assert_ne!(node, NodeBuf::new("route"));
+    }
+
+    #[test]
+    fn test_node_buf_span() {
+        let mut node = NodeBuf::new("server");
+        assert_eq!(node.span(), None);
+
+        let start = Position {
+            offset: 20,
+            line: 3,
+            column: 1,
+        };
+        let end = Position {
+            offset: 26,
+            column: 7,
+            ..start
+        };
+        node.set_span(Some(Span::new(start, end)));
+        node.set_source(Some("config.gpnd".into()));
+        assert_eq!(node.source(), Some("config.gpnd"));
+        assert_eq!(node.span().map(|span| span.len()), Some(6));
+        assert_eq!(node.span().unwrap().to_string(), "3:1");
+        assert_eq!(node, NodeBuf::new("server"));
+    }
+
+    #[cfg(feature = "smallvec")]
+    #[test]
+    fn test_node_buf_inline() {
+        let mut node = crate::parser::parse("server \"a\" \"b\" \"c\" port=80 tls=true")
+            .unwrap()
+            .nodes_mut()
+            .remove(0);
+        assert!(!node.args.0.spilled());
+        node.args_mut().push(Value::from("d"));
+        assert!(node.args.0.spilled());
+        assert_eq!(node.params_ref().len(), 2);
+    }
+}
@@ -0,0 +1,726 @@
+use alloc::collections::BTreeSet;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::Value;
+
+/// The entries of [`Params`]. With the `smallvec` feature, up to two are kept
+/// inline.
+#[cfg(not(feature = "smallvec"))]
+type Entries = Vec<(Arc<str>, Value<'static>)>;
+#[cfg(feature = "smallvec")]
+type Entries = smallvec::SmallVec<[(Arc<str>, Value<'static>); 2]>;
+
+/// The parameters of a [`NodeBuf`](super::NodeBuf), in the order they were
+/// written.
+///
+/// Keys are usually unique, but [`Params::push`] allows duplicates. Which
+/// occurrence lookups find is decided by the [`DuplicateParams`] policy, by
+/// default the last, so that later parameters override earlier ones.
+///
+/// With [`ParamOrder::Sorted`], the parameters are kept sorted by key instead,
+/// like a `BTreeMap`, so that they are iterated and written in the same order
+/// however they were built.
+///
+/// Parameters compare equal if they have the same entries in the same order,
+/// whatever their policy and order are.
+#[derive(Clone, Debug, Default)]
+pub struct Params {
+    entries: Entries,
+    policy: DuplicateParams,
+    order: ParamOrder,
+}
+
+/// Which occurrence of a repeated parameter is used when looking it up.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum DuplicateParams {
+    /// Later occurrences override earlier ones.
+    #[default]
+    LastWins,
+    /// The first occurrence is used, and later ones are ignored.
+    FirstWins,
+}
+
+/// The order that [`Params`] keeps its parameters in.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ParamOrder {
+    /// The order they were written or inserted in.
+    #[default]
+    Written,
+    /// Sorted by key, with the occurrences of a repeated key in the order
+    /// they were written.
+    Sorted,
+}
+
+impl Params {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn with_policy(policy: DuplicateParams) -> Self {
+        Self {
+            policy,
+            ..Self::default()
+        }
+    }
+
+    /// Create parameters that are kept in `order`.
+    ///
+    /// ```
+    /// # use gpnd::{ParamOrder, Params};
+    /// let mut params = Params::with_order(ParamOrder::Sorted);
+    /// params.insert("port", 80);
+    /// params.insert("host", "localhost");
+    /// assert_eq!(params.keys().collect::<Vec<_>>(), ["host", "port"]);
+    /// ```
+    #[must_use]
+    pub fn with_order(order: ParamOrder) -> Self {
+        Self {
+            order,
+            ..Self::default()
+        }
+    }
+
+    #[must_use]
+    pub fn policy(&self) -> DuplicateParams {
+        self.policy
+    }
+
+    pub fn set_policy(&mut self, policy: DuplicateParams) {
+        self.policy = policy;
+    }
+
+    #[must_use]
+    pub fn order(&self) -> ParamOrder {
+        self.order
+    }
+
+    /// Set the order, sorting the parameters if it is [`ParamOrder::Sorted`].
+    pub fn set_order(&mut self, order: ParamOrder) {
+        self.order = order;
+        if order == ParamOrder::Sorted {
+            self.sort();
+        }
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Add a new entry where the order puts it, returning its index.
+    fn add(&mut self, key: Arc<str>, value: Value<'static>) -> usize {
+        let index = match self.order {
+            ParamOrder::Written => self.entries.len(),
+            ParamOrder::Sorted => self.entries.partition_point(|(other, _)| *other <= key),
+        };
+        self.entries.insert(index, (key, value));
+        index
+    }
+
+    fn position(&self, key: &str) -> Option<usize> {
+        if self.order == ParamOrder::Sorted {
+            let start = self.entries.partition_point(|(other, _)| **other < *key);
+            let end = self.entries.partition_point(|(other, _)| **other <= *key);
+            return match self.policy {
+                _ if start == end => None,
+                DuplicateParams::LastWins => Some(end - 1),
+                DuplicateParams::FirstWins => Some(start),
+            };
+        }
+        let mut entries = self.entries.iter();
+        match self.policy {
+            DuplicateParams::LastWins => entries.rposition(|(other, _)| **other == *key),
+            DuplicateParams::FirstWins => entries.position(|(other, _)| **other == *key),
+        }
+    }
+
+    #[must_use]
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.position(key).is_some()
+    }
+
+    /// Get the value of `key`, following the duplicate policy.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&Value<'static>> {
+        self.position(key).map(|index| &self.entries[index].1)
+    }
+
+    /// Get the value of `key` mutably, following the duplicate policy.
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Value<'static>> {
+        self.position(key).map(|index| &mut self.entries[index].1)
+    }
+
+    /// Set the value of `key`, returning the previous value.
+    ///
+    /// If the key is already present, the occurrence that lookups would find
+    /// is replaced where it is, and any duplicates are removed. Otherwise the
+    /// parameter is added where the order puts it.
+    pub fn insert<K, V>(&mut self, key: K, value: V) -> Option<Value<'static>>
+    where
+        K: Into<Arc<str>>,
+        V: Into<Value<'static>>,
+    {
+        let key = key.into();
+        let value = value.into();
+        let Some(index) = self.position(&key) else {
+            self.add(key, value);
+            return None;
+        };
+        Some(self.replace(index, value).0)
+    }
+
+    /// Replace the value at `index`, and remove the other occurrences of its
+    /// key, returning the previous value and the new index of the entry.
+    fn replace(&mut self, index: usize, value: Value<'static>) -> (Value<'static>, usize) {
+        let key = Arc::clone(&self.entries[index].0);
+        let previous = core::mem::replace(&mut self.entries[index].1, value);
+        let before = self.entries[..index]
+            .iter()
+            .filter(|(other, _)| *other == key)
+            .count();
+        let mut current = 0;
+        self.entries.retain(|(other, _)| {
+            current += 1;
+            current - 1 == index || *other != key
+        });
+        (previous, index - before)
+    }
+
+    /// Append a parameter, even if the key is already present.
+    ///
+    /// With [`ParamOrder::Sorted`], it is added after the other parameters
+    /// with the same key.
+    pub fn push<K, V>(&mut self, key: K, value: V)
+    where
+        K: Into<Arc<str>>,
+        V: Into<Value<'static>>,
+    {
+        self.add(key.into(), value.into());
+    }
+
+    /// Remove every occurrence of `key`, returning the value that lookups
+    /// would have found.
+    pub fn remove(&mut self, key: &str) -> Option<Value<'static>> {
+        let index = self.position(key)?;
+        let removed = core::mem::take(&mut self.entries[index].1);
+        self.entries.retain(|(other, _)| **other != *key);
+        Some(removed)
+    }
+
+    /// Get the entry for `key`, to insert or modify its value in place.
+    ///
+    /// ```
+    /// # use gpnd::Params;
+    /// let mut params = Params::new();
+    /// params.entry("retries").or_insert(3);
+    /// params
+    ///     .entry("retries")
+    ///     .and_modify(|value| *value = 5.into())
+    ///     .or_insert(0);
+    /// assert_eq!(params.get("retries"), Some(&5.into()));
+    /// ```
+    pub fn entry<K>(&mut self, key: K) -> ParamEntry<'_>
+    where
+        K: Into<Arc<str>>,
+    {
+        let key = key.into();
+        match self.position(&key) {
+            Some(index) => ParamEntry::Occupied(OccupiedParamEntry {
+                params: self,
+                index,
+            }),
+            None => ParamEntry::Vacant(VacantParamEntry { params: self, key }),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Sort the parameters by key, keeping the occurrences of a repeated key
+    /// in the order they were written, so that lookups find the same values.
+    pub fn sort(&mut self) {
+        self.entries.sort_by(|(lhs, _), (rhs, _)| lhs.cmp(rhs));
+    }
+
+    /// Iterate over every parameter in order, including duplicates.
+    #[must_use]
+    pub fn iter(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = (&str, &Value<'static>)> + ExactSizeIterator {
+        self.entries.iter().map(|(key, value)| (&**key, value))
+    }
+
+    /// Iterate over the parameters that lookups would find, one per key, in
+    /// the order they were written.
+    #[must_use]
+    pub fn iter_unique(&self) -> IterUnique<'_> {
+        // Sorted occurrences of a key are next to each other, so that only
+        // written parameters need to remember which keys were seen.
+        let mut found = Vec::new();
+        if self.order == ParamOrder::Written {
+            let mut seen = BTreeSet::new();
+            let keys = self.keys();
+            found = match self.policy {
+                DuplicateParams::FirstWins => keys.map(|key| seen.insert(key)).collect(),
+                DuplicateParams::LastWins => {
+                    let mut found: Vec<_> = keys.rev().map(|key| seen.insert(key)).collect();
+                    found.reverse();
+                    found
+                }
+            };
+        }
+        IterUnique {
+            params: self,
+            found,
+            front: 0,
+            back: self.entries.len(),
+        }
+    }
+
+    /// Iterate over every value of `key`, in order.
+    #[must_use]
+    pub fn get_all<'key>(
+        &self,
+        key: &'key str,
+    ) -> impl DoubleEndedIterator<Item = &Value<'static>> + use<'_, 'key> {
+        self.entries
+            .iter()
+            .filter(move |(other, _)| **other == *key)
+            .map(|(_, value)| value)
+    }
+
+    pub fn iter_mut(
+        &mut self,
+    ) -> impl DoubleEndedIterator<Item = (&str, &mut Value<'static>)> + ExactSizeIterator {
+        self.entries.iter_mut().map(|(key, value)| (&**key, value))
+    }
+
+    #[must_use]
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &str> + ExactSizeIterator {
+        self.entries.iter().map(|(key, _)| &**key)
+    }
+
+    #[must_use]
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &Value<'static>> + ExactSizeIterator {
+        self.entries.iter().map(|(_, value)| value)
+    }
+}
+
+/// An iterator over the parameters that lookups would find, see
+/// [`Params::iter_unique`].
+#[derive(Clone, Debug)]
+pub struct IterUnique<'params> {
+    params: &'params Params,
+    /// Whether each entry is the one that lookups find, unless the
+    /// parameters are sorted.
+    found: Vec<bool>,
+    front: usize,
+    back: usize,
+}
+
+impl<'params> IterUnique<'params> {
+    fn entry(&self, index: usize) -> Option<(&'params str, &'params Value<'static>)> {
+        let entries = &self.params.entries;
+        let (key, value) = &entries[index];
+        let found = match (self.params.order, self.params.policy) {
+            (ParamOrder::Written, _) => self.found[index],
+            (ParamOrder::Sorted, DuplicateParams::LastWins) => {
+                entries.get(index + 1).is_none_or(|(next, _)| next != key)
+            }
+            (ParamOrder::Sorted, DuplicateParams::FirstWins) => {
+                index == 0 || entries[index - 1].0 != *key
+            }
+        };
+        found.then_some((&**key, value))
+    }
+}
+
+impl<'params> Iterator for IterUnique<'params> {
+    type Item = (&'params str, &'params Value<'static>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.front < self.back {
+            self.front += 1;
+            if let Some(entry) = self.entry(self.front - 1) {
+                return Some(entry);
+            }
+        }
+        None
+    }
+}
+
+impl DoubleEndedIterator for IterUnique<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        while self.front < self.back {
+            self.back -= 1;
+            if let Some(entry) = self.entry(self.back) {
+                return Some(entry);
+            }
+        }
+        None
+    }
+}
+
+/// A parameter that may or may not be present, see [`Params::entry`].
+#[derive(Debug)]
+pub enum ParamEntry<'params> {
+    Occupied(OccupiedParamEntry<'params>),
+    Vacant(VacantParamEntry<'params>),
+}
+
+impl<'params> ParamEntry<'params> {
+    #[must_use]
+    pub fn key(&self) -> &str {
+        match self {
+            Self::Occupied(entry) => entry.key(),
+            Self::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// Insert `default` if the parameter is absent, and return its value.
+    pub fn or_insert<V>(self, default: V) -> &'params mut Value<'static>
+    where
+        V: Into<Value<'static>>,
+    {
+        self.or_insert_with(|| default)
+    }
+
+    /// Insert the result of `default` if the parameter is absent, and return
+    /// its value.
+    pub fn or_insert_with<F, V>(self, default: F) -> &'params mut Value<'static>
+    where
+        F: FnOnce() -> V,
+        V: Into<Value<'static>>,
+    {
+        match self {
+            Self::Occupied(entry) => entry.into_mut(),
+            Self::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Insert `Null` if the parameter is absent, and return its value.
+    pub fn or_default(self) -> &'params mut Value<'static> {
+        self.or_insert_with(Value::default)
+    }
+
+    /// Modify the value if the parameter is present.
+    #[must_use]
+    pub fn and_modify<F>(mut self, modify: F) -> Self
+    where
+        F: FnOnce(&mut Value<'static>),
+    {
+        if let Self::Occupied(entry) = &mut self {
+            modify(entry.get_mut());
+        }
+        self
+    }
+}
+
+/// A parameter that is present, see [`Params::entry`].
+///
+/// If the key is repeated, this is the occurrence that lookups would find.
+#[derive(Debug)]
+pub struct OccupiedParamEntry<'params> {
+    params: &'params mut Params,
+    index: usize,
+}
+
+impl<'params> OccupiedParamEntry<'params> {
+    #[must_use]
+    pub fn key(&self) -> &str {
+        &self.params.entries[self.index].0
+    }
+
+    #[must_use]
+    pub fn get(&self) -> &Value<'static> {
+        &self.params.entries[self.index].1
+    }
+
+    pub fn get_mut(&mut self) -> &mut Value<'static> {
+        &mut self.params.entries[self.index].1
+    }
+
+    #[must_use]
+    pub fn into_mut(self) -> &'params mut Value<'static> {
+        &mut self.params.entries[self.index].1
+    }
+
+    /// Replace the value, and remove any duplicates of the key, returning
+    /// the previous value, like [`Params::insert`].
+    pub fn insert<V>(&mut self, value: V) -> Value<'static>
+    where
+        V: Into<Value<'static>>,
+    {
+        let (previous, index) = self.params.replace(self.index, value.into());
+        self.index = index;
+        previous
+    }
+
+    /// Remove every occurrence of the key, returning the value, like
+    /// [`Params::remove`].
+    #[allow(clippy::must_use_candidate)]
+    pub fn remove(self) -> Value<'static> {
+        let key = Arc::clone(&self.params.entries[self.index].0);
+        let value = core::mem::take(&mut self.params.entries[self.index].1);
+        self.params.entries.retain(|(other, _)| *other != key);
+        value
+    }
+}
+
+/// A parameter that is absent, see [`Params::entry`].
+#[derive(Debug)]
+pub struct VacantParamEntry<'params> {
+    params: &'params mut Params,
+    key: Arc<str>,
+}
+
+impl<'params> VacantParamEntry<'params> {
+    #[must_use]
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Add the parameter where the order of the parameters puts it, and
+    /// return its value.
+    pub fn insert<V>(self, value: V) -> &'params mut Value<'static>
+    where
+        V: Into<Value<'static>>,
+    {
+        let index = self.params.add(self.key, value.into());
+        &mut self.params.entries[index].1
+    }
+}
+
+impl PartialEq for Params {
+    fn eq(&self, other: &Self) -> bool {
+        self.entries == other.entries
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for Params
+where
+    K: Into<Arc<str>>,
+    V: Into<Value<'static>>,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut params = Self::new();
+        params.extend(iter);
+        params
+    }
+}
+
+/// Extends with parameters using [`Params::insert`], so existing keys are
+/// replaced.
+impl<K, V> Extend<(K, V)> for Params
+where
+    K: Into<Arc<str>>,
+    V: Into<Value<'static>>,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl IntoIterator for Params {
+    type Item = (Arc<str>, Value<'static>);
+    type IntoIter = vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        #[cfg(feature = "smallvec")]
+        return self.entries.into_vec().into_iter();
+        #[cfg(not(feature = "smallvec"))]
+        self.entries.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::{DuplicateParams, ParamEntry, ParamOrder, Params};
+    use crate::Value;
+
+    #[test]
+    fn test_params_insert() {
+        let mut params = Params::from_iter([("a", 1_u8), ("b", 2)]);
+        params.push("a", 3_u8);
+        assert_eq!(params.len(), 3);
+        assert_eq!(params.get("a"), Some(&3_u8.into()));
+
+        // Inserting replaces the occurrence that lookups find in place, and
+        // drops the rest.
+        assert_eq!(params.insert("a", 4_u8), Some(3_u8.into()));
+        assert_eq!(params.keys().collect::<Vec<_>>(), ["b", "a"]);
+        assert_eq!(params.get("a"), Some(&4_u8.into()));
+
+        assert_eq!(params.remove("a"), Some(4_u8.into()));
+        assert!(!params.contains_key("a"));
+        assert_eq!(params.insert("c", true), None);
+        assert_eq!(params.keys().collect::<Vec<_>>(), ["b", "c"]);
+    }
+
+    #[test]
+    fn test_params_duplicate_insert() {
+        for (policy, found) in [
+            (DuplicateParams::LastWins, 3_u8),
+            (DuplicateParams::FirstWins, 1),
+        ] {
+            let duplicated = || {
+                let mut params = Params::with_policy(policy);
+                params.push("a", 1_u8);
+                params.push("b", 2_u8);
+                params.push("a", 3_u8);
+                params
+            };
+            let keys = match policy {
+                DuplicateParams::LastWins => ["b", "a"],
+                DuplicateParams::FirstWins => ["a", "b"],
+            };
+
+            let mut params = duplicated();
+            assert_eq!(params.get("a"), Some(&found.into()));
+            *params.get_mut("a").unwrap() = 4_u8.into();
+            assert_eq!(params.get("a"), Some(&4_u8.into()));
+            assert_eq!(params.get_all("a").count(), 2);
+
+            let mut params = duplicated();
+            assert_eq!(params.insert("a", 4_u8), Some(found.into()));
+            assert_eq!(params.keys().collect::<Vec<_>>(), keys);
+            assert_eq!(params.get("a"), Some(&4_u8.into()));
+
+            let mut params = duplicated();
+            let ParamEntry::Occupied(mut entry) = params.entry("a") else {
+                panic!("`a` should be present");
+            };
+            assert_eq!(entry.get(), &Value::from(found));
+            assert_eq!(entry.insert(4_u8), Value::from(found));
+            assert_eq!(entry.get(), &Value::from(4_u8));
+            assert_eq!(params.keys().collect::<Vec<_>>(), keys);
+            assert_eq!(params.get("a"), Some(&4_u8.into()));
+        }
+    }
+
+    #[test]
+    fn test_params_duplicate_policy() {
+        let mut params = Params::from_iter([("include", "a"), ("mode", "x")]);
+        params.push("include", "b");
+        assert_eq!(
+            params.get_all("include").collect::<Vec<_>>(),
+            [&Value::from("a"), &Value::from("b")]
+        );
+        assert_eq!(
+            params.iter_unique().collect::<Vec<_>>(),
+            [("mode", &"x".into()), ("include", &"b".into())]
+        );
+
+        params.set_policy(DuplicateParams::FirstWins);
+        assert_eq!(params.get("include"), Some(&"a".into()));
+        assert_eq!(
+            params.iter_unique().collect::<Vec<_>>(),
+            [("include", &"a".into()), ("mode", &"x".into())]
+        );
+        assert_eq!(
+            params.iter_unique().next_back(),
+            Some(("mode", &"x".into()))
+        );
+        assert_eq!(params.remove("include"), Some("a".into()));
+        assert_eq!(params.len(), 1);
+    }
+
+    #[test]
+    fn test_params_entry() {
+        let mut params = Params::from_iter([("port", 80_u16)]);
+        params.push("port", 8080_u16);
+
+        let ParamEntry::Occupied(mut entry) = params.entry("port") else {
+            panic!("`port` should be present");
+        };
+        assert_eq!(entry.insert(8443_u16), Value::U16(8080));
+        assert_eq!(params.get_all("port").count(), 1);
+
+        *params.entry("tls").or_insert(false) = true.into();
+        params
+            .entry("tls")
+            .and_modify(|value| *value = false.into())
+            .or_insert(true);
+        assert_eq!(params.get("tls"), Some(&false.into()));
+        assert!(params.entry("host").or_default().is_null());
+
+        let ParamEntry::Occupied(entry) = params.entry("port") else {
+            panic!("`port` should be present");
+        };
+        assert_eq!(entry.remove(), Value::U16(8443));
+        assert_eq!(params.keys().collect::<Vec<_>>(), ["tls", "host"]);
+    }
+
+    #[test]
+    fn test_params_sorted() {
+        let mut params = Params::from_iter([("port", 80_u16), ("host", 1)]);
+        params.push("port", 8080_u16);
+        params.set_order(ParamOrder::Sorted);
+        assert_eq!(params.keys().collect::<Vec<_>>(), ["host", "port", "port"]);
+        assert_eq!(params.get("port"), Some(&8080_u16.into()));
+
+        params.insert("tls", true);
+        params.push("alias", "a");
+        params.entry("cert").or_insert("c");
+        params.push("port", 8443_u16);
+        assert_eq!(
+            params.keys().collect::<Vec<_>>(),
+            ["alias", "cert", "host", "port", "port", "port", "tls"]
+        );
+        assert_eq!(params.get("port"), Some(&8443_u16.into()));
+        assert_eq!(params.insert("port", 443_u16), Some(8443_u16.into()));
+        assert_eq!(
+            params.iter_unique().collect::<Vec<_>>(),
+            [
+                ("alias", &"a".into()),
+                ("cert", &"c".into()),
+                ("host", &1_u16.into()),
+                ("port", &443_u16.into()),
+                ("tls", &true.into()),
+            ]
+        );
+
+        params.push("port", 8443_u16);
+        params.set_policy(DuplicateParams::FirstWins);
+        assert_eq!(params.get("port"), Some(&443_u16.into()));
+        assert_eq!(params.get("missing"), None);
+        assert_eq!(
+            params
+                .iter_unique()
+                .rev()
+                .map(|(key, _)| key)
+                .collect::<Vec<_>>(),
+            ["tls", "port", "host", "cert", "alias"]
+        );
+        assert_eq!(params.iter_unique().nth(3), Some(("port", &443_u16.into())));
+    }
+
+    #[test]
+    fn test_params_eq() {
+        let written = Params::from_iter([("host", "a"), ("port", "b")]);
+        let mut sorted = Params::with_order(ParamOrder::Sorted);
+        sorted.extend([("port", "b"), ("host", "a")]);
+        sorted.set_policy(DuplicateParams::FirstWins);
+        assert_eq!(written, sorted);
+        sorted.push("host", "c");
+        assert_ne!(written, sorted);
+        assert_ne!(
+            Params::from_iter([("port", "b"), ("host", "a")]),
+            Params::from_iter([("host", "a"), ("port", "b")])
+        );
+    }
+}
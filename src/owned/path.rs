@@ -0,0 +1,305 @@
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt;
+
+use super::{DocumentBuf, NodeBuf};
+use crate::{Node, Value};
+
+/// An error from editing a [`DocumentBuf`] by path, with the segment of the
+/// path that could not be followed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PathError {
+    kind: PathErrorKind,
+    path: String,
+    segment: usize,
+}
+
+impl PathError {
+    fn new(kind: PathErrorKind, path: &str, segment: usize) -> Self {
+        Self {
+            kind,
+            path: path.to_owned(),
+            segment,
+        }
+    }
+
+    #[must_use]
+    pub fn kind(&self) -> PathErrorKind {
+        self.kind
+    }
+
+    #[must_use]
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The index of the segment that failed, among the segments of the path.
+    #[must_use]
+    pub fn segment_index(&self) -> usize {
+        self.segment
+    }
+
+    /// The text of the segment that failed, which is empty if the path is.
+    #[must_use]
+    pub fn segment(&self) -> &str {
+        segments(&self.path).nth(self.segment).unwrap_or("")
+    }
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            PathErrorKind::Empty => write!(f, "path `{}` does not name a node", self.path),
+            PathErrorKind::InvalidSegment => write!(
+                f,
+                "invalid segment `{}` in path `{}`",
+                self.segment(),
+                self.path
+            ),
+            PathErrorKind::NodeNotFound => write!(
+                f,
+                "no node `{}` at segment {} of path `{}`",
+                self.segment(),
+                self.segment + 1,
+                self.path
+            ),
+            PathErrorKind::ParamNotFound => write!(
+                f,
+                "no parameter `{}` at the end of path `{}`",
+                self.segment(),
+                self.path
+            ),
+        }
+    }
+}
+
+impl Error for PathError {}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PathErrorKind {
+    /// The path has no segments, or a parameter path has no segment for
+    /// the node before the key.
+    Empty,
+    /// A segment has an occurrence suffix that is not `[n]`.
+    InvalidSegment,
+    NodeNotFound,
+    ParamNotFound,
+}
+
+fn segments(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|segment| !segment.is_empty())
+}
+
+/// Split a segment into a name and the occurrence of the name, such as
+/// `server[1]` for the second `server`.
+fn parse_segment(segment: &str) -> Option<(&str, usize)> {
+    match segment.strip_suffix(']') {
+        Some(rest) => {
+            let (name, occurrence) = rest.split_once('[')?;
+            Some((name, occurrence.parse().ok()?))
+        }
+        None => Some((segment, 0)),
+    }
+}
+
+/// Parse the segment at `index` of `path`.
+fn parse_at<'path>(
+    path: &str,
+    index: usize,
+    segment: &'path str,
+) -> Result<(&'path str, usize), PathError> {
+    parse_segment(segment).ok_or_else(|| PathError::new(PathErrorKind::InvalidSegment, path, index))
+}
+
+/// The index of the node that `segment` names among `nodes`.
+fn find(nodes: &[NodeBuf], (name, occurrence): (&str, usize)) -> Option<usize> {
+    nodes
+        .iter()
+        .enumerate()
+        .filter(|(_, node)| node.name() == name)
+        .nth(occurrence)
+        .map(|(index, _)| index)
+}
+
+/// Editing by path.
+///
+/// Paths are names separated by `/`, as taken by
+/// [`Document::get_path`](crate::Document::get_path). A name can be followed
+/// by the occurrence of the name among its siblings, so that `server[1]` is
+/// the second `server` node. This is the form of the paths in
+/// [`Change`](crate::diff::Change)s.
+impl DocumentBuf {
+    /// Get a node by path.
+    pub fn get_path_mut(&mut self, path: &str) -> Result<&mut NodeBuf, PathError> {
+        let not_found = |index| PathError::new(PathErrorKind::NodeNotFound, path, index);
+
+        let mut segments = segments(path).enumerate();
+        let (_, first) = segments
+            .next()
+            .ok_or_else(|| PathError::new(PathErrorKind::Empty, path, 0))?;
+        let position =
+            find(self.nodes_ref(), parse_at(path, 0, first)?).ok_or_else(|| not_found(0))?;
+        let mut node = &mut self.nodes_mut()[position];
+        for (index, text) in segments {
+            let position = find(node.children_ref(), parse_at(path, index, text)?)
+                .ok_or_else(|| not_found(index))?;
+            node = &mut node.children_mut()[position];
+        }
+        Ok(node)
+    }
+
+    /// Set a parameter by path, such as `"server/port"`, where the last
+    /// segment is the parameter key and the rest is the path of its node,
+    /// returning the previous value.
+    ///
+    /// ```
+    /// # use gpnd::Document;
+    /// let mut document = gpnd::parser::parse("server port=80 { tls }").unwrap();
+    /// document.set("server/port", 8080).unwrap();
+    /// document.set("server/tls/enabled", true).unwrap();
+    /// assert_eq!(document.get_path_value("server/port").unwrap(), 8080);
+    /// assert_eq!(
+    ///     document.set("client/port", 1).unwrap_err().to_string(),
+    ///     "no node `client` at segment 1 of path `client/port`"
+    /// );
+    /// ```
+    pub fn set<V>(&mut self, path: &str, value: V) -> Result<Option<Value<'static>>, PathError>
+    where
+        V: Into<Value<'static>>,
+    {
+        let (node, key) = self.param_path(path)?;
+        Ok(node.params_mut().insert(key, value))
+    }
+
+    /// Remove a parameter by path, see [`DocumentBuf::set`].
+    pub fn remove_value(&mut self, path: &str) -> Result<Value<'static>, PathError> {
+        let count = segments(path).count();
+        let (node, key) = self.param_path(path)?;
+        node.params_mut()
+            .remove(key)
+            .ok_or_else(|| PathError::new(PathErrorKind::ParamNotFound, path, count - 1))
+    }
+
+    /// Remove a node by path, with its children.
+    pub fn remove(&mut self, path: &str) -> Result<NodeBuf, PathError> {
+        let count = segments(path).count();
+        let (siblings, last) = self.siblings_path(path)?;
+        let position = find(siblings, parse_at(path, count - 1, last)?)
+            .ok_or_else(|| PathError::new(PathErrorKind::NodeNotFound, path, count - 1))?;
+        Ok(siblings.remove(position))
+    }
+
+    /// Append a child to the node at `path`.
+    pub fn insert_child(&mut self, path: &str, child: NodeBuf) -> Result<(), PathError> {
+        self.get_path_mut(path)?.children_mut().push(child);
+        Ok(())
+    }
+
+    /// The nodes that the last segment of `path` is found among, and that
+    /// segment.
+    fn siblings_path<'path>(
+        &mut self,
+        path: &'path str,
+    ) -> Result<(&mut Vec<NodeBuf>, &'path str), PathError> {
+        let parts: Vec<_> = segments(path).collect();
+        let Some((last, parents)) = parts.split_last() else {
+            return Err(PathError::new(PathErrorKind::Empty, path, 0));
+        };
+        if parents.is_empty() {
+            return Ok((self.nodes_mut(), last));
+        }
+        let parent = self
+            .get_path_mut(&parents.join("/"))
+            .map_err(|error| PathError {
+                path: path.to_owned(),
+                ..error
+            })?;
+        Ok((parent.children_mut(), last))
+    }
+
+    /// The node and key of a parameter path.
+    fn param_path<'path>(
+        &mut self,
+        path: &'path str,
+    ) -> Result<(&mut NodeBuf, &'path str), PathError> {
+        let parts: Vec<_> = segments(path).collect();
+        let Some((key, parents)) = parts.split_last() else {
+            return Err(PathError::new(PathErrorKind::Empty, path, 0));
+        };
+        if parents.is_empty() {
+            return Err(PathError::new(PathErrorKind::Empty, path, 0));
+        }
+        let node = self
+            .get_path_mut(&parents.join("/"))
+            .map_err(|error| PathError {
+                path: path.to_owned(),
+                ..error
+            })?;
+        Ok((node, key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+    use crate::parser::parse;
+    use crate::{Document, Node};
+
+    #[test]
+    fn test_path_mutation() {
+        let mut document = parse("server { tls; listen port=80; listen port=81 }\nlog").unwrap();
+        assert_eq!(
+            document.set("server/listen[1]/port", 8081_i64).unwrap(),
+            Some(Value::I64(81))
+        );
+        assert_eq!(
+            document.remove_value("server/listen/port").unwrap(),
+            Value::I64(80)
+        );
+        document
+            .insert_child("server/tls", NodeBuf::new("cert"))
+            .unwrap();
+        assert_eq!(document.remove("log").unwrap().name(), "log");
+        assert_eq!(document.remove("server/listen").unwrap().params().len(), 0);
+        assert_eq!(
+            document,
+            parse("server { tls { cert }; listen port=8081 }").unwrap()
+        );
+        assert!(document.get_path("server/tls/cert").is_some());
+    }
+
+    #[test]
+    fn test_path_errors() {
+        let mut document = parse("server { tls }").unwrap();
+        let error = document.remove("server/tls/cert").unwrap_err();
+        assert_eq!(error.kind(), PathErrorKind::NodeNotFound);
+        assert_eq!(error.segment(), "cert");
+        assert_eq!(
+            document.set("server/listen/port", 1).unwrap_err().segment(),
+            "listen"
+        );
+        assert_eq!(
+            document
+                .remove_value("server/port")
+                .unwrap_err()
+                .to_string(),
+            "no parameter `port` at the end of path `server/port`"
+        );
+        assert_eq!(
+            document.set("port", 1).unwrap_err().kind(),
+            PathErrorKind::Empty
+        );
+        assert_eq!(
+            document.get_path_mut("server[x]").unwrap_err().kind(),
+            PathErrorKind::InvalidSegment
+        );
+        assert_eq!(
+            document.remove("").unwrap_err().kind(),
+            PathErrorKind::Empty
+        );
+    }
+}
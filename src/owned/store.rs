@@ -0,0 +1,340 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use super::{DocumentBuf, NodeBuf};
+
+/// A handle to a node in a [`NodeStore`].
+///
+/// Handles stay valid while other nodes are inserted and removed. Once the
+/// node itself is removed, its handle no longer resolves, even if its slot is
+/// reused for another node.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct NodeHandle {
+    index: usize,
+    generation: u32,
+}
+
+impl fmt::Display for NodeHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}v{}", self.index, self.generation)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Entry {
+    /// The node, with its children moved out into the store.
+    node: NodeBuf,
+    parent: Option<NodeHandle>,
+    children: Vec<NodeHandle>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct Slot {
+    generation: u32,
+    entry: Option<Entry>,
+}
+
+/// A document whose nodes are addressed by [`NodeHandle`]s, for tools that
+/// need to refer to nodes across a series of edits, or to navigate from a
+/// node to its parent.
+///
+/// Nodes are kept without their children, which are only reachable through
+/// [`NodeStore::children`]. Convert a [`DocumentBuf`] into a store with
+/// `From`, and back with [`NodeStore::into_document`].
+#[derive(Clone, Debug, Default)]
+pub struct NodeStore {
+    slots: Vec<Slot>,
+    free: Vec<usize>,
+    roots: Vec<NodeHandle>,
+}
+
+impl NodeStore {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of nodes in the store, at any depth.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.slots.len() - self.free.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn entry(&self, handle: NodeHandle) -> Option<&Entry> {
+        let slot = self.slots.get(handle.index)?;
+        (slot.generation == handle.generation)
+            .then_some(slot.entry.as_ref())
+            .flatten()
+    }
+
+    fn entry_mut(&mut self, handle: NodeHandle) -> Option<&mut Entry> {
+        let slot = self.slots.get_mut(handle.index)?;
+        (slot.generation == handle.generation)
+            .then_some(slot.entry.as_mut())
+            .flatten()
+    }
+
+    #[must_use]
+    pub fn contains(&self, handle: NodeHandle) -> bool {
+        self.entry(handle).is_some()
+    }
+
+    /// Get the node, without its children.
+    #[must_use]
+    pub fn get(&self, handle: NodeHandle) -> Option<&NodeBuf> {
+        self.entry(handle).map(|entry| &entry.node)
+    }
+
+    /// Get the node mutably, without its children. Children pushed to it
+    /// directly come before those in the store when the node is taken out.
+    pub fn get_mut(&mut self, handle: NodeHandle) -> Option<&mut NodeBuf> {
+        self.entry_mut(handle).map(|entry| &mut entry.node)
+    }
+
+    /// The handles of the top-level nodes.
+    #[must_use]
+    pub fn roots(&self) -> &[NodeHandle] {
+        &self.roots
+    }
+
+    #[must_use]
+    pub fn parent(&self, handle: NodeHandle) -> Option<NodeHandle> {
+        self.entry(handle)?.parent
+    }
+
+    #[must_use]
+    pub fn children(&self, handle: NodeHandle) -> &[NodeHandle] {
+        self.entry(handle)
+            .map_or(&[], |entry| entry.children.as_slice())
+    }
+
+    /// The handles of the parent of `handle`, its parent, and so on up to a
+    /// top-level node.
+    pub fn ancestors(&self, handle: NodeHandle) -> impl Iterator<Item = NodeHandle> + '_ {
+        core::iter::successors(self.parent(handle), |handle| self.parent(*handle))
+    }
+
+    /// Put an entry in a free slot, or a new one.
+    fn alloc(&mut self, entry: Entry) -> NodeHandle {
+        if let Some(index) = self.free.pop() {
+            let slot = &mut self.slots[index];
+            slot.entry = Some(entry);
+            NodeHandle {
+                index,
+                generation: slot.generation,
+            }
+        } else {
+            self.slots.push(Slot {
+                generation: 0,
+                entry: Some(entry),
+            });
+            NodeHandle {
+                index: self.slots.len() - 1,
+                generation: 0,
+            }
+        }
+    }
+
+    /// Store a node and its children, returning the handle of the node, which
+    /// is left for the caller to add to the children of `parent`.
+    ///
+    /// The nodes are stored with a stack rather than by recursion, so that
+    /// deeply nested nodes cannot overflow the stack.
+    fn store(&mut self, node: NodeBuf, parent: Option<NodeHandle>) -> NodeHandle {
+        let mut pending = vec![(node, parent)];
+        let mut top = None;
+        while let Some((mut node, parent)) = pending.pop() {
+            let children = core::mem::take(node.children_mut());
+            let handle = self.alloc(Entry {
+                node,
+                parent,
+                children: Vec::new(),
+            });
+            match (top, parent.and_then(|parent| self.entry_mut(parent))) {
+                (Some(_), Some(parent)) => parent.children.push(handle),
+                _ => top = Some(handle),
+            }
+            // The first child is stored next, with its children, before the
+            // second, so that each parent gets its children in order.
+            pending.extend(
+                children
+                    .into_iter()
+                    .rev()
+                    .map(|child| (child, Some(handle))),
+            );
+        }
+        top.unwrap_or_else(|| unreachable!("the node itself is stored first"))
+    }
+
+    /// Add a top-level node and its children, returning the handle of the
+    /// node.
+    pub fn push_root(&mut self, node: NodeBuf) -> NodeHandle {
+        let handle = self.store(node, None);
+        self.roots.push(handle);
+        handle
+    }
+
+    /// Add a node and its children as the last child of `parent`, returning
+    /// the handle of the node, or `None` if `parent` is not in the store.
+    pub fn push_child(&mut self, parent: NodeHandle, node: NodeBuf) -> Option<NodeHandle> {
+        let index = self.entry(parent)?.children.len();
+        self.insert_child(parent, index, node)
+    }
+
+    /// Add a node and its children as the child of `parent` at `index`,
+    /// returning the handle of the node, or `None` if `parent` is not in the
+    /// store.
+    ///
+    /// # Panics
+    /// Panics if `index` is greater than the number of children of `parent`.
+    pub fn insert_child(
+        &mut self,
+        parent: NodeHandle,
+        index: usize,
+        node: NodeBuf,
+    ) -> Option<NodeHandle> {
+        let len = self.entry(parent)?.children.len();
+        assert!(
+            index <= len,
+            "insertion index (is {index}) should be <= len (is {len})"
+        );
+        let handle = self.store(node, Some(parent));
+        self.entry_mut(parent)?.children.insert(index, handle);
+        Some(handle)
+    }
+
+    /// Take the entry out of the slot of `handle`, making the handle invalid.
+    fn release(&mut self, handle: NodeHandle) -> Option<Entry> {
+        let slot = self.slots.get_mut(handle.index)?;
+        if slot.generation != handle.generation {
+            return None;
+        }
+        let entry = slot.entry.take()?;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(handle.index);
+        Some(entry)
+    }
+
+    /// Take a node out of the slot of `handle`, with its children, making the
+    /// handles of all of them invalid.
+    ///
+    /// Like [`NodeStore::store`], this uses a stack rather than recursion.
+    fn take(&mut self, handle: NodeHandle) -> Option<NodeBuf> {
+        let entry = self.release(handle)?;
+        let mut stack = vec![(entry.node, entry.children.into_iter())];
+        loop {
+            let (_, children) = stack.last_mut()?;
+            if let Some(child) = children.next() {
+                if let Some(entry) = self.release(child) {
+                    stack.push((entry.node, entry.children.into_iter()));
+                }
+                continue;
+            }
+            let (node, _) = stack.pop()?;
+            match stack.last_mut() {
+                Some((parent, _)) => parent.children_mut().push(node),
+                None => return Some(node),
+            }
+        }
+    }
+
+    /// Remove a node and its children from the store.
+    pub fn remove(&mut self, handle: NodeHandle) -> Option<NodeBuf> {
+        let parent = self.entry(handle)?.parent;
+        let siblings = match parent.and_then(|parent| self.entry_mut(parent)) {
+            Some(parent) => &mut parent.children,
+            None => &mut self.roots,
+        };
+        siblings.retain(|sibling| *sibling != handle);
+        self.take(handle)
+    }
+
+    /// Rebuild the document from the nodes in the store.
+    #[must_use]
+    pub fn into_document(mut self) -> DocumentBuf {
+        core::mem::take(&mut self.roots)
+            .into_iter()
+            .filter_map(|root| self.take(root))
+            .collect()
+    }
+}
+
+impl From<DocumentBuf> for NodeStore {
+    fn from(document: DocumentBuf) -> Self {
+        let mut store = Self::new();
+        for node in document {
+            store.push_root(node);
+        }
+        store
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::NodeStore;
+    use crate::{document, node, Node};
+
+    #[test]
+    fn test_node_store() {
+        let document = document![
+            node!("server" { node!("tls"), node!("log") }),
+            node!("client")
+        ];
+        let mut store = NodeStore::from(document);
+        assert_eq!(store.len(), 4);
+
+        let server = store.roots()[0];
+        let tls = store.children(server)[0];
+        let log = store.children(server)[1];
+        assert_eq!(store.parent(log), Some(server));
+        assert_eq!(store.parent(server), None);
+
+        // Handles to other nodes survive removals and insertions.
+        assert_eq!(store.remove(tls), Some(node!("tls")));
+        let cert = store
+            .insert_child(server, 0, node!("cert" { node!("key") }))
+            .unwrap();
+        assert!(!store.contains(tls));
+        assert_ne!(cert, tls);
+        let key = store.children(cert)[0];
+        assert_eq!(store.ancestors(key).collect::<Vec<_>>(), [cert, server]);
+        assert_eq!(store.get(log).map(Node::name), Some("log"));
+        store
+            .get_mut(log)
+            .unwrap()
+            .params_mut()
+            .insert("level", "info");
+        assert_eq!(store.len(), 5);
+
+        let document = store.into_document();
+        assert_eq!(
+            document,
+            document![
+                node!("server" { node!("cert" { node!("key") }), node!("log" level="info") }),
+                node!("client"),
+            ]
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_node_store_insert_out_of_bounds() {
+        let mut store = NodeStore::from(document![node!("server" { node!("tls") })]);
+        let server = store.roots()[0];
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            store.insert_child(server, 2, node!("log"))
+        }));
+        assert!(result.is_err());
+        // Nothing was stored for the node that was not inserted.
+        assert_eq!(store.len(), 2);
+        assert_eq!(store.children(server).len(), 1);
+    }
+}
@@ -0,0 +1,433 @@
+//! Parsing into a bump arena, for workloads that parse many documents and
+//! only read them, such as batch validation.
+//!
+//! A [`ParsedDocument`] keeps its nodes, arguments, and parameters in a
+//! [`Bump`] owned by the caller, so that parsing takes a few large
+//! allocations rather than several for every node, and the nodes of a
+//! document are close together in memory. Node names, parameter keys, and
+//! strings without escapes are borrowed from the source.
+//!
+//! ```
+//! # use bumpalo::Bump;
+//! # use gpnd::parser::Parser;
+//! # use gpnd::{Document, Node};
+//! let arena = Bump::new();
+//! let document = Parser::new("server \"main\" port=80 { tls }")
+//!     .parse_in(&arena)
+//!     .unwrap();
+//! let server = document.get_node(0).unwrap();
+//! assert_eq!(server.get_param("port").unwrap(), 80);
+//! assert_eq!(server.get_child(0).unwrap().name(), "tls");
+//! ```
+
+use alloc::borrow::{Cow, ToOwned};
+use alloc::format;
+
+use bumpalo::boxed::Box;
+use bumpalo::collections::Vec;
+use bumpalo::Bump;
+
+use super::config::DuplicatePolicy;
+use super::document::{annotation, split_name, unexpected, Parser};
+use super::error::{ParseError, ParseErrorKind};
+use super::literal::parse_string;
+use super::token::{Token, TokenKind};
+use crate::{Document, Node, Span, Value};
+
+/// A document parsed into an arena, see [`Parser::parse_in`].
+#[derive(Debug)]
+pub struct ParsedDocument<'arena> {
+    version: Option<&'arena str>,
+    nodes: Box<'arena, [ArenaNode<'arena>]>,
+}
+
+impl Document for ParsedDocument<'_> {
+    fn nodes(&self) -> alloc::vec::Vec<&dyn Node> {
+        self.nodes_iter().collect()
+    }
+
+    fn nodes_iter(&self) -> alloc::boxed::Box<dyn Iterator<Item = &dyn Node> + '_> {
+        alloc::boxed::Box::new(self.nodes.iter().map(|node| -> &dyn Node { node }))
+    }
+
+    fn get_node(&self, index: usize) -> Option<&dyn Node> {
+        self.nodes.get(index).map(|node| -> &dyn Node { node })
+    }
+
+    fn has_nodes(&self) -> bool {
+        !self.nodes.is_empty()
+    }
+
+    fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn version(&self) -> Option<&str> {
+        self.version
+    }
+}
+
+/// A node of a [`ParsedDocument`].
+///
+/// As with [`NodeBuf`](crate::NodeBuf), later parameters override earlier
+/// ones with the same key.
+#[derive(Debug)]
+pub struct ArenaNode<'arena> {
+    namespace: Option<&'arena str>,
+    name: &'arena str,
+    type_annotation: Option<&'arena str>,
+    args: Box<'arena, [Value<'arena>]>,
+    params: Box<'arena, [(&'arena str, Value<'arena>)]>,
+    children: Box<'arena, [ArenaNode<'arena>]>,
+    span: Span,
+}
+
+impl ArenaNode<'_> {
+    /// Whether the parameter at `index` is the last with its key.
+    fn is_last(&self, index: usize) -> bool {
+        let key = self.params[index].0;
+        self.params[index + 1..]
+            .iter()
+            .all(|(other, _)| *other != key)
+    }
+}
+
+impl Node for ArenaNode<'_> {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn namespace(&self) -> Option<&str> {
+        self.namespace
+    }
+
+    fn type_annotation(&self) -> Option<&str> {
+        self.type_annotation
+    }
+
+    fn args(&self) -> alloc::vec::Vec<Value<'_>> {
+        self.args.iter().map(Value::borrowed).collect()
+    }
+
+    fn params(&self) -> alloc::vec::Vec<(&str, Value<'_>)> {
+        (0..self.params.len())
+            .filter(|index| self.is_last(*index))
+            .map(|index| (self.params[index].0, self.params[index].1.borrowed()))
+            .collect()
+    }
+
+    fn params_all(&self) -> alloc::vec::Vec<(&str, Value<'_>)> {
+        self.params
+            .iter()
+            .map(|(key, value)| (*key, value.borrowed()))
+            .collect()
+    }
+
+    fn get_arg(&self, index: usize) -> Option<Value<'_>> {
+        self.args.get(index).map(Value::borrowed)
+    }
+
+    fn arg_count(&self) -> usize {
+        self.args.len()
+    }
+
+    fn get_param(&self, key: &str) -> Option<Value<'_>> {
+        self.params
+            .iter()
+            .rfind(|(other, _)| *other == key)
+            .map(|(_, value)| value.borrowed())
+    }
+
+    fn has_args(&self) -> bool {
+        !self.args.is_empty()
+    }
+
+    fn has_params(&self) -> bool {
+        !self.params.is_empty()
+    }
+
+    fn children(&self) -> alloc::vec::Vec<&dyn Node> {
+        self.children_iter().collect()
+    }
+
+    fn children_iter(&self) -> alloc::boxed::Box<dyn Iterator<Item = &dyn Node> + '_> {
+        alloc::boxed::Box::new(self.children.iter().map(|child| -> &dyn Node { child }))
+    }
+
+    fn get_child(&self, index: usize) -> Option<&dyn Node> {
+        self.children.get(index).map(|child| -> &dyn Node { child })
+    }
+
+    fn has_children(&self) -> bool {
+        !self.children.is_empty()
+    }
+
+    fn span(&self) -> Option<Span> {
+        Some(self.span)
+    }
+}
+
+impl<'src> Parser<'src> {
+    /// Get the text of a string token, borrowing it unless it has escapes or
+    /// is normalized.
+    fn arena_string<'arena>(
+        &self,
+        arena: &'arena Bump,
+        token: &Token<'src>,
+    ) -> Result<&'arena str, ParseError>
+    where
+        'src: 'arena,
+    {
+        let text = parse_string(token.text)
+            .map_err(|error| ParseError::new(ParseErrorKind::Literal(error), token.span))?;
+        Ok(self.arena_text(arena, text))
+    }
+
+    /// Normalize `text`, and move it into `arena` unless it is borrowed.
+    fn arena_text<'arena>(&self, arena: &'arena Bump, text: Cow<'src, str>) -> &'arena str
+    where
+        'src: 'arena,
+    {
+        match self.normalize(text) {
+            Cow::Borrowed(text) => text,
+            Cow::Owned(text) => arena.alloc_str(&text),
+        }
+    }
+
+    /// Parse the source as a single document into `arena`, see the
+    /// [module](super::arena) documentation.
+    ///
+    /// Comments are dropped, and nodes cannot have anchors, so `*name`
+    /// references to nodes fail. Values can still have anchors if they are
+    /// enabled.
+    ///
+    /// # Errors
+    /// Fails if the source is not valid, or holds more than one document.
+    pub fn parse_in<'arena>(
+        mut self,
+        arena: &'arena Bump,
+    ) -> Result<ParsedDocument<'arena>, ParseError>
+    where
+        'src: 'arena,
+    {
+        let version = self.directives()?;
+        let version = version.map(|version| &*arena.alloc_str(&version));
+        let nodes = self.arena_nodes(arena, false)?;
+        match self.next()? {
+            token if token.kind == TokenKind::End => Ok(ParsedDocument { version, nodes }),
+            token => Err(unexpected("node", &token)),
+        }
+    }
+
+    /// Parse nodes into `arena`, up to the end of a document or a block.
+    fn arena_nodes<'arena>(
+        &mut self,
+        arena: &'arena Bump,
+        nested: bool,
+    ) -> Result<Box<'arena, [ArenaNode<'arena>]>, ParseError>
+    where
+        'src: 'arena,
+    {
+        let mut nodes = Vec::new_in(arena);
+        loop {
+            self.skip_newlines(true)?;
+            let token = self.peek()?;
+            match token.kind {
+                TokenKind::RightBrace if nested => break,
+                TokenKind::End | TokenKind::Separator if !nested => break,
+                TokenKind::End | TokenKind::Separator => return Err(unexpected("`}`", &token)),
+                TokenKind::Directive => {
+                    return Err(ParseError::new(
+                        ParseErrorKind::MisplacedDirective(token.text[1..].to_owned()),
+                        token.span,
+                    ));
+                }
+                _ => {
+                    let node = self.arena_node(arena)?;
+                    match self.node_index(&nodes, &node, &token)? {
+                        Some(index) if index == nodes.len() => nodes.push(node),
+                        Some(index) => nodes[index] = node,
+                        None => {}
+                    }
+                }
+            }
+        }
+        self.lexer.take_comments();
+        Ok(nodes.into_boxed_slice())
+    }
+
+    /// Parse a node into `arena`.
+    fn arena_node<'arena>(&mut self, arena: &'arena Bump) -> Result<ArenaNode<'arena>, ParseError>
+    where
+        'src: 'arena,
+    {
+        self.lexer.take_comments();
+        let token = self.next()?;
+        let (type_annotation, name_token) = match token.kind {
+            TokenKind::Annotation => (
+                Some(self.arena_text(arena, annotation(&token)?)),
+                self.next()?,
+            ),
+            _ => (None, token),
+        };
+        let (namespace, name) = match name_token.kind {
+            TokenKind::Ident if self.sigil(&name_token, '*').is_some() => {
+                let kind = ParseErrorKind::UnknownAnchor(name_token.text[1..].to_owned());
+                return Err(ParseError::new(kind, name_token.span));
+            }
+            TokenKind::Ident => {
+                let (namespace, name) = split_name(&name_token)?;
+                (
+                    namespace.map(|namespace| self.arena_text(arena, namespace.into())),
+                    self.arena_text(arena, name.into()),
+                )
+            }
+            TokenKind::String => (None, self.arena_string(arena, &name_token)?),
+            _ => return Err(unexpected("node name", &name_token)),
+        };
+        let (type_annotation, namespace) = match (type_annotation, namespace) {
+            (Some(annotation), None) if self.config().paren_namespaces => (None, Some(annotation)),
+            (Some(_), Some(_)) if self.config().paren_namespaces => {
+                let name = format!("{}{}", token.text, name_token.text);
+                return Err(ParseError::new(
+                    ParseErrorKind::InvalidName(name),
+                    token.span,
+                ));
+            }
+            names => names,
+        };
+        let mut args = Vec::new_in(arena);
+        let mut params = Vec::new_in(arena);
+        let mut children = None;
+        loop {
+            let entry = self.peek()?;
+            match entry.kind {
+                TokenKind::Newline
+                | TokenKind::Semicolon
+                | TokenKind::RightBrace
+                | TokenKind::Separator
+                | TokenKind::End => break,
+                TokenKind::LeftBrace => {
+                    self.next()?;
+                    children =
+                        Some(self.nested(entry.span, |parser| parser.arena_nodes(arena, true))?);
+                    self.next()?;
+                    self.terminator()?;
+                    break;
+                }
+                TokenKind::Ident | TokenKind::String => {
+                    self.next()?;
+                    if self.peek()?.kind == TokenKind::Equals {
+                        self.next()?;
+                        let key = match entry.kind {
+                            TokenKind::String => self.arena_string(arena, &entry)?,
+                            _ => self.arena_text(arena, entry.text.into()),
+                        };
+                        let value = self.arena_value(arena)?;
+                        match params.iter().position(|(other, _)| *other == key) {
+                            None => params.push((key, value)),
+                            Some(index) => match self.config().duplicate_params {
+                                DuplicatePolicy::CollectAll => params.push((key, value)),
+                                DuplicatePolicy::Error => {
+                                    let kind = ParseErrorKind::DuplicateParam(key.to_owned());
+                                    return Err(ParseError::new(kind, entry.span));
+                                }
+                                DuplicatePolicy::FirstWins => {}
+                                DuplicatePolicy::LastWins => params[index].1 = value,
+                            },
+                        }
+                    } else if self.sigil(&entry, '&').is_none() {
+                        args.push(match entry.kind {
+                            TokenKind::String => {
+                                Value::String(Cow::Borrowed(self.arena_string(arena, &entry)?))
+                            }
+                            _ => self.value(Some(entry))?,
+                        });
+                    }
+                }
+                _ => args.push(self.value(None)?),
+            }
+            self.comma()?;
+        }
+        Ok(ArenaNode {
+            namespace,
+            name,
+            type_annotation,
+            args: args.into_boxed_slice(),
+            params: params.into_boxed_slice(),
+            children: children.unwrap_or_else(|| Vec::new_in(arena).into_boxed_slice()),
+            span: Span::new(token.span.start, self.last_end),
+        })
+    }
+
+    /// Parse a value, borrowing it if it is a string without escapes.
+    fn arena_value<'arena>(&mut self, arena: &'arena Bump) -> Result<Value<'arena>, ParseError>
+    where
+        'src: 'arena,
+    {
+        let token = self.peek()?;
+        if token.kind == TokenKind::String {
+            self.next()?;
+            return Ok(Value::String(Cow::Borrowed(
+                self.arena_string(arena, &token)?,
+            )));
+        }
+        self.value(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+    use crate::diff::diff;
+    use crate::parser::{parse, ParserConfig};
+
+    #[test]
+    fn test_parse_in() {
+        let source = concat!(
+            "@version \"1\"\n",
+            "// A comment.\n",
+            "http:server \"main\" \"a\\tb\" port=80 port=8080 { tls; \"log file\" path=\"x\" }\n",
+            "(\"a\\tb\")client [1 2] #{ a=1 }\n",
+        );
+        let arena = Bump::new();
+        let document = Parser::new(source).parse_in(&arena).unwrap();
+        assert!(diff(&document, &parse(source).unwrap()).is_empty());
+        assert_eq!(document.version(), Some("1"));
+
+        let server = document.get_node(0).unwrap();
+        assert_eq!(server.namespace(), Some("http"));
+        assert_eq!(server.params(), [("port", Value::I64(8080))]);
+        assert_eq!(server.params_all().len(), 2);
+        assert_eq!(server.span().unwrap().to_string(), "3:1");
+        let Some(Value::String(Cow::Borrowed(main))) = server.get_arg(0) else {
+            panic!("strings without escapes should be borrowed");
+        };
+        assert!(source.contains(main));
+        assert_eq!(server.get_child(1).unwrap().name(), "log file");
+        assert_eq!(
+            document.get_node(1).unwrap().type_annotation(),
+            Some("a\tb")
+        );
+
+        let error = |source: &str| Parser::new(source).parse_in(&arena).unwrap_err();
+        assert_eq!(
+            error("a {").to_string(),
+            "1:4: expected `}`, found end of input"
+        );
+        assert_eq!(
+            error("a\n@version \"1\"").kind().to_string(),
+            "directive `@version` must come before any nodes"
+        );
+        let config = ParserConfig {
+            anchors: true,
+            ..ParserConfig::default()
+        };
+        assert!(Parser::new("a &x\n*x")
+            .with_config(config)
+            .parse_in(&arena)
+            .is_err());
+    }
+}
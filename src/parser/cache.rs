@@ -0,0 +1,281 @@
+//! Reusing the tokens of lines that did not change since the last time a
+//! source was lexed, for sources that are parsed again after small edits,
+//! such as when formatting a file on save.
+
+use std::collections::HashMap;
+use std::hash::{BuildHasher, RandomState};
+
+use super::document::Parser;
+use super::error::ParseError;
+use super::lexer::Lexer;
+use super::token::{Comment, Token, TokenKind};
+use crate::{Position, Span};
+
+/// A token or a comment from a [`Lexer`], in the order they were read.
+#[derive(Clone, Debug)]
+pub(super) enum Lexeme<'src> {
+    Comment(Comment<'src>),
+    Token(Token<'src>),
+    Error(ParseError),
+}
+
+/// A token or a comment of a [`Run`], without its text, and with a span that
+/// is relative to the start of the run.
+#[derive(Copy, Clone, Debug)]
+enum Item {
+    Comment(Span),
+    Token(TokenKind, Span),
+}
+
+/// The tokens of one or more lines, which are lexed together since tokens
+/// such as block comments and strings can span lines.
+#[derive(Clone, Debug)]
+struct Run {
+    text: Box<str>,
+    items: Vec<Item>,
+    /// Where the run ends, relative to its start.
+    end: Position,
+    /// Whether the run ends at the end of the source.
+    last: bool,
+}
+
+impl Run {
+    /// Whether the run can be reused at the start of `rest`.
+    fn matches(&self, rest: &str) -> bool {
+        if self.last {
+            rest == &*self.text
+        } else {
+            rest.starts_with(&*self.text)
+        }
+    }
+
+    /// Add the tokens and comments of the run to `lexemes`, where it starts
+    /// at `start` in `source`.
+    fn replay<'src>(&self, source: &'src str, start: Position, lexemes: &mut Vec<Lexeme<'src>>) {
+        for item in &self.items {
+            let (Item::Comment(span) | Item::Token(_, span)) = *item;
+            let span = Span::new(absolute(start, span.start), absolute(start, span.end));
+            let text = &source[span.start.offset..span.end.offset];
+            lexemes.push(match *item {
+                Item::Comment(_) => Lexeme::Comment(Comment { text, span }),
+                Item::Token(kind, _) => Lexeme::Token(Token { kind, text, span }),
+            });
+        }
+    }
+}
+
+/// The position of `position`, relative to `start`, in the source. Runs
+/// start at the start of a line, so the column is the same.
+fn absolute(start: Position, position: Position) -> Position {
+    Position {
+        offset: start.offset + position.offset,
+        line: start.line + position.line,
+        column: position.column,
+    }
+}
+
+/// Lex the lines of `source` from `start`, which is the start of a line, up
+/// to and including the next newline that ends a line, returning the run and
+/// the error that stopped it, if any.
+fn lex_run(source: &str, start: Position) -> (Run, Option<ParseError>) {
+    let relative = |position: Position| Position {
+        offset: position.offset - start.offset,
+        line: position.line - start.line,
+        column: position.column,
+    };
+    let relative_span = |span: Span| Span::new(relative(span.start), relative(span.end));
+    let mut lexer = Lexer::resume_line(source, start);
+    let mut items = Vec::new();
+    let (end, error) = loop {
+        let token = lexer.next_token();
+        items.extend(
+            lexer
+                .take_comments()
+                .into_iter()
+                .map(|comment| Item::Comment(relative_span(comment.span))),
+        );
+        match token {
+            Ok(token) => {
+                items.push(Item::Token(token.kind, relative_span(token.span)));
+                if matches!(token.kind, TokenKind::Newline | TokenKind::End) {
+                    break (token, None);
+                }
+            }
+            Err(error) => {
+                let position = lexer.position();
+                let end = Token {
+                    kind: TokenKind::End,
+                    text: "",
+                    span: Span::new(position, position),
+                };
+                break (end, Some(error));
+            }
+        }
+    };
+    let run = Run {
+        text: source[start.offset..end.span.end.offset].into(),
+        items,
+        end: relative(end.span.end),
+        last: end.kind == TokenKind::End,
+    };
+    (run, error)
+}
+
+/// A cache of the tokens of a source, by the text of the lines they were
+/// read from, so that lexing the source again after an edit only lexes the
+/// lines that changed.
+///
+/// The cache keeps the tokens of the last source that was lexed, so that it
+/// does not grow with every edit.
+///
+/// ```
+/// # use gpnd::parser::{Parser, TokenCache};
+/// let mut cache = TokenCache::new();
+/// let source = "server port=80\nclient retries=3\n";
+/// Parser::new(source)
+///     .with_token_cache(&mut cache)
+///     .parse()
+///     .unwrap();
+/// let edited = "server port=8080\nclient retries=3\n";
+/// Parser::new(edited)
+///     .with_token_cache(&mut cache)
+///     .parse()
+///     .unwrap();
+/// // The second line, and the empty run at the end of the source.
+/// assert_eq!(cache.reused(), 2);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TokenCache {
+    /// The runs, by a hash of their first line.
+    runs: HashMap<u64, Vec<Run>>,
+    hasher: RandomState,
+    reused: usize,
+}
+
+impl TokenCache {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of runs of lines that were reused the last time a source
+    /// was lexed. A run is a line, or several if a token spans them.
+    #[must_use]
+    pub fn reused(&self) -> usize {
+        self.reused
+    }
+
+    /// The number of runs of lines that are cached.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.runs.values().map(Vec::len).sum()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.runs.clear();
+        self.reused = 0;
+    }
+
+    /// Lex `source`, reusing the tokens of the lines that are cached, and
+    /// return a lexer that gives the tokens.
+    ///
+    /// A lexing error ends the tokens, and the lines from the one it was
+    /// found in are not cached.
+    pub fn lexer<'src>(&mut self, source: &'src str) -> Lexer<'src> {
+        let mut runs: HashMap<u64, Vec<Run>> = HashMap::new();
+        let mut lexemes = Vec::new();
+        let mut start = Position::START;
+        self.reused = 0;
+        loop {
+            let rest = &source[start.offset..];
+            let line = rest.find('\n').map_or(rest, |index| &rest[..=index]);
+            let key = self.hasher.hash_one(line);
+            let cached = self.runs.get_mut(&key).and_then(|candidates| {
+                let index = candidates.iter().position(|run| run.matches(rest))?;
+                Some(candidates.swap_remove(index))
+            });
+            let run = if let Some(run) = cached {
+                self.reused += 1;
+                run
+            } else {
+                let (run, error) = lex_run(source, start);
+                if let Some(error) = error {
+                    run.replay(source, start, &mut lexemes);
+                    lexemes.push(Lexeme::Error(error));
+                    break;
+                }
+                run
+            };
+            run.replay(source, start, &mut lexemes);
+            let (end, last) = (absolute(start, run.end), run.last);
+            runs.entry(key).or_default().push(run);
+            if last {
+                break;
+            }
+            start = end;
+        }
+        self.runs = runs;
+        Lexer::replay(source, lexemes)
+    }
+}
+
+impl Parser<'_> {
+    /// Read the tokens of the source through `cache`, see [`TokenCache`].
+    #[must_use]
+    pub fn with_token_cache(mut self, cache: &mut TokenCache) -> Self {
+        self.lexer = cache.lexer(self.lexer.source());
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    fn tokens(mut lexer: Lexer<'_>) -> Vec<(Token<'_>, Vec<Comment<'_>>)> {
+        let mut tokens = Vec::new();
+        loop {
+            let token = lexer.next_token().unwrap();
+            tokens.push((token, lexer.take_comments()));
+            if token.kind == TokenKind::End {
+                return tokens;
+            }
+        }
+    }
+
+    #[test]
+    fn test_token_cache() {
+        let mut cache = TokenCache::new();
+        let sources = [
+            "a 1 // A.\nb \"x\ny\" /* c\nd */ k=1\nf\n",
+            "a 1 // A.\nb \"x\ny\" /* c\nd */ k=1\ng h=2\nf\n",
+            "z\na 1 // A.\nb \"x\ny\" /* c\nd */ k=1\nf",
+            "\u{e9} \"x\ny\" /* c\nd */ k=1\n\u{e9} \"x\ny\" /* c\nd */ k=1\n",
+        ];
+        for source in sources {
+            assert_eq!(tokens(cache.lexer(source)), tokens(Lexer::new(source)));
+            let document = Parser::new(source).with_token_cache(&mut cache).parse();
+            assert_eq!(document.unwrap(), parse(source).unwrap());
+        }
+        assert_eq!(cache.reused(), 3);
+
+        // Only the empty run at the end of input is shared.
+        cache.lexer(sources[1]);
+        assert_eq!(cache.reused(), 1);
+        cache.lexer(sources[1]);
+        assert_eq!(cache.reused(), 5);
+        assert_eq!(cache.len(), 5);
+
+        let error = Parser::new("a\nb \"c")
+            .with_token_cache(&mut cache)
+            .parse()
+            .unwrap_err();
+        assert_eq!(error, parse("a\nb \"c").unwrap_err());
+    }
+}
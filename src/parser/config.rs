@@ -0,0 +1,122 @@
+use crate::lint::DEFAULT_MAX_DIAGNOSTICS;
+use crate::DuplicateParams;
+
+/// Options controlling how source text is parsed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ParserConfig {
+    /// How to treat numbers followed by a unit, such as `10kb` or `250ms`.
+    pub unit_suffixes: UnitSuffixes,
+    /// Whether `&name` anchors and `*name` references are read, see
+    /// [`Parser`](super::Parser).
+    pub anchors: bool,
+    /// Whether commas are allowed after arguments, parameters, list items,
+    /// and map entries, including after the last one, as in `a 1, b=2,` or
+    /// `[1, 2,]`.
+    pub commas: bool,
+    /// Whether `(ns)name` gives a node the namespace `ns`, as `ns:name` does,
+    /// rather than the type annotation `ns`.
+    pub paren_namespaces: bool,
+    /// What to do with a parameter that a node already has, as in
+    /// `a port=80 port=8080`, which also sets the [`DuplicateParams`] policy
+    /// of the parameters of parsed nodes.
+    pub duplicate_params: DuplicatePolicy,
+    /// What to do with a node that has the same name and namespace as an
+    /// earlier sibling.
+    pub duplicate_nodes: DuplicatePolicy,
+    /// Whether identifiers and strings are normalized, see
+    /// [`Normalization`].
+    #[cfg(feature = "unicode")]
+    pub normalization: Normalization,
+    /// How deeply blocks of children, lists, and maps may be nested, beyond
+    /// which the source is rejected rather than risk overflowing the stack.
+    pub max_depth: usize,
+    /// How many nodes and values `*name` references may copy in a document,
+    /// counting those nested in each copy, beyond which the source is
+    /// rejected rather than risk anchors that refer to each other expanding
+    /// a short source into an enormous document.
+    pub max_expansion: usize,
+    /// How many warnings are collected, after which a last
+    /// [`WarningKind::TooManyWarnings`](crate::WarningKind::TooManyWarnings)
+    /// says that the rest were dropped.
+    pub max_warnings: usize,
+}
+
+impl Default for ParserConfig {
+    fn default() -> Self {
+        Self {
+            unit_suffixes: UnitSuffixes::default(),
+            anchors: false,
+            commas: false,
+            paren_namespaces: false,
+            duplicate_params: DuplicatePolicy::default(),
+            duplicate_nodes: DuplicatePolicy::default(),
+            #[cfg(feature = "unicode")]
+            normalization: Normalization::default(),
+            max_depth: 128,
+            max_expansion: 65_536,
+            max_warnings: DEFAULT_MAX_DIAGNOSTICS,
+        }
+    }
+}
+
+/// How to treat repeated parameters or sibling nodes.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// Every occurrence is kept, in the order they were written, with a
+    /// warning for each repeated parameter.
+    #[default]
+    CollectAll,
+    /// A repeated occurrence is an error.
+    Error,
+    /// Later occurrences are dropped.
+    FirstWins,
+    /// Later occurrences replace the earlier one, in its place.
+    LastWins,
+}
+
+/// The policy that lookups in the [`Params`](crate::Params) of a parsed node
+/// follow, which find the last occurrence of a parameter unless only the
+/// first was kept.
+impl From<DuplicatePolicy> for DuplicateParams {
+    fn from(policy: DuplicatePolicy) -> Self {
+        match policy {
+            DuplicatePolicy::FirstWins => Self::FirstWins,
+            DuplicatePolicy::CollectAll | DuplicatePolicy::Error | DuplicatePolicy::LastWins => {
+                Self::LastWins
+            }
+        }
+    }
+}
+
+/// How to treat text that is not in Unicode Normalization Form C (NFC), such
+/// as names written on systems that decompose accented letters, so that
+/// documents compare equal however their text was composed.
+#[cfg(feature = "unicode")]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Normalization {
+    /// Text is kept as it was written.
+    #[default]
+    Preserve,
+    /// Node names, namespaces, type annotations, keys, and strings are
+    /// normalized to NFC.
+    Nfc,
+    /// Like `Nfc`, but an identifier that is not already in NFC is an error,
+    /// while quoted text is still normalized.
+    Strict,
+}
+
+/// How to treat number literals with a unit suffix.
+///
+/// See [`Unit`](super::literal::Unit) for the recognized suffixes.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum UnitSuffixes {
+    /// A suffix is a syntax error.
+    #[default]
+    Reject,
+    /// The number is scaled to the base unit, and produced as a
+    /// `Value::Size` of bytes or a `Value::Duration` of nanoseconds.
+    Normalize,
+    /// The literal is produced verbatim as a `Value::String`, so that the
+    /// application can interpret the unit itself.
+    Preserve,
+}
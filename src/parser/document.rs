@@ -0,0 +1,1412 @@
+use alloc::borrow::{Cow, ToOwned};
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::str::FromStr;
+
+#[cfg(feature = "unicode")]
+use super::config::Normalization;
+use super::config::{DuplicatePolicy, ParserConfig};
+use super::error::{ParseError, ParseErrorKind};
+use super::lexer::Lexer;
+use super::literal::{is_rounded, parse_number, parse_string};
+use super::token::{Comment, Token, TokenKind};
+use crate::lint::push_limited;
+use crate::{
+    Document, DocumentBuf, Interner, Node, NodeBuf, NodePath, Position, Span, Value, Warning,
+    WarningKind,
+};
+
+/// Parses source text into [`DocumentBuf`]s.
+///
+/// A document is a list of nodes, each on its own line or ended by `;`. A
+/// node is a name, followed by arguments, `key=value` parameters, and an
+/// optional block of children in braces:
+///
+/// ```text
+/// @version "1.0"
+///
+/// // A comment.
+/// server "localhost" port=8080 {
+///     tls enabled=true
+///     http:route "/" methods=["GET" "HEAD"]
+/// }
+/// ```
+///
+/// The name of a node can be preceded by a type annotation in parentheses,
+/// such as `(widget)button`, see
+/// [`Node::type_annotation`].
+///
+/// A source can hold several documents separated by `---` lines, see
+/// [`Parser::documents`].
+///
+/// If [`ParserConfig::anchors`] is set, a node can be given an anchor by
+/// writing `&name` among its arguments, and a node written as `*name` is a
+/// copy of it, with any arguments, parameters, and children written after
+/// the reference added to the copy. A value is given an anchor by writing
+/// `&name` before it, and `*name` in place of a value is a copy of it. An
+/// anchor must come before its references in the same document:
+///
+/// ```text
+/// server &defaults port=80 { log level="info" }
+/// *defaults port=8080 { tls }
+/// limits burst=&burst 10 rate=*burst
+/// ```
+///
+/// Node names, namespaces, and parameter keys are interned, so that the nodes
+/// of a source share one copy of each, see [`Parser::parse_interned`].
+#[derive(Clone, Debug)]
+pub struct Parser<'src> {
+    pub(super) lexer: Lexer<'src>,
+    peeked: Option<Token<'src>>,
+    /// The end of the last token taken.
+    pub(super) last_end: Position,
+    config: ParserConfig,
+    source: Option<Arc<str>>,
+    anchors: BTreeMap<String, NodeBuf>,
+    value_anchors: BTreeMap<String, Value<'static>>,
+    interner: Interner,
+    warnings: Vec<Warning>,
+    /// The number of blocks, lists, and maps being parsed.
+    depth: usize,
+    /// The number of nodes and values copied by references in the document.
+    expanded: usize,
+}
+
+/// A document parsed by [`Parser::parse_with_warnings`], with the warnings
+/// found in it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseOutput {
+    pub document: DocumentBuf,
+    pub warnings: Vec<Warning>,
+}
+
+impl<'src> Parser<'src> {
+    #[must_use]
+    pub fn new(source: &'src str) -> Self {
+        Self {
+            lexer: Lexer::new(source),
+            peeked: None,
+            last_end: Position::START,
+            config: ParserConfig::default(),
+            source: None,
+            anchors: BTreeMap::new(),
+            value_anchors: BTreeMap::new(),
+            interner: Interner::new(),
+            warnings: Vec::new(),
+            depth: 0,
+            expanded: 0,
+        }
+    }
+
+    #[must_use]
+    pub fn with_config(mut self, config: ParserConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Set the name of the source, such as a file path, which is recorded on
+    /// the parsed documents and nodes.
+    #[must_use]
+    pub fn with_source_name<S>(mut self, name: S) -> Self
+    where
+        S: Into<Arc<str>>,
+    {
+        self.source = Some(name.into());
+        self
+    }
+
+    #[must_use]
+    pub fn config(&self) -> ParserConfig {
+        self.config
+    }
+
+    /// The name of the source, see [`Parser::with_source_name`].
+    #[must_use]
+    pub fn source_name(&self) -> Option<&str> {
+        self.source.as_deref()
+    }
+
+    /// Parse the source as a single document.
+    ///
+    /// # Errors
+    /// Fails if the source is not valid, or holds more than one document.
+    pub fn parse(mut self) -> Result<DocumentBuf, ParseError> {
+        self.single_document()
+    }
+
+    /// Parse the source as a single document, like [`Parser::parse`], with
+    /// the [`Warning`]s found in it, such as duplicate parameters and map
+    /// keys, and numbers rounded by [`UnitSuffixes::Normalize`].
+    ///
+    /// ```
+    /// # use gpnd::parser::Parser;
+    /// let output = Parser::new("server port=80 port=8080")
+    ///     .parse_with_warnings()
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     output.warnings[0].to_string(),
+    ///     "1:16: duplicate parameter `port`"
+    /// );
+    /// ```
+    ///
+    /// [`UnitSuffixes::Normalize`]: super::UnitSuffixes::Normalize
+    ///
+    /// # Errors
+    /// Fails if the source is not valid, or holds more than one document.
+    pub fn parse_with_warnings(mut self) -> Result<ParseOutput, ParseError> {
+        let document = self.single_document()?;
+        Ok(ParseOutput {
+            document,
+            warnings: self.warnings,
+        })
+    }
+
+    /// Parse the source as a single document, like [`Parser::parse`], with
+    /// the strings of every document parsed into `interner` shared between
+    /// them.
+    ///
+    /// ```
+    /// # use gpnd::parser::Parser;
+    /// # use gpnd::Interner;
+    /// let mut interner = Interner::new();
+    /// for source in ["server port=80", "server port=8080", "client"] {
+    ///     Parser::new(source).parse_interned(&mut interner).unwrap();
+    /// }
+    /// assert_eq!(interner.len(), 3);
+    /// ```
+    ///
+    /// # Errors
+    /// Fails if the source is not valid, or holds more than one document.
+    pub fn parse_interned(mut self, interner: &mut Interner) -> Result<DocumentBuf, ParseError> {
+        core::mem::swap(&mut self.interner, interner);
+        let document = self.single_document();
+        core::mem::swap(&mut self.interner, interner);
+        document
+    }
+
+    fn single_document(&mut self) -> Result<DocumentBuf, ParseError> {
+        let document = self.document()?;
+        match self.next()? {
+            token if token.kind == TokenKind::End => Ok(document),
+            token => Err(unexpected("node", &token)),
+        }
+    }
+
+    /// Parse the source as a stream of documents separated by `---` lines.
+    ///
+    /// Empty documents are skipped, so a stream can start or end with a
+    /// separator, which makes it easy to append documents to a file over time.
+    #[must_use]
+    pub fn documents(self) -> Documents<'src> {
+        Documents {
+            parser: self,
+            done: false,
+        }
+    }
+
+    pub(super) fn peek(&mut self) -> Result<Token<'src>, ParseError> {
+        if let Some(token) = self.peeked {
+            return Ok(token);
+        }
+        let token = self.lexer.next_token()?;
+        if token.kind == TokenKind::Comma && !self.config.commas {
+            return Err(ParseError::new(
+                ParseErrorKind::UnexpectedChar(','),
+                token.span,
+            ));
+        }
+        // Quoted annotations are strings, which are normalized rather than
+        // rejected.
+        #[cfg(feature = "unicode")]
+        if self.config.normalization == Normalization::Strict
+            && (token.kind == TokenKind::Ident
+                || (token.kind == TokenKind::Annotation && !token.text.starts_with("(\"")))
+            && !unicode_normalization::is_nfc(token.text)
+        {
+            return Err(ParseError::new(
+                ParseErrorKind::Unnormalized(token.text.to_owned()),
+                token.span,
+            ));
+        }
+        self.peeked = Some(token);
+        Ok(token)
+    }
+
+    pub(super) fn next(&mut self) -> Result<Token<'src>, ParseError> {
+        let token = self.peek()?;
+        self.peeked = None;
+        self.last_end = token.span.end;
+        Ok(token)
+    }
+
+    /// Skip a comma after an entry, a list item, or a map entry, which is
+    /// only read with [`ParserConfig::commas`].
+    pub(super) fn comma(&mut self) -> Result<(), ParseError> {
+        if self.peek()?.kind == TokenKind::Comma {
+            self.next()?;
+        }
+        Ok(())
+    }
+
+    /// Skip newlines, and semicolons if `semicolons` is set.
+    pub(super) fn skip_newlines(&mut self, semicolons: bool) -> Result<(), ParseError> {
+        while matches!(self.peek()?.kind, TokenKind::Newline)
+            || (semicolons && self.peek()?.kind == TokenKind::Semicolon)
+        {
+            self.next()?;
+        }
+        Ok(())
+    }
+
+    /// Parse a document, up to a separator or the end of the source.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            name = "parse",
+            skip_all,
+            fields(source = self.source.as_deref(), nodes),
+            err(Display),
+        )
+    )]
+    fn document(&mut self) -> Result<DocumentBuf, ParseError> {
+        self.anchors.clear();
+        self.value_anchors.clear();
+        self.expanded = 0;
+        let mut document = DocumentBuf::new();
+        document.set_version(self.directives()?);
+        *document.nodes_mut() = self.nodes(false)?;
+        document.set_source(self.source.clone());
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("nodes", document.nodes_ref().len());
+        Ok(document)
+    }
+
+    /// Parse the directives at the start of a document, returning the
+    /// version.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    pub(super) fn directives(&mut self) -> Result<Option<String>, ParseError> {
+        let mut version = None;
+        loop {
+            self.skip_newlines(true)?;
+            let token = self.peek()?;
+            if token.kind != TokenKind::Directive {
+                return Ok(version);
+            }
+            self.next()?;
+            self.lexer.take_comments();
+            let name = &token.text[1..];
+            match name {
+                "version" if version.is_some() => {
+                    return Err(ParseError::new(
+                        ParseErrorKind::DuplicateDirective(name.to_owned()),
+                        token.span,
+                    ));
+                }
+                "version" => {
+                    let value = self.next()?;
+                    if value.kind != TokenKind::String {
+                        return Err(unexpected("a version string", &value));
+                    }
+                    version = Some(string(&value)?);
+                    self.terminator()?;
+                }
+                _ => {
+                    return Err(ParseError::new(
+                        ParseErrorKind::UnknownDirective(name.to_owned()),
+                        token.span,
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Parse nodes up to the end of a document, or the closing brace of a
+    /// block if `nested`, which is left to the caller.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", skip_all, fields(nested = nested))
+    )]
+    pub(super) fn nodes(&mut self, nested: bool) -> Result<Vec<NodeBuf>, ParseError> {
+        let mut nodes = Vec::new();
+        loop {
+            self.skip_newlines(true)?;
+            let token = self.peek()?;
+            match token.kind {
+                // Comments after the last node are not attached to any node.
+                TokenKind::RightBrace if nested => {
+                    self.lexer.take_comments();
+                    break;
+                }
+                TokenKind::End | TokenKind::Separator if !nested => {
+                    self.lexer.take_comments();
+                    break;
+                }
+                TokenKind::End | TokenKind::Separator => return Err(unexpected("`}`", &token)),
+                TokenKind::Directive => {
+                    return Err(ParseError::new(
+                        ParseErrorKind::MisplacedDirective(token.text[1..].to_owned()),
+                        token.span,
+                    ));
+                }
+                _ => {
+                    let node = self.node()?;
+                    match self.node_index(&nodes, &node, &token)? {
+                        Some(index) if index == nodes.len() => nodes.push(node),
+                        Some(index) => nodes[index] = node,
+                        None => {}
+                    }
+                }
+            }
+        }
+        Ok(nodes)
+    }
+
+    /// Find the index among its earlier siblings that a node goes at, as set
+    /// by the `duplicate_nodes` option of the [`ParserConfig`]. This is the
+    /// number of siblings to add it after them, or `None` to drop it.
+    pub(super) fn node_index<N: Node>(
+        &self,
+        nodes: &[N],
+        node: &N,
+        token: &Token<'_>,
+    ) -> Result<Option<usize>, ParseError> {
+        let policy = self.config.duplicate_nodes;
+        let index = match policy {
+            DuplicatePolicy::CollectAll => None,
+            _ => nodes
+                .iter()
+                .position(|other| other.is_named(node.namespace(), node.name())),
+        };
+        match (index, policy) {
+            (None, _) | (_, DuplicatePolicy::CollectAll) => Ok(Some(nodes.len())),
+            (Some(_), DuplicatePolicy::Error) => {
+                let name = match node.namespace() {
+                    Some(namespace) => format!("{namespace}:{}", node.name()),
+                    None => node.name().to_owned(),
+                };
+                let kind = ParseErrorKind::DuplicateNode(name);
+                Err(ParseError::new(kind, token.span))
+            }
+            (Some(_), DuplicatePolicy::FirstWins) => Ok(None),
+            (Some(index), DuplicatePolicy::LastWins) => Ok(Some(index)),
+        }
+    }
+
+    fn node(&mut self) -> Result<NodeBuf, ParseError> {
+        let leading = self.lexer.take_comments();
+        let token = self.next()?;
+        let mut node = self.node_name(&token)?;
+        if self.entries(&mut node)? {
+            let brace = self.next()?;
+            // Drop the comments in the header and after the brace, rather
+            // than attach them to the first child.
+            self.peek()?;
+            self.lexer.take_comments();
+            let children = self.nested(brace.span, |parser| parser.nodes(true))?;
+            node.children_mut().extend(children);
+            self.next()?;
+            self.terminator()?;
+        }
+        self.finish_node(&mut node, &token, &leading)?;
+        Ok(node)
+    }
+
+    /// Start a node from the token that names it, or the type annotation
+    /// before its name.
+    pub(super) fn node_name(&mut self, token: &Token<'src>) -> Result<NodeBuf, ParseError> {
+        match token.kind {
+            TokenKind::Annotation => {
+                let name = self.next()?;
+                if name.kind == TokenKind::Annotation {
+                    return Err(unexpected("node name", &name));
+                }
+                let mut node = self.node_name(&name)?;
+                let annotation = self.intern(&annotation(token)?);
+                if !self.config.paren_namespaces {
+                    node.set_type_annotation(Some(annotation));
+                } else if node.namespace().is_none() {
+                    node.set_namespace(Some(annotation));
+                } else {
+                    let kind = ParseErrorKind::InvalidName(format!("{}{}", token.text, name.text));
+                    return Err(ParseError::new(kind, token.span));
+                }
+                Ok(node)
+            }
+            TokenKind::Ident if self.sigil(token, '*').is_some() => self.reference(token),
+            TokenKind::Ident => {
+                let (namespace, name) = split_name(token)?;
+                let mut node = NodeBuf::new(self.intern(name));
+                node.set_namespace(namespace.map(|namespace| self.intern(namespace)));
+                Ok(node)
+            }
+            TokenKind::String => Ok(NodeBuf::new(self.intern(&string(token)?))),
+            _ => Err(unexpected("node name", token)),
+        }
+    }
+
+    /// Parse the arguments and parameters of a node, returning whether they
+    /// are followed by a block of children, whose brace is left to the
+    /// caller.
+    pub(super) fn entries(&mut self, node: &mut NodeBuf) -> Result<bool, ParseError> {
+        node.params_mut()
+            .set_policy(self.config.duplicate_params.into());
+        loop {
+            let entry = self.peek()?;
+            match entry.kind {
+                TokenKind::Newline
+                | TokenKind::Semicolon
+                | TokenKind::RightBrace
+                | TokenKind::Separator
+                | TokenKind::End => return Ok(false),
+                TokenKind::LeftBrace => return Ok(true),
+                TokenKind::Ident | TokenKind::String => {
+                    self.next()?;
+                    if self.peek()?.kind == TokenKind::Equals {
+                        self.next()?;
+                        let key = match entry.kind {
+                            TokenKind::String => self.intern(&string(&entry)?),
+                            _ => self.intern(entry.text),
+                        };
+                        let (value, span) = self.spanned_value(None)?;
+                        // Parameters written after a reference replace those
+                        // of the copy.
+                        let policy = match node.reference() {
+                            Some(_) => DuplicatePolicy::LastWins,
+                            None => self.config.duplicate_params,
+                        };
+                        if node.params_ref().contains_key(&key) {
+                            match policy {
+                                DuplicatePolicy::CollectAll => {
+                                    let kind = WarningKind::DuplicateParam(String::from(&*key));
+                                    self.warn(kind, entry.span);
+                                    node.params_mut().push(key.clone(), value);
+                                }
+                                DuplicatePolicy::Error => {
+                                    let kind = ParseErrorKind::DuplicateParam(String::from(&*key));
+                                    return Err(ParseError::new(kind, entry.span));
+                                }
+                                DuplicatePolicy::FirstWins => {
+                                    self.comma()?;
+                                    continue;
+                                }
+                                DuplicatePolicy::LastWins => {
+                                    node.params_mut().insert(key.clone(), value);
+                                    node.param_spans_mut().retain(|(other, _)| *other != key);
+                                }
+                            }
+                        } else {
+                            node.params_mut().push(key.clone(), value);
+                        }
+                        node.param_spans_mut().push((key, span));
+                    } else if let Some(anchor) = self.sigil(&entry, '&') {
+                        node.set_anchor(Some(anchor));
+                    } else {
+                        let (value, span) = self.spanned_value(Some(entry))?;
+                        node.arg_spans_mut().push(span);
+                        node.args_mut().push(value);
+                    }
+                }
+                _ => {
+                    let (value, span) = self.spanned_value(None)?;
+                    node.arg_spans_mut().push(span);
+                    node.args_mut().push(value);
+                }
+            }
+            self.comma()?;
+        }
+    }
+
+    /// Record the anchor, span, source, and comments of a node that was
+    /// named by `token`, once it has been parsed up to its end.
+    pub(super) fn finish_node(
+        &mut self,
+        node: &mut NodeBuf,
+        token: &Token<'src>,
+        leading: &[Comment<'src>],
+    ) -> Result<(), ParseError> {
+        if let Some(anchor) = node.anchor() {
+            if self.anchors.contains_key(anchor) {
+                let kind = ParseErrorKind::DuplicateAnchor(anchor.to_owned());
+                return Err(ParseError::new(kind, token.span));
+            }
+            self.anchors.insert(anchor.to_owned(), node.clone());
+        }
+        node.set_span(Some(Span::new(token.span.start, self.last_end)));
+        node.set_source(self.source.clone());
+        node.leading_comments_mut().extend(
+            leading
+                .iter()
+                .flat_map(Comment::lines)
+                .map(ToOwned::to_owned),
+        );
+        // Only comments after the end of the node, on the same line, are its
+        // own. Comments within the node are dropped.
+        let trailing: Vec<_> = self
+            .lexer
+            .take_comments()
+            .iter()
+            .filter(|comment| comment.span.start.offset >= self.last_end.offset)
+            .filter(|comment| comment.span.start.line == self.last_end.line)
+            .flat_map(Comment::lines)
+            .collect();
+        if !trailing.is_empty() {
+            node.set_trailing_comment(Some(trailing.join(" ")));
+        }
+        Ok(())
+    }
+
+    /// Expect the end of a node, which is left to the caller.
+    pub(super) fn terminator(&mut self) -> Result<(), ParseError> {
+        let token = self.peek()?;
+        match token.kind {
+            TokenKind::Newline
+            | TokenKind::Semicolon
+            | TokenKind::RightBrace
+            | TokenKind::Separator
+            | TokenKind::End => Ok(()),
+            _ => Err(unexpected("end of node", &token)),
+        }
+    }
+
+    /// Copy the node anchored by a `*name` reference.
+    fn reference(&mut self, token: &Token<'src>) -> Result<NodeBuf, ParseError> {
+        let name = &token.text[1..];
+        let node = self.anchors.get(name).ok_or_else(|| {
+            ParseError::new(ParseErrorKind::UnknownAnchor(name.to_owned()), token.span)
+        })?;
+        let size = expansion(Item::Node(node), self.remaining());
+        self.expand(size, token.span)?;
+        let mut node = self.anchors[name].clone();
+        node.set_anchor(None::<String>);
+        node.set_reference(Some(name));
+        node.leading_comments_mut().clear();
+        node.set_trailing_comment(None::<String>);
+        Ok(node)
+    }
+
+    /// How many more nodes and values references may copy, plus one, so that
+    /// counting a copy can stop as soon as it is too large.
+    fn remaining(&self) -> usize {
+        self.config.max_expansion.saturating_sub(self.expanded) + 1
+    }
+
+    /// Count `size` nodes and values copied by the reference at `span`,
+    /// failing if that is more than [`ParserConfig::max_expansion`].
+    fn expand(&mut self, size: usize, span: Span) -> Result<(), ParseError> {
+        self.expanded += size;
+        if self.expanded > self.config.max_expansion {
+            let kind = ParseErrorKind::TooLarge(self.config.max_expansion);
+            return Err(ParseError::new(kind, span));
+        }
+        Ok(())
+    }
+
+    /// Parse the contents of a block, list, or map opened at `span`, failing
+    /// if that nests deeper than [`ParserConfig::max_depth`].
+    pub(super) fn nested<T, F>(&mut self, span: Span, parse: F) -> Result<T, ParseError>
+    where
+        F: FnOnce(&mut Self) -> Result<T, ParseError>,
+    {
+        if self.depth >= self.config.max_depth {
+            let kind = ParseErrorKind::TooDeep(self.config.max_depth);
+            return Err(ParseError::new(kind, span));
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    /// Normalize `text` as set by the `normalization` option of the
+    /// [`ParserConfig`], which needs the `unicode` feature.
+    #[cfg_attr(not(feature = "unicode"), allow(clippy::unused_self))]
+    pub(super) fn normalize<'text>(&self, text: Cow<'text, str>) -> Cow<'text, str> {
+        #[cfg(feature = "unicode")]
+        if self.config.normalization != Normalization::Preserve
+            && !unicode_normalization::is_nfc(&text)
+        {
+            return Cow::Owned(unicode_normalization::UnicodeNormalization::nfc(&*text).collect());
+        }
+        text
+    }
+
+    /// Normalize and intern the text of a name or a key.
+    fn intern(&mut self, text: &str) -> Arc<str> {
+        let text = self.normalize(Cow::Borrowed(text));
+        self.interner.intern(&text)
+    }
+
+    fn warn(&mut self, kind: WarningKind, span: Span) {
+        let warning = |kind| Warning {
+            path: NodePath::new(),
+            span: Some(span),
+            kind,
+        };
+        push_limited(
+            &mut self.warnings,
+            self.config.max_warnings,
+            || warning(kind),
+            || warning(WarningKind::TooManyWarnings),
+        );
+    }
+
+    /// The name after `sigil` if `token` is an anchor or a reference, and
+    /// they are enabled.
+    pub(super) fn sigil<'token>(&self, token: &Token<'token>, sigil: char) -> Option<&'token str> {
+        let name = token.text.strip_prefix(sigil)?;
+        (self.config.anchors && token.kind == TokenKind::Ident && !name.is_empty()).then_some(name)
+    }
+
+    /// Parse a value, with the span of its text.
+    fn spanned_value(
+        &mut self,
+        token: Option<Token<'src>>,
+    ) -> Result<(Value<'static>, Span), ParseError> {
+        let start = match token {
+            Some(token) => token.span.start,
+            None => self.peek()?.span.start,
+        };
+        let value = self.value(token)?;
+        Ok((value, Span::new(start, self.last_end)))
+    }
+
+    /// Parse a value, starting with `token` if it was already taken.
+    pub(super) fn value(
+        &mut self,
+        token: Option<Token<'src>>,
+    ) -> Result<Value<'static>, ParseError> {
+        let token = match token {
+            Some(token) => token,
+            None => self.next()?,
+        };
+        match token.kind {
+            TokenKind::String => Ok(Value::from(
+                self.normalize(string(&token)?.into()).into_owned(),
+            )),
+            TokenKind::Number => {
+                let value = parse_number(token.text, &self.config)
+                    .map_err(|error| ParseError::new(ParseErrorKind::Literal(error), token.span))?;
+                if is_rounded(token.text, self.config) {
+                    let kind = WarningKind::RoundedNumber(token.text.to_owned());
+                    self.warn(kind, token.span);
+                }
+                Ok(value)
+            }
+            TokenKind::Keyword => Ok(Value::F64(match token.text {
+                "#inf" => f64::INFINITY,
+                "#-inf" => f64::NEG_INFINITY,
+                _ => f64::NAN,
+            })),
+            TokenKind::Ident if self.sigil(&token, '*').is_some() => {
+                let name = &token.text[1..];
+                let value = self.value_anchors.get(name).ok_or_else(|| {
+                    ParseError::new(ParseErrorKind::UnknownAnchor(name.to_owned()), token.span)
+                })?;
+                let size = expansion(Item::Value(value), self.remaining());
+                self.expand(size, token.span)?;
+                Ok(self.value_anchors[name].clone())
+            }
+            TokenKind::Ident if self.sigil(&token, '&').is_some() => {
+                let name = &token.text[1..];
+                let value = self.nested(token.span, |parser| parser.value(None))?;
+                if self.value_anchors.contains_key(name) {
+                    return Err(ParseError::new(
+                        ParseErrorKind::DuplicateAnchor(name.to_owned()),
+                        token.span,
+                    ));
+                }
+                self.value_anchors.insert(name.to_owned(), value.clone());
+                Ok(value)
+            }
+            TokenKind::Ident => match token.text {
+                "true" => Ok(Value::Bool(true)),
+                "false" => Ok(Value::Bool(false)),
+                "null" => Ok(Value::Null),
+                _ => Err(unexpected("value", &token)),
+            },
+            TokenKind::LeftBracket => self.nested(token.span, Self::list),
+            TokenKind::MapOpen => self.nested(token.span, Self::map),
+            _ => Err(unexpected("value", &token)),
+        }
+    }
+
+    /// Parse the items of a list, after its opening bracket.
+    fn list(&mut self) -> Result<Value<'static>, ParseError> {
+        let mut items = Vec::new();
+        loop {
+            self.skip_newlines(false)?;
+            if self.peek()?.kind == TokenKind::RightBracket {
+                self.next()?;
+                break;
+            }
+            items.push(self.value(None)?);
+            self.comma()?;
+        }
+        Ok(Value::List(Box::new(items)))
+    }
+
+    /// Parse the entries of a map, after its opening `#{`.
+    fn map(&mut self) -> Result<Value<'static>, ParseError> {
+        let mut entries = BTreeMap::new();
+        loop {
+            self.skip_newlines(false)?;
+            let key = self.next()?;
+            let span = key.span;
+            let key = match key.kind {
+                TokenKind::RightBrace => break,
+                TokenKind::Ident => self.normalize(key.text.into()).into_owned(),
+                TokenKind::String => self.normalize(string(&key)?.into()).into_owned(),
+                _ => return Err(unexpected("map key", &key)),
+            };
+            let equals = self.next()?;
+            if equals.kind != TokenKind::Equals {
+                return Err(unexpected("`=`", &equals));
+            }
+            if entries.contains_key(key.as_str()) {
+                self.warn(WarningKind::DuplicateMapKey(key.clone()), span);
+            }
+            entries.insert(key.into(), self.value(None)?);
+            self.comma()?;
+        }
+        Ok(Value::Map(Box::new(entries)))
+    }
+}
+
+/// Split an identifier naming a node into its namespace and name.
+/// A node or a value, counted by [`expansion`].
+enum Item<'node> {
+    Node(&'node NodeBuf),
+    Value(&'node Value<'static>),
+}
+
+/// The number of nodes and values in `item`, including itself, counting no
+/// more than `limit`.
+fn expansion(item: Item<'_>, limit: usize) -> usize {
+    let mut count = 0;
+    let mut stack = Vec::from([item]);
+    while let Some(item) = stack.pop() {
+        count += 1;
+        if count >= limit {
+            break;
+        }
+        match item {
+            Item::Node(node) => {
+                stack.extend(node.args_ref().iter().map(Item::Value));
+                stack.extend(node.params_ref().values().map(Item::Value));
+                stack.extend(node.children_ref().iter().map(Item::Node));
+            }
+            Item::Value(Value::List(items)) => stack.extend(items.iter().map(Item::Value)),
+            Item::Value(Value::Map(entries)) => stack.extend(entries.values().map(Item::Value)),
+            Item::Value(_) => {}
+        }
+    }
+    count
+}
+
+pub(super) fn split_name<'token>(
+    token: &Token<'token>,
+) -> Result<(Option<&'token str>, &'token str), ParseError> {
+    match token.text.split_once(':') {
+        None => Ok((None, token.text)),
+        Some((namespace, name))
+            if !namespace.is_empty() && !name.is_empty() && !name.contains(':') =>
+        {
+            Ok((Some(namespace), name))
+        }
+        Some(_) => Err(ParseError::new(
+            ParseErrorKind::InvalidName(token.text.to_owned()),
+            token.span,
+        )),
+    }
+}
+
+/// The type in a type annotation token, such as `widget` in `(widget)`.
+pub(super) fn annotation<'token>(token: &Token<'token>) -> Result<Cow<'token, str>, ParseError> {
+    let inner = Token {
+        text: &token.text[1..token.text.len() - 1],
+        ..*token
+    };
+    if inner.text.starts_with('"') {
+        string(&inner).map(Cow::Owned)
+    } else {
+        Ok(Cow::Borrowed(inner.text))
+    }
+}
+
+/// An iterator over the documents in a stream, see [`Parser::documents`].
+///
+/// Iteration stops after the first error.
+#[derive(Clone, Debug)]
+pub struct Documents<'src> {
+    parser: Parser<'src>,
+    done: bool,
+}
+
+impl Iterator for Documents<'_> {
+    type Item = Result<DocumentBuf, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let document = match self.parser.document() {
+                Ok(document) => document,
+                Err(error) => {
+                    self.done = true;
+                    return Some(Err(error));
+                }
+            };
+            match self.parser.next() {
+                Ok(token) if token.kind == TokenKind::End => self.done = true,
+                Ok(_) => {}
+                Err(error) => {
+                    self.done = true;
+                    return Some(Err(error));
+                }
+            }
+            if document.has_nodes() || document.version().is_some() {
+                return Some(Ok(document));
+            }
+        }
+        None
+    }
+}
+
+pub(super) fn unexpected(expected: &'static str, token: &Token<'_>) -> ParseError {
+    ParseError::new(
+        ParseErrorKind::Unexpected {
+            expected,
+            found: token.describe(),
+        },
+        token.span,
+    )
+}
+
+pub(super) fn string(token: &Token<'_>) -> Result<String, ParseError> {
+    parse_string(token.text)
+        .map(Into::into)
+        .map_err(|error| ParseError::new(ParseErrorKind::Literal(error), token.span))
+}
+
+/// Parse `source` as a single document.
+///
+/// ```
+/// # use gpnd::{Document, Node, Value};
+/// let document = gpnd::parser::parse("server port=8080 { tls }").unwrap();
+/// let server = document.get_node(0).unwrap();
+/// assert_eq!(server.get_param("port"), Some(Value::I64(8080)));
+/// assert_eq!(server.get_child(0).map(Node::name), Some("tls"));
+/// ```
+///
+/// # Errors
+/// Fails if the source is not valid, see [`Parser`].
+pub fn parse(source: &str) -> Result<DocumentBuf, ParseError> {
+    Parser::new(source).parse()
+}
+
+/// Parse `source` as a single document, with the given options.
+///
+/// # Errors
+/// Fails if the source is not valid, see [`Parser`].
+pub fn parse_with(source: &str, config: ParserConfig) -> Result<DocumentBuf, ParseError> {
+    Parser::new(source).with_config(config).parse()
+}
+
+/// Parse `source` as a stream of documents, see [`Parser::documents`].
+#[must_use]
+pub fn parse_documents(source: &str) -> Documents<'_> {
+    Parser::new(source).documents()
+}
+
+impl FromStr for DocumentBuf {
+    type Err = ParseError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        parse(source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+    use crate::DuplicateParams;
+
+    #[test]
+    fn test_parse_document() {
+        let document = parse(
+            r#"
+            @version "1.0"
+            server "localhost" port=8080 ; log level="debug"
+            /* block */ http:route "/" methods=["GET" "HEAD"] \
+                limits=#{ rate=#inf burst=null } {
+                "quoted name" true
+            }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(document.version(), Some("1.0"));
+        assert_eq!(document.nodes().len(), 3);
+
+        let server = document.get_node(0).unwrap();
+        assert_eq!(server.args(), [Value::from("localhost")]);
+        assert_eq!(server.get_param("port"), Some(Value::I64(8080)));
+
+        let route = document.get_node(2).unwrap();
+        assert!(route.is_named(Some("http"), "route"));
+        assert_eq!(
+            route.get_param("methods"),
+            Some(Value::from(["GET", "HEAD"]))
+        );
+        let limits = route.get_param("limits").unwrap();
+        assert_eq!(limits.to_string(), r#"#{"burst"=null "rate"=#inf}"#);
+        let child = route.get_child(0).unwrap();
+        assert_eq!(child.name(), "quoted name");
+        assert_eq!(child.args(), [Value::Bool(true)]);
+        let span = route.span().unwrap();
+        assert_eq!((span.start.line, span.end.line), (4, 7));
+        let span = route.param_span("methods").unwrap();
+        assert_eq!((span.start.column, span.end.column), (48, 62));
+        assert_eq!(server.arg_span(0).unwrap().len(), 11);
+        assert_eq!(server.arg_span(1), None);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = parse("node {\n  child\n").unwrap_err();
+        assert_eq!(error.to_string(), "3:1: expected `}`, found end of input");
+        let error = parse("node bare").unwrap_err();
+        assert_eq!(error.to_string(), "1:6: expected value, found `bare`");
+        let error = parse("node\n@version \"1\"").unwrap_err();
+        assert_eq!(
+            error.kind(),
+            &ParseErrorKind::MisplacedDirective("version".to_owned())
+        );
+        assert!(parse("a:b:c").is_err());
+        assert!(parse("one\n---\ntwo").is_err());
+    }
+
+    #[test]
+    fn test_parse_type_annotation() {
+        use crate::Node;
+
+        let document =
+            parse("(widget)button \"ok\"\n(\"my type\")http:route { (x)\"a b\" }").unwrap();
+        let nodes = document.nodes_ref();
+        assert_eq!(nodes[0].type_annotation(), Some("widget"));
+        assert_eq!(nodes[0].name(), "button");
+        assert_eq!(nodes[0].span().unwrap().start.column, 1);
+        assert!(nodes[1].is_named(Some("http"), "route"));
+        assert_eq!(nodes[1].type_annotation(), Some("my type"));
+        assert_eq!(nodes[1].children_ref()[0].type_annotation(), Some("x"));
+        assert_ne!(document, parse("button \"ok\"").unwrap());
+
+        let error = parse("(a)(b)c").unwrap_err();
+        assert_eq!(error.to_string(), "1:4: expected node name, found `(b)`");
+        assert!(parse("(a) b").is_ok());
+        assert!(parse("a (b)").is_err());
+        assert!(parse("(a b)c").is_err());
+    }
+
+    #[test]
+    fn test_parse_namespaces() {
+        use crate::emit::to_string;
+        use crate::Node;
+
+        let document = parse("plugin:widget 1 { core:slot; slot }").unwrap();
+        let widget = &document.nodes_ref()[0];
+        assert_eq!(widget.namespace(), Some("plugin"));
+        assert_eq!(widget.name(), "widget");
+        assert!(widget.children_ref()[0].is_named(Some("core"), "slot"));
+        assert!(widget.children_ref()[1].is_named(None, "slot"));
+        assert_eq!(parse(&to_string(&document)).unwrap(), document);
+
+        let config = ParserConfig {
+            paren_namespaces: true,
+            ..ParserConfig::default()
+        };
+        let parens = parse_with("(plugin)widget 1 { (core)slot; slot }", config).unwrap();
+        assert_eq!(parens, document);
+        assert_eq!(parens.nodes_ref()[0].type_annotation(), None);
+        assert_eq!(
+            to_string(&parens),
+            "plugin:widget 1 {\n    core:slot\n    slot\n}\n"
+        );
+        let error = parse_with("(a)b:c", config).unwrap_err();
+        assert_eq!(
+            error.kind(),
+            &ParseErrorKind::InvalidName("(a)b:c".to_owned())
+        );
+
+        #[cfg(feature = "arena")]
+        {
+            let arena = bumpalo::Bump::new();
+            let source = "(plugin)widget 1 { (core)slot; slot }";
+            let arena_document = Parser::new(source)
+                .with_config(config)
+                .parse_in(&arena)
+                .unwrap();
+            assert!(crate::diff::diff(&arena_document, &document).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_parse_documents() {
+        let source = "---\n@version \"1\"\none\n---\n// empty\n---\ntwo; three\n---\n";
+        let documents = parse_documents(source)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(documents.len(), 2);
+        assert_eq!(documents[0].version(), Some("1"));
+        assert_eq!(documents[1].nodes().len(), 2);
+
+        let mut documents = parse_documents("one\n---\ntwo {\n---\nthree");
+        assert!(documents.next().unwrap().is_ok());
+        assert!(documents.next().unwrap().is_err());
+        assert!(documents.next().is_none());
+    }
+
+    #[test]
+    fn test_parse_interned() {
+        let shared = |lhs: &str, rhs: &str| core::ptr::eq(lhs.as_ptr(), rhs.as_ptr());
+        let document = parse("http:route port=1\nhttp:route \"port\"=2 { route }").unwrap();
+        let [first, second] = [0, 1].map(|index| document.get_node(index).unwrap());
+        assert!(shared(first.name(), second.name()));
+        assert!(shared(first.name(), second.get_child(0).unwrap().name()));
+        assert!(shared(
+            first.namespace().unwrap(),
+            second.namespace().unwrap()
+        ));
+        assert!(shared(first.params()[0].0, second.params()[0].0));
+
+        let mut interner = Interner::new();
+        let first = Parser::new("a x=1").parse_interned(&mut interner).unwrap();
+        let second = Parser::new("a; b").parse_interned(&mut interner).unwrap();
+        assert!(shared(
+            first.get_node(0).unwrap().name(),
+            second.get_node(0).unwrap().name()
+        ));
+        assert_eq!(interner.len(), 3);
+        assert!(interner.contains("x"));
+    }
+
+    #[test]
+    fn test_parse_anchors() {
+        use crate::Node;
+
+        let config = ParserConfig {
+            anchors: true,
+            ..ParserConfig::default()
+        };
+        let source = concat!(
+            "server &defaults \"main\" port=80 { log level=&level \"info\" }\n",
+            "*defaults port=8080 { tls }\n",
+            "client level=*level list=[&one 1 *one]\n",
+        );
+        let document = parse_with(source, config).unwrap();
+        assert_eq!(
+            document,
+            parse(concat!(
+                "server \"main\" port=80 { log level=\"info\" }\n",
+                "server \"main\" port=8080 { log level=\"info\"; tls }\n",
+                "client level=\"info\" list=[1 1]\n",
+            ))
+            .unwrap()
+        );
+        let nodes = document.nodes_ref();
+        assert_eq!(nodes[0].anchor(), Some("defaults"));
+        assert_eq!(nodes[1].anchor(), None);
+        assert_eq!(nodes[1].reference(), Some("defaults"));
+        assert_eq!(nodes[1].span().unwrap().start.line, 2);
+
+        let error = parse_with("*a\na &a", config).unwrap_err();
+        assert_eq!(error.to_string(), "1:1: no anchor `&a` before `*a`");
+        let error = parse_with("a &a\nb &a", config).unwrap_err();
+        assert_eq!(
+            error.kind(),
+            &ParseErrorKind::DuplicateAnchor("a".to_owned())
+        );
+        let error = parse_with("a { b x=*a }; c &a", config).unwrap_err();
+        assert_eq!(error.kind(), &ParseErrorKind::UnknownAnchor("a".to_owned()));
+        let documents: Result<Vec<_>, _> = Parser::new("a &a\n---\nb { *a }")
+            .with_config(config)
+            .documents()
+            .collect();
+        assert!(documents.is_err());
+        assert!(parse("*a").is_ok());
+    }
+
+    #[test]
+    fn test_parse_commas() {
+        let config = ParserConfig {
+            commas: true,
+            ..ParserConfig::default()
+        };
+        let source = concat!(
+            "server \"main\", port=80, hosts=[\"a\", \"b\",], { tls on=true, }\n",
+            "limits #{ rate=1, burst=2, } [\n    1,\n    2,\n],\n",
+        );
+        assert_eq!(
+            parse_with(source, config).unwrap(),
+            parse(concat!(
+                "server \"main\" port=80 hosts=[\"a\" \"b\"] { tls on=true }\n",
+                "limits #{ rate=1 burst=2 } [1 2]\n",
+            ))
+            .unwrap()
+        );
+
+        let error = parse("a 1, 2").unwrap_err();
+        assert_eq!(error.kind(), &ParseErrorKind::UnexpectedChar(','));
+        assert_eq!(error.to_string(), "1:4: unexpected character `,`");
+        assert!(parse_with("a 1,, 2", config).is_err());
+        assert!(parse_with("a [, 1]", config).is_err());
+        assert!(parse_with("a, 1", config).is_err());
+    }
+
+    #[cfg(feature = "unicode")]
+    #[test]
+    fn test_parse_normalization() {
+        let decomposed =
+            "(ty\u{301}pe)cafe\u{301}:name\u{301} \"e\u{301}\" ke\u{301}y=#{ \"e\u{301}\"=1 }";
+        let composed = "(t\u{fd}pe)caf\u{e9}:nam\u{e9} \"\u{e9}\" k\u{e9}y=#{ \"\u{e9}\"=1 }";
+        let nfc = ParserConfig {
+            normalization: Normalization::Nfc,
+            ..ParserConfig::default()
+        };
+        assert_eq!(
+            parse_with(decomposed, nfc).unwrap(),
+            parse(composed).unwrap()
+        );
+        assert_ne!(parse(decomposed).unwrap(), parse(composed).unwrap());
+
+        let strict = ParserConfig {
+            normalization: Normalization::Strict,
+            ..ParserConfig::default()
+        };
+        let error = parse_with("cafe\u{301} 1", strict).unwrap_err();
+        assert_eq!(
+            error.kind(),
+            &ParseErrorKind::Unnormalized("cafe\u{301}".into())
+        );
+        assert_eq!(error.span().to_string(), "1:1");
+        assert!(parse_with("a k\u{e9}y=1", strict).is_ok());
+        assert_eq!(
+            parse_with("\"cafe\u{301}\" \"e\u{301}\"", strict).unwrap(),
+            parse("\"caf\u{e9}\" \"\u{e9}\"").unwrap()
+        );
+
+        #[cfg(feature = "arena")]
+        {
+            let arena = bumpalo::Bump::new();
+            let document = Parser::new(decomposed)
+                .with_config(nfc)
+                .parse_in(&arena)
+                .unwrap();
+            assert!(crate::diff::diff(&document, &parse(composed).unwrap()).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_parse_duplicates() {
+        let source = "a x=1 y=2 x=3\nb 1\nns:a\na 2 { c; c 1 }\n";
+        let parse_policy = |policy| {
+            let config = ParserConfig {
+                duplicate_params: policy,
+                duplicate_nodes: policy,
+                ..ParserConfig::default()
+            };
+            parse_with(source, config)
+        };
+        assert_eq!(parse_policy(DuplicatePolicy::CollectAll), parse(source));
+        assert_eq!(
+            parse_policy(DuplicatePolicy::FirstWins).unwrap(),
+            parse("a x=1 y=2\nb 1\nns:a\n").unwrap()
+        );
+        assert_eq!(
+            parse_policy(DuplicatePolicy::LastWins).unwrap(),
+            parse("a 2 { c 1 }\nb 1\nns:a\n").unwrap()
+        );
+        let error = parse_policy(DuplicatePolicy::Error).unwrap_err();
+        assert_eq!(error.to_string(), "1:11: duplicate parameter `x`");
+        let config = ParserConfig {
+            duplicate_nodes: DuplicatePolicy::Error,
+            ..ParserConfig::default()
+        };
+        let error = parse_with("ns:a\nb\nns:a 1", config).unwrap_err();
+        assert_eq!(
+            error.kind(),
+            &ParseErrorKind::DuplicateNode("ns:a".to_owned())
+        );
+        assert_eq!(error.span().to_string(), "3:1");
+
+        let document = parse_with("a x=1 x=2", config).unwrap();
+        let node = document.get_node(0).unwrap();
+        assert_eq!(node.params_all().len(), 2);
+
+        for (policy, params, column) in [
+            (DuplicatePolicy::FirstWins, DuplicateParams::FirstWins, 5),
+            (DuplicatePolicy::LastWins, DuplicateParams::LastWins, 9),
+        ] {
+            let config = ParserConfig {
+                duplicate_params: policy,
+                ..ParserConfig::default()
+            };
+            let mut document = parse_with("a x=1 x=22", config).unwrap();
+            let node = &mut document.nodes_mut()[0];
+            assert_eq!(node.params_ref().policy(), params);
+            assert_eq!(node.param_span("x").unwrap().start.column, column);
+            assert_eq!(node.param_spans_mut().len(), 1);
+        }
+
+        #[cfg(feature = "arena")]
+        for policy in [DuplicatePolicy::FirstWins, DuplicatePolicy::LastWins] {
+            let arena = bumpalo::Bump::new();
+            let config = ParserConfig {
+                duplicate_params: policy,
+                duplicate_nodes: policy,
+                ..ParserConfig::default()
+            };
+            let document = Parser::new(source)
+                .with_config(config)
+                .parse_in(&arena)
+                .unwrap();
+            let expected = parse_policy(policy).unwrap();
+            assert!(crate::diff::diff(&document, &expected).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_parse_max_expansion() {
+        let config = ParserConfig {
+            anchors: true,
+            ..ParserConfig::default()
+        };
+        let chain = |levels: usize| {
+            let links = (1..=levels).map(|level| {
+                let last = level - 1;
+                format!("n{level} &a{level} {{ *a{last}; *a{last} }}\n")
+            });
+            String::from("n0 &a0\n") + &links.collect::<String>()
+        };
+        assert!(parse_with(&chain(10), config).is_ok());
+        let error = parse_with(&chain(18), config).unwrap_err();
+        assert_eq!(error.kind(), &ParseErrorKind::TooLarge(65_536));
+        assert_eq!(error.span().start.line, 16);
+        let error = parse_with(&chain(100), config).unwrap_err();
+        assert_eq!(error.kind(), &ParseErrorKind::TooLarge(65_536));
+
+        let links = (1..40).map(|level| {
+            let last = level - 1;
+            format!(" v{level}=&v{level} [*v{last} *v{last}]")
+        });
+        let source = String::from("a v0=&v0 [1 1]") + &links.collect::<String>();
+        let error = parse_with(&source, config).unwrap_err();
+        assert_eq!(error.kind(), &ParseErrorKind::TooLarge(65_536));
+
+        let config = ParserConfig {
+            max_expansion: 3,
+            ..config
+        };
+        assert!(parse_with("a &a { b }; *a; c x=&x 1 y=*x", config).is_ok());
+        let error = parse_with("a &a { b }; *a; *a", config).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "1:17: references expand to more than 3 nodes and values"
+        );
+        let documents: Result<Vec<_>, _> = Parser::new("a &a { b }; *a\n---\na &a { b }; *a")
+            .with_config(config)
+            .documents()
+            .collect();
+        assert_eq!(documents.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_parse_max_depth() {
+        let nested = |depth: usize| "a {".repeat(depth) + &"}".repeat(depth);
+        assert!(parse(&nested(128)).is_ok());
+        let error = parse(&nested(100_000)).unwrap_err();
+        assert_eq!(error.kind(), &ParseErrorKind::TooDeep(128));
+        assert_eq!(error.span().to_string(), "1:387");
+
+        let error = parse(&format!("a {}", "[".repeat(100_000))).unwrap_err();
+        assert_eq!(error.kind(), &ParseErrorKind::TooDeep(128));
+        let error = parse(&format!("a k={}", "#{ k=".repeat(100_000))).unwrap_err();
+        assert_eq!(error.kind(), &ParseErrorKind::TooDeep(128));
+
+        let config = ParserConfig {
+            max_depth: 2,
+            ..ParserConfig::default()
+        };
+        assert!(parse_with("a { b [1] { c } }", config).is_ok());
+        let error = parse_with("a { b { c { d } } }", config).unwrap_err();
+        assert_eq!(error.to_string(), "1:11: nesting deeper than 2 levels");
+        assert!(parse_with("a [[[1]]]", config).is_err());
+    }
+
+    #[test]
+    fn test_parse_comments() {
+        let source = "// One\n/*\n * Two\n */\na 1 /* inside */ { // open\n    b // after b\n    \
+                      // dangling\n}\nc; d // after d\n";
+        let document = parse(source).unwrap();
+        let nodes = document.nodes();
+        assert_eq!(nodes[0].leading_comments(), ["One", "* Two"]);
+        assert_eq!(nodes[0].trailing_comment(), None);
+        assert!(nodes[0].children()[0].leading_comments().is_empty());
+        assert_eq!(nodes[0].children()[0].trailing_comment(), Some("after b"));
+        assert_eq!(nodes[1].trailing_comment(), None);
+        assert_eq!(nodes[2].trailing_comment(), Some("after d"));
+    }
+
+    #[test]
+    fn test_parse_warnings() {
+        let config = ParserConfig {
+            unit_suffixes: super::super::UnitSuffixes::Normalize,
+            ..ParserConfig::default()
+        };
+        let output = Parser::new("a x=1 x=2 t=1.5ns s=1.5kb m=#{ k=1 k=2 }\nb x=1")
+            .with_config(config)
+            .parse_with_warnings()
+            .unwrap();
+        assert_eq!(
+            output.document.get_node(0).unwrap().get_param("x"),
+            Some(Value::I64(2))
+        );
+        let warnings: Vec<_> = output.warnings.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            warnings,
+            [
+                "1:7: duplicate parameter `x`",
+                "1:13: `1.5ns` was rounded",
+                "1:36: duplicate map key `k`",
+            ]
+        );
+        assert!(Parser::new("a x=1 y=#{ x=1 }")
+            .parse_with_warnings()
+            .unwrap()
+            .warnings
+            .is_empty());
+
+        let config = ParserConfig {
+            max_warnings: 2,
+            ..ParserConfig::default()
+        };
+        let output = Parser::new("a x=1 x=2 x=3 x=4 x=5")
+            .with_config(config)
+            .parse_with_warnings()
+            .unwrap();
+        let warnings: Vec<_> = output.warnings.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            warnings,
+            [
+                "1:7: duplicate parameter `x`",
+                "1:11: duplicate parameter `x`",
+                "1:15: too many warnings, stopping",
+            ]
+        );
+        assert_eq!(output.document.get_node(0).unwrap().params_all().len(), 5);
+    }
+}
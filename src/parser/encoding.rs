@@ -0,0 +1,179 @@
+//! Parsing sources in encodings other than UTF-8, with [`encoding_rs`].
+//!
+//! A source is transcoded to UTF-8 before it is lexed. The encoding is named
+//! by a [WHATWG label](https://encoding.spec.whatwg.org/#names-and-labels),
+//! such as `"latin1"`, `"shift_jis"`, or `"utf-16le"`, or sniffed from the
+//! source when no label is given:
+//!
+//! 1. A byte order mark names UTF-8, UTF-16LE, or UTF-16BE.
+//! 2. A source whose first or second byte is zero is UTF-16, big-endian or
+//!    little-endian respectively, since documents start with ASCII.
+//! 3. A source that is valid UTF-8 is UTF-8.
+//! 4. Anything else is windows-1252, the superset of Latin-1 that the web reads
+//!    Latin-1 as.
+
+use alloc::borrow::{Cow, ToOwned};
+use alloc::string::String;
+use core::error::Error;
+use core::fmt;
+
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252};
+
+use super::document::Parser;
+use super::error::ParseError;
+use crate::DocumentBuf;
+
+/// An error from [`decode`] or [`parse_bytes`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EncodingError {
+    /// A label that does not name an encoding.
+    UnknownLabel(String),
+    /// A source that is not valid in the named encoding.
+    Malformed(&'static str),
+    Parse(ParseError),
+}
+
+impl fmt::Display for EncodingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownLabel(label) => write!(f, "unknown encoding `{label}`"),
+            Self::Malformed(encoding) => write!(f, "source is not valid {encoding}"),
+            Self::Parse(error) => error.fmt(f),
+        }
+    }
+}
+
+impl Error for EncodingError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Parse(error) => Some(error),
+            Self::UnknownLabel(_) | Self::Malformed(_) => None,
+        }
+    }
+}
+
+impl From<ParseError> for EncodingError {
+    fn from(error: ParseError) -> Self {
+        Self::Parse(error)
+    }
+}
+
+/// Guess the encoding of `bytes`, see the [module](self) documentation,
+/// returning it with the length of its byte order mark.
+#[must_use]
+pub fn sniff(bytes: &[u8]) -> (&'static Encoding, usize) {
+    if let Some(found) = Encoding::for_bom(bytes) {
+        return found;
+    }
+    let encoding = match bytes {
+        [0, _, ..] => UTF_16BE,
+        [_, 0, ..] => UTF_16LE,
+        _ if core::str::from_utf8(bytes).is_ok() => UTF_8,
+        _ => WINDOWS_1252,
+    };
+    (encoding, 0)
+}
+
+/// Transcode `bytes` to UTF-8 from the encoding named by `label`, or the
+/// one that is sniffed from them, borrowing them if they are already UTF-8.
+///
+/// A byte order mark for the encoding is removed.
+///
+/// ```
+/// # use gpnd::parser::decode;
+/// assert_eq!(decode(b"caf\xe9", Some("latin1")).unwrap(), "café");
+/// assert_eq!(decode(b"a\0=\x001\0", None).unwrap(), "a=1");
+/// ```
+///
+/// # Errors
+/// Fails if `label` is not known, or if `bytes` are not valid in the
+/// encoding.
+pub fn decode<'src>(
+    bytes: &'src [u8],
+    label: Option<&str>,
+) -> Result<Cow<'src, str>, EncodingError> {
+    let (encoding, bom) = match label {
+        Some(label) => {
+            let encoding = Encoding::for_label(label.as_bytes())
+                .ok_or_else(|| EncodingError::UnknownLabel(label.to_owned()))?;
+            let bom = match Encoding::for_bom(bytes) {
+                Some((found, length)) if found == encoding => length,
+                _ => 0,
+            };
+            (encoding, bom)
+        }
+        None => sniff(bytes),
+    };
+    encoding
+        .decode_without_bom_handling_and_without_replacement(&bytes[bom..])
+        .ok_or(EncodingError::Malformed(encoding.name()))
+}
+
+/// Parse `bytes` as a single document, after transcoding them to UTF-8
+/// with [`decode`].
+///
+/// # Errors
+/// Fails if the source cannot be transcoded, or is not valid.
+pub fn parse_bytes(bytes: &[u8], label: Option<&str>) -> Result<DocumentBuf, EncodingError> {
+    let source = decode(bytes, label)?;
+    Ok(Parser::new(&source).parse()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use alloc::vec::Vec;
+
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_encoding() {
+        let expected = parse("caf\u{e9} name=\"\u{65e5}\u{672c}\"").unwrap();
+        let utf16 = |little: bool| {
+            let mut bytes = Vec::new();
+            for unit in "\u{feff}caf\u{e9} name=\"\u{65e5}\u{672c}\"".encode_utf16() {
+                if little {
+                    bytes.extend(unit.to_le_bytes());
+                } else {
+                    bytes.extend(unit.to_be_bytes());
+                }
+            }
+            bytes
+        };
+        assert_eq!(parse_bytes(&utf16(true), None).unwrap(), expected);
+        assert_eq!(parse_bytes(&utf16(false), None).unwrap(), expected);
+        assert_eq!(
+            parse_bytes(&utf16(true), Some("utf-16le")).unwrap(),
+            expected
+        );
+        assert_eq!(
+            parse_bytes(&utf16(false)[2..], None).unwrap(),
+            parse_bytes(&utf16(false), None).unwrap()
+        );
+        let shift_jis = b"caf\xe9 name=\"\x93\xfa\x96\x7b\"";
+        assert!(parse_bytes(shift_jis, Some("shift_jis")).is_err());
+        assert_eq!(
+            parse_bytes(b"cafe name=\"\x93\xfa\x96\x7b\"", Some("shift_jis")).unwrap(),
+            parse("cafe name=\"\u{65e5}\u{672c}\"").unwrap()
+        );
+        assert_eq!(decode(b"caf\xe9", None).unwrap(), "caf\u{e9}");
+        assert!(matches!(
+            decode("café".as_bytes(), None),
+            Ok(Cow::Borrowed(_))
+        ));
+
+        assert_eq!(
+            decode(b"a", Some("ebcdic")).unwrap_err().to_string(),
+            "unknown encoding `ebcdic`"
+        );
+        assert_eq!(
+            decode(b"\xff", Some("utf-8")).unwrap_err(),
+            EncodingError::Malformed("UTF-8")
+        );
+        assert!(matches!(
+            parse_bytes(b"a {", None),
+            Err(EncodingError::Parse(_))
+        ));
+    }
+}
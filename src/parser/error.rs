@@ -0,0 +1,129 @@
+use alloc::string::{String, ToString};
+use core::error::Error;
+use core::fmt;
+
+use super::literal::LiteralError;
+use crate::{Snippet, Span};
+
+/// What went wrong while parsing, see [`ParseError`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// A character that cannot start a token.
+    UnexpectedChar(char),
+    /// A token that is valid, but not where it appeared.
+    Unexpected {
+        expected: &'static str,
+        found: String,
+    },
+    UnterminatedString,
+    UnterminatedComment,
+    /// A `#` keyword other than `#inf`, `#-inf`, and `#nan`.
+    UnknownKeyword(String),
+    /// A string or number literal that could not be read.
+    Literal(LiteralError),
+    /// A node name with an empty namespace or name, such as `ns:` or `a:b:c`.
+    InvalidName(String),
+    UnknownDirective(String),
+    DuplicateDirective(String),
+    /// A directive after the first node of a document.
+    MisplacedDirective(String),
+    /// A `*name` reference before any `&name` anchor.
+    UnknownAnchor(String),
+    DuplicateAnchor(String),
+    /// A repeated parameter, with
+    /// [`DuplicatePolicy::Error`](super::DuplicatePolicy::Error).
+    DuplicateParam(String),
+    /// A node with the same name as an earlier sibling, with
+    /// [`DuplicatePolicy::Error`](super::DuplicatePolicy::Error).
+    DuplicateNode(String),
+    /// An identifier that is not in Unicode Normalization Form C, with
+    /// [`Normalization::Strict`](super::Normalization::Strict).
+    #[cfg(feature = "unicode")]
+    Unnormalized(String),
+    /// Nesting deeper than
+    /// [`ParserConfig::max_depth`](super::ParserConfig::max_depth).
+    TooDeep(usize),
+    /// References that copy more than
+    /// [`ParserConfig::max_expansion`](super::ParserConfig::max_expansion)
+    /// nodes and values.
+    TooLarge(usize),
+    /// A [`ParserConfig`](super::ParserConfig) option, named by its field,
+    /// that the parsing method cannot follow.
+    Unsupported(&'static str),
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedChar(ch) => write!(f, "unexpected character `{}`", ch.escape_debug()),
+            Self::Unexpected { expected, found } => write!(f, "expected {expected}, found {found}"),
+            Self::UnterminatedString => f.write_str("unterminated string"),
+            Self::UnterminatedComment => f.write_str("unterminated block comment"),
+            Self::UnknownKeyword(keyword) => write!(f, "unknown keyword `{keyword}`"),
+            Self::Literal(error) => error.fmt(f),
+            Self::InvalidName(name) => write!(f, "invalid node name `{name}`"),
+            Self::UnknownDirective(name) => write!(f, "unknown directive `@{name}`"),
+            Self::DuplicateDirective(name) => write!(f, "duplicate directive `@{name}`"),
+            Self::MisplacedDirective(name) => {
+                write!(f, "directive `@{name}` must come before any nodes")
+            }
+            Self::UnknownAnchor(name) => write!(f, "no anchor `&{name}` before `*{name}`"),
+            Self::DuplicateAnchor(name) => write!(f, "duplicate anchor `&{name}`"),
+            Self::DuplicateParam(key) => write!(f, "duplicate parameter `{key}`"),
+            Self::DuplicateNode(name) => write!(f, "duplicate node `{name}`"),
+            #[cfg(feature = "unicode")]
+            Self::Unnormalized(text) => write!(f, "identifier `{text}` is not in NFC"),
+            Self::TooDeep(depth) => write!(f, "nesting deeper than {depth} levels"),
+            Self::TooLarge(limit) => {
+                write!(f, "references expand to more than {limit} nodes and values")
+            }
+            Self::Unsupported(option) => write!(f, "option `{option}` is not supported here"),
+        }
+    }
+}
+
+/// An error in source text, with the span it was found at.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    kind: ParseErrorKind,
+    span: Span,
+}
+
+impl ParseError {
+    #[must_use]
+    pub fn new(kind: ParseErrorKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+
+    #[must_use]
+    pub fn kind(&self) -> &ParseErrorKind {
+        &self.kind
+    }
+
+    #[must_use]
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Show the error with an excerpt of `source`, which it was found in,
+    /// with the text it was found at underlined.
+    #[must_use]
+    pub fn snippet<'src>(&self, source: &'src str) -> Snippet<'src> {
+        Snippet::new(source, self.span).with_message(self.kind.to_string())
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.span, self.kind)
+    }
+}
+
+impl Error for ParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.kind {
+            ParseErrorKind::Literal(error) => Some(error),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,286 @@
+//! Splitting documents across sources with `include` nodes.
+//!
+//! When a document is parsed by [`Parser::parse_with_includes`], every node
+//! written as `include "path"` is replaced by the nodes of the source that an
+//! [`IncludeResolver`] finds for the path. Included sources can include others
+//! in turn, and every node records the name of the source it came from, see
+//! [`Node::source`].
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::hash::BuildHasher;
+use std::path::Path;
+use std::{fmt, fs, io};
+
+use super::config::ParserConfig;
+use super::document::Parser;
+use super::error::ParseError;
+use crate::{DocumentBuf, Node, NodeBuf, Span, Value};
+
+/// Finds the sources named by `include` nodes.
+pub trait IncludeResolver {
+    /// Find the source for `path`, as written in an `include` node of the
+    /// source named `from`, returning the name of the source and its text.
+    ///
+    /// The name identifies the source, so that cycles can be found, and is
+    /// recorded on the nodes parsed from it.
+    fn resolve(&mut self, path: &str, from: Option<&str>) -> io::Result<(String, String)>;
+}
+
+/// Resolves includes as file paths, relative to the directory of the file
+/// that includes them.
+///
+/// Sources are named by their canonical paths.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct FileResolver;
+
+impl IncludeResolver for FileResolver {
+    fn resolve(&mut self, path: &str, from: Option<&str>) -> io::Result<(String, String)> {
+        let path = match from.and_then(|from| Path::new(from).parent()) {
+            Some(directory) => directory.join(path),
+            None => Path::new(path).to_owned(),
+        };
+        let path = fs::canonicalize(path)?;
+        let text = fs::read_to_string(&path)?;
+        Ok((path.to_string_lossy().into_owned(), text))
+    }
+}
+
+/// Resolves includes by looking up the path, which is also the name of the
+/// source.
+impl<S> IncludeResolver for HashMap<String, String, S>
+where
+    S: BuildHasher,
+{
+    fn resolve(&mut self, path: &str, _from: Option<&str>) -> io::Result<(String, String)> {
+        match self.get(path) {
+            Some(text) => Ok((path.to_owned(), text.clone())),
+            None => Err(io::ErrorKind::NotFound.into()),
+        }
+    }
+}
+
+/// An error from parsing a document with includes.
+#[derive(Debug)]
+pub enum IncludeError {
+    /// A source could not be parsed.
+    Parse {
+        file: Option<String>,
+        error: ParseError,
+    },
+    /// The resolver could not find `path`, included from `file`.
+    Resolve {
+        path: String,
+        file: Option<String>,
+        error: io::Error,
+    },
+    /// An `include` node that does not have exactly one string argument, or
+    /// has parameters or children.
+    Invalid {
+        file: Option<String>,
+        span: Option<Span>,
+    },
+    /// Sources that include each other, from the first to include the next
+    /// to the last, which is the first again.
+    Cycle(Vec<String>),
+}
+
+impl fmt::Display for IncludeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse {
+                file: Some(file),
+                error,
+            } => write!(f, "{file}:{error}"),
+            Self::Parse { file: None, error } => error.fmt(f),
+            Self::Resolve { path, error, .. } => {
+                write!(f, "cannot include `{path}`: {error}")
+            }
+            Self::Invalid { file, span } => {
+                if let Some(file) = file {
+                    write!(f, "{file}:")?;
+                }
+                if let Some(span) = span {
+                    write!(f, "{span}: ")?;
+                }
+                f.write_str("`include` must have a path and nothing else")
+            }
+            Self::Cycle(files) => write!(f, "include cycle: {}", files.join(" -> ")),
+        }
+    }
+}
+
+impl Error for IncludeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Parse { error, .. } => Some(error),
+            Self::Resolve { error, .. } => Some(error),
+            Self::Invalid { .. } | Self::Cycle(_) => None,
+        }
+    }
+}
+
+/// Replace the `include` nodes among `nodes` and their descendants, where
+/// `stack` holds the names of the sources being included.
+fn expand_includes<R>(
+    nodes: Vec<NodeBuf>,
+    from: Option<&str>,
+    config: ParserConfig,
+    resolver: &mut R,
+    stack: &mut Vec<String>,
+) -> Result<Vec<NodeBuf>, IncludeError>
+where
+    R: IncludeResolver + ?Sized,
+{
+    let mut expanded = Vec::with_capacity(nodes.len());
+    for mut node in nodes {
+        if !node.is_named(None, "include") {
+            let children = std::mem::take(node.children_mut());
+            *node.children_mut() = expand_includes(children, from, config, resolver, stack)?;
+            expanded.push(node);
+            continue;
+        }
+        let path = match node.args_ref() {
+            [Value::String(path)] if !node.has_params() && !node.has_children() => path,
+            _ => {
+                return Err(IncludeError::Invalid {
+                    file: from.map(ToOwned::to_owned),
+                    span: node.span(),
+                });
+            }
+        };
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("include", path = path.as_ref(), from).entered();
+        let (name, text) = resolver
+            .resolve(path, from)
+            .map_err(|error| IncludeError::Resolve {
+                path: path.to_string(),
+                file: from.map(ToOwned::to_owned),
+                error,
+            })?;
+        if let Some(start) = stack.iter().position(|other| *other == name) {
+            let mut cycle = stack[start..].to_vec();
+            cycle.push(name);
+            return Err(IncludeError::Cycle(cycle));
+        }
+        let mut included = Parser::new(&text)
+            .with_config(config)
+            .with_source_name(name.as_str())
+            .parse()
+            .map_err(|error| IncludeError::Parse {
+                file: Some(name.clone()),
+                error,
+            })?;
+        stack.push(name);
+        let nodes = std::mem::take(included.nodes_mut());
+        let from = stack.last().cloned();
+        expanded.extend(expand_includes(
+            nodes,
+            from.as_deref(),
+            config,
+            resolver,
+            stack,
+        )?);
+        stack.pop();
+    }
+    Ok(expanded)
+}
+
+impl Parser<'_> {
+    /// Parse the source as a single document, replacing `include` nodes, see
+    /// the [module](self) documentation.
+    ///
+    /// ```
+    /// # use std::collections::HashMap;
+    /// # use gpnd::parser::Parser;
+    /// let mut sources = HashMap::from([("tls".to_owned(), "tls enabled=true".to_owned())]);
+    /// let document = Parser::new("server { include \"tls\" }")
+    ///     .parse_with_includes(&mut sources)
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     document,
+    ///     gpnd::parser::parse("server { tls enabled=true }").unwrap()
+    /// );
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(source = self.source_name()),
+            err(Display),
+        )
+    )]
+    pub fn parse_with_includes<R>(self, resolver: &mut R) -> Result<DocumentBuf, IncludeError>
+    where
+        R: IncludeResolver + ?Sized,
+    {
+        let config = self.config();
+        let name = self.source_name().map(ToOwned::to_owned);
+        let mut document = self.parse().map_err(|error| IncludeError::Parse {
+            file: name.clone(),
+            error,
+        })?;
+        let nodes = std::mem::take(document.nodes_mut());
+        let mut stack: Vec<_> = name.iter().cloned().collect();
+        *document.nodes_mut() =
+            expand_includes(nodes, name.as_deref(), config, resolver, &mut stack)?;
+        Ok(document)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+    use crate::Document;
+
+    #[test]
+    fn test_includes() {
+        let mut sources: HashMap<_, _> = [
+            ("main", "a\ninclude \"b\"\nc { include \"d\" }"),
+            ("b", "b1; include \"d\"; b2"),
+            ("d", "d x=1"),
+            ("cycle", "include \"e\""),
+            ("e", "e { include \"cycle\" }"),
+            ("invalid", "include \"d\" x=1"),
+            ("broken", "a {"),
+        ]
+        .into_iter()
+        .map(|(name, text)| (name.to_owned(), text.to_owned()))
+        .collect();
+
+        let document = Parser::new(&sources["main"].clone())
+            .with_source_name("main")
+            .parse_with_includes(&mut sources)
+            .unwrap();
+        assert_eq!(document, parse("a; b1; d x=1; b2; c { d x=1 }").unwrap());
+        let names: Vec<_> = document
+            .iter_dfs()
+            .map(|(node, _)| node.source().unwrap())
+            .collect();
+        assert_eq!(names, ["main", "b", "d", "b", "main", "d"]);
+
+        let error = |source: &str, sources: &mut HashMap<String, String>| {
+            Parser::new(source)
+                .parse_with_includes(sources)
+                .unwrap_err()
+        };
+        assert_eq!(
+            error("include \"cycle\"", &mut sources).to_string(),
+            "include cycle: cycle -> e -> cycle"
+        );
+        assert!(matches!(
+            error("include \"missing\"", &mut sources),
+            IncludeError::Resolve { path, file: None, .. } if path == "missing"
+        ));
+        assert_eq!(
+            error("include \"invalid\"", &mut sources).to_string(),
+            "invalid:1:1: `include` must have a path and nothing else"
+        );
+        assert!(matches!(
+            error("include \"broken\"", &mut sources),
+            IncludeError::Parse { file: Some(file), .. } if file == "broken"
+        ));
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::VecDeque;
+use alloc::vec::Vec;
 
 pub trait Buffered: Iterator {
     type ItemSlice<'items>
@@ -29,13 +29,34 @@ pub trait Peekable {
     fn look(&mut self, count: usize) -> Option<Self::ItemSlice<'_>>;
 }
 
+/// A source of bytes that is read a block at a time, so that the bytes can
+/// be scanned as slices rather than pulled through an iterator one by one.
+pub trait Chunked {
+    /// The bytes that have been read and not consumed, reading another block
+    /// if there are none. The chunk is only empty at the end of the source.
+    fn chunk(&mut self) -> &[u8];
+
+    /// Consume the first `count` bytes of the chunk.
+    ///
+    /// # Panics
+    /// If `count` is longer than the chunk.
+    fn consume(&mut self, count: usize);
+}
+
+/// The number of bytes that [`SourceBytes`] reads for each chunk.
+pub const BLOCK_SIZE: usize = 4096;
+
 #[derive(Clone, Debug)]
 pub struct SourceBytes<S>
 where
     S: Iterator<Item = u8>,
 {
     iter: S,
-    buffer: VecDeque<S::Item>,
+    /// The items taken from `iter` ahead of time, from `head` onwards. The
+    /// items before `head` have been consumed, and are dropped when the
+    /// buffer needs to grow.
+    buffer: Vec<S::Item>,
+    head: usize,
 }
 
 impl<S> SourceBytes<S>
@@ -48,7 +69,8 @@ where
     {
         Self {
             iter: iter.into_iter(),
-            buffer: VecDeque::new(),
+            buffer: Vec::new(),
+            head: 0,
         }
     }
 }
@@ -60,7 +82,15 @@ where
     type Item = S::Item;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.buffer.pop_front().or_else(|| self.iter.next())
+        let Some(&item) = self.buffer.get(self.head) else {
+            return self.iter.next();
+        };
+        self.head += 1;
+        if self.head == self.buffer.len() {
+            self.buffer.clear();
+            self.head = 0;
+        }
+        Some(item)
     }
 }
 
@@ -68,14 +98,46 @@ impl<S> Buffered for SourceBytes<S>
 where
     S: Iterator<Item = u8>,
 {
-    type ItemSlice<'a> = &'a [u8] where Self: 'a;
+    type ItemSlice<'a>
+        = &'a [u8]
+    where
+        Self: 'a;
 
     fn buffer(&mut self, count: usize) -> Option<Self::ItemSlice<'_>> {
-        if self.buffer.len() < count {
+        let buffered = self.buffer.len() - self.head;
+        if buffered < count {
+            // Only move the buffered items down once at least as many have
+            // been consumed, so that each item is moved once on average.
+            if self.head >= buffered {
+                self.buffer.drain(..self.head);
+                self.head = 0;
+            }
             self.buffer
-                .extend(self.iter.by_ref().take(count - self.buffer.len()));
+                .extend(self.iter.by_ref().take(count - buffered));
+        }
+        self.buffer.get(self.head..self.head + count)
+    }
+}
+
+impl<S> Chunked for SourceBytes<S>
+where
+    S: Iterator<Item = u8>,
+{
+    fn chunk(&mut self) -> &[u8] {
+        if self.head == self.buffer.len() {
+            self.buffer.clear();
+            self.head = 0;
+            self.buffer.extend(self.iter.by_ref().take(BLOCK_SIZE));
         }
-        self.buffer.make_contiguous().get(0..count)
+        &self.buffer[self.head..]
+    }
+
+    fn consume(&mut self, count: usize) {
+        assert!(
+            count <= self.buffer.len() - self.head,
+            "consumed more than the chunk"
+        );
+        self.head += count;
     }
 }
 
@@ -83,8 +145,14 @@ impl<S> Peekable for SourceBytes<S>
 where
     S: Iterator<Item = u8>,
 {
-    type Item<'item> = u8 where Self: 'item;
-    type ItemSlice<'items> = &'items [u8] where Self: 'items;
+    type Item<'item>
+        = u8
+    where
+        Self: 'item;
+    type ItemSlice<'items>
+        = &'items [u8]
+    where
+        Self: 'items;
 
     fn peek(&mut self) -> Option<Self::Item<'_>> {
         self.buffer(1).and_then(|slice| slice.first().copied())
@@ -123,7 +191,7 @@ where
         // A single character can be at most 4 bytes.
         for (i, byte) in self.0.by_ref().take(4).enumerate() {
             buf[i] = byte;
-            if let Ok(slice) = std::str::from_utf8(&buf[..=i]) {
+            if let Ok(slice) = core::str::from_utf8(&buf[..=i]) {
                 return slice.chars().next();
             }
         }
@@ -135,7 +203,10 @@ impl<S> Buffered for SourceChars<&mut S>
 where
     for<'a> S: Iterator<Item = u8> + Buffered<ItemSlice<'a> = &'a [u8]> + 'a,
 {
-    type ItemSlice<'items> = &'items str where Self: 'items;
+    type ItemSlice<'items>
+        = &'items str
+    where
+        Self: 'items;
 
     // Allowed specifically here because the borrow checker is incorrect.
     #[allow(unsafe_code)]
@@ -158,7 +229,7 @@ where
             let buf: *const [u8] = buf;
             let buf: &[u8] = unsafe { &*buf };
 
-            if let Ok(slice) = std::str::from_utf8(buf) {
+            if let Ok(slice) = core::str::from_utf8(buf) {
                 if slice.chars().count() >= count {
                     return Some(slice);
                 }
@@ -173,8 +244,14 @@ where
     for<'a> S: Iterator<Item = u8> + 'a,
     for<'a> Self: Buffered<ItemSlice<'a> = &'a str> + 'a,
 {
-    type Item<'item> = char where Self: 'item;
-    type ItemSlice<'items> = &'items str where Self: 'items;
+    type Item<'item>
+        = char
+    where
+        Self: 'item;
+    type ItemSlice<'items>
+        = &'items str
+    where
+        Self: 'items;
 
     fn peek(&mut self) -> Option<Self::Item<'_>> {
         self.buffer(1).and_then(|slice| slice.chars().next())
@@ -187,7 +264,10 @@ where
 
 #[cfg(test)]
 mod tests {
-    use super::{Buffered, SourceBytes, SourceChars};
+    use alloc::string::String;
+    use alloc::vec;
+
+    use super::{Buffered, Chunked, SourceBytes, SourceChars, BLOCK_SIZE};
 
     #[test]
     fn test_source_chars() {
@@ -196,6 +276,35 @@ mod tests {
         assert_eq!(source, chars.collect::<String>());
     }
 
+    #[test]
+    fn test_source_bytes_buffer() {
+        let source = b"abcdefghij";
+        let mut bytes = SourceBytes::new(source.iter().copied());
+        for (index, byte) in source.iter().enumerate() {
+            assert_eq!(bytes.buffer(2), source.get(index..index + 2));
+            assert_eq!(bytes.buffer(1), Some(&[*byte][..]));
+            assert!(bytes.head < 2, "the consumed bytes should be dropped");
+            assert_eq!(bytes.next(), Some(*byte));
+        }
+        assert_eq!(bytes.buffer(1), None);
+        assert_eq!(bytes.next(), None);
+    }
+
+    #[test]
+    fn test_source_bytes_chunk() {
+        let source = vec![b'a'; BLOCK_SIZE + 10];
+        let mut bytes = SourceBytes::new(source.iter().copied());
+        assert_eq!(bytes.buffer(2), Some(&b"aa"[..]));
+        assert_eq!(bytes.chunk().len(), 2);
+        bytes.consume(2);
+        assert_eq!(bytes.chunk().len(), BLOCK_SIZE);
+        bytes.consume(BLOCK_SIZE - 1);
+        assert_eq!(bytes.next(), Some(b'a'));
+        assert_eq!(bytes.chunk().len(), 8);
+        bytes.consume(8);
+        assert!(bytes.chunk().is_empty());
+    }
+
     #[test]
     fn test_source_chars_buffer() {
         let source = "abcdefg";
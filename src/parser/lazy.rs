@@ -0,0 +1,390 @@
+//! Parsing the blocks of children in a document only when they are read.
+//!
+//! A [`LazyDocument`] parses the nodes at the top level of the source, and
+//! only lexes their blocks of children to find where they end. A block is
+//! parsed the first time its children are read, and then kept, so that
+//! reading a few settings from a large document only parses those settings.
+//!
+//! ```
+//! # use gpnd::parser::Parser;
+//! # use gpnd::{Document, Node};
+//! let document = Parser::new("server port=80 { tls }\nclient { retries 3 }")
+//!     .parse_lazy()
+//!     .unwrap();
+//! let server = document.get(0).unwrap();
+//! assert_eq!(server.get_param("port").unwrap(), 80);
+//! assert!(!server.is_parsed());
+//! assert_eq!(server.get_child(0).unwrap().name(), "tls");
+//! assert!(server.is_parsed());
+//! assert!(!document.get(1).unwrap().is_parsed());
+//! ```
+
+use std::sync::{Arc, OnceLock};
+
+use super::config::ParserConfig;
+use super::document::{unexpected, Parser};
+use super::error::{ParseError, ParseErrorKind};
+use super::lexer::Lexer;
+use super::token::TokenKind;
+use crate::{Document, Node, NodeBuf, Position, Span, Value};
+
+/// A document whose blocks of children are parsed when they are read, see
+/// [`Parser::parse_lazy`].
+#[derive(Debug)]
+pub struct LazyDocument<'src> {
+    version: Option<String>,
+    source: Option<Arc<str>>,
+    nodes: Vec<LazyNode<'src>>,
+}
+
+impl<'src> LazyDocument<'src> {
+    /// The node at the top level at `index`.
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<&LazyNode<'src>> {
+        self.nodes.get(index)
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, LazyNode<'src>> {
+        self.nodes.iter()
+    }
+
+    /// Parse every block that has not been parsed yet.
+    ///
+    /// # Errors
+    /// Fails with the error of the first block that is not valid.
+    pub fn parse_all(&self) -> Result<(), ParseError> {
+        self.nodes
+            .iter()
+            .try_for_each(|node| node.try_children().map(drop))
+    }
+}
+
+impl<'document, 'src> IntoIterator for &'document LazyDocument<'src> {
+    type Item = &'document LazyNode<'src>;
+    type IntoIter = std::slice::Iter<'document, LazyNode<'src>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl Document for LazyDocument<'_> {
+    fn nodes(&self) -> Vec<&dyn Node> {
+        self.nodes
+            .iter()
+            .map(|node| -> &dyn Node { node })
+            .collect()
+    }
+
+    fn get_node(&self, index: usize) -> Option<&dyn Node> {
+        self.nodes.get(index).map(|node| -> &dyn Node { node })
+    }
+
+    fn has_nodes(&self) -> bool {
+        !self.nodes.is_empty()
+    }
+
+    fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    fn source(&self) -> Option<&str> {
+        self.source.as_deref()
+    }
+}
+
+/// A node at the top level of a [`LazyDocument`].
+///
+/// Reading its children through [`Node`] parses its block if it has not been
+/// parsed yet, and panics if the block is not valid. Use
+/// [`LazyNode::try_children`] to handle the error instead.
+#[derive(Debug)]
+pub struct LazyNode<'src> {
+    node: NodeBuf,
+    block: Option<Block<'src>>,
+}
+
+/// A block of children that has been lexed, but maybe not parsed.
+#[derive(Debug)]
+struct Block<'src> {
+    text: &'src str,
+    /// The position after the opening brace.
+    start: Position,
+    config: ParserConfig,
+    source: Option<Arc<str>>,
+    children: OnceLock<Result<Vec<NodeBuf>, ParseError>>,
+}
+
+impl Block<'_> {
+    fn parse(&self) -> Result<Vec<NodeBuf>, ParseError> {
+        let mut parser = Parser::new(self.text).with_config(self.config);
+        if let Some(source) = &self.source {
+            parser = parser.with_source_name(Arc::clone(source));
+        }
+        parser.lexer = Lexer::resume(self.text, self.start);
+        // Drop the comments after the brace, as for blocks that are not lazy.
+        parser.peek()?;
+        parser.lexer.take_comments();
+        parser.nodes(true)
+    }
+}
+
+impl LazyNode<'_> {
+    /// Whether the block of children has been parsed, or there is none.
+    #[must_use]
+    pub fn is_parsed(&self) -> bool {
+        self.block
+            .as_ref()
+            .is_none_or(|block| block.children.get().is_some())
+    }
+
+    /// The children of this node, parsing its block if it has not been
+    /// parsed yet.
+    ///
+    /// # Errors
+    /// Fails if the block is not valid. The error is kept, and returned again
+    /// by later calls.
+    pub fn try_children(&self) -> Result<&[NodeBuf], ParseError> {
+        let Some(block) = &self.block else {
+            return Ok(&[]);
+        };
+        match block.children.get_or_init(|| block.parse()) {
+            Ok(children) => Ok(children),
+            Err(error) => Err(error.clone()),
+        }
+    }
+
+    /// Parse the block of children if it has not been parsed yet, and move
+    /// the node into a [`NodeBuf`].
+    ///
+    /// # Errors
+    /// Fails if the block is not valid.
+    pub fn into_node_buf(self) -> Result<NodeBuf, ParseError> {
+        let mut node = self.node;
+        if let Some(mut block) = self.block {
+            let children = block.children.take();
+            *node.children_mut() = children.unwrap_or_else(|| block.parse())?;
+        }
+        Ok(node)
+    }
+}
+
+impl Node for LazyNode<'_> {
+    fn name(&self) -> &str {
+        self.node.name()
+    }
+
+    fn namespace(&self) -> Option<&str> {
+        self.node.namespace()
+    }
+
+    fn type_annotation(&self) -> Option<&str> {
+        self.node.type_annotation()
+    }
+
+    fn args(&self) -> Vec<Value<'_>> {
+        self.node.args()
+    }
+
+    fn params(&self) -> Vec<(&str, Value<'_>)> {
+        self.node.params()
+    }
+
+    fn params_all(&self) -> Vec<(&str, Value<'_>)> {
+        self.node.params_all()
+    }
+
+    fn get_arg(&self, index: usize) -> Option<Value<'_>> {
+        self.node.get_arg(index)
+    }
+
+    fn arg_count(&self) -> usize {
+        self.node.arg_count()
+    }
+
+    fn get_param(&self, key: &str) -> Option<Value<'_>> {
+        self.node.get_param(key)
+    }
+
+    fn has_args(&self) -> bool {
+        self.node.has_args()
+    }
+
+    fn has_params(&self) -> bool {
+        self.node.has_params()
+    }
+
+    fn children(&self) -> Vec<&dyn Node> {
+        match self.try_children() {
+            Ok(children) => children
+                .iter()
+                .map(|child| -> &dyn Node { child })
+                .collect(),
+            Err(error) => panic!("invalid block of children: {error}"),
+        }
+    }
+
+    fn get_child(&self, index: usize) -> Option<&dyn Node> {
+        self.children().get(index).copied()
+    }
+
+    fn has_children(&self) -> bool {
+        self.block.is_some() && !self.children().is_empty()
+    }
+
+    fn span(&self) -> Option<Span> {
+        self.node.span()
+    }
+
+    fn arg_span(&self, index: usize) -> Option<Span> {
+        self.node.arg_span(index)
+    }
+
+    fn param_span(&self, key: &str) -> Option<Span> {
+        self.node.param_span(key)
+    }
+
+    fn source(&self) -> Option<&str> {
+        self.node.source()
+    }
+
+    fn leading_comments(&self) -> Vec<&str> {
+        self.node.leading_comments()
+    }
+
+    fn trailing_comment(&self) -> Option<&str> {
+        self.node.trailing_comment()
+    }
+}
+
+impl<'src> Parser<'src> {
+    /// Parse the source as a single document, leaving the blocks of children
+    /// of its nodes to be parsed when they are read, see the
+    /// [module](super::lazy) documentation.
+    ///
+    /// The blocks are still lexed, so errors in their tokens are found here.
+    /// Nodes at the top level cannot be `*name` references, and anchors in a
+    /// block are only visible within that block.
+    ///
+    /// # Errors
+    /// Fails if the source is not valid, not counting the contents of blocks,
+    /// or holds more than one document.
+    pub fn parse_lazy(mut self) -> Result<LazyDocument<'src>, ParseError> {
+        let version = self.directives()?;
+        let mut nodes = Vec::new();
+        loop {
+            self.skip_newlines(true)?;
+            let token = self.peek()?;
+            match token.kind {
+                TokenKind::End => break,
+                TokenKind::Separator => return Err(unexpected("node", &token)),
+                TokenKind::Directive => {
+                    return Err(ParseError::new(
+                        ParseErrorKind::MisplacedDirective(token.text[1..].to_owned()),
+                        token.span,
+                    ));
+                }
+                _ => {
+                    let node = self.lazy_node()?;
+                    match self.node_index(&nodes, &node, &token)? {
+                        Some(index) if index == nodes.len() => nodes.push(node),
+                        Some(index) => nodes[index] = node,
+                        None => {}
+                    }
+                }
+            }
+        }
+        Ok(LazyDocument {
+            version,
+            source: self.source_name().map(Arc::from),
+            nodes,
+        })
+    }
+
+    fn lazy_node(&mut self) -> Result<LazyNode<'src>, ParseError> {
+        let leading = self.lexer.take_comments();
+        let token = self.next()?;
+        if self.sigil(&token, '*').is_some() {
+            let kind = ParseErrorKind::UnknownAnchor(token.text[1..].to_owned());
+            return Err(ParseError::new(kind, token.span));
+        }
+        let mut node = self.node_name(&token)?;
+        let block = if self.entries(&mut node)? {
+            self.next()?;
+            let start = self.lexer.position();
+            self.skip_block()?;
+            self.terminator()?;
+            Some(Block {
+                text: self.lexer.source(),
+                start,
+                config: self.config(),
+                source: self.source_name().map(Arc::from),
+                children: OnceLock::new(),
+            })
+        } else {
+            None
+        };
+        self.finish_node(&mut node, &token, &leading)?;
+        Ok(LazyNode { node, block })
+    }
+
+    /// Lex up to and past the brace that closes a block.
+    fn skip_block(&mut self) -> Result<(), ParseError> {
+        let mut depth = 0_usize;
+        loop {
+            let token = self.next()?;
+            match token.kind {
+                TokenKind::LeftBrace | TokenKind::MapOpen => depth += 1,
+                TokenKind::RightBrace if depth == 0 => return Ok(()),
+                TokenKind::RightBrace => depth -= 1,
+                TokenKind::End | TokenKind::Separator => return Err(unexpected("`}`", &token)),
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::diff;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_parse_lazy() {
+        let source = concat!(
+            "@version \"1\"\n",
+            "// A comment.\n",
+            "server \"main\" port=80 { // Dropped.\n",
+            "    tls #{ a=1 }\n",
+            "    log { level \"info\" } // Trailing.\n",
+            "}\n",
+            "client\n",
+        );
+        let document = Parser::new(source).parse_lazy().unwrap();
+        assert_eq!(document.version(), Some("1"));
+        let server = document.get(0).unwrap();
+        assert_eq!(server.leading_comments(), ["A comment."]);
+        assert!(!server.is_parsed());
+        assert_eq!(
+            server.get_child(1).unwrap().span().unwrap().to_string(),
+            "5:5"
+        );
+        assert!(server.is_parsed());
+        assert!(diff(&document, &parse(source).unwrap()).is_empty());
+
+        let document = Parser::new("a { b = }\nc { d }").parse_lazy().unwrap();
+        assert!(document.get(1).unwrap().try_children().is_ok());
+        let error = document.get(0).unwrap().try_children().unwrap_err();
+        assert_eq!(error.to_string(), "1:7: expected value, found `=`");
+        assert!(document.parse_all().is_err());
+
+        let error = |source: &str| Parser::new(source).parse_lazy().unwrap_err().to_string();
+        assert_eq!(error("a {\n"), "2:1: expected `}`, found end of input");
+        assert_eq!(error("a { \"b }"), "1:5: unterminated string");
+    }
+}
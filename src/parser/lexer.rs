@@ -1,4 +1,13 @@
-use super::iter::{SourceBytes, SourceChars};
+use alloc::borrow::ToOwned;
+use alloc::vec::Vec;
+
+#[cfg(feature = "std")]
+use super::cache::Lexeme;
+use super::error::{ParseError, ParseErrorKind};
+use super::iter::{Chunked, SourceBytes, SourceChars};
+use super::scan;
+use super::token::{Comment, Token, TokenKind};
+use crate::{Position, Span};
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Cursor {
@@ -61,15 +70,18 @@ impl Cursor {
     }
 }
 
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
+// A slice is two words, but boxing it to keep the variants the same size
+// would allocate for every selection.
+#[allow(variant_size_differences)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 pub enum Selection<'src> {
     /// There is currently nothing in the buffer.
     #[default]
     Empty,
     /// The buffer contains a single character.
     Char(char),
-    /// The buffer contains a slice.
-    Slice(Box<&'src str>),
+    /// The buffer contains a slice, borrowed from the source.
+    Slice(&'src str),
     /// No more text can be consumed.
     EndOfFile,
 }
@@ -80,12 +92,11 @@ impl<'src> Selection<'src> {
         matches!(self, Self::Char(x) if *x == ch)
     }
 
-    #[allow(clippy::missing_panics_doc)]
     #[must_use]
-    pub fn eq_slice(&self, slice: &'src str) -> bool {
-        match self {
-            Self::Char(buf) if slice.len() == 1 => buf == &slice.chars().nth(0).unwrap(),
-            Self::Slice(buf) => buf.as_ref() == &slice,
+    pub fn eq_slice(&self, slice: &str) -> bool {
+        match *self {
+            Self::Char(buf) => slice.strip_prefix(buf) == Some(""),
+            Self::Slice(buf) => buf == slice,
             _ => false,
         }
     }
@@ -100,7 +111,7 @@ impl<'src> Selection<'src> {
     pub fn to_char_unchecked(self) -> char {
         match self {
             Self::Char(ch) => ch,
-            Self::Slice(slice) if slice.len() == 1 => slice.chars().nth(0).unwrap(),
+            Self::Slice(slice) if slice.chars().count() == 1 => slice.chars().next().unwrap(),
             _ => panic!("buffer was not a single character"),
         }
     }
@@ -110,9 +121,9 @@ impl<'src> Selection<'src> {
     /// # Panics
     /// Panics if the buffer does not contain a slice.
     #[must_use]
-    pub fn to_slice_unchecked(&self) -> &str {
+    pub fn to_slice_unchecked(self) -> &'src str {
         match self {
-            Self::Slice(slice) => slice.as_ref(),
+            Self::Slice(slice) => slice,
             _ => panic!("buffer was not a string slice"),
         }
     }
@@ -147,6 +158,53 @@ where
         self.cursor
     }
 
+    /// Consume the bytes for which `predicate` holds, a chunk at a time,
+    /// returning how many there were.
+    pub fn skip_while<P>(&mut self, mut predicate: P) -> usize
+    where
+        P: FnMut(u8) -> bool,
+    {
+        self.scan_while(&mut predicate, |_| {})
+    }
+
+    /// Consume the bytes for which `predicate` holds, a chunk at a time,
+    /// returning them.
+    pub fn take_while<P>(&mut self, mut predicate: P) -> Vec<u8>
+    where
+        P: FnMut(u8) -> bool,
+    {
+        let mut taken = Vec::new();
+        self.scan_while(&mut predicate, |bytes| taken.extend_from_slice(bytes));
+        taken
+    }
+
+    /// Consume the bytes for which `predicate` holds, giving each run of them
+    /// within a chunk to `scanned`, and move the cursor past them.
+    fn scan_while<P, F>(&mut self, predicate: &mut P, mut scanned: F) -> usize
+    where
+        P: FnMut(u8) -> bool,
+        F: FnMut(&[u8]),
+    {
+        let mut count = 0;
+        loop {
+            let chunk = self.source.chunk();
+            let len = chunk
+                .iter()
+                .position(|byte| !predicate(*byte))
+                .unwrap_or(chunk.len());
+            let end = len < chunk.len() || chunk.is_empty();
+            scanned(&chunk[..len]);
+            self.source.consume(len);
+            count += len;
+            if end {
+                break;
+            }
+        }
+        self.cursor = Cursor::Index(self.cursor.advance().index() + count);
+        count
+    }
+
+    #[allow(dead_code)]
     fn source_chars(&mut self) -> impl Iterator + '_ {
         SourceChars::new(self.source.by_ref())
     }
@@ -172,9 +230,7 @@ where
     //         Cursor::Slice(index, length) => self
     //             .source
     //             .get(index..index + length)
-    //             .map_or(Selection::EndOfFile, |slice| {
-    //                 Selection::Slice(Box::new(slice))
-    //             }),
+    //             .map_or(Selection::EndOfFile, Selection::Slice),
     //     };
     //     self.cursor.advance();
     //     buf
@@ -193,8 +249,375 @@ where
     // }
 }
 
+/// Splits source text into [`Token`]s.
+///
+/// Spaces, comments, and line continuations (a `\` at the end of a line) are
+/// skipped, but newlines are kept, since they end nodes.
+#[derive(Clone, Debug)]
+pub struct Lexer<'src> {
+    source: &'src str,
+    position: Position,
+    line_start: bool,
+    comments: Vec<Comment<'src>>,
+    /// The tokens to give instead of lexing the source, see
+    /// [`TokenCache`](super::cache::TokenCache).
+    #[cfg(feature = "std")]
+    replay: Option<alloc::vec::IntoIter<Lexeme<'src>>>,
+}
+
+impl<'src> Lexer<'src> {
+    #[must_use]
+    pub fn new(source: &'src str) -> Self {
+        Self {
+            source,
+            position: Position::START,
+            line_start: true,
+            comments: Vec::new(),
+            #[cfg(feature = "std")]
+            replay: None,
+        }
+    }
+
+    /// Start lexing `source` at `position`, which must be the position of a
+    /// character that does not start a line.
+    #[cfg(feature = "std")]
+    pub(super) fn resume(source: &'src str, position: Position) -> Self {
+        Self {
+            position,
+            line_start: false,
+            ..Self::new(source)
+        }
+    }
+
+    /// Start lexing `source` at `position`, which must be the start of a
+    /// line.
+    #[cfg(feature = "std")]
+    pub(super) fn resume_line(source: &'src str, position: Position) -> Self {
+        Self {
+            position,
+            ..Self::new(source)
+        }
+    }
+
+    /// Give `lexemes`, which were lexed from `source` before, rather than
+    /// lexing it again.
+    #[cfg(feature = "std")]
+    pub(super) fn replay(source: &'src str, lexemes: Vec<Lexeme<'src>>) -> Self {
+        Self {
+            replay: Some(lexemes.into_iter()),
+            ..Self::new(source)
+        }
+    }
+
+    /// Take the comments that were skipped since the last call.
+    pub fn take_comments(&mut self) -> Vec<Comment<'src>> {
+        core::mem::take(&mut self.comments)
+    }
+
+    /// Skip a line comment, and keep it.
+    fn line_comment(&mut self) {
+        let start = self.position;
+        self.advance(scan::line_len(self.rest().as_bytes()));
+        self.keep_comment(start);
+    }
+
+    fn keep_comment(&mut self, start: Position) {
+        self.comments.push(Comment {
+            text: &self.source[start.offset..self.position.offset],
+            span: Span::new(start, self.position),
+        });
+    }
+
+    /// The whole source, including the text that was lexed.
+    #[cfg(feature = "std")]
+    pub(super) fn source(&self) -> &'src str {
+        self.source
+    }
+
+    /// The position of the next character.
+    #[must_use]
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    fn rest(&self) -> &'src str {
+        &self.source[self.position.offset..]
+    }
+
+    fn peek_char(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let ch = self.peek_char()?;
+        self.position.offset += ch.len_utf8();
+        if ch == '\n' {
+            self.position.line += 1;
+            self.position.column = 1;
+        } else {
+            self.position.column += 1;
+        }
+        Some(ch)
+    }
+
+    /// Move past the next `len` bytes, which must end at a character
+    /// boundary.
+    fn advance(&mut self, len: usize) {
+        let text = &self.rest()[..len];
+        scan::advance(&mut self.position, text);
+    }
+
+    fn bump_while(&mut self, predicate: impl Fn(char) -> bool) {
+        while self.peek_char().is_some_and(&predicate) {
+            self.bump();
+        }
+    }
+
+    fn error(&self, kind: ParseErrorKind, start: Position) -> ParseError {
+        ParseError::new(kind, Span::new(start, self.position))
+    }
+
+    fn skip_trivia(&mut self) -> Result<(), ParseError> {
+        loop {
+            let start = self.position;
+            let rest = self.rest();
+            if rest.starts_with([' ', '\t']) {
+                self.advance(scan::blank_len(rest.as_bytes()));
+            } else if rest.starts_with('\u{feff}') {
+                self.bump();
+            } else if rest.starts_with("//") {
+                self.line_comment();
+            } else if rest.starts_with("/*") {
+                self.block_comment()?;
+                self.keep_comment(start);
+            } else if rest.starts_with('\\') {
+                self.bump();
+                self.bump_while(|ch| ch == ' ' || ch == '\t');
+                if self.rest().starts_with("//") {
+                    self.line_comment();
+                }
+                self.bump_while(|ch| ch == '\r');
+                if self.bump() != Some('\n') {
+                    return Err(self.error(ParseErrorKind::UnexpectedChar('\\'), start));
+                }
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Skip a block comment, which may be nested.
+    fn block_comment(&mut self) -> Result<(), ParseError> {
+        let start = self.position;
+        let mut depth = 0_usize;
+        loop {
+            let rest = self.rest();
+            if rest.starts_with("/*") {
+                depth += 1;
+                self.bump();
+            } else if rest.starts_with("*/") {
+                depth -= 1;
+                self.bump();
+                if depth == 0 {
+                    self.bump();
+                    return Ok(());
+                }
+            } else if rest.is_empty() {
+                return Err(self.error(ParseErrorKind::UnterminatedComment, start));
+            }
+            self.bump();
+        }
+    }
+
+    /// Consume the rest of a word, up to a delimiter.
+    fn word(&mut self) {
+        loop {
+            self.advance(scan::word_len(self.rest().as_bytes()));
+            let Some(ch) = self.peek_char() else {
+                break;
+            };
+            let rest = self.rest();
+            if ch.is_whitespace()
+                || ";={}[]()\",\\".contains(ch)
+                || rest.starts_with("//")
+                || rest.starts_with("/*")
+            {
+                break;
+            }
+            self.bump();
+        }
+    }
+
+    /// Consume a quoted string, leaving escapes to be read by
+    /// [`parse_string`](super::literal::parse_string).
+    fn quoted(&mut self, start: Position) -> Result<(), ParseError> {
+        self.bump();
+        loop {
+            self.advance(scan::string_len(self.rest().as_bytes()));
+            match self.bump() {
+                Some('"') => return Ok(()),
+                Some('\\') => {
+                    self.bump();
+                }
+                Some(_) => {}
+                None => return Err(self.error(ParseErrorKind::UnterminatedString, start)),
+            }
+        }
+    }
+
+    /// Consume a raw string, such as `r"C:\"` or `r#"say "hi""#`, if one
+    /// starts here.
+    fn raw(&mut self, start: Position) -> Result<bool, ParseError> {
+        let Some(rest) = self.rest().strip_prefix('r') else {
+            return Ok(false);
+        };
+        let hashes = rest.len() - rest.trim_start_matches('#').len();
+        if !rest[hashes..].starts_with('"') {
+            return Ok(false);
+        }
+        let body = &rest.as_bytes()[hashes + 1..];
+        let Some(length) = scan::raw_string_len(body, hashes) else {
+            self.bump_while(|_| true);
+            return Err(self.error(ParseErrorKind::UnterminatedString, start));
+        };
+        let end = self.position.offset + 1 + hashes + 1 + length + 1 + hashes;
+        while self.position.offset < end {
+            self.bump();
+        }
+        Ok(true)
+    }
+
+    /// Consume a type annotation, such as `(widget)` or `("my widget")`, if
+    /// one starts here. Otherwise nothing is consumed.
+    fn annotation(&mut self, start: Position) -> bool {
+        self.bump();
+        let typed = match self.peek_char() {
+            Some('"') => self.quoted(start).is_ok(),
+            Some(ch) if !ch.is_whitespace() && !";={}[]()\",\\".contains(ch) => {
+                self.word();
+                true
+            }
+            _ => false,
+        };
+        if typed && self.peek_char() == Some(')') {
+            self.bump();
+            true
+        } else {
+            self.position = start;
+            false
+        }
+    }
+
+    /// Read the next token, which is [`TokenKind::End`] at the end of the
+    /// source.
+    pub fn next_token(&mut self) -> Result<Token<'src>, ParseError> {
+        #[cfg(feature = "std")]
+        if let Some(replay) = &mut self.replay {
+            for lexeme in replay.by_ref() {
+                match lexeme {
+                    Lexeme::Comment(comment) => self.comments.push(comment),
+                    Lexeme::Token(token) => {
+                        self.position = token.span.end;
+                        return Ok(token);
+                    }
+                    Lexeme::Error(error) => return Err(error),
+                }
+            }
+            return Ok(Token {
+                kind: TokenKind::End,
+                text: "",
+                span: Span::new(self.position, self.position),
+            });
+        }
+        self.skip_trivia()?;
+        let start = self.position;
+        let line_start = core::mem::replace(&mut self.line_start, false);
+        let rest = self.rest();
+        let Some(ch) = self.peek_char() else {
+            return Ok(Token {
+                kind: TokenKind::End,
+                text: "",
+                span: Span::new(start, start),
+            });
+        };
+        let kind = match ch {
+            '\n' | '\r' if rest.starts_with('\n') || rest.starts_with("\r\n") => {
+                self.bump_while(|ch| ch == '\r');
+                self.bump();
+                self.line_start = true;
+                TokenKind::Newline
+            }
+            ';' | '=' | ',' | '{' | '}' | '[' | ']' => {
+                self.bump();
+                match ch {
+                    ';' => TokenKind::Semicolon,
+                    ',' => TokenKind::Comma,
+                    '=' => TokenKind::Equals,
+                    '{' => TokenKind::LeftBrace,
+                    '}' => TokenKind::RightBrace,
+                    '[' => TokenKind::LeftBracket,
+                    _ => TokenKind::RightBracket,
+                }
+            }
+            '"' => {
+                self.quoted(start)?;
+                TokenKind::String
+            }
+            'r' if self.raw(start)? => TokenKind::String,
+            '#' if rest.starts_with("#{") => {
+                self.bump();
+                self.bump();
+                TokenKind::MapOpen
+            }
+            '#' => {
+                self.bump();
+                self.word();
+                let text = &self.source[start.offset..self.position.offset];
+                if !matches!(text, "#inf" | "#-inf" | "#nan") {
+                    return Err(self.error(ParseErrorKind::UnknownKeyword(text.to_owned()), start));
+                }
+                TokenKind::Keyword
+            }
+            '@' => {
+                self.bump();
+                self.word();
+                TokenKind::Directive
+            }
+            '(' if self.annotation(start) => TokenKind::Annotation,
+            ch if ch.is_ascii_digit()
+                || (matches!(ch, '+' | '-')
+                    && rest[1..].starts_with(|ch: char| ch.is_ascii_digit())) =>
+            {
+                self.word();
+                TokenKind::Number
+            }
+            ch if !ch.is_whitespace() && !"()\",\\".contains(ch) => {
+                self.word();
+                let text = &self.source[start.offset..self.position.offset];
+                if line_start && text == "---" {
+                    TokenKind::Separator
+                } else {
+                    TokenKind::Ident
+                }
+            }
+            ch => {
+                self.bump();
+                return Err(self.error(ParseErrorKind::UnexpectedChar(ch), start));
+            }
+        };
+        Ok(Token {
+            kind,
+            text: &self.source[start.offset..self.position.offset],
+            span: Span::new(start, self.position),
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use alloc::format;
+    use alloc::string::String;
+
     use super::*;
 
     #[test]
@@ -212,6 +635,63 @@ mod tests {
         assert_eq!(cursor.advance().index(), 10);
     }
 
+    #[test]
+    fn test_selection() {
+        let source = String::from("r\u{e9}sum\u{e9}");
+        let selection = Selection::Slice(&source[1..3]);
+        assert!(selection.eq_slice("\u{e9}"));
+        assert_eq!(selection.to_char_unchecked(), '\u{e9}');
+        assert!(Selection::Char('\u{e9}').eq_slice("\u{e9}"));
+        assert!(!Selection::Char('r').eq_slice("re"));
+        let slice = Selection::Slice(&source[..4]).to_slice_unchecked();
+        assert_eq!(slice, "r\u{e9}s");
+    }
+
+    #[test]
+    fn test_lexer() {
+        let mut lexer = Lexer::new("a:b 1.5 \\\n  r#\"x\"# /* c /* d */ */ #{;\n---");
+        let mut kinds = Vec::new();
+        loop {
+            let token = lexer.next_token().unwrap();
+            kinds.push((token.kind, token.text));
+            if token.kind == TokenKind::End {
+                break;
+            }
+        }
+        assert_eq!(
+            kinds,
+            [
+                (TokenKind::Ident, "a:b"),
+                (TokenKind::Number, "1.5"),
+                (TokenKind::String, "r#\"x\"#"),
+                (TokenKind::MapOpen, "#{"),
+                (TokenKind::Semicolon, ";"),
+                (TokenKind::Newline, "\n"),
+                (TokenKind::Separator, "---"),
+                (TokenKind::End, ""),
+            ]
+        );
+        assert_eq!(lexer.position().line, 3);
+
+        let token = Lexer::new("r##\"a\"#b\"## c").next_token().unwrap();
+        assert_eq!(token.text, "r##\"a\"#b\"##");
+
+        let error = Lexer::new("\"abc").next_token().unwrap_err();
+        assert_eq!(error.kind(), &ParseErrorKind::UnterminatedString);
+    }
+
+    #[test]
+    fn test_scanner_scan_while() {
+        let source = format!("{}x1 2", " ".repeat(5000));
+        let mut scanner = Scanner::new(source.bytes());
+        assert_eq!(scanner.skip_while(|byte| byte == b' '), 5000);
+        assert_eq!(scanner.cursor(), Cursor::Index(5000));
+        assert_eq!(scanner.take_while(|byte| byte != b' '), b"x1");
+        assert_eq!(scanner.skip_while(|_| true), 2);
+        assert_eq!(scanner.skip_while(|_| true), 0);
+        assert_eq!(scanner.cursor().index(), 5004);
+    }
+
     // #[test]
     // fn test_scanner_take() {
     //     let mut scan = Scanner::new("123456789");
@@ -0,0 +1,512 @@
+use alloc::borrow::{Cow, ToOwned};
+use alloc::format;
+use alloc::string::String;
+use core::error::Error;
+
+use super::config::{ParserConfig, UnitSuffixes};
+use crate::Value;
+
+const SECOND: u64 = 1_000_000_000;
+
+/// The duration suffixes, from the largest unit to the smallest, with the
+/// number of nanoseconds in each.
+const DURATIONS: [(&str, u64); 7] = [
+    ("d", 24 * 60 * 60 * SECOND),
+    ("h", 60 * 60 * SECOND),
+    ("m", 60 * SECOND),
+    ("s", SECOND),
+    ("ms", 1_000_000),
+    ("us", 1_000),
+    ("ns", 1),
+];
+
+/// The size suffixes, from the largest unit to the smallest, with the number
+/// of bytes in each.
+const SIZES: [(&str, u64); 9] = [
+    ("TiB", 1 << 40),
+    ("TB", 1_000_000_000_000),
+    ("GiB", 1 << 30),
+    ("GB", 1_000_000_000),
+    ("MiB", 1 << 20),
+    ("MB", 1_000_000),
+    ("KiB", 1 << 10),
+    ("kB", 1_000),
+    ("B", 1),
+];
+
+/// A unit that can follow a number literal, with the factor that scales it to
+/// the base unit.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Unit {
+    /// A size, the factor scales to bytes.
+    Size(u64),
+    /// A duration, the factor scales to nanoseconds.
+    Duration(u64),
+}
+
+impl Unit {
+    /// Look up the unit for a suffix.
+    ///
+    /// Sizes are case-insensitive and use SI prefixes for powers of 1000
+    /// (`kb`, `MB`) and IEC prefixes for powers of 1024 (`KiB`, `mib`).
+    /// Durations are case-sensitive: `ns`, `us` (or `µs`), `ms`, `s`, `m`,
+    /// `h`, and `d`.
+    #[must_use]
+    pub fn from_suffix(suffix: &str) -> Option<Self> {
+        let suffix = if suffix == "µs" { "us" } else { suffix };
+        if let Some((_, factor)) = DURATIONS.iter().find(|(unit, _)| *unit == suffix) {
+            return Some(Self::Duration(*factor));
+        }
+        SIZES
+            .iter()
+            .find(|(unit, _)| unit.eq_ignore_ascii_case(suffix))
+            .map(|(_, factor)| Self::Size(*factor))
+    }
+
+    /// The factor that scales a quantity of this unit to the base unit.
+    #[must_use]
+    pub fn factor(self) -> u64 {
+        match self {
+            Self::Size(factor) | Self::Duration(factor) => factor,
+        }
+    }
+
+    /// The value of `quantity` of the base unit of this unit, a
+    /// [`Value::Size`] or a [`Value::Duration`].
+    fn value(self, quantity: u64) -> Value<'static> {
+        match self {
+            Self::Size(_) => Value::Size(quantity),
+            Self::Duration(_) => Value::Duration(quantity),
+        }
+    }
+}
+
+/// Write a number of bytes in the largest size unit it is a whole number of,
+/// such as `4MiB`, which [`parse_number`] reads back as the same
+/// [`Value::Size`].
+pub(crate) fn write_size<W>(f: &mut W, bytes: u64) -> core::fmt::Result
+where
+    W: core::fmt::Write,
+{
+    write_quantity(f, bytes, &SIZES)
+}
+
+/// Write a number of nanoseconds in the largest duration unit it is a whole
+/// number of, such as `250ms`, which [`parse_number`] reads back as the same
+/// [`Value::Duration`].
+pub(crate) fn write_duration<W>(f: &mut W, nanos: u64) -> core::fmt::Result
+where
+    W: core::fmt::Write,
+{
+    write_quantity(f, nanos, &DURATIONS)
+}
+
+fn write_quantity<W>(f: &mut W, quantity: u64, units: &[(&str, u64)]) -> core::fmt::Result
+where
+    W: core::fmt::Write,
+{
+    let (suffix, factor) = units
+        .iter()
+        .find(|(_, factor)| quantity != 0 && quantity.is_multiple_of(*factor))
+        .unwrap_or(&units[units.len() - 1]);
+    write!(f, "{}{suffix}", quantity / factor)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LiteralError {
+    /// The text is not a number.
+    Invalid(String),
+    /// The number has a suffix, but suffixes are not enabled.
+    UnexpectedSuffix(String),
+    /// The suffix is not a known unit.
+    UnknownUnit(String),
+    /// The number does not fit in the type it would be produced as.
+    OutOfRange(String),
+    /// A string contains an unknown or malformed escape sequence.
+    InvalidEscape(String),
+}
+
+impl core::fmt::Display for LiteralError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Invalid(text) => write!(f, "invalid number literal `{text}`"),
+            Self::UnexpectedSuffix(text) => {
+                write!(
+                    f,
+                    "number literal `{text}` has a unit suffix, which is not enabled"
+                )
+            }
+            Self::UnknownUnit(unit) => write!(f, "unknown unit `{unit}`"),
+            Self::OutOfRange(text) => write!(f, "number literal `{text}` is out of range"),
+            Self::InvalidEscape(escape) => write!(f, "invalid escape `{escape}`"),
+        }
+    }
+}
+
+impl Error for LiteralError {}
+
+/// Split a number literal into the number and its suffix, if any.
+///
+/// Literals with a radix prefix (`0x`, `0o`, `0b`) never have a suffix, since
+/// it would be ambiguous with hexadecimal digits.
+fn split_suffix(text: &str) -> (&str, &str) {
+    let unsigned = text.trim_start_matches(['+', '-']);
+    if unsigned.starts_with("0x") || unsigned.starts_with("0o") || unsigned.starts_with("0b") {
+        return (text, "");
+    }
+    let bytes = text.as_bytes();
+    let mut index = text.len() - unsigned.len();
+    while index < bytes.len() {
+        match bytes[index] {
+            b'0'..=b'9' | b'_' | b'.' => index += 1,
+            // An exponent is only an exponent if it is followed by digits.
+            b'e' | b'E' => {
+                let rest = &bytes[index + 1..];
+                let rest = rest
+                    .strip_prefix(b"+")
+                    .or_else(|| rest.strip_prefix(b"-"))
+                    .unwrap_or(rest);
+                if rest.first().is_some_and(u8::is_ascii_digit) {
+                    index = bytes.len() - rest.len();
+                } else {
+                    break;
+                }
+            }
+            _ => break,
+        }
+    }
+    text.split_at(index)
+}
+
+/// Parse a number literal without a suffix.
+///
+/// Integers are produced as an `I64` when they fit, otherwise as a `U64`.
+/// Numbers with a fractional part or an exponent are produced as an `F64`.
+fn parse_plain(text: &str) -> Result<Value<'static>, LiteralError> {
+    let invalid = || LiteralError::Invalid(text.to_owned());
+    let (negative, unsigned) = match text.as_bytes().first() {
+        Some(b'-') => (true, &text[1..]),
+        Some(b'+') => (false, &text[1..]),
+        _ => (false, text),
+    };
+    // Underscores are only allowed between digits.
+    if unsigned.is_empty() || unsigned.starts_with('_') || unsigned.ends_with('_') {
+        return Err(invalid());
+    }
+    let digits = unsigned.replace('_', "");
+
+    let radix = match digits.get(..2) {
+        Some("0x") => 16,
+        Some("0o") => 8,
+        Some("0b") => 2,
+        _ => 10,
+    };
+    if radix != 10 || !digits.contains(['.', 'e', 'E']) {
+        let digits = if radix == 10 { &digits } else { &digits[2..] };
+        if digits.is_empty() || !digits.chars().all(|ch| ch.is_digit(radix)) {
+            return Err(invalid());
+        }
+        let magnitude = u64::from_str_radix(digits, radix)
+            .map_err(|_| LiteralError::OutOfRange(text.to_owned()))?;
+        return if negative {
+            0_i64
+                .checked_sub_unsigned(magnitude)
+                .map(Value::I64)
+                .ok_or_else(|| LiteralError::OutOfRange(text.to_owned()))
+        } else {
+            Ok(i64::try_from(magnitude).map_or(Value::U64(magnitude), Value::I64))
+        };
+    }
+
+    // Rust accepts `inf` and `nan`, and a leading or trailing dot, none of
+    // which are valid literals here.
+    let valid = digits.chars().next().is_some_and(|ch| ch.is_ascii_digit())
+        && !digits.contains(".e")
+        && !digits.contains(".E")
+        && !digits.ends_with('.')
+        && digits
+            .chars()
+            .all(|ch| ch.is_ascii_digit() || "+-.eE".contains(ch));
+    if !valid {
+        return Err(invalid());
+    }
+    let magnitude: f64 = digits.parse().map_err(|_| invalid())?;
+    if magnitude.is_infinite() {
+        return Err(LiteralError::OutOfRange(text.to_owned()));
+    }
+    Ok(Value::F64(if negative { -magnitude } else { magnitude }))
+}
+
+/// Scale a number to the base unit of `unit`.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+fn scale(text: &str, number: &Value<'_>, unit: Unit) -> Result<u64, LiteralError> {
+    let out_of_range = || LiteralError::OutOfRange(text.to_owned());
+    match *number {
+        Value::I64(inner) => u64::try_from(inner)
+            .ok()
+            .and_then(|inner| inner.checked_mul(unit.factor()))
+            .ok_or_else(out_of_range),
+        Value::U64(inner) => inner.checked_mul(unit.factor()).ok_or_else(out_of_range),
+        Value::F64(inner) => {
+            let scaled = inner * unit.factor() as f64;
+            // The upper bound is exclusive, because `u64::MAX` rounds up when
+            // converted to a float.
+            if !(scaled > -0.5 && scaled < u64::MAX as f64) {
+                return Err(out_of_range());
+            }
+            // Round half away from zero by hand, since `f64::round` needs
+            // `std`.
+            let whole = scaled as u64;
+            Ok(whole + u64::from(scaled - whole as f64 >= 0.5))
+        }
+        _ => unreachable!("number literals are only parsed to I64, U64, and F64"),
+    }
+}
+
+/// Whether [`parse_number`] rounds `text` to a whole number of the base unit
+/// of its suffix, losing its fractional part.
+#[allow(clippy::cast_precision_loss)]
+pub(super) fn is_rounded(text: &str, config: ParserConfig) -> bool {
+    if config.unit_suffixes != UnitSuffixes::Normalize {
+        return false;
+    }
+    let (number, suffix) = split_suffix(text);
+    match (Unit::from_suffix(suffix), parse_plain(number)) {
+        (Some(unit), Ok(Value::F64(inner))) => inner * unit.factor() as f64 % 1.0 != 0.0,
+        _ => false,
+    }
+}
+
+/// Parse a number with a duration suffix, such as `250ms` or `1.5h`, to a
+/// whole number of nanoseconds.
+pub(crate) fn parse_duration(text: &str) -> Option<u64> {
+    let (number, suffix) = split_suffix(text);
+    let unit @ Unit::Duration(_) = Unit::from_suffix(suffix)? else {
+        return None;
+    };
+    scale(text, &parse_plain(number).ok()?, unit).ok()
+}
+
+/// Parse a number literal, which may have a unit suffix depending on the
+/// `config`.
+///
+/// ```
+/// # use gpnd::parser::config::{ParserConfig, UnitSuffixes};
+/// # use gpnd::parser::literal::parse_number;
+/// # use gpnd::Value;
+/// let config = ParserConfig {
+///     unit_suffixes: UnitSuffixes::Normalize,
+///     ..ParserConfig::default()
+/// };
+/// assert_eq!(parse_number("4KiB", &config).unwrap(), Value::Size(4096));
+/// assert_eq!(
+///     parse_number("250ms", &config).unwrap(),
+///     Value::Duration(250_000_000)
+/// );
+/// ```
+pub fn parse_number(text: &str, config: &ParserConfig) -> Result<Value<'static>, LiteralError> {
+    let (number, suffix) = split_suffix(text);
+    if suffix.is_empty() {
+        return parse_plain(number);
+    }
+    let unit = Unit::from_suffix(suffix);
+    match config.unit_suffixes {
+        UnitSuffixes::Reject => Err(LiteralError::UnexpectedSuffix(text.to_owned())),
+        UnitSuffixes::Normalize => {
+            let unit = unit.ok_or_else(|| LiteralError::UnknownUnit(suffix.to_owned()))?;
+            Ok(unit.value(scale(text, &parse_plain(number)?, unit)?))
+        }
+        UnitSuffixes::Preserve => {
+            unit.ok_or_else(|| LiteralError::UnknownUnit(suffix.to_owned()))?;
+            parse_plain(number)?;
+            Ok(Value::String(Cow::Owned(text.to_owned())))
+        }
+    }
+}
+
+/// Read a quoted string literal, such as `"a\tb"`, or a raw string literal,
+/// such as `r#"a\tb"#`, including its delimiters.
+///
+/// Quoted strings support the escapes `\"`, `\\`, `\n`, `\r`, `\t`, and
+/// `\u{...}`. The text is borrowed if there is nothing to unescape.
+///
+/// ```
+/// # use gpnd::parser::literal::parse_string;
+/// assert_eq!(parse_string(r#""a\tb""#).unwrap(), "a\tb");
+/// assert_eq!(parse_string(r##"r#"a\tb"#"##).unwrap(), r"a\tb");
+/// ```
+pub fn parse_string(text: &str) -> Result<Cow<'_, str>, LiteralError> {
+    let invalid = || LiteralError::Invalid(text.to_owned());
+    if let Some(raw) = text.strip_prefix('r') {
+        let hashes = raw.len() - raw.trim_start_matches('#').len();
+        return raw
+            .get(hashes..raw.len() - hashes)
+            .and_then(|raw| raw.strip_prefix('"'))
+            .and_then(|raw| raw.strip_suffix('"'))
+            .map(Cow::Borrowed)
+            .ok_or_else(invalid);
+    }
+    let inner = text
+        .strip_prefix('"')
+        .and_then(|text| text.strip_suffix('"'))
+        .ok_or_else(invalid)?;
+    if !inner.contains('\\') {
+        return Ok(Cow::Borrowed(inner));
+    }
+
+    let mut string = String::with_capacity(inner.len());
+    let mut chars = inner.char_indices();
+    while let Some((index, ch)) = chars.next() {
+        if ch != '\\' {
+            string.push(ch);
+            continue;
+        }
+        let escape = || {
+            let end = inner[index + 1..]
+                .char_indices()
+                .nth(1)
+                .map_or(inner.len(), |(end, _)| index + 1 + end);
+            LiteralError::InvalidEscape(inner[index..end].to_owned())
+        };
+        match chars.next().map(|(_, ch)| ch) {
+            Some('"') => string.push('"'),
+            Some('\\') => string.push('\\'),
+            Some('n') => string.push('\n'),
+            Some('r') => string.push('\r'),
+            Some('t') => string.push('\t'),
+            Some('u') => {
+                let rest = &inner[index + 2..];
+                let code = rest
+                    .strip_prefix('{')
+                    .and_then(|rest| rest.split_once('}'))
+                    .map(|(code, _)| code)
+                    .ok_or_else(escape)?;
+                let ch = u32::from_str_radix(code, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| LiteralError::InvalidEscape(format!("\\u{{{code}}}")))?;
+                string.push(ch);
+                chars.nth(code.len() + 1);
+            }
+            _ => return Err(escape()),
+        }
+    }
+    Ok(Cow::Owned(string))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_plain() {
+        let config = ParserConfig::default();
+        assert_eq!(parse_number("42", &config).unwrap(), Value::I64(42));
+        assert_eq!(parse_number("-1_000", &config).unwrap(), Value::I64(-1000));
+        assert_eq!(parse_number("0xff", &config).unwrap(), Value::I64(255));
+        assert_eq!(
+            parse_number("18446744073709551615", &config).unwrap(),
+            Value::U64(u64::MAX)
+        );
+        assert_eq!(parse_number("1.5e3", &config).unwrap(), Value::F64(1500.0));
+        assert!(parse_number("1.", &config).is_err());
+        assert!(parse_number("inf", &config).is_err());
+        assert!(parse_number("1_", &config).is_err());
+    }
+
+    #[test]
+    fn test_parse_suffix() {
+        let mut config = ParserConfig::default();
+        assert_eq!(
+            parse_number("10kb", &config),
+            Err(LiteralError::UnexpectedSuffix("10kb".to_owned()))
+        );
+
+        config.unit_suffixes = UnitSuffixes::Normalize;
+        assert_eq!(parse_number("10kb", &config).unwrap(), Value::Size(10_000));
+        assert_eq!(parse_number("4MiB", &config).unwrap(), Value::Size(4 << 20));
+        assert_eq!(
+            parse_number("1.5s", &config).unwrap(),
+            Value::Duration(1_500_000_000)
+        );
+        assert_eq!(
+            parse_number("2e1ms", &config).unwrap(),
+            Value::Duration(20_000_000)
+        );
+        assert_eq!(parse_number("10ns", &config).unwrap(), Value::Duration(10));
+        assert_ne!(
+            parse_number("10kb", &config).unwrap(),
+            parse_number("10000ns", &config).unwrap()
+        );
+        assert_ne!(
+            parse_number("10b", &config).unwrap(),
+            parse_number("10ns", &config).unwrap()
+        );
+        assert!(matches!(
+            parse_number("5parsecs", &config),
+            Err(LiteralError::UnknownUnit(_))
+        ));
+        assert!(matches!(
+            parse_number("-5s", &config),
+            Err(LiteralError::OutOfRange(_))
+        ));
+
+        config.unit_suffixes = UnitSuffixes::Preserve;
+        assert_eq!(parse_number("5s", &config).unwrap(), Value::from("5s"));
+    }
+
+    #[test]
+    fn test_write_units() {
+        let config = ParserConfig {
+            unit_suffixes: UnitSuffixes::Normalize,
+            ..ParserConfig::default()
+        };
+        let write = |value: &Value<'_>| {
+            let mut text = String::new();
+            match *value {
+                Value::Size(bytes) => write_size(&mut text, bytes).unwrap(),
+                Value::Duration(nanos) => write_duration(&mut text, nanos).unwrap(),
+                _ => unreachable!(),
+            }
+            text
+        };
+        for (source, written) in [
+            ("4MiB", "4MiB"),
+            ("1500kb", "1500kB"),
+            ("0kb", "0B"),
+            ("1.5s", "1500ms"),
+            ("90m", "90m"),
+            ("48h", "2d"),
+            ("0s", "0ns"),
+        ] {
+            let value = parse_number(source, &config).unwrap();
+            assert_eq!(write(&value), written);
+            assert_eq!(parse_number(written, &config).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn test_parse_string() {
+        assert!(matches!(
+            parse_string(r#""plain""#),
+            Ok(Cow::Borrowed("plain"))
+        ));
+        assert_eq!(
+            parse_string(r#""\"q\" \u{1F600}\n""#).unwrap(),
+            "\"q\" \u{1F600}\n"
+        );
+        assert_eq!(parse_string(r###"r##"a"#b"##"###).unwrap(), "a\"#b");
+        assert_eq!(
+            parse_string(r#""\x""#),
+            Err(LiteralError::InvalidEscape(r"\x".to_owned()))
+        );
+        assert_eq!(
+            parse_string(r#""\u{110000}""#),
+            Err(LiteralError::InvalidEscape(r"\u{110000}".to_owned()))
+        );
+    }
+}
@@ -0,0 +1,166 @@
+//! Loading documents from files in one call, with errors that name the file.
+
+use core::str::Utf8Error;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::{fmt, fs, io};
+
+use super::document::Parser;
+#[cfg(feature = "encoding")]
+use super::encoding::{decode, EncodingError};
+use super::error::ParseError;
+use crate::{DocumentBuf, Span};
+
+/// What went wrong while loading a file, see [`LoadError`].
+#[derive(Debug)]
+pub enum LoadErrorKind {
+    /// The file could not be read.
+    Io(io::Error),
+    /// The file is not valid UTF-8.
+    Utf8(Utf8Error),
+    /// The file could not be transcoded to UTF-8, see [`decode`].
+    #[cfg(feature = "encoding")]
+    Encoding(EncodingError),
+    /// The file is not a valid document.
+    Parse(ParseError),
+}
+
+impl fmt::Display for LoadErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => error.fmt(f),
+            Self::Utf8(error) => error.fmt(f),
+            #[cfg(feature = "encoding")]
+            Self::Encoding(error) => error.fmt(f),
+            Self::Parse(error) => error.fmt(f),
+        }
+    }
+}
+
+/// An error from [`DocumentBuf::from_path`], with the path of the file.
+#[derive(Debug)]
+pub struct LoadError {
+    path: PathBuf,
+    kind: LoadErrorKind,
+}
+
+impl LoadError {
+    #[must_use]
+    pub fn new(path: PathBuf, kind: LoadErrorKind) -> Self {
+        Self { path, kind }
+    }
+
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    #[must_use]
+    pub fn kind(&self) -> &LoadErrorKind {
+        &self.kind
+    }
+
+    #[must_use]
+    pub fn into_kind(self) -> LoadErrorKind {
+        self.kind
+    }
+
+    /// The span of a syntax error in the file.
+    #[must_use]
+    pub fn span(&self) -> Option<Span> {
+        match &self.kind {
+            LoadErrorKind::Parse(error) => Some(error.span()),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            LoadErrorKind::Parse(error) => write!(f, "{}:{error}", self.path.display()),
+            kind => write!(f, "{}: {kind}", self.path.display()),
+        }
+    }
+}
+
+impl Error for LoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(match &self.kind {
+            LoadErrorKind::Io(error) => error,
+            LoadErrorKind::Utf8(error) => error,
+            #[cfg(feature = "encoding")]
+            LoadErrorKind::Encoding(error) => error,
+            LoadErrorKind::Parse(error) => error,
+        })
+    }
+}
+
+impl DocumentBuf {
+    /// Read, decode, and parse the file at `path` as a single document.
+    ///
+    /// The file must be UTF-8, or with the `encoding` feature, in any
+    /// encoding that can be sniffed from its bytes. The path is recorded as
+    /// the source of the document and its nodes.
+    ///
+    /// ```no_run
+    /// # use gpnd::DocumentBuf;
+    /// match DocumentBuf::from_path("app.gpnd") {
+    ///     Ok(document) => println!("{} nodes", document.nodes_ref().len()),
+    ///     // Such as `app.gpnd:3:7: expected value, found `}``.
+    ///     Err(error) => eprintln!("{error}"),
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// Fails if the file cannot be read or decoded, or is not valid, with the
+    /// path of the file.
+    pub fn from_path<P>(path: P) -> Result<Self, LoadError>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let fail = |kind| LoadError::new(path.to_owned(), kind);
+        let bytes = fs::read(path).map_err(|error| fail(LoadErrorKind::Io(error)))?;
+        #[cfg(feature = "encoding")]
+        let source = decode(&bytes, None).map_err(|error| fail(LoadErrorKind::Encoding(error)))?;
+        #[cfg(not(feature = "encoding"))]
+        let source = String::from_utf8(bytes)
+            .map_err(|error| fail(LoadErrorKind::Utf8(error.utf8_error())))?;
+        Parser::new(&source)
+            .with_source_name(path.to_string_lossy())
+            .parse()
+            .map_err(|error| fail(LoadErrorKind::Parse(error)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Document, Node};
+
+    #[test]
+    fn test_from_path() {
+        let path = std::env::temp_dir().join(format!("gpnd-load-{}.gpnd", std::process::id()));
+        fs::write(&path, "server port=80 {\n    tls\n}\n").unwrap();
+        let document = DocumentBuf::from_path(&path).unwrap();
+        let server = document.get_node(0).unwrap();
+        assert_eq!(server.source(), Some(&*path.to_string_lossy()));
+        assert_eq!(server.get_child(0).map(Node::name), Some("tls"));
+
+        fs::write(&path, "server {\n    port=\n}\n").unwrap();
+        let error = DocumentBuf::from_path(&path).unwrap_err();
+        assert_eq!(error.path(), path);
+        assert_eq!(error.span().map(|span| span.start.line), Some(2));
+        assert!(error
+            .to_string()
+            .starts_with(&format!("{}:2:", path.display())));
+        fs::remove_file(&path).unwrap();
+
+        let error = DocumentBuf::from_path(&path).unwrap_err();
+        assert!(matches!(error.kind(), LoadErrorKind::Io(_)));
+        assert!(error
+            .to_string()
+            .starts_with(&format!("{}: ", path.display())));
+    }
+}
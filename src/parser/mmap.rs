@@ -0,0 +1,143 @@
+//! Parsing files mapped into memory, so that large documents do not need to
+//! be read into a `String` first.
+//!
+//! The file is parsed into an arena, see the [`arena`](super::arena) module,
+//! and its names, keys, and strings without escapes are borrowed from the
+//! mapped file rather than copied.
+
+use core::error::Error;
+use core::fmt;
+use core::str::Utf8Error;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use bumpalo::Bump;
+use memmap2::Mmap;
+
+use super::arena::ParsedDocument;
+use super::document::Parser;
+use super::error::ParseError;
+
+/// An error from [`parse_file_mmap`].
+#[derive(Debug)]
+pub enum MmapError {
+    /// The file could not be opened or mapped.
+    Io(io::Error),
+    /// The file is not valid UTF-8.
+    Utf8(Utf8Error),
+    /// The file is not a valid document.
+    Parse(ParseError),
+}
+
+impl fmt::Display for MmapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => error.fmt(f),
+            Self::Utf8(error) => error.fmt(f),
+            Self::Parse(error) => error.fmt(f),
+        }
+    }
+}
+
+impl Error for MmapError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::Utf8(error) => Some(error),
+            Self::Parse(error) => Some(error),
+        }
+    }
+}
+
+impl From<io::Error> for MmapError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<Utf8Error> for MmapError {
+    fn from(error: Utf8Error) -> Self {
+        Self::Utf8(error)
+    }
+}
+
+impl From<ParseError> for MmapError {
+    fn from(error: ParseError) -> Self {
+        Self::Parse(error)
+    }
+}
+
+/// Map the file at `path` into memory, parse it as a single document, and
+/// pass the document to `read`, returning what it returns.
+///
+/// The document borrows from the mapped file, so it is only available within
+/// `read`.
+///
+/// ```no_run
+/// # use gpnd::parser::parse_file_mmap;
+/// # use gpnd::Document;
+/// // SAFETY: nothing else writes to the file while it is parsed.
+/// let count = unsafe { parse_file_mmap("large.gpnd", |document| document.iter_dfs().count()) };
+/// println!("{} nodes", count.unwrap());
+/// ```
+///
+/// # Safety
+/// The file must not be changed, by this or any other process, until this
+/// returns. The mapped memory would change under the parser and the
+/// document, which is undefined behavior.
+///
+/// # Errors
+/// Fails if the file cannot be mapped, or is not a valid document.
+#[allow(unsafe_code)]
+pub unsafe fn parse_file_mmap<P, F, T>(path: P, read: F) -> Result<T, MmapError>
+where
+    P: AsRef<Path>,
+    F: FnOnce(&ParsedDocument<'_>) -> T,
+{
+    let file = File::open(path)?;
+    // SAFETY: the caller ensures that the file is not changed while the map
+    // is alive, and the map is dropped before this returns.
+    let map = unsafe { Mmap::map(&file)? };
+    let text = core::str::from_utf8(&map)?;
+    let arena = Bump::new();
+    let document = Parser::new(text).parse_in(&arena)?;
+    Ok(read(&document))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Document;
+
+    #[test]
+    #[allow(unsafe_code)]
+    fn test_parse_file_mmap() {
+        let path = std::env::temp_dir().join(format!("gpnd-mmap-{}.gpnd", std::process::id()));
+        std::fs::write(&path, "server \"main\" port=80 {\n    tls\n}\n").unwrap();
+        // SAFETY: the file is only written by this test.
+        let names = unsafe {
+            parse_file_mmap(&path, |document| {
+                document
+                    .iter_dfs()
+                    .map(|(node, _)| node.name().to_owned())
+                    .collect::<Vec<_>>()
+            })
+        };
+        assert_eq!(names.unwrap(), ["server", "tls"]);
+
+        std::fs::write(&path, "server {").unwrap();
+        // SAFETY: as above.
+        let error = unsafe { parse_file_mmap(&path, |_| ()) }.unwrap_err();
+        assert!(matches!(error, MmapError::Parse(_)));
+        std::fs::write(&path, b"\xff").unwrap();
+        // SAFETY: as above.
+        let error = unsafe { parse_file_mmap(&path, |_| ()) }.unwrap_err();
+        assert!(matches!(error, MmapError::Utf8(_)));
+        std::fs::remove_file(&path).unwrap();
+
+        // SAFETY: the file does not exist.
+        let error = unsafe { parse_file_mmap(&path, |_| ()) }.unwrap_err();
+        assert!(matches!(error, MmapError::Io(_)));
+    }
+}
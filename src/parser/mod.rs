@@ -1,2 +1,50 @@
+#[cfg(feature = "arena")]
+pub mod arena;
+#[cfg(feature = "std")]
+pub mod cache;
+pub mod config;
+pub mod document;
+#[cfg(feature = "encoding")]
+pub mod encoding;
+pub mod error;
+#[cfg(feature = "std")]
+pub mod include;
 pub mod iter;
+#[cfg(feature = "std")]
+pub mod lazy;
 pub mod lexer;
+pub mod literal;
+#[cfg(feature = "std")]
+pub mod load;
+#[cfg(feature = "mmap")]
+pub mod mmap;
+pub mod pull;
+mod scan;
+#[cfg(feature = "std")]
+pub mod template;
+pub mod token;
+#[cfg(feature = "notify")]
+pub mod watch;
+
+#[cfg(feature = "arena")]
+pub use arena::*;
+#[cfg(feature = "std")]
+pub use cache::*;
+pub use config::*;
+pub use document::*;
+#[cfg(feature = "encoding")]
+pub use encoding::*;
+pub use error::*;
+#[cfg(feature = "std")]
+pub use include::*;
+#[cfg(feature = "std")]
+pub use lazy::*;
+#[cfg(feature = "std")]
+pub use load::*;
+#[cfg(feature = "mmap")]
+pub use mmap::*;
+pub use pull::*;
+#[cfg(feature = "std")]
+pub use template::*;
+#[cfg(feature = "notify")]
+pub use watch::*;
@@ -0,0 +1,230 @@
+//! Parsing a document as a stream of events, so that nodes can be read
+//! without holding the whole document in memory.
+//!
+//! Every node is reported by an [`Event::Start`] with its arguments and
+//! parameters, then the events of its children, then an [`Event::End`]. The
+//! memory that the parser holds is bounded by how deeply the nodes are
+//! nested, not by the size of the document, see the
+//! [`pipeline`](crate::pipeline) module for transforming the events.
+//!
+//! ```
+//! # use gpnd::parser::{Event, Parser};
+//! # use gpnd::Node;
+//! let names: Vec<_> = Parser::new("server { tls }\nclient")
+//!     .events()
+//!     .filter_map(|event| match event.unwrap() {
+//!         Event::Start(node) => Some(node.name().to_owned()),
+//!         _ => None,
+//!     })
+//!     .collect();
+//! assert_eq!(names, ["server", "tls", "client"]);
+//! ```
+
+use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
+use alloc::string::String;
+
+use super::config::DuplicatePolicy;
+use super::document::{unexpected, Parser};
+use super::error::{ParseError, ParseErrorKind};
+use super::token::TokenKind;
+use crate::{NodeBuf, Position, Span};
+
+/// An event in a stream from [`Parser::events`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    /// The `@version` directive, which comes before any nodes.
+    Version(String),
+    /// The start of a node, without its children.
+    ///
+    /// The span of a node with children covers it up to the opening brace,
+    /// and comments after the closing brace are dropped.
+    Start(Box<NodeBuf>),
+    /// The end of the last node that was started.
+    End,
+}
+
+/// An iterator over the events of a document, see [`Parser::events`].
+///
+/// Iteration stops after the first error.
+#[derive(Clone, Debug)]
+pub struct Events<'src> {
+    parser: Parser<'src>,
+    started: bool,
+    done: bool,
+    /// The number of blocks of children that are open.
+    depth: usize,
+    /// Whether the last node that was started has no children, so that its
+    /// end comes next.
+    leaf: bool,
+}
+
+impl Events<'_> {
+    /// The number of nodes that have been started and not ended.
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        self.depth + usize::from(self.leaf)
+    }
+
+    fn event(&mut self) -> Result<Option<Event>, ParseError> {
+        if core::mem::take(&mut self.leaf) {
+            return Ok(Some(Event::End));
+        }
+        if !core::mem::replace(&mut self.started, true) {
+            if self.parser.config().duplicate_nodes != DuplicatePolicy::CollectAll {
+                let kind = ParseErrorKind::Unsupported("duplicate_nodes");
+                return Err(ParseError::new(
+                    kind,
+                    Span::new(Position::START, Position::START),
+                ));
+            }
+            if let Some(version) = self.parser.directives()? {
+                return Ok(Some(Event::Version(version)));
+            }
+        }
+        self.parser.skip_newlines(true)?;
+        let token = self.parser.peek()?;
+        match token.kind {
+            TokenKind::RightBrace if self.depth > 0 => {
+                self.parser.next()?;
+                self.parser.terminator()?;
+                self.parser.lexer.take_comments();
+                self.depth -= 1;
+                Ok(Some(Event::End))
+            }
+            TokenKind::End if self.depth == 0 => {
+                self.parser.lexer.take_comments();
+                Ok(None)
+            }
+            TokenKind::End | TokenKind::Separator if self.depth > 0 => {
+                Err(unexpected("`}`", &token))
+            }
+            TokenKind::End | TokenKind::Separator => Err(unexpected("node", &token)),
+            TokenKind::Directive => Err(ParseError::new(
+                ParseErrorKind::MisplacedDirective(token.text[1..].to_owned()),
+                token.span,
+            )),
+            _ => self.start().map(Some),
+        }
+    }
+
+    fn start(&mut self) -> Result<Event, ParseError> {
+        let parser = &mut self.parser;
+        let leading = parser.lexer.take_comments();
+        let token = parser.next()?;
+        if parser.sigil(&token, '*').is_some() {
+            let kind = ParseErrorKind::UnknownAnchor(token.text[1..].to_owned());
+            return Err(ParseError::new(kind, token.span));
+        }
+        let mut node = parser.node_name(&token)?;
+        if parser.entries(&mut node)? {
+            parser.next()?;
+            // Drop the comments in the header and after the brace, as for
+            // whole nodes.
+            parser.peek()?;
+            parser.lexer.take_comments();
+            self.depth += 1;
+        } else {
+            self.leaf = true;
+        }
+        parser.finish_node(&mut node, &token, &leading)?;
+        Ok(Event::Start(Box::new(node)))
+    }
+}
+
+impl Iterator for Events<'_> {
+    type Item = Result<Event, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let event = self.event().transpose();
+        self.done = !matches!(event, Some(Ok(_)));
+        event
+    }
+}
+
+impl<'src> Parser<'src> {
+    /// Parse the source as a single document, as a stream of events, see the
+    /// [module](super::pull) documentation.
+    ///
+    /// Nodes cannot be `*name` references, since the anchored nodes are not
+    /// kept. Values can still have anchors if they are enabled.
+    ///
+    /// Nodes are not compared with their siblings, so the first event is an
+    /// [`ParseErrorKind::Unsupported`] error unless the `duplicate_nodes`
+    /// option is [`DuplicatePolicy::CollectAll`].
+    #[must_use]
+    pub fn events(self) -> Events<'src> {
+        Events {
+            parser: self,
+            started: false,
+            done: false,
+            depth: 0,
+            leaf: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+    use alloc::vec::Vec;
+
+    use super::*;
+    use crate::parser::ParserConfig;
+    use crate::Node;
+
+    fn events(source: &str) -> Vec<String> {
+        Parser::new(source)
+            .events()
+            .map(|event| match event {
+                Ok(Event::Version(version)) => format!("@{version}"),
+                Ok(Event::Start(node)) => format!("<{node}"),
+                Ok(Event::End) => ">".to_owned(),
+                Err(error) => format!("!{error}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_events() {
+        assert_eq!(
+            events("@version \"1\"\na 1 { b k=2; c { d } }\n// Comment.\ne"),
+            ["@1", "<a 1", "<b k=2", ">", "<c", "<d", ">", ">", ">", "<e", ">"]
+        );
+        assert!(events("").is_empty());
+        assert_eq!(
+            events("a {\nb"),
+            ["<a", "<b", ">", "!2:2: expected `}`, found end of input"]
+        );
+        assert_eq!(
+            events("a\n}"),
+            ["<a", ">", "!2:1: expected node name, found `}`"]
+        );
+
+        let mut events = Parser::new("a { b }").events();
+        let Some(Ok(Event::Start(node))) = events.next() else {
+            panic!("expected the start of a node");
+        };
+        assert_eq!(node.span().unwrap().len(), 3);
+        assert_eq!(events.depth(), 1);
+        events.next();
+        assert_eq!(events.depth(), 2);
+        assert_eq!(events.by_ref().count(), 2);
+        assert_eq!(events.depth(), 0);
+
+        let config = ParserConfig {
+            duplicate_nodes: DuplicatePolicy::FirstWins,
+            ..ParserConfig::default()
+        };
+        let mut events = Parser::new("a\na").with_config(config).events();
+        let error = events.next().unwrap().unwrap_err();
+        assert_eq!(
+            error.kind(),
+            &ParseErrorKind::Unsupported("duplicate_nodes")
+        );
+        assert!(events.next().is_none());
+    }
+}
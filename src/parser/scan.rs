@@ -0,0 +1,245 @@
+//! Scanning runs of bytes for the lexer, rather than decoding one character
+//! at a time.
+//!
+//! Runs of blanks and word characters are scanned eight bytes at a time,
+//! classifying every byte of a `u64` with a few integer operations and
+//! falling back to single bytes only in the chunk where the run ends. With
+//! the `simd` feature, searches for particular bytes use `memchr`, which
+//! compares many bytes at once with the vector instructions that the CPU
+//! supports.
+
+use crate::Position;
+
+/// Whether an ASCII byte ends a word, see [`word_len`]. `/` ends a word only
+/// if it starts a comment, which is left to the lexer.
+const fn is_delimiter(byte: u8) -> bool {
+    matches!(
+        byte,
+        b' ' | b'\t'
+            | b'\n'
+            | b'\r'
+            | 0x0b
+            | 0x0c
+            | b';'
+            | b'='
+            | b'{'
+            | b'}'
+            | b'['
+            | b']'
+            | b'('
+            | b')'
+            | b'"'
+            | b','
+            | b'\\'
+            | b'/'
+    )
+}
+
+/// The number of bytes scanned at once by [`blank_len`] and [`word_len`].
+const LANES: usize = 8;
+/// The low bit of every byte in a chunk.
+const ONES: u64 = u64::from_ne_bytes([0x01; LANES]);
+/// The high bit of every byte in a chunk.
+const HIGH: u64 = ONES * 0x80;
+
+/// The high bit of every byte of `chunk` that is zero.
+const fn zero(chunk: u64) -> u64 {
+    // Adding 0x7f to the low seven bits carries into the high bit unless
+    // they are all zero, and never into the next byte.
+    !(((chunk & !HIGH) + !HIGH) | chunk) & HIGH
+}
+
+/// The high bit of every byte of `chunk` that equals `byte`.
+const fn equal(chunk: u64, byte: u8) -> u64 {
+    zero(chunk ^ (ONES * byte as u64))
+}
+
+/// The high bit of every byte of `chunk` that is at least `byte`, which must
+/// be in `1..=0x80`. Only the low seven bits of each byte are compared.
+const fn at_least(chunk: u64, byte: u8) -> u64 {
+    ((chunk & !HIGH) + ONES * (0x80 - byte as u64)) & HIGH
+}
+
+/// The high bit of every byte of `chunk` in `low..=high`, for ASCII bytes.
+const fn within(chunk: u64, low: u8, high: u8) -> u64 {
+    at_least(chunk, low) & !at_least(chunk, high + 1)
+}
+
+/// The high bit of every byte of `chunk` that is a word character, for the
+/// common ranges of them that hold no delimiters. Bytes left out here are
+/// checked one at a time.
+const fn plain(chunk: u64) -> u64 {
+    let ranges = within(chunk, b'-', b'.')
+        | within(chunk, b'0', b':')
+        | within(chunk, b'>', b'Z')
+        | within(chunk, b'^', b'z');
+    ranges & !chunk
+}
+
+/// The index of the first byte of a chunk that `mask` leaves clear, if there
+/// is one.
+fn first_clear(mask: u64) -> Option<usize> {
+    let clear = !mask & HIGH;
+    (clear != 0).then(|| clear.trailing_zeros() as usize / LANES)
+}
+
+/// The length of the run of spaces and tabs at the start of `bytes`.
+pub(super) fn blank_len(bytes: &[u8]) -> usize {
+    let (chunks, rest) = bytes.as_chunks::<LANES>();
+    for (index, chunk) in chunks.iter().enumerate() {
+        let chunk = u64::from_le_bytes(*chunk);
+        if let Some(offset) = first_clear(equal(chunk, b' ') | equal(chunk, b'\t')) {
+            return index * LANES + offset;
+        }
+    }
+    let start = chunks.len() * LANES;
+    start
+        + rest
+            .iter()
+            .position(|byte| !matches!(byte, b' ' | b'\t'))
+            .unwrap_or(rest.len())
+}
+
+/// The length of the run of ASCII word characters at the start of `bytes`.
+/// The run also stops at the first non-ASCII byte, which the lexer checks
+/// itself.
+pub(super) fn word_len(bytes: &[u8]) -> usize {
+    let is_word = |byte: &u8| byte.is_ascii() && !is_delimiter(*byte);
+    let (chunks, rest) = bytes.as_chunks::<LANES>();
+    for (index, chunk) in chunks.iter().enumerate() {
+        let Some(offset) = first_clear(plain(u64::from_le_bytes(*chunk))) else {
+            continue;
+        };
+        if let Some(stop) = chunk[offset..].iter().position(|byte| !is_word(byte)) {
+            return index * LANES + offset + stop;
+        }
+    }
+    let start = chunks.len() * LANES;
+    start
+        + rest
+            .iter()
+            .position(|byte| !is_word(byte))
+            .unwrap_or(rest.len())
+}
+
+/// The length of `bytes` up to the end of the line.
+pub(super) fn line_len(bytes: &[u8]) -> usize {
+    #[cfg(feature = "simd")]
+    let index = memchr::memchr(b'\n', bytes);
+    #[cfg(not(feature = "simd"))]
+    let index = bytes.iter().position(|byte| *byte == b'\n');
+    index.unwrap_or(bytes.len())
+}
+
+/// The length of the body of a quoted string at the start of `bytes`, up to
+/// a quote or an escape.
+pub(super) fn string_len(bytes: &[u8]) -> usize {
+    #[cfg(feature = "simd")]
+    let index = memchr::memchr2(b'"', b'\\', bytes);
+    #[cfg(not(feature = "simd"))]
+    let index = bytes.iter().position(|byte| matches!(byte, b'"' | b'\\'));
+    index.unwrap_or(bytes.len())
+}
+
+/// The length of the body of a raw string at the start of `bytes`, up to a
+/// quote that is followed by `hashes` hashes, if there is one.
+pub(super) fn raw_string_len(bytes: &[u8], hashes: usize) -> Option<usize> {
+    let mut offset = 0;
+    loop {
+        let rest = &bytes[offset..];
+        #[cfg(feature = "simd")]
+        let index = memchr::memchr(b'"', rest)?;
+        #[cfg(not(feature = "simd"))]
+        let index = rest.iter().position(|byte| *byte == b'"')?;
+        let quote = offset + index;
+        let closing = bytes.get(quote + 1..quote + 1 + hashes);
+        if closing.is_some_and(|closing| closing.iter().all(|byte| *byte == b'#')) {
+            return Some(quote);
+        }
+        offset = quote + 1;
+    }
+}
+
+/// Move `position` past `text`, counting lines and characters.
+pub(super) fn advance(position: &mut Position, text: &str) {
+    let bytes = text.as_bytes();
+    position.offset += bytes.len();
+    #[cfg(feature = "simd")]
+    let (lines, last) = (
+        memchr::memchr_iter(b'\n', bytes).count(),
+        memchr::memrchr(b'\n', bytes),
+    );
+    #[cfg(not(feature = "simd"))]
+    #[allow(clippy::naive_bytecount)]
+    let (lines, last) = (
+        bytes.iter().filter(|byte| **byte == b'\n').count(),
+        bytes.iter().rposition(|byte| *byte == b'\n'),
+    );
+    // Characters are counted by the bytes that start them.
+    let chars = |bytes: &[u8]| bytes.iter().filter(|byte| (**byte & 0xc0) != 0x80).count();
+    match last {
+        Some(last) => {
+            position.line += lines;
+            position.column = 1 + chars(&bytes[last + 1..]);
+        }
+        None => position.column += chars(bytes),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan() {
+        assert_eq!(blank_len(b" \t x"), 3);
+        assert_eq!(word_len(b"a:b-c.d=1"), 7);
+        assert_eq!(word_len("ab\u{e9}".as_bytes()), 2);
+        assert_eq!(word_len(b"a//b"), 1);
+        assert_eq!(line_len(b"abc\r\ndef"), 4);
+        assert_eq!(line_len(b"abc"), 3);
+        assert_eq!(string_len(b"ab\\\"c\""), 2);
+        assert_eq!(raw_string_len(b"a\"b\"#c\"##", 2), Some(6));
+        assert_eq!(raw_string_len(b"a\"#", 2), None);
+        assert_eq!(raw_string_len(b"ab\"", 0), Some(2));
+
+        let mut position = Position::START;
+        advance(&mut position, "a\u{e9}b");
+        assert_eq!((position.offset, position.line, position.column), (4, 1, 4));
+        advance(&mut position, "x\n\u{e9}\u{e9}\ny\u{e9}");
+        assert_eq!(
+            (position.offset, position.line, position.column),
+            (14, 3, 3)
+        );
+    }
+
+    #[test]
+    fn test_scan_chunks() {
+        // Every byte ends or continues a run at every offset of two chunks
+        // and a remainder, as it does when scanned one byte at a time.
+        for byte in 0..=u8::MAX {
+            for len in 0..20 {
+                let mut bytes = [b'a'; 20];
+                bytes[len] = byte;
+                let expected = if byte.is_ascii() && !is_delimiter(byte) {
+                    20
+                } else {
+                    len
+                };
+                assert_eq!(word_len(&bytes), expected, "{byte:#04x} at {len}");
+
+                let mut bytes = [b'\t'; 20];
+                bytes[len] = byte;
+                let expected = if matches!(byte, b' ' | b'\t') {
+                    20
+                } else {
+                    len
+                };
+                assert_eq!(blank_len(&bytes), expected, "{byte:#04x} at {len}");
+            }
+        }
+        assert_eq!(word_len(b"a-b.c:d>e?f@g^h_i`j|k~l<m"), 25);
+        assert_eq!(word_len(b"name#1!$%&'*+rest(x)"), 17);
+        assert_eq!(blank_len(b" \t \t \t \t \t \t\n"), 12);
+    }
+}
@@ -0,0 +1,312 @@
+//! Expanding `${name}` placeholders in the strings of a parsed document.
+//!
+//! Interpolation is a separate pass over a [`DocumentBuf`], which is only made
+//! when asked for by [`DocumentBuf::interpolate`]. Placeholders are expanded
+//! in string arguments and parameter values, including those inside lists and
+//! maps, but not in names or keys. A literal `${` is written `$${`.
+//!
+//! Placeholders can also be expanded from the environment of the process, by
+//! [`DocumentBuf::expand_env`], with [`EnvOptions`] to limit which variables
+//! a document can read.
+
+use std::borrow::{Borrow, Cow};
+use std::collections::HashMap;
+use std::error::Error;
+use std::hash::{BuildHasher, Hash};
+use std::{env, fmt};
+
+use crate::{DocumentBuf, NodeBuf, Value};
+
+/// An error from interpolating a document, see [`DocumentBuf::interpolate`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InterpolationError {
+    /// Placeholders named variables that have no value, listed once each in
+    /// the order they were found.
+    Unresolved(Vec<String>),
+    /// A string has a `${` without a closing `}`.
+    Unterminated(String),
+}
+
+impl fmt::Display for InterpolationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unresolved(names) => {
+                f.write_str("unresolved variables: ")?;
+                for (index, name) in names.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "`{name}`")?;
+                }
+                Ok(())
+            }
+            Self::Unterminated(text) => write!(f, "unterminated placeholder in {text:?}"),
+        }
+    }
+}
+
+impl Error for InterpolationError {}
+
+/// Options for [`DocumentBuf::expand_env`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EnvOptions {
+    /// The names of the variables that can be expanded, or [`None`] to allow
+    /// every variable. Other variables are treated as if they were not set.
+    pub allowlist: Option<Vec<String>>,
+    /// Whether a placeholder for a variable that is not set is an error.
+    /// Otherwise it expands to an empty string, as in a shell.
+    pub strict: bool,
+}
+
+impl EnvOptions {
+    /// Read the variable `name` from the environment, if it is allowed.
+    fn lookup(&self, name: &str) -> Option<String> {
+        let allowed = self
+            .allowlist
+            .as_ref()
+            .is_none_or(|allowlist| allowlist.iter().any(|allowed| allowed == name));
+        let value = allowed.then(|| env::var(name).ok()).flatten();
+        match value {
+            None if !self.strict => Some(String::new()),
+            value => value,
+        }
+    }
+}
+
+/// Expand the placeholders in `text`, pushing the names without a value onto
+/// `unresolved`, or return [`None`] if there are none to expand.
+fn expand<F>(
+    text: &str,
+    lookup: &mut F,
+    unresolved: &mut Vec<String>,
+) -> Result<Option<String>, InterpolationError>
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    if !text.contains("${") {
+        return Ok(None);
+    }
+    let mut expanded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('$') {
+        expanded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if let Some(after) = rest.strip_prefix("$${") {
+            expanded.push_str("${");
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("${") {
+            let end = after
+                .find('}')
+                .ok_or_else(|| InterpolationError::Unterminated(text.to_owned()))?;
+            let name = &after[..end];
+            match lookup(name) {
+                Some(value) => expanded.push_str(&value),
+                None if unresolved.iter().any(|other| other == name) => {}
+                None => unresolved.push(name.to_owned()),
+            }
+            rest = &after[end + 1..];
+        } else {
+            expanded.push('$');
+            rest = &rest[1..];
+        }
+    }
+    expanded.push_str(rest);
+    Ok(Some(expanded))
+}
+
+fn expand_value<F>(
+    value: &mut Value<'static>,
+    lookup: &mut F,
+    unresolved: &mut Vec<String>,
+) -> Result<(), InterpolationError>
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    match value {
+        Value::String(text) => {
+            if let Some(expanded) = expand(text, lookup, unresolved)? {
+                *text = Cow::Owned(expanded);
+            }
+        }
+        Value::List(items) => {
+            for item in items.iter_mut() {
+                expand_value(item, lookup, unresolved)?;
+            }
+        }
+        Value::Map(items) => {
+            for item in items.values_mut() {
+                expand_value(item, lookup, unresolved)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn expand_node<F>(
+    node: &mut NodeBuf,
+    lookup: &mut F,
+    unresolved: &mut Vec<String>,
+) -> Result<(), InterpolationError>
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    for value in node.args_mut() {
+        expand_value(value, lookup, unresolved)?;
+    }
+    for (_, value) in node.params_mut().iter_mut() {
+        expand_value(value, lookup, unresolved)?;
+    }
+    for child in node.children_mut() {
+        expand_node(child, lookup, unresolved)?;
+    }
+    Ok(())
+}
+
+impl DocumentBuf {
+    /// Expand `${name}` placeholders with the values in `variables`, see the
+    /// [module](self) documentation.
+    ///
+    /// If any placeholder cannot be expanded, the document is left unchanged,
+    /// and the error lists every variable without a value.
+    ///
+    /// ```
+    /// # use std::collections::HashMap;
+    /// # use gpnd::{Document, Node};
+    /// let mut document =
+    ///     gpnd::parser::parse(r#"server url="http://${host}:${port}/$${path}""#).unwrap();
+    /// let variables = HashMap::from([("host", "localhost"), ("port", "8080")]);
+    /// document.interpolate(&variables).unwrap();
+    /// let server = document.get_node(0).unwrap();
+    /// assert_eq!(
+    ///     server.get_param("url").unwrap(),
+    ///     "http://localhost:8080/${path}"
+    /// );
+    /// ```
+    pub fn interpolate<K, V, S>(
+        &mut self,
+        variables: &HashMap<K, V, S>,
+    ) -> Result<(), InterpolationError>
+    where
+        K: Borrow<str> + Hash + Eq,
+        V: AsRef<str>,
+        S: BuildHasher,
+    {
+        self.interpolate_with(|name| variables.get(name).map(|value| value.as_ref().to_owned()))
+    }
+
+    /// Expand `${name}` placeholders with the values that `lookup` returns,
+    /// see [`DocumentBuf::interpolate`].
+    pub fn interpolate_with<F>(&mut self, mut lookup: F) -> Result<(), InterpolationError>
+    where
+        F: FnMut(&str) -> Option<String>,
+    {
+        let mut nodes = self.nodes_ref().to_vec();
+        let mut unresolved = Vec::new();
+        for node in &mut nodes {
+            expand_node(node, &mut lookup, &mut unresolved)?;
+        }
+        if !unresolved.is_empty() {
+            return Err(InterpolationError::Unresolved(unresolved));
+        }
+        *self.nodes_mut() = nodes;
+        Ok(())
+    }
+
+    /// Expand `${NAME}` placeholders with the environment variables of the
+    /// process, see [`EnvOptions`].
+    ///
+    /// ```
+    /// # use gpnd::{Document, Node};
+    /// # use gpnd::parser::EnvOptions;
+    /// let mut document = gpnd::parser::parse(r#"server home="${HOME}" token="${TOKEN}""#).unwrap();
+    /// let options = EnvOptions {
+    ///     allowlist: Some(vec!["HOME".to_owned()]),
+    ///     strict: false,
+    /// };
+    /// document.expand_env(&options).unwrap();
+    /// let server = document.get_node(0).unwrap();
+    /// assert_eq!(server.get_param("token").unwrap(), "");
+    /// ```
+    pub fn expand_env(&mut self, options: &EnvOptions) -> Result<(), InterpolationError> {
+        self.interpolate_with(|name| options.lookup(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_interpolate() {
+        let mut document = parse(concat!(
+            "a \"${x}${x}\" \"$${x} ${y}\" list=[\"${y}\" 1] map=#{ k=\"${x}\" }\n",
+            "b \"$5 ${\" { c \"${z}-${x}\" }\n",
+        ))
+        .unwrap();
+        let original = document.clone();
+        let variables = HashMap::from([("x".to_owned(), "1".to_owned())]);
+        assert_eq!(
+            document.interpolate(&variables),
+            Err(InterpolationError::Unterminated("$5 ${".to_owned()))
+        );
+
+        let variables = HashMap::from([("x", "1")]);
+        let mut document = parse("a \"${y}\" \"${x}\" { b \"${z}${y}\" }").unwrap();
+        let error = document.interpolate(&variables).unwrap_err();
+        assert_eq!(error.to_string(), "unresolved variables: `y`, `z`");
+        assert_eq!(
+            document,
+            parse("a \"${y}\" \"${x}\" { b \"${z}${y}\" }").unwrap()
+        );
+
+        let mut document = original;
+        let variables = HashMap::from([("x", "1"), ("y", "2"), ("z", "3")]);
+        *document.nodes_mut()[1].args_mut() = vec![Value::from("$5")].into();
+        document.interpolate(&variables).unwrap();
+        assert_eq!(
+            document,
+            parse(concat!(
+                "a \"11\" \"${x} 2\" list=[\"2\" 1] map=#{ k=\"1\" }\n",
+                "b \"$5\" { c \"3-1\" }\n",
+            ))
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_expand_env() {
+        env::set_var("GPND_TEST_HOST", "example.com");
+        env::remove_var("GPND_TEST_MISSING");
+        let source = "a \"${GPND_TEST_HOST}/${GPND_TEST_MISSING}\"";
+        let expand = |options: &EnvOptions| {
+            let mut document = parse(source).unwrap();
+            document.expand_env(options).map(|()| document)
+        };
+
+        let document = expand(&EnvOptions::default()).unwrap();
+        assert_eq!(document, parse("a \"example.com/\"").unwrap());
+
+        let strict = EnvOptions {
+            strict: true,
+            ..EnvOptions::default()
+        };
+        assert_eq!(
+            expand(&strict),
+            Err(InterpolationError::Unresolved(vec![
+                "GPND_TEST_MISSING".to_owned()
+            ]))
+        );
+
+        let allowlist = EnvOptions {
+            allowlist: Some(vec!["GPND_TEST_MISSING".to_owned()]),
+            strict: true,
+        };
+        let error = expand(&allowlist).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "unresolved variables: `GPND_TEST_HOST`, `GPND_TEST_MISSING`"
+        );
+    }
+}
@@ -0,0 +1,91 @@
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::{format, vec};
+
+use crate::Span;
+
+/// The kind of a [`Token`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TokenKind {
+    /// A bare word, such as a node name, a parameter key, or `true`.
+    Ident,
+    /// A quoted or raw string, including its delimiters.
+    String,
+    /// A number literal, including any unit suffix.
+    Number,
+    /// A `#` keyword, such as `#inf`.
+    Keyword,
+    /// A directive name, such as `@version`.
+    Directive,
+    /// A type annotation before a node name, such as `(widget)`, including
+    /// its parentheses.
+    Annotation,
+    Equals,
+    /// A `,`, which is only allowed with [`ParserConfig::commas`].
+    ///
+    /// [`ParserConfig::commas`]: super::ParserConfig::commas
+    Comma,
+    Semicolon,
+    Newline,
+    LeftBrace,
+    RightBrace,
+    LeftBracket,
+    RightBracket,
+    /// The `#{` that opens a map.
+    MapOpen,
+    /// A `---` line, which separates documents in a stream.
+    Separator,
+    End,
+}
+
+/// A token of source text, with the text it was read from.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Token<'src> {
+    pub kind: TokenKind,
+    pub text: &'src str,
+    pub span: Span,
+}
+
+impl Token<'_> {
+    /// Describe the token for an error message.
+    #[must_use]
+    pub fn describe(&self) -> String {
+        match self.kind {
+            TokenKind::Newline => "newline".to_owned(),
+            TokenKind::End => "end of input".to_owned(),
+            _ => format!("`{}`", self.text),
+        }
+    }
+}
+
+/// A comment, including its delimiters.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Comment<'src> {
+    pub text: &'src str,
+    pub span: Span,
+}
+
+impl<'src> Comment<'src> {
+    /// The lines of text in the comment, without delimiters or leading and
+    /// trailing whitespace. Blank first and last lines of block comments are
+    /// left out.
+    #[must_use]
+    pub fn lines(&self) -> Vec<&'src str> {
+        if let Some(inner) = self.text.strip_prefix("//") {
+            return vec![inner.trim()];
+        }
+        let inner = self
+            .text
+            .strip_prefix("/*")
+            .and_then(|inner| inner.strip_suffix("*/"))
+            .unwrap_or(self.text);
+        let mut lines: Vec<_> = inner.lines().map(str::trim).collect();
+        while lines.last().is_some_and(|line| line.is_empty()) {
+            lines.pop();
+        }
+        let blank = lines.iter().take_while(|line| line.is_empty()).count();
+        lines.drain(..blank);
+        lines
+    }
+}
@@ -0,0 +1,246 @@
+//! Reloading a document whenever its file changes, with [`notify`].
+//!
+//! A [`WatchedDocument`] keeps the last document that was loaded without
+//! errors, and calls its subscribers with every new document, or with the
+//! error that kept a change from loading, so that a long-running service can
+//! apply changes to its configuration without restarting.
+//!
+//! ```no_run
+//! # use gpnd::parser::WatchedDocument;
+//! let config = WatchedDocument::new("app.gpnd").unwrap();
+//! config.subscribe(|result| match result {
+//!     Ok(document) => println!("reloaded {} nodes", document.nodes_ref().len()),
+//!     Err(error) => eprintln!("keeping the last configuration: {error}"),
+//! });
+//! ```
+
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::{fmt, fs};
+
+use notify::{recommended_watcher, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use super::load::{LoadError, LoadErrorKind};
+use crate::DocumentBuf;
+
+/// An error from [`WatchedDocument::new`].
+#[derive(Debug)]
+pub enum WatchError {
+    /// The file could not be loaded the first time.
+    Load(LoadError),
+    /// The file could not be watched.
+    Notify(notify::Error),
+}
+
+impl fmt::Display for WatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Load(error) => error.fmt(f),
+            Self::Notify(error) => write!(f, "cannot watch file: {error}"),
+        }
+    }
+}
+
+impl Error for WatchError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Load(error) => Some(error),
+            Self::Notify(error) => Some(error),
+        }
+    }
+}
+
+impl From<LoadError> for WatchError {
+    fn from(error: LoadError) -> Self {
+        Self::Load(error)
+    }
+}
+
+impl From<notify::Error> for WatchError {
+    fn from(error: notify::Error) -> Self {
+        Self::Notify(error)
+    }
+}
+
+type Subscriber = Box<dyn FnMut(Result<&DocumentBuf, &LoadError>) + Send>;
+
+/// The state shared with the thread that handles file events.
+struct Shared {
+    path: PathBuf,
+    document: Mutex<Arc<DocumentBuf>>,
+    subscribers: Mutex<Vec<Subscriber>>,
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // A subscriber that panics leaves nothing half-updated.
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl Shared {
+    fn reload(&self) {
+        let result = DocumentBuf::from_path(&self.path).map(Arc::new);
+        if let Ok(document) = &result {
+            let mut current = lock(&self.document);
+            if *current == *document {
+                return;
+            }
+            *current = Arc::clone(document);
+        }
+        for subscriber in lock(&self.subscribers).iter_mut() {
+            subscriber(result.as_deref());
+        }
+    }
+}
+
+/// A document that is parsed again whenever its file changes, see the
+/// [module](self) documentation.
+///
+/// The directory of the file is watched rather than the file, so that
+/// editors that save by replacing the file are followed. Changes that leave
+/// the document equal to the current one are not reported, but one change
+/// can cause several events, each of which reports an error if the file
+/// does not load.
+pub struct WatchedDocument {
+    shared: Arc<Shared>,
+    // Stops watching when dropped.
+    _watcher: RecommendedWatcher,
+}
+
+impl WatchedDocument {
+    /// Load the file at `path`, and start watching it.
+    ///
+    /// # Errors
+    /// Fails if the file cannot be loaded, see [`DocumentBuf::from_path`],
+    /// or watched.
+    pub fn new<P>(path: P) -> Result<Self, WatchError>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let document = DocumentBuf::from_path(path)?;
+        // Events name files by the path of the watched directory, so it must
+        // be the same as that of the file.
+        let path = fs::canonicalize(path)
+            .map_err(|error| LoadError::new(path.to_owned(), LoadErrorKind::Io(error)))?;
+        let shared = Arc::new(Shared {
+            path,
+            document: Mutex::new(Arc::new(document)),
+            subscribers: Mutex::new(Vec::new()),
+        });
+        let handler = Arc::clone(&shared);
+        let mut watcher = recommended_watcher(move |event: notify::Result<notify::Event>| {
+            let Ok(event) = event else {
+                return;
+            };
+            if !matches!(event.kind, EventKind::Access(_))
+                && event.paths.iter().any(|path| *path == handler.path)
+            {
+                handler.reload();
+            }
+        })?;
+        let directory = shared.path.parent().unwrap_or(&shared.path);
+        watcher.watch(directory, RecursiveMode::NonRecursive)?;
+        Ok(Self {
+            shared,
+            _watcher: watcher,
+        })
+    }
+
+    /// The canonical path of the file.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.shared.path
+    }
+
+    /// The last document that was loaded without errors.
+    #[must_use]
+    pub fn document(&self) -> Arc<DocumentBuf> {
+        Arc::clone(&lock(&self.shared.document))
+    }
+
+    /// Call `subscriber` with every new document, or the error that kept a
+    /// change from loading.
+    ///
+    /// Subscribers are called on the thread that watches the file, and must
+    /// not subscribe others from within the call.
+    pub fn subscribe<F>(&self, subscriber: F)
+    where
+        F: FnMut(Result<&DocumentBuf, &LoadError>) + Send + 'static,
+    {
+        lock(&self.shared.subscribers).push(Box::new(subscriber));
+    }
+
+    /// Load the file again now, as if it had changed, such as when a service
+    /// is asked to reload its configuration.
+    pub fn reload(&self) {
+        self.shared.reload();
+    }
+}
+
+impl fmt::Debug for WatchedDocument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WatchedDocument")
+            .field("path", &self.shared.path)
+            .field("document", &self.document())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_watched_document() {
+        let directory = std::env::temp_dir().join(format!("gpnd-watch-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("app.gpnd");
+        // Replace the file at once, as editors do, so that it is never seen
+        // half-written.
+        let save = |text: &str| {
+            let temporary = directory.join("app.gpnd.tmp");
+            fs::write(&temporary, text).unwrap();
+            fs::rename(&temporary, &path).unwrap();
+        };
+        save("server port=80");
+        let watched = WatchedDocument::new(&path).unwrap();
+        assert_eq!(*watched.document(), parse("server port=80").unwrap());
+
+        let (sender, receiver) = mpsc::channel();
+        watched.subscribe(move |result| {
+            sender
+                .send(result.cloned().map_err(ToString::to_string))
+                .unwrap();
+        });
+        let timeout = Duration::from_secs(10);
+        save("server port=8080");
+        let document = receiver.recv_timeout(timeout).unwrap().unwrap();
+        assert_eq!(document, parse("server port=8080").unwrap());
+        assert_eq!(*watched.document(), document);
+
+        save("server {");
+        let error = receiver.recv_timeout(timeout).unwrap().unwrap_err();
+        assert!(error.contains("app.gpnd:1:9: "), "{error}");
+        assert_eq!(*watched.document(), document);
+
+        // Changes that leave the document as it was are not reported.
+        save("server port=8080");
+        save("server port=8080 // unchanged");
+        watched.reload();
+        save("server port=443");
+        // An error is reported for every event that found it.
+        let document = loop {
+            if let Ok(document) = receiver.recv_timeout(timeout).unwrap() {
+                break document;
+            }
+        };
+        assert_eq!(document, parse("server port=443").unwrap());
+        drop(watched);
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
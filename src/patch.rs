@@ -0,0 +1,311 @@
+//! Patches, which are lists of edits to make to a document by path.
+//!
+//! Paths are those taken by [`DocumentBuf::get_path_mut`]. A patch can be
+//! written as a document itself, with a node for each operation, whose first
+//! argument is the path:
+//!
+//! ```text
+//! add "server" { tls enabled=true }
+//! replace "server/listen[1]" { listen port=8443 }
+//! remove "server/log"
+//! set "server/port" 8080
+//! unset "server/host"
+//! ```
+//!
+//! `add` appends its children to the node at the path, or to the top level
+//! if the path is empty. `replace` replaces the node at the path with its
+//! only child. `set` and `unset` change the parameter at the path.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt;
+
+use crate::{Document, DocumentBuf, NodeBuf, PathError, Value};
+
+/// An edit made by a [`Patch`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum Operation {
+    /// Append a node to the node at `parent`, or to the top level if
+    /// `parent` is empty.
+    Add {
+        parent: String,
+        node: NodeBuf,
+    },
+    Remove {
+        path: String,
+    },
+    Replace {
+        path: String,
+        node: NodeBuf,
+    },
+    /// Set the parameter at `path`.
+    Set {
+        path: String,
+        value: Value<'static>,
+    },
+    /// Remove the parameter at `path`.
+    Unset {
+        path: String,
+    },
+}
+
+impl Operation {
+    fn apply(&self, document: &mut DocumentBuf) -> Result<(), PathError> {
+        match self {
+            Self::Add { parent, node } if parent.split('/').all(str::is_empty) => {
+                document.nodes_mut().push(node.clone());
+            }
+            Self::Add { parent, node } => document.insert_child(parent, node.clone())?,
+            Self::Remove { path } => {
+                document.remove(path)?;
+            }
+            Self::Replace { path, node } => *document.get_path_mut(path)? = node.clone(),
+            Self::Set { path, value } => {
+                document.set(path, value.clone())?;
+            }
+            Self::Unset { path } => {
+                document.remove_value(path)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn to_node(&self) -> NodeBuf {
+        let (name, path) = match self {
+            Self::Add { parent, .. } => ("add", parent),
+            Self::Remove { path } => ("remove", path),
+            Self::Replace { path, .. } => ("replace", path),
+            Self::Set { path, .. } => ("set", path),
+            Self::Unset { path } => ("unset", path),
+        };
+        let mut operation = NodeBuf::new(name);
+        operation.args_mut().push(Value::from(path.clone()));
+        match self {
+            Self::Add { node, .. } | Self::Replace { node, .. } => {
+                operation.children_mut().push(node.clone());
+            }
+            Self::Set { value, .. } => operation.args_mut().push(value.clone()),
+            Self::Remove { .. } | Self::Unset { .. } => {}
+        }
+        operation
+    }
+}
+
+/// An error from reading or applying a [`Patch`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PatchError {
+    /// The node at `index` of a patch document is not a valid operation.
+    Invalid { index: usize, reason: &'static str },
+    /// The operation at `index` could not be applied.
+    Apply { index: usize, error: PathError },
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid { index, reason } => write!(f, "invalid operation {index}: {reason}"),
+            Self::Apply { index, error } => write!(f, "operation {index} failed: {error}"),
+        }
+    }
+}
+
+impl Error for PatchError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Invalid { .. } => None,
+            Self::Apply { error, .. } => Some(error),
+        }
+    }
+}
+
+/// A list of [`Operation`]s, see the [module](self) documentation.
+///
+/// ```
+/// # use gpnd::patch::Patch;
+/// let mut document = gpnd::parser::parse("server port=80 { log }").unwrap();
+/// let patch = gpnd::parser::parse("set \"server/port\" 8080\nremove \"server/log\"").unwrap();
+/// let patch = Patch::from_document(&patch).unwrap();
+/// document.apply_patch(&patch).unwrap();
+/// assert_eq!(document, gpnd::parser::parse("server port=8080").unwrap());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Patch {
+    operations: Vec<Operation>,
+}
+
+impl Patch {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn operations(&self) -> &[Operation] {
+        &self.operations
+    }
+
+    pub fn operations_mut(&mut self) -> &mut Vec<Operation> {
+        &mut self.operations
+    }
+
+    pub fn push(&mut self, operation: Operation) {
+        self.operations.push(operation);
+    }
+
+    /// Read a patch written as a document, see the [module](self)
+    /// documentation.
+    pub fn from_document<D>(document: &D) -> Result<Self, PatchError>
+    where
+        D: Document + ?Sized,
+    {
+        let mut operations = Vec::new();
+        for (index, node) in document.nodes().into_iter().enumerate() {
+            let invalid = |reason| PatchError::Invalid { index, reason };
+            let args = node.args();
+            let path = match args.first() {
+                Some(Value::String(path)) => path.to_string(),
+                _ => return Err(invalid("the first argument must be a path")),
+            };
+            let expected_args = if node.name() == "set" { 2 } else { 1 };
+            if args.len() != expected_args {
+                return Err(invalid("wrong number of arguments"));
+            }
+            let children = node.children();
+            if !matches!(node.name(), "add" | "replace") && !children.is_empty() {
+                return Err(invalid("unexpected children"));
+            }
+            match node.name() {
+                "add" => operations.extend(children.into_iter().map(|child| Operation::Add {
+                    parent: path.clone(),
+                    node: child.to_node_buf(),
+                })),
+                "replace" => match children.as_slice() {
+                    [child] => operations.push(Operation::Replace {
+                        path,
+                        node: child.to_node_buf(),
+                    }),
+                    _ => return Err(invalid("`replace` must have one child")),
+                },
+                "remove" => operations.push(Operation::Remove { path }),
+                "set" => operations.push(Operation::Set {
+                    path,
+                    value: args[1].clone().into_owned(),
+                }),
+                "unset" => operations.push(Operation::Unset { path }),
+                _ => return Err(invalid("unknown operation")),
+            }
+        }
+        Ok(Self { operations })
+    }
+
+    /// Write the patch as a document, which [`Patch::from_document`] reads
+    /// back.
+    #[must_use]
+    pub fn to_document(&self) -> DocumentBuf {
+        DocumentBuf::from(
+            self.operations
+                .iter()
+                .map(Operation::to_node)
+                .collect::<Vec<_>>(),
+        )
+    }
+}
+
+impl FromIterator<Operation> for Patch {
+    fn from_iter<I>(operations: I) -> Self
+    where
+        I: IntoIterator<Item = Operation>,
+    {
+        Self {
+            operations: operations.into_iter().collect(),
+        }
+    }
+}
+
+impl DocumentBuf {
+    /// Apply the operations of `patch` in order.
+    ///
+    /// The patch is applied completely or not at all: if an operation
+    /// fails, the document is left unchanged.
+    pub fn apply_patch(&mut self, patch: &Patch) -> Result<(), PatchError> {
+        let mut patched = self.clone();
+        for (index, operation) in patch.operations.iter().enumerate() {
+            operation
+                .apply(&mut patched)
+                .map_err(|error| PatchError::Apply { index, error })?;
+        }
+        *self = patched;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::borrow::ToOwned;
+
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_patch() {
+        let source = concat!(
+            "add \"\" { client }\n",
+            "add \"server\" { tls; listen port=9000 }\n",
+            "replace \"server/listen[1]\" { listen port=8443 }\n",
+            "remove \"server/log\"\n",
+            "set \"server/port\" 8080\n",
+            "unset \"server/host\"\n",
+        );
+        let patch = Patch::from_document(&parse(source).unwrap()).unwrap();
+        assert_eq!(patch.operations().len(), 7);
+        assert_eq!(Patch::from_document(&patch.to_document()).unwrap(), patch);
+
+        let mut document =
+            parse("server host=\"a\" port=80 { listen port=80; listen port=443; log }").unwrap();
+        document.apply_patch(&patch).unwrap();
+        assert_eq!(
+            document,
+            parse(concat!(
+                "server port=8080 { listen port=80; listen port=8443; tls; listen port=9000 }\n",
+                "client\n",
+            ))
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_patch_errors() {
+        let invalid = |source: &str| Patch::from_document(&parse(source).unwrap()).unwrap_err();
+        assert_eq!(
+            invalid("set \"a\"\n"),
+            PatchError::Invalid {
+                index: 0,
+                reason: "wrong number of arguments"
+            }
+        );
+        assert_eq!(
+            invalid("remove \"a\"\nmove \"a\"").to_string(),
+            "invalid operation 1: unknown operation"
+        );
+        assert!(matches!(
+            invalid("replace \"a\" { b; c }"),
+            PatchError::Invalid { index: 0, .. }
+        ));
+
+        let mut document = parse("a x=1").unwrap();
+        let patch: Patch = [
+            Operation::Unset {
+                path: "a/x".to_owned(),
+            },
+            Operation::Remove {
+                path: "b".to_owned(),
+            },
+        ]
+        .into_iter()
+        .collect();
+        let error = document.apply_patch(&patch).unwrap_err();
+        assert!(matches!(error, PatchError::Apply { index: 1, .. }));
+        assert_eq!(document, parse("a x=1").unwrap());
+    }
+}
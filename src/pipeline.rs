@@ -0,0 +1,279 @@
+//! Transforming documents as streams of events, in memory that is bounded by
+//! how deeply their nodes are nested rather than by their size.
+//!
+//! A pipeline reads the events of [`Parser::events`], changes them with the
+//! adapters of [`EventIterator`], and writes them with
+//! [`Emitter::emit_event`](crate::emit::Emitter::emit_event). No stage holds
+//! more than the nodes that are open, so filtering a document of many
+//! gigabytes takes as little memory as filtering a small one, as long as the
+//! source itself is not read into memory, such as a memory-mapped file.
+//!
+//! ```
+//! # use gpnd::emit::IoEmitter;
+//! # use gpnd::parser::Parser;
+//! # use gpnd::pipeline::EventIterator;
+//! # use gpnd::Node;
+//! let source = "server port=80 {\n    debug\n    tls\n}\ndebug level=2\n";
+//! let mut emitter = IoEmitter::new(Vec::new());
+//! Parser::new(source)
+//!     .events()
+//!     .filter_nodes(|node, _| node.name() != "debug")
+//!     .map_nodes(|node, _| {
+//!         node.params_mut().remove("port");
+//!     })
+//!     .write_to(&mut emitter)
+//!     .unwrap();
+//! assert_eq!(emitter.into_inner(), b"server {\n    tls\n}\n");
+//! ```
+//!
+//! [`Parser::events`]: crate::parser::Parser::events
+
+use std::error::Error;
+use std::{fmt, io};
+
+use crate::emit::IoEmitter;
+use crate::parser::{Event, ParseError};
+use crate::{DocumentBuf, NodeBuf};
+
+/// Adapters for streams of events, such as from
+/// [`Parser::events`](crate::parser::Parser::events).
+///
+/// The depth that the adapters give with each node counts the nodes it is
+/// nested in, so nodes at the top level are at depth `0`. Errors are passed
+/// through as they are.
+pub trait EventIterator: Iterator<Item = Result<Event, ParseError>> + Sized {
+    /// Keep only the nodes for which `predicate` returns `true`. The children
+    /// of a node that is dropped are dropped with it, without being given to
+    /// `predicate`.
+    fn filter_nodes<P>(self, predicate: P) -> FilterNodes<Self, P>
+    where
+        P: FnMut(&NodeBuf, usize) -> bool,
+    {
+        FilterNodes {
+            events: self,
+            predicate,
+            depth: 0,
+            dropped: None,
+        }
+    }
+
+    /// Change each node with `map`, before its children.
+    fn map_nodes<F>(self, map: F) -> MapNodes<Self, F>
+    where
+        F: FnMut(&mut NodeBuf, usize),
+    {
+        MapNodes {
+            events: self,
+            map,
+            depth: 0,
+        }
+    }
+
+    /// Write every event with `emitter`.
+    ///
+    /// # Errors
+    /// Fails with the first error in the stream, or if the writer fails.
+    fn write_to<W>(self, emitter: &mut IoEmitter<W>) -> Result<(), PipelineError>
+    where
+        W: io::Write,
+    {
+        for event in self {
+            emitter.emit_event(&event?)?;
+        }
+        Ok(())
+    }
+
+    /// Collect the events into a document, which holds all of it in memory.
+    ///
+    /// # Errors
+    /// Fails with the first error in the stream.
+    ///
+    /// # Panics
+    /// If there are more [`Event::End`]s than [`Event::Start`]s.
+    fn into_document(self) -> Result<DocumentBuf, ParseError> {
+        let mut document = DocumentBuf::new();
+        let mut open: Vec<NodeBuf> = Vec::new();
+        for event in self {
+            match event? {
+                Event::Version(version) => document.set_version(Some(version)),
+                Event::Start(node) => open.push(*node),
+                Event::End => {
+                    let node = open.pop().expect("an end event without a start");
+                    match open.last_mut() {
+                        Some(parent) => parent.children_mut().push(node),
+                        None => document.nodes_mut().push(node),
+                    }
+                }
+            }
+        }
+        Ok(document)
+    }
+}
+
+impl<I> EventIterator for I where I: Iterator<Item = Result<Event, ParseError>> {}
+
+/// An error from [`EventIterator::write_to`].
+#[derive(Debug)]
+pub enum PipelineError {
+    Parse(ParseError),
+    Io(io::Error),
+}
+
+impl fmt::Display for PipelineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(error) => error.fmt(f),
+            Self::Io(error) => error.fmt(f),
+        }
+    }
+}
+
+impl Error for PipelineError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Parse(error) => Some(error),
+            Self::Io(error) => Some(error),
+        }
+    }
+}
+
+impl From<ParseError> for PipelineError {
+    fn from(error: ParseError) -> Self {
+        Self::Parse(error)
+    }
+}
+
+impl From<io::Error> for PipelineError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+/// The depth of the next node after `event`, which was at `depth`.
+fn next_depth(event: &Event, depth: usize) -> usize {
+    match event {
+        Event::Start(_) => depth + 1,
+        Event::End => depth.saturating_sub(1),
+        Event::Version(_) => depth,
+    }
+}
+
+/// An adapter that drops nodes, see [`EventIterator::filter_nodes`].
+#[derive(Clone, Debug)]
+pub struct FilterNodes<I, P> {
+    events: I,
+    predicate: P,
+    depth: usize,
+    /// The depth of the node being dropped, if any.
+    dropped: Option<usize>,
+}
+
+impl<I, P> Iterator for FilterNodes<I, P>
+where
+    I: Iterator<Item = Result<Event, ParseError>>,
+    P: FnMut(&NodeBuf, usize) -> bool,
+{
+    type Item = Result<Event, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let event = match self.events.next()? {
+                Ok(event) => event,
+                Err(error) => return Some(Err(error)),
+            };
+            let depth = self.depth;
+            self.depth = next_depth(&event, depth);
+            match &event {
+                Event::Start(node) if self.dropped.is_none() && !(self.predicate)(node, depth) => {
+                    self.dropped = Some(depth);
+                }
+                Event::End if self.dropped == Some(self.depth) => {
+                    self.dropped = None;
+                    continue;
+                }
+                _ => {}
+            }
+            if self.dropped.is_none() {
+                return Some(Ok(event));
+            }
+        }
+    }
+}
+
+/// An adapter that changes nodes, see [`EventIterator::map_nodes`].
+#[derive(Clone, Debug)]
+pub struct MapNodes<I, F> {
+    events: I,
+    map: F,
+    depth: usize,
+}
+
+impl<I, F> Iterator for MapNodes<I, F>
+where
+    I: Iterator<Item = Result<Event, ParseError>>,
+    F: FnMut(&mut NodeBuf, usize),
+{
+    type Item = Result<Event, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut event = self.events.next()?;
+        if let Ok(event) = &mut event {
+            let depth = self.depth;
+            self.depth = next_depth(event, depth);
+            if let Event::Start(node) = event {
+                (self.map)(node, depth);
+            }
+        }
+        Some(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{parse, Parser};
+    use crate::{Document, Node, Value};
+
+    #[test]
+    fn test_pipeline() {
+        let source = concat!(
+            "@version \"1\"\n",
+            "a 1 {\n",
+            "    b {\n",
+            "        c\n",
+            "    }\n",
+            "    d k=2 // Comment.\n",
+            "}\n",
+            "b\n",
+        );
+        let document = Parser::new(source).events().into_document().unwrap();
+        assert_eq!(document, parse(source).unwrap());
+        assert_eq!(document.version(), Some("1"));
+
+        let mut depths = Vec::new();
+        let document = Parser::new(source)
+            .events()
+            .filter_nodes(|node, depth| depth == 0 || node.name() != "b")
+            .map_nodes(|node, depth| {
+                depths.push((node.name().to_owned(), depth));
+                node.args_mut().push(Value::I64(depth.try_into().unwrap()));
+            })
+            .into_document()
+            .unwrap();
+        assert_eq!(
+            document,
+            parse("@version \"1\"\na 1 0 { d 1 k=2 }\nb 0").unwrap()
+        );
+        assert_eq!(depths.len(), 3);
+
+        let mut emitter = IoEmitter::new(Vec::new());
+        Parser::new(source).events().write_to(&mut emitter).unwrap();
+        assert_eq!(String::from_utf8(emitter.into_inner()).unwrap(), source);
+
+        let error = Parser::new("a {")
+            .events()
+            .write_to(&mut IoEmitter::new(Vec::new()))
+            .unwrap_err();
+        assert!(matches!(error, PipelineError::Parse(_)));
+    }
+}
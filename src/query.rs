@@ -0,0 +1,864 @@
+//! Selecting nodes with queries like CSS selectors.
+//!
+//! A query is a list of steps, such as `server > listen[port > 1024]`. Each
+//! step names the nodes it matches, or is `*` to match any node, and may be
+//! followed by predicates in brackets. Steps are separated by `>` to select
+//! the children of the previous step, or by whitespace to select any of its
+//! descendants. Steps separated by `+` select the next sibling of the
+//! previous step, and by `~` any later sibling. The first step matches nodes
+//! at any depth, unless the query starts with `>`, which matches only
+//! top-level nodes. Several queries can be separated by `,` to select the
+//! nodes matching any of them.
+//!
+//! Names are matched as by [`Node::is_named`], so `listen` does not match
+//! `web:listen`.
+//!
+//! The predicates are:
+//!
+//! - `[key]`, for nodes that have the parameter `key`.
+//! - `[key = value]`, for nodes whose parameter `key` compares to `value` by
+//!   `=`, `!=`, `<`, `<=`, `>`, or `>=`.
+//! - `[0 = value]`, which compares the argument at an index instead.
+//! - `:has(query)`, for nodes that the query selects any nodes from. It may
+//!   start with any of `>`, `+`, or `~`, and otherwise selects descendants, so
+//!   `group:has(> item)` matches the parents of `item` nodes.
+//! - `:first`, `:last`, and `:nth(n)`, for the first, last, or `n`th (from
+//!   0) of the nodes the step matches among the same siblings. These apply
+//!   after the other predicates, in order.
+//!
+//! Pseudo-classes and namespaces are both written after a `:`, so a
+//! namespace named like a pseudo-class is written as a string, such as
+//! `"first":item`.
+//!
+//! Values are written as in documents, except that bare words other than
+//! `true`, `false`, and `null` are strings. Numbers are compared by value,
+//! see [`Value::numeric_eq`], and strings are ordered by their bytes.
+//!
+//! ```
+//! # use gpnd::Document;
+//! let document = gpnd::parser::parse(
+//!     "server { listen port=80; listen port=8080 }\nclient { listen port=9000 }",
+//! )
+//! .unwrap();
+//! let nodes = document.query("server > listen[port > 1024]").unwrap();
+//! assert_eq!(nodes.len(), 1);
+//! assert_eq!(nodes[0].get_param("port").unwrap(), 8080);
+//! ```
+
+use alloc::borrow::{Cow, ToOwned};
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::error::Error;
+use core::fmt;
+use core::str::FromStr;
+
+use crate::parser::literal::{parse_number, parse_string};
+use crate::parser::ParserConfig;
+use crate::{Document, Node, Span, Value};
+
+/// An error in the text of a query, with the byte offset it was found at.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QueryError {
+    kind: QueryErrorKind,
+    offset: usize,
+}
+
+impl QueryError {
+    #[must_use]
+    pub fn kind(&self) -> &QueryErrorKind {
+        &self.kind
+    }
+
+    /// The byte offset in the query text that the error was found at.
+    #[must_use]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "at offset {}: {}", self.offset, self.kind)
+    }
+}
+
+impl Error for QueryError {}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum QueryErrorKind {
+    /// A token was found where something else was expected, which is
+    /// described.
+    Unexpected {
+        expected: &'static str,
+        found: String,
+    },
+    /// The query ended where something else was expected.
+    UnexpectedEnd { expected: &'static str },
+    /// A value in a predicate is not a valid literal.
+    InvalidValue(String),
+}
+
+impl fmt::Display for QueryErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unexpected { expected, found } => {
+                write!(f, "expected {expected}, found `{found}`")
+            }
+            Self::UnexpectedEnd { expected } => {
+                write!(f, "expected {expected}, found end of query")
+            }
+            Self::InvalidValue(value) => write!(f, "invalid value `{value}`"),
+        }
+    }
+}
+
+/// How a step relates to the nodes selected by the step before it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Combinator {
+    Child,
+    Descendant,
+    /// The next sibling, written `+`.
+    Next,
+    /// Any later sibling, written `~`.
+    Following,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Operand {
+    Param(String),
+    Arg(usize),
+}
+
+impl Operand {
+    fn get<'node>(&self, node: &'node dyn Node) -> Option<Value<'node>> {
+        match self {
+            Self::Param(key) => node.get_param(key),
+            Self::Arg(index) => node.get_arg(*index),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Predicate {
+    Exists(Operand),
+    Compare(Operand, Comparison, Value<'static>),
+    /// A `:has(...)` relative query, which matches nodes that it selects
+    /// any nodes from.
+    Relative(Vec<Step>),
+}
+
+/// Order two values if they are both numbers or both strings.
+fn compare(lhs: &Value<'_>, rhs: &Value<'_>) -> Option<Ordering> {
+    if let (Some(lhs), Some(rhs)) = (lhs.as_integer(), rhs.as_integer()) {
+        return Some(lhs.cmp(&rhs));
+    }
+    if let (Some(lhs), Some(rhs)) = (lhs.as_float(), rhs.as_float()) {
+        return lhs.partial_cmp(&rhs);
+    }
+    match (lhs, rhs) {
+        (Value::String(lhs), Value::String(rhs)) => Some(lhs.cmp(rhs)),
+        _ => None,
+    }
+}
+
+impl Predicate {
+    fn matches(&self, candidate: Candidate<'_>, roots: &[&dyn Node]) -> bool {
+        let node = candidate.node;
+        match self {
+            Self::Exists(operand) => operand.get(node).is_some(),
+            Self::Relative(steps) => !select_steps(Some(&[candidate]), steps, roots).is_empty(),
+            Self::Compare(operand, comparison, value) => {
+                let Some(actual) = operand.get(node) else {
+                    return false;
+                };
+                match comparison {
+                    Comparison::Eq => actual.numeric_eq(value),
+                    Comparison::Ne => !actual.numeric_eq(value),
+                    Comparison::Lt => compare(&actual, value).is_some_and(Ordering::is_lt),
+                    Comparison::Le => compare(&actual, value).is_some_and(Ordering::is_le),
+                    Comparison::Gt => compare(&actual, value).is_some_and(Ordering::is_gt),
+                    Comparison::Ge => compare(&actual, value).is_some_and(Ordering::is_ge),
+                }
+            }
+        }
+    }
+}
+
+/// A position among the siblings that a step matches.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Position {
+    First,
+    Last,
+    Nth(usize),
+}
+
+impl Position {
+    fn matches(self, index: usize, count: usize) -> bool {
+        match self {
+            Self::First => index == 0,
+            Self::Last => index + 1 == count,
+            Self::Nth(nth) => index == nth,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Step {
+    combinator: Combinator,
+    /// The namespace and name to match, or [`None`] for `*`.
+    name: Option<(Option<String>, String)>,
+    predicates: Vec<Predicate>,
+    positions: Vec<Position>,
+}
+
+impl Step {
+    fn matches(&self, candidate: Candidate<'_>, roots: &[&dyn Node]) -> bool {
+        self.name
+            .as_ref()
+            .is_none_or(|(namespace, name)| candidate.node.is_named(namespace.as_deref(), name))
+            && self
+                .predicates
+                .iter()
+                .all(|predicate| predicate.matches(candidate, roots))
+    }
+}
+
+/// A node that a step selected, with its parent, or [`None`] for top-level
+/// nodes.
+#[derive(Copy, Clone)]
+struct Candidate<'doc> {
+    node: &'doc dyn Node,
+    parent: Option<&'doc dyn Node>,
+}
+
+impl<'doc> Candidate<'doc> {
+    fn siblings(self, roots: &[&'doc dyn Node]) -> Vec<&'doc dyn Node> {
+        self.parent.map_or_else(|| roots.to_vec(), Node::children)
+    }
+
+    /// The siblings after this node.
+    fn following(self, roots: &[&'doc dyn Node]) -> Vec<Candidate<'doc>> {
+        let siblings = self.siblings(roots);
+        let position = siblings
+            .iter()
+            .position(|sibling| address(*sibling) == address(self.node));
+        siblings
+            .into_iter()
+            .skip(position.map_or(usize::MAX, |position| position + 1))
+            .map(|node| Candidate {
+                node,
+                parent: self.parent,
+            })
+            .collect()
+    }
+}
+
+/// A parsed query, see the [module](self) documentation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Query {
+    alternatives: Vec<Vec<Step>>,
+}
+
+impl Query {
+    /// Parse the text of a query.
+    ///
+    /// # Errors
+    /// Fails if the query is not valid, see the [module](self)
+    /// documentation.
+    pub fn parse(text: &str) -> Result<Self, QueryError> {
+        QueryParser { text, offset: 0 }.query()
+    }
+
+    /// Select the nodes of `document` that match the query, in depth-first
+    /// order, without duplicates.
+    pub fn select<'doc, D>(&self, document: &'doc D) -> Vec<&'doc dyn Node>
+    where
+        D: Document + ?Sized,
+    {
+        self.selected(document)
+            .into_iter()
+            .map(|(node, _)| node)
+            .collect()
+    }
+
+    /// Select the nodes of `document` that match the query, like
+    /// [`Query::select`], with the spans of the nodes and of the values that
+    /// the predicates of the last step tested.
+    ///
+    /// ```
+    /// # use gpnd::Document;
+    /// let source = "server { listen port=80; listen port=8080 }";
+    /// let document = gpnd::parser::parse(source).unwrap();
+    /// let matches = document.query_matches("listen[port > 1024]").unwrap();
+    /// let span = matches[0].value_spans()[0];
+    /// assert_eq!(&source[span.start.offset..span.end.offset], "8080");
+    /// ```
+    pub fn matches<'doc, D>(&self, document: &'doc D) -> Vec<QueryMatch<'doc>>
+    where
+        D: Document + ?Sized,
+    {
+        self.selected(document)
+            .into_iter()
+            .map(|(node, step)| {
+                let mut value_spans = Vec::new();
+                for predicate in &step.predicates {
+                    let (Predicate::Exists(operand) | Predicate::Compare(operand, _, _)) =
+                        predicate
+                    else {
+                        continue;
+                    };
+                    let span = match operand {
+                        Operand::Param(key) => node.param_span(key),
+                        Operand::Arg(index) => node.arg_span(*index),
+                    };
+                    if let Some(span) = span.filter(|span| !value_spans.contains(span)) {
+                        value_spans.push(span);
+                    }
+                }
+                QueryMatch { node, value_spans }
+            })
+            .collect()
+    }
+
+    /// The selected nodes in depth-first order, each with the last step of
+    /// the first alternative that selected it.
+    fn selected<'doc, D>(&self, document: &'doc D) -> Vec<(&'doc dyn Node, &Step)>
+    where
+        D: Document + ?Sized,
+    {
+        let roots = document.nodes();
+        let mut selected = BTreeMap::new();
+        for steps in &self.alternatives {
+            let last = steps.last().expect("queries have a step");
+            for candidate in select_steps(None, steps, &roots) {
+                selected.entry(address(candidate.node)).or_insert(last);
+            }
+        }
+        document
+            .iter_dfs()
+            .filter_map(|(node, _)| Some((node, *selected.get(&address(node))?)))
+            .collect()
+    }
+}
+
+/// A node selected by [`Query::matches`], with where it was parsed from.
+#[derive(Clone)]
+pub struct QueryMatch<'doc> {
+    node: &'doc dyn Node,
+    value_spans: Vec<Span>,
+}
+
+impl<'doc> QueryMatch<'doc> {
+    #[must_use]
+    pub fn node(&self) -> &'doc dyn Node {
+        self.node
+    }
+
+    /// The span of the node, see [`Node::span`].
+    #[must_use]
+    pub fn span(&self) -> Option<Span> {
+        self.node.span()
+    }
+
+    /// The spans of the argument and parameter values that the predicates of
+    /// the last step of the query tested, in the order they were tested.
+    #[must_use]
+    pub fn value_spans(&self) -> &[Span] {
+        &self.value_spans
+    }
+}
+
+impl fmt::Debug for QueryMatch<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QueryMatch")
+            .field("node", &self.node.name())
+            .field("span", &self.span())
+            .field("value_spans", &self.value_spans)
+            .finish()
+    }
+}
+
+impl FromStr for Query {
+    type Err = QueryError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Self::parse(text)
+    }
+}
+
+/// The address of a node, which identifies it within a document.
+fn address(node: &dyn Node) -> *const () {
+    core::ptr::from_ref(node).cast()
+}
+
+/// Push the descendants of `node` onto `nodes`, depth-first.
+fn descendants<'doc>(node: &'doc dyn Node, nodes: &mut Vec<Candidate<'doc>>) {
+    for child in node.children() {
+        nodes.push(Candidate {
+            node: child,
+            parent: Some(node),
+        });
+        descendants(child, nodes);
+    }
+}
+
+/// Select the nodes that `steps` match, starting from `context`, or from the
+/// document if there is none.
+fn select_steps<'doc>(
+    context: Option<&[Candidate<'doc>]>,
+    steps: &[Step],
+    roots: &[&'doc dyn Node],
+) -> Vec<Candidate<'doc>> {
+    let mut current = context.map(<[_]>::to_vec);
+    for step in steps {
+        let mut candidates = Vec::new();
+        match (&current, step.combinator) {
+            (None, Combinator::Child) => {
+                candidates.extend(roots.iter().map(|&node| Candidate { node, parent: None }));
+            }
+            (None, Combinator::Descendant) => {
+                for &node in roots {
+                    candidates.push(Candidate { node, parent: None });
+                    descendants(node, &mut candidates);
+                }
+            }
+            (None, Combinator::Next | Combinator::Following) => {}
+            (Some(current), Combinator::Child) => {
+                for candidate in current {
+                    candidates.extend(candidate.node.children().into_iter().map(|node| {
+                        Candidate {
+                            node,
+                            parent: Some(candidate.node),
+                        }
+                    }));
+                }
+            }
+            (Some(current), Combinator::Descendant) => {
+                for candidate in current {
+                    descendants(candidate.node, &mut candidates);
+                }
+            }
+            (Some(current), Combinator::Next) => {
+                for candidate in current {
+                    candidates.extend(candidate.following(roots).into_iter().take(1));
+                }
+            }
+            (Some(current), Combinator::Following) => {
+                for candidate in current {
+                    candidates.extend(candidate.following(roots));
+                }
+            }
+        }
+        let mut seen = BTreeSet::new();
+        candidates.retain(|candidate| {
+            step.matches(*candidate, roots) && seen.insert(address(candidate.node))
+        });
+        for position in &step.positions {
+            let parent = |candidate: &Candidate<'_>| candidate.parent.map(address);
+            let mut counts = BTreeMap::new();
+            for candidate in &candidates {
+                *counts.entry(parent(candidate)).or_insert(0) += 1;
+            }
+            let mut indices = BTreeMap::new();
+            candidates.retain(|candidate| {
+                let index = indices.entry(parent(candidate)).or_insert(0);
+                *index += 1;
+                position.matches(*index - 1, counts[&parent(candidate)])
+            });
+        }
+        current = Some(candidates);
+    }
+    current.unwrap_or_default()
+}
+
+/// Whether `ch` can be part of a name, key, or bare value.
+fn is_word_char(ch: char) -> bool {
+    !ch.is_whitespace() && !"[]()>,=!<\":*".contains(ch)
+}
+
+struct QueryParser<'text> {
+    text: &'text str,
+    offset: usize,
+}
+
+impl<'text> QueryParser<'text> {
+    fn rest(&self) -> &'text str {
+        &self.text[self.offset..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    /// Skip whitespace, returning whether there was any.
+    fn skip_whitespace(&mut self) -> bool {
+        let rest = self.rest();
+        let trimmed = rest.trim_start();
+        self.offset += rest.len() - trimmed.len();
+        rest.len() != trimmed.len()
+    }
+
+    fn eat(&mut self, prefix: &str) -> bool {
+        let found = self.rest().starts_with(prefix);
+        if found {
+            self.offset += prefix.len();
+        }
+        found
+    }
+
+    fn error(&self, expected: &'static str) -> QueryError {
+        let kind = match self.peek() {
+            Some(ch) => QueryErrorKind::Unexpected {
+                expected,
+                found: ch.to_string(),
+            },
+            None => QueryErrorKind::UnexpectedEnd { expected },
+        };
+        QueryError {
+            kind,
+            offset: self.offset,
+        }
+    }
+
+    fn query(&mut self) -> Result<Query, QueryError> {
+        let mut alternatives = vec![self.steps(false)?];
+        while self.eat(",") {
+            alternatives.push(self.steps(false)?);
+        }
+        match self.peek() {
+            None => Ok(Query { alternatives }),
+            Some(_) => Err(self.error("`,` or a step")),
+        }
+    }
+
+    fn combinator(&mut self) -> Option<Combinator> {
+        [
+            (">", Combinator::Child),
+            ("+", Combinator::Next),
+            ("~", Combinator::Following),
+        ]
+        .into_iter()
+        .find_map(|(text, combinator)| self.eat(text).then_some(combinator))
+    }
+
+    /// Read the steps of a query, which is `relative` in `:has(...)`, where
+    /// it can start with any combinator.
+    fn steps(&mut self, relative: bool) -> Result<Vec<Step>, QueryError> {
+        self.skip_whitespace();
+        let start = self.offset;
+        let mut combinator = match self.combinator() {
+            Some(Combinator::Next | Combinator::Following) if !relative => {
+                self.offset = start;
+                return Err(self.error("`>` or a step"));
+            }
+            Some(combinator) => combinator,
+            None => Combinator::Descendant,
+        };
+        let mut steps = Vec::new();
+        loop {
+            self.skip_whitespace();
+            steps.push(self.step(combinator)?);
+            let space = self.skip_whitespace();
+            combinator = match self.combinator() {
+                Some(combinator) => combinator,
+                None if space && !matches!(self.peek(), None | Some(',' | ')')) => {
+                    Combinator::Descendant
+                }
+                None => return Ok(steps),
+            };
+        }
+    }
+
+    fn step(&mut self, combinator: Combinator) -> Result<Step, QueryError> {
+        let name = if self.eat("*") {
+            None
+        } else {
+            let name = self.word("a node name or `*`")?.into_owned();
+            let start = self.offset;
+            if self.eat(":") && self.pseudo_class().is_none() {
+                let local = self.word("a node name")?.into_owned();
+                Some((Some(name), local))
+            } else {
+                self.offset = start;
+                Some((None, name))
+            }
+        };
+        let mut step = Step {
+            combinator,
+            name,
+            predicates: Vec::new(),
+            positions: Vec::new(),
+        };
+        loop {
+            if self.eat("[") {
+                step.predicates.push(self.predicate()?);
+            } else if self.eat(":") {
+                match self.pseudo_class() {
+                    Some("first") => step.positions.push(Position::First),
+                    Some("last") => step.positions.push(Position::Last),
+                    Some("nth") => {
+                        let start = self.offset;
+                        let nth = self.word("an index")?;
+                        let nth = nth.parse().map_err(|_| QueryError {
+                            kind: QueryErrorKind::InvalidValue(nth.into_owned()),
+                            offset: start,
+                        })?;
+                        self.close()?;
+                        step.positions.push(Position::Nth(nth));
+                    }
+                    Some(_) => {
+                        let steps = self.steps(true)?;
+                        self.close()?;
+                        step.predicates.push(Predicate::Relative(steps));
+                    }
+                    None => return Err(self.error("`first`, `last`, `nth(`, or `has(`")),
+                }
+            } else {
+                return Ok(step);
+            }
+        }
+    }
+
+    /// Read the name of a pseudo-class after a `:`, with the `(` that opens
+    /// its argument.
+    fn pseudo_class(&mut self) -> Option<&'static str> {
+        let found = ["first", "last", "nth(", "has("]
+            .into_iter()
+            .find(|name| self.rest().starts_with(name))?;
+        let after = self.rest()[found.len()..].chars().next();
+        if !found.ends_with('(') && after.is_some_and(is_word_char) {
+            return None;
+        }
+        self.offset += found.len();
+        Some(found.trim_end_matches('('))
+    }
+
+    fn close(&mut self) -> Result<(), QueryError> {
+        self.skip_whitespace();
+        if self.eat(")") {
+            Ok(())
+        } else {
+            Err(self.error("`)`"))
+        }
+    }
+
+    /// Read a bare or quoted word.
+    fn word(&mut self, expected: &'static str) -> Result<Cow<'text, str>, QueryError> {
+        let start = self.offset;
+        if self.peek() == Some('"') {
+            let text = self.string()?;
+            return parse_string(text).map_err(|_| QueryError {
+                kind: QueryErrorKind::InvalidValue(text.to_owned()),
+                offset: start,
+            });
+        }
+        let rest = self.rest();
+        let length = rest.find(|ch| !is_word_char(ch)).unwrap_or(rest.len());
+        if length == 0 {
+            return Err(self.error(expected));
+        }
+        self.offset += length;
+        Ok(Cow::Borrowed(&rest[..length]))
+    }
+
+    /// Read a quoted string, including its quotes.
+    fn string(&mut self) -> Result<&'text str, QueryError> {
+        let rest = self.rest();
+        let mut escaped = false;
+        for (index, ch) in rest.char_indices().skip(1) {
+            match ch {
+                '"' if !escaped => {
+                    self.offset += index + 1;
+                    return Ok(&rest[..=index]);
+                }
+                '\\' => escaped = !escaped,
+                _ => escaped = false,
+            }
+        }
+        self.offset += rest.len();
+        Err(self.error("`\"`"))
+    }
+
+    fn predicate(&mut self) -> Result<Predicate, QueryError> {
+        self.skip_whitespace();
+        let key = self.word("a parameter key or argument index")?;
+        let operand = match key.parse() {
+            Ok(index) => Operand::Arg(index),
+            Err(_) => Operand::Param(key.into_owned()),
+        };
+        self.skip_whitespace();
+        if self.eat("]") {
+            return Ok(Predicate::Exists(operand));
+        }
+        let comparison = [
+            ("!=", Comparison::Ne),
+            ("<=", Comparison::Le),
+            (">=", Comparison::Ge),
+            ("=", Comparison::Eq),
+            ("<", Comparison::Lt),
+            (">", Comparison::Gt),
+        ]
+        .into_iter()
+        .find_map(|(text, comparison)| self.eat(text).then_some(comparison))
+        .ok_or_else(|| self.error("a comparison or `]`"))?;
+        self.skip_whitespace();
+        let value = self.value()?;
+        self.skip_whitespace();
+        if !self.eat("]") {
+            return Err(self.error("`]`"));
+        }
+        Ok(Predicate::Compare(operand, comparison, value))
+    }
+
+    fn value(&mut self) -> Result<Value<'static>, QueryError> {
+        let start = self.offset;
+        if self.peek() == Some('"') {
+            return Ok(Value::String(Cow::Owned(
+                self.word("a value")?.into_owned(),
+            )));
+        }
+        let word = self.word("a value")?;
+        Ok(match &*word {
+            "true" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            "null" => Value::Null,
+            text if text.starts_with(|ch: char| ch.is_ascii_digit() || "+-.".contains(ch)) => {
+                parse_number(text, &ParserConfig::default()).map_err(|_| QueryError {
+                    kind: QueryErrorKind::InvalidValue(text.to_owned()),
+                    offset: start,
+                })?
+            }
+            text => Value::String(Cow::Owned(text.to_owned())),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_query() {
+        let document = parse(concat!(
+            "server \"main\" { listen port=80; listen port=8080 tls=true; log }\n",
+            "server \"backup\" { group { listen port=9000 } }\n",
+            "web:listen port=443\n",
+        ))
+        .unwrap();
+        let ports = |query: &str| -> Vec<String> {
+            document
+                .query(query)
+                .unwrap()
+                .iter()
+                .map(|node| node.get_param("port").unwrap().to_string())
+                .collect()
+        };
+        assert_eq!(ports("listen"), ["80", "8080", "9000"]);
+        assert_eq!(ports("server > listen"), ["80", "8080"]);
+        assert_eq!(ports("server listen"), ["80", "8080", "9000"]);
+        assert_eq!(ports("server>*>listen"), ["9000"]);
+        assert!(ports("> listen").is_empty());
+        assert_eq!(ports("> web:listen"), ["443"]);
+        assert_eq!(ports("listen[port > 1024]"), ["8080", "9000"]);
+        assert_eq!(ports("listen[port != 80][port <= 8080]"), ["8080"]);
+        assert_eq!(ports("listen[tls]"), ["8080"]);
+        assert_eq!(
+            ports("server[0 = main] listen, web:listen"),
+            ["80", "8080", "443"]
+        );
+        assert_eq!(ports("server[0=\"backup\"] listen"), ["9000"]);
+        assert_eq!(document.query("server, *").unwrap().len(), 8);
+    }
+
+    #[test]
+    fn test_query_axes() {
+        let document = parse(concat!(
+            "group { item 0; item 1; note; item 2 }\n",
+            "list { item 3; group { item 4 } }\n",
+            "item 5\n",
+        ))
+        .unwrap();
+        let args = |query: &str| -> Vec<String> {
+            document
+                .query(query)
+                .unwrap()
+                .iter()
+                .map(|node| node.args()[0].to_string())
+                .collect()
+        };
+        assert_eq!(args("group > item"), ["0", "1", "2", "4"]);
+        assert_eq!(args("item:first"), ["0", "3", "4", "5"]);
+        assert_eq!(args("group > item:last"), ["2", "4"]);
+        assert_eq!(args("> group > item:nth(1)"), ["1"]);
+        assert_eq!(args("item[0 > 0]:first"), ["1", "3", "4", "5"]);
+        assert_eq!(args("note + item, note ~ *"), ["2"]);
+        assert_eq!(args("item:first ~ item"), ["1", "2"]);
+        assert_eq!(args("> list + item"), ["5"]);
+        assert_eq!(args("*:has(> note) item"), ["0", "1", "2"]);
+        assert_eq!(args("list:has(group item) > item"), ["3"]);
+        assert_eq!(args("item:has(+ note)"), ["1"]);
+        assert_eq!(document.query("*:has(~ item)").unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_query_matches() {
+        let source = "a 1 x=2 { b y=3 }\nb 4 y=5";
+        let document = parse(source).unwrap();
+        let text = |span: Span| &source[span.start.offset..span.end.offset];
+        let matches = document.query_matches("a[0 = 1][x][0], b[y > 3]").unwrap();
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].node().name(), "a");
+        let values: Vec<_> = matches[0]
+            .value_spans()
+            .iter()
+            .map(|span| text(*span))
+            .collect();
+        assert_eq!(values, ["1", "2"]);
+        assert_eq!(text(matches[1].span().unwrap()), "b 4 y=5");
+        assert_eq!(text(matches[1].value_spans()[0]), "5");
+
+        let matches = document.query_matches("b").unwrap();
+        assert!(matches.iter().all(|found| found.value_spans().is_empty()));
+    }
+
+    #[test]
+    fn test_query_errors() {
+        let error = |query: &str| Query::parse(query).unwrap_err();
+        assert_eq!(
+            error("a >").kind(),
+            &QueryErrorKind::UnexpectedEnd {
+                expected: "a node name or `*`"
+            }
+        );
+        assert_eq!(error("a[port ~ 1]").offset(), 7);
+        assert_eq!(error("a[port = 1").offset(), 10);
+        assert_eq!(
+            error("a[port = 1x]").kind(),
+            &QueryErrorKind::InvalidValue("1x".to_owned())
+        );
+        assert_eq!(
+            error("a]").to_string(),
+            "at offset 1: expected `,` or a step, found `]`"
+        );
+        assert_eq!(error("+ a").offset(), 0);
+        assert_eq!(
+            error("a:nth(x)").kind(),
+            &QueryErrorKind::InvalidValue("x".to_owned())
+        );
+        assert_eq!(error("a:has(b").offset(), 7);
+    }
+}
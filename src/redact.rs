@@ -0,0 +1,203 @@
+//! Hiding secret values, so that documents can be logged or displayed.
+//!
+//! A [`Redactor`] matches parameter keys against patterns, where `*` matches
+//! any text and letters match regardless of case, and replaces the values of
+//! the parameters that match with a placeholder. Entries of map values are
+//! matched by their keys in the same way.
+//!
+//! ```
+//! # use gpnd::redact::Redactor;
+//! # use gpnd::{Document, Node};
+//! let document = gpnd::parser::parse(r#"db user="app" password="hunter2""#).unwrap();
+//! let redacted = document.redact(&Redactor::default());
+//! let db = redacted.get_node(0).unwrap();
+//! assert_eq!(db.get_param("user").unwrap(), "app");
+//! assert_eq!(db.get_param("password").unwrap(), "<redacted>");
+//! ```
+
+use alloc::borrow::{Cow, ToOwned};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{NodeBuf, Value};
+
+/// The placeholder that values are replaced with, unless another is given.
+pub const DEFAULT_PLACEHOLDER: &str = "<redacted>";
+
+/// The patterns that [`Redactor::default`] matches.
+pub const DEFAULT_PATTERNS: &[&str] = &[
+    "*password*",
+    "*passwd*",
+    "*secret*",
+    "*token*",
+    "*api_key*",
+    "*apikey*",
+    "*private_key*",
+    "*credential*",
+];
+
+/// Decides which values [`Document::redact`](crate::Document::redact)
+/// replaces, see the [module](self) documentation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Redactor {
+    patterns: Vec<String>,
+    placeholder: String,
+}
+
+impl Redactor {
+    /// Match the keys that match any of `patterns`.
+    #[must_use]
+    pub fn new<I, S>(patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            patterns: patterns.into_iter().map(Into::into).collect(),
+            placeholder: DEFAULT_PLACEHOLDER.to_owned(),
+        }
+    }
+
+    /// Replace values with `placeholder`, rather than the
+    /// [`DEFAULT_PLACEHOLDER`].
+    #[must_use]
+    pub fn with_placeholder<S>(mut self, placeholder: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.placeholder = placeholder.into();
+        self
+    }
+
+    #[must_use]
+    pub fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
+
+    #[must_use]
+    pub fn placeholder(&self) -> &str {
+        &self.placeholder
+    }
+
+    /// Whether the values of `key` are redacted.
+    #[must_use]
+    pub fn matches(&self, key: &str) -> bool {
+        self.patterns.iter().any(|pattern| glob_match(pattern, key))
+    }
+
+    fn redact_value(&self, value: &mut Value<'static>) {
+        match value {
+            Value::List(items) => items.iter_mut().for_each(|item| self.redact_value(item)),
+            Value::Map(entries) => {
+                for (key, value) in entries.iter_mut() {
+                    if self.matches(key) {
+                        *value = self.placeholder_value();
+                    } else {
+                        self.redact_value(value);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn placeholder_value(&self) -> Value<'static> {
+        Value::String(Cow::Owned(self.placeholder.clone()))
+    }
+
+    pub(crate) fn redact_node(&self, node: &mut NodeBuf) {
+        node.args_mut()
+            .iter_mut()
+            .for_each(|value| self.redact_value(value));
+        for (key, value) in node.params_mut().iter_mut() {
+            if self.matches(key) {
+                *value = self.placeholder_value();
+            } else {
+                self.redact_value(value);
+            }
+        }
+        node.children_mut()
+            .iter_mut()
+            .for_each(|child| self.redact_node(child));
+    }
+}
+
+/// Match the [`DEFAULT_PATTERNS`].
+impl Default for Redactor {
+    fn default() -> Self {
+        Self::new(DEFAULT_PATTERNS.iter().copied())
+    }
+}
+
+/// Whether `text` matches `pattern`, where `*` matches any text, ignoring
+/// ASCII case.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // The position of the last `*` in the pattern, and of the text after it.
+    let mut star = None;
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if p < pattern.len() && pattern[p].eq_ignore_ascii_case(&text[t]) {
+            p += 1;
+            t += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|ch| *ch == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+    use crate::Document;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*token*", "github_TOKEN"));
+        assert!(glob_match("*token*", "token"));
+        assert!(glob_match("a*b*c", "axxbyyc"));
+        assert!(!glob_match("a*b*c", "axxbyy"));
+        assert!(glob_match("key", "KEY"));
+        assert!(!glob_match("key", "keys"));
+        assert!(glob_match("*", ""));
+    }
+
+    #[test]
+    fn test_redact() {
+        let document = parse(concat!(
+            "@version \"1\"\n",
+            "db password=\"a\" host=\"h\" options=#{ auth_token=\"b\" port=1 } {\n",
+            "    user \"c\" Secret=[1 2]\n",
+            "}\n",
+        ))
+        .unwrap();
+        let redactor = Redactor::default().with_placeholder("***");
+        let redacted = document.redact(&redactor);
+        assert_eq!(
+            redacted,
+            parse(concat!(
+                "@version \"1\"\n",
+                "db password=\"***\" host=\"h\" options=#{ auth_token=\"***\" port=1 } {\n",
+                "    user \"c\" Secret=\"***\"\n",
+                "}\n",
+            ))
+            .unwrap()
+        );
+
+        let redacted = document.redact(&Redactor::new(["host", "*"]));
+        assert!(redacted
+            .iter_dfs()
+            .flat_map(|(node, _)| node.params())
+            .all(|(_, value)| value == DEFAULT_PLACEHOLDER));
+    }
+}
@@ -0,0 +1,374 @@
+//! Schemas written as documents, so that they can be shipped as files.
+//!
+//! A schema document lists the allowed nodes with `node` entries, which
+//! describe their arguments, parameters, and children in their own blocks:
+//!
+//! ```text
+//! node "server" min=1 max=1 {
+//!     arg "string"
+//!     arg "integer" optional=true
+//!     param "port" "integer" required=true min=1 max=65535
+//!     param "tags" "list"
+//!     node "route" {
+//!         rest "string"
+//!         allow-unknown-params
+//!     }
+//! }
+//! allow-unknown-nodes
+//! ```
+//!
+//! - `node "name"` allows a node, `min` to `max` times, by default any number
+//!   of times, and warns about it if `deprecated=true`.
+//! - `arg "type"` expects another argument, or allows it if `optional=true`.
+//! - `rest "type"` allows any number of arguments after the declared ones.
+//! - `param "key" "type"` allows a parameter, or expects it if `required=true`.
+//!   Integer parameters can be restricted with `min` and `max`, which are
+//!   inclusive.
+//! - `allow-unknown-nodes` and `allow-unknown-params` allow nodes and
+//!   parameters that are not declared.
+//!
+//! Types are `any`, `bool`, `integer`, `float`, `number`, `string`, `list`,
+//! `map`, and `null`, as in [`ValueType`], or the name of a [`ValueKind`],
+//! such as `u16`, for values of exactly that kind.
+
+use alloc::borrow::ToOwned;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt;
+
+use super::{Cardinality, IntegerRange, NodeSchema, Schema, ValueType};
+use crate::diff::occurrences;
+use crate::{Document, Node, NodePath, Span, Value, ValueKind};
+
+/// What is wrong with an entry of a schema document, see [`SchemaError`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SchemaErrorKind {
+    /// An entry that is not allowed where it appears.
+    UnknownEntry(String),
+    UnknownType(String),
+    /// A parameter of an entry that it does not take.
+    UnknownParam(String),
+    /// An entry with the wrong arguments, or parameters of the wrong type.
+    Invalid(&'static str),
+}
+
+impl fmt::Display for SchemaErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownEntry(name) => write!(f, "unknown entry `{name}`"),
+            Self::UnknownType(name) => write!(f, "unknown type `{name}`"),
+            Self::UnknownParam(key) => write!(f, "unknown parameter `{key}`"),
+            Self::Invalid(reason) => f.write_str(reason),
+        }
+    }
+}
+
+/// An error in a schema document, with the path and span of the entry it was
+/// found on, as in a [`Violation`](super::Violation).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SchemaError {
+    pub path: NodePath,
+    pub span: Option<Span>,
+    pub kind: SchemaErrorKind,
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.span {
+            Some(span) => write!(f, "{} (at {span}): {}", self.path, self.kind),
+            None => write!(f, "{}: {}", self.path, self.kind),
+        }
+    }
+}
+
+impl Error for SchemaError {}
+
+/// Read the type named `name`.
+fn value_type(name: &str) -> Option<ValueType> {
+    Some(match name {
+        "any" => ValueType::Any,
+        "bool" => ValueType::Bool,
+        "integer" => ValueType::Integer,
+        "float" => ValueType::Float,
+        "number" => ValueType::Number,
+        "string" => ValueType::String,
+        "list" => ValueType::List,
+        "map" => ValueType::Map,
+        "null" => ValueType::Null,
+        _ => ValueType::Kind(
+            *ValueKind::ALL
+                .iter()
+                .find(|kind| kind.as_str().eq_ignore_ascii_case(name))?,
+        ),
+    })
+}
+
+/// An entry of a schema document, with its path.
+struct Entry<'doc> {
+    node: &'doc dyn Node,
+    path: NodePath,
+}
+
+impl Entry<'_> {
+    fn error(&self, kind: SchemaErrorKind) -> SchemaError {
+        SchemaError {
+            path: self.path.clone(),
+            span: self.node.span(),
+            kind,
+        }
+    }
+
+    /// Check that the entry has `args` string arguments, and only the
+    /// parameters `keys`, and return the arguments.
+    fn strings(&self, args: usize, keys: &[&str]) -> Result<Vec<String>, SchemaError> {
+        if let Some((key, _)) = self
+            .node
+            .params()
+            .into_iter()
+            .find(|(key, _)| !keys.contains(key))
+        {
+            return Err(self.error(SchemaErrorKind::UnknownParam(key.to_owned())));
+        }
+        let strings: Vec<_> = self
+            .node
+            .args()
+            .iter()
+            .filter_map(|value| match value {
+                Value::String(text) => Some(text.to_string()),
+                _ => None,
+            })
+            .collect();
+        if self.node.args().len() != args || strings.len() != args {
+            let reason = match args {
+                0 => "expected no arguments",
+                1 => "expected one string argument",
+                _ => "expected two string arguments",
+            };
+            return Err(self.error(SchemaErrorKind::Invalid(reason)));
+        }
+        Ok(strings)
+    }
+
+    fn value_type(&self, name: &str) -> Result<ValueType, SchemaError> {
+        value_type(name).ok_or_else(|| self.error(SchemaErrorKind::UnknownType(name.to_owned())))
+    }
+
+    fn flag(&self, key: &str) -> Result<bool, SchemaError> {
+        match self.node.get_param(key) {
+            None => Ok(false),
+            Some(Value::Bool(flag)) => Ok(flag),
+            Some(_) => Err(self.error(SchemaErrorKind::Invalid("expected a bool"))),
+        }
+    }
+
+    fn bound(&self, key: &str) -> Result<Option<i128>, SchemaError> {
+        self.node
+            .get_param(key)
+            .map(|value| {
+                value
+                    .as_integer()
+                    .ok_or_else(|| self.error(SchemaErrorKind::Invalid("expected an integer")))
+            })
+            .transpose()
+    }
+
+    fn count(&self, key: &str) -> Result<Option<usize>, SchemaError> {
+        self.node
+            .get_param(key)
+            .map(|value| {
+                value
+                    .as_integer()
+                    .and_then(|count| usize::try_from(count).ok())
+                    .ok_or_else(|| {
+                        self.error(SchemaErrorKind::Invalid("expected a non-negative integer"))
+                    })
+            })
+            .transpose()
+    }
+
+    /// Check that the entry has no children.
+    fn leaf(&self) -> Result<(), SchemaError> {
+        if self.node.has_children() {
+            return Err(self.error(SchemaErrorKind::Invalid("unexpected children")));
+        }
+        Ok(())
+    }
+}
+
+fn entries<'doc>(parent: &NodePath, nodes: &[&'doc dyn Node]) -> Vec<Entry<'doc>> {
+    nodes
+        .iter()
+        .zip(occurrences(nodes))
+        .map(|(node, key)| Entry {
+            node: *node,
+            path: parent.child(key.0, key.1),
+        })
+        .collect()
+}
+
+/// Read a `node` entry, with its cardinality.
+fn node_schema(entry: &Entry<'_>) -> Result<(NodeSchema, Cardinality), SchemaError> {
+    let [name] = <[String; 1]>::try_from(entry.strings(1, &["min", "max", "deprecated"])?)
+        .expect("one argument was checked");
+    let cardinality = Cardinality::range(entry.count("min")?.unwrap_or(0), entry.count("max")?);
+    let mut schema = NodeSchema::new(name);
+    if entry.flag("deprecated")? {
+        schema = schema.deprecated();
+    }
+    for child in entries(&entry.path, &entry.node.children()) {
+        match child.node.name() {
+            "node" => {
+                let (node, cardinality) = node_schema(&child)?;
+                schema = schema.child(node, cardinality);
+                continue;
+            }
+            "arg" => {
+                let [ty] = <[String; 1]>::try_from(child.strings(1, &["optional"])?)
+                    .expect("one argument was checked");
+                let ty = child.value_type(&ty)?;
+                schema = if child.flag("optional")? {
+                    schema.optional_arg(ty)
+                } else {
+                    schema.arg(ty)
+                };
+            }
+            "rest" => {
+                let [ty] = <[String; 1]>::try_from(child.strings(1, &[])?)
+                    .expect("one argument was checked");
+                schema = schema.rest_args(child.value_type(&ty)?);
+            }
+            "param" => {
+                let [key, ty] =
+                    <[String; 2]>::try_from(child.strings(2, &["required", "min", "max"])?)
+                        .expect("two arguments were checked");
+                let ty = child.value_type(&ty)?;
+                let range = IntegerRange::new(child.bound("min")?, child.bound("max")?);
+                if range.min.is_some() || range.max.is_some() {
+                    schema = schema.param_range(key.as_str(), range);
+                }
+                schema = if child.flag("required")? {
+                    schema.required_param(key, ty)
+                } else {
+                    schema.param(key, ty)
+                };
+            }
+            "allow-unknown-params" => {
+                child.strings(0, &[])?;
+                schema = schema.allow_unknown_params();
+            }
+            "allow-unknown-nodes" => {
+                child.strings(0, &[])?;
+                schema = schema.allow_unknown_children();
+            }
+            name => return Err(child.error(SchemaErrorKind::UnknownEntry(name.to_owned()))),
+        }
+        child.leaf()?;
+    }
+    Ok((schema, cardinality))
+}
+
+impl Schema {
+    /// Read a schema written as a document, see the [module](self)
+    /// documentation.
+    ///
+    /// ```
+    /// # use gpnd::schema::{validate, Schema};
+    /// let schema =
+    ///     gpnd::parser::parse(r#"node "server" { param "port" "u16" required=true }"#).unwrap();
+    /// let schema = Schema::from_document(&schema).unwrap();
+    /// assert!(validate(&gpnd::parser::parse("server port=80").unwrap(), &schema).is_err());
+    /// ```
+    pub fn from_document<D>(document: &D) -> Result<Self, SchemaError>
+    where
+        D: Document + ?Sized,
+    {
+        let mut schema = Self::new();
+        for entry in entries(&NodePath::new(), &document.nodes()) {
+            match entry.node.name() {
+                "node" => {
+                    let (node, cardinality) = node_schema(&entry)?;
+                    schema = schema.node(node, cardinality);
+                }
+                "allow-unknown-nodes" => {
+                    entry.strings(0, &[])?;
+                    entry.leaf()?;
+                    schema = schema.allow_unknown_nodes();
+                }
+                name => return Err(entry.error(SchemaErrorKind::UnknownEntry(name.to_owned()))),
+            }
+        }
+        Ok(schema)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_schema_from_document() {
+        let document = parse(concat!(
+            "node \"server\" min=1 max=1 {\n",
+            "    arg \"string\"\n",
+            "    arg \"integer\" optional=true\n",
+            "    param \"port\" \"U16\" required=true min=1\n",
+            "    param \"tags\" \"list\"\n",
+            "    node \"route\" {\n",
+            "        rest \"string\"\n",
+            "        allow-unknown-params\n",
+            "    }\n",
+            "}\n",
+            "allow-unknown-nodes\n",
+        ))
+        .unwrap();
+        let expected = Schema::new()
+            .node(
+                NodeSchema::new("server")
+                    .arg(ValueType::String)
+                    .optional_arg(ValueType::Integer)
+                    .param_range("port", IntegerRange::new(Some(1), None))
+                    .required_param("port", ValueType::Kind(ValueKind::U16))
+                    .param("tags", ValueType::List)
+                    .child(
+                        NodeSchema::new("route")
+                            .rest_args(ValueType::String)
+                            .allow_unknown_params(),
+                        Cardinality::ANY,
+                    ),
+                Cardinality::ONE,
+            )
+            .allow_unknown_nodes();
+        assert_eq!(Schema::from_document(&document).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_schema_document_errors() {
+        let error = |source: &str| Schema::from_document(&parse(source).unwrap()).unwrap_err();
+        assert_eq!(
+            error("node \"a\" { arg \"text\" }").to_string(),
+            "node > arg (at 1:12): unknown type `text`"
+        );
+        assert_eq!(
+            error("node \"a\"\nnode \"b\" { param \"x\" }").kind,
+            SchemaErrorKind::Invalid("expected two string arguments")
+        );
+        assert_eq!(
+            error("node \"a\" count=1").kind,
+            SchemaErrorKind::UnknownParam("count".to_owned())
+        );
+        assert_eq!(
+            error("node \"a\" min=-1").kind,
+            SchemaErrorKind::Invalid("expected a non-negative integer")
+        );
+        assert_eq!(
+            error("node \"a\" { rest \"any\" { b } }").path.to_string(),
+            "node > rest"
+        );
+        assert_eq!(
+            error("field \"a\"").kind,
+            SchemaErrorKind::UnknownEntry("field".to_owned())
+        );
+    }
+}
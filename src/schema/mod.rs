@@ -0,0 +1,936 @@
+//! Declaring the expected shape of documents, and checking documents against
+//! it.
+//!
+//! A [`Schema`] lists the nodes allowed at the top level of a document, each
+//! described by a [`NodeSchema`] and how many times it may appear. Node
+//! schemas describe arguments, parameters, and children in the same way.
+//!
+//! [`validate`] reports every violation it finds, rather than stopping at the
+//! first. [`validate_with_warnings`] also reports [`Warning`]s for documents
+//! that match, such as deprecated nodes and duplicate parameters.
+
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+pub mod meta;
+pub mod reflect;
+
+pub use meta::*;
+pub use reflect::*;
+
+use crate::diff::occurrences;
+use crate::lint::{push_limited, DEFAULT_MAX_DIAGNOSTICS};
+use crate::{Document, Node, NodePath, Span, Value, ValueKind, Warning, WarningKind};
+
+/// The type of value expected for an argument or a parameter.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ValueType {
+    #[default]
+    Any,
+    Bool,
+    /// An integer of any width.
+    Integer,
+    /// A float of any width.
+    Float,
+    /// An integer or a float.
+    Number,
+    String,
+    /// A list or a slice.
+    List,
+    Map,
+    Null,
+    /// A value of exactly this kind.
+    Kind(ValueKind),
+}
+
+impl ValueType {
+    #[must_use]
+    pub fn matches(self, value: &Value<'_>) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Bool => matches!(value, Value::Bool(_)),
+            Self::Integer => value.as_integer().is_some(),
+            Self::Float => matches!(value, Value::F32(_) | Value::F64(_)),
+            Self::Number => Self::Integer.matches(value) || Self::Float.matches(value),
+            Self::String => matches!(value, Value::String(_)),
+            Self::List => matches!(value, Value::List(_) | Value::Slice(_)),
+            Self::Map => matches!(value, Value::Map(_)),
+            Self::Null => value.is_null(),
+            Self::Kind(kind) => value.kind() == kind,
+        }
+    }
+}
+
+impl fmt::Display for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Any => f.write_str("any value"),
+            Self::Bool => f.write_str("a bool"),
+            Self::Integer => f.write_str("an integer"),
+            Self::Float => f.write_str("a float"),
+            Self::Number => f.write_str("a number"),
+            Self::String => f.write_str("a string"),
+            Self::List => f.write_str("a list"),
+            Self::Map => f.write_str("a map"),
+            Self::Null => f.write_str("null"),
+            Self::Kind(kind) => write!(f, "a `{kind}`"),
+        }
+    }
+}
+
+/// How many times a node may appear among its siblings.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Cardinality {
+    pub min: usize,
+    pub max: Option<usize>,
+}
+
+impl Cardinality {
+    pub const ONE: Self = Self::range(1, Some(1));
+    pub const OPTIONAL: Self = Self::range(0, Some(1));
+    pub const ANY: Self = Self::range(0, None);
+    pub const AT_LEAST_ONE: Self = Self::range(1, None);
+
+    #[must_use]
+    pub const fn range(min: usize, max: Option<usize>) -> Self {
+        Self { min, max }
+    }
+
+    #[must_use]
+    pub fn contains(&self, count: usize) -> bool {
+        count >= self.min && self.max.is_none_or(|max| count <= max)
+    }
+}
+
+impl fmt::Display for Cardinality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.max {
+            Some(max) if max == self.min => write!(f, "{max}"),
+            Some(max) => write!(f, "{} to {max}", self.min),
+            None => write!(f, "at least {}", self.min),
+        }
+    }
+}
+
+/// The integers allowed for a parameter, between inclusive bounds.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct IntegerRange {
+    pub min: Option<i128>,
+    pub max: Option<i128>,
+}
+
+impl IntegerRange {
+    #[must_use]
+    pub const fn new(min: Option<i128>, max: Option<i128>) -> Self {
+        Self { min, max }
+    }
+
+    #[must_use]
+    pub fn contains(&self, value: i128) -> bool {
+        self.min.is_none_or(|min| value >= min) && self.max.is_none_or(|max| value <= max)
+    }
+}
+
+impl fmt::Display for IntegerRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.min, self.max) {
+            (Some(min), Some(max)) => write!(f, "between {min} and {max}"),
+            (Some(min), None) => write!(f, "at least {min}"),
+            (None, Some(max)) => write!(f, "at most {max}"),
+            (None, None) => f.write_str("any integer"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ParamSchema {
+    key: String,
+    ty: ValueType,
+    required: bool,
+}
+
+/// The nodes allowed in a list of siblings, such as the top level of a
+/// document or the children of a node.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct NodesSchema {
+    nodes: Vec<(NodeSchema, Cardinality)>,
+    allow_unknown: bool,
+}
+
+/// The expected shape of a document.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[must_use]
+pub struct Schema {
+    nodes: NodesSchema,
+    max_diagnostics: usize,
+}
+
+impl Default for Schema {
+    fn default() -> Self {
+        Self {
+            nodes: NodesSchema::default(),
+            max_diagnostics: DEFAULT_MAX_DIAGNOSTICS,
+        }
+    }
+}
+
+impl Schema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop after collecting `max` violations, or `max` warnings, so that a
+    /// pathological document cannot grow the lists without bound. Once a
+    /// limit is reached, a last [`ViolationKind::TooManyViolations`] or
+    /// [`WarningKind::TooManyWarnings`] says that the rest were dropped.
+    pub fn max_diagnostics(mut self, max: usize) -> Self {
+        self.max_diagnostics = max;
+        self
+    }
+
+    /// Allow a top-level node, appearing as many times as `cardinality` says.
+    pub fn node(mut self, node: NodeSchema, cardinality: Cardinality) -> Self {
+        self.nodes.nodes.push((node, cardinality));
+        self
+    }
+
+    /// Allow top-level nodes that are not declared, without checking them.
+    pub fn allow_unknown_nodes(mut self) -> Self {
+        self.nodes.allow_unknown = true;
+        self
+    }
+}
+
+/// The expected shape of a node.
+///
+/// By default a node has no arguments, parameters, or children, and each one
+/// that is declared is allowed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[must_use]
+pub struct NodeSchema {
+    name: String,
+    args: Vec<ValueType>,
+    required_args: usize,
+    rest_args: Option<ValueType>,
+    params: Vec<ParamSchema>,
+    param_ranges: Vec<(String, IntegerRange)>,
+    allow_unknown_params: bool,
+    allow_null_optionals: bool,
+    children: NodesSchema,
+    deprecated: bool,
+}
+
+impl NodeSchema {
+    pub fn new<S>(name: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            name: name.into(),
+            ..Self::default()
+        }
+    }
+
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Expect another argument. Required arguments should be declared before
+    /// any optional ones.
+    pub fn arg(mut self, ty: ValueType) -> Self {
+        self.args.push(ty);
+        self.required_args += 1;
+        self
+    }
+
+    /// Allow another argument, which may be left out.
+    pub fn optional_arg(mut self, ty: ValueType) -> Self {
+        self.args.push(ty);
+        self
+    }
+
+    /// Allow any number of arguments after the declared ones.
+    pub fn rest_args(mut self, ty: ValueType) -> Self {
+        self.rest_args = Some(ty);
+        self
+    }
+
+    /// Allow a parameter, which may be left out.
+    pub fn param<S>(mut self, key: S, ty: ValueType) -> Self
+    where
+        S: Into<String>,
+    {
+        self.params.push(ParamSchema {
+            key: key.into(),
+            ty,
+            required: false,
+        });
+        self
+    }
+
+    /// Expect a parameter.
+    pub fn required_param<S>(mut self, key: S, ty: ValueType) -> Self
+    where
+        S: Into<String>,
+    {
+        self.params.push(ParamSchema {
+            key: key.into(),
+            ty,
+            required: true,
+        });
+        self
+    }
+
+    /// Restrict the parameter `key`, when it is an integer, to `range`.
+    pub fn param_range<S>(mut self, key: S, range: IntegerRange) -> Self
+    where
+        S: Into<String>,
+    {
+        self.param_ranges.push((key.into(), range));
+        self
+    }
+
+    /// Allow parameters that are not declared, without checking them.
+    pub fn allow_unknown_params(mut self) -> Self {
+        self.allow_unknown_params = true;
+        self
+    }
+
+    /// Accept `Null` for optional arguments and parameters whatever their
+    /// type, as derived nodes give for fields that are `None`.
+    pub fn allow_null_optionals(mut self) -> Self {
+        self.allow_null_optionals = true;
+        self
+    }
+
+    /// Allow a child node, appearing as many times as `cardinality` says.
+    pub fn child(mut self, child: NodeSchema, cardinality: Cardinality) -> Self {
+        self.children.nodes.push((child, cardinality));
+        self
+    }
+
+    /// Allow children that are not declared, without checking them.
+    pub fn allow_unknown_children(mut self) -> Self {
+        self.children.allow_unknown = true;
+        self
+    }
+
+    /// Warn about the node wherever it appears, see [`validate_with_warnings`].
+    pub fn deprecated(mut self) -> Self {
+        self.deprecated = true;
+        self
+    }
+
+    fn max_args(&self) -> Option<usize> {
+        self.rest_args.is_none().then_some(self.args.len())
+    }
+}
+
+/// A way in which a document does not match a schema.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ViolationKind {
+    UnknownNode,
+    NodeCount {
+        name: String,
+        expected: Cardinality,
+        found: usize,
+    },
+    ArgCount {
+        expected: Cardinality,
+        found: usize,
+    },
+    ArgType {
+        index: usize,
+        expected: ValueType,
+        found: ValueKind,
+    },
+    MissingParam {
+        key: String,
+    },
+    UnknownParam {
+        key: String,
+    },
+    ParamType {
+        key: String,
+        expected: ValueType,
+        found: ValueKind,
+    },
+    ParamRange {
+        key: String,
+        expected: IntegerRange,
+        found: i128,
+    },
+    /// The last violation, when there are more than the limit, after which
+    /// the rest were dropped, see [`Schema::max_diagnostics`].
+    TooManyViolations,
+}
+
+impl fmt::Display for ViolationKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownNode => f.write_str("unknown node"),
+            Self::NodeCount {
+                name,
+                expected,
+                found,
+            } => write!(f, "expected {expected} `{name}` nodes, found {found}"),
+            Self::ArgCount { expected, found } => {
+                write!(f, "expected {expected} arguments, found {found}")
+            }
+            Self::ArgType {
+                index,
+                expected,
+                found,
+            } => write!(
+                f,
+                "expected argument {index} to be {expected}, found `{found}`"
+            ),
+            Self::MissingParam { key } => write!(f, "missing parameter `{key}`"),
+            Self::UnknownParam { key } => write!(f, "unknown parameter `{key}`"),
+            Self::ParamType {
+                key,
+                expected,
+                found,
+            } => write!(
+                f,
+                "expected parameter `{key}` to be {expected}, found `{found}`"
+            ),
+            Self::ParamRange {
+                key,
+                expected,
+                found,
+            } => write!(
+                f,
+                "expected parameter `{key}` to be {expected}, found {found}"
+            ),
+            Self::TooManyViolations => f.write_str("too many violations, stopping"),
+        }
+    }
+}
+
+/// A violation of a schema, with the path and span of the node it was found
+/// on. The path of a violation of the top-level nodes is empty.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Violation {
+    pub path: NodePath,
+    pub span: Option<Span>,
+    pub kind: ViolationKind,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.path, self.span) {
+            (path, _) if path.is_empty() => write!(f, "{}", self.kind),
+            (path, Some(span)) => write!(f, "{path} (at {span}): {}", self.kind),
+            (path, None) => write!(f, "{path}: {}", self.kind),
+        }
+    }
+}
+
+/// Check `document` against `schema`.
+///
+/// # Errors
+/// Returns every violation of the schema, in document order.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(violations))
+)]
+pub fn validate<D>(document: &D, schema: &Schema) -> Result<(), Vec<Violation>>
+where
+    D: Document + ?Sized,
+{
+    validate_with_warnings(document, schema).map(drop)
+}
+
+/// Check `document` against `schema`, like [`validate`], returning the
+/// [`Warning`]s found if it matches.
+///
+/// Nodes marked [`NodeSchema::deprecated`], and parameters written more than
+/// once on a node, are reported as warnings, in document order.
+///
+/// ```
+/// # use gpnd::schema::{validate_with_warnings, Cardinality, NodeSchema, Schema};
+/// let schema = Schema::new().node(NodeSchema::new("legacy").deprecated(), Cardinality::ANY);
+/// let document = gpnd::parser::parse("legacy").unwrap();
+/// let warnings = validate_with_warnings(&document, &schema).unwrap();
+/// assert_eq!(
+///     warnings[0].to_string(),
+///     "legacy (at 1:1): `legacy` is deprecated"
+/// );
+/// ```
+///
+/// # Errors
+/// Returns every violation of the schema, in document order.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(violations, warnings))
+)]
+pub fn validate_with_warnings<D>(
+    document: &D,
+    schema: &Schema,
+) -> Result<Vec<Warning>, Vec<Violation>>
+where
+    D: Document + ?Sized,
+{
+    let mut report = Report::new(schema.max_diagnostics);
+    let path = NodePath::new();
+    validate_nodes(&path, None, &document.nodes(), &schema.nodes, &mut report);
+    #[cfg(feature = "tracing")]
+    tracing::Span::current()
+        .record("violations", report.violations.len())
+        .record("warnings", report.warnings.len());
+    if report.violations.is_empty() {
+        Ok(report.warnings)
+    } else {
+        Err(report.violations)
+    }
+}
+
+/// The violations and warnings found by [`validate_with_warnings`], up to
+/// `limit` of each.
+struct Report {
+    violations: Vec<Violation>,
+    warnings: Vec<Warning>,
+    limit: usize,
+}
+
+impl Report {
+    fn new(limit: usize) -> Self {
+        Self {
+            violations: Vec::new(),
+            warnings: Vec::new(),
+            limit,
+        }
+    }
+
+    fn violate(&mut self, violation: Violation) {
+        push_limited(
+            &mut self.violations,
+            self.limit,
+            || violation,
+            || Violation {
+                path: NodePath::new(),
+                span: None,
+                kind: ViolationKind::TooManyViolations,
+            },
+        );
+    }
+
+    fn warn(&mut self, warning: Warning) {
+        push_limited(
+            &mut self.warnings,
+            self.limit,
+            || warning,
+            || Warning {
+                path: NodePath::new(),
+                span: None,
+                kind: WarningKind::TooManyWarnings,
+            },
+        );
+    }
+}
+
+fn validate_nodes(
+    parent: &NodePath,
+    span: Option<Span>,
+    nodes: &[&dyn Node],
+    schema: &NodesSchema,
+    report: &mut Report,
+) {
+    for (node, key) in nodes.iter().zip(occurrences(nodes)) {
+        let path = parent.child(key.0, key.1);
+        match schema.nodes.iter().find(|(node, _)| node.name == key.0) {
+            Some((schema, _)) => validate_node(&path, *node, schema, report),
+            None if schema.allow_unknown => {}
+            None => report.violate(Violation {
+                path,
+                span: node.span(),
+                kind: ViolationKind::UnknownNode,
+            }),
+        }
+    }
+    for (node, cardinality) in &schema.nodes {
+        let found = nodes
+            .iter()
+            .filter(|other| other.name() == node.name)
+            .count();
+        if !cardinality.contains(found) {
+            report.violate(Violation {
+                path: parent.clone(),
+                span,
+                kind: ViolationKind::NodeCount {
+                    name: node.name.clone(),
+                    expected: *cardinality,
+                    found,
+                },
+            });
+        }
+    }
+}
+
+fn validate_node(path: &NodePath, node: &dyn Node, schema: &NodeSchema, report: &mut Report) {
+    let span = node.span();
+    let mut warn = |kind| {
+        report.warn(Warning {
+            path: path.clone(),
+            span,
+            kind,
+        });
+    };
+    if schema.deprecated {
+        warn(WarningKind::DeprecatedNode(schema.name.clone()));
+    }
+    let all = node.params_all();
+    for (index, (key, _)) in all.iter().enumerate() {
+        if all[..index].iter().any(|(other, _)| other == key)
+            && !all[index + 1..].iter().any(|(other, _)| other == key)
+        {
+            warn(WarningKind::DuplicateParam((*key).to_owned()));
+        }
+    }
+
+    let mut violate = |kind| {
+        report.violate(Violation {
+            path: path.clone(),
+            span,
+            kind,
+        });
+    };
+
+    let args = node.args();
+    let expected = Cardinality::range(schema.required_args, schema.max_args());
+    if !expected.contains(args.len()) {
+        violate(ViolationKind::ArgCount {
+            expected,
+            found: args.len(),
+        });
+    }
+    for (index, value) in args.iter().enumerate() {
+        let Some(ty) = schema.args.get(index).or(schema.rest_args.as_ref()) else {
+            break;
+        };
+        let null = schema.allow_null_optionals && index >= schema.required_args && value.is_null();
+        if !null && !ty.matches(value) {
+            violate(ViolationKind::ArgType {
+                index,
+                expected: *ty,
+                found: value.kind(),
+            });
+        }
+    }
+
+    let params = node.params();
+    for param in &schema.params {
+        if param.required && !params.iter().any(|(key, _)| *key == param.key) {
+            violate(ViolationKind::MissingParam {
+                key: param.key.clone(),
+            });
+        }
+    }
+    for (key, value) in &params {
+        match schema.params.iter().find(|param| param.key == *key) {
+            Some(param) if schema.allow_null_optionals && !param.required && value.is_null() => {}
+            Some(param) if !param.ty.matches(value) => violate(ViolationKind::ParamType {
+                key: (*key).to_owned(),
+                expected: param.ty,
+                found: value.kind(),
+            }),
+            Some(_) => {}
+            None if schema.allow_unknown_params => {}
+            None => violate(ViolationKind::UnknownParam {
+                key: (*key).to_owned(),
+            }),
+        }
+    }
+    for (key, range) in &schema.param_ranges {
+        let found = params
+            .iter()
+            .find(|(other, _)| other == key)
+            .and_then(|(_, value)| value.as_integer());
+        if let Some(found) = found.filter(|found| !range.contains(*found)) {
+            violate(ViolationKind::ParamRange {
+                key: key.clone(),
+                expected: *range,
+                found,
+            });
+        }
+    }
+
+    validate_nodes(path, span, &node.children(), &schema.children, report);
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::borrow::ToOwned;
+    use alloc::string::ToString;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use super::{
+        validate, validate_with_warnings, Cardinality, NodeSchema, Schema, ValueType, ViolationKind,
+    };
+    use crate::{DocumentBuf, NodeBuf, Value, ValueKind, WarningKind};
+
+    fn schema() -> Schema {
+        Schema::new().node(
+            NodeSchema::new("server")
+                .arg(ValueType::String)
+                .optional_arg(ValueType::Integer)
+                .required_param("port", ValueType::Integer)
+                .param("tls", ValueType::Bool)
+                .child(
+                    NodeSchema::new("listen").rest_args(ValueType::String),
+                    Cardinality::AT_LEAST_ONE,
+                ),
+            Cardinality::ONE,
+        )
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_node_reflect() {
+        use alloc::string::String;
+
+        use super::{FieldType, NodeReflect};
+
+        #[derive(crate::Node)]
+        #[node(schema)]
+        struct Listen {
+            #[node(arg)]
+            host: String,
+            port: u16,
+            backlog: Option<u32>,
+        }
+
+        #[derive(crate::Document)]
+        struct Config {
+            #[document(node)]
+            listen: Listen,
+        }
+
+        assert_eq!(Listen::NAME, "listen");
+        assert_eq!(Listen::ARG_COUNT, 1);
+        assert_eq!(Listen::PARAM_NAMES, ["port", "backlog"]);
+        assert_eq!(
+            Listen::PARAM_TYPES[1],
+            FieldType {
+                value: ValueType::Integer,
+                required: false,
+            }
+        );
+        const { assert!(!Listen::HAS_CHILDREN) };
+
+        let schema = Schema::new().node(Listen::schema(), Cardinality::ANY);
+        let config = Config {
+            listen: Listen {
+                host: "localhost".to_owned(),
+                port: 80,
+                backlog: None,
+            },
+        };
+        assert_eq!(validate(&config, &schema), Ok(()));
+        let document = DocumentBuf::from(vec![NodeBuf::new("listen")]);
+        assert_eq!(validate(&document, &schema).unwrap_err().len(), 2);
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_node_reflect_validate() {
+        use alloc::string::String;
+
+        use super::{IntegerRange, NodeReflect, Violation};
+        use crate::Node;
+
+        #[derive(crate::Node)]
+        #[node(schema)]
+        struct Server {
+            #[node(param = "port", default = 8080, range = "1..=65535")]
+            port: Option<u32>,
+            #[node(default = "localhost")]
+            host: Option<String>,
+            #[node(required, range = "..10")]
+            workers: Option<i8>,
+        }
+
+        assert_eq!(
+            Server::PARAM_RANGES,
+            [
+                ("port", IntegerRange::new(Some(1), Some(65535))),
+                ("workers", IntegerRange::new(None, Some(9))),
+            ]
+        );
+        let mut server = Server {
+            port: None,
+            host: None,
+            workers: Some(4),
+        };
+        assert_eq!(server.get_param("port"), Some(Value::U32(8080)));
+        assert_eq!(server.get_param("host"), Some(Value::from("localhost")));
+        assert_eq!(server.validate(), Ok(()));
+
+        server.port = Some(0);
+        server.workers = None;
+        let violations = server.validate().unwrap_err();
+        assert_eq!(
+            violations
+                .iter()
+                .map(Violation::to_string)
+                .collect::<Vec<_>>(),
+            [
+                "server: expected parameter `workers` to be an integer, found `Null`",
+                "server: expected parameter `port` to be between 1 and 65535, found 0",
+            ]
+        );
+        let document = DocumentBuf::from(vec![NodeBuf::new(Server::NAME)]);
+        let schema = Schema::new().node(Server::schema(), Cardinality::ANY);
+        assert_eq!(
+            validate(&document, &schema).unwrap_err()[0].kind,
+            ViolationKind::MissingParam {
+                key: "workers".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut server = NodeBuf::new("server");
+        server.args_mut().push(Value::from("main"));
+        server.params_mut().insert("port", 80_u16);
+        let mut listen = NodeBuf::new("listen");
+        listen.args_mut().push(Value::from("0.0.0.0"));
+        server.children_mut().push(listen);
+        let document = DocumentBuf::from(vec![server.clone()]);
+        assert_eq!(validate(&document, &schema()), Ok(()));
+
+        let mut nulls = server.clone();
+        nulls.args_mut().push(Value::Null);
+        nulls.params_mut().insert("tls", Value::Null);
+        let nulls = DocumentBuf::from(vec![nulls]);
+        assert_eq!(validate(&nulls, &schema()).unwrap_err().len(), 2);
+        let nullable = Schema::new().node(
+            NodeSchema::new("server")
+                .arg(ValueType::String)
+                .optional_arg(ValueType::Integer)
+                .param("port", ValueType::Integer)
+                .param("tls", ValueType::Bool)
+                .allow_unknown_children()
+                .allow_null_optionals(),
+            Cardinality::ONE,
+        );
+        assert_eq!(validate(&nulls, &nullable), Ok(()));
+
+        server.args_mut().push(Value::Bool(true));
+        server.args_mut().push(Value::Null);
+        server.params_mut().remove("port");
+        server.params_mut().insert("tls", "yes");
+        server.params_mut().insert("host", "localhost");
+        server.children_mut().clear();
+        let document = DocumentBuf::from(vec![server, NodeBuf::new("client")]);
+        let violations = validate(&document, &schema()).unwrap_err();
+        let kinds: Vec<_> = violations.iter().map(|violation| &violation.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                &ViolationKind::ArgCount {
+                    expected: Cardinality::range(1, Some(2)),
+                    found: 3,
+                },
+                &ViolationKind::ArgType {
+                    index: 1,
+                    expected: ValueType::Integer,
+                    found: ValueKind::Bool,
+                },
+                &ViolationKind::MissingParam {
+                    key: "port".to_owned(),
+                },
+                &ViolationKind::ParamType {
+                    key: "tls".to_owned(),
+                    expected: ValueType::Bool,
+                    found: ValueKind::String,
+                },
+                &ViolationKind::UnknownParam {
+                    key: "host".to_owned(),
+                },
+                &ViolationKind::NodeCount {
+                    name: "listen".to_owned(),
+                    expected: Cardinality::AT_LEAST_ONE,
+                    found: 0,
+                },
+                &ViolationKind::UnknownNode,
+            ]
+        );
+        assert_eq!(
+            violations[0].to_string(),
+            "server: expected 1 to 2 arguments, found 3"
+        );
+        assert_eq!(violations[6].path.to_string(), "client");
+    }
+
+    #[test]
+    fn test_validate_warnings() {
+        let schema = Schema::new().node(
+            NodeSchema::new("server")
+                .param("port", ValueType::Integer)
+                .child(NodeSchema::new("old").deprecated(), Cardinality::ANY),
+            Cardinality::ONE,
+        );
+        let document = crate::parser::parse("server port=80 port=8080 { old; old }").unwrap();
+        let warnings = validate_with_warnings(&document, &schema).unwrap();
+        let warnings: Vec<_> = warnings
+            .iter()
+            .map(|warning| (warning.path.to_string(), &warning.kind))
+            .collect();
+        assert_eq!(
+            warnings,
+            [
+                (
+                    "server".to_owned(),
+                    &WarningKind::DuplicateParam("port".to_owned())
+                ),
+                (
+                    "server > old".to_owned(),
+                    &WarningKind::DeprecatedNode("old".to_owned())
+                ),
+                (
+                    "server > old[1]".to_owned(),
+                    &WarningKind::DeprecatedNode("old".to_owned())
+                ),
+            ]
+        );
+        let document = crate::parser::parse("server port=80 { old x=1 }").unwrap();
+        assert_eq!(
+            validate_with_warnings(&document, &schema)
+                .unwrap_err()
+                .len(),
+            1
+        );
+
+        let schema = schema.max_diagnostics(1);
+        let document = crate::parser::parse("server port=80 port=8080 { old; old }").unwrap();
+        let warnings = validate_with_warnings(&document, &schema).unwrap();
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(warnings[1].kind, WarningKind::TooManyWarnings);
+        let document = crate::parser::parse("server port=true { old x=1; old y=2 }").unwrap();
+        let violations = validate(&document, &schema).unwrap_err();
+        let violations: Vec<_> = violations.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            violations,
+            [
+                "server (at 1:1): expected parameter `port` to be an integer, found `Bool`",
+                "too many violations, stopping",
+            ]
+        );
+        assert_eq!(
+            crate::lint::Diagnostic::from(crate::schema::Violation {
+                path: crate::NodePath::new(),
+                span: None,
+                kind: ViolationKind::TooManyViolations,
+            })
+            .rule,
+            "max-diagnostics"
+        );
+    }
+}
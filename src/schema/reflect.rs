@@ -0,0 +1,194 @@
+use alloc::borrow::Cow;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::num::{
+    NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU16, NonZeroU32,
+    NonZeroU64, NonZeroU8, NonZeroUsize,
+};
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+use super::{validate_node, IntegerRange, NodeSchema, Report, ValueType, Violation};
+use crate::lint::DEFAULT_MAX_DIAGNOSTICS;
+use crate::{Node, NodePath, ToValue, Value};
+
+/// The expected type of an argument or a parameter, and whether it can be
+/// left out.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FieldType {
+    pub value: ValueType,
+    pub required: bool,
+}
+
+impl FieldType {
+    #[must_use]
+    pub const fn required(value: ValueType) -> Self {
+        Self {
+            value,
+            required: true,
+        }
+    }
+}
+
+/// Rust types with a known [`FieldType`], for the fields of derived nodes.
+///
+/// This is implemented for the same types as [`ToValue`].
+pub trait SchemaType {
+    const FIELD_TYPE: FieldType;
+}
+
+macro_rules! impl_schema_type {
+    ($($value:ident => $($ty:ty),+);+ $(;)?) => {
+        $($(impl SchemaType for $ty {
+            const FIELD_TYPE: FieldType = FieldType::required(ValueType::$value);
+        })+)+
+    };
+}
+
+impl_schema_type!(
+    Integer => u8, u16, u32, u64, usize, i8, i16, i32, i64, isize;
+    Integer => NonZeroU8, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroUsize;
+    Integer => NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroIsize;
+    Float => f32, f64;
+    Bool => bool;
+    String => char, str, String, Cow<'_, str>;
+    Any => Value<'_>;
+);
+
+impl<T> SchemaType for &T
+where
+    T: SchemaType + ?Sized,
+{
+    const FIELD_TYPE: FieldType = T::FIELD_TYPE;
+}
+
+/// Optional values can be left out.
+impl<T> SchemaType for Option<T>
+where
+    T: SchemaType,
+{
+    const FIELD_TYPE: FieldType = FieldType {
+        value: T::FIELD_TYPE.value,
+        required: false,
+    };
+}
+
+impl<T> SchemaType for [T] {
+    const FIELD_TYPE: FieldType = FieldType::required(ValueType::List);
+}
+
+impl<T, const N: usize> SchemaType for [T; N] {
+    const FIELD_TYPE: FieldType = FieldType::required(ValueType::List);
+}
+
+impl<T> SchemaType for Vec<T> {
+    const FIELD_TYPE: FieldType = FieldType::required(ValueType::List);
+}
+
+impl<K, V> SchemaType for BTreeMap<K, V> {
+    const FIELD_TYPE: FieldType = FieldType::required(ValueType::Map);
+}
+
+#[cfg(feature = "std")]
+impl<K, V, S> SchemaType for HashMap<K, V, S> {
+    const FIELD_TYPE: FieldType = FieldType::required(ValueType::Map);
+}
+
+/// Fields that can be left out, and are filled in with a default, for
+/// `#[node(default = ...)]`.
+pub trait FieldDefault {
+    type Value;
+
+    /// The value of the field, or of `default` if it is left out.
+    fn value_or<F>(&self, default: F) -> Value<'_>
+    where
+        F: FnOnce() -> Self::Value;
+}
+
+impl<T> FieldDefault for Option<T>
+where
+    T: ToValue,
+{
+    type Value = T;
+
+    fn value_or<F>(&self, default: F) -> Value<'_>
+    where
+        F: FnOnce() -> T,
+    {
+        match self {
+            Some(value) => value.to_value(),
+            None => default().to_value().into_owned(),
+        }
+    }
+}
+
+/// What a node type expects, known without an instance of it. This is
+/// implemented by `#[derive(Node)]`.
+pub trait NodeReflect {
+    const NAME: &'static str;
+
+    const ARG_COUNT: usize = Self::ARG_TYPES.len();
+
+    const ARG_TYPES: &'static [FieldType];
+
+    const PARAM_NAMES: &'static [&'static str];
+
+    /// The types of the parameters, in the same order as
+    /// [`NodeReflect::PARAM_NAMES`].
+    const PARAM_TYPES: &'static [FieldType];
+
+    /// The ranges of integer parameters, by key.
+    const PARAM_RANGES: &'static [(&'static str, IntegerRange)] = &[];
+
+    const HAS_CHILDREN: bool;
+
+    /// Build a schema from the other items, for use with
+    /// [`validate`](super::validate).
+    ///
+    /// Children are allowed without being checked, because their types are
+    /// not known, and optional fields may be `Null`, as they are when `None`.
+    fn schema() -> NodeSchema {
+        let mut schema = NodeSchema::new(Self::NAME).allow_null_optionals();
+        for field in Self::ARG_TYPES {
+            schema = if field.required {
+                schema.arg(field.value)
+            } else {
+                schema.optional_arg(field.value)
+            };
+        }
+        for (key, field) in Self::PARAM_NAMES.iter().zip(Self::PARAM_TYPES) {
+            schema = if field.required {
+                schema.required_param(*key, field.value)
+            } else {
+                schema.param(*key, field.value)
+            };
+        }
+        for (key, range) in Self::PARAM_RANGES {
+            schema = schema.param_range(*key, *range);
+        }
+        if Self::HAS_CHILDREN {
+            schema = schema.allow_unknown_children();
+        }
+        schema
+    }
+
+    /// Check the node against its [`schema`](NodeReflect::schema), with the
+    /// name of the node as the path of violations.
+    ///
+    /// # Errors
+    /// Returns every violation of the schema.
+    fn validate(&self) -> Result<(), Vec<Violation>>
+    where
+        Self: Node + Sized,
+    {
+        let mut report = Report::new(DEFAULT_MAX_DIAGNOSTICS);
+        let path = NodePath::new().child(Self::NAME, 0);
+        validate_node(&path, self, &Self::schema(), &mut report);
+        if report.violations.is_empty() {
+            Ok(())
+        } else {
+            Err(report.violations)
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use alloc::string::{String, ToString};
+use core::fmt;
+
+/// A position in source text.
+///
+/// The offset is in bytes, and the line and column are counted from one, with
+/// columns counted in characters.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Position {
+    /// The position of the first character in a source.
+    pub const START: Self = Self {
+        offset: 0,
+        line: 1,
+        column: 1,
+    };
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Self::START
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// The range of source text that something was parsed from, from `start` up
+/// to but not including `end`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+impl Span {
+    #[must_use]
+    pub fn new(start: Position, end: Position) -> Self {
+        Self { start, end }
+    }
+
+    /// The length of the span in bytes.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.end.offset.saturating_sub(self.start.offset)
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Displays the start of the span, as `line:column`.
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.start.fmt(f)
+    }
+}
+
+/// An excerpt of source text, with line numbers and the text of a span
+/// underlined with carets, as in:
+///
+/// ```text
+/// 1:6: expected value, found `bare`
+///   |
+/// 1 | node bare
+///   |      ^^^^
+/// ```
+///
+/// ```
+/// # use gpnd::parser::parse;
+/// let source = "server {\n    port bare\n}";
+/// let error = parse(source).unwrap_err();
+/// assert_eq!(
+///     error.snippet(source).to_string(),
+///     "2:10: expected value, found `bare`\n  |\n2 |     port bare\n  |          ^^^^\n"
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct Snippet<'src> {
+    source: &'src str,
+    span: Span,
+    message: Option<String>,
+    color: bool,
+}
+
+impl<'src> Snippet<'src> {
+    #[must_use]
+    pub fn new(source: &'src str, span: Span) -> Self {
+        Self {
+            source,
+            span,
+            message: None,
+            color: false,
+        }
+    }
+
+    /// Write `message` before the excerpt, after the position of the span.
+    #[must_use]
+    pub fn with_message<S>(mut self, message: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.message = Some(message.into());
+        self
+    }
+
+    /// Color the line numbers and carets with ANSI escapes, for terminals.
+    #[must_use]
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// The last line of the span, leaving out a line that it only ends at the
+    /// start of.
+    fn last_line(&self) -> usize {
+        let Span { start, end } = self.span;
+        if end.line > start.line && end.column == 1 {
+            end.line - 1
+        } else {
+            end.line.max(start.line)
+        }
+    }
+}
+
+impl fmt::Display for Snippet<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (gutter, caret, reset) = if self.color {
+            ("\x1b[1;34m", "\x1b[1;31m", "\x1b[0m")
+        } else {
+            ("", "", "")
+        };
+        if let Some(message) = &self.message {
+            writeln!(f, "{}: {message}", self.span)?;
+        }
+        let Span { start, end } = self.span;
+        let last = self.last_line();
+        let width = last.to_string().len();
+        writeln!(f, "{gutter}{:width$} |{reset}", "")?;
+
+        let start_offset = start.offset.min(self.source.len());
+        let mut offset = self.source[..start_offset]
+            .rfind('\n')
+            .map_or(0, |index| index + 1);
+        for (number, line) in (start.line..=last).zip(self.source[offset..].split('\n')) {
+            let text = line.strip_suffix('\r').unwrap_or(line);
+            let from = start.offset.saturating_sub(offset).min(text.len());
+            let to = if number == end.line {
+                end.offset.saturating_sub(offset).min(text.len())
+            } else {
+                text.len()
+            };
+            let (Some(before), Some(underlined)) = (text.get(..from), text.get(from..to.max(from)))
+            else {
+                break;
+            };
+            let padding: String = before
+                .chars()
+                .map(|ch| if ch == '\t' { '\t' } else { ' ' })
+                .collect();
+            let carets = "^".repeat(underlined.chars().count().max(1));
+            writeln!(f, "{gutter}{number:>width$} |{reset} {text}")?;
+            writeln!(
+                f,
+                "{gutter}{:width$} |{reset} {padding}{caret}{carets}{reset}",
+                ""
+            )?;
+            offset += line.len() + 1;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::{Position, Snippet, Span};
+    use crate::parser::parse;
+
+    #[test]
+    fn test_snippet() {
+        let source = "a {\n\tb \"x\n\n}";
+        let error = parse(source).unwrap_err();
+        assert_eq!(
+            error.snippet(source).to_string(),
+            "2:4: unterminated string\n  |\n2 | \tb \"x\n  | \t  ^^\n3 | \n  | ^\n4 | }\n  | ^\n"
+        );
+
+        let error = parse("a {").unwrap_err();
+        assert_eq!(
+            error.snippet("a {").with_color(true).to_string(),
+            concat!(
+                "1:4: expected `}`, found end of input\n",
+                "\x1b[1;34m  |\x1b[0m\n",
+                "\x1b[1;34m1 |\x1b[0m a {\n",
+                "\x1b[1;34m  |\x1b[0m    \x1b[1;31m^\x1b[0m\n",
+            )
+        );
+
+        let position = |offset, line, column| Position {
+            offset,
+            line,
+            column,
+        };
+        let source = "one\ntwo\r\n";
+        let span = Span::new(position(4, 2, 1), position(9, 3, 1));
+        assert_eq!(
+            Snippet::new(source, span).to_string(),
+            "  |\n2 | two\n  | ^^^\n"
+        );
+    }
+}
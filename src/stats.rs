@@ -0,0 +1,112 @@
+//! Counting what a document holds, such as to check that generated documents
+//! stay within expected bounds.
+//!
+//! ```
+//! # use gpnd::{Document, ValueKind};
+//! let document = gpnd::parser::parse("server port=80 { listen \"a\" [1 2] }").unwrap();
+//! let stats = document.stats();
+//! assert_eq!(stats.nodes, 2);
+//! assert_eq!(stats.max_depth, 2);
+//! assert_eq!(stats.value_count(ValueKind::I64), 3);
+//! ```
+
+use alloc::collections::BTreeMap;
+
+use crate::{Document, Node, Value, ValueKind};
+
+/// The counts returned by [`Document::stats`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DocumentStats {
+    /// The number of nodes at every level.
+    pub nodes: usize,
+    /// The number of levels of nodes, which is 1 if there are only top-level
+    /// nodes, and 0 if there are none.
+    pub max_depth: usize,
+    pub args: usize,
+    /// The number of parameters, including every occurrence of a repeated
+    /// key.
+    pub params: usize,
+    /// The number of values of each kind, including the items of lists and
+    /// the values of maps as well as the lists and maps themselves.
+    pub value_kinds: BTreeMap<ValueKind, usize>,
+}
+
+impl DocumentStats {
+    /// Count the nodes and values of `document`.
+    #[must_use]
+    pub fn new(document: &(impl Document + ?Sized)) -> Self {
+        let mut stats = Self::default();
+        for (node, depth) in document.iter_dfs() {
+            stats.add_node(node, depth + 1);
+        }
+        stats
+    }
+
+    /// The number of values of `kind`.
+    #[must_use]
+    pub fn value_count(&self, kind: ValueKind) -> usize {
+        self.value_kinds.get(&kind).copied().unwrap_or_default()
+    }
+
+    /// The number of values of every kind.
+    #[must_use]
+    pub fn values(&self) -> usize {
+        self.value_kinds.values().sum()
+    }
+
+    fn add_node(&mut self, node: &dyn Node, depth: usize) {
+        self.nodes += 1;
+        self.max_depth = self.max_depth.max(depth);
+        for value in node.args_iter() {
+            self.args += 1;
+            self.add_value(&value);
+        }
+        for (_, value) in node.params_all() {
+            self.params += 1;
+            self.add_value(&value);
+        }
+    }
+
+    fn add_value(&mut self, value: &Value<'_>) {
+        *self.value_kinds.entry(value.kind()).or_default() += 1;
+        if let Value::Map(map) = value {
+            for value in map.values() {
+                self.add_value(value);
+            }
+        }
+        for value in value.items().unwrap_or_default() {
+            self.add_value(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DocumentStats;
+    use crate::parser::parse;
+    use crate::{Document, DocumentBuf, ValueKind};
+
+    #[test]
+    fn test_document_stats() {
+        assert_eq!(DocumentBuf::new().stats(), DocumentStats::default());
+
+        let document = parse(concat!(
+            "a 1 \"x\" k=2 k=3 { b { c m=#{ x=1.5 y=[true 4] } } }\n",
+            "d\n",
+        ))
+        .unwrap();
+        let stats = document.stats();
+        assert_eq!(stats.nodes, 4);
+        assert_eq!(stats.max_depth, 3);
+        assert_eq!(stats.args, 2);
+        assert_eq!(stats.params, 3);
+        assert_eq!(stats.values(), 9);
+        assert_eq!(stats.value_count(ValueKind::I64), 4);
+        assert_eq!(stats.value_count(ValueKind::String), 1);
+        assert_eq!(stats.value_count(ValueKind::F64), 1);
+        assert_eq!(stats.value_count(ValueKind::Bool), 1);
+        assert_eq!(stats.value_count(ValueKind::Map), 1);
+        assert_eq!(stats.value_count(ValueKind::Slice), 1);
+        assert_eq!(stats.value_count(ValueKind::U8), 0);
+    }
+}
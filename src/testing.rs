@@ -0,0 +1,156 @@
+//! Assertions for tests of documents, and of the parser and emitter settings
+//! that read and write them.
+//!
+//! Documents are compared semantically, as by [`node_eq`], so that spans,
+//! sources, comments, and the order of parameters do not matter. When an
+//! assertion fails, both documents are shown as by
+//! [`to_test_string`], which puts every value on
+//! its own line with its kind.
+//!
+//! This module needs the `testing` feature, which is meant for
+//! `[dev-dependencies]`.
+//!
+//! ```
+//! use gpnd::parser::ParserConfig;
+//! use gpnd::testing::{assert_parses_to, assert_roundtrip};
+//! use gpnd::{document, node};
+//!
+//! assert_roundtrip("server \"main\" port=8080 { tls }");
+//! assert_parses_to("server port=0x50", &document![node!("server" port=80_i64)]);
+//! ```
+
+use alloc::format;
+use alloc::string::String;
+
+use crate::emit::{to_string_with, to_test_string, FormatOptions};
+use crate::parser::{Parser, ParserConfig};
+use crate::{node_eq, Document, DocumentBuf};
+
+/// Whether two documents have the same version, and nodes that are equal by
+/// [`node_eq`].
+fn documents_eq<L, R>(lhs: &L, rhs: &R) -> bool
+where
+    L: Document + ?Sized,
+    R: Document + ?Sized,
+{
+    let (lhs_nodes, rhs_nodes) = (lhs.nodes(), rhs.nodes());
+    lhs.version() == rhs.version()
+        && lhs_nodes.len() == rhs_nodes.len()
+        && lhs_nodes
+            .into_iter()
+            .zip(rhs_nodes)
+            .all(|(lhs, rhs)| node_eq(lhs, rhs))
+}
+
+#[track_caller]
+fn parse(source: &str, config: ParserConfig, what: &str) -> DocumentBuf {
+    Parser::new(source)
+        .with_config(config)
+        .parse()
+        .unwrap_or_else(|error| panic!("{what} does not parse: {error}\n{what}:\n{source}"))
+}
+
+#[track_caller]
+fn assert_documents_eq<L, R>(lhs: &L, rhs: &R, message: &str)
+where
+    L: Document + ?Sized,
+    R: Document + ?Sized,
+{
+    assert!(
+        documents_eq(lhs, rhs),
+        "{message}\nleft:\n{}\nright:\n{}",
+        to_test_string(lhs),
+        to_test_string(rhs),
+    );
+}
+
+/// Assert that `source` parses, and that writing the document and parsing it
+/// again gives an equal document.
+///
+/// # Panics
+/// Panics if either parse fails, or the documents are not equal.
+#[track_caller]
+pub fn assert_roundtrip(source: &str) {
+    assert_roundtrip_with(source, ParserConfig::default(), &FormatOptions::default());
+}
+
+/// Assert that `source` round-trips, like [`assert_roundtrip`], when parsed
+/// with `config` and written with `options`.
+///
+/// # Panics
+/// Panics if either parse fails, or the documents are not equal.
+#[track_caller]
+pub fn assert_roundtrip_with(source: &str, config: ParserConfig, options: &FormatOptions) {
+    let document = parse(source, config, "source");
+    let emitted: String = to_string_with(&document, options);
+    let reparsed = parse(&emitted, config, "written document");
+    assert_documents_eq(
+        &document,
+        &reparsed,
+        &format!("document changed in a round trip through:\n{emitted}"),
+    );
+}
+
+/// Assert that `source` parses to a document equal to `expected`.
+///
+/// # Panics
+/// Panics if the source does not parse, or the documents are not equal.
+#[track_caller]
+pub fn assert_parses_to<D>(source: &str, expected: &D)
+where
+    D: Document + ?Sized,
+{
+    assert_parses_to_with(source, ParserConfig::default(), expected);
+}
+
+/// Assert that `source` parses to `expected`, like [`assert_parses_to`],
+/// when parsed with `config`.
+///
+/// # Panics
+/// Panics if the source does not parse, or the documents are not equal.
+#[track_caller]
+pub fn assert_parses_to_with<D>(source: &str, config: ParserConfig, expected: &D)
+where
+    D: Document + ?Sized,
+{
+    let document = parse(source, config, "source");
+    assert_documents_eq(
+        &document,
+        expected,
+        &format!("source parsed to a different document:\n{source}"),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::UnitSuffixes;
+    use crate::{document, node, Value};
+
+    #[test]
+    fn test_assertions() {
+        assert_roundtrip("@version \"1\"\na \"b\" c=[1 2.5] { d; e f=#{ g=null } }");
+        let config = ParserConfig {
+            unit_suffixes: UnitSuffixes::Normalize,
+            ..ParserConfig::default()
+        };
+        assert_roundtrip_with("timeout 5s", config, &FormatOptions::default());
+        assert_parses_to_with(
+            "timeout 5s size=1kib",
+            config,
+            &document![node!("timeout" (Value::Duration(5_000_000_000)) size=(Value::Size(1024)))],
+        );
+
+        let panic = std::panic::catch_unwind(|| {
+            assert_parses_to("server port=80", &document![node!("server" port=81)]);
+        })
+        .unwrap_err();
+        let message = panic.downcast_ref::<String>().unwrap();
+        assert!(message.starts_with("source parsed to a different document"));
+        assert!(
+            message.contains("    param \"port\" = I32 81\n"),
+            "{message}"
+        );
+        assert!(std::panic::catch_unwind(|| assert_roundtrip("a {")).is_err());
+    }
+}
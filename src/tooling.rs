@@ -0,0 +1,380 @@
+//! Building blocks for editor tooling, such as a language server, from the
+//! spans of a parsed document.
+//!
+//! [`document_symbols`] gives the outline of a document, and
+//! [`folding_ranges`] gives the lines that an editor can fold. Nodes that
+//! were not parsed, and so have no span, are left out, along with their
+//! children. [`semantic_tokens`] classifies the text of a source for
+//! highlighting, even if it does not parse.
+//!
+//! Lines and columns are counted from one, as in [`Position`], so they need
+//! to be shifted for protocols that count from zero.
+//!
+//! ```
+//! # use gpnd::tooling::{document_symbols, folding_ranges};
+//! let document = gpnd::parser::parse("server 1 {\n    tls\n}\nclient").unwrap();
+//! let symbols = document_symbols(&document);
+//! assert_eq!(symbols[0].name, "server");
+//! assert_eq!(symbols[0].children[0].name, "tls");
+//! let ranges = folding_ranges(&document);
+//! assert_eq!((ranges[0].start_line, ranges[0].end_line), (1, 3));
+//! ```
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::emit::{write_ident, FormatOptions};
+use crate::parser::lexer::Lexer;
+use crate::parser::parse_documents;
+use crate::parser::token::TokenKind;
+use crate::{Document, Node, Position, Span};
+
+/// A node in the outline of a document, see [`document_symbols`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DocumentSymbol {
+    /// The name of the node, with its namespace.
+    pub name: String,
+    /// The arguments of the node as they would be written, if it has any.
+    pub detail: Option<String>,
+    /// The span of the whole node, including its children.
+    pub span: Span,
+    /// The span of the name of the node, which is where an editor puts the
+    /// cursor for the symbol.
+    pub name_span: Span,
+    pub children: Vec<DocumentSymbol>,
+}
+
+/// The end of `text`, if it starts at `start` and is on one line.
+fn advance(start: Position, text: &str) -> Position {
+    Position {
+        offset: start.offset + text.len(),
+        line: start.line,
+        column: start.column + text.chars().count(),
+    }
+}
+
+fn symbol(node: &dyn Node) -> Option<DocumentSymbol> {
+    let span = node.span()?;
+    let mut name = String::new();
+    if let Some(namespace) = node.namespace() {
+        name.push_str(namespace);
+        name.push(':');
+    }
+    name.push_str(node.name());
+    // The name is written as the emitter would, which is how it was read
+    // unless it was quoted without needing to be.
+    let mut written = String::new();
+    if node.namespace().is_some() {
+        written.clone_from(&name);
+    } else {
+        write_ident(&mut written, node.name(), &FormatOptions::default())
+            .expect("writing to a string does not fail");
+    }
+    let name_span = Span::new(span.start, advance(span.start, &written).min(span.end));
+    let args: Vec<String> = node.args_iter().map(|arg| arg.to_string()).collect();
+    Some(DocumentSymbol {
+        name,
+        detail: (!args.is_empty()).then(|| args.join(" ")),
+        span,
+        name_span,
+        children: symbols(node.children()),
+    })
+}
+
+fn symbols(nodes: Vec<&dyn Node>) -> Vec<DocumentSymbol> {
+    nodes.into_iter().filter_map(symbol).collect()
+}
+
+/// The outline of a document, with a symbol for each node that has a span.
+#[must_use]
+pub fn document_symbols(document: &dyn Document) -> Vec<DocumentSymbol> {
+    symbols(document.nodes())
+}
+
+/// What a [`FoldingRange`] folds.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum FoldingKind {
+    /// A node with children, or with entries that span lines.
+    Node,
+    /// An argument or parameter value that spans lines, such as a list or a
+    /// multi-line string.
+    Value,
+}
+
+/// Lines that an editor can fold, from `start_line` to `end_line`
+/// inclusive.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FoldingRange {
+    pub start_line: usize,
+    pub end_line: usize,
+    pub kind: FoldingKind,
+}
+
+impl FoldingRange {
+    /// The lines of `span`, if it spans more than one.
+    fn of(span: Span, kind: FoldingKind) -> Option<Self> {
+        (span.end.line > span.start.line).then_some(Self {
+            start_line: span.start.line,
+            end_line: span.end.line,
+            kind,
+        })
+    }
+}
+
+fn fold_nodes(nodes: Vec<&dyn Node>, ranges: &mut Vec<FoldingRange>) {
+    for node in nodes {
+        let Some(span) = node.span() else {
+            continue;
+        };
+        ranges.extend(FoldingRange::of(span, FoldingKind::Node));
+        let args = (0..node.args_iter().count()).filter_map(|index| node.arg_span(index));
+        let params = node
+            .params()
+            .into_iter()
+            .filter_map(|(key, _)| node.param_span(key));
+        let mut values: Vec<Span> = args.chain(params).collect();
+        values.sort_unstable();
+        values.dedup();
+        ranges.extend(
+            values
+                .into_iter()
+                .filter_map(|span| FoldingRange::of(span, FoldingKind::Value)),
+        );
+        fold_nodes(node.children(), ranges);
+    }
+}
+
+/// The ranges of lines that can be folded, in the order that they start,
+/// with outer ranges before the ranges in them.
+#[must_use]
+pub fn folding_ranges(document: &dyn Document) -> Vec<FoldingRange> {
+    let mut ranges = Vec::new();
+    fold_nodes(document.nodes(), &mut ranges);
+    ranges
+}
+
+/// The class of a [`SemanticToken`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SemanticKind {
+    NodeName,
+    /// The type annotation of a node, such as `(widget)`.
+    Type,
+    /// The key of a parameter, or of an entry of a map.
+    Key,
+    String,
+    Number,
+    /// A `#` keyword, a directive, `true`, `false`, `null`, or an anchor.
+    Keyword,
+    Comment,
+    /// The text that a source failed to parse at.
+    Error,
+}
+
+/// A span of source text with its class, see [`semantic_tokens`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SemanticToken {
+    pub kind: SemanticKind,
+    pub span: Span,
+}
+
+/// Classify the text of `source` for highlighting, in order.
+///
+/// Punctuation and whitespace are left out. If the source does not parse,
+/// the text of the first error is an [`SemanticKind::Error`] in place of the
+/// tokens there, and tokens are classified up to the first error of the
+/// lexer.
+///
+/// ```
+/// # use gpnd::tooling::{semantic_tokens, SemanticKind};
+/// let kinds: Vec<_> = semantic_tokens("server port=80 // Comment.")
+///     .into_iter()
+///     .map(|token| token.kind)
+///     .collect();
+/// assert_eq!(
+///     kinds,
+///     [
+///         SemanticKind::NodeName,
+///         SemanticKind::Key,
+///         SemanticKind::Number,
+///         SemanticKind::Comment,
+///     ]
+/// );
+/// ```
+#[must_use]
+pub fn semantic_tokens(source: &str) -> Vec<SemanticToken> {
+    let mut lexer = Lexer::new(source);
+    let mut tokens = Vec::new();
+    let mut classified = Vec::new();
+    while let Ok(token) = lexer.next_token() {
+        classified.extend(
+            lexer
+                .take_comments()
+                .into_iter()
+                .map(|comment| SemanticToken {
+                    kind: SemanticKind::Comment,
+                    span: comment.span,
+                }),
+        );
+        if token.kind == TokenKind::End {
+            break;
+        }
+        tokens.push(token);
+    }
+
+    // A name starts each node, after the end of the last one, and a key is
+    // followed by `=`. The brace that closes a map does not end a node.
+    let (mut statement, mut maps) = (true, 0_usize);
+    for (index, token) in tokens.iter().enumerate() {
+        let next = tokens.get(index + 1).map(|token| token.kind);
+        let kind = match token.kind {
+            TokenKind::Ident | TokenKind::String if next == Some(TokenKind::Equals) => {
+                Some(SemanticKind::Key)
+            }
+            TokenKind::Ident | TokenKind::String if statement => Some(SemanticKind::NodeName),
+            TokenKind::String => Some(SemanticKind::String),
+            TokenKind::Number => Some(SemanticKind::Number),
+            TokenKind::Annotation => Some(SemanticKind::Type),
+            TokenKind::Keyword | TokenKind::Directive => Some(SemanticKind::Keyword),
+            TokenKind::Ident => match token.text {
+                "true" | "false" | "null" => Some(SemanticKind::Keyword),
+                text if text.starts_with(['&', '*']) => Some(SemanticKind::Keyword),
+                _ => Some(SemanticKind::Error),
+            },
+            _ => None,
+        };
+        statement = match token.kind {
+            TokenKind::MapOpen => {
+                maps += 1;
+                false
+            }
+            TokenKind::RightBrace if maps > 0 => {
+                maps -= 1;
+                false
+            }
+            kind => matches!(
+                kind,
+                TokenKind::Newline
+                    | TokenKind::Annotation
+                    | TokenKind::Semicolon
+                    | TokenKind::LeftBrace
+                    | TokenKind::RightBrace
+                    | TokenKind::Separator
+            ),
+        };
+        classified.extend(kind.map(|kind| SemanticToken {
+            kind,
+            span: token.span,
+        }));
+    }
+
+    let error = parse_documents(source).find_map(Result::err);
+    if let Some(span) = error
+        .map(|error| error.span())
+        .filter(|span| !span.is_empty())
+    {
+        classified.retain(|token| {
+            token.span.end.offset <= span.start.offset || token.span.start.offset >= span.end.offset
+        });
+        classified.push(SemanticToken {
+            kind: SemanticKind::Error,
+            span,
+        });
+    }
+    classified.sort_by_key(|token| token.span.start.offset);
+    classified
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+    use crate::NodeBuf;
+
+    #[test]
+    fn test_tooling() {
+        let source = concat!(
+            "app:server \"main\" 80 {\n",
+            "    \"tls cert\" path=\"a\n",
+            ".pem\"\n",
+            "    route [\n",
+            "        \"/\"\n",
+            "    ]\n",
+            "}\n",
+            "client\n",
+        );
+        let mut document = parse(source).unwrap();
+        document.nodes_mut().push(NodeBuf::new("built"));
+        let symbols = document_symbols(&document);
+        assert_eq!(symbols.len(), 2);
+        let server = &symbols[0];
+        assert_eq!(server.name, "app:server");
+        assert_eq!(server.detail.as_deref(), Some("\"main\" 80"));
+        assert_eq!((server.span.start.line, server.span.end.line), (1, 7));
+        assert_eq!(server.name_span.len(), 10);
+        let tls = &server.children[0];
+        assert_eq!(tls.name, "tls cert");
+        assert_eq!(tls.detail, None);
+        assert_eq!(
+            &source[tls.name_span.start.offset..tls.name_span.end.offset],
+            "\"tls cert\""
+        );
+        assert_eq!(symbols[1].name, "client");
+        assert!(symbols[1].children.is_empty());
+
+        let ranges: Vec<_> = folding_ranges(&document)
+            .into_iter()
+            .map(|range| (range.start_line, range.end_line, range.kind))
+            .collect();
+        assert_eq!(
+            ranges,
+            [
+                (1, 7, FoldingKind::Node),
+                (2, 3, FoldingKind::Node),
+                (2, 3, FoldingKind::Value),
+                (4, 6, FoldingKind::Node),
+                (4, 6, FoldingKind::Value),
+            ]
+        );
+
+        let classify = |source| {
+            semantic_tokens(source)
+                .into_iter()
+                .map(|token| {
+                    (
+                        token.kind,
+                        &source[token.span.start.offset..token.span.end.offset],
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            classify(
+                "@version \"1\"\n/* A. */ \"a b\" 1.5 #inf { c \"k\"=true m=#{ x=null } \"s\" }"
+            ),
+            [
+                (SemanticKind::Keyword, "@version"),
+                (SemanticKind::String, "\"1\""),
+                (SemanticKind::Comment, "/* A. */"),
+                (SemanticKind::NodeName, "\"a b\""),
+                (SemanticKind::Number, "1.5"),
+                (SemanticKind::Keyword, "#inf"),
+                (SemanticKind::NodeName, "c"),
+                (SemanticKind::Key, "\"k\""),
+                (SemanticKind::Keyword, "true"),
+                (SemanticKind::Key, "m"),
+                (SemanticKind::Key, "x"),
+                (SemanticKind::Keyword, "null"),
+                (SemanticKind::String, "\"s\""),
+            ]
+        );
+        assert_eq!(
+            classify("a 1 b\nc #bad d"),
+            [
+                (SemanticKind::NodeName, "a"),
+                (SemanticKind::Number, "1"),
+                (SemanticKind::Error, "b"),
+                (SemanticKind::NodeName, "c"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,111 @@
+use core::iter::Map;
+use core::slice;
+
+use crate::{DocumentBuf, IterUnique, Node, NodeBuf, Value};
+
+/// A [`Node`] with concrete iterator and child types, so that generic code
+/// over it is statically dispatched.
+///
+/// This is for trees of a single, known node type. The [`Node`] trait is
+/// still required, and the iterators here should agree with it.
+pub trait StaticNode: Node {
+    type Child: StaticNode;
+
+    type Args<'node>: Iterator<Item = Value<'node>>
+    where
+        Self: 'node;
+
+    type Params<'node>: Iterator<Item = (&'node str, Value<'node>)>
+    where
+        Self: 'node;
+
+    type Children<'node>: Iterator<Item = &'node Self::Child>
+    where
+        Self: 'node;
+
+    fn static_args(&self) -> Self::Args<'_>;
+
+    fn static_params(&self) -> Self::Params<'_>;
+
+    fn static_children(&self) -> Self::Children<'_>;
+}
+
+/// A [`Document`](crate::Document) of a single, known node type, see
+/// [`StaticNode`].
+pub trait StaticDocument {
+    type Node: StaticNode;
+
+    type Nodes<'document>: Iterator<Item = &'document Self::Node>
+    where
+        Self: 'document;
+
+    fn static_nodes(&self) -> Self::Nodes<'_>;
+}
+
+type BorrowArg<'node> = fn(&'node Value<'static>) -> Value<'node>;
+
+type BorrowParam<'node> = fn((&'node str, &'node Value<'static>)) -> (&'node str, Value<'node>);
+
+impl StaticNode for NodeBuf {
+    type Child = NodeBuf;
+
+    type Args<'node> = Map<slice::Iter<'node, Value<'static>>, BorrowArg<'node>>;
+
+    type Params<'node> = Map<IterUnique<'node>, BorrowParam<'node>>;
+
+    type Children<'node> = slice::Iter<'node, NodeBuf>;
+
+    fn static_args(&self) -> Self::Args<'_> {
+        self.args_ref().iter().map(Value::borrowed)
+    }
+
+    fn static_params(&self) -> Self::Params<'_> {
+        self.params_ref()
+            .iter_unique()
+            .map(|(key, value)| (key, value.borrowed()))
+    }
+
+    fn static_children(&self) -> Self::Children<'_> {
+        self.children_ref().iter()
+    }
+}
+
+impl StaticDocument for DocumentBuf {
+    type Node = NodeBuf;
+
+    type Nodes<'document> = slice::Iter<'document, NodeBuf>;
+
+    fn static_nodes(&self) -> Self::Nodes<'_> {
+        self.nodes_ref().iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use super::{StaticDocument, StaticNode};
+    use crate::{DocumentBuf, NodeBuf, Value};
+
+    fn count<N: StaticNode>(node: &N) -> usize {
+        1 + node.static_children().map(count).sum::<usize>()
+    }
+
+    #[test]
+    fn test_static_dispatch() {
+        let mut server = NodeBuf::new("server");
+        server.args_mut().push(Value::from(1_u8));
+        server.params_mut().insert("port", 80_u8);
+        server.children_mut().push(NodeBuf::new("tls"));
+        let document = DocumentBuf::from(vec![server, NodeBuf::new("client")]);
+
+        assert_eq!(document.static_nodes().map(count).sum::<usize>(), 3);
+        let server = document.static_nodes().next().unwrap();
+        assert_eq!(server.static_args().collect::<Vec<_>>(), [Value::U8(1)]);
+        assert_eq!(
+            server.static_params().collect::<Vec<_>>(),
+            [("port", Value::U8(80))]
+        );
+    }
+}
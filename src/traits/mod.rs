@@ -1,3 +1,17 @@
+pub mod dispatch;
+pub mod mutation;
 pub mod node;
+#[cfg(feature = "rayon")]
+pub mod parallel;
+pub mod traverse;
+pub mod typed;
+pub mod visit;
 
+pub use dispatch::*;
+pub use mutation::*;
 pub use node::*;
+#[cfg(feature = "rayon")]
+pub use parallel::*;
+pub use traverse::*;
+pub use typed::*;
+pub use visit::*;
@@ -0,0 +1,120 @@
+use crate::{Document, Node, NodeBuf, Value};
+
+/// Modify a node in place.
+///
+/// Values and children are taken as owned types, so that the trait can be
+/// implemented by any node storage and used as a trait object.
+pub trait NodeMut: Node {
+    fn set_name(&mut self, name: &str);
+
+    fn push_arg(&mut self, value: Value<'static>);
+
+    /// Insert an argument at `index`, shifting the following arguments.
+    ///
+    /// # Panics
+    /// Panics if `index` is greater than the number of arguments.
+    fn insert_arg(&mut self, index: usize, value: Value<'static>);
+
+    /// Replace the argument at `index`, returning the previous value.
+    ///
+    /// # Panics
+    /// Panics if there is no argument at `index`.
+    fn set_arg(&mut self, index: usize, value: Value<'static>) -> Value<'static>;
+
+    fn remove_arg(&mut self, index: usize) -> Option<Value<'static>>;
+
+    /// Set the parameter `key`, returning the previous value.
+    fn insert_param(&mut self, key: &str, value: Value<'static>) -> Option<Value<'static>>;
+
+    fn remove_param(&mut self, key: &str) -> Option<Value<'static>>;
+
+    fn push_child(&mut self, child: NodeBuf);
+
+    /// Insert a child node at `index`, shifting the following children.
+    ///
+    /// # Panics
+    /// Panics if `index` is greater than the number of children.
+    fn insert_child(&mut self, index: usize, child: NodeBuf);
+
+    fn remove_child(&mut self, index: usize) -> Option<NodeBuf>;
+
+    fn get_child_mut(&mut self, index: usize) -> Option<&mut dyn NodeMut>;
+}
+
+/// Modify a document in place.
+pub trait DocumentMut: Document {
+    fn push_node(&mut self, node: NodeBuf);
+
+    /// Insert a node at `index`, shifting the following nodes.
+    ///
+    /// # Panics
+    /// Panics if `index` is greater than the number of nodes.
+    fn insert_node(&mut self, index: usize, node: NodeBuf);
+
+    fn remove_node(&mut self, index: usize) -> Option<NodeBuf>;
+
+    fn get_node_mut(&mut self, index: usize) -> Option<&mut dyn NodeMut>;
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::borrow::ToOwned;
+    use alloc::vec::Vec;
+
+    use super::*;
+    use crate::DocumentBuf;
+
+    #[test]
+    fn test_node_mut() {
+        let mut node = NodeBuf::new("server");
+        let node: &mut dyn NodeMut = &mut node;
+
+        node.push_arg(Value::from("b"));
+        node.insert_arg(0, Value::from("a"));
+        assert_eq!(node.set_arg(1, Value::from("c")), "b");
+        assert_eq!(node.args(), [Value::from("a"), Value::from("c")]);
+        assert_eq!(node.remove_arg(0), Some(Value::from("a")));
+        assert_eq!(node.remove_arg(1), None);
+
+        assert_eq!(node.insert_param("port", Value::U16(80)), None);
+        assert_eq!(
+            node.insert_param("port", Value::U16(443)),
+            Some(Value::U16(80))
+        );
+        assert_eq!(node.remove_param("port"), Some(Value::U16(443)));
+        assert!(!node.has_params());
+
+        node.push_child(NodeBuf::new("b"));
+        node.insert_child(0, NodeBuf::new("a"));
+        node.get_child_mut(1).unwrap().set_name("c");
+        assert_eq!(
+            node.children()
+                .into_iter()
+                .map(Node::name)
+                .collect::<Vec<_>>(),
+            ["a", "c"]
+        );
+        assert_eq!(
+            node.remove_child(0).map(|child| child.name().to_owned()),
+            Some("a".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_document_mut() {
+        let mut document = DocumentBuf::new();
+        document.push_node(NodeBuf::new("b"));
+        document.insert_node(0, NodeBuf::new("a"));
+        document
+            .get_node_mut(0)
+            .unwrap()
+            .insert_param("key", Value::Bool(true));
+
+        assert_eq!(
+            document.get_node(0).unwrap().get_param("key"),
+            Some(Value::Bool(true))
+        );
+        assert_eq!(document.remove_node(1), Some(NodeBuf::new("b")));
+        assert_eq!(document.remove_node(1), None);
+    }
+}
@@ -1,10 +1,25 @@
-use std::collections::HashMap;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
 
-use crate::Value;
+use crate::query::{Query, QueryError, QueryMatch};
+use crate::redact::Redactor;
+use crate::stats::DocumentStats;
+use crate::{
+    walk_document, BreadthFirst, DepthFirst, DocumentBuf, DocumentVisitor, NodeBuf, NodePath, Span,
+    Value,
+};
 
 pub trait Document {
     fn nodes(&self) -> Vec<&dyn Node>;
 
+    /// Iterate over the top-level nodes.
+    ///
+    /// The default collects [`Document::nodes`], implementors that store
+    /// their nodes should override this to avoid the allocation.
+    fn nodes_iter(&self) -> Box<dyn Iterator<Item = &dyn Node> + '_> {
+        Box::new(self.nodes().into_iter())
+    }
+
     fn get_node(&self, index: usize) -> Option<&dyn Node> {
         self.nodes().get(index).copied()
     }
@@ -12,39 +27,545 @@ pub trait Document {
     fn has_nodes(&self) -> bool {
         !self.nodes().is_empty()
     }
+
+    /// The number of top-level nodes.
+    fn len(&self) -> usize {
+        self.nodes().len()
+    }
+
+    fn is_empty(&self) -> bool {
+        !self.has_nodes()
+    }
+
+    /// The version given by a leading `@version` directive, if any.
+    fn version(&self) -> Option<&str> {
+        None
+    }
+
+    /// The name of the source this document was parsed from, if known.
+    fn source(&self) -> Option<&str> {
+        None
+    }
+
+    /// Iterate over the top-level nodes called `name`, in order.
+    fn nodes_named<'node>(
+        &'node self,
+        name: &'node str,
+    ) -> Box<dyn DoubleEndedIterator<Item = &'node dyn Node> + 'node> {
+        Box::new(
+            self.nodes()
+                .into_iter()
+                .filter(move |node| node.name() == name),
+        )
+    }
+
+    /// Get the first top-level node called `name`.
+    ///
+    /// This is the usual way to get a node that should appear once, such as a
+    /// `settings` node, without depending on its position.
+    fn first_node_named(&self, name: &str) -> Option<&dyn Node> {
+        self.nodes().into_iter().find(|node| node.name() == name)
+    }
+
+    /// Iterate over every node in the document depth-first, with its depth.
+    fn iter_dfs(&self) -> DepthFirst<'_> {
+        DepthFirst::new(self.nodes())
+    }
+
+    /// Iterate over every node in the document breadth-first, with its depth.
+    fn iter_bfs(&self) -> BreadthFirst<'_> {
+        BreadthFirst::new(self.nodes())
+    }
+
+    /// Walk the document with `visitor`, see [`DocumentVisitor`].
+    fn accept(&self, visitor: &mut dyn DocumentVisitor) {
+        walk_document(visitor, self);
+    }
+
+    /// Get a node by a path of names separated by `/`, such as
+    /// `"server/tls/cert"`.
+    ///
+    /// The first segment names a top-level node, and each following segment
+    /// names a child of the previous one. The first node with a matching name
+    /// is taken at every step.
+    fn get_path(&self, path: &str) -> Option<&dyn Node> {
+        let mut segments = path.split('/').filter(|segment| !segment.is_empty());
+        let node = self.first_node_named(segments.next()?)?;
+        segments.try_fold(node, |node, segment| node.first_child_named(segment))
+    }
+
+    /// Get a parameter by path, such as `"server/tls/port"`, where the last
+    /// segment is the parameter key and the rest is the path of its node (see
+    /// [`Document::get_path`]).
+    fn get_path_value(&self, path: &str) -> Option<Value<'_>> {
+        let (path, key) = path.rsplit_once('/')?;
+        self.get_path(path)?.get_param(key)
+    }
+
+    /// Select the nodes that match a query, such as
+    /// `"server > listen[port > 1024]"`, in depth-first order. See the
+    /// [`query`](crate::query) module for the syntax.
+    fn query(&self, query: &str) -> Result<Vec<&dyn Node>, QueryError> {
+        Ok(Query::parse(query)?.select(self))
+    }
+
+    /// Select the nodes that match a query, with their spans, see
+    /// [`Query::matches`].
+    fn query_matches(&self, query: &str) -> Result<Vec<QueryMatch<'_>>, QueryError> {
+        Ok(Query::parse(query)?.matches(self))
+    }
+
+    /// Copy the document, replacing the values of parameters that `redactor`
+    /// matches, see the [`redact`](crate::redact) module.
+    fn redact(&self, redactor: &Redactor) -> DocumentBuf {
+        let mut document: DocumentBuf = self.nodes().into_iter().map(Node::to_node_buf).collect();
+        document.set_version(self.version());
+        document.set_source(self.source().map(Into::into));
+        for node in document.nodes_mut() {
+            redactor.redact_node(node);
+        }
+        document
+    }
+
+    /// Whether two documents mean the same thing: they have the same version
+    /// and their nodes are equal by [`node_eq`], in the same order.
+    ///
+    /// Formatting, comments, spans, sources, the order of parameters, and the
+    /// widths of numbers are ignored.
+    ///
+    /// ```
+    /// # use gpnd::Document;
+    /// # use gpnd::parser::parse;
+    /// let lhs = parse("server port=80 host=\"a\" // main\n").unwrap();
+    /// let rhs = parse("server host=\"a\" port=80.0").unwrap();
+    /// assert!(lhs.semantic_eq(&rhs));
+    /// ```
+    fn semantic_eq(&self, other: &dyn Document) -> bool {
+        let (lhs, rhs) = (self.nodes(), other.nodes());
+        self.version() == other.version()
+            && lhs.len() == rhs.len()
+            && lhs.into_iter().zip(rhs).all(|(lhs, rhs)| node_eq(lhs, rhs))
+    }
+
+    /// Like [`Document::semantic_eq`], but also ignoring the order of nodes
+    /// at every level, for documents whose nodes are not read in order.
+    fn semantic_eq_unordered(&self, other: &dyn Document) -> bool {
+        self.version() == other.version() && unordered_eq(self.nodes(), other.nodes())
+    }
+
+    /// Get the path of `node`, which must be a reference to a node of this
+    /// document, such as one from [`Document::iter_dfs`] or
+    /// [`Document::query`], or `None` if it is not.
+    fn node_path(&self, node: &dyn Node) -> Option<NodePath> {
+        let mut path = NodePath::new();
+        path.find(&self.nodes(), node).then_some(path)
+    }
+
+    /// Count the nodes, arguments, parameters, and values of each kind in the
+    /// document, and how deeply its nodes are nested.
+    fn stats(&self) -> DocumentStats {
+        DocumentStats::new(self)
+    }
+
+    /// Find the first node in the document, depth-first, that `predicate`
+    /// returns `true` for.
+    ///
+    /// ```
+    /// # use gpnd::{Document, Node};
+    /// let document = gpnd::parser::parse("server { listen port=80; listen port=8080 }").unwrap();
+    /// let listen = document
+    ///     .find(|node| node.get_param("port").is_some_and(|port| port != 80))
+    ///     .unwrap();
+    /// assert_eq!(listen.get_param("port").unwrap(), 8080);
+    /// ```
+    fn find<P>(&self, mut predicate: P) -> Option<&dyn Node>
+    where
+        P: FnMut(&dyn Node) -> bool,
+        Self: Sized,
+    {
+        self.iter_dfs()
+            .map(|(node, _)| node)
+            .find(|node| predicate(*node))
+    }
+
+    /// Iterate over the nodes in the document, depth-first, that `predicate`
+    /// returns `true` for.
+    ///
+    /// The tree is traversed lazily, as the iterator is advanced.
+    fn find_all<P>(&self, mut predicate: P) -> impl Iterator<Item = &dyn Node>
+    where
+        P: FnMut(&dyn Node) -> bool,
+        Self: Sized,
+    {
+        self.iter_dfs()
+            .map(|(node, _)| node)
+            .filter(move |node| predicate(*node))
+    }
 }
 
 pub trait Node {
     fn name(&self) -> &str;
 
+    /// The namespace of the node name, such as `ns` in `ns:name`, which keeps
+    /// node types from different vocabularies apart. Nodes have no namespace
+    /// by default.
+    fn namespace(&self) -> Option<&str> {
+        None
+    }
+
+    /// The type annotation of the node, such as `widget` in `(widget)button`,
+    /// which tells apart nodes of different types with the same name. Nodes
+    /// have no type annotation by default.
+    fn type_annotation(&self) -> Option<&str> {
+        None
+    }
+
     fn args(&self) -> Vec<Value<'_>>;
 
-    fn params(&self) -> HashMap<&str, Value<'_>>;
+    /// The parameters of this node, in the order they were written.
+    ///
+    /// If a key is repeated, only the occurrence that [`Node::get_param`]
+    /// finds is included. See [`Node::params_all`] for every occurrence.
+    fn params(&self) -> Vec<(&str, Value<'_>)>;
+
+    /// Every parameter of this node in order, including repeated keys.
+    ///
+    /// This is the same as [`Node::params`] for nodes that cannot repeat keys.
+    fn params_all(&self) -> Vec<(&str, Value<'_>)> {
+        self.params()
+    }
+
+    /// Iterate over the arguments of this node.
+    ///
+    /// The default collects [`Node::args`], implementors that store their
+    /// arguments should override this to avoid the allocation.
+    fn args_iter(&self) -> Box<dyn Iterator<Item = Value<'_>> + '_> {
+        Box::new(self.args().into_iter())
+    }
+
+    /// Iterate over the parameters of this node, like [`Node::params`].
+    ///
+    /// The default collects [`Node::params`], implementors that store their
+    /// parameters should override this to avoid the allocation.
+    fn params_iter(&self) -> Box<dyn Iterator<Item = (&str, Value<'_>)> + '_> {
+        Box::new(self.params().into_iter())
+    }
+
+    /// Iterate over every parameter of this node, like [`Node::params_all`].
+    ///
+    /// The default collects [`Node::params_all`], implementors that store
+    /// their parameters should override this to avoid the allocation.
+    fn params_all_iter(&self) -> Box<dyn Iterator<Item = (&str, Value<'_>)> + '_> {
+        Box::new(self.params_all().into_iter())
+    }
 
     fn get_arg(&self, index: usize) -> Option<Value<'_>> {
-        self.args().get(index).cloned()
+        self.args_iter().nth(index)
+    }
+
+    /// The number of arguments of this node.
+    ///
+    /// The default counts [`Node::args_iter`], implementors that store their
+    /// arguments should override this.
+    fn arg_count(&self) -> usize {
+        self.args_iter().count()
+    }
+
+    /// Get an argument counting from the end, so that `get_arg_back(0)` is
+    /// the last argument.
+    fn get_arg_back(&self, index: usize) -> Option<Value<'_>> {
+        let count = self.arg_count();
+        self.get_arg(count.checked_sub(index)?.checked_sub(1)?)
+    }
+
+    /// The last argument of this node, such as the destination in
+    /// `copy "a" "b" "dest"`.
+    fn last_arg(&self) -> Option<Value<'_>> {
+        self.get_arg_back(0)
     }
 
     fn get_param(&self, key: &str) -> Option<Value<'_>> {
-        self.params().get(key).cloned()
+        self.params_iter()
+            .filter_map(|(name, value)| (name == key).then_some(value))
+            .last()
+    }
+
+    /// Get a parameter whose key matches `key` case-insensitively, so that
+    /// `Port` finds `port`.
+    fn get_param_ci(&self, key: &str) -> Option<Value<'_>> {
+        self.get_param_matching(key, KeyMatch::IgnoreCase)
+    }
+
+    /// Get a parameter whose key matches `key` by `matching`. If several keys
+    /// match, the last one is used.
+    fn get_param_matching(&self, key: &str, matching: KeyMatch) -> Option<Value<'_>> {
+        self.params_iter()
+            .filter_map(|(name, value)| matching.matches(name, key).then_some(value))
+            .last()
+    }
+
+    /// Every value given for `key`, in order.
+    fn get_param_all(&self, key: &str) -> Vec<Value<'_>> {
+        self.params_all()
+            .into_iter()
+            .filter_map(|(name, value)| (name == key).then_some(value))
+            .collect()
     }
 
     fn has_args(&self) -> bool {
-        !self.args().is_empty()
+        self.args_iter().next().is_some()
     }
 
     fn has_params(&self) -> bool {
-        !self.params().is_empty()
+        self.params_iter().next().is_some()
+    }
+
+    /// The nodes nested under this one. Nodes have no children by default.
+    fn children(&self) -> Vec<&dyn Node> {
+        Vec::new()
+    }
+
+    /// Iterate over the nodes nested under this one.
+    ///
+    /// The default collects [`Node::children`], implementors that store their
+    /// children should override this to avoid the allocation.
+    fn children_iter(&self) -> Box<dyn Iterator<Item = &dyn Node> + '_> {
+        Box::new(self.children().into_iter())
+    }
+
+    fn get_child(&self, index: usize) -> Option<&dyn Node> {
+        self.children_iter().nth(index)
+    }
+
+    fn has_children(&self) -> bool {
+        self.children_iter().next().is_some()
+    }
+
+    /// Whether the node is called `name` in `namespace`, where a `namespace`
+    /// of `None` only matches nodes without one.
+    fn is_named(&self, namespace: Option<&str>, name: &str) -> bool {
+        self.namespace() == namespace && self.name() == name
+    }
+
+    /// Where in the source text this node was parsed from, if it was.
+    fn span(&self) -> Option<Span> {
+        None
+    }
+
+    /// Where in the source text the argument at `index` was parsed from.
+    fn arg_span(&self, _index: usize) -> Option<Span> {
+        None
+    }
+
+    /// Where in the source text the value of the parameter `key` was parsed
+    /// from.
+    fn param_span(&self, _key: &str) -> Option<Span> {
+        None
+    }
+
+    /// The name of the source this node was parsed from, such as a file path.
+    fn source(&self) -> Option<&str> {
+        None
+    }
+
+    /// The lines of the comments before this node, which the emitter writes
+    /// on the lines above it.
+    fn leading_comments(&self) -> Vec<&str> {
+        Vec::new()
+    }
+
+    /// The comment at the end of the line of this node.
+    fn trailing_comment(&self) -> Option<&str> {
+        None
+    }
+
+    /// Get the first child called `name`.
+    fn first_child_named(&self, name: &str) -> Option<&dyn Node> {
+        self.children().into_iter().find(|node| node.name() == name)
+    }
+
+    /// Copy this node and its children into a [`NodeBuf`], so that it can be
+    /// kept apart from whatever it was borrowed from.
+    ///
+    /// Every parameter is copied, including repeated keys, and so are the
+    /// span, source, and comments.
+    fn to_node_buf(&self) -> NodeBuf {
+        let mut node = NodeBuf::new(self.name());
+        node.set_namespace(self.namespace());
+        node.set_type_annotation(self.type_annotation());
+        node.args_mut()
+            .extend(self.args_iter().map(Value::into_owned));
+        for (key, value) in self.params_all() {
+            node.params_mut().push(key, value.into_owned());
+        }
+        node.children_mut()
+            .extend(self.children().into_iter().map(Node::to_node_buf));
+        node.set_span(self.span());
+        node.arg_spans_mut()
+            .extend((0..).map_while(|index| self.arg_span(index)));
+        node.param_spans_mut().extend(
+            self.params()
+                .into_iter()
+                .filter_map(|(key, _)| Some((key.into(), self.param_span(key)?))),
+        );
+        node.set_source(self.source().map(Into::into));
+        node.leading_comments_mut()
+            .extend(self.leading_comments().into_iter().map(Into::into));
+        node.set_trailing_comment(self.trailing_comment());
+        node
+    }
+}
+
+/// How parameter keys are compared, see [`Node::get_param_matching`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum KeyMatch {
+    #[default]
+    Exact,
+    /// ASCII letters are compared case-insensitively.
+    IgnoreCase,
+    /// Like `IgnoreCase`, and `-` and `_` are also treated as the same, as is
+    /// common for command line flags, so that `max_conns` finds `Max-Conns`.
+    Normalized,
+}
+
+impl KeyMatch {
+    #[must_use]
+    pub fn matches(self, lhs: &str, rhs: &str) -> bool {
+        let normalize = |ch: u8| match ch {
+            b'_' => b'-',
+            ch => ch.to_ascii_lowercase(),
+        };
+        match self {
+            Self::Exact => lhs == rhs,
+            Self::IgnoreCase => lhs.eq_ignore_ascii_case(rhs),
+            Self::Normalized => {
+                lhs.len() == rhs.len()
+                    && lhs
+                        .bytes()
+                        .zip(rhs.bytes())
+                        .all(|(lhs, rhs)| normalize(lhs) == normalize(rhs))
+            }
+        }
+    }
+}
+
+/// Compare two nodes of any implementation, by their namespace, name, type
+/// annotation, arguments, parameters, and children.
+///
+/// Values are compared with [`Value::numeric_eq`], so a derived node with a
+/// `u16` field equals a parsed node with the same number. Parameters are
+/// compared by key, regardless of their order, and spans and sources are
+/// ignored.
+#[must_use]
+pub fn node_eq(lhs: &dyn Node, rhs: &dyn Node) -> bool {
+    let (lhs_children, rhs_children) = (lhs.children(), rhs.children());
+    header_eq(lhs, rhs)
+        && lhs_children.len() == rhs_children.len()
+        && lhs_children
+            .into_iter()
+            .zip(rhs_children)
+            .all(|(lhs, rhs)| node_eq(lhs, rhs))
+}
+
+/// Compare two lists of nodes like [`node_eq`], but regardless of the order
+/// of the nodes in either, or of their children.
+fn unordered_eq(lhs: Vec<&dyn Node>, mut rhs: Vec<&dyn Node>) -> bool {
+    lhs.len() == rhs.len()
+        && lhs.into_iter().all(|lhs| {
+            rhs.iter()
+                .position(|rhs| {
+                    header_eq(lhs, *rhs) && unordered_eq(lhs.children(), rhs.children())
+                })
+                .map(|index| rhs.swap_remove(index))
+                .is_some()
+        })
+}
+
+/// Compare everything about two nodes that [`node_eq`] does, except their
+/// children.
+fn header_eq(lhs: &dyn Node, rhs: &dyn Node) -> bool {
+    let (lhs_params, rhs_params) = (lhs.params(), rhs.params());
+    lhs.namespace() == rhs.namespace()
+        && lhs.name() == rhs.name()
+        && lhs.type_annotation() == rhs.type_annotation()
+        && lhs.args_iter().count() == rhs.args_iter().count()
+        && lhs
+            .args_iter()
+            .zip(rhs.args_iter())
+            .all(|(lhs, rhs)| lhs.numeric_eq(&rhs))
+        && lhs_params.len() == rhs_params.len()
+        && lhs_params
+            .into_iter()
+            .all(|(key, value)| rhs.get_param(key).is_some_and(|rhs| value.numeric_eq(&rhs)))
+}
+
+/// Types that hold zero or more nodes, such as a single node or a `Vec` of
+/// them.
+///
+/// This is what lets `#[derive(Document)]` flatten collections of nodes into
+/// the document's node list.
+pub trait NodeList {
+    /// Append references to the held nodes, in order.
+    fn extend_nodes<'node>(&'node self, nodes: &mut Vec<&'node dyn Node>);
+}
+
+impl<T> NodeList for T
+where
+    T: Node,
+{
+    fn extend_nodes<'node>(&'node self, nodes: &mut Vec<&'node dyn Node>) {
+        nodes.push(self);
+    }
+}
+
+impl<T> NodeList for Option<T>
+where
+    T: Node,
+{
+    fn extend_nodes<'node>(&'node self, nodes: &mut Vec<&'node dyn Node>) {
+        if let Some(node) = self {
+            nodes.push(node);
+        }
+    }
+}
+
+impl<T> NodeList for [T]
+where
+    T: Node,
+{
+    fn extend_nodes<'node>(&'node self, nodes: &mut Vec<&'node dyn Node>) {
+        nodes.extend(self.iter().map(|node| -> &dyn Node { node }));
+    }
+}
+
+impl<T, const N: usize> NodeList for [T; N]
+where
+    T: Node,
+{
+    fn extend_nodes<'node>(&'node self, nodes: &mut Vec<&'node dyn Node>) {
+        self.as_slice().extend_nodes(nodes);
+    }
+}
+
+impl<T> NodeList for Vec<T>
+where
+    T: Node,
+{
+    fn extend_nodes<'node>(&'node self, nodes: &mut Vec<&'node dyn Node>) {
+        self.as_slice().extend_nodes(nodes);
     }
 }
 
 #[cfg(test)]
 mod test {
-    use std::collections::HashMap;
-
-    use once_cell::sync::Lazy;
+    use alloc::borrow::ToOwned;
+    use alloc::string::String;
+    use alloc::vec;
+    use std::println;
+    use std::sync::LazyLock;
 
     use super::*;
+    use crate::{DocumentBuf, NodeBuf};
 
     struct Parent {
         arg_one: String,
@@ -80,7 +601,7 @@ mod test {
     }
 
     impl Node for Parent {
-        fn name(&self) -> &str {
+        fn name(&self) -> &'static str {
             "parent"
         }
 
@@ -101,12 +622,12 @@ mod test {
             }
         }
 
-        fn params(&self) -> HashMap<&str, Value<'_>> {
-            HashMap::from([
+        fn params(&self) -> Vec<(&str, Value<'_>)> {
+            vec![
                 ("one", Value::from(&self.param_one)),
                 ("two", Value::from(self.param_two)),
                 ("three", Value::from(self.param_three)),
-            ])
+            ]
         }
 
         fn get_param(&self, key: &str) -> Option<Value<'_>> {
@@ -117,10 +638,14 @@ mod test {
                 _ => None,
             }
         }
+
+        fn children(&self) -> Vec<&dyn Node> {
+            self.nodes()
+        }
     }
 
     impl Node for ChildOne {
-        fn name(&self) -> &str {
+        fn name(&self) -> &'static str {
             "one"
         }
 
@@ -128,13 +653,13 @@ mod test {
             vec![Value::from(self.arg)]
         }
 
-        fn params(&self) -> HashMap<&str, Value<'_>> {
-            HashMap::new()
+        fn params(&self) -> Vec<(&str, Value<'_>)> {
+            vec![]
         }
     }
 
     impl Node for ChildTwo {
-        fn name(&self) -> &str {
+        fn name(&self) -> &'static str {
             "two"
         }
 
@@ -142,12 +667,12 @@ mod test {
             vec![]
         }
 
-        fn params(&self) -> HashMap<&str, Value<'_>> {
-            HashMap::from([("foo", Value::from(&self.param_foo))])
+        fn params(&self) -> Vec<(&str, Value<'_>)> {
+            vec![("foo", Value::from(&self.param_foo))]
         }
     }
 
-    static PARENT_NODE: Lazy<Parent> = Lazy::new(|| Parent {
+    static PARENT_NODE: LazyLock<Parent> = LazyLock::new(|| Parent {
         arg_one: "foo".to_owned(),
         arg_two: 2.3,
         arg_three: Some(95),
@@ -172,6 +697,76 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_document_named() {
+        let document: &dyn Document = &*PARENT_NODE;
+        assert_eq!(document.nodes_named("two").count(), 1);
+        assert_eq!(document.nodes_named("three").count(), 0);
+        assert_eq!(
+            document
+                .first_node_named("one")
+                .and_then(|node| node.get_arg(0)),
+            Some(Value::Uint(usize::MAX))
+        );
+        assert!(document.first_node_named("parent").is_none());
+    }
+
+    #[test]
+    fn test_document_path() {
+        let mut server = NodeBuf::new("server");
+        let mut tls = NodeBuf::new("tls");
+        tls.params_mut().insert("port", 443_u16);
+        tls.children_mut().push(NodeBuf::new("cert"));
+        server.children_mut().push(tls);
+        let document = DocumentBuf::from(vec![NodeBuf::new("client"), server]);
+
+        assert_eq!(
+            document.get_path("server/tls/cert").map(Node::name),
+            Some("cert")
+        );
+        assert_eq!(
+            document.get_path("/server/").map(Node::name),
+            Some("server")
+        );
+        assert!(document.get_path("server/cert").is_none());
+        assert!(document.get_path("").is_none());
+        assert_eq!(
+            document.get_path_value("server/tls/port"),
+            Some(Value::U16(443))
+        );
+        assert_eq!(document.get_path_value("server/tls/host"), None);
+        assert_eq!(document.get_path_value("port"), None);
+    }
+
+    #[test]
+    fn test_document_find() {
+        let document = crate::parser::parse("a { b x=1; c { b x=2 } }\nb x=3").unwrap();
+        assert_eq!(
+            document.find(|node| node.has_children()).map(Node::name),
+            Some("a")
+        );
+        assert!(document.find(|node| node.name() == "d").is_none());
+
+        let mut visited = 0;
+        let first = document
+            .find_all(|node| {
+                visited += 1;
+                node.name() == "b"
+            })
+            .next();
+        assert_eq!(
+            first.and_then(|node| node.get_param("x")),
+            Some(Value::I64(1))
+        );
+        assert_eq!(visited, 2);
+
+        let xs: Vec<_> = document
+            .find_all(|node| node.name() == "b")
+            .filter_map(|node| node.get_param("x"))
+            .collect();
+        assert_eq!(xs, [Value::I64(1), Value::I64(2), Value::I64(3)]);
+    }
+
     #[test]
     fn test_node_args() {
         for (index, value) in PARENT_NODE.args().into_iter().enumerate() {
@@ -184,6 +779,22 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_node_args_back() {
+        assert_eq!(PARENT_NODE.arg_count(), 3);
+        assert_eq!(PARENT_NODE.last_arg(), Some(Value::from(95)));
+        assert_eq!(PARENT_NODE.get_arg_back(2), Some(Value::from("foo")));
+        assert_eq!(PARENT_NODE.get_arg_back(3), None);
+
+        let mut copy = NodeBuf::new("copy");
+        copy.args_mut().extend(["a", "b", "dest"].map(Value::from));
+        assert_eq!(copy.arg_count(), 3);
+        assert_eq!(copy.last_arg(), Some(Value::from("dest")));
+        assert_eq!(copy.get_arg_back(1), Some(Value::from("b")));
+        assert_eq!(copy.get_arg_back(usize::MAX), None);
+        assert_eq!(NodeBuf::new("empty").last_arg(), None);
+    }
+
     #[test]
     fn test_node_params() {
         for (key, value) in PARENT_NODE.params() {
@@ -195,4 +806,243 @@ mod test {
             );
         }
     }
+
+    #[test]
+    fn test_node_params_order() {
+        let keys: Vec<_> = PARENT_NODE
+            .params()
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect();
+        assert_eq!(keys, ["one", "two", "three"]);
+        assert_eq!(PARENT_NODE.get_param("two"), Some(Value::from(3.2)));
+    }
+
+    #[test]
+    fn test_node_iter() {
+        let node = ChildTwo {
+            param_foo: "bar".to_owned(),
+        };
+        assert!(!node.has_args());
+        assert!(node.has_params());
+        assert_eq!(node.args_iter().count(), 0);
+        assert_eq!(
+            node.params_iter().collect::<Vec<_>>(),
+            [("foo", Value::from("bar"))]
+        );
+        assert_eq!(PARENT_NODE.get_arg(1), Some(Value::from(2.3)));
+        assert_eq!(PARENT_NODE.get_param("one"), Some(Value::from("bar")));
+
+        let document: &dyn Document = &*PARENT_NODE;
+        assert_eq!(
+            document.nodes_iter().map(Node::name).collect::<Vec<_>>(),
+            ["one", "two"]
+        );
+
+        let mut node = NodeBuf::new("include");
+        node.params_mut().push("path", "a");
+        node.params_mut().push("path", "b");
+        node.children_mut().push(NodeBuf::new("child"));
+        assert_eq!(node.params_iter().count(), 1);
+        assert_eq!(
+            node.params_all_iter().collect::<Vec<_>>(),
+            [("path", Value::from("a")), ("path", Value::from("b"))]
+        );
+        assert_eq!(
+            node.children_iter().map(Node::name).collect::<Vec<_>>(),
+            ["child"]
+        );
+    }
+
+    #[test]
+    fn test_node_children() {
+        let node: &dyn Node = &*PARENT_NODE;
+        assert!(node.has_children());
+        assert_eq!(node.get_child(1).map(Node::name), Some("two"));
+        assert!(node.get_child(2).is_none());
+
+        let child = node.get_child(0).unwrap();
+        assert!(!child.has_children());
+        assert!(child.children().is_empty());
+    }
+
+    #[test]
+    fn test_node_to_node_buf() {
+        let node = PARENT_NODE.to_node_buf();
+        assert_eq!(node.name(), "parent");
+        assert_eq!(node.args(), PARENT_NODE.args());
+        assert_eq!(node.params(), PARENT_NODE.params());
+        assert_eq!(
+            node.get_child(1).unwrap().get_param("foo"),
+            Some(Value::from("bar"))
+        );
+
+        let mut owned = NodeBuf::new("owned");
+        owned.params_mut().push("key", 1);
+        owned.params_mut().push("key", 2);
+        owned.children_mut().push(node);
+        assert_eq!(owned.to_node_buf(), owned);
+    }
+
+    #[test]
+    fn test_node_param_matching() {
+        let mut node = NodeBuf::new("server");
+        node.params_mut().insert("Max-Conns", 10);
+        node.params_mut().insert("port", 80);
+        assert_eq!(node.get_param_ci("PORT"), Some(Value::I32(80)));
+        assert_eq!(node.get_param_ci("max_conns"), None);
+        assert_eq!(
+            node.get_param_matching("max_conns", KeyMatch::Normalized),
+            Some(Value::I32(10))
+        );
+        assert_eq!(node.get_param_matching("Port", KeyMatch::Exact), None);
+    }
+
+    #[test]
+    fn test_node_eq() {
+        let mut owned = PARENT_NODE.to_node_buf();
+        assert!(node_eq(&*PARENT_NODE, &owned));
+
+        owned.args_mut()[2] = Value::U8(95);
+        let two = owned.params_mut().insert("two", Value::F32(3.2)).unwrap();
+        assert!(!node_eq(&*PARENT_NODE, &owned));
+        owned.params_mut().insert("two", two);
+        assert!(node_eq(&*PARENT_NODE, &owned));
+        assert_ne!(PARENT_NODE.to_node_buf(), owned);
+
+        owned.children_mut().pop();
+        assert!(!node_eq(&owned, &*PARENT_NODE));
+    }
+
+    #[test]
+    fn test_semantic_eq() {
+        use crate::parser::parse;
+
+        let lhs = parse("@version \"1\"\na 1 x=2 { b; c 0.5 }\n/* d */ d true").unwrap();
+        let rhs = parse("@version \"1\"\na 1.0 x=2 {\n    b\n    c 0.5\n}\nd true // d").unwrap();
+        assert!(lhs.semantic_eq(&rhs));
+        assert!(lhs.semantic_eq_unordered(&rhs));
+
+        let reordered = parse("@version \"1\"\nd true\na 1 x=2 { c 0.5; b }").unwrap();
+        assert!(!lhs.semantic_eq(&reordered));
+        assert!(lhs.semantic_eq_unordered(&reordered));
+
+        let changed = parse("@version \"1\"\nd true\na 1 x=3 { c 0.5; b }").unwrap();
+        assert!(!lhs.semantic_eq_unordered(&changed));
+        let unversioned = parse("a 1 x=2 { b; c 0.5 }\nd true").unwrap();
+        assert!(!lhs.semantic_eq(&unversioned));
+        assert!(!lhs.semantic_eq_unordered(&parse("a 1 x=2 { b; c 0.5 }\na").unwrap()));
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_node() {
+        #[derive(crate::Node)]
+        #[node(name = "parent", namespace = "test")]
+        struct Derived {
+            #[node(arg)]
+            arg_one: String,
+            #[node(arg)]
+            arg_two: f64,
+            #[node(param = "one")]
+            param_one: String,
+            #[node(skip)]
+            _cache: (),
+            three: Option<i32>,
+            #[node(child)]
+            child_two: ChildTwo,
+            #[node(children)]
+            rest: Vec<ChildOne>,
+        }
+
+        #[derive(crate::Node)]
+        struct TupleNode(u8, #[node(param = "key")] bool);
+
+        #[derive(crate::Node)]
+        struct HTTPServer;
+
+        #[derive(crate::Node)]
+        struct Ipv4TLSConfig;
+
+        // Field types only need `ToValue`, unless the node has a schema.
+        struct Port(u16);
+
+        impl crate::ToValue for Port {
+            fn to_value(&self) -> Value<'_> {
+                Value::U16(self.0)
+            }
+        }
+
+        #[derive(crate::Node)]
+        struct Listen {
+            port: Port,
+        }
+
+        let node = Derived {
+            arg_one: "foo".to_owned(),
+            arg_two: 2.3,
+            param_one: "bar".to_owned(),
+            _cache: (),
+            three: None,
+            child_two: ChildTwo {
+                param_foo: "baz".to_owned(),
+            },
+            rest: vec![ChildOne { arg: 1 }, ChildOne { arg: 2 }],
+        };
+        assert_eq!(node.name(), "parent");
+        assert_eq!(node.namespace(), Some("test"));
+        assert_eq!(node.args(), [Value::from("foo"), Value::from(2.3)]);
+        assert_eq!(node.get_arg(1), Some(Value::from(2.3)));
+        assert_eq!(node.get_param("one"), Some(Value::from("bar")));
+        assert_eq!(node.get_param("three"), Some(Value::Null));
+        assert_eq!(node.params().len(), 2);
+        assert_eq!(
+            node.children()
+                .into_iter()
+                .map(Node::name)
+                .collect::<Vec<_>>(),
+            ["two", "one", "one"]
+        );
+
+        let node = TupleNode(7, true);
+        assert_eq!(node.name(), "tuple-node");
+        assert_eq!(node.args(), [Value::U8(7)]);
+        assert_eq!(node.get_param("key"), Some(Value::Bool(true)));
+        assert_eq!(HTTPServer.name(), "http-server");
+        assert_eq!(Ipv4TLSConfig.name(), "ipv4-tls-config");
+
+        let node = Listen { port: Port(80) };
+        assert_eq!(node.get_param("port"), Some(Value::U16(80)));
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_document() {
+        #[derive(crate::Document)]
+        struct Derived {
+            #[document(node)]
+            first: ChildTwo,
+            #[document(node)]
+            many: Vec<ChildOne>,
+            #[document(node)]
+            maybe: Option<ChildOne>,
+            _ignored: u8,
+        }
+
+        let document = Derived {
+            first: ChildTwo {
+                param_foo: "foo".to_owned(),
+            },
+            many: vec![ChildOne { arg: 1 }, ChildOne { arg: 2 }],
+            maybe: None,
+            _ignored: 0,
+        };
+        assert_eq!(document.nodes().len(), 3);
+        assert_eq!(document.get_node(0).map(Node::name), Some("two"));
+        assert_eq!(
+            document.get_node(2).and_then(|node| node.get_arg(0)),
+            Some(Value::Uint(2))
+        );
+        assert!(document.get_node(3).is_none());
+    }
 }
@@ -0,0 +1,95 @@
+use rayon::iter::{walk_tree_prefix, IntoParallelRefIterator, ParallelIterator};
+
+use super::visit::DocumentVisitor;
+use crate::{DocumentBuf, NodeBuf};
+
+/// Traversing an owned document on every core, with [`rayon`].
+///
+/// ```
+/// # use gpnd::parser::parse;
+/// # use gpnd::ParallelDocument;
+/// # use rayon::iter::ParallelIterator;
+/// let document = parse("a { b; c { d } }\ne").unwrap();
+/// assert_eq!(document.par_iter_nodes().count(), 5);
+/// let deepest = document.par_iter_nodes().map(|(_, depth)| depth).max();
+/// assert_eq!(deepest, Some(2));
+/// ```
+pub trait ParallelDocument {
+    /// Every node of the document with its depth, in no particular order.
+    fn par_iter_nodes(&self) -> impl ParallelIterator<Item = (&NodeBuf, usize)>;
+
+    /// Visit each top-level node, and everything in it, with a visitor of
+    /// its own made by `make`, returning the visitors in the order of their
+    /// nodes so that their results can be combined.
+    fn par_visit<V, F>(&self, make: F) -> Vec<V>
+    where
+        V: DocumentVisitor + Send,
+        F: Fn() -> V + Sync;
+}
+
+impl ParallelDocument for DocumentBuf {
+    fn par_iter_nodes(&self) -> impl ParallelIterator<Item = (&NodeBuf, usize)> {
+        self.nodes_ref().par_iter().flat_map(|node| {
+            walk_tree_prefix((node, 0), |&(node, depth)| {
+                node.children_ref()
+                    .iter()
+                    .map(move |child| (child, depth + 1))
+            })
+        })
+    }
+
+    fn par_visit<V, F>(&self, make: F) -> Vec<V>
+    where
+        V: DocumentVisitor + Send,
+        F: Fn() -> V + Sync,
+    {
+        self.nodes_ref()
+            .par_iter()
+            .map(|node| {
+                let mut visitor = make();
+                visitor.visit_node(node);
+                visitor
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+    use crate::{Document, Node, Value};
+
+    #[derive(Default)]
+    struct Sum(i64);
+
+    impl DocumentVisitor for Sum {
+        fn visit_arg(&mut self, _index: usize, value: &Value<'_>) {
+            if let Value::I64(value) = value {
+                self.0 += value;
+            }
+        }
+    }
+
+    #[test]
+    fn test_parallel_document() {
+        let document = parse("a 1 { b 2; c 3 { d 4 } }\ne 5\nf").unwrap();
+        let mut names: Vec<_> = document
+            .par_iter_nodes()
+            .map(|(node, depth)| format!("{}{depth}", node.name()))
+            .collect();
+        names.sort();
+        assert_eq!(names, ["a0", "b1", "c1", "d2", "e0", "f0"]);
+        assert_eq!(
+            document.par_iter_nodes().count(),
+            document.iter_dfs().count()
+        );
+
+        let sums: Vec<_> = document
+            .par_visit(Sum::default)
+            .into_iter()
+            .map(|sum| sum.0)
+            .collect();
+        assert_eq!(sums, [10, 5, 0]);
+    }
+}
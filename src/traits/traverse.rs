@@ -0,0 +1,202 @@
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::{Node, Value};
+
+/// A depth-first, pre-order iterator over a tree of nodes, yielding each node
+/// with its depth, where top-level nodes have a depth of zero.
+///
+/// See [`Document::iter_dfs`](crate::Document::iter_dfs).
+pub struct DepthFirst<'node> {
+    stack: Vec<(&'node dyn Node, usize)>,
+}
+
+impl<'node> DepthFirst<'node> {
+    /// Traverse the trees rooted at `nodes`, in order.
+    pub fn new<I>(nodes: I) -> Self
+    where
+        I: IntoIterator<Item = &'node dyn Node>,
+        I::IntoIter: DoubleEndedIterator,
+    {
+        Self {
+            stack: nodes.into_iter().rev().map(|node| (node, 0)).collect(),
+        }
+    }
+}
+
+impl<'node> Iterator for DepthFirst<'node> {
+    type Item = (&'node dyn Node, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (node, depth) = self.stack.pop()?;
+        self.stack.extend(
+            node.children()
+                .into_iter()
+                .rev()
+                .map(|child| (child, depth + 1)),
+        );
+        Some((node, depth))
+    }
+}
+
+impl fmt::Debug for DepthFirst<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DepthFirst")
+            .field("pending", &self.stack.len())
+            .finish()
+    }
+}
+
+/// A breadth-first iterator over a tree of nodes, yielding each node with its
+/// depth, where top-level nodes have a depth of zero.
+///
+/// See [`Document::iter_bfs`](crate::Document::iter_bfs).
+pub struct BreadthFirst<'node> {
+    queue: VecDeque<(&'node dyn Node, usize)>,
+}
+
+impl<'node> BreadthFirst<'node> {
+    /// Traverse the trees rooted at `nodes`, level by level.
+    pub fn new<I>(nodes: I) -> Self
+    where
+        I: IntoIterator<Item = &'node dyn Node>,
+    {
+        Self {
+            queue: nodes.into_iter().map(|node| (node, 0)).collect(),
+        }
+    }
+}
+
+impl<'node> Iterator for BreadthFirst<'node> {
+    type Item = (&'node dyn Node, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (node, depth) = self.queue.pop_front()?;
+        self.queue
+            .extend(node.children().into_iter().map(|child| (child, depth + 1)));
+        Some((node, depth))
+    }
+}
+
+impl fmt::Debug for BreadthFirst<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BreadthFirst")
+            .field("pending", &self.queue.len())
+            .finish()
+    }
+}
+
+/// Filters for traversals that yield nodes with their depth, such as
+/// [`DepthFirst`] and [`BreadthFirst`], so that common selections can be
+/// chained.
+///
+/// ```
+/// # use gpnd::{Document, Node, TraverseExt};
+/// let document = gpnd::parser::parse("list { item enabled=true; item enabled=false }").unwrap();
+/// let enabled = document
+///     .iter_dfs()
+///     .named("item")
+///     .with_param("enabled", true)
+///     .count();
+/// assert_eq!(enabled, 1);
+/// ```
+pub trait TraverseExt<'node>: Iterator<Item = (&'node dyn Node, usize)> + Sized {
+    /// Keep the nodes called `name`.
+    fn named(self, name: &str) -> impl Iterator<Item = (&'node dyn Node, usize)> {
+        self.filter(move |(node, _)| node.name() == name)
+    }
+
+    /// Keep the nodes that have the parameter `key`, with a value equal to
+    /// `value` by [`Value::numeric_eq`].
+    fn with_param<'value, V>(
+        self,
+        key: &str,
+        value: V,
+    ) -> impl Iterator<Item = (&'node dyn Node, usize)>
+    where
+        V: Into<Value<'value>>,
+    {
+        let value = value.into();
+        self.filter(move |(node, _)| {
+            node.get_param(key)
+                .is_some_and(|param| param.numeric_eq(&value))
+        })
+    }
+
+    /// Keep the nodes that have the parameter `key`, with any value.
+    fn has_param(self, key: &str) -> impl Iterator<Item = (&'node dyn Node, usize)> {
+        self.filter(move |(node, _)| node.get_param(key).is_some())
+    }
+
+    /// Keep the nodes at `depth`, where top-level nodes have a depth of zero.
+    fn at_depth(self, depth: usize) -> impl Iterator<Item = (&'node dyn Node, usize)> {
+        self.filter(move |(_, other)| *other == depth)
+    }
+
+    /// Drop the depths, yielding only the nodes.
+    fn nodes(self) -> impl Iterator<Item = &'node dyn Node> {
+        self.map(|(node, _)| node)
+    }
+}
+
+impl<'node, I> TraverseExt<'node> for I where I: Iterator<Item = (&'node dyn Node, usize)> {}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use super::TraverseExt;
+    use crate::{Document, DocumentBuf, Node, NodeBuf};
+
+    fn tree() -> DocumentBuf {
+        let mut a = NodeBuf::new("a");
+        let mut b = NodeBuf::new("b");
+        b.children_mut().push(NodeBuf::new("c"));
+        a.children_mut().push(b);
+        a.children_mut().push(NodeBuf::new("d"));
+        DocumentBuf::from(vec![a, NodeBuf::new("e")])
+    }
+
+    #[test]
+    fn test_traverse() {
+        let document = tree();
+        let dfs: Vec<_> = document
+            .iter_dfs()
+            .map(|(node, depth)| (node.name(), depth))
+            .collect();
+        assert_eq!(dfs, [("a", 0), ("b", 1), ("c", 2), ("d", 1), ("e", 0)]);
+
+        let bfs: Vec<_> = document
+            .iter_bfs()
+            .map(|(node, depth)| (node.name(), depth))
+            .collect();
+        assert_eq!(bfs, [("a", 0), ("e", 0), ("b", 1), ("d", 1), ("c", 2)]);
+    }
+
+    #[test]
+    fn test_traverse_filters() {
+        let mut document = tree();
+        document.nodes_mut()[0].children_mut()[1]
+            .params_mut()
+            .insert("port", 80_u16);
+
+        let names: Vec<_> = document
+            .iter_dfs()
+            .at_depth(1)
+            .nodes()
+            .map(Node::name)
+            .collect();
+        assert_eq!(names, ["b", "d"]);
+        let names: Vec<_> = document
+            .iter_bfs()
+            .with_param("port", 80)
+            .nodes()
+            .map(Node::name)
+            .collect();
+        assert_eq!(names, ["d"]);
+        assert_eq!(document.iter_dfs().named("c").at_depth(2).count(), 1);
+        assert_eq!(document.iter_dfs().has_param("port").at_depth(0).count(), 0);
+    }
+}
@@ -0,0 +1,236 @@
+use alloc::borrow::ToOwned;
+use alloc::string::{String, ToString};
+use core::error::Error;
+use core::fmt;
+
+use crate::{IntoInnerError, Node, NodePath, Span, TryFromValue, Value};
+
+/// Where on a node a value was looked up.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ValueLocation {
+    Arg(usize),
+    Param(String),
+}
+
+impl fmt::Display for ValueLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Arg(index) => write!(f, "argument {index}"),
+            Self::Param(key) => write!(f, "parameter `{key}`"),
+        }
+    }
+}
+
+/// The error returned by the typed getters of [`NodeExt`].
+#[derive(Clone, Debug)]
+pub enum NodeValueError {
+    /// The node has no value at the location.
+    Missing {
+        node: String,
+        path: Option<NodePath>,
+        location: ValueLocation,
+    },
+    /// The value at the location could not be converted to the requested type.
+    Convert {
+        node: String,
+        path: Option<NodePath>,
+        location: ValueLocation,
+        source: IntoInnerError,
+    },
+}
+
+impl NodeValueError {
+    /// The name of the node the value was looked up on.
+    #[must_use]
+    pub fn node(&self) -> &str {
+        match self {
+            Self::Missing { node, .. } | Self::Convert { node, .. } => node,
+        }
+    }
+
+    /// The path of the node, if it was given with
+    /// [`NodeValueError::with_path`].
+    #[must_use]
+    pub fn path(&self) -> Option<&NodePath> {
+        match self {
+            Self::Missing { path, .. } | Self::Convert { path, .. } => path.as_ref(),
+        }
+    }
+
+    /// Set the path of the node, such as from [`Document::node_path`], which
+    /// is then shown instead of its name.
+    ///
+    /// [`Document::node_path`]: crate::Document::node_path
+    #[must_use]
+    pub fn with_path(mut self, node_path: NodePath) -> Self {
+        match &mut self {
+            Self::Missing { path, .. } | Self::Convert { path, .. } => *path = Some(node_path),
+        }
+        self
+    }
+
+    #[must_use]
+    pub fn location(&self) -> &ValueLocation {
+        match self {
+            Self::Missing { location, .. } | Self::Convert { location, .. } => location,
+        }
+    }
+}
+
+impl fmt::Display for NodeValueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let node = match self.path() {
+            Some(path) => path.to_string(),
+            None => self.node().to_owned(),
+        };
+        match self {
+            Self::Missing { location, .. } => write!(f, "node `{node}` has no {location}"),
+            Self::Convert {
+                location, source, ..
+            } => write!(f, "{location} of node `{node}`: {source}"),
+        }
+    }
+}
+
+impl Error for NodeValueError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Missing { .. } => None,
+            Self::Convert { source, .. } => Some(source),
+        }
+    }
+}
+
+/// Typed getters for every [`Node`], including `dyn Node`.
+pub trait NodeExt: Node {
+    /// Get the argument at `index`, converted to `T` with [`TryFromValue`].
+    ///
+    /// # Errors
+    /// Returns an error naming the node and index if the argument is missing,
+    /// or if it cannot be converted.
+    fn arg_as<'node, T>(&'node self, index: usize) -> Result<T, NodeValueError>
+    where
+        T: TryFromValue<'node>,
+    {
+        let location = ValueLocation::Arg(index);
+        convert(
+            self.name(),
+            location,
+            self.get_arg(index),
+            self.arg_span(index),
+        )
+    }
+
+    /// Get the parameter `key`, converted to `T` with [`TryFromValue`].
+    ///
+    /// # Errors
+    /// Returns an error naming the node and key if the parameter is missing,
+    /// or if it cannot be converted.
+    fn param_as<'node, T>(&'node self, key: &str) -> Result<T, NodeValueError>
+    where
+        T: TryFromValue<'node>,
+    {
+        let location = ValueLocation::Param(key.to_owned());
+        convert(
+            self.name(),
+            location,
+            self.get_param(key),
+            self.param_span(key),
+        )
+    }
+}
+
+impl<N> NodeExt for N where N: Node + ?Sized {}
+
+fn convert<'node, T>(
+    node: &str,
+    location: ValueLocation,
+    value: Option<Value<'node>>,
+    span: Option<Span>,
+) -> Result<T, NodeValueError>
+where
+    T: TryFromValue<'node>,
+{
+    let Some(value) = value else {
+        return Err(NodeValueError::Missing {
+            node: node.to_owned(),
+            path: None,
+            location,
+        });
+    };
+    T::try_from_value(value).map_err(|source| NodeValueError::Convert {
+        node: node.to_owned(),
+        path: None,
+        location,
+        source: match span {
+            Some(span) => source.with_span(span),
+            None => source,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::borrow::ToOwned;
+    use alloc::string::{String, ToString};
+
+    use super::{NodeExt, NodeValueError, ValueLocation};
+    use crate::{Document, NodeBuf, Value, ValueKind};
+
+    #[test]
+    fn test_node_typed_getters() {
+        let document = crate::parser::parse("server \"localhost\" port=8080 tls=null").unwrap();
+        let node = document.get_node(0).unwrap();
+
+        assert_eq!(node.arg_as::<&str>(0).unwrap(), "localhost");
+        assert_eq!(node.arg_as::<String>(0).unwrap(), "localhost");
+        assert_eq!(node.param_as::<u16>("port").unwrap(), 8080);
+        assert_eq!(node.param_as::<i64>("port").unwrap(), 8080);
+        assert_eq!(node.param_as::<Option<u16>>("port").unwrap(), Some(8080));
+        assert_eq!(node.param_as::<Option<u16>>("tls").unwrap(), None);
+
+        let mut owned = NodeBuf::new("server");
+        owned.args_mut().push(Value::from("localhost".to_owned()));
+        owned.params_mut().insert("port", 8080_u16);
+        assert_eq!(owned.arg_as::<String>(0).unwrap(), "localhost");
+        assert_eq!(owned.param_as::<u64>("port").unwrap(), 8080);
+
+        let error = node.arg_as::<&str>(1).unwrap_err();
+        assert!(matches!(error, NodeValueError::Missing { .. }));
+        assert_eq!(error.location(), &ValueLocation::Arg(1));
+        assert_eq!(error.to_string(), "node `server` has no argument 1");
+
+        let error = node.param_as::<u8>("port").unwrap_err();
+        let NodeValueError::Convert { source, .. } = &error else {
+            panic!("expected a conversion error");
+        };
+        assert_eq!(source.kind(), ValueKind::I64);
+        assert_eq!(source.value(), &Value::I64(8080));
+        assert_eq!(error.node(), "server");
+        assert!(error
+            .to_string()
+            .starts_with("parameter `port` of node `server`"));
+        assert!(node.param_as::<bool>("port").is_err());
+
+        // Parsed nodes give the span of the value to the error.
+        let document = crate::parser::parse("server port=\"80\"").unwrap();
+        let node = document.get_node(0).unwrap();
+        let NodeValueError::Convert { source, .. } = node.param_as::<u16>("port").unwrap_err()
+        else {
+            panic!("expected a conversion error");
+        };
+        assert_eq!(source.span().map(|span| span.start.column), Some(13));
+
+        let document = crate::parser::parse("server { tls; tls { cert } }").unwrap();
+        let tls = document.query("tls").unwrap()[1];
+        let error = tls
+            .arg_as::<&str>(0)
+            .unwrap_err()
+            .with_path(document.node_path(tls).unwrap());
+        assert_eq!(error.node(), "tls");
+        assert_eq!(
+            error.to_string(),
+            "node `server > tls[1]` has no argument 0"
+        );
+    }
+}
@@ -0,0 +1,112 @@
+use crate::{Document, Node, Value};
+
+/// A visitor for walking the nodes of a document, and their values.
+///
+/// Every method does nothing by default, except [`DocumentVisitor::visit_node`]
+/// which walks into the node with [`walk_node`]. Override it to skip a node,
+/// calling [`walk_node`] only for the nodes that should be walked into.
+pub trait DocumentVisitor {
+    fn visit_node(&mut self, node: &dyn Node) {
+        walk_node(self, node);
+    }
+
+    /// Called before the values and children of a node are visited.
+    fn enter_node(&mut self, _node: &dyn Node) {}
+
+    /// Called after the values and children of a node are visited.
+    fn exit_node(&mut self, _node: &dyn Node) {}
+
+    fn visit_arg(&mut self, _index: usize, _value: &Value<'_>) {}
+
+    fn visit_param(&mut self, _key: &str, _value: &Value<'_>) {}
+}
+
+/// Visit every top-level node of `document`, in order.
+pub fn walk_document<V, D>(visitor: &mut V, document: &D)
+where
+    V: DocumentVisitor + ?Sized,
+    D: Document + ?Sized,
+{
+    for node in document.nodes() {
+        visitor.visit_node(node);
+    }
+}
+
+/// Enter `node`, visit its arguments, parameters, and children, then exit it.
+pub fn walk_node<V>(visitor: &mut V, node: &dyn Node)
+where
+    V: DocumentVisitor + ?Sized,
+{
+    visitor.enter_node(node);
+    for (index, value) in node.args_iter().enumerate() {
+        visitor.visit_arg(index, &value);
+    }
+    for (key, value) in node.params_iter() {
+        visitor.visit_param(key, &value);
+    }
+    for child in node.children() {
+        visitor.visit_node(child);
+    }
+    visitor.exit_node(node);
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use alloc::{format, vec};
+
+    use super::{walk_node, DocumentVisitor};
+    use crate::{Document, DocumentBuf, Node, NodeBuf, Value};
+
+    #[derive(Default)]
+    struct Collect {
+        depth: usize,
+        events: Vec<String>,
+    }
+
+    impl DocumentVisitor for Collect {
+        fn visit_node(&mut self, node: &dyn Node) {
+            if node.name() != "skip" {
+                walk_node(self, node);
+            }
+        }
+
+        fn enter_node(&mut self, node: &dyn Node) {
+            self.events
+                .push(format!("{}{}", " ".repeat(self.depth), node.name()));
+            self.depth += 1;
+        }
+
+        fn exit_node(&mut self, _node: &dyn Node) {
+            self.depth -= 1;
+        }
+
+        fn visit_arg(&mut self, index: usize, value: &Value<'_>) {
+            self.events
+                .push(format!("{}{index}={value:?}", " ".repeat(self.depth)));
+        }
+
+        fn visit_param(&mut self, key: &str, value: &Value<'_>) {
+            self.events
+                .push(format!("{}{key}={value:?}", " ".repeat(self.depth)));
+        }
+    }
+
+    #[test]
+    fn test_visitor() {
+        let mut server = NodeBuf::new("server");
+        server.args_mut().push(Value::Bool(true));
+        server.params_mut().insert("port", 80_u8);
+        server.children_mut().push(NodeBuf::new("tls"));
+        server.children_mut().push(NodeBuf::new("skip"));
+        let document = DocumentBuf::from(vec![server, NodeBuf::new("client")]);
+
+        let mut visitor = Collect::default();
+        document.accept(&mut visitor);
+        assert_eq!(
+            visitor.events,
+            ["server", " 0=Bool(true)", " port=U8(80)", " tls", "client"]
+        );
+    }
+}
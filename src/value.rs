@@ -1,7 +1,33 @@
-use std::borrow::Cow;
-use std::error::Error;
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::error::Error;
+use core::net::{IpAddr, SocketAddr};
+use core::num::{
+    NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU16, NonZeroU32,
+    NonZeroU64, NonZeroU8, NonZeroUsize,
+};
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+#[cfg(feature = "std")]
+use std::path::PathBuf;
 
-#[derive(Clone, Debug, PartialEq, PartialOrd)]
+use crate::parser::literal::parse_duration;
+use crate::{Span, ValueLocation};
+
+/// The value of a node argument or parameter.
+///
+/// The `List` and `Map` payloads are boxed, so that the common scalar and
+/// string values are not padded to the size of the largest collection.
+///
+/// A `List` and a `Slice` with the same items compare as equal, in the same way
+/// that the two variants of a `Cow` do.
+#[allow(clippy::box_collection)]
+#[derive(Clone, Debug, Default)]
 pub enum Value<'borrow> {
     U8(u8),
     U16(u16),
@@ -16,13 +42,539 @@ pub enum Value<'borrow> {
     F32(f32),
     F64(f64),
     Bool(bool),
+    /// A size in bytes, read from a number with a size suffix, such as `4MiB`,
+    /// with
+    /// [`UnitSuffixes::Normalize`](crate::parser::config::UnitSuffixes::Normalize).
+    Size(u64),
+    /// A duration in nanoseconds, read from a number with a duration suffix,
+    /// such as `250ms`, with
+    /// [`UnitSuffixes::Normalize`](crate::parser::config::UnitSuffixes::Normalize).
+    Duration(u64),
     String(Cow<'borrow, str>),
-    List(Vec<Value<'borrow>>),
+    List(Box<Vec<Value<'borrow>>>),
+    /// A list borrowed from elsewhere, so that implementors which already own
+    /// their items do not need to clone them into a new `List`.
+    Slice(&'borrow [Value<'borrow>]),
+    Map(Box<BTreeMap<Cow<'borrow, str>, Value<'borrow>>>),
+    #[default]
     Null,
 }
 
 impl crate::Sealed for Value<'_> {}
 
+impl<'borrow> Value<'borrow> {
+    /// Get the items of a `List` or a `Slice`.
+    pub(crate) fn items(&self) -> Option<&[Value<'borrow>]> {
+        match self {
+            Self::List(items) => Some(items),
+            Self::Slice(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    /// The position of the variant in the declaration, where `List` and
+    /// `Slice` share a position.
+    const fn rank(&self) -> u8 {
+        match self {
+            Self::U8(_) => 0,
+            Self::U16(_) => 1,
+            Self::U32(_) => 2,
+            Self::U64(_) => 3,
+            Self::Uint(_) => 4,
+            Self::I8(_) => 5,
+            Self::I16(_) => 6,
+            Self::I32(_) => 7,
+            Self::I64(_) => 8,
+            Self::Int(_) => 9,
+            Self::F32(_) => 10,
+            Self::F64(_) => 11,
+            Self::Bool(_) => 12,
+            Self::Size(_) => 13,
+            Self::Duration(_) => 14,
+            Self::String(_) => 15,
+            Self::List(_) | Self::Slice(_) => 16,
+            Self::Map(_) => 17,
+            Self::Null => 18,
+        }
+    }
+
+    /// Borrow this value as another `Value` without copying any strings or
+    /// list items.
+    ///
+    /// Owned strings become `Cow::Borrowed` and lists become a `Slice`. A
+    /// `Map` is rebuilt with borrowed keys, because there is no borrowed map
+    /// variant.
+    #[must_use]
+    pub fn borrowed(&self) -> Value<'_> {
+        match self {
+            Self::U8(inner) => Value::U8(*inner),
+            Self::U16(inner) => Value::U16(*inner),
+            Self::U32(inner) => Value::U32(*inner),
+            Self::U64(inner) => Value::U64(*inner),
+            Self::Uint(inner) => Value::Uint(*inner),
+            Self::I8(inner) => Value::I8(*inner),
+            Self::I16(inner) => Value::I16(*inner),
+            Self::I32(inner) => Value::I32(*inner),
+            Self::I64(inner) => Value::I64(*inner),
+            Self::Int(inner) => Value::Int(*inner),
+            Self::F32(inner) => Value::F32(*inner),
+            Self::F64(inner) => Value::F64(*inner),
+            Self::Bool(inner) => Value::Bool(*inner),
+            Self::Size(inner) => Value::Size(*inner),
+            Self::Duration(inner) => Value::Duration(*inner),
+            Self::String(inner) => Value::String(Cow::Borrowed(inner)),
+            Self::List(inner) => Value::Slice(inner),
+            Self::Slice(inner) => Value::Slice(inner),
+            Self::Map(inner) => Value::Map(Box::new(
+                inner
+                    .iter()
+                    .map(|(key, value)| (Cow::Borrowed(key.as_ref()), value.borrowed()))
+                    .collect(),
+            )),
+            Self::Null => Value::Null,
+        }
+    }
+
+    /// Copy any borrowed strings and list items, so that the value no longer
+    /// borrows from anything.
+    ///
+    /// ```
+    /// # use gpnd::Value;
+    /// let name = String::from("gpnd");
+    /// let value = Value::from_iter([Value::from(&name)])
+    ///     .borrowed()
+    ///     .into_owned();
+    /// drop(name);
+    /// assert_eq!(value, Value::from(["gpnd"]));
+    /// ```
+    #[must_use]
+    pub fn into_owned(self) -> Value<'static> {
+        match self {
+            Self::U8(inner) => Value::U8(inner),
+            Self::U16(inner) => Value::U16(inner),
+            Self::U32(inner) => Value::U32(inner),
+            Self::U64(inner) => Value::U64(inner),
+            Self::Uint(inner) => Value::Uint(inner),
+            Self::I8(inner) => Value::I8(inner),
+            Self::I16(inner) => Value::I16(inner),
+            Self::I32(inner) => Value::I32(inner),
+            Self::I64(inner) => Value::I64(inner),
+            Self::Int(inner) => Value::Int(inner),
+            Self::F32(inner) => Value::F32(inner),
+            Self::F64(inner) => Value::F64(inner),
+            Self::Bool(inner) => Value::Bool(inner),
+            Self::Size(inner) => Value::Size(inner),
+            Self::Duration(inner) => Value::Duration(inner),
+            Self::String(inner) => Value::String(Cow::Owned(inner.into_owned())),
+            Self::List(inner) => {
+                Value::List(Box::new(inner.into_iter().map(Value::into_owned).collect()))
+            }
+            Self::Slice(inner) => Value::List(Box::new(
+                inner
+                    .iter()
+                    .map(|item| item.borrowed().into_owned())
+                    .collect(),
+            )),
+            Self::Map(inner) => Value::Map(Box::new(
+                inner
+                    .into_iter()
+                    .map(|(key, value)| (Cow::Owned(key.into_owned()), value.into_owned()))
+                    .collect(),
+            )),
+            Self::Null => Value::Null,
+        }
+    }
+}
+
+impl Value<'_> {
+    #[must_use]
+    pub const fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+
+    /// Borrow the text of a `String`, whether it is owned or borrowed.
+    #[must_use]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(inner) => Some(inner),
+            _ => None,
+        }
+    }
+
+    /// Take the text of a `String`, copying it only if it is borrowed.
+    ///
+    /// Unlike [`IntoInner<String>`](IntoInner), this does not fail on
+    /// borrowed strings.
+    pub fn into_string(self) -> Result<String, IntoInnerError> {
+        match self {
+            Self::String(inner) => Ok(inner.into_owned()),
+            _ => Err(IntoInnerError::new(self, "String")),
+        }
+    }
+
+    /// Iterate over references to the items of a `List` or a `Slice`, or to
+    /// the value itself if it is a scalar or a `Map`, or over nothing if it is
+    /// `Null`, in the same way as the `IntoIterator` implementation.
+    pub fn iter(&self) -> core::slice::Iter<'_, Self> {
+        match self {
+            Self::List(items) => items.iter(),
+            Self::Slice(items) => items.iter(),
+            Self::Null => [].iter(),
+            value => core::slice::from_ref(value).iter(),
+        }
+    }
+
+    /// Return `self`, or `default` if `self` is `Null`.
+    #[must_use]
+    pub fn or(self, default: Self) -> Self {
+        match self {
+            Self::Null => default,
+            value => value,
+        }
+    }
+
+    /// Return `self`, or the result of `default` if `self` is `Null`.
+    #[must_use]
+    pub fn or_else<F>(self, default: F) -> Self
+    where
+        F: FnOnce() -> Self,
+    {
+        match self {
+            Self::Null => default(),
+            value => value,
+        }
+    }
+}
+
+/// The error returned by the conversions of [`OptionValueExt`], which can
+/// fail because the value is missing or because it has the wrong kind.
+#[derive(Clone, Debug)]
+pub enum ValueError {
+    Missing,
+    Convert(IntoInnerError),
+}
+
+impl core::fmt::Display for ValueError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Missing => f.write_str("value is missing"),
+            Self::Convert(error) => error.fmt(f),
+        }
+    }
+}
+
+impl Error for ValueError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Missing => None,
+            Self::Convert(error) => Some(error),
+        }
+    }
+}
+
+impl From<IntoInnerError> for ValueError {
+    fn from(error: IntoInnerError) -> Self {
+        Self::Convert(error)
+    }
+}
+
+macro_rules! option_value_integers {
+    ($($method:ident => $int:ty),+) => {
+        $(
+            #[doc = concat!("Get the value as a `", stringify!($int), "`, from any integer variant")]
+            /// that fits.
+            fn $method(&self) -> Result<$int, ValueError> {
+                let value = self.require()?;
+                value
+                    .as_integer()
+                    .and_then(|inner| <$int>::try_from(inner).ok())
+                    .ok_or(ValueError::Convert(IntoInnerError::new(value.clone(), stringify!($int))))
+            }
+        )+
+    };
+}
+
+/// Extension methods for an optional [`Value`], such as the result of
+/// [`Node::get_param`](crate::Node::get_param).
+///
+/// The conversions fail with [`ValueError::Missing`] if there is no value,
+/// so that a missing key and a value of the wrong kind can be handled with a
+/// single `?`.
+///
+/// ```
+/// # use gpnd::{OptionValueExt, Value, ValueError};
+/// fn port(value: Option<Value<'_>>) -> Result<u16, ValueError> {
+///     value.as_u16()
+/// }
+/// assert_eq!(port(Some(Value::I64(8080))).unwrap(), 8080);
+/// assert!(matches!(port(None), Err(ValueError::Missing)));
+/// assert!(matches!(
+///     port(Some(Value::I64(-1))),
+///     Err(ValueError::Convert(_))
+/// ));
+/// ```
+pub trait OptionValueExt<'borrow>: crate::Sealed {
+    /// Treat a missing value the same as `Null`.
+    fn unwrap_or_null(self) -> Value<'borrow>;
+
+    /// Return the value if it is present and not `Null`, otherwise `default`.
+    fn or_value<T>(self, default: T) -> Value<'borrow>
+    where
+        T: Into<Value<'borrow>>;
+
+    /// Get the value, or fail if it is missing.
+    fn require(&self) -> Result<&Value<'borrow>, ValueError>;
+
+    /// Convert the value to `T` with [`IntoInner`], or fail if it is missing.
+    fn into_inner_opt<T>(self) -> Result<T, ValueError>
+    where
+        Value<'borrow>: IntoInner<T>;
+
+    fn as_bool(&self) -> Result<bool, ValueError> {
+        Ok(self.require()?.get::<bool>()?)
+    }
+
+    fn as_str(&self) -> Result<&str, ValueError>;
+
+    /// Get the value as an `f64`, from any numeric variant.
+    fn as_f64(&self) -> Result<f64, ValueError> {
+        let value = self.require()?;
+        value
+            .as_float()
+            .ok_or(ValueError::Convert(IntoInnerError::new(
+                value.clone(),
+                "f64",
+            )))
+    }
+
+    option_value_integers!(
+        as_u8 => u8,
+        as_u16 => u16,
+        as_u32 => u32,
+        as_u64 => u64,
+        as_usize => usize,
+        as_i8 => i8,
+        as_i16 => i16,
+        as_i32 => i32,
+        as_i64 => i64,
+        as_isize => isize
+    );
+}
+
+impl crate::Sealed for Option<Value<'_>> {}
+
+impl<'borrow> OptionValueExt<'borrow> for Option<Value<'borrow>> {
+    fn unwrap_or_null(self) -> Value<'borrow> {
+        self.unwrap_or_default()
+    }
+
+    fn or_value<T>(self, default: T) -> Value<'borrow>
+    where
+        T: Into<Value<'borrow>>,
+    {
+        self.unwrap_or_null().or_else(|| default.into())
+    }
+
+    fn require(&self) -> Result<&Value<'borrow>, ValueError> {
+        self.as_ref().ok_or(ValueError::Missing)
+    }
+
+    fn into_inner_opt<T>(self) -> Result<T, ValueError>
+    where
+        Value<'borrow>: IntoInner<T>,
+    {
+        Ok(self.ok_or(ValueError::Missing)?.into_inner()?)
+    }
+
+    fn as_str(&self) -> Result<&str, ValueError> {
+        Ok(self.require()?.get::<&str>()?)
+    }
+}
+
+macro_rules! impl_cmp {
+    ($($variant:ident),+) => {
+        impl PartialEq for Value<'_> {
+            fn eq(&self, other: &Self) -> bool {
+                match (self, other) {
+                    $((Self::$variant(lhs), Self::$variant(rhs)) => lhs == rhs,)+
+                    (Self::Null, Self::Null) => true,
+                    _ => match (self.items(), other.items()) {
+                        (Some(lhs), Some(rhs)) => lhs == rhs,
+                        _ => false,
+                    },
+                }
+            }
+        }
+
+        impl PartialOrd for Value<'_> {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                match (self, other) {
+                    $((Self::$variant(lhs), Self::$variant(rhs)) => lhs.partial_cmp(rhs),)+
+                    _ => match (self.items(), other.items()) {
+                        (Some(lhs), Some(rhs)) => lhs.partial_cmp(rhs),
+                        _ => Some(self.rank().cmp(&other.rank())),
+                    },
+                }
+            }
+        }
+    };
+}
+
+impl_cmp!(
+    U8, U16, U32, U64, Uint, I8, I16, I32, I64, Int, F32, F64, Bool, Size, Duration, String, Map
+);
+
+impl Value<'_> {
+    /// Get any integer variant as an `i128`, which can hold all of them.
+    pub(crate) fn as_integer(&self) -> Option<i128> {
+        match *self {
+            Self::U8(inner) => Some(i128::from(inner)),
+            Self::U16(inner) => Some(i128::from(inner)),
+            Self::U32(inner) => Some(i128::from(inner)),
+            Self::U64(inner) => Some(i128::from(inner)),
+            Self::Uint(inner) => i128::try_from(inner).ok(),
+            Self::I8(inner) => Some(i128::from(inner)),
+            Self::I16(inner) => Some(i128::from(inner)),
+            Self::I32(inner) => Some(i128::from(inner)),
+            Self::I64(inner) => Some(i128::from(inner)),
+            Self::Int(inner) => i128::try_from(inner).ok(),
+            _ => None,
+        }
+    }
+
+    /// Compare two values like `==`, except that numbers are compared by value
+    /// regardless of the width of their variants, so that `U16(5)` equals
+    /// `I64(5)` and `F32(0.5)` equals `F64(0.5)`. Lists and maps are compared
+    /// item by item in the same way.
+    ///
+    /// ```
+    /// # use gpnd::Value;
+    /// assert_ne!(Value::U16(5), Value::I64(5));
+    /// assert!(Value::U16(5).numeric_eq(&Value::I64(5)));
+    /// assert!(Value::from([1_u8, 2]).numeric_eq(&Value::from([1.0, 2.0])));
+    /// ```
+    #[must_use]
+    pub fn numeric_eq(&self, other: &Value<'_>) -> bool {
+        if let (Some(lhs), Some(rhs)) = (self.as_integer(), other.as_integer()) {
+            return lhs == rhs;
+        }
+        if let (Some(lhs), Some(rhs)) = (self.as_float(), other.as_float()) {
+            return lhs == rhs;
+        }
+        match (self, other) {
+            (Self::Map(lhs), Value::Map(rhs)) => {
+                lhs.len() == rhs.len()
+                    && lhs
+                        .iter()
+                        .zip(rhs.iter())
+                        .all(|((lhs_key, lhs), (rhs_key, rhs))| {
+                            lhs_key == rhs_key && lhs.numeric_eq(rhs)
+                        })
+            }
+            _ => match (self.items(), other.items()) {
+                (Some(lhs), Some(rhs)) => {
+                    lhs.len() == rhs.len()
+                        && lhs.iter().zip(rhs).all(|(lhs, rhs)| lhs.numeric_eq(rhs))
+                }
+                _ => self == other,
+            },
+        }
+    }
+
+    /// Get any numeric variant as an `f64`, which may lose precision for large
+    /// integers.
+    #[allow(clippy::cast_precision_loss)]
+    pub(crate) fn as_float(&self) -> Option<f64> {
+        match *self {
+            Self::F32(inner) => Some(f64::from(inner)),
+            Self::F64(inner) => Some(inner),
+            _ => self.as_integer().map(|inner| inner as f64),
+        }
+    }
+}
+
+// Comparisons with primitives are by numeric value, regardless of the width
+// of the variant, so that `Value::U16(5) == 5` holds even though the literal
+// is an `i32`.
+macro_rules! impl_eq_integer {
+    ($($int:ty),+) => {
+        $(
+            impl PartialEq<$int> for Value<'_> {
+                fn eq(&self, other: &$int) -> bool {
+                    self.numeric_eq(&Value::from(*other))
+                }
+            }
+
+            impl PartialEq<Value<'_>> for $int {
+                fn eq(&self, other: &Value<'_>) -> bool {
+                    other == self
+                }
+            }
+        )+
+    };
+}
+
+impl_eq_integer!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+macro_rules! impl_eq_float {
+    ($($float:ty),+) => {
+        $(
+            impl PartialEq<$float> for Value<'_> {
+                fn eq(&self, other: &$float) -> bool {
+                    self.as_float().is_some_and(|lhs| lhs == f64::from(*other))
+                }
+            }
+
+            impl PartialEq<Value<'_>> for $float {
+                fn eq(&self, other: &Value<'_>) -> bool {
+                    other == self
+                }
+            }
+        )+
+    };
+}
+
+impl_eq_float!(f32, f64);
+
+impl PartialEq<bool> for Value<'_> {
+    fn eq(&self, other: &bool) -> bool {
+        matches!(self, Self::Bool(inner) if inner == other)
+    }
+}
+
+impl PartialEq<Value<'_>> for bool {
+    fn eq(&self, other: &Value<'_>) -> bool {
+        other == self
+    }
+}
+
+macro_rules! impl_eq_str {
+    (impl<$($other:lifetime)?> $str:ty) => {
+        #[allow(single_use_lifetimes)]
+        impl<$($other)?> PartialEq<$str> for Value<'_> {
+            fn eq(&self, other: &$str) -> bool {
+                matches!(self, Self::String(inner) if inner.as_ref() == AsRef::<str>::as_ref(other))
+            }
+        }
+
+        #[allow(single_use_lifetimes)]
+        impl<$($other)?> PartialEq<Value<'_>> for $str {
+            fn eq(&self, other: &Value<'_>) -> bool {
+                other == self
+            }
+        }
+    };
+    ($other:lifetime; $($str:ty),+) => {
+        $(impl_eq_str!(impl<$other> $str);)+
+    };
+    ($($str:ty),+) => {
+        $(impl_eq_str!(impl<> $str);)+
+    };
+}
+
+impl_eq_str!(str, String);
+impl_eq_str!('other; &'other str, Cow<'other, str>);
+
 macro_rules! ignore {
     ($ignore:tt, $instead:tt) => {
         $instead
@@ -31,17 +583,44 @@ macro_rules! ignore {
 
 macro_rules! impl_kind {
     ($($variant:ident$($inner:ty)?),+) => {
+        /// The variant of a [`Value`], without its inner value.
+        ///
+        /// The `Display` implementation writes the name of the variant.
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub enum ValueKind {
+            $($variant,)+
+        }
+
+        impl ValueKind {
+            /// Every kind, in the order of the variants.
+            pub const ALL: &'static [Self] = &[$(Self::$variant,)+];
+
+            /// Get the name of the variant.
+            #[must_use]
+            pub const fn as_str(self) -> &'static str {
+                match self {
+                    $(Self::$variant => stringify!($variant),)+
+                }
+            }
+        }
+
         impl Value<'_> {
             #[must_use]
-            pub const fn kind(&self) -> &'static str {
+            pub const fn kind(&self) -> ValueKind {
                 match self {
-                    $(Self::$variant$((ignore!($inner, _)))? => stringify!($variant),)+
+                    $(Self::$variant$((ignore!($inner, _)))? => ValueKind::$variant,)+
                 }
             }
         }
     };
 }
 
+impl core::fmt::Display for ValueKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 impl_kind!(
     U8(u8),
     U16(u16),
@@ -56,8 +635,12 @@ impl_kind!(
     F32(f32),
     F64(f64),
     Bool(bool),
+    Size(u64),
+    Duration(u64),
     String(Cow<'borrow, str>),
     List(Vec<Value>),
+    Slice(&[Value]),
+    Map(BTreeMap<Cow<'borrow, str>, Value>),
     Null
 );
 
@@ -70,7 +653,7 @@ macro_rules! impl_from {
         impl<'borrow> From<$from> for Value<'borrow> {
             $(#[$meta])*
             fn from(other: $from) -> Self {
-                Self::$variant(other)
+                Self::$variant(other.into())
             }
         }
 
@@ -117,9 +700,57 @@ impl_from!(
     ///
     /// This is not suitable for converting any other iterables.
     /// See `FromIterator<T> for Value` for recursive conversion from any `Iterator`.
-    Vec<Value<'borrow>> => List
+    Vec<Value<'borrow>> => List,
+    /// Direct conversion from `BTreeMap<Cow<'_, str>, Value>` to the variant
+    /// `Value::Map`.
+    ///
+    /// See `From<HashMap<K, V>> for Value` for conversion from other maps.
+    BTreeMap<Cow<'borrow, str>, Value<'borrow>> => Map
+);
+
+macro_rules! impl_from_nonzero {
+    ($($nonzero:ty => $variant:ident),+) => {
+        $(
+            impl From<$nonzero> for Value<'_> {
+                fn from(other: $nonzero) -> Self {
+                    Self::$variant(other.get())
+                }
+            }
+
+            impl From<Option<$nonzero>> for Value<'_> {
+                fn from(other: Option<$nonzero>) -> Self {
+                    other.map_or(Self::Null, Into::into)
+                }
+            }
+        )+
+    };
+}
+
+impl_from_nonzero!(
+    NonZeroU8 => U8,
+    NonZeroU16 => U16,
+    NonZeroU32 => U32,
+    NonZeroU64 => U64,
+    NonZeroUsize => Uint,
+    NonZeroI8 => I8,
+    NonZeroI16 => I16,
+    NonZeroI32 => I32,
+    NonZeroI64 => I64,
+    NonZeroIsize => Int
 );
 
+impl From<char> for Value<'_> {
+    fn from(other: char) -> Self {
+        Self::String(Cow::Owned(other.to_string()))
+    }
+}
+
+impl From<Option<char>> for Value<'_> {
+    fn from(other: Option<char>) -> Self {
+        other.map_or(Self::Null, Into::into)
+    }
+}
+
 impl From<String> for Value<'_> {
     fn from(other: String) -> Self {
         Self::String(Cow::Owned(other))
@@ -138,27 +769,375 @@ impl<'borrow> From<&'borrow str> for Value<'borrow> {
     }
 }
 
+impl<'borrow, T, const N: usize> From<[T; N]> for Value<'borrow>
+where
+    T: Into<Value<'borrow>>,
+{
+    fn from(other: [T; N]) -> Self {
+        Self::from_iter(other)
+    }
+}
+
+impl<'borrow, T> From<&[T]> for Value<'borrow>
+where
+    T: Clone + Into<Value<'borrow>>,
+{
+    fn from(other: &[T]) -> Self {
+        other.iter().cloned().collect()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'borrow, K, V, S> From<HashMap<K, V, S>> for Value<'borrow>
+where
+    K: Into<Cow<'borrow, str>>,
+    V: Into<Value<'borrow>>,
+{
+    fn from(other: HashMap<K, V, S>) -> Self {
+        Self::Map(Box::new(
+            other
+                .into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        ))
+    }
+}
+
 impl<T> FromIterator<T> for Value<'_>
 where
     T: Into<Self>,
 {
     fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
-        Self::List(iter.into_iter().map(Into::into).collect())
+        Self::List(Box::new(iter.into_iter().map(Into::into).collect()))
+    }
+}
+
+/// An iterator over the items of a [`Value`], see its `IntoIterator`
+/// implementation.
+#[derive(Clone, Debug)]
+pub struct ValueIntoIter<'borrow>(IntoIterInner<'borrow>);
+
+#[derive(Clone, Debug)]
+enum IntoIterInner<'borrow> {
+    List(alloc::vec::IntoIter<Value<'borrow>>),
+    Slice(core::slice::Iter<'borrow, Value<'borrow>>),
+    Once(Option<Value<'borrow>>),
+}
+
+impl<'borrow> Iterator for ValueIntoIter<'borrow> {
+    type Item = Value<'borrow>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            IntoIterInner::List(items) => items.next(),
+            IntoIterInner::Slice(items) => items.next().map(Value::borrowed),
+            IntoIterInner::Once(item) => item.take(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len();
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for ValueIntoIter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            IntoIterInner::List(items) => items.next_back(),
+            IntoIterInner::Slice(items) => items.next_back().map(Value::borrowed),
+            IntoIterInner::Once(item) => item.take(),
+        }
+    }
+}
+
+impl ExactSizeIterator for ValueIntoIter<'_> {
+    fn len(&self) -> usize {
+        match &self.0 {
+            IntoIterInner::List(items) => items.len(),
+            IntoIterInner::Slice(items) => items.len(),
+            IntoIterInner::Once(item) => usize::from(item.is_some()),
+        }
+    }
+}
+
+impl core::iter::FusedIterator for ValueIntoIter<'_> {}
+
+/// Iterate over the items of a `List` or a `Slice`, or over the value itself
+/// if it is a scalar or a `Map`, or over nothing if it is `Null`.
+///
+/// The items of a `Slice` are yielded as if by [`Value::borrowed`].
+///
+/// ```
+/// # use gpnd::Value;
+/// let hosts = Value::from(["a", "b"]);
+/// assert_eq!(hosts.into_iter().collect::<Vec<_>>(), ["a", "b"]);
+/// assert_eq!(Value::from("a").into_iter().collect::<Vec<_>>(), ["a"]);
+/// assert_eq!(Value::Null.into_iter().count(), 0);
+/// ```
+impl<'borrow> IntoIterator for Value<'borrow> {
+    type Item = Self;
+    type IntoIter = ValueIntoIter<'borrow>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        ValueIntoIter(match self {
+            Self::List(items) => IntoIterInner::List(items.into_iter()),
+            Self::Slice(items) => IntoIterInner::Slice(items.iter()),
+            Self::Null => IntoIterInner::Once(None),
+            value => IntoIterInner::Once(Some(value)),
+        })
+    }
+}
+
+impl<'value, 'borrow> IntoIterator for &'value Value<'borrow> {
+    type Item = &'value Value<'borrow>;
+    type IntoIter = core::slice::Iter<'value, Value<'borrow>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Convert a reference into a [`Value`] that borrows from it.
+///
+/// Unlike `From`, this does not consume or clone the source, which makes it
+/// suitable for implementing [`Node`](crate::Node) over fields of a struct.
+pub trait ToValue {
+    fn to_value(&self) -> Value<'_>;
+}
+
+macro_rules! impl_to_value {
+    ($($from:ty),+) => {
+        $(
+            impl ToValue for $from {
+                fn to_value(&self) -> Value<'_> {
+                    Value::from(*self)
+                }
+            }
+        )+
+    };
+}
+
+impl_to_value!(
+    u8,
+    u16,
+    u32,
+    u64,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    isize,
+    f32,
+    f64,
+    bool,
+    char,
+    NonZeroU8,
+    NonZeroU16,
+    NonZeroU32,
+    NonZeroU64,
+    NonZeroUsize,
+    NonZeroI8,
+    NonZeroI16,
+    NonZeroI32,
+    NonZeroI64,
+    NonZeroIsize
+);
+
+impl ToValue for str {
+    fn to_value(&self) -> Value<'_> {
+        Value::from(self)
+    }
+}
+
+impl ToValue for String {
+    fn to_value(&self) -> Value<'_> {
+        Value::from(self)
+    }
+}
+
+impl ToValue for Cow<'_, str> {
+    fn to_value(&self) -> Value<'_> {
+        Value::String(Cow::Borrowed(self))
+    }
+}
+
+impl ToValue for Value<'_> {
+    fn to_value(&self) -> Value<'_> {
+        self.borrowed()
+    }
+}
+
+impl<T> ToValue for &T
+where
+    T: ToValue + ?Sized,
+{
+    fn to_value(&self) -> Value<'_> {
+        (**self).to_value()
+    }
+}
+
+impl<T> ToValue for Option<T>
+where
+    T: ToValue,
+{
+    fn to_value(&self) -> Value<'_> {
+        self.as_ref().map_or(Value::Null, ToValue::to_value)
+    }
+}
+
+impl<T> ToValue for [T]
+where
+    T: ToValue,
+{
+    fn to_value(&self) -> Value<'_> {
+        self.iter().map(ToValue::to_value).collect()
+    }
+}
+
+impl<T, const N: usize> ToValue for [T; N]
+where
+    T: ToValue,
+{
+    fn to_value(&self) -> Value<'_> {
+        self.as_slice().to_value()
+    }
+}
+
+impl<T> ToValue for Vec<T>
+where
+    T: ToValue,
+{
+    fn to_value(&self) -> Value<'_> {
+        self.as_slice().to_value()
+    }
+}
+
+impl<K, V> ToValue for BTreeMap<K, V>
+where
+    K: AsRef<str>,
+    V: ToValue,
+{
+    fn to_value(&self) -> Value<'_> {
+        Value::Map(Box::new(
+            self.iter()
+                .map(|(key, value)| (Cow::Borrowed(key.as_ref()), value.to_value()))
+                .collect(),
+        ))
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+#[cfg(feature = "std")]
+impl<K, V, S> ToValue for HashMap<K, V, S>
+where
+    K: AsRef<str>,
+    V: ToValue,
+{
+    fn to_value(&self) -> Value<'_> {
+        Value::Map(Box::new(
+            self.iter()
+                .map(|(key, value)| (Cow::Borrowed(key.as_ref()), value.to_value()))
+                .collect(),
+        ))
+    }
+}
+
+/// The error returned when a [`Value`] cannot be converted to a type, which
+/// keeps the value, and can be given the location it was found at.
+#[derive(Clone, Debug)]
 pub struct IntoInnerError {
-    variant: &'static str,
+    value: Value<'static>,
     into_type: &'static str,
+    context: Option<Box<Context>>,
+}
+
+/// Where the value of an [`IntoInnerError`] was found, if known.
+#[derive(Clone, Debug, Default)]
+struct Context {
+    location: Option<ValueLocation>,
+    span: Option<Span>,
+}
+
+impl IntoInnerError {
+    /// The error for a `value` that cannot be converted to the type named
+    /// `into_type`.
+    ///
+    /// Borrowed strings and lists in the value are copied.
+    #[must_use]
+    pub fn new(value: Value<'_>, into_type: &'static str) -> Self {
+        Self {
+            value: value.into_owned(),
+            into_type,
+            context: None,
+        }
+    }
+
+    /// The kind of the value that could not be converted.
+    #[must_use]
+    pub fn kind(&self) -> ValueKind {
+        self.value.kind()
+    }
+
+    /// The name of the type that the value could not be converted to.
+    #[must_use]
+    pub fn into_type(&self) -> &'static str {
+        self.into_type
+    }
+
+    /// The value that could not be converted.
+    #[must_use]
+    pub fn value(&self) -> &Value<'static> {
+        &self.value
+    }
+
+    /// Take back the value that could not be converted, such as to try
+    /// another conversion.
+    #[must_use]
+    pub fn into_value(self) -> Value<'static> {
+        self.value
+    }
+
+    /// The argument or parameter that the value was found at, if known.
+    #[must_use]
+    pub fn location(&self) -> Option<&ValueLocation> {
+        self.context.as_ref()?.location.as_ref()
+    }
+
+    /// The span of the value in its source, if known.
+    #[must_use]
+    pub fn span(&self) -> Option<Span> {
+        self.context.as_ref()?.span
+    }
+
+    /// Record the argument or parameter that the value was found at.
+    #[must_use]
+    pub fn with_location(mut self, location: ValueLocation) -> Self {
+        self.context.get_or_insert_default().location = Some(location);
+        self
+    }
+
+    /// Record the span of the value in its source.
+    #[must_use]
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.context.get_or_insert_default().span = Some(span);
+        self
+    }
 }
 
-impl std::fmt::Display for IntoInnerError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for IntoInnerError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if let Some(span) = self.span() {
+            write!(f, "{span}: ")?;
+        }
+        if let Some(location) = self.location() {
+            write!(f, "{location}: ")?;
+        }
         write!(
             f,
-            "cannot not convert variant `{}` to a `{}`",
-            self.variant, self.into_type
+            "cannot convert variant `{}` to a `{}`",
+            self.kind(),
+            self.into_type
         )
     }
 }
@@ -171,18 +1150,18 @@ pub trait IntoInner<T>: crate::Sealed {
 
 macro_rules! impl_into_inner {
     ($($variant:ident => $inner:ty),+) => {
-        $(impl_into_inner!($variant, $inner);)+
+        $(impl_into_inner!($variant, $inner, value => value);)+
     };
-    ($variant:ident, $inner:ty) => {
+    (box $($variant:ident => $inner:ty),+) => {
+        $(impl_into_inner!($variant, $inner, value => *value);)+
+    };
+    ($variant:ident, $inner:ty, $value:ident => $unwrap:expr) => {
         #[allow(single_use_lifetimes)]
         impl<'borrow> IntoInner<$inner> for Value<'borrow> {
             fn into_inner(self) -> Result<$inner, IntoInnerError> {
                 match self {
-                    Self::$variant(value) => Ok(value),
-                    _ => Err(IntoInnerError {
-                        variant: self.kind(),
-                        into_type: stringify!($inner),
-                    }),
+                    Self::$variant($value) => Ok($unwrap),
+                    _ => Err(IntoInnerError::new(self, stringify!($inner))),
                 }
             }
         }
@@ -191,12 +1170,9 @@ macro_rules! impl_into_inner {
         impl<'borrow> IntoInner<Option<$inner>> for Value<'borrow> {
             fn into_inner(self) -> Result<Option<$inner>, IntoInnerError> {
                 match self {
-                    Self::$variant(value) => Ok(Some(value)),
+                    Self::$variant($value) => Ok(Some($unwrap)),
                     Self::Null => Ok(None),
-                    _ => Err(IntoInnerError {
-                        variant: self.kind(),
-                        into_type: const_format::formatcp!("Option<{}>", stringify!($inner)),
-                    }),
+                    _ => Err(IntoInnerError::new(self, const_format::formatcp!("Option<{}>", stringify!($inner)))),
                 }
             }
         }
@@ -217,18 +1193,29 @@ impl_into_inner!(
     F32 => f32,
     F64 => f64,
     Bool => bool,
-    String => Cow<'borrow, str>,
-    List => Vec<Value<'borrow>>
+    String => Cow<'borrow, str>
+);
+
+impl_into_inner!(
+    box
+    List => Vec<Value<'borrow>>,
+    Map => BTreeMap<Cow<'borrow, str>, Value<'borrow>>
 );
 
 impl<'borrow> IntoInner<&'borrow str> for Value<'borrow> {
     fn into_inner(self) -> Result<&'borrow str, IntoInnerError> {
         match self {
             Self::String(Cow::Borrowed(inner)) => Ok(inner),
-            _ => Err(IntoInnerError {
-                variant: self.kind(),
-                into_type: "&str",
-            }),
+            _ => Err(IntoInnerError::new(self, "&str")),
+        }
+    }
+}
+
+impl<'borrow> IntoInner<&'borrow [Value<'borrow>]> for Value<'borrow> {
+    fn into_inner(self) -> Result<&'borrow [Value<'borrow>], IntoInnerError> {
+        match self {
+            Self::Slice(inner) => Ok(inner),
+            _ => Err(IntoInnerError::new(self, "&[Value]")),
         }
     }
 }
@@ -237,19 +1224,307 @@ impl IntoInner<String> for Value<'_> {
     fn into_inner(self) -> Result<String, IntoInnerError> {
         match self {
             Self::String(Cow::Owned(inner)) => Ok(inner),
-            _ => Err(IntoInnerError {
-                variant: self.kind(),
-                into_type: "String",
-            }),
+            _ => Err(IntoInnerError::new(self, "String")),
+        }
+    }
+}
+
+macro_rules! impl_into_inner_with {
+    ($($(#[$attr:meta])* $ty:ty => |$value:ident| $convert:expr;)+) => {
+        $(
+            $(#[$attr])*
+            impl IntoInner<$ty> for Value<'_> {
+                fn into_inner(self) -> Result<$ty, IntoInnerError> {
+                    let $value = &self;
+                    $convert.ok_or_else(|| IntoInnerError::new(self, stringify!($ty)))
+                }
+            }
+
+            $(#[$attr])*
+            impl IntoInner<Option<$ty>> for Value<'_> {
+                fn into_inner(self) -> Result<Option<$ty>, IntoInnerError> {
+                    match self {
+                        Self::Null => Ok(None),
+                        value => IntoInner::<$ty>::into_inner(value)
+                            .map(Some)
+                            .map_err(|error| IntoInnerError {
+                                into_type: concat!("Option<", stringify!($ty), ">"),
+                                ..error
+                            }),
+                    }
+                }
+            }
+
+            $(#[$attr])*
+            impl TryFrom<Value<'_>> for $ty {
+                type Error = IntoInnerError;
+
+                fn try_from(value: Value<'_>) -> Result<Self, Self::Error> {
+                    value.into_inner()
+                }
+            }
+        )+
+    };
+}
+
+// Types that configuration values usually end up in, which are written as
+// strings, or for durations, also as integers.
+impl_into_inner_with!(
+    /// Integers are a number of seconds, so that `timeout=30` is thirty
+    /// seconds, and strings are a number with a duration suffix, such as
+    /// `250ms` or `1.5h`.
+    Duration => |value| match value {
+        Self::Duration(inner) => Some(Duration::from_nanos(*inner)),
+        Self::String(inner) => parse_duration(inner).map(Duration::from_nanos),
+        value => value
+            .as_integer()
+            .and_then(|inner| u64::try_from(inner).ok())
+            .map(Duration::from_secs),
+    };
+    IpAddr => |value| value.as_inner().ok().and_then(|inner: &str| inner.parse().ok());
+    SocketAddr => |value| value.as_inner().ok().and_then(|inner: &str| inner.parse().ok());
+    #[cfg(feature = "std")]
+    PathBuf => |value| value.as_inner().ok().map(|inner: &str| PathBuf::from(inner));
+);
+
+/// Convert a [`Value`] to `Self` from any variant that holds it, as the typed
+/// getters of [`NodeExt`](crate::NodeExt) do.
+///
+/// Unlike [`IntoInner`], integers convert from any integer variant that fits,
+/// as with [`OptionValueExt::as_u16`], and a `String` is taken from both owned
+/// and borrowed strings.
+///
+/// ```
+/// # use gpnd::{TryFromValue, Value};
+/// assert_eq!(u16::try_from_value(Value::I64(8080)).unwrap(), 8080);
+/// assert!(u8::try_from_value(Value::I64(256)).is_err());
+/// assert_eq!(String::try_from_value(Value::from("a")).unwrap(), "a");
+/// ```
+pub trait TryFromValue<'value>: Sized {
+    /// # Errors
+    /// Fails if the value does not hold a `Self`.
+    fn try_from_value(value: Value<'value>) -> Result<Self, IntoInnerError>;
+}
+
+macro_rules! impl_try_from_value {
+    (integer $($int:ty),+) => {
+        $(
+            impl TryFromValue<'_> for $int {
+                fn try_from_value(value: Value<'_>) -> Result<Self, IntoInnerError> {
+                    match value.as_integer().and_then(|inner| Self::try_from(inner).ok()) {
+                        Some(inner) => Ok(inner),
+                        None => Err(IntoInnerError::new(value, stringify!($int))),
+                    }
+                }
+            }
+        )+
+    };
+    ($($ty:ty),+) => {
+        $(
+            #[allow(single_use_lifetimes)]
+            impl<'value> TryFromValue<'value> for $ty {
+                fn try_from_value(value: Value<'value>) -> Result<Self, IntoInnerError> {
+                    value.into_inner()
+                }
+            }
+        )+
+    };
+}
+
+impl_try_from_value!(integer u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+impl_try_from_value!(
+    f32,
+    bool,
+    &'value str,
+    Cow<'value, str>,
+    &'value [Value<'value>],
+    Vec<Value<'value>>,
+    BTreeMap<Cow<'value, str>, Value<'value>>,
+    Duration,
+    IpAddr,
+    SocketAddr
+);
+
+#[cfg(feature = "std")]
+impl_try_from_value!(PathBuf);
+
+impl TryFromValue<'_> for f64 {
+    fn try_from_value(value: Value<'_>) -> Result<Self, IntoInnerError> {
+        match value.as_float() {
+            Some(inner) => Ok(inner),
+            None => Err(IntoInnerError::new(value, "f64")),
+        }
+    }
+}
+
+impl TryFromValue<'_> for String {
+    fn try_from_value(value: Value<'_>) -> Result<Self, IntoInnerError> {
+        value.into_string()
+    }
+}
+
+impl<'value, T> TryFromValue<'value> for Option<T>
+where
+    T: TryFromValue<'value>,
+{
+    /// `Null` converts to `None`.
+    fn try_from_value(value: Value<'value>) -> Result<Self, IntoInnerError> {
+        match value {
+            Value::Null => Ok(None),
+            value => T::try_from_value(value).map(Some),
+        }
+    }
+}
+
+pub trait AsInner<'value, T>: crate::Sealed {
+    /// Borrow the inner value of the variant as `T`, without consuming.
+    ///
+    /// Scalar types are copied out, strings and lists are borrowed.
+    fn as_inner(&'value self) -> Result<T, IntoInnerError>;
+}
+
+macro_rules! impl_as_inner {
+    ($($variant:ident => $inner:ty),+) => {
+        $(impl_as_inner!($variant, $inner);)+
+    };
+    ($variant:ident, $inner:ty) => {
+        #[allow(single_use_lifetimes)]
+        impl<'value> AsInner<'value, $inner> for Value<'_> {
+            fn as_inner(&'value self) -> Result<$inner, IntoInnerError> {
+                match self {
+                    Self::$variant(value) => Ok(*value),
+                    _ => Err(IntoInnerError::new(self.clone(), stringify!($inner))),
+                }
+            }
+        }
+
+        #[allow(single_use_lifetimes)]
+        impl<'value> AsInner<'value, Option<$inner>> for Value<'_> {
+            fn as_inner(&'value self) -> Result<Option<$inner>, IntoInnerError> {
+                match self {
+                    Self::$variant(value) => Ok(Some(*value)),
+                    Self::Null => Ok(None),
+                    _ => Err(IntoInnerError::new(self.clone(), const_format::formatcp!("Option<{}>", stringify!($inner)))),
+                }
+            }
+        }
+    };
+}
+
+impl_as_inner!(
+    U8 => u8,
+    U16 => u16,
+    U32 => u32,
+    U64 => u64,
+    Uint => usize,
+    I8 => i8,
+    I16 => i16,
+    I32 => i32,
+    I64 => i64,
+    Int => isize,
+    F32 => f32,
+    F64 => f64,
+    Bool => bool
+);
+
+impl<'value> AsInner<'value, &'value str> for Value<'_> {
+    fn as_inner(&'value self) -> Result<&'value str, IntoInnerError> {
+        match self {
+            Self::String(inner) => Ok(inner.as_ref()),
+            _ => Err(IntoInnerError::new(self.clone(), "&str")),
+        }
+    }
+}
+
+impl<'value> AsInner<'value, Option<&'value str>> for Value<'_> {
+    fn as_inner(&'value self) -> Result<Option<&'value str>, IntoInnerError> {
+        match self {
+            Self::String(inner) => Ok(Some(inner.as_ref())),
+            Self::Null => Ok(None),
+            _ => Err(IntoInnerError::new(self.clone(), "Option<&str>")),
+        }
+    }
+}
+
+impl<'value, 'borrow> AsInner<'value, &'value [Value<'borrow>]> for Value<'borrow> {
+    fn as_inner(&'value self) -> Result<&'value [Value<'borrow>], IntoInnerError> {
+        match self {
+            Self::List(inner) => Ok(inner.as_slice()),
+            Self::Slice(inner) => Ok(inner),
+            _ => Err(IntoInnerError::new(self.clone(), "&[Value]")),
+        }
+    }
+}
+
+impl<'value, 'borrow> AsInner<'value, Option<&'value [Value<'borrow>]>> for Value<'borrow> {
+    fn as_inner(&'value self) -> Result<Option<&'value [Value<'borrow>]>, IntoInnerError> {
+        match self {
+            Self::List(inner) => Ok(Some(inner.as_slice())),
+            Self::Slice(inner) => Ok(Some(inner)),
+            Self::Null => Ok(None),
+            _ => Err(IntoInnerError::new(self.clone(), "Option<&[Value]>")),
+        }
+    }
+}
+
+impl<'value, 'borrow> AsInner<'value, &'value BTreeMap<Cow<'borrow, str>, Value<'borrow>>>
+    for Value<'borrow>
+{
+    fn as_inner(
+        &'value self,
+    ) -> Result<&'value BTreeMap<Cow<'borrow, str>, Value<'borrow>>, IntoInnerError> {
+        match self {
+            Self::Map(inner) => Ok(inner.as_ref()),
+            _ => Err(IntoInnerError::new(
+                self.clone(),
+                "&BTreeMap<Cow<str>, Value>",
+            )),
         }
     }
 }
 
+impl Value<'_> {
+    /// Get the inner value as `T` without consuming, see [`AsInner`].
+    ///
+    /// ```
+    /// # use gpnd::Value;
+    /// let value = Value::from(8080_u16);
+    /// assert_eq!(value.get::<u16>().unwrap(), 8080);
+    /// assert!(value.get::<&str>().is_err());
+    /// ```
+    pub fn get<'value, T>(&'value self) -> Result<T, IntoInnerError>
+    where
+        Self: AsInner<'value, T>,
+    {
+        self.as_inner()
+    }
+
+    /// Consume the value and convert it to `T`, see [`IntoInner`].
+    ///
+    /// This is the same as [`IntoInner::into_inner`], but the target type can
+    /// be given with a turbofish.
+    pub fn into_type<T>(self) -> Result<T, IntoInnerError>
+    where
+        Self: IntoInner<T>,
+    {
+        self.into_inner()
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
-    use std::borrow::Cow;
+    use alloc::borrow::{Cow, ToOwned};
+    use alloc::boxed::Box;
+    use alloc::collections::BTreeMap;
+    use alloc::string::{String, ToString};
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use core::num::NonZeroU32;
+    #[cfg(feature = "std")]
+    use std::collections::HashMap;
 
-    use super::{IntoInner, Value};
+    use super::{AsInner, IntoInner, OptionValueExt, ToValue, Value, ValueError, ValueKind};
 
     #[test]
     fn test_roundtrip_str_borrowed() {
@@ -287,4 +1562,253 @@ pub mod tests {
 
         assert_eq!(expect, inner);
     }
+
+    #[test]
+    fn test_get_scalar() {
+        let value = Value::from(42_u32);
+
+        assert_eq!(value.get::<u32>().unwrap(), 42);
+        assert_eq!(value.get::<Option<u32>>().unwrap(), Some(42));
+        assert!(value.get::<i32>().is_err());
+        // The value was not consumed by any of the above.
+        assert_eq!(value.into_type::<u32>().unwrap(), 42);
+    }
+
+    #[test]
+    fn test_get_borrowed() {
+        let value = Value::from("foo".to_owned());
+        let inner: &str = value.as_inner().unwrap();
+        assert_eq!(inner, "foo");
+
+        let value = Value::from_iter([1_u8, 2, 3]);
+        let inner = value.get::<&[Value<'_>]>().unwrap();
+        assert_eq!(inner, &[Value::U8(1), Value::U8(2), Value::U8(3)]);
+
+        assert_eq!(Value::Null.get::<Option<&str>>().unwrap(), None);
+    }
+
+    #[test]
+    fn test_from_broader() {
+        assert_eq!(Value::from('x'), Value::from("x"));
+        assert_eq!(Value::from(NonZeroU32::new(7)), Value::U32(7));
+        assert_eq!(Value::from(None::<NonZeroU32>), Value::Null);
+        assert_eq!(
+            Value::from([1_i32, 2]),
+            Value::from(vec![Value::I32(1), Value::I32(2)])
+        );
+        assert_eq!(
+            Value::from(["a", "b"].as_slice()),
+            Value::from(vec![Value::from("a"), Value::from("b")])
+        );
+
+        #[cfg(feature = "std")]
+        {
+            let map = HashMap::from([("port".to_owned(), 8080_u16)]);
+            let value = Value::from(map);
+            let inner = value.get::<&BTreeMap<_, _>>().unwrap();
+            assert_eq!(inner.get("port"), Some(&Value::U16(8080)));
+        }
+    }
+
+    #[test]
+    fn test_value_size() {
+        // The largest inline payload is a `Cow<'_, str>`, whose niche holds the
+        // discriminant.
+        assert_eq!(size_of::<Value<'_>>(), size_of::<Cow<'_, str>>());
+    }
+
+    #[test]
+    fn test_borrowed_slice() {
+        let owned = Value::from_iter(["a", "b"]);
+        let borrowed = owned.borrowed();
+
+        assert!(matches!(borrowed, Value::Slice(_)));
+        assert_eq!(owned, borrowed);
+        assert_eq!(borrowed.get::<&[Value<'_>]>().unwrap().len(), 2);
+        assert!(Value::from_iter(["a"]) < borrowed);
+    }
+
+    #[test]
+    fn test_into_iter() {
+        let list = Value::from_iter([1_u8, 2, 3]);
+        let mut items = list.borrowed().into_iter();
+        assert_eq!(items.len(), 3);
+        assert_eq!(items.next_back(), Some(3_u8.into()));
+        assert_eq!(items.collect::<Vec<_>>(), [1_u8, 2]);
+        assert_eq!(list.iter().next_back(), Some(&3_u8.into()));
+        let mut sum = 0;
+        for item in list {
+            sum += item.get::<u8>().unwrap();
+        }
+        assert_eq!(sum, 6);
+
+        let map = Value::Map(Box::new(BTreeMap::from([("a".into(), 1_u8.into())])));
+        assert_eq!(map.iter().collect::<Vec<_>>(), [&map]);
+        assert_eq!(map.clone().into_iter().collect::<Vec<_>>(), [map]);
+        assert_eq!((&Value::Null).into_iter().len(), 0);
+        assert_eq!(Value::Null.into_iter().next(), None);
+    }
+
+    #[test]
+    fn test_as_str() {
+        let owned = Value::from("owned".to_owned());
+        let borrowed = Value::from("borrowed");
+        assert_eq!(owned.as_str(), Some("owned"));
+        assert_eq!(borrowed.as_str(), Some("borrowed"));
+        assert_eq!(Value::Null.as_str(), None);
+        assert_eq!(owned.into_string().unwrap(), "owned");
+        assert_eq!(borrowed.into_string().unwrap(), "borrowed");
+        let error = Value::Bool(true).into_string().unwrap_err();
+        assert_eq!(error.kind(), ValueKind::Bool);
+    }
+
+    #[test]
+    fn test_into_std_types() {
+        use core::net::{IpAddr, Ipv4Addr, SocketAddr};
+        use core::time::Duration;
+        #[cfg(feature = "std")]
+        use std::path::PathBuf;
+
+        let timeout: Duration = Value::from("1.5s").into_inner().unwrap();
+        assert_eq!(timeout, Duration::from_millis(1500));
+        assert_eq!(
+            Duration::try_from(Value::Duration(250_000_000)).unwrap(),
+            Duration::from_millis(250)
+        );
+        assert_eq!(
+            Duration::try_from(Value::U64(30)).unwrap(),
+            Duration::from_secs(30)
+        );
+        assert!(Duration::try_from(Value::from("1.5kb")).is_err());
+        assert!(Duration::try_from(Value::from("-1s")).is_err());
+        assert!(Duration::try_from(Value::I64(-1)).is_err());
+
+        let host: IpAddr = Value::from("127.0.0.1").into_inner().unwrap();
+        assert_eq!(host, Ipv4Addr::LOCALHOST);
+        let listen = SocketAddr::try_from(Value::from("[::1]:8080")).unwrap();
+        assert_eq!(listen.port(), 8080);
+        let error = SocketAddr::try_from(Value::from("localhost")).unwrap_err();
+        assert_eq!(error.into_type(), "SocketAddr");
+
+        #[cfg(feature = "std")]
+        {
+            let path: Option<PathBuf> = Value::from("/etc/app.gpnd".to_owned())
+                .into_inner()
+                .unwrap();
+            assert_eq!(path, Some(PathBuf::from("/etc/app.gpnd")));
+            assert_eq!(
+                IntoInner::<Option<PathBuf>>::into_inner(Value::Null).unwrap(),
+                None
+            );
+            let error = IntoInner::<Option<PathBuf>>::into_inner(Value::Bool(true)).unwrap_err();
+            assert_eq!(error.into_type(), "Option<PathBuf>");
+        }
+    }
+
+    #[test]
+    fn test_kind() {
+        assert_eq!(Value::from(1_u8).kind(), ValueKind::U8);
+        assert_eq!(Value::from("foo").kind().to_string(), "String");
+
+        let error = Value::Null.get::<bool>().unwrap_err();
+        assert_eq!(error.kind(), ValueKind::Null);
+        assert_eq!(error.into_type(), "bool");
+    }
+
+    #[test]
+    fn test_into_inner_error() {
+        use crate::{Position, Span, ValueLocation};
+
+        let error = Value::from("8080").into_type::<u16>().unwrap_err();
+        assert_eq!(error.value(), &Value::from("8080"));
+        assert_eq!(
+            error.to_string(),
+            "cannot convert variant `String` to a `u16`"
+        );
+        // The value can be taken back to try another conversion.
+        let port = error.into_value().into_string().unwrap();
+        assert_eq!(port.parse::<u16>().unwrap(), 8080);
+
+        let start = Position {
+            offset: 12,
+            line: 2,
+            column: 5,
+        };
+        let error = Value::Bool(true)
+            .get::<&str>()
+            .unwrap_err()
+            .with_location(ValueLocation::Param("host".into()))
+            .with_span(Span::new(start, start));
+        assert_eq!(error.location(), Some(&ValueLocation::Param("host".into())));
+        assert_eq!(
+            error.to_string(),
+            "2:5: parameter `host`: cannot convert variant `Bool` to a `&str`"
+        );
+    }
+
+    #[test]
+    fn test_eq_primitive() {
+        assert_eq!(Value::U16(5), 5);
+        assert_eq!(5, Value::U16(5));
+        assert_eq!(Value::I64(-1), -1_i8);
+        assert_ne!(Value::U8(255), -1_i8);
+        assert_eq!(Value::F32(0.5), 0.5);
+        assert_eq!(Value::U8(2), 2.0);
+        assert_eq!(Value::Bool(true), true);
+        assert_eq!(Value::from("foo"), "foo");
+        assert_eq!("foo", Value::from("foo".to_owned()));
+        assert_ne!(Value::from("5"), 5);
+        assert_ne!(Value::Null, false);
+    }
+
+    #[test]
+    fn test_null_coalescing() {
+        assert!(Value::default().is_null());
+        assert_eq!(Value::Null.or(Value::from(1_u8)), Value::U8(1));
+        assert_eq!(Value::from(2_u8).or(Value::from(1_u8)), Value::U8(2));
+
+        let missing: Option<Value<'_>> = None;
+        assert!(missing.clone().unwrap_or_null().is_null());
+        assert_eq!(missing.or_value(8080_u16), 8080);
+        assert_eq!(Some(Value::Null).or_value("default"), "default");
+        assert_eq!(Some(Value::from(true)).or_value(false), true);
+    }
+
+    #[test]
+    fn test_option_value_conversions() {
+        let port = Some(Value::I64(8080));
+        assert_eq!(port.as_u16().unwrap(), 8080);
+        assert!(port
+            .as_f64()
+            .is_ok_and(|port| (port - 8080.0).abs() < f64::EPSILON));
+        assert!(matches!(port.as_u8(), Err(ValueError::Convert(_))));
+        assert!(matches!(port.as_str(), Err(ValueError::Convert(_))));
+        assert_eq!(port.into_inner_opt::<i64>().unwrap(), 8080);
+
+        let missing: Option<Value<'_>> = None;
+        assert!(matches!(missing.as_bool(), Err(ValueError::Missing)));
+        assert!(matches!(
+            missing.into_inner_opt::<String>(),
+            Err(ValueError::Missing)
+        ));
+        assert_eq!(Some(Value::from("on")).as_str().unwrap(), "on");
+    }
+
+    #[test]
+    fn test_to_value() {
+        let owned = "foo".to_owned();
+        assert!(matches!(
+            owned.to_value(),
+            Value::String(Cow::Borrowed("foo"))
+        ));
+        assert_eq!(5_u16.to_value(), Value::U16(5));
+        assert_eq!(None::<u8>.to_value(), Value::Null);
+        assert_eq!(vec![1_u8, 2].to_value(), Value::from([1_u8, 2]));
+
+        #[cfg(feature = "std")]
+        {
+            let map = HashMap::from([("a", 1_u8)]);
+            assert_eq!(map.to_value(), Value::from(map.clone()));
+        }
+    }
 }
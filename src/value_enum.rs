@@ -0,0 +1,173 @@
+use alloc::string::String;
+use core::error::Error;
+use core::fmt;
+
+use crate::{Value, ValueKind};
+
+/// An enum without fields that is written as one of a fixed set of strings,
+/// such as `log-level="debug"`.
+///
+/// This is usually derived with `#[derive(ValueEnum)]` and the `derive`
+/// feature, which also converts the enum to and from a [`Value`], and lets it
+/// be the field of a derived [`Node`](crate::Node).
+pub trait ValueEnum: Sized {
+    /// The names of the variants, in the order they are declared.
+    const NAMES: &'static [&'static str];
+
+    /// The variant with the given name, which must match exactly.
+    fn from_name(name: &str) -> Option<Self>;
+
+    /// The name of the variant.
+    fn name(&self) -> &'static str;
+
+    /// Convert a string value to the variant with its name.
+    ///
+    /// # Errors
+    /// Fails if the value is not a string, or not one of [`Self::NAMES`].
+    fn from_value(value: &Value<'_>) -> Result<Self, EnumValueError> {
+        let Value::String(name) = value else {
+            return Err(EnumValueError::NotString {
+                kind: value.kind(),
+                expected: Self::NAMES,
+            });
+        };
+        Self::from_name(name).ok_or_else(|| EnumValueError::UnknownName {
+            name: String::from(&**name),
+            expected: Self::NAMES,
+        })
+    }
+}
+
+/// The error returned when a [`Value`] is not the name of a variant of a
+/// [`ValueEnum`], which lists the names that are.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EnumValueError {
+    NotString {
+        kind: ValueKind,
+        expected: &'static [&'static str],
+    },
+    UnknownName {
+        name: String,
+        expected: &'static [&'static str],
+    },
+}
+
+impl EnumValueError {
+    /// The names of the variants.
+    #[must_use]
+    pub fn expected(&self) -> &'static [&'static str] {
+        match self {
+            Self::NotString { expected, .. } | Self::UnknownName { expected, .. } => expected,
+        }
+    }
+}
+
+impl fmt::Display for EnumValueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotString { kind, .. } => write!(f, "expected a string, found `{kind}`")?,
+            Self::UnknownName { name, .. } => write!(f, "unknown value `{name}`")?,
+        }
+        for (index, name) in self.expected().iter().enumerate() {
+            let separator = if index == 0 { ", expected one of" } else { "," };
+            write!(f, "{separator} `{name}`")?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for EnumValueError {}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::{EnumValueError, ValueEnum};
+    use crate::{Value, ValueKind};
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    enum LogLevel {
+        Debug,
+        Info,
+    }
+
+    impl ValueEnum for LogLevel {
+        const NAMES: &'static [&'static str] = &["debug", "info"];
+
+        fn from_name(name: &str) -> Option<Self> {
+            match name {
+                "debug" => Some(Self::Debug),
+                "info" => Some(Self::Info),
+                _ => None,
+            }
+        }
+
+        fn name(&self) -> &'static str {
+            Self::NAMES[*self as usize]
+        }
+    }
+
+    #[test]
+    fn test_value_enum() {
+        assert_eq!(LogLevel::from_value(&"info".into()), Ok(LogLevel::Info));
+        assert_eq!(LogLevel::Debug.name(), "debug");
+
+        let error = LogLevel::from_value(&"trace".into()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "unknown value `trace`, expected one of `debug`, `info`"
+        );
+        let error = LogLevel::from_value(&Value::I64(1)).unwrap_err();
+        assert_eq!(
+            error,
+            EnumValueError::NotString {
+                kind: ValueKind::I64,
+                expected: LogLevel::NAMES,
+            }
+        );
+        assert!(error
+            .to_string()
+            .starts_with("expected a string, found `I64`, expected one of"));
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_value_enum() {
+        use crate::{Node, ToValue};
+
+        #[derive(Debug, PartialEq, crate::ValueEnum)]
+        enum Format {
+            PlainText,
+            #[value(rename = "json")]
+            Json,
+        }
+
+        #[derive(Debug, PartialEq, crate::ValueEnum)]
+        #[value(rename_all = "SCREAMING_SNAKE_CASE")]
+        enum Mode {
+            ReadOnly,
+            ReadWrite,
+        }
+
+        #[derive(crate::Node)]
+        struct Output {
+            format: Format,
+        }
+
+        assert_eq!(Format::NAMES, ["plain-text", "json"]);
+        assert_eq!(Mode::NAMES, ["READ_ONLY", "READ_WRITE"]);
+        assert_eq!(Format::PlainText.to_value(), "plain-text");
+        assert_eq!(Value::from(Mode::ReadWrite), "READ_WRITE");
+        assert_eq!(Format::try_from(Value::from("json")), Ok(Format::Json));
+        assert_eq!(
+            Mode::try_from(&Value::from("READ_ONLY")),
+            Ok(Mode::ReadOnly)
+        );
+        assert!(Mode::try_from(Value::from("read-only")).is_err());
+
+        let output = Output {
+            format: Format::Json,
+        };
+        assert_eq!(output.get_param("format"), Some(Value::from("json")));
+    }
+}
@@ -0,0 +1,60 @@
+use alloc::string::String;
+use core::fmt;
+
+use crate::{NodePath, Span};
+
+/// Something in a document that is allowed, but probably not what was meant,
+/// see [`Warning`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum WarningKind {
+    /// A parameter written more than once on a node, of which lookups find
+    /// only one.
+    DuplicateParam(String),
+    /// A key written more than once in a map, of which only the last is kept.
+    DuplicateMapKey(String),
+    /// A number with a unit, such as `1.5ns`, that was rounded to a whole
+    /// number of the base unit.
+    RoundedNumber(String),
+    /// A node that its schema marks as deprecated.
+    DeprecatedNode(String),
+    /// The last warning, when there are more than the limit, after which the
+    /// rest were dropped.
+    TooManyWarnings,
+}
+
+impl fmt::Display for WarningKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DuplicateParam(key) => write!(f, "duplicate parameter `{key}`"),
+            Self::DuplicateMapKey(key) => write!(f, "duplicate map key `{key}`"),
+            Self::RoundedNumber(text) => write!(f, "`{text}` was rounded"),
+            Self::DeprecatedNode(name) => write!(f, "`{name}` is deprecated"),
+            Self::TooManyWarnings => f.write_str("too many warnings, stopping"),
+        }
+    }
+}
+
+/// A non-fatal diagnostic, reported alongside a successful result by the
+/// parser and the schema validator.
+///
+/// The path is that of the node, as in a schema
+/// [`Violation`](crate::schema::Violation), and is empty for warnings from
+/// the parser, which have a span instead.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Warning {
+    pub path: NodePath,
+    pub span: Option<Span>,
+    pub kind: WarningKind,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.path, self.span) {
+            (path, Some(span)) if path.is_empty() => write!(f, "{span}: {}", self.kind),
+            (path, None) if path.is_empty() => write!(f, "{}", self.kind),
+            (path, Some(span)) => write!(f, "{path} (at {span}): {}", self.kind),
+            (path, None) => write!(f, "{path}: {}", self.kind),
+        }
+    }
+}
@@ -0,0 +1,269 @@
+//! Bindings for JavaScript through `wasm-bindgen`, so that web playgrounds
+//! and tools on Node.js can use the same parser.
+//!
+//! A [`JsDocument`] is a handle to a document that stays in the memory of
+//! the module, and a [`JsNode`] is a handle to one of its nodes. Values are
+//! converted when they are read: numbers become `number`s, or `bigint`s for
+//! integers that a `number` cannot hold exactly, lists become arrays, and
+//! maps become plain objects.
+//!
+//! ```js
+//! import { parse } from "gpnd";
+//!
+//! const document = parse("server port=80 { tls }");
+//! const server = document.node(0);
+//! server.param("port"); // 80
+//! server.child(0).name; // "tls"
+//! ```
+
+use std::rc::Rc;
+
+use js_sys::{Array, Object, Reflect};
+use wasm_bindgen::prelude::*;
+
+use crate::{Document, DocumentBuf, Node, NodeBuf, Value};
+
+/// Parse `source` as a single document.
+///
+/// # Errors
+/// Throws if the source is not valid.
+#[wasm_bindgen]
+pub fn parse(source: &str) -> Result<JsDocument, JsError> {
+    let document = crate::parser::parse(source)?;
+    Ok(JsDocument {
+        document: Rc::new(document),
+    })
+}
+
+/// Format `document` with the default options.
+#[wasm_bindgen(js_name = toString)]
+#[must_use]
+pub fn to_string(document: &JsDocument) -> String {
+    crate::emit::to_string(&*document.document)
+}
+
+/// A handle to a document, see [`parse`].
+#[wasm_bindgen(js_name = Document)]
+#[derive(Clone, Debug)]
+pub struct JsDocument {
+    document: Rc<DocumentBuf>,
+}
+
+#[wasm_bindgen(js_class = Document)]
+impl JsDocument {
+    /// The version from the `@version` directive, if there is one.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn version(&self) -> Option<String> {
+        self.document.version().map(str::to_owned)
+    }
+
+    /// The number of nodes at the top level.
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn length(&self) -> usize {
+        self.document.nodes_ref().len()
+    }
+
+    /// The node at the top level at `index`.
+    #[must_use]
+    pub fn node(&self, index: usize) -> Option<JsNode> {
+        self.document.nodes_ref().get(index)?;
+        Some(JsNode {
+            document: Rc::clone(&self.document),
+            path: vec![index],
+        })
+    }
+
+    #[must_use]
+    pub fn nodes(&self) -> Vec<JsNode> {
+        (0..self.length())
+            .filter_map(|index| self.node(index))
+            .collect()
+    }
+
+    /// Format the document with the default options.
+    #[wasm_bindgen(js_name = toString)]
+    #[must_use]
+    pub fn emit(&self) -> String {
+        to_string(self)
+    }
+}
+
+/// A handle to a node of a [`JsDocument`], which keeps the document alive.
+#[wasm_bindgen(js_name = Node)]
+#[derive(Clone, Debug)]
+pub struct JsNode {
+    document: Rc<DocumentBuf>,
+    /// The index of the node at each level, from the top.
+    path: Vec<usize>,
+}
+
+impl JsNode {
+    fn node(&self) -> &NodeBuf {
+        let (first, rest) = self.path.split_first().expect("paths are not empty");
+        rest.iter()
+            .fold(&self.document.nodes_ref()[*first], |node, index| {
+                &node.children_ref()[*index]
+            })
+    }
+}
+
+#[wasm_bindgen(js_class = Node)]
+impl JsNode {
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn name(&self) -> String {
+        self.node().name().to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    #[must_use]
+    pub fn namespace(&self) -> Option<String> {
+        self.node().namespace().map(str::to_owned)
+    }
+
+    #[must_use]
+    pub fn args(&self) -> Vec<JsValue> {
+        self.node().args().iter().map(to_js).collect()
+    }
+
+    /// The argument at `index`, or `undefined`.
+    #[must_use]
+    pub fn arg(&self, index: usize) -> JsValue {
+        self.node()
+            .get_arg(index)
+            .map_or(JsValue::UNDEFINED, |value| to_js(&value))
+    }
+
+    /// The parameters as a plain object, where repeated keys keep the value
+    /// that lookups find.
+    #[must_use]
+    pub fn params(&self) -> Object {
+        let object = Object::new();
+        for (key, value) in self.node().params() {
+            set(&object, key, &to_js(&value));
+        }
+        object
+    }
+
+    /// The parameter with `key`, or `undefined`.
+    #[must_use]
+    pub fn param(&self, key: &str) -> JsValue {
+        self.node()
+            .get_param(key)
+            .map_or(JsValue::UNDEFINED, |value| to_js(&value))
+    }
+
+    /// The number of children.
+    #[wasm_bindgen(getter = childCount)]
+    #[must_use]
+    pub fn child_count(&self) -> usize {
+        self.node().children_ref().len()
+    }
+
+    #[must_use]
+    pub fn child(&self, index: usize) -> Option<JsNode> {
+        self.node().children_ref().get(index)?;
+        let mut path = self.path.clone();
+        path.push(index);
+        Some(JsNode {
+            document: Rc::clone(&self.document),
+            path,
+        })
+    }
+
+    #[must_use]
+    pub fn children(&self) -> Vec<JsNode> {
+        (0..self.child_count())
+            .filter_map(|index| self.child(index))
+            .collect()
+    }
+
+    /// Format the node and its children with the default options.
+    #[wasm_bindgen(js_name = toString)]
+    #[must_use]
+    pub fn emit(&self) -> String {
+        format!("{:#}", self.node())
+    }
+}
+
+fn set(object: &Object, key: &str, value: &JsValue) {
+    Reflect::set(object, &JsValue::from_str(key), value).expect("plain objects accept any key");
+}
+
+/// Convert a value for JavaScript, see the [module](self) documentation.
+#[allow(clippy::cast_precision_loss)]
+fn to_js(value: &Value<'_>) -> JsValue {
+    // The largest integer that every smaller one can be held by a `number`.
+    const EXACT: u64 = 1 << 53;
+    let unsigned = |inner: u64| {
+        if inner <= EXACT {
+            JsValue::from_f64(inner as f64)
+        } else {
+            JsValue::from(inner)
+        }
+    };
+    let signed = |inner: i64| {
+        if inner.unsigned_abs() <= EXACT {
+            JsValue::from_f64(inner as f64)
+        } else {
+            JsValue::from(inner)
+        }
+    };
+    match value {
+        Value::U8(inner) => JsValue::from(*inner),
+        Value::U16(inner) => JsValue::from(*inner),
+        Value::U32(inner) => JsValue::from(*inner),
+        Value::U64(inner) | Value::Size(inner) | Value::Duration(inner) => unsigned(*inner),
+        Value::Uint(inner) => unsigned(*inner as u64),
+        Value::I8(inner) => JsValue::from(*inner),
+        Value::I16(inner) => JsValue::from(*inner),
+        Value::I32(inner) => JsValue::from(*inner),
+        Value::I64(inner) => signed(*inner),
+        Value::Int(inner) => signed(*inner as i64),
+        Value::F32(inner) => JsValue::from_f64(f64::from(*inner)),
+        Value::F64(inner) => JsValue::from_f64(*inner),
+        Value::Bool(inner) => JsValue::from_bool(*inner),
+        Value::String(inner) => JsValue::from_str(inner),
+        Value::List(_) | Value::Slice(_) => {
+            let items = value.items().unwrap_or_default();
+            items.iter().map(to_js).collect::<Array>().into()
+        }
+        Value::Map(entries) => {
+            let object = Object::new();
+            for (key, value) in entries.iter() {
+                set(&object, key, &to_js(value));
+            }
+            object.into()
+        }
+        Value::Null => JsValue::NULL,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wasm_handles() {
+        let document = parse("@version \"1\"\nserver port=80 { tls; log { file } }").unwrap();
+        assert_eq!(document.version().as_deref(), Some("1"));
+        assert_eq!(document.length(), 1);
+        assert!(document.node(1).is_none());
+
+        let server = document.node(0).unwrap();
+        assert_eq!(server.name(), "server");
+        assert_eq!(server.child_count(), 2);
+        let names: Vec<_> = server.children().iter().map(JsNode::name).collect();
+        assert_eq!(names, ["tls", "log"]);
+        let file = server.child(1).unwrap().child(0).unwrap();
+        assert_eq!(file.name(), "file");
+        assert!(file.child(0).is_none());
+        drop(document);
+        assert_eq!(
+            server.emit(),
+            "server port=80 {\n    tls\n    log {\n        file\n    }\n}\n"
+        );
+    }
+}